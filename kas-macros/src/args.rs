@@ -64,16 +64,18 @@ pub fn read_attrs(ast: &mut DeriveInput) -> Result<Args> {
                 if core.is_none() {
                     core = Some(member(i, field.ident.clone()));
                 } else {
-                    attr.span()
-                        .unwrap()
-                        .error("multiple fields marked with #[core]")
-                        .emit();
+                    return Err(Error::new(
+                        attr.span(),
+                        "multiple fields marked with #[core]",
+                    ));
                 }
             } else if attr.path == parse_quote! { layout_data } {
                 if layout_data.is_none() {
                     if field.ty != parse_quote! { <Self as kas::LayoutData>::Data }
                         && field.ty != parse_quote! { <Self as LayoutData>::Data }
                     {
+                        // Warnings are only supported on nightly
+                        #[cfg(feature = "nightly")]
                         field
                             .ty
                             .span()
@@ -83,10 +85,10 @@ pub fn read_attrs(ast: &mut DeriveInput) -> Result<Args> {
                     }
                     layout_data = Some(member(i, field.ident.clone()));
                 } else {
-                    attr.span()
-                        .unwrap()
-                        .error("multiple fields marked with #[layout_data]")
-                        .emit();
+                    return Err(Error::new(
+                        attr.span(),
+                        "multiple fields marked with #[layout_data]",
+                    ));
                 }
             } else if attr.path == parse_quote! { widget } {
                 let ident = member(i, field.ident.clone());
@@ -105,19 +107,19 @@ pub fn read_attrs(ast: &mut DeriveInput) -> Result<Args> {
             if widget.is_none() {
                 widget = Some(syn::parse2(attr.tokens)?);
             } else {
-                attr.span()
-                    .unwrap()
-                    .error("multiple #[widget(..)] attributes on type")
-                    .emit()
+                return Err(Error::new(
+                    attr.span(),
+                    "multiple #[widget(..)] attributes on type",
+                ));
             }
         } else if attr.path == parse_quote! { layout } {
             if layout.is_none() {
                 layout = Some(syn::parse2(attr.tokens)?);
             } else {
-                attr.span()
-                    .unwrap()
-                    .error("multiple #[layout(..)] attributes on type")
-                    .emit()
+                return Err(Error::new(
+                    attr.span(),
+                    "multiple #[layout(..)] attributes on type",
+                ));
             }
         } else if attr.path == parse_quote! { handler } {
             handler.push(syn::parse2(attr.tokens)?);
@@ -173,6 +175,8 @@ mod kw {
     custom_keyword!(substitutions);
     custom_keyword!(halign);
     custom_keyword!(valign);
+    custom_keyword!(map);
+    custom_keyword!(event);
 }
 
 #[derive(Debug)]
@@ -184,6 +188,7 @@ pub struct WidgetAttrArgs {
     pub halign: Option<Ident>,
     pub valign: Option<Ident>,
     pub handler: Option<Ident>,
+    pub map: Option<Expr>,
 }
 
 #[derive(Debug)]
@@ -247,6 +252,7 @@ impl Parse for WidgetAttrArgs {
             halign: None,
             valign: None,
             handler: None,
+            map: None,
         };
         if input.is_empty() {
             return Ok(args);
@@ -282,9 +288,19 @@ impl Parse for WidgetAttrArgs {
                 let _: Eq = content.parse()?;
                 args.valign = Some(content.parse()?);
             } else if args.handler.is_none() && lookahead.peek(kw::handler) {
-                let _: kw::handler = content.parse()?;
+                let kw: kw::handler = content.parse()?;
                 let _: Eq = content.parse()?;
                 args.handler = Some(content.parse()?);
+                if args.map.is_some() {
+                    return Err(Error::new(kw.span(), "cannot use both `handler` and `map`"));
+                }
+            } else if args.map.is_none() && lookahead.peek(kw::map) {
+                let kw: kw::map = content.parse()?;
+                let _: Eq = content.parse()?;
+                args.map = Some(content.parse()?);
+                if args.handler.is_some() {
+                    return Err(Error::new(kw.span(), "cannot use both `handler` and `map`"));
+                }
             } else {
                 return Err(lookahead.error());
             }
@@ -308,6 +324,7 @@ impl ToTokens for WidgetAttrArgs {
             || self.halign.is_some()
             || self.valign.is_some()
             || self.handler.is_some()
+            || self.map.is_some()
         {
             let comma = TokenTree::from(Punct::new(',', Spacing::Alone));
             let mut args = TokenStream::new();
@@ -346,10 +363,16 @@ impl ToTokens for WidgetAttrArgs {
             }
             if let Some(ref ident) = self.handler {
                 if !args.is_empty() {
-                    args.append(comma);
+                    args.append(comma.clone());
                 }
                 args.append_all(quote! { handler = #ident });
             }
+            if let Some(ref expr) = self.map {
+                if !args.is_empty() {
+                    args.append(comma);
+                }
+                args.append_all(quote! { map = #expr });
+            }
             tokens.append_all(quote! { ( #args ) });
         }
     }
@@ -476,6 +499,7 @@ impl Parse for LayoutArgs {
 pub struct HandlerArgs {
     pub msg: Type,
     pub substitutions: HashMap<Ident, Type>,
+    pub event: Option<Ident>,
     pub generics: Generics,
 }
 
@@ -484,12 +508,14 @@ impl Parse for HandlerArgs {
         let (mut have_msg, mut have_subs, mut have_gen) = (false, false, false);
         let mut msg = parse_quote! { kas::event::VoidMsg };
         let mut substitutions = HashMap::new();
+        let mut event = None;
         let mut generics = Generics::default();
 
         if input.is_empty() {
             return Ok(HandlerArgs {
                 msg,
                 substitutions,
+                event,
                 generics,
             });
         }
@@ -524,6 +550,10 @@ impl Parse for HandlerArgs {
                     }
                     substitutions.insert(ident, ty);
                 }
+            } else if event.is_none() && lookahead.peek(kw::event) {
+                let _: kw::event = content.parse()?;
+                let _: Eq = content.parse()?;
+                event = Some(content.parse()?);
             } else if !have_gen && lookahead.peek(kw::generics) {
                 have_gen = true;
                 let _: kw::generics = content.parse()?;
@@ -552,6 +582,7 @@ impl Parse for HandlerArgs {
         Ok(HandlerArgs {
             msg,
             substitutions,
+            event,
             generics,
         })
     }
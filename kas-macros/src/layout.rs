@@ -30,8 +30,8 @@ pub(crate) fn derive(
 
     let find_id_area = layout.area.map(|area_widget| {
         quote! {
-            if self.rect().contains(coord) {
-                Some(self.#area_widget.id())
+            if kas::WidgetCore::rect(self).contains(coord) {
+                Some(kas::WidgetCore::id(&self.#area_widget))
             }
         }
     });
@@ -106,11 +106,11 @@ pub(crate) fn derive(
             solver.for_child(
                 &mut #data,
                 #child_info,
-                |axis| child.size_rules(size_handle, axis)
+                |axis| kas::layout::child_size_rules(child, size_handle, axis)
             );
         });
 
-        set_rect.append_all(quote! { let mut align = kas::AlignHints::NONE; });
+        set_rect.append_all(quote! { let mut align = parent_align.child(); });
         if let Some(toks) = args.halign_toks()? {
             set_rect.append_all(quote! { align.horiz = Some(#toks); });
         }
@@ -118,21 +118,26 @@ pub(crate) fn derive(
             set_rect.append_all(quote! { align.vert = Some(#toks); });
         }
         set_rect.append_all(quote! {
-            self.#ident.set_rect(size_handle, setter.child_rect(#child_info), align);
+            let rect = setter.child_rect(#child_info);
+            let rect = kas::layout::child_rect_dir(rect, outer, align.direction);
+            if align.vert.is_none() {
+                align.baseline = setter.baseline();
+            }
+            kas::layout::child_set_rect(&mut self.#ident, size_handle, rect, align);
         });
 
         draw.append_all(quote! {
-            let c0 = self.#ident.rect().pos;
-            let c1 = c0 + Coord::from(self.#ident.rect().size);
+            let c0 = kas::WidgetCore::rect(&self.#ident).pos;
+            let c1 = c0 + Coord::from(kas::WidgetCore::rect(&self.#ident).size);
             if c0.0 <= pos1.0 && c1.0 >= pos0.0 && c0.1 <= pos1.1 && c1.1 >= pos0.1 {
-                self.#ident.draw(draw_handle, mgr);
+                kas::layout::child_draw(&self.#ident, draw_handle, mgr);
             }
         });
 
         // TODO: more efficient search strategy?
         find_id_else.append_all(quote! {
-            if self.#ident.rect().contains(coord) {
-                self.#ident.find_id(coord)
+            if let Some(id) = kas::layout::child_find_id(&self.#ident, coord) {
+                Some(id)
             } else
         });
     }
@@ -255,8 +260,8 @@ pub(crate) fn derive(
 
     let find_id_body = find_id_area.unwrap_or_else(|| {
         quote! {
-            #find_id_else if self.rect().contains(coord) {
-                Some(self.id())
+            #find_id_else if kas::WidgetCore::rect(self).contains(coord) {
+                Some(kas::WidgetCore::id(self))
             }
         }
     });
@@ -272,7 +277,6 @@ pub(crate) fn derive(
             -> kas::layout::SizeRules
         {
             use std::iter;
-            use kas::WidgetCore;
             use kas::layout::RulesSolver;
 
             let mut solver = <Self as kas::LayoutData>::Solver::new(
@@ -295,11 +299,11 @@ pub(crate) fn derive(
             &mut self,
             size_handle: &mut dyn kas::draw::SizeHandle,
             rect: kas::geom::Rect,
-            _: kas::AlignHints,
+            parent_align: kas::AlignHints,
         ) {
-            use kas::{WidgetCore, Widget};
             use kas::layout::{Margins, RulesSetter};
-            self.core_data_mut().rect = rect;
+            kas::WidgetCore::core_data_mut(self).rect = rect;
+            let outer = rect;
 
             let margins = if #is_frame {
                 Margins::outer_frame(size_handle.outer_frame())
@@ -316,8 +320,6 @@ pub(crate) fn derive(
         }
 
         fn find_id(&self, coord: kas::geom::Coord) -> Option<kas::WidgetId> {
-            use kas::WidgetCore;
-
             #find_id_body else {
                 None
             }
@@ -328,9 +330,9 @@ pub(crate) fn derive(
             draw_handle: &mut dyn kas::draw::DrawHandle,
             mgr: &kas::event::ManagerState
         ) {
-            use kas::{geom::Coord, WidgetCore};
+            use kas::geom::Coord;
             if #is_frame {
-                draw_handle.outer_frame(self.core_data().rect);
+                draw_handle.outer_frame(kas::WidgetCore::rect(self));
             }
 
             let rect = draw_handle.target_rect();
@@ -4,7 +4,7 @@
 //     https://www.apache.org/licenses/LICENSE-2.0
 
 #![recursion_limit = "128"]
-#![feature(proc_macro_diagnostic)]
+#![cfg_attr(feature = "nightly", feature(proc_macro_diagnostic))]
 
 extern crate proc_macro;
 
@@ -19,9 +19,10 @@ use syn::punctuated::Punctuated;
 use syn::spanned::Spanned;
 use syn::token::Comma;
 use syn::Token;
-use syn::{parse_macro_input, parse_quote};
+use syn::{parse_macro_input, parse_quote, Error};
 use syn::{
-    DeriveInput, FnArg, GenericParam, Generics, Ident, ImplItemMethod, Type, TypeParam, TypePath,
+    DeriveInput, Expr, FnArg, GenericParam, Generics, Ident, ImplItemMethod, Pat, Type, TypeParam,
+    TypePath,
 };
 
 use self::args::ChildType;
@@ -235,33 +236,48 @@ pub fn derive(input: proc_macro::TokenStream) -> proc_macro::TokenStream {
             let ident = &child.ident;
             let handler = if let Some(ref h) = child.args.handler {
                 quote! { r.try_into().unwrap_or_else(|msg| self.#h(mgr, msg)) }
+            } else if let Some(ref map) = child.args.map {
+                quote! { r.try_into().unwrap_or_else(|msg| Response::Msg((#map)(msg))) }
             } else {
                 quote! { r.into() }
             };
             ev_to_num.append_all(quote! {
-                if id <= self.#ident.id() {
+                if id <= kas::WidgetCore::id(&self.#ident) {
                     let r = self.#ident.handle(mgr, id, event);
                     #handler
                 } else
             });
         }
 
-        let handler = if args.children.is_empty() {
-            // rely on the default implementation
-            quote! {}
-        } else {
+        let mut handler = if let Some(ref f) = handler.event {
             quote! {
-                fn handle(&mut self, mgr: &mut kas::event::Manager, id: kas::WidgetId, event: kas::event::Event)
+                fn handle_event(&mut self, mgr: &mut kas::event::Manager, event: kas::event::Event)
                 -> kas::event::Response<Self::Msg>
                 {
-                    use kas::{WidgetCore, event::Response};
-                    #ev_to_num {
-                        debug_assert!(id == self.id(), "Handler::handle: bad WidgetId");
-                        Response::Unhandled(event)
-                    }
+                    self.#f(mgr, event)
                 }
             }
+        } else {
+            quote! {}
         };
+        if !args.children.is_empty() {
+            // otherwise rely on the default implementation
+            handler.append_all(quote! {
+                fn handle(&mut self, mgr: &mut kas::event::Manager, id: kas::WidgetId, event: kas::event::Event)
+                -> kas::event::Response<Self::Msg>
+                {
+                    use kas::event::{Handler, Manager, Response};
+                    let r: Response<Self::Msg> = #ev_to_num {
+                        debug_assert!(id == kas::WidgetCore::id(self), "Handler::handle: bad WidgetId");
+                        return Manager::handle_generic(self, mgr, event);
+                    };
+                    match r {
+                        Response::Unhandled(event) => self.handle_event(mgr, event),
+                        r => r,
+                    }
+                }
+            });
+        }
 
         toks.append_all(quote! {
             impl #impl_generics kas::event::Handler
@@ -279,19 +295,17 @@ pub fn derive(input: proc_macro::TokenStream) -> proc_macro::TokenStream {
 /// Macro to create a widget with anonymous type
 ///
 /// See the [`kas::macros`](../kas/macros/index.html) module documentation.
-///
-/// Currently usage of this macro requires `#![feature(proc_macro_hygiene)]`.
 #[proc_macro]
 pub fn make_widget(input: proc_macro::TokenStream) -> proc_macro::TokenStream {
     let mut find_handler_ty_buf: Vec<(Ident, Type)> = vec![];
-    // find type of handler's message; return None on error
+    // find type of handler's message
     let mut find_handler_ty = |handler: &Ident,
                                impls: &Vec<(Option<TypePath>, Vec<ImplItemMethod>)>|
-     -> Option<Type> {
+     -> syn::Result<Type> {
         // check the buffer in case we did this already
         for (ident, ty) in &find_handler_ty_buf {
             if ident == handler {
-                return Some(ty.clone());
+                return Ok(ty.clone());
             }
         }
 
@@ -301,29 +315,19 @@ pub fn make_widget(input: proc_macro::TokenStream) -> proc_macro::TokenStream {
             for f in &impl_block.1 {
                 if f.sig.ident == *handler {
                     if let Some(x) = x {
-                        handler
-                            .span()
-                            .unwrap()
-                            .error("multiple methods with this name")
-                            .emit();
-                        x.0.span()
-                            .unwrap()
-                            .error("first method with this name")
-                            .emit();
-                        f.sig
-                            .ident
-                            .span()
-                            .unwrap()
-                            .error("second method with this name")
-                            .emit();
-                        return None;
+                        let mut err = Error::new(handler.span(), "multiple methods with this name");
+                        err.combine(Error::new(x.0.span(), "first method with this name"));
+                        err.combine(Error::new(
+                            f.sig.ident.span(),
+                            "second method with this name",
+                        ));
+                        return Err(err);
                     }
                     if f.sig.inputs.len() != 3 {
-                        f.sig.span()
-                            .unwrap()
-                            .error("handler functions must have signature: fn handler(&mut self, mgr: &mut Manager, msg: T)")
-                            .emit();
-                        return None;
+                        return Err(Error::new(
+                            f.sig.span(),
+                            "handler functions must have signature: fn handler(&mut self, mgr: &mut Manager, msg: T)",
+                        ));
                     }
                     let arg = f.sig.inputs.last().unwrap();
                     let ty = match arg {
@@ -336,14 +340,12 @@ pub fn make_widget(input: proc_macro::TokenStream) -> proc_macro::TokenStream {
         }
         if let Some(x) = x {
             find_handler_ty_buf.push((handler.clone(), x.1.clone()));
-            Some(x.1)
+            Ok(x.1)
         } else {
-            handler
-                .span()
-                .unwrap()
-                .error("no methods with this name found")
-                .emit();
-            None
+            Err(Error::new(
+                handler.span(),
+                "no methods with this name found",
+            ))
         }
     };
 
@@ -364,6 +366,14 @@ pub fn make_widget(input: proc_macro::TokenStream) -> proc_macro::TokenStream {
     };
     // debug impl
     let mut debug_fields = TokenStream::new();
+    // accessors for named child widgets
+    let mut accessors = TokenStream::new();
+    let user_methods: Vec<Ident> = args
+        .impls
+        .iter()
+        .filter(|block| block.0.is_none())
+        .flat_map(|block| block.1.iter().map(|f| f.sig.ident.clone()))
+        .collect();
 
     // extra generic types and where clause for handler impl
     let mut handler_extra = Punctuated::<_, Comma>::new();
@@ -398,21 +408,29 @@ pub fn make_widget(input: proc_macro::TokenStream) -> proc_macro::TokenStream {
                         handler_clauses.push(quote! { #ty: kas::event::Handler<Msg = #tyr> });
                     } else {
                         // No typing. If a handler is specified, then the child must implement
-                        // Handler<Msg = X> where the handler takes type X; otherwise
-                        // we use `msg.into()` and this conversion must be supported.
+                        // Handler<Msg = X> where the handler takes type X; if
+                        // a map closure has typed argument X, likewise;
+                        // otherwise we use `msg.into()` and this conversion
+                        // must be supported.
                         if let Some(ref handler) = wattr.args.handler {
-                            if let Some(ty_bound) = find_handler_ty(handler, &args.impls) {
-                                handler_clauses
-                                    .push(quote! { #ty: kas::event::Handler<Msg = #ty_bound> });
-                            } else {
-                                return quote! {}.into(); // exit after emitting error
+                            match find_handler_ty(handler, &args.impls) {
+                                Ok(ty_bound) => handler_clauses
+                                    .push(quote! { #ty: kas::event::Handler<Msg = #ty_bound> }),
+                                Err(err) => return err.to_compile_error().into(),
                             }
+                        } else if let Some(ty_bound) =
+                            wattr.args.map.as_ref().and_then(closure_arg_ty)
+                        {
+                            handler_clauses
+                                .push(quote! { #ty: kas::event::Handler<Msg = #ty_bound> });
                         } else {
-                            name_buf.push_str("R");
+                            name_buf.push('R');
                             let tyr = Ident::new(&name_buf, Span::call_site());
                             handler_extra.push(tyr.clone());
                             handler_clauses.push(quote! { #ty: kas::event::Handler<Msg = #tyr> });
-                            handler_clauses.push(quote! { #msg: From<#tyr> });
+                            if wattr.args.map.is_none() {
+                                handler_clauses.push(quote! { #msg: From<#tyr> });
+                            }
                         }
                     }
 
@@ -435,6 +453,24 @@ pub fn make_widget(input: proc_macro::TokenStream) -> proc_macro::TokenStream {
 
         let value = &field.value;
 
+        if let (Some(_), Some(name)) = (&attr, &field.ident) {
+            let name_mut = Ident::new(&format!("{}_mut", name), name.span());
+            if !user_methods.contains(name) {
+                accessors.append_all(quote! {
+                    pub fn #name(&self) -> &#ty {
+                        &self.#name
+                    }
+                });
+            }
+            if !user_methods.contains(&name_mut) {
+                accessors.append_all(quote! {
+                    pub fn #name_mut(&mut self) -> &mut #ty {
+                        &mut self.#name
+                    }
+                });
+            }
+        }
+
         field_toks.append_all(quote! { #attr #ident: #ty, });
         field_val_toks.append_all(quote! { #ident: #value, });
         debug_fields
@@ -443,7 +479,12 @@ pub fn make_widget(input: proc_macro::TokenStream) -> proc_macro::TokenStream {
 
     let (impl_generics, ty_generics, where_clause) = args.generics.split_for_impl();
 
-    let mut impls = quote! {};
+    let mut impls = quote! {
+        #[allow(dead_code)]
+        impl #impl_generics AnonWidget #ty_generics #where_clause {
+            #accessors
+        }
+    };
 
     for impl_block in args.impls {
         let mut contents = TokenStream::new();
@@ -471,9 +512,9 @@ pub fn make_widget(input: proc_macro::TokenStream) -> proc_macro::TokenStream {
     // TODO: we should probably not rely on recursive macro expansion here!
     // (I.e. use direct code generation for Widget derivation, instead of derive.)
     let toks = (quote! { {
+        #[derive(Clone, Debug, kas::macros::Widget)]
         #[handler(msg = #msg, generics = < #handler_extra > #handler_where)]
         #extra_attrs
-        #[derive(Clone, Debug, kas::macros::Widget)]
         struct AnonWidget #impl_generics #where_clause {
             #field_toks
         }
@@ -489,6 +530,18 @@ pub fn make_widget(input: proc_macro::TokenStream) -> proc_macro::TokenStream {
     toks
 }
 
+// Type of the argument of a closure `|arg: T| ..`, if given
+fn closure_arg_ty(expr: &Expr) -> Option<&Type> {
+    if let Expr::Closure(closure) = expr {
+        if closure.inputs.len() == 1 {
+            if let Pat::Type(pat) = &closure.inputs[0] {
+                return Some(&pat.ty);
+            }
+        }
+    }
+    None
+}
+
 /// Macro to derive `From<VoidMsg>`
 ///
 /// See the [`kas::macros`](../kas/macros/index.html) module documentation.
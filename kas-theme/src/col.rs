@@ -7,11 +7,12 @@
 
 use log::warn;
 
-use kas::draw::Colour;
+use kas::draw::{Colour, Emphasis, WidgetStyle};
 use kas::event::HighlightState;
+use kas::ThemeAction;
 
 /// Provides standard theme colours
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, PartialEq)]
 pub struct ThemeColours {
     pub background: Colour,
     pub frame: Colour,
@@ -36,6 +37,7 @@ impl ThemeColours {
             "default" => Self::new(),
             "light" => Self::light(),
             "dark" => Self::dark(),
+            "high-contrast" => Self::high_contrast(),
             other => {
                 warn!("ThemeColours::open: scheme \"{}\" not found", other);
                 return None;
@@ -94,6 +96,99 @@ impl ThemeColours {
         }
     }
 
+    /// High-contrast scheme
+    ///
+    /// White on black with a yellow navigation highlight.
+    pub fn high_contrast() -> Self {
+        ThemeColours {
+            background: Colour::grey(0.0),
+            frame: Colour::grey(1.0),
+            text_area: Colour::grey(0.0),
+            text: Colour::grey(1.0),
+            label_text: Colour::grey(1.0),
+            button_text: Colour::grey(0.0),
+            key_nav_focus: Colour::new(1.0, 1.0, 0.0),
+            button: Colour::grey(1.0),
+            button_highlighted: Colour::new(0.0, 1.0, 1.0),
+            button_depressed: Colour::new(1.0, 1.0, 0.0),
+            checkbox: Colour::grey(1.0),
+        }
+    }
+
+    /// Get a colour by field name
+    #[cfg(feature = "config")]
+    pub(crate) fn get_mut(&mut self, name: &str) -> Option<&mut Colour> {
+        Some(match name {
+            "background" => &mut self.background,
+            "frame" => &mut self.frame,
+            "text_area" => &mut self.text_area,
+            "text" => &mut self.text,
+            "label_text" => &mut self.label_text,
+            "button_text" => &mut self.button_text,
+            "key_nav_focus" => &mut self.key_nav_focus,
+            "button" => &mut self.button,
+            "button_highlighted" => &mut self.button_highlighted,
+            "button_depressed" => &mut self.button_depressed,
+            "checkbox" => &mut self.checkbox,
+            _ => return None,
+        })
+    }
+
+    /// List all colours with their field names
+    #[cfg(feature = "config")]
+    pub(crate) fn entries(&self) -> [(&'static str, Colour); 11] {
+        [
+            ("background", self.background),
+            ("frame", self.frame),
+            ("text_area", self.text_area),
+            ("text", self.text),
+            ("label_text", self.label_text),
+            ("button_text", self.button_text),
+            ("key_nav_focus", self.key_nav_focus),
+            ("button", self.button),
+            ("button_highlighted", self.button_highlighted),
+            ("button_depressed", self.button_depressed),
+            ("checkbox", self.checkbox),
+        ]
+    }
+
+    /// Apply a widget's style overrides
+    ///
+    /// An explicit body colour takes precedence over emphasis: `Primary` uses
+    /// the `key_nav_focus` colour as an accent and `Danger` uses red. Hints
+    /// not supported here are ignored.
+    pub fn with_style(&self, style: &WidgetStyle) -> ThemeColours {
+        let mut cols = self.clone();
+        let body = style.colour.or_else(|| match style.emphasis {
+            Some(Emphasis::Primary) => Some(self.key_nav_focus),
+            Some(Emphasis::Danger) => Some(Colour::new(0.8, 0.1, 0.1)),
+            _ => None,
+        });
+        if let Some(col) = body {
+            let lighten = |c: f32| c + (1.0 - c) * 0.25;
+            cols.button = col;
+            cols.button_highlighted = Colour {
+                r: lighten(col.r),
+                g: lighten(col.g),
+                b: lighten(col.b),
+                a: col.a,
+            };
+            cols.button_depressed = Colour {
+                r: col.r * 0.75,
+                g: col.g * 0.75,
+                b: col.b * 0.75,
+                a: col.a,
+            };
+            cols.checkbox = col;
+        }
+        if let Some(col) = style.text_colour {
+            cols.text = col;
+            cols.label_text = col;
+            cols.button_text = col;
+        }
+        cols
+    }
+
     /// Get colour for navigation highlight region, if any
     pub fn nav_region(&self, highlights: HighlightState) -> Option<Colour> {
         if highlights.key_focus {
@@ -133,3 +228,92 @@ impl ThemeColours {
         self.button_state(highlights)
     }
 }
+
+/// The colours in use by a theme
+///
+/// High-contrast mode replaces the user's scheme, which is restored when the
+/// mode is disabled.
+#[derive(Clone, Debug)]
+pub(crate) struct Scheme {
+    /// The colours in use
+    pub cols: ThemeColours,
+    /// The user's scheme, while high-contrast mode replaces `cols`
+    saved: Option<ThemeColours>,
+}
+
+impl Scheme {
+    /// Construct, using the given colours
+    pub fn new(cols: ThemeColours) -> Self {
+        Scheme { cols, saved: None }
+    }
+
+    /// Set the user's scheme
+    ///
+    /// In high-contrast mode this is saved for later.
+    pub fn set_user(&mut self, cols: ThemeColours) -> ThemeAction {
+        match self.saved.as_mut() {
+            Some(saved) => {
+                *saved = cols;
+                ThemeAction::None
+            }
+            None => {
+                self.cols = cols;
+                ThemeAction::RedrawAll
+            }
+        }
+    }
+
+    /// Enable or disable high-contrast mode
+    pub fn set_high_contrast(&mut self, high_contrast: bool) -> ThemeAction {
+        if high_contrast == self.saved.is_some() {
+            return ThemeAction::None;
+        }
+        if high_contrast {
+            let cols = std::mem::replace(&mut self.cols, ThemeColours::high_contrast());
+            self.saved = Some(cols);
+        } else {
+            self.cols = self.saved.take().unwrap();
+        }
+        ThemeAction::RedrawAll
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn style_check_mark() {
+        let red = Colour::new(1.0, 0.0, 0.0);
+        let base = ThemeColours::new();
+        let state = HighlightState::default();
+        assert_eq!(base.check_mark_state(state, true), Some(base.checkbox));
+
+        let cols = base.with_style(&WidgetStyle::new().colour(red));
+        assert_eq!(cols.check_mark_state(state, true), Some(red));
+        assert_eq!(cols.check_mark_state(state, false), None);
+
+        // Text colour does not affect the mark
+        let cols = base.with_style(&WidgetStyle::new().text_colour(red));
+        assert_eq!(cols.check_mark_state(state, true), Some(base.checkbox));
+        assert_eq!(cols.label_text, red);
+    }
+
+    #[test]
+    fn scheme_high_contrast() {
+        let mut scheme = Scheme::new(ThemeColours::dark());
+        assert_eq!(scheme.set_high_contrast(false), ThemeAction::None);
+        assert_eq!(scheme.set_high_contrast(true), ThemeAction::RedrawAll);
+        assert_eq!(scheme.cols, ThemeColours::high_contrast());
+        assert_eq!(scheme.set_high_contrast(true), ThemeAction::None);
+
+        // The user's choice is kept while high contrast is active
+        assert_eq!(scheme.set_user(ThemeColours::light()), ThemeAction::None);
+        assert_eq!(scheme.cols, ThemeColours::high_contrast());
+        assert_eq!(scheme.set_high_contrast(false), ThemeAction::RedrawAll);
+        assert_eq!(scheme.cols, ThemeColours::light());
+
+        assert_eq!(scheme.set_user(ThemeColours::new()), ThemeAction::RedrawAll);
+        assert_eq!(scheme.cols, ThemeColours::new());
+    }
+}
@@ -0,0 +1,361 @@
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License in the LICENSE-APACHE file or at:
+//     https://www.apache.org/licenses/LICENSE-2.0
+
+//! Theme configuration
+//!
+//! With the `config` feature, a [`ThemeConfig`] may be read from a TOML file:
+//! ```toml
+//! # Font size in points (before DPI scaling)
+//! font_size = 16.0
+//! # Inner radius of rounded frames, relative to the outer radius
+//! corner_radius = 0.4
+//! # Light angle to the screen normal and bearing, in radians
+//! light_direction = [0.3, 0.4]
+//!
+//! [metrics]
+//! margin = 3.0
+//!
+//! [colours]
+//! background = [0.9, 0.9, 0.9]
+//! ```
+//!
+//! All keys are optional: a missing key leaves the theme's own default in
+//! place. Unknown keys and sections are ignored so that configuration files
+//! remain usable across versions.
+
+use crate::col::Scheme;
+use crate::{DimensionsParams, ThemeColours};
+use kas::ThemeAction;
+
+#[cfg(feature = "config")]
+pub use self::serde_impl::ConfigError;
+
+/// Theme configuration
+///
+/// Each field is optional; `None` means "use the theme's default". Themes
+/// consume this via [`crate::Theme::apply_config`].
+#[derive(Clone, Debug, Default, PartialEq)]
+#[cfg_attr(
+    feature = "config",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(from = "serde_impl::Repr", into = "serde_impl::Repr")
+)]
+pub struct ThemeConfig {
+    /// Font size
+    pub font_size: Option<f32>,
+    /// Inner radius of rounded frames, relative to the outer radius
+    ///
+    /// See [`kas::draw::DrawRounded::rounded_frame`].
+    pub corner_radius: Option<f32>,
+    /// Light direction `(a, b)` for shaded drawing
+    ///
+    /// Here `0 ≤ a < pi/2` is the angle to the screen normal and `b` is the
+    /// bearing (from UP, clockwise), both in radians. This is used by the
+    /// toolkit's draw implementation, not by the theme.
+    pub light_direction: Option<(f32, f32)>,
+    /// Inner margin (section `metrics`)
+    pub margin: Option<f32>,
+    /// Frame size (section `metrics`)
+    pub frame_size: Option<f32>,
+    /// Button frame size (section `metrics`)
+    pub button_frame: Option<f32>,
+    /// Scrollbar width & min length (section `metrics`)
+    pub scrollbar_size: Option<f32>,
+    /// Colours (section `colours`)
+    ///
+    /// If the section is present, colours not listed take their value from
+    /// [`ThemeColours::new`].
+    pub colours: Option<ThemeColours>,
+}
+
+impl ThemeConfig {
+    /// Apply metrics to `params`
+    pub fn apply_metrics(&self, params: &mut DimensionsParams) {
+        let update = |param: &mut f32, value: Option<f32>| {
+            if let Some(v) = value {
+                *param = v;
+            }
+        };
+        update(&mut params.margin, self.margin);
+        update(&mut params.frame_size, self.frame_size);
+        update(&mut params.button_frame, self.button_frame);
+        update(&mut params.scrollbar_size, self.scrollbar_size);
+    }
+
+    /// Apply to the parameters of a built-in theme
+    ///
+    /// Configured colours replace the user's scheme (thus, while high-contrast
+    /// mode is active, they are only used once it is disabled).
+    pub(crate) fn apply_to(
+        &self,
+        font_size: &mut f32,
+        corner_radius: &mut f32,
+        dims: &mut DimensionsParams,
+        scheme: &mut Scheme,
+    ) -> ThemeAction {
+        if let Some(size) = self.font_size {
+            *font_size = size;
+        }
+        if let Some(radius) = self.corner_radius {
+            *corner_radius = radius;
+        }
+        self.apply_metrics(dims);
+        if let Some(cols) = self.colours.as_ref() {
+            let _ = scheme.set_user(cols.clone());
+        }
+        ThemeAction::ThemeResize
+    }
+}
+
+#[cfg(feature = "config")]
+mod serde_impl {
+    use log::warn;
+    use serde::{Deserialize, Serialize};
+    use std::collections::BTreeMap;
+    use std::fmt;
+    use std::path::Path;
+
+    use super::ThemeConfig;
+    use crate::ThemeColours;
+    use kas::draw::Colour;
+
+    /// Error reading a [`ThemeConfig`]
+    #[derive(Debug)]
+    pub enum ConfigError {
+        /// Failed to read the file
+        Io(std::io::Error),
+        /// Syntax or type error
+        Parse(toml::de::Error),
+    }
+
+    impl fmt::Display for ConfigError {
+        fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+            match self {
+                ConfigError::Io(e) => write!(f, "error reading theme config: {}", e),
+                ConfigError::Parse(e) => write!(f, "error parsing theme config: {}", e),
+            }
+        }
+    }
+
+    impl std::error::Error for ConfigError {}
+
+    impl From<std::io::Error> for ConfigError {
+        fn from(e: std::io::Error) -> Self {
+            ConfigError::Io(e)
+        }
+    }
+
+    impl From<toml::de::Error> for ConfigError {
+        fn from(e: toml::de::Error) -> Self {
+            ConfigError::Parse(e)
+        }
+    }
+
+    impl ThemeConfig {
+        /// Load from a TOML file
+        pub fn load<P: AsRef<Path>>(path: P) -> Result<Self, ConfigError> {
+            let source = std::fs::read_to_string(path)?;
+            Self::parse(&source)
+        }
+
+        /// Parse from a TOML string
+        pub fn parse(source: &str) -> Result<Self, ConfigError> {
+            Ok(toml::from_str(source)?)
+        }
+    }
+
+    /// Format as TOML, readable by [`ThemeConfig::parse`]
+    impl fmt::Display for ThemeConfig {
+        fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+            let text = toml::to_string(self).map_err(|_| fmt::Error)?;
+            f.write_str(&text)
+        }
+    }
+
+    /// A colour, written as `[r, g, b]`
+    #[derive(Clone, Copy, Serialize, Deserialize)]
+    struct Rgb(f32, f32, f32);
+
+    /// The file layout: tables must follow plain values
+    #[derive(Clone, Default, Serialize, Deserialize)]
+    #[serde(default)]
+    pub struct Repr {
+        font_size: Option<f32>,
+        corner_radius: Option<f32>,
+        light_direction: Option<(f32, f32)>,
+        metrics: Metrics,
+        colours: Option<BTreeMap<String, Rgb>>,
+    }
+
+    #[derive(Clone, Default, Serialize, Deserialize)]
+    #[serde(default)]
+    struct Metrics {
+        margin: Option<f32>,
+        frame_size: Option<f32>,
+        button_frame: Option<f32>,
+        scrollbar_size: Option<f32>,
+    }
+
+    impl From<Repr> for ThemeConfig {
+        fn from(repr: Repr) -> Self {
+            let colours = repr.colours.map(|map| {
+                let mut cols = ThemeColours::new();
+                for (key, Rgb(r, g, b)) in map {
+                    match cols.get_mut(&key) {
+                        Some(col) => *col = Colour::new(r, g, b),
+                        None => warn!("ThemeConfig: ignoring unknown colour {}", key),
+                    }
+                }
+                cols
+            });
+            ThemeConfig {
+                font_size: repr.font_size,
+                corner_radius: repr.corner_radius,
+                light_direction: repr.light_direction,
+                margin: repr.metrics.margin,
+                frame_size: repr.metrics.frame_size,
+                button_frame: repr.metrics.button_frame,
+                scrollbar_size: repr.metrics.scrollbar_size,
+                colours,
+            }
+        }
+    }
+
+    impl From<ThemeConfig> for Repr {
+        fn from(config: ThemeConfig) -> Self {
+            let colours = config.colours.map(|cols| {
+                cols.entries()
+                    .iter()
+                    .map(|(key, col)| (key.to_string(), Rgb(col.r, col.g, col.b)))
+                    .collect()
+            });
+            Repr {
+                font_size: config.font_size,
+                corner_radius: config.corner_radius,
+                light_direction: config.light_direction,
+                metrics: Metrics {
+                    margin: config.margin,
+                    frame_size: config.frame_size,
+                    button_frame: config.button_frame,
+                    scrollbar_size: config.scrollbar_size,
+                },
+                colours,
+            }
+        }
+    }
+}
+
+#[cfg(all(test, feature = "config"))]
+mod test {
+    use super::*;
+    use kas::draw::Colour;
+
+    #[test]
+    fn round_trip() {
+        let mut colours = ThemeColours::dark();
+        colours.frame = Colour::new(0.125, 0.3, 0.7);
+        let config = ThemeConfig {
+            font_size: Some(14.5),
+            corner_radius: Some(0.25),
+            light_direction: Some((0.3, 0.4)),
+            margin: Some(3.0),
+            frame_size: None,
+            button_frame: Some(7.0),
+            scrollbar_size: None,
+            colours: Some(colours),
+        };
+
+        let text = config.to_string();
+        assert_eq!(ThemeConfig::parse(&text).unwrap(), config);
+
+        let empty = ThemeConfig::default();
+        assert_eq!(ThemeConfig::parse(&empty.to_string()).unwrap(), empty);
+    }
+
+    #[test]
+    fn defaults_and_unknown() {
+        let text = r#"
+            # comment
+            font_size = 12.0
+            future_option = 1.0
+            future_name = "sparkly"
+            future_date = 2020-04-01
+
+            [metrics]
+            margin = 1.0 # trailing comment
+            future_metric = { a = 1, b = [true, false] }
+
+            [future_section]
+            foo = [1.0, 2.0]
+
+            [colours]
+            text = [1.0, 0.0, 0.0]
+            sparkle = [0.0, 0.0, 0.0]
+        "#;
+        let config = ThemeConfig::parse(text).unwrap();
+
+        let mut colours = ThemeColours::new();
+        colours.text = Colour::new(1.0, 0.0, 0.0);
+        let expected = ThemeConfig {
+            font_size: Some(12.0),
+            margin: Some(1.0),
+            colours: Some(colours),
+            ..Default::default()
+        };
+        assert_eq!(config, expected);
+
+        let mut params = DimensionsParams {
+            margin: 2.0,
+            frame_size: 4.0,
+            button_frame: 6.0,
+            scrollbar_size: 8.0,
+        };
+        config.apply_metrics(&mut params);
+        assert_eq!(params.margin, 1.0);
+        assert_eq!(params.frame_size, 4.0);
+    }
+
+    #[test]
+    fn apply_to_theme() {
+        let config =
+            ThemeConfig::parse("corner_radius = 0.2\n[colours]\nframe = [1.0, 0.0, 0.0]").unwrap();
+        let mut font_size = 18.0;
+        let mut corner_radius = 0.5;
+        let mut dims = DimensionsParams {
+            margin: 2.0,
+            frame_size: 4.0,
+            button_frame: 6.0,
+            scrollbar_size: 8.0,
+        };
+        let mut apply = |scheme: &mut Scheme| {
+            config.apply_to(&mut font_size, &mut corner_radius, &mut dims, scheme)
+        };
+
+        let mut scheme = Scheme::new(ThemeColours::dark());
+        assert_eq!(apply(&mut scheme), ThemeAction::ThemeResize);
+        assert_eq!(scheme.cols.frame, Colour::new(1.0, 0.0, 0.0));
+        assert_eq!(scheme.cols.background, ThemeColours::new().background);
+
+        // In high-contrast mode only the saved scheme changes
+        let mut scheme = Scheme::new(ThemeColours::dark());
+        let _ = scheme.set_high_contrast(true);
+        apply(&mut scheme);
+        assert_eq!(scheme.cols, ThemeColours::high_contrast());
+        let _ = scheme.set_high_contrast(false);
+        assert_eq!(scheme.cols.frame, Colour::new(1.0, 0.0, 0.0));
+
+        assert_eq!(font_size, 18.0);
+        assert_eq!(corner_radius, 0.2);
+        assert_eq!(dims.margin, 2.0);
+    }
+
+    #[test]
+    fn errors() {
+        let is_parse_err = |text| matches!(ThemeConfig::parse(text), Err(ConfigError::Parse(_)));
+        assert!(is_parse_err("font_size = [1.0, 2.0]"));
+        assert!(is_parse_err("\n[colours]\nframe = 0.5"));
+        assert!(is_parse_err("font_size"));
+    }
+}
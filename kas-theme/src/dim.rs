@@ -10,7 +10,7 @@
 use std::any::Any;
 use std::f32;
 
-use kas::draw::{self, DrawText, FontId, TextClass};
+use kas::draw::{self, DrawText, FontId, TextClass, TextStyle};
 use kas::geom::Size;
 use kas::layout::{AxisInfo, SizeRules, StretchPolicy};
 use kas::Direction::{Horizontal, Vertical};
@@ -19,7 +19,7 @@ use kas::Direction::{Horizontal, Vertical};
 ///
 /// All dimensions are multiplied by the DPI factor, then rounded to the
 /// nearest integer. Example: `(2.0 * 1.25).round() = 3.0`.
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, PartialEq)]
 pub struct DimensionsParams {
     /// Inner margin
     pub margin: f32,
@@ -34,6 +34,7 @@ pub struct DimensionsParams {
 /// Dimensions available within [`DimensionsWindow`]
 #[derive(Clone, Debug)]
 pub struct Dimensions {
+    pub dpi_factor: f32,
     pub font_id: FontId,
     pub font_scale: f32,
     pub line_height: u32,
@@ -53,6 +54,7 @@ impl Dimensions {
         let margin = (params.margin * dpi_factor).round() as u32;
         let frame = (params.frame_size * dpi_factor).round() as u32;
         Dimensions {
+            dpi_factor,
             font_id,
             font_scale,
             line_height,
@@ -113,6 +115,14 @@ impl<'a, Draw> SizeHandle<'a, Draw> {
     }
 }
 
+/// True if text of this class is wrapped to the available width
+fn line_wrap(class: TextClass) -> bool {
+    match class {
+        TextClass::Label | TextClass::EditMulti => true,
+        TextClass::Button | TextClass::Edit => false,
+    }
+}
+
 impl<'a, Draw: DrawText> draw::SizeHandle for SizeHandle<'a, Draw> {
     fn outer_frame(&self) -> (Size, Size) {
         let f = self.dims.frame as u32;
@@ -132,22 +142,40 @@ impl<'a, Draw: DrawText> draw::SizeHandle for SizeHandle<'a, Draw> {
     }
 
     fn text_bound(&mut self, text: &str, class: TextClass, axis: AxisInfo) -> SizeRules {
+        self.text_bound_styled(text, class, TextStyle::default(), axis)
+    }
+
+    fn text_bound_styled(
+        &mut self,
+        text: &str,
+        class: TextClass,
+        style: TextStyle,
+        axis: AxisInfo,
+    ) -> SizeRules {
         let font_id = self.dims.font_id;
         let font_scale = self.dims.font_scale;
+        let style = style.scale(self.dims.dpi_factor);
         let line_height = self.dims.line_height;
+        // Distance between baselines of consecutive lines
+        let line_step = style
+            .line_height
+            .resolve(font_scale, line_height as f32)
+            .round()
+            .max(0.0) as u32;
         let mut bounds = (f32::INFINITY, f32::INFINITY);
         if let Some(size) = axis.size_other_if_fixed(Horizontal) {
             bounds.1 = size as f32;
         } else if let Some(size) = axis.size_other_if_fixed(Vertical) {
             bounds.0 = size as f32;
         }
-        let line_wrap = match class {
-            TextClass::Label | TextClass::EditMulti => true,
-            TextClass::Button | TextClass::Edit => false,
+        let line_wrap = line_wrap(class);
+        let bounds = if style.is_default() {
+            self.draw
+                .text_bound(text, font_id, font_scale, bounds, line_wrap)
+        } else {
+            self.draw
+                .text_bound_styled(text, font_id, font_scale, bounds, line_wrap, style)
         };
-        let bounds = self
-            .draw
-            .text_bound(text, font_id, font_scale, bounds, line_wrap);
 
         if axis.is_horizontal() {
             let bound = bounds.0 as u32;
@@ -159,7 +187,7 @@ impl<'a, Draw: DrawText> draw::SizeHandle for SizeHandle<'a, Draw> {
             SizeRules::new(min, ideal, StretchPolicy::LowUtility)
         } else {
             let min = match class {
-                TextClass::EditMulti => line_height * 3,
+                TextClass::EditMulti => line_height + line_step * 2,
                 _ => line_height,
             };
             let ideal = (bounds.1 as u32).max(line_height);
@@ -167,10 +195,28 @@ impl<'a, Draw: DrawText> draw::SizeHandle for SizeHandle<'a, Draw> {
                 TextClass::Button | TextClass::Edit => StretchPolicy::Fixed,
                 _ => StretchPolicy::Filler,
             };
-            SizeRules::new(min, ideal, stretch)
+            let ascent = self.draw.font_ascent(font_id, font_scale);
+            SizeRules::new(min, ideal, stretch).with_baseline(ascent.round() as u32)
         }
     }
 
+    fn measure_text(&mut self, text: &str, class: TextClass, max_width: Option<u32>) -> Size {
+        let line_wrap = line_wrap(class) && max_width.is_some();
+        let bounds = match max_width {
+            Some(w) if line_wrap => (w as f32, f32::INFINITY),
+            _ => (f32::INFINITY, f32::INFINITY),
+        };
+        let font_id = self.dims.font_id;
+        let font_scale = self.dims.font_scale;
+        let (w, h) = self
+            .draw
+            .text_bound(text, font_id, font_scale, bounds, line_wrap);
+        Size(
+            w.ceil() as u32,
+            (h.ceil() as u32).max(self.dims.line_height),
+        )
+    }
+
     fn button_surround(&self) -> (Size, Size) {
         let s = Size::uniform(self.dims.button_frame);
         (s, s)
@@ -195,3 +241,69 @@ impl<'a, Draw: DrawText> draw::SizeHandle for SizeHandle<'a, Draw> {
         (s, s, 2 * s)
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use kas::draw::{Colour, Draw, Font, Region, SizeHandle as _, TextProperties};
+    use kas::geom::Rect;
+
+    /// Lays out 10 pixels per char and one line per `font_scale`
+    struct TextDraw;
+
+    impl Draw for TextDraw {
+        fn as_any_mut(&mut self) -> &mut dyn Any {
+            self
+        }
+        fn add_clip_region(&mut self, _: Region, _: Rect, _: Option<Colour>) -> Region {
+            Region::default()
+        }
+        fn rect(&mut self, _: Region, _: Rect, _: Colour) {}
+        fn frame(&mut self, _: Region, _: Rect, _: Rect, _: Colour) {}
+    }
+
+    impl DrawText for TextDraw {
+        fn load_font(&mut self, _: Font<'static>) -> FontId {
+            FontId(0)
+        }
+        fn text(&mut self, _: Rect, _: &str, _: TextProperties) {}
+        fn text_bound(
+            &mut self,
+            text: &str,
+            _: FontId,
+            font_scale: f32,
+            bounds: (f32, f32),
+            line_wrap: bool,
+        ) -> (f32, f32) {
+            let width = 10.0 * text.chars().count() as f32;
+            if line_wrap && width > bounds.0 {
+                let lines = (width / bounds.0).ceil();
+                (bounds.0, lines * font_scale)
+            } else {
+                (width, font_scale)
+            }
+        }
+    }
+
+    #[test]
+    fn measure_text_class() {
+        let params = DimensionsParams {
+            margin: 2.0,
+            frame_size: 4.0,
+            button_frame: 6.0,
+            scrollbar_size: 8.0,
+        };
+        let dims = Dimensions::new(params, FontId(0), 20.0, 1.0);
+        let mut draw = TextDraw;
+        let mut handle = SizeHandle::new(&mut draw, &dims);
+        let mut measure = |class| handle.measure_text("abcdefghij", class, Some(40));
+
+        assert_eq!(measure(TextClass::Label), Size(40, 60));
+        assert_eq!(measure(TextClass::EditMulti), Size(40, 60));
+        assert_eq!(measure(TextClass::Button), Size(100, 20));
+        assert_eq!(measure(TextClass::Edit), Size(100, 20));
+
+        let size = handle.measure_text("abcdefghij", TextClass::Label, None);
+        assert_eq!(size, Size(100, 20));
+    }
+}
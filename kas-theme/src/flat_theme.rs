@@ -7,11 +7,14 @@
 //!
 //! Widget size and appearance can be modified through themes.
 
+use std::borrow::Cow;
 use std::f32;
 
-use crate::{Dimensions, DimensionsParams, DimensionsWindow, Theme, ThemeColours};
+use crate::col::Scheme;
+use crate::{Dimensions, DimensionsParams, DimensionsWindow, Theme, ThemeColours, ThemeConfig};
 use kas::draw::{
     self, Colour, Draw, DrawRounded, DrawText, FontId, Region, TextClass, TextProperties,
+    TextStyle, WidgetStyle,
 };
 use kas::event::HighlightState;
 use kas::geom::{Coord, Rect};
@@ -22,7 +25,9 @@ use kas::{Align, Direction, ThemeAction, ThemeApi};
 pub struct FlatTheme {
     font_id: FontId,
     font_size: f32,
-    cols: ThemeColours,
+    dims: DimensionsParams,
+    corner_radius: f32,
+    scheme: Scheme,
 }
 
 impl FlatTheme {
@@ -31,7 +36,9 @@ impl FlatTheme {
         FlatTheme {
             font_id: Default::default(),
             font_size: 18.0,
-            cols: ThemeColours::new(),
+            dims: DIMS,
+            corner_radius: 0.5,
+            scheme: Scheme::new(ThemeColours::new()),
         }
     }
 }
@@ -46,7 +53,11 @@ const DIMS: DimensionsParams = DimensionsParams {
 pub struct DrawHandle<'a, D: Draw> {
     draw: &'a mut D,
     window: &'a mut DimensionsWindow,
-    cols: &'a ThemeColours,
+    cols: Cow<'a, ThemeColours>,
+    /// Colours before applying `style`
+    base_cols: &'a ThemeColours,
+    style: WidgetStyle,
+    corner_radius: f32,
     rect: Rect,
     offset: Coord,
     pass: Region,
@@ -64,12 +75,21 @@ impl<D: Draw + DrawRounded + DrawText + 'static> Theme<D> for FlatTheme {
         self.font_id = crate::load_fonts(draw);
     }
 
+    fn apply_config(&mut self, config: &ThemeConfig) -> ThemeAction {
+        config.apply_to(
+            &mut self.font_size,
+            &mut self.corner_radius,
+            &mut self.dims,
+            &mut self.scheme,
+        )
+    }
+
     fn new_window(&self, _draw: &mut D, dpi_factor: f32) -> Self::Window {
-        DimensionsWindow::new(DIMS, self.font_id, self.font_size, dpi_factor)
+        DimensionsWindow::new(self.dims.clone(), self.font_id, self.font_size, dpi_factor)
     }
 
     fn update_window(&self, window: &mut Self::Window, dpi_factor: f32) {
-        window.dims = Dimensions::new(DIMS, self.font_id, self.font_size, dpi_factor);
+        window.dims = Dimensions::new(self.dims.clone(), self.font_id, self.font_size, dpi_factor);
     }
 
     #[cfg(not(feature = "gat"))]
@@ -84,7 +104,12 @@ impl<D: Draw + DrawRounded + DrawText + 'static> Theme<D> for FlatTheme {
         DrawHandle {
             draw: transmute::<&'a mut D, &'static mut D>(draw),
             window: transmute::<&'a mut Self::Window, &'static mut Self::Window>(window),
-            cols: transmute::<&'a ThemeColours, &'static ThemeColours>(&self.cols),
+            cols: Cow::Borrowed(transmute::<&'a ThemeColours, &'static ThemeColours>(
+                &self.scheme.cols,
+            )),
+            base_cols: transmute::<&'a ThemeColours, &'static ThemeColours>(&self.scheme.cols),
+            style: WidgetStyle::DEFAULT,
+            corner_radius: self.corner_radius,
             rect,
             offset: Coord::ZERO,
             pass: Region::default(),
@@ -100,7 +125,10 @@ impl<D: Draw + DrawRounded + DrawText + 'static> Theme<D> for FlatTheme {
         DrawHandle {
             draw,
             window,
-            cols: &self.cols,
+            cols: Cow::Borrowed(&self.scheme.cols),
+            base_cols: &self.scheme.cols,
+            style: WidgetStyle::DEFAULT,
+            corner_radius: self.corner_radius,
             rect,
             offset: Coord::ZERO,
             pass: Region::default(),
@@ -108,7 +136,7 @@ impl<D: Draw + DrawRounded + DrawText + 'static> Theme<D> for FlatTheme {
     }
 
     fn clear_colour(&self) -> Colour {
-        self.cols.background
+        self.scheme.cols.background
     }
 }
 
@@ -119,13 +147,15 @@ impl ThemeApi for FlatTheme {
     }
 
     fn set_colours(&mut self, scheme: &str) -> ThemeAction {
-        if let Some(scheme) = ThemeColours::open(scheme) {
-            self.cols = scheme;
-            ThemeAction::RedrawAll
-        } else {
-            ThemeAction::None
+        match ThemeColours::open(scheme) {
+            Some(cols) => self.scheme.set_user(cols),
+            None => ThemeAction::None,
         }
     }
+
+    fn set_high_contrast(&mut self, high_contrast: bool) -> ThemeAction {
+        self.scheme.set_high_contrast(high_contrast)
+    }
 }
 
 impl<'a, D: Draw + DrawRounded> DrawHandle<'a, D> {
@@ -162,11 +192,14 @@ impl<'a, D: Draw + DrawRounded + DrawText> draw::DrawHandle for DrawHandle<'a, D
         f: &mut dyn FnMut(&mut dyn draw::DrawHandle),
     ) {
         let rect = rect + self.offset;
-        let pass = self.draw.add_clip_region(rect);
+        let pass = self.draw.add_clip_region(self.pass, rect, None);
         let mut handle = DrawHandle {
             draw: self.draw,
             window: self.window,
-            cols: self.cols,
+            cols: self.cols.clone(),
+            base_cols: self.base_cols,
+            style: self.style,
+            corner_radius: self.corner_radius,
             rect,
             offset: self.offset - offset,
             pass,
@@ -183,10 +216,21 @@ impl<'a, D: Draw + DrawRounded + DrawText> draw::DrawHandle for DrawHandle<'a, D
         let outer = rect + self.offset;
         let inner = outer.shrink(self.window.dims.frame);
         self.draw
-            .rounded_frame(self.pass, outer, inner, 0.5, self.cols.frame);
+            .rounded_frame(self.pass, outer, inner, self.corner_radius, self.cols.frame);
     }
 
     fn text(&mut self, rect: Rect, text: &str, class: TextClass, align: (Align, Align)) {
+        self.text_styled(rect, text, class, TextStyle::default(), align);
+    }
+
+    fn text_styled(
+        &mut self,
+        rect: Rect,
+        text: &str,
+        class: TextClass,
+        style: TextStyle,
+        align: (Align, Align),
+    ) {
         let props = TextProperties {
             font: self.window.dims.font_id,
             scale: self.window.dims.font_scale,
@@ -200,6 +244,11 @@ impl<'a, D: Draw + DrawRounded + DrawText> draw::DrawHandle for DrawHandle<'a, D
                 TextClass::Label | TextClass::EditMulti => true,
                 TextClass::Button | TextClass::Edit => false,
             },
+            style: match style.is_default() {
+                true => None,
+                false => Some(style.scale(self.window.dims.dpi_factor)),
+            },
+            transform: false,
         };
         self.draw.text(rect + self.offset, text, props);
     }
@@ -214,7 +263,8 @@ impl<'a, D: Draw + DrawRounded + DrawText> draw::DrawHandle for DrawHandle<'a, D
 
         if let Some(col) = self.cols.nav_region(highlights) {
             let outer = outer.shrink(self.window.dims.button_frame / 3);
-            self.draw.rounded_frame(self.pass, outer, inner, 0.5, col);
+            self.draw
+                .rounded_frame(self.pass, outer, inner, self.corner_radius, col);
         }
     }
 
@@ -279,4 +329,16 @@ impl<'a, D: Draw + DrawRounded + DrawText> draw::DrawHandle for DrawHandle<'a, D
         self.draw.rounded_frame(self.pass, outer, inner, 0.0, col);
         self.draw.rect(self.pass, inner, col);
     }
+
+    fn style(&self) -> WidgetStyle {
+        self.style
+    }
+
+    fn set_style(&mut self, style: WidgetStyle) {
+        self.cols = match style.is_default() {
+            true => Cow::Borrowed(self.base_cols),
+            false => Cow::Owned(self.base_cols.with_style(&style)),
+        };
+        self.style = style;
+    }
 }
@@ -19,6 +19,7 @@
 #![cfg_attr(feature = "stack_dst", feature(unsize))]
 
 mod col;
+mod config;
 mod dim;
 mod flat_theme;
 mod font;
@@ -32,6 +33,9 @@ mod traits;
 pub use kas;
 
 pub use col::ThemeColours;
+#[cfg(feature = "config")]
+pub use config::ConfigError;
+pub use config::ThemeConfig;
 pub use dim::{Dimensions, DimensionsParams, DimensionsWindow};
 pub use flat_theme::FlatTheme;
 pub(crate) use font::load_fonts;
@@ -8,7 +8,7 @@
 use std::collections::HashMap;
 use std::marker::Unsize;
 
-use crate::{StackDst, Theme, ThemeDst, WindowDst};
+use crate::{StackDst, Theme, ThemeConfig, ThemeDst, WindowDst};
 use kas::draw::{Colour, DrawHandle};
 use kas::geom::Rect;
 use kas::{ThemeAction, ThemeApi};
@@ -90,6 +90,14 @@ impl<Draw: 'static> Theme<Draw> for MultiTheme<Draw> {
         }
     }
 
+    fn apply_config(&mut self, config: &ThemeConfig) -> ThemeAction {
+        let mut action = ThemeAction::None;
+        for theme in &mut self.themes {
+            action = action.max(theme.apply_config(config));
+        }
+        action
+    }
+
     fn new_window(&self, draw: &mut Draw, dpi_factor: f32) -> Self::Window {
         self.themes[self.active].new_window(draw, dpi_factor)
     }
@@ -144,6 +152,14 @@ impl<Draw> ThemeApi for MultiTheme<Draw> {
         action
     }
 
+    fn set_high_contrast(&mut self, high_contrast: bool) -> ThemeAction {
+        let mut action = ThemeAction::None;
+        for theme in &mut self.themes {
+            action = action.max(theme.set_high_contrast(high_contrast));
+        }
+        action
+    }
+
     fn set_theme(&mut self, theme: &str) -> ThemeAction {
         if let Some(index) = self.names.get(theme).cloned() {
             if index != self.active {
@@ -5,12 +5,14 @@
 
 //! Shaded theme
 
+use std::borrow::Cow;
 use std::f32;
 
-use crate::{Dimensions, DimensionsParams, DimensionsWindow, Theme, ThemeColours};
+use crate::col::Scheme;
+use crate::{Dimensions, DimensionsParams, DimensionsWindow, Theme, ThemeColours, ThemeConfig};
 use kas::draw::{
     self, Colour, Draw, DrawRounded, DrawShaded, DrawText, FontId, Region, TextClass,
-    TextProperties,
+    TextProperties, TextStyle, WidgetStyle,
 };
 use kas::event::HighlightState;
 use kas::geom::{Coord, Rect};
@@ -21,7 +23,9 @@ use kas::{Align, Direction, ThemeAction, ThemeApi};
 pub struct ShadedTheme {
     font_id: FontId,
     font_size: f32,
-    cols: ThemeColours,
+    dims: DimensionsParams,
+    corner_radius: f32,
+    scheme: Scheme,
 }
 
 impl ShadedTheme {
@@ -30,7 +34,9 @@ impl ShadedTheme {
         ShadedTheme {
             font_id: Default::default(),
             font_size: 18.0,
-            cols: ThemeColours::new(),
+            dims: DIMS,
+            corner_radius: 0.5,
+            scheme: Scheme::new(ThemeColours::new()),
         }
     }
 }
@@ -45,7 +51,11 @@ const DIMS: DimensionsParams = DimensionsParams {
 pub struct DrawHandle<'a, D: Draw> {
     draw: &'a mut D,
     window: &'a mut DimensionsWindow,
-    cols: &'a ThemeColours,
+    cols: Cow<'a, ThemeColours>,
+    /// Colours before applying `style`
+    base_cols: &'a ThemeColours,
+    style: WidgetStyle,
+    corner_radius: f32,
     rect: Rect,
     offset: Coord,
     pass: Region,
@@ -66,12 +76,21 @@ where
         self.font_id = crate::load_fonts(draw);
     }
 
+    fn apply_config(&mut self, config: &ThemeConfig) -> ThemeAction {
+        config.apply_to(
+            &mut self.font_size,
+            &mut self.corner_radius,
+            &mut self.dims,
+            &mut self.scheme,
+        )
+    }
+
     fn new_window(&self, _draw: &mut D, dpi_factor: f32) -> Self::Window {
-        DimensionsWindow::new(DIMS, self.font_id, self.font_size, dpi_factor)
+        DimensionsWindow::new(self.dims.clone(), self.font_id, self.font_size, dpi_factor)
     }
 
     fn update_window(&self, window: &mut Self::Window, dpi_factor: f32) {
-        window.dims = Dimensions::new(DIMS, self.font_id, self.font_size, dpi_factor);
+        window.dims = Dimensions::new(self.dims.clone(), self.font_id, self.font_size, dpi_factor);
     }
 
     #[cfg(not(feature = "gat"))]
@@ -86,7 +105,12 @@ where
         DrawHandle {
             draw: transmute::<&'a mut D, &'static mut D>(draw),
             window: transmute::<&'a mut Self::Window, &'static mut Self::Window>(window),
-            cols: transmute::<&'a ThemeColours, &'static ThemeColours>(&self.cols),
+            cols: Cow::Borrowed(transmute::<&'a ThemeColours, &'static ThemeColours>(
+                &self.scheme.cols,
+            )),
+            base_cols: transmute::<&'a ThemeColours, &'static ThemeColours>(&self.scheme.cols),
+            style: WidgetStyle::DEFAULT,
+            corner_radius: self.corner_radius,
             rect,
             offset: Coord::ZERO,
             pass: Region::default(),
@@ -102,7 +126,10 @@ where
         DrawHandle {
             draw,
             window,
-            cols: &self.cols,
+            cols: Cow::Borrowed(&self.scheme.cols),
+            base_cols: &self.scheme.cols,
+            style: WidgetStyle::DEFAULT,
+            corner_radius: self.corner_radius,
             rect,
             offset: Coord::ZERO,
             pass: Region::default(),
@@ -110,7 +137,7 @@ where
     }
 
     fn clear_colour(&self) -> Colour {
-        self.cols.background
+        self.scheme.cols.background
     }
 }
 
@@ -121,13 +148,15 @@ impl ThemeApi for ShadedTheme {
     }
 
     fn set_colours(&mut self, scheme: &str) -> ThemeAction {
-        if let Some(scheme) = ThemeColours::open(scheme) {
-            self.cols = scheme;
-            ThemeAction::RedrawAll
-        } else {
-            ThemeAction::None
+        match ThemeColours::open(scheme) {
+            Some(cols) => self.scheme.set_user(cols),
+            None => ThemeAction::None,
         }
     }
+
+    fn set_high_contrast(&mut self, high_contrast: bool) -> ThemeAction {
+        self.scheme.set_high_contrast(high_contrast)
+    }
 }
 
 impl<'a, D: Draw + DrawShaded> DrawHandle<'a, D> {
@@ -164,11 +193,14 @@ where
         f: &mut dyn FnMut(&mut dyn draw::DrawHandle),
     ) {
         let rect = rect + self.offset;
-        let pass = self.draw.add_clip_region(rect);
+        let pass = self.draw.add_clip_region(self.pass, rect, None);
         let mut handle = DrawHandle {
             draw: self.draw,
             window: self.window,
-            cols: self.cols,
+            cols: self.cols.clone(),
+            base_cols: self.base_cols,
+            style: self.style,
+            corner_radius: self.corner_radius,
             rect,
             offset: self.offset - offset,
             pass,
@@ -189,6 +221,17 @@ where
     }
 
     fn text(&mut self, rect: Rect, text: &str, class: TextClass, align: (Align, Align)) {
+        self.text_styled(rect, text, class, TextStyle::default(), align);
+    }
+
+    fn text_styled(
+        &mut self,
+        rect: Rect,
+        text: &str,
+        class: TextClass,
+        style: TextStyle,
+        align: (Align, Align),
+    ) {
         let props = TextProperties {
             font: self.window.dims.font_id,
             scale: self.window.dims.font_scale,
@@ -202,6 +245,11 @@ where
                 TextClass::Label | TextClass::EditMulti => true,
                 TextClass::Button | TextClass::Edit => false,
             },
+            style: match style.is_default() {
+                true => None,
+                false => Some(style.scale(self.window.dims.dpi_factor)),
+            },
+            transform: false,
         };
         self.draw.text(rect + self.offset, text, props);
     }
@@ -217,7 +265,8 @@ where
 
         if let Some(col) = self.cols.nav_region(highlights) {
             let outer = outer.shrink(self.window.dims.button_frame / 3);
-            self.draw.rounded_frame(self.pass, outer, inner, 0.5, col);
+            self.draw
+                .rounded_frame(self.pass, outer, inner, self.corner_radius, col);
         }
     }
 
@@ -275,4 +324,16 @@ where
             .shaded_round_frame(self.pass, outer, inner, (0.0, 0.6), col);
         self.draw.rect(self.pass, inner, col);
     }
+
+    fn style(&self) -> WidgetStyle {
+        self.style
+    }
+
+    fn set_style(&mut self, style: WidgetStyle) {
+        self.cols = match style.is_default() {
+            true => Cow::Borrowed(self.base_cols),
+            false => Cow::Owned(self.base_cols.with_style(&style)),
+        };
+        self.style = style;
+    }
 }
@@ -8,10 +8,10 @@
 use std::any::Any;
 use std::ops::DerefMut;
 
-use super::{StackDst, Theme, Window};
+use super::{StackDst, Theme, ThemeConfig, Window};
 use kas::draw::{Colour, DrawHandle, SizeHandle};
 use kas::geom::Rect;
-use kas::ThemeApi;
+use kas::{ThemeAction, ThemeApi};
 
 /// As [`Theme`], but without associated types
 ///
@@ -26,6 +26,11 @@ pub trait ThemeDst<Draw>: ThemeApi {
     /// See also [`Theme::init`].
     fn init(&mut self, draw: &mut Draw);
 
+    /// Apply a configuration
+    ///
+    /// See also [`Theme::apply_config`].
+    fn apply_config(&mut self, config: &ThemeConfig) -> ThemeAction;
+
     /// Construct per-window storage
     ///
     /// Uses a [`StackDst`] to avoid requiring an associated type.
@@ -83,6 +88,10 @@ where
         self.init(draw);
     }
 
+    fn apply_config(&mut self, config: &ThemeConfig) -> ThemeAction {
+        self.apply_config(config)
+    }
+
     fn new_window(&self, draw: &mut Draw, dpi_factor: f32) -> StackDst<dyn WindowDst<Draw>> {
         StackDst::new_or_boxed(<T as Theme<Draw>>::new_window(self, draw, dpi_factor))
     }
@@ -114,6 +123,10 @@ impl<'a, T: Theme<Draw>, Draw> ThemeDst<Draw> for T {
         self.init(draw);
     }
 
+    fn apply_config(&mut self, config: &ThemeConfig) -> ThemeAction {
+        self.apply_config(config)
+    }
+
     fn new_window(&self, draw: &mut Draw, dpi_factor: f32) -> StackDst<dyn WindowDst<Draw>> {
         StackDst::new_or_boxed(<T as Theme<Draw>>::new_window(self, draw, dpi_factor))
     }
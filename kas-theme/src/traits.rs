@@ -10,7 +10,9 @@ use std::ops::{Deref, DerefMut};
 
 use kas::draw::{Colour, DrawHandle, SizeHandle};
 use kas::geom::Rect;
-use kas::ThemeApi;
+use kas::{ThemeAction, ThemeApi};
+
+use crate::ThemeConfig;
 
 /// A *theme* provides widget sizing and drawing implementations.
 ///
@@ -39,6 +41,15 @@ pub trait Theme<Draw>: ThemeApi {
     /// [`DrawText::load_font`]: kas::draw::DrawText::load_font
     fn init(&mut self, draw: &mut Draw);
 
+    /// Apply a configuration
+    ///
+    /// Values not specified by `config` are left unchanged. Themes may ignore
+    /// values they do not support; the default implementation ignores all.
+    fn apply_config(&mut self, config: &ThemeConfig) -> ThemeAction {
+        let _ = config;
+        ThemeAction::None
+    }
+
     /// Construct per-window storage
     ///
     /// On "standard" monitors, the `dpi_factor` is 1. High-DPI screens may
@@ -120,6 +131,10 @@ impl<T: Theme<Draw>, Draw> Theme<Draw> for Box<T> {
         self.deref_mut().init(draw);
     }
 
+    fn apply_config(&mut self, config: &ThemeConfig) -> ThemeAction {
+        self.deref_mut().apply_config(config)
+    }
+
     fn new_window(&self, draw: &mut Draw, dpi_factor: f32) -> Self::Window {
         self.deref().new_window(draw, dpi_factor)
     }
@@ -0,0 +1,324 @@
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License in the LICENSE-APACHE file or at:
+//     https://www.apache.org/licenses/LICENSE-2.0
+
+//! Draw pipeline benchmarks
+//!
+//! These render to an offscreen texture via [`Embedded`], thus require a
+//! graphics adapter but no display. Machines without a GPU may use a software
+//! Vulkan driver (e.g. Mesa's lavapipe or SwiftShader). The adapter is
+//! selected as usual (see [`Options::from_env`]); if none is available, the
+//! benchmarks are skipped.
+//!
+//! Each load is measured in four ways:
+//!
+//! -   `fill`: time spent drawing the widget, i.e. queueing vertices
+//! -   `encode`: the rest of [`Embedded::draw`] when the widget is redrawn:
+//!     vertex upload, render pass encoding and submission
+//! -   `replay`: [`Embedded::draw`] when the widget tree is drawn but the
+//!     widget is unchanged, thus its cached geometry is replayed
+//! -   `retained`: [`Embedded::draw`] when nothing changed, thus the last
+//!     frame's uploaded vertices are rendered again without drawing widgets
+//!
+//! Additionally, `static_window` measures a window of labels repainted
+//! repeatedly without changes (as on expose events from the OS), comparing
+//! `expose_replay` (drawing the widget tree from cached geometry) with
+//! `expose_retained` (rendering the retained last frame).
+
+use criterion::{criterion_group, criterion_main, Criterion};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::{Duration, Instant};
+
+use kas::draw::{Colour, DrawHandle, DrawRounded, DrawShaded, SizeHandle};
+use kas::event::{Manager, ManagerState, UpdateHandle};
+use kas::geom::{Coord, Rect, Size};
+use kas::layout::{AxisInfo, SizeRules, StretchPolicy};
+use kas::widget::{Column, Label, Window};
+use kas::{Layout, Widget, WidgetCore};
+use kas_wgpu::draw::DrawPipe;
+use kas_wgpu::{wgpu, EmbedTarget, Embedded, Options};
+
+const TARGET_SIZE: Size = Size(1024, 768);
+const TARGET_FORMAT: wgpu::TextureFormat = wgpu::TextureFormat::Bgra8UnormSrgb;
+
+/// True if a graphics adapter is available
+///
+/// wgpu 0.4 aborts the process when no adapter exists (it panics within an
+/// `extern "C"` function, thus the panic cannot be caught), hence we probe in
+/// a child process.
+fn have_adapter(options: &wgpu::RequestAdapterOptions) -> bool {
+    const PROBE_VAR: &str = "KAS_BENCH_PROBE";
+    if std::env::var_os(PROBE_VAR).is_some() {
+        let _ = wgpu::Adapter::request(options);
+        std::process::exit(0);
+    }
+    let exe = match std::env::current_exe() {
+        Ok(exe) => exe,
+        Err(_) => return false,
+    };
+    let status = std::process::Command::new(exe)
+        .arg("--bench")
+        .env(PROBE_VAR, "1")
+        .stdout(std::process::Stdio::null())
+        .stderr(std::process::Stdio::null())
+        .status();
+    matches!(status, Ok(status) if status.success())
+}
+
+/// Total time spent in `Bench::draw`, in nanoseconds
+static FILL_NANOS: AtomicU64 = AtomicU64::new(0);
+
+#[derive(Clone, Copy, Debug)]
+enum Load {
+    /// Rounded lines, drawn by `FlatRound`
+    Lines(usize),
+    /// Rounded shaded frames, drawn by `ShadedRound`
+    RoundFrames(usize),
+    /// Square shaded frames, drawn by `ShadedSquare`
+    SquareFrames(usize),
+}
+
+#[derive(Clone, Debug, kas::macros::Widget)]
+#[handler]
+struct Bench {
+    #[core]
+    core: kas::CoreData,
+    load: Load,
+    handle: UpdateHandle,
+}
+
+impl Layout for Bench {
+    fn size_rules(&mut self, _: &mut dyn SizeHandle, _: AxisInfo) -> SizeRules {
+        SizeRules::new(0, 0, StretchPolicy::Maximise)
+    }
+
+    fn draw(&self, draw_handle: &mut dyn DrawHandle, _: &ManagerState) {
+        let start = Instant::now();
+        let (region, offset, draw) = draw_handle.draw_device();
+        let draw = draw.as_any_mut().downcast_mut::<DrawPipe<()>>().unwrap();
+
+        // Deterministic positions, spread over the widget's rect
+        let rect = self.core.rect + offset;
+        let (w, h) = (rect.size.0.max(1) as usize, rect.size.1.max(1) as usize);
+        let pos = |i: usize| rect.pos + Coord((i * 37 % w) as i32, (i * 91 % h) as i32);
+        let col = Colour::new(0.2, 0.4, 0.8);
+        let norm = (0.0, 0.6);
+
+        match self.load {
+            Load::Lines(n) => {
+                for i in 0..n {
+                    draw.rounded_line(region, pos(i), pos(i + 1), 2.0, col);
+                }
+            }
+            Load::RoundFrames(n) => {
+                for i in 0..n {
+                    let outer = Rect::new(pos(i), Size(40, 30));
+                    draw.shaded_round_frame(region, outer, outer.shrink(5), norm, col);
+                }
+            }
+            Load::SquareFrames(n) => {
+                for i in 0..n {
+                    let outer = Rect::new(pos(i), Size(40, 30));
+                    draw.shaded_square_frame(region, outer, outer.shrink(5), norm, col);
+                }
+            }
+        }
+
+        let nanos = start.elapsed().as_nanos() as u64;
+        FILL_NANOS.fetch_add(nanos, Ordering::Relaxed);
+    }
+}
+
+impl Widget for Bench {
+    fn configure(&mut self, mgr: &mut Manager) {
+        // Triggering the handle marks this widget for redrawing
+        mgr.update_on_handle(self.handle, self.id());
+    }
+}
+
+/// How frames are drawn by [`Target::frames`]
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum Mode {
+    /// The widget is redrawn each frame
+    Redraw,
+    /// The widget tree is drawn but the widget is unchanged
+    Replay,
+    /// Nothing is drawn; the last frame is rendered again
+    Retained,
+}
+
+/// An embedded UI drawing one [`Load`] to an offscreen texture
+struct Target {
+    embedded: Embedded<(), kas_theme::FlatTheme>,
+    view: wgpu::TextureView,
+    handle: UpdateHandle,
+    _texture: wgpu::Texture,
+}
+
+impl Target {
+    fn new(load: Load) -> Option<Self> {
+        let handle = UpdateHandle::new();
+        let widget = Bench {
+            core: Default::default(),
+            load,
+            handle,
+        };
+        Target::new_window(Box::new(Window::new("bench", widget)), handle)
+    }
+
+    /// Construct for any `window`; `handle` is triggered by [`Mode::Redraw`]
+    fn new_window(window: Box<dyn kas::Window>, handle: UpdateHandle) -> Option<Self> {
+        let options = Options::from_env();
+        let adapter_options = wgpu::RequestAdapterOptions {
+            power_preference: options.power_preference,
+            backends: options.backends,
+        };
+        if !have_adapter(&adapter_options) {
+            return None;
+        }
+        let adapter = wgpu::Adapter::request(&adapter_options)?;
+        let (device, queue) = adapter.request_device(&wgpu::DeviceDescriptor {
+            extensions: wgpu::Extensions {
+                anisotropic_filtering: false,
+            },
+            limits: wgpu::Limits::default(),
+        });
+
+        let texture = device.create_texture(&wgpu::TextureDescriptor {
+            size: wgpu::Extent3d {
+                width: TARGET_SIZE.0,
+                height: TARGET_SIZE.1,
+                depth: 1,
+            },
+            array_layer_count: 1,
+            mip_level_count: 1,
+            sample_count: 1,
+            dimension: wgpu::TextureDimension::D2,
+            format: TARGET_FORMAT,
+            usage: wgpu::TextureUsage::OUTPUT_ATTACHMENT,
+        });
+        let view = texture.create_default_view();
+
+        let target = EmbedTarget {
+            format: TARGET_FORMAT,
+            size: TARGET_SIZE,
+            dpi_factor: 1.0,
+        };
+        let theme = kas_theme::FlatTheme::new();
+        let embedded = Embedded::new(device, queue, (), theme, options, window, target).ok()?;
+
+        Some(Target {
+            embedded,
+            view,
+            handle,
+            _texture: texture,
+        })
+    }
+
+    /// Draw `iters` frames, returning the time spent in fill and in the rest
+    /// of drawing
+    fn frames(&mut self, iters: u64, mode: Mode) -> (Duration, Duration) {
+        let mut total = Duration::default();
+        FILL_NANOS.store(0, Ordering::Relaxed);
+        for _ in 0..iters {
+            match mode {
+                Mode::Redraw => self.embedded.trigger_update(self.handle, 0),
+                Mode::Replay => self.embedded.redraw(),
+                Mode::Retained => (),
+            }
+            let start = Instant::now();
+            self.embedded.draw(&self.view);
+            total += start.elapsed();
+            // Do not let the GPU fall behind (not measured)
+            self.embedded.device().poll(true);
+        }
+        let fill = Duration::from_nanos(FILL_NANOS.load(Ordering::Relaxed));
+        (fill, total - fill)
+    }
+}
+
+fn bench_load(c: &mut Criterion, name: &str, load: Load) {
+    let mut target = match Target::new(load) {
+        Some(target) => target,
+        None => {
+            eprintln!("{}: no graphics adapter available; skipping", name);
+            return;
+        }
+    };
+
+    let mut group = c.benchmark_group(name);
+    group.bench_function("fill", |b| {
+        b.iter_custom(|iters| target.frames(iters, Mode::Redraw).0)
+    });
+    group.bench_function("encode", |b| {
+        b.iter_custom(|iters| target.frames(iters, Mode::Redraw).1)
+    });
+    group.bench_function("replay", |b| {
+        b.iter_custom(|iters| {
+            let (fill, rest) = target.frames(iters, Mode::Replay);
+            fill + rest
+        })
+    });
+    group.bench_function("retained", |b| {
+        // Ensure the last frame was rendered (not replayed) at least once
+        target.frames(1, Mode::Redraw);
+        b.iter_custom(|iters| {
+            let (fill, rest) = target.frames(iters, Mode::Retained);
+            fill + rest
+        })
+    });
+    group.finish();
+}
+
+fn flat_round(c: &mut Criterion) {
+    bench_load(c, "flat_round_10k_lines", Load::Lines(10_000));
+}
+
+fn shaded_round(c: &mut Criterion) {
+    bench_load(c, "shaded_round_1k_frames", Load::RoundFrames(1_000));
+}
+
+fn shaded_square(c: &mut Criterion) {
+    bench_load(c, "shaded_square_1k_frames", Load::SquareFrames(1_000));
+}
+
+fn static_window(c: &mut Criterion) {
+    let name = "static_window";
+    let labels = (0..50)
+        .map(|i| Label::new(format!("Label {}", i)))
+        .collect();
+    let window = Box::new(Window::new("static", Column::new(labels)));
+    let mut target = match Target::new_window(window, UpdateHandle::new()) {
+        Some(target) => target,
+        None => {
+            eprintln!("{}: no graphics adapter available; skipping", name);
+            return;
+        }
+    };
+
+    let mut group = c.benchmark_group(name);
+    group.bench_function("expose_replay", |b| {
+        b.iter_custom(|iters| {
+            let (fill, rest) = target.frames(iters, Mode::Replay);
+            fill + rest
+        })
+    });
+    group.bench_function("expose_retained", |b| {
+        target.frames(1, Mode::Replay);
+        b.iter_custom(|iters| {
+            let (fill, rest) = target.frames(iters, Mode::Retained);
+            fill + rest
+        })
+    });
+    group.finish();
+}
+
+criterion_group!(
+    benches,
+    flat_round,
+    shaded_round,
+    shaded_square,
+    static_window
+);
+criterion_main!(benches);
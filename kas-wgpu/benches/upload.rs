@@ -0,0 +1,193 @@
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License in the LICENSE-APACHE file or at:
+//     https://www.apache.org/licenses/LICENSE-2.0
+
+//! Vertex upload benchmarks
+//!
+//! These compare upload of heavy geometry via [`VertexRing`] with creating a
+//! mapped vertex buffer per pass per frame. Each iteration uploads one frame's
+//! vertices and submits a render pass using them; every few frames the device
+//! is polled to wait on completed work, as a swap chain would.
+//!
+//! A graphics adapter is required; if none is available, the benchmarks are
+//! skipped.
+
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion};
+
+use kas_wgpu::draw::{Pod, VertexRing};
+use kas_wgpu::{wgpu, Options};
+
+/// Passes per frame (as with several clip regions)
+const PASSES: usize = 4;
+
+/// True if a graphics adapter is available
+///
+/// wgpu 0.4 aborts the process when no adapter exists (it panics within an
+/// `extern "C"` function, thus the panic cannot be caught), hence we probe in
+/// a child process.
+fn have_adapter(options: &wgpu::RequestAdapterOptions) -> bool {
+    const PROBE_VAR: &str = "KAS_BENCH_PROBE";
+    if std::env::var_os(PROBE_VAR).is_some() {
+        let _ = wgpu::Adapter::request(options);
+        std::process::exit(0);
+    }
+    let exe = match std::env::current_exe() {
+        Ok(exe) => exe,
+        Err(_) => return false,
+    };
+    let status = std::process::Command::new(exe)
+        .arg("--bench")
+        .env(PROBE_VAR, "1")
+        .stdout(std::process::Stdio::null())
+        .stderr(std::process::Stdio::null())
+        .status();
+    matches!(status, Ok(status) if status.success())
+}
+
+#[repr(C)]
+#[derive(Clone, Copy)]
+struct Vertex([f32; 8]);
+
+unsafe impl Pod for Vertex {}
+
+struct Target {
+    device: wgpu::Device,
+    queue: wgpu::Queue,
+    view: wgpu::TextureView,
+}
+
+impl Target {
+    fn new() -> Option<Self> {
+        let options = Options::from_env();
+        let adapter_options = wgpu::RequestAdapterOptions {
+            power_preference: options.power_preference,
+            backends: options.backends,
+        };
+        if !have_adapter(&adapter_options) {
+            return None;
+        }
+        let adapter = wgpu::Adapter::request(&adapter_options)?;
+        let (device, queue) = adapter.request_device(&wgpu::DeviceDescriptor {
+            extensions: wgpu::Extensions {
+                anisotropic_filtering: false,
+            },
+            limits: wgpu::Limits::default(),
+        });
+        let texture = device.create_texture(&wgpu::TextureDescriptor {
+            size: wgpu::Extent3d {
+                width: 64,
+                height: 64,
+                depth: 1,
+            },
+            array_layer_count: 1,
+            mip_level_count: 1,
+            sample_count: 1,
+            dimension: wgpu::TextureDimension::D2,
+            format: kas_wgpu::draw::DEFAULT_FORMAT,
+            usage: wgpu::TextureUsage::OUTPUT_ATTACHMENT,
+        });
+        let view = texture.create_default_view();
+        Some(Target {
+            device,
+            queue,
+            view,
+        })
+    }
+
+    /// Submit a pass in which `bind` binds vertex buffers (nothing is drawn)
+    fn submit<F: FnOnce(&mut wgpu::RenderPass)>(
+        &mut self,
+        mut encoder: wgpu::CommandEncoder,
+        bind: F,
+    ) {
+        {
+            let mut rpass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+                color_attachments: &[wgpu::RenderPassColorAttachmentDescriptor {
+                    attachment: &self.view,
+                    resolve_target: None,
+                    load_op: wgpu::LoadOp::Clear,
+                    store_op: wgpu::StoreOp::Store,
+                    clear_color: wgpu::Color::BLACK,
+                }],
+                depth_stencil_attachment: None,
+            });
+            bind(&mut rpass);
+        }
+        self.queue.submit(&[encoder.finish()]);
+    }
+}
+
+fn frame_data(len: usize) -> Vec<Vec<Vertex>> {
+    let per_pass = len / PASSES;
+    (0..PASSES)
+        .map(|p| {
+            (0..per_pass)
+                .map(|i| Vertex([(p * per_pass + i) as f32; 8]))
+                .collect()
+        })
+        .collect()
+}
+
+fn upload(c: &mut Criterion) {
+    let name = "upload";
+    let mut target = match Target::new() {
+        Some(target) => target,
+        None => {
+            eprintln!("{}: no graphics adapter available; skipping", name);
+            return;
+        }
+    };
+
+    let mut group = c.benchmark_group(name);
+    for len in [1_000, 30_000, 300_000].iter().cloned() {
+        group.bench_function(BenchmarkId::new("mapped", len), |b| {
+            let data = frame_data(len);
+            let mut frame = 0;
+            b.iter(|| {
+                let desc = wgpu::CommandEncoderDescriptor { todo: 0 };
+                let encoder = target.device.create_command_encoder(&desc);
+                let buffers: Vec<_> = data
+                    .iter()
+                    .map(|v| {
+                        target
+                            .device
+                            .create_buffer_mapped(v.len(), wgpu::BufferUsage::VERTEX)
+                            .fill_from_slice(v)
+                    })
+                    .collect();
+                target.submit(encoder, |rpass| {
+                    for buffer in &buffers {
+                        rpass.set_vertex_buffers(0, &[(buffer, 0)]);
+                    }
+                });
+                frame += 1;
+                target.device.poll(frame % 3 == 0);
+            })
+        });
+
+        group.bench_function(BenchmarkId::new("ring", len), |b| {
+            let data = frame_data(len);
+            let mut passes = data.clone();
+            let mut ring = VertexRing::new();
+            let mut frame = 0;
+            b.iter(|| {
+                passes.clone_from(&data);
+                let desc = wgpu::CommandEncoderDescriptor { todo: 0 };
+                let mut encoder = target.device.create_command_encoder(&desc);
+                ring.upload(&target.device, &mut encoder, &mut passes);
+                target.submit(encoder, |rpass| {
+                    for pass in 0..PASSES {
+                        ring.set_buffer(pass, rpass);
+                    }
+                });
+                frame += 1;
+                target.device.poll(frame % 3 == 0);
+            })
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(benches, upload);
+criterion_main!(benches);
@@ -20,8 +20,8 @@ use kas::widget::Window;
 use kas::{Align, AlignHints, Direction, Layout, Widget, WidgetCore};
 use kas_wgpu::draw::DrawPipe;
 
-#[handler]
 #[derive(Clone, Debug, kas :: macros :: Widget)]
+#[handler]
 struct Clock {
     #[core]
     core: kas::CoreData,
@@ -4,7 +4,6 @@
 //     https://www.apache.org/licenses/LICENSE-2.0
 
 //! Dynamic widget example
-#![feature(proc_macro_hygiene)]
 
 use kas::class::HasText;
 use kas::event::{Callback, Manager, Response, VoidMsg};
@@ -6,9 +6,7 @@
 //! Mandlebrot example
 //!
 //! Demonstrates use of a custom draw pipe.
-#![feature(proc_macro_hygiene)]
 
-use shaderc::{Compiler, ShaderKind};
 use std::mem::size_of;
 use wgpu::ShaderModule;
 
@@ -22,7 +20,9 @@ use kas::layout::{AxisInfo, SizeRules, StretchPolicy};
 use kas::macros::make_widget;
 use kas::widget::{Label, Window};
 use kas::{AlignHints, Layout, WidgetCore, WidgetId};
-use kas_wgpu::draw::{CustomPipe, CustomPipeBuilder, DrawCustom, DrawPipe, Vec2};
+use kas_wgpu::draw::{
+    ndc_scale, CustomPipe, CustomPipeBuilder, DrawCustom, DrawPipe, ShaderKind, ShaderManager, Vec2,
+};
 use kas_wgpu::Options;
 
 const VERTEX: &'static str = "
@@ -85,18 +85,13 @@ struct Shaders {
 }
 
 impl Shaders {
-    fn compile(device: &wgpu::Device) -> Self {
-        let mut compiler = Compiler::new().unwrap();
-
-        let artifact = compiler
-            .compile_into_spirv(VERTEX, ShaderKind::Vertex, "VERTEX", "main", None)
+    fn compile(device: &wgpu::Device, shaders: &ShaderManager) -> Self {
+        let vertex = shaders
+            .compile(device, VERTEX, ShaderKind::Vertex, "VERTEX")
             .unwrap();
-        let vertex = device.create_shader_module(&artifact.as_binary());
-
-        let artifact = compiler
-            .compile_into_spirv(FRAGMENT, ShaderKind::Fragment, "FRAGMENT", "main", None)
+        let fragment = shaders
+            .compile(device, FRAGMENT, ShaderKind::Fragment, "FRAGMENT")
             .unwrap();
-        let fragment = device.create_shader_module(&artifact.as_binary());
 
         Shaders { vertex, fragment }
     }
@@ -111,12 +106,18 @@ struct PipeBuilder;
 impl CustomPipeBuilder for PipeBuilder {
     type Pipe = Pipe;
 
-    fn build(&mut self, device: &wgpu::Device, size: Size) -> Self::Pipe {
+    fn build(
+        &mut self,
+        device: &wgpu::Device,
+        shaders: &ShaderManager,
+        tex_format: wgpu::TextureFormat,
+        size: Size,
+    ) -> Self::Pipe {
         // Note: real apps should compile shaders once and share between windows
-        let shaders = Shaders::compile(device);
+        let shaders = Shaders::compile(device, shaders);
 
         type Scale = [f32; 2];
-        let scale_factor: Scale = [2.0 / size.0 as f32, 2.0 / size.1 as f32];
+        let scale_factor: Scale = ndc_scale(size);
         let scale_buf = device
             .create_buffer_mapped(
                 scale_factor.len(),
@@ -164,7 +165,7 @@ impl CustomPipeBuilder for PipeBuilder {
             }),
             primitive_topology: wgpu::PrimitiveTopology::TriangleList,
             color_states: &[wgpu::ColorStateDescriptor {
-                format: wgpu::TextureFormat::Bgra8UnormSrgb,
+                format: tex_format,
                 color_blend: wgpu::BlendDescriptor::REPLACE,
                 alpha_blend: wgpu::BlendDescriptor::REPLACE,
                 write_mask: wgpu::ColorWrite::ALL,
@@ -213,7 +214,7 @@ impl CustomPipe for Pipe {
 
     fn resize(&mut self, device: &wgpu::Device, encoder: &mut wgpu::CommandEncoder, size: Size) {
         type Scale = [f32; 2];
-        let scale_factor: Scale = [2.0 / size.0 as f32, 2.0 / size.1 as f32];
+        let scale_factor: Scale = ndc_scale(size);
         let scale_buf = device
             .create_buffer_mapped(scale_factor.len(), wgpu::BufferUsage::COPY_SRC)
             .fill_from_slice(&scale_factor);
@@ -243,7 +244,13 @@ impl CustomPipe for Pipe {
         ]);
     }
 
-    fn render(&mut self, device: &wgpu::Device, pass: usize, rpass: &mut wgpu::RenderPass) {
+    fn render(
+        &mut self,
+        device: &wgpu::Device,
+        pass: usize,
+        _: Rect,
+        rpass: &mut wgpu::RenderPass,
+    ) {
         if pass >= self.passes.len() {
             return;
         }
@@ -273,8 +280,8 @@ impl Pipe {
     }
 }
 
-#[widget]
 #[derive(Clone, Debug, kas :: macros :: Widget)]
+#[widget]
 struct Mandlebrot {
     #[core]
     core: kas::CoreData,
@@ -308,14 +315,23 @@ impl Handler for Mandlebrot {
 
     fn handle(&mut self, mgr: &mut Manager, _: WidgetId, event: Event) -> Response<Self::Msg> {
         match event {
-            Event::Action(Action::Scroll(delta)) => {
+            Event::Action(Action::Scroll(delta, coord)) => {
                 let factor = match delta {
                     ScrollDelta::LineDelta(_, y) => -0.5 * y as f32,
                     ScrollDelta::PixelDelta(coord) => -0.01 * coord.1 as f32,
                 };
-                self.scale *= 2f32.powf(factor);
                 let size = self.core.rect.size.0.min(self.core.rect.size.1);
+                let old_scalar = Vec2::splat(2.0 * self.scale / size as f32);
+                self.scale *= 2f32.powf(factor);
                 self.scalar = Vec2::splat(self.scale / size as f32);
+
+                if mgr.modifiers().ctrl {
+                    // Zoom around the cursor: keep the point under it fixed
+                    let local = coord - self.core.rect.pos;
+                    let rel = Vec2::from(local) - Vec2::from(self.core.rect.size) * 0.5;
+                    let new_scalar = Vec2::splat(2.0 * self.scale / size as f32);
+                    self.centre = self.centre + rel * (old_scalar - new_scalar);
+                }
                 mgr.redraw(self.id());
                 Response::Msg(())
             }
@@ -0,0 +1,113 @@
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License in the LICENSE-APACHE file or at:
+//     https://www.apache.org/licenses/LICENSE-2.0
+
+//! Cache of widget geometry between frames
+
+use std::collections::HashMap;
+
+use super::{flat_round, shaded_round, shaded_square};
+use kas::draw::TextProperties;
+use kas::geom::Rect;
+use kas::WidgetId;
+
+/// Geometry recorded while drawing a widget
+pub struct Entry {
+    key: u64,
+    /// True if used since the last call to [`WidgetCache::prune`]
+    used: bool,
+    pub shaded_square: shaded_square::Vertices,
+    pub shaded_round: shaded_round::Vertices,
+    pub flat_round: flat_round::Vertices,
+    pub text: Vec<(Rect, String, TextProperties)>,
+}
+
+/// A recording in progress
+pub struct Recording {
+    pub id: WidgetId,
+    key: u64,
+    pub pass: usize,
+    /// Start index in each pipeline: `shaded_square, shaded_round, flat_round`
+    pub starts: (usize, usize, usize),
+    pub text: Vec<(Rect, String, TextProperties)>,
+    /// False if anything was drawn which cannot be replayed
+    pub valid: bool,
+}
+
+/// Widget geometry cache
+///
+/// Only one widget is recorded at a time; see [`kas::layout::child_draw`].
+#[derive(Default)]
+pub struct WidgetCache {
+    entries: HashMap<WidgetId, Entry>,
+    pub recording: Option<Recording>,
+}
+
+impl WidgetCache {
+    /// Get the entry for `id` if cached under `key`, marking it as used
+    ///
+    /// Any other entry for `id` is discarded.
+    pub fn get(&mut self, id: WidgetId, key: u64) -> Option<&Entry> {
+        if self.entries.get(&id).map(|e| e.key != key).unwrap_or(false) {
+            self.entries.remove(&id);
+            return None;
+        }
+        let entry = self.entries.get_mut(&id)?;
+        entry.used = true;
+        Some(entry)
+    }
+
+    /// Start a recording
+    pub fn begin(&mut self, id: WidgetId, key: u64, pass: usize, starts: (usize, usize, usize)) {
+        self.recording = Some(Recording {
+            id,
+            key,
+            pass,
+            starts,
+            text: vec![],
+            valid: true,
+        });
+    }
+
+    /// Mark any recording in progress as not replayable
+    #[inline]
+    pub fn invalidate(&mut self) {
+        if let Some(rec) = self.recording.as_mut() {
+            rec.valid = false;
+        }
+    }
+
+    /// Record a text draw, if recording
+    #[inline]
+    pub fn text(&mut self, rect: Rect, text: &str, props: TextProperties) {
+        if let Some(rec) = self.recording.as_mut() {
+            rec.text.push((rect, text.to_string(), props));
+        }
+    }
+
+    /// Store a completed recording
+    pub fn insert(
+        &mut self,
+        rec: Recording,
+        shaded_square: shaded_square::Vertices,
+        shaded_round: shaded_round::Vertices,
+        flat_round: flat_round::Vertices,
+    ) {
+        let entry = Entry {
+            key: rec.key,
+            used: true,
+            shaded_square,
+            shaded_round,
+            flat_round,
+            text: rec.text,
+        };
+        self.entries.insert(rec.id, entry);
+    }
+
+    /// Remove entries not used since the last call
+    pub fn prune(&mut self) {
+        self.entries
+            .retain(|_, entry| std::mem::replace(&mut entry.used, false));
+    }
+}
@@ -5,7 +5,10 @@
 
 //! Custom draw pipes
 
-use super::DrawPipe;
+use log::warn;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use super::{DrawPipe, ShaderManager};
 use kas::draw::Region;
 use kas::geom::{Rect, Size};
 
@@ -23,7 +26,17 @@ pub trait CustomPipeBuilder {
     type Pipe: CustomPipe;
 
     /// Build a pipe
-    fn build(&mut self, device: &wgpu::Device, size: Size) -> Self::Pipe;
+    ///
+    /// The `tex_format` is the format of the render target; pipelines must
+    /// use this in their `color_states`. The `size` is the initial size of
+    /// the window. Shaders may be compiled via [`ShaderManager::compile`].
+    fn build(
+        &mut self,
+        device: &wgpu::Device,
+        shaders: &ShaderManager,
+        tex_format: wgpu::TextureFormat,
+        size: Size,
+    ) -> Self::Pipe;
 }
 
 /// A custom draw pipe
@@ -36,21 +49,66 @@ pub trait CustomPipeBuilder {
 /// [`crate::Toolkit::new_custom`].
 ///
 /// Note that `kas-wgpu` accepts only a single custom pipe. To use more than
-/// one, you will have to implement your own multiplexer (presumably using an
-/// enum for the `Param` type).
+/// one, use [`MultiPipeBuilder`] (or implement your own multiplexer).
 pub trait CustomPipe {
     /// User parameter type
     type Param;
 
     /// Called whenever the window is resized
+    ///
+    /// The pipe is constructed with the initial window size (see
+    /// [`CustomPipeBuilder::build`]); thereafter this method is called with
+    /// the new size each time the window's swap chain is recreated with a
+    /// different size, always before the next call to [`CustomPipe::render`].
+    /// Resources sized to the window (e.g. render targets) should be
+    /// recreated here.
     fn resize(&mut self, device: &wgpu::Device, encoder: &mut wgpu::CommandEncoder, size: Size);
 
+    /// True if this pipe requires a depth attachment
+    ///
+    /// If true, each window allocates a depth texture of format
+    /// [`crate::draw::DEPTH_FORMAT`], resized with the window, which is attached to all
+    /// render passes (cleared to `1.0` at the start of each frame). Pipelines
+    /// of this pipe must then use [`crate::draw::DEPTH_FORMAT`] in their
+    /// `depth_stencil_state`. Built-in pipes ignore depth.
+    ///
+    /// Shaped clip regions (see [`kas::draw::Draw::add_clip_shape`]) use a
+    /// stencil attachment, which is not available when a depth attachment is
+    /// used; these are then clipped to their bounding rect only. Custom pipes
+    /// not using depth are rendered without the stencil attachment, thus are
+    /// always clipped to the bounding rect.
+    ///
+    /// This is queried once, after the pipe is built. The default
+    /// implementation returns `false`.
+    fn uses_depth(&self) -> bool {
+        false
+    }
+
+    /// Called after built-in shaders are reloaded
+    ///
+    /// With the `shader_reload` feature, this is called on each window's pipe
+    /// after the built-in shaders are successfully reloaded and built-in
+    /// pipelines have been rebuilt. Pipes may recompile their own shaders via
+    /// [`ShaderManager::compile`] and rebuild pipelines here (the `tex_format`
+    /// passed to [`CustomPipeBuilder::build`] is unchanged).
+    ///
+    /// The default implementation does nothing.
+    fn reload_shaders(&mut self, _device: &wgpu::Device, _shaders: &ShaderManager) {}
+
     /// Invoke user-defined custom routine
     ///
     /// Custom add-primitives / update function called from user code by
     /// [`DrawCustom::custom`].
     fn invoke(&mut self, pass: usize, rect: Rect, param: Self::Param);
 
+    /// True if nothing is queued for this `pass`
+    ///
+    /// Render passes are skipped for regions where all pipes are empty. The
+    /// default implementation returns `false`, thus never skips a pass.
+    fn is_empty(&self, _pass: usize) -> bool {
+        false
+    }
+
     /// Do a render pass.
     ///
     /// Rendering uses one pass per region, where each region has its own
@@ -58,13 +116,28 @@ pub trait CustomPipe {
     /// Each widget invoking this pipe will give the correct `pass` number for
     /// the widget in [`CustomPipe::invoke`]; multiple widgets may use the same
     /// `pass`.
-    fn render(&mut self, device: &wgpu::Device, pass: usize, rpass: &mut wgpu::RenderPass);
+    ///
+    /// The `rect` is the clip region (scissor rect) of this `pass`, in window
+    /// coordinates. See also [`crate::draw::ndc_scale`].
+    fn render(
+        &mut self,
+        device: &wgpu::Device,
+        pass: usize,
+        rect: Rect,
+        rpass: &mut wgpu::RenderPass,
+    );
 }
 
 /// A dummy implementation (does nothing)
 impl CustomPipeBuilder for () {
     type Pipe = ();
-    fn build(&mut self, _: &wgpu::Device, _: Size) -> Self::Pipe {
+    fn build(
+        &mut self,
+        _: &wgpu::Device,
+        _: &ShaderManager,
+        _: wgpu::TextureFormat,
+        _: Size,
+    ) -> Self::Pipe {
         ()
     }
 }
@@ -76,11 +149,226 @@ impl CustomPipe for () {
     type Param = Void;
     fn resize(&mut self, _: &wgpu::Device, _: &mut wgpu::CommandEncoder, _: Size) {}
     fn invoke(&mut self, _: usize, _: Rect, _: Self::Param) {}
-    fn render(&mut self, _: &wgpu::Device, _: usize, _: &mut wgpu::RenderPass) {}
+    fn is_empty(&self, _: usize) -> bool {
+        true
+    }
+    fn render(&mut self, _: &wgpu::Device, _: usize, _: Rect, _: &mut wgpu::RenderPass) {}
+}
+
+/// Identifier of a pipe within a [`MultiPipe`]
+///
+/// This is returned by [`MultiPipeBuilder::add`] and is only valid for pipes
+/// built by the same builder.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct CustomPipeId(usize, usize);
+
+/// Source of unique [`MultiPipeBuilder`] identifiers
+static NEXT_BUILDER: AtomicUsize = AtomicUsize::new(0);
+
+/// Object-safe variant of [`CustomPipeBuilder`], used by [`MultiPipeBuilder`]
+trait BoxedPipeBuilder<P> {
+    fn build_boxed(
+        &mut self,
+        device: &wgpu::Device,
+        shaders: &ShaderManager,
+        tex_format: wgpu::TextureFormat,
+        size: Size,
+    ) -> Box<dyn CustomPipe<Param = P>>;
+}
+
+impl<P, CB: CustomPipeBuilder> BoxedPipeBuilder<P> for CB
+where
+    CB::Pipe: CustomPipe<Param = P> + 'static,
+{
+    fn build_boxed(
+        &mut self,
+        device: &wgpu::Device,
+        shaders: &ShaderManager,
+        tex_format: wgpu::TextureFormat,
+        size: Size,
+    ) -> Box<dyn CustomPipe<Param = P>> {
+        Box::new(self.build(device, shaders, tex_format, size))
+    }
+}
+
+/// Builder for a [`MultiPipe`]
+///
+/// This allows use of multiple custom pipes, so long as all share the same
+/// `Param` type. Each pipe is identified by the [`CustomPipeId`] returned when
+/// it is added.
+pub struct MultiPipeBuilder<P> {
+    id: usize,
+    builders: Vec<Box<dyn BoxedPipeBuilder<P>>>,
+}
+
+impl<P> MultiPipeBuilder<P> {
+    /// Construct, with no pipes
+    pub fn new() -> Self {
+        MultiPipeBuilder {
+            id: NEXT_BUILDER.fetch_add(1, Ordering::Relaxed),
+            builders: Vec::new(),
+        }
+    }
+
+    /// Add a pipe
+    ///
+    /// The returned identifier must be passed with the parameter when
+    /// invoking this pipe via [`DrawCustom::custom`].
+    pub fn add<CB: CustomPipeBuilder + 'static>(&mut self, builder: CB) -> CustomPipeId
+    where
+        CB::Pipe: CustomPipe<Param = P> + 'static,
+    {
+        let id = CustomPipeId(self.id, self.builders.len());
+        self.builders.push(Box::new(builder));
+        id
+    }
+}
+
+impl<P> Default for MultiPipeBuilder<P> {
+    fn default() -> Self {
+        MultiPipeBuilder::new()
+    }
+}
+
+impl<P: 'static> CustomPipeBuilder for MultiPipeBuilder<P> {
+    type Pipe = MultiPipe<P>;
+
+    fn build(
+        &mut self,
+        device: &wgpu::Device,
+        shaders: &ShaderManager,
+        tex_format: wgpu::TextureFormat,
+        size: Size,
+    ) -> Self::Pipe {
+        let pipes = self
+            .builders
+            .iter_mut()
+            .map(|b| b.build_boxed(device, shaders, tex_format, size))
+            .collect();
+        MultiPipe {
+            builder: self.id,
+            pipes,
+        }
+    }
+}
+
+/// A collection of custom pipes
+///
+/// Constructed via [`MultiPipeBuilder`]. Invocations are routed to the pipe
+/// identified by the [`CustomPipeId`] passed with the parameter. Invocations
+/// with an identifier from another builder are ignored (with a warning).
+///
+/// Within each pass, all custom pipes are rendered in the order they were
+/// added, after the built-in shaded pipes and before the built-in flat
+/// (rounded) pipe and text.
+pub struct MultiPipe<P> {
+    builder: usize,
+    pipes: Vec<Box<dyn CustomPipe<Param = P>>>,
+}
+
+impl<P> CustomPipe for MultiPipe<P> {
+    type Param = (CustomPipeId, P);
+
+    fn resize(&mut self, device: &wgpu::Device, encoder: &mut wgpu::CommandEncoder, size: Size) {
+        for pipe in &mut self.pipes {
+            pipe.resize(device, encoder, size);
+        }
+    }
+
+    fn uses_depth(&self) -> bool {
+        self.pipes.iter().any(|pipe| pipe.uses_depth())
+    }
+
+    fn reload_shaders(&mut self, device: &wgpu::Device, shaders: &ShaderManager) {
+        for pipe in &mut self.pipes {
+            pipe.reload_shaders(device, shaders);
+        }
+    }
+
+    fn invoke(&mut self, pass: usize, rect: Rect, param: Self::Param) {
+        let (id, param) = param;
+        match self.pipes.get_mut(id.1) {
+            Some(pipe) if id.0 == self.builder => pipe.invoke(pass, rect, param),
+            _ => warn!("MultiPipe: ignoring invocation of foreign pipe {:?}", id),
+        }
+    }
+
+    fn is_empty(&self, pass: usize) -> bool {
+        self.pipes.iter().all(|pipe| pipe.is_empty(pass))
+    }
+
+    fn render(
+        &mut self,
+        device: &wgpu::Device,
+        pass: usize,
+        rect: Rect,
+        rpass: &mut wgpu::RenderPass,
+    ) {
+        for pipe in &mut self.pipes {
+            pipe.render(device, pass, rect, rpass);
+        }
+    }
 }
 
 impl<C: CustomPipe> DrawCustom<C> for DrawPipe<C> {
     fn custom(&mut self, region: Region, rect: Rect, param: C::Param) {
+        // Custom draws cannot be replayed from the widget cache
+        self.cache.invalidate();
         self.custom.invoke(region.0, rect, param);
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use std::cell::RefCell;
+    use std::rc::Rc;
+
+    /// A pipe recording the parameter of each invocation
+    struct Recorder(Rc<RefCell<Vec<u32>>>);
+
+    impl CustomPipeBuilder for Recorder {
+        type Pipe = Recorder;
+        fn build(
+            &mut self,
+            _: &wgpu::Device,
+            _: &ShaderManager,
+            _: wgpu::TextureFormat,
+            _: Size,
+        ) -> Self::Pipe {
+            Recorder(self.0.clone())
+        }
+    }
+
+    impl CustomPipe for Recorder {
+        type Param = u32;
+        fn resize(&mut self, _: &wgpu::Device, _: &mut wgpu::CommandEncoder, _: Size) {}
+        fn invoke(&mut self, _: usize, _: Rect, param: u32) {
+            self.0.borrow_mut().push(param);
+        }
+        fn render(&mut self, _: &wgpu::Device, _: usize, _: Rect, _: &mut wgpu::RenderPass) {}
+    }
+
+    #[test]
+    fn multi_pipe_ids() {
+        let params = Rc::new(RefCell::new(vec![]));
+        let mut builder = MultiPipeBuilder::new();
+        let id = builder.add(Recorder(params.clone()));
+        let mut other = MultiPipeBuilder::new();
+        let foreign = (
+            other.add(Recorder(params.clone())),
+            other.add(Recorder(params.clone())),
+        );
+
+        let mut multi = MultiPipe {
+            builder: builder.id,
+            pipes: vec![Box::new(Recorder(params.clone()))],
+        };
+        let rect = Rect::new(Default::default(), Size(1, 1));
+        multi.invoke(0, rect, (id, 1));
+        // Foreign identifiers are ignored, whether or not the index is valid
+        multi.invoke(0, rect, (foreign.0, 2));
+        multi.invoke(0, rect, (foreign.1, 3));
+        assert_eq!(*params.borrow(), [1]);
+    }
+}
@@ -11,12 +11,50 @@ use std::any::Any;
 use std::f32::consts::FRAC_PI_2;
 use wgpu_glyph::GlyphBrushBuilder;
 
-use super::{CustomPipe, CustomPipeBuilder, DrawPipe, FlatRound, ShadedRound, ShadedSquare, Vec2};
+use super::filter::ColourPass;
+use super::{
+    CustomPipe, CustomPipeBuilder, DrawPipe, FlatRound, FrameStats, RenderStats, ShadedRound,
+    ShadedSquare, StencilMask, Vec2,
+};
 use crate::shared::SharedState;
-use kas::draw::{Colour, Draw, DrawRounded, DrawShaded, Region};
-use kas::geom::{Coord, Rect, Size};
+use crate::ColourFilter;
+use kas::draw::{ClipShape, Colour, Draw, DrawRounded, DrawShaded, DrawText, Region};
+use kas::geom::{Affine, Coord, Rect, Size};
+use kas::WidgetId;
 use kas_theme::Theme;
 
+/// Map `rect` through `transform`, rounding corners to whole pixels
+///
+/// Returns `None` if `transform` rotates or shears `rect`, since the result
+/// would not be an axis-aligned rect.
+fn snap_rect(transform: Affine, rect: Rect) -> Option<Rect> {
+    if transform.x.1 != 0.0 || transform.y.0 != 0.0 {
+        return None;
+    }
+    let a = transform.apply(Vec2::from(rect.pos));
+    let b = transform.apply(Vec2::from(rect.pos) + Vec2::from(rect.size));
+    let (a, b) = (a.min(b), a.max(b));
+    let pos = Coord(a.0.round() as i32, a.1.round() as i32);
+    let size = Size(
+        (b.0.round() as i32 - pos.0) as u32,
+        (b.1.round() as i32 - pos.1) as u32,
+    );
+    Some(Rect::new(pos, size))
+}
+
+/// Normal of the light direction used by shaded pipes
+fn light_norm(dir: (f32, f32)) -> [f32; 3] {
+    // Light dir: `(a, b)` where `0 ≤ a < pi/2` is the angle to the screen
+    // normal (i.e. `a = 0` is straight at the screen) and `b` is the bearing
+    // (from UP, clockwise), both in radians.
+    assert!(dir.0 >= 0.0);
+    assert!(dir.0 < FRAC_PI_2);
+    let a = (dir.0.sin(), dir.0.cos());
+    // We normalise intensity:
+    let f = a.0 / a.1;
+    [dir.1.sin() * f, -dir.1.cos() * f, 1.0]
+}
+
 impl<C: CustomPipe> DrawPipe<C> {
     /// Construct
     // TODO: do we want to share state across windows? With glyph_brush this is
@@ -26,98 +64,372 @@ impl<C: CustomPipe> DrawPipe<C> {
         tex_format: wgpu::TextureFormat,
         size: Size,
     ) -> Self {
-        // Light dir: `(a, b)` where `0 ≤ a < pi/2` is the angle to the screen
-        // normal (i.e. `a = 0` is straight at the screen) and `b` is the bearing
-        // (from UP, clockwise), both in radians.
-        let dir: (f32, f32) = (0.3, 0.4);
-        assert!(dir.0 >= 0.0);
-        assert!(dir.0 < FRAC_PI_2);
-        let a = (dir.0.sin(), dir.0.cos());
-        // We normalise intensity:
-        let f = a.0 / a.1;
-        let norm = [dir.1.sin() * f, -dir.1.cos() * f, 1.0];
-
-        let custom = shared.custom.build(&shared.device, size);
-
-        let glyph_brush =
-            GlyphBrushBuilder::using_fonts(vec![]).build(&mut shared.device, tex_format);
+        let norm = light_norm(shared.light_dir);
+        let custom = shared
+            .custom
+            .build(&shared.device, &shared.shaders, tex_format, size);
+        let depth = custom.uses_depth();
+
+        let cache = shared.glyph_cache;
+        let glyph_brush = GlyphBrushBuilder::using_fonts(vec![])
+            .initial_cache_size(cache.initial_size)
+            .gpu_cache_scale_tolerance(cache.scale_tolerance)
+            .gpu_cache_position_tolerance(cache.position_tolerance)
+            .build(&mut shared.device, tex_format);
 
         let region = Rect {
             pos: Coord::ZERO,
             size,
         };
 
+        // A depth attachment precludes our stencil attachment
+        let stencil = match depth {
+            false => Some(StencilMask::new(shared, tex_format, size)),
+            true => None,
+        };
+
+        let colour_filter = shared.colour_filter;
+        let colour_pass = colour_filter
+            .matrix()
+            .map(|matrix| ColourPass::new(shared, tex_format, size, matrix));
+
         DrawPipe {
             clip_regions: vec![region],
-            shaded_square: ShadedSquare::new(shared, size, norm),
-            shaded_round: ShadedRound::new(shared, size, norm),
+            clip_shapes: vec![vec![]],
+            stencil,
+            shaded_square: ShadedSquare::new(shared, tex_format, size, norm, depth),
+            shaded_round: ShadedRound::new(shared, tex_format, size, norm, depth),
             custom,
-            flat_round: FlatRound::new(shared, size),
+            flat_round: FlatRound::new(shared, tex_format, size, depth),
             glyph_brush,
+            cache: Default::default(),
+            tex_format,
+            colour_filter,
+            colour_pass,
+            emoji: shared.emoji_font.clone().map(|font| (font, None)),
+            precache_ascii: cache.precache_ascii,
+            opacity: 1.0,
+            transform: Affine::IDENTITY,
+            transforms: vec![],
+            frame_text: vec![],
+            last_frame: None,
+            pixel_snap: shared.pixel_snap,
+            text_gamma: shared.text_gamma,
+            depth,
+            stats: FrameStats::new(shared.render_stats),
         }
     }
 
+    /// Rebuild built-in pipelines (after shaders are reloaded)
+    ///
+    /// The custom pipe is then notified via [`CustomPipe::reload_shaders`].
+    #[cfg(feature = "shader_reload")]
+    pub fn reload_shaders<CB, T>(&mut self, shared: &SharedState<CB, T>) {
+        let size = self.clip_regions[0].size;
+        let norm = light_norm(shared.light_dir);
+        let format = self.tex_format;
+        self.last_frame = None;
+        self.shaded_square = ShadedSquare::new(shared, format, size, norm, self.depth);
+        self.shaded_round = ShadedRound::new(shared, format, size, norm, self.depth);
+        self.flat_round = FlatRound::new(shared, format, size, self.depth);
+        if !self.depth {
+            self.stencil = Some(StencilMask::new(shared, format, size));
+        }
+        if let Some(matrix) = self.colour_filter.matrix() {
+            self.colour_pass = Some(ColourPass::new(shared, format, size, matrix));
+        }
+        self.custom.reload_shaders(&shared.device, &shared.shaders);
+    }
+
+    /// Set the colour filter
+    ///
+    /// Resources for the post-process pass are created when a filter is
+    /// enabled and released when it is disabled. The window must be redrawn
+    /// for the change to take effect.
+    pub fn set_colour_filter<CB, T>(&mut self, shared: &SharedState<CB, T>, filter: ColourFilter) {
+        if filter == self.colour_filter {
+            return;
+        }
+        self.colour_filter = filter;
+        let size = self.clip_regions[0].size;
+        self.colour_pass = filter
+            .matrix()
+            .map(|matrix| ColourPass::new(shared, self.tex_format, size, matrix));
+    }
+
+    /// The format of the render target
+    ///
+    /// All built-in pipes and the custom pipe use this format.
+    #[inline]
+    pub fn tex_format(&self) -> wgpu::TextureFormat {
+        self.tex_format
+    }
+
+    /// True if a depth attachment is required
+    ///
+    /// See [`CustomPipe::uses_depth`]. Shaped clip regions are only supported
+    /// when this is false.
+    pub fn uses_depth(&self) -> bool {
+        self.depth
+    }
+
+    /// Statistics of the last rendered frame, if enabled
+    ///
+    /// See [`RenderStats`].
+    #[inline]
+    pub fn render_stats(&self) -> Option<RenderStats> {
+        self.stats.last()
+    }
+
     /// Process window resize
     pub fn resize(&mut self, device: &wgpu::Device, size: Size) -> wgpu::CommandBuffer {
         self.clip_regions[0].size = size;
         let mut encoder =
             device.create_command_encoder(&wgpu::CommandEncoderDescriptor { todo: 0 });
+        if let Some(stencil) = self.stencil.as_mut() {
+            stencil.resize(device, &mut encoder, size);
+        }
         self.shaded_square.resize(device, &mut encoder, size);
         self.shaded_round.resize(device, &mut encoder, size);
         self.custom.resize(device, &mut encoder, size);
         self.flat_round.resize(device, &mut encoder, size);
+        self.last_frame = None;
+        if let Some(pass) = self.colour_pass.as_mut() {
+            pass.resize(device, size);
+        }
         encoder.finish()
     }
 
     /// Render batched draw instructions via `rpass`
+    ///
+    /// A `depth_view` must be passed if and only if [`DrawPipe::uses_depth`].
+    /// Otherwise, an internal stencil attachment is used.
+    ///
+    /// If a colour filter is set, everything is rendered to an intermediate
+    /// texture which is then filtered onto `frame_view`.
     pub fn render(
         &mut self,
         device: &mut wgpu::Device,
         frame_view: &wgpu::TextureView,
+        depth_view: Option<&wgpu::TextureView>,
         clear_color: wgpu::Color,
     ) -> wgpu::CommandBuffer {
         let desc = wgpu::CommandEncoderDescriptor { todo: 0 };
         let mut encoder = device.create_command_encoder(&desc);
+        self.shaded_square.upload(device, &mut encoder);
+        self.shaded_round.upload(device, &mut encoder);
+        self.flat_round.upload(device, &mut encoder);
+
+        let custom_used = self.encode(device, &mut encoder, frame_view, depth_view, clear_color);
+        self.stats.finish(false);
+
+        // Retain the frame for replay, unless it used the custom pipe (which
+        // may not retain its geometry)
+        self.last_frame = if custom_used {
+            None
+        } else {
+            let mut last = self.last_frame.take().unwrap_or_default();
+            last.clip_regions.clone_from(&self.clip_regions);
+            last.clip_shapes.clone_from(&self.clip_shapes);
+            std::mem::swap(&mut last.text, &mut self.frame_text);
+            Some(last)
+        };
+        self.frame_text.clear();
+
+        // Keep only first clip region (which is the entire window)
+        self.clip_regions.truncate(1);
+        self.clip_shapes.truncate(1);
+        self.cache.prune();
+        self.transforms.clear();
+        self.set_transform(Affine::IDENTITY);
+
+        encoder.finish()
+    }
+
+    /// Render the last frame again, without re-encoding its geometry
+    ///
+    /// This re-uses the vertices uploaded by the last call to
+    /// [`DrawPipe::render`], and is thus much cheaper than drawing the widget
+    /// tree and rendering. It is intended for redraws requested by the OS
+    /// (e.g. after the window was exposed) when nothing has changed.
+    ///
+    /// Nothing may be drawn since the last call to [`DrawPipe::render`], and
+    /// the size must not have changed. Returns `None` (doing nothing) if the
+    /// last frame cannot be replayed, which is the case if nothing was
+    /// rendered yet or if the custom pipe was used.
+    pub fn replay(
+        &mut self,
+        device: &mut wgpu::Device,
+        frame_view: &wgpu::TextureView,
+        depth_view: Option<&wgpu::TextureView>,
+        clear_color: wgpu::Color,
+    ) -> Option<wgpu::CommandBuffer> {
+        let last = self.last_frame.as_mut()?;
+        self.clip_regions.clone_from(&last.clip_regions);
+        self.clip_shapes.clone_from(&last.clip_shapes);
+        // glyph_brush only re-uses its vertices if the same text is queued
+        let text = std::mem::take(&mut last.text);
+        for (rect, text, props) in text.iter() {
+            self.queue_text(*rect, text, *props);
+        }
+        if let Some(last) = self.last_frame.as_mut() {
+            last.text = text;
+        }
+
+        let desc = wgpu::CommandEncoderDescriptor { todo: 0 };
+        let mut encoder = device.create_command_encoder(&desc);
+        self.encode(device, &mut encoder, frame_view, depth_view, clear_color);
+        self.stats.finish(true);
+
+        self.clip_regions.truncate(1);
+        self.clip_shapes.truncate(1);
+        Some(encoder.finish())
+    }
+
+    /// Encode render passes for uploaded vertices and queued text
+    ///
+    /// Returns true if the custom pipe rendered anything.
+    fn encode(
+        &mut self,
+        device: &mut wgpu::Device,
+        encoder: &mut wgpu::CommandEncoder,
+        frame_view: &wgpu::TextureView,
+        depth_view: Option<&wgpu::TextureView>,
+        clear_color: wgpu::Color,
+    ) -> bool {
+        let target_view = match self.colour_pass.as_ref() {
+            Some(pass) => pass.frame_view(),
+            None => frame_view,
+        };
+
+        // Built-in pipes use either the depth or the stencil attachment
+        let attachment = match self.stencil.as_ref() {
+            Some(stencil) => Some(stencil.view()),
+            None => depth_view,
+        };
+
         let mut load_op = wgpu::LoadOp::Clear;
+        // True if a previous pass may have left non-zero stencil values
+        let mut stencil_dirty = false;
+        let mut custom_used = false;
 
         // We use a separate render pass for each clipped region.
         for (pass, region) in self.clip_regions.iter().enumerate() {
-            let mut rpass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
-                color_attachments: &[wgpu::RenderPassColorAttachmentDescriptor {
-                    attachment: frame_view,
-                    resolve_target: None,
-                    load_op: load_op,
-                    store_op: wgpu::StoreOp::Store,
-                    clear_color,
-                }],
-                depth_stencil_attachment: None,
-            });
+            // The first pass must always run since it clears the frame.
+            if pass > 0 && self.is_pass_empty(pass) {
+                continue;
+            }
+            let custom_pass = !self.custom.is_empty(pass);
+            custom_used |= custom_pass;
+            // Custom pipes not using depth are incompatible with the stencil
+            // attachment, thus are rendered in a pass of their own.
+            let split = custom_pass && self.stencil.is_some();
+            let shapes = &self.clip_shapes[pass];
+
+            let mut rpass = begin_pass(encoder, target_view, attachment, load_op, clear_color);
             rpass.set_scissor_rect(
                 region.pos.0 as u32,
                 region.pos.1 as u32,
                 region.size.0,
                 region.size.1,
             );
+            if let Some(stencil) = self.stencil.as_ref() {
+                if stencil_dirty || !shapes.is_empty() {
+                    stencil.render(device, *region, shapes, &mut rpass);
+                    stencil_dirty = !shapes.is_empty();
+                }
+                rpass.set_stencil_reference(shapes.len() as u32);
+            }
+
+            let vertices = (
+                self.shaded_square.render(pass, &mut rpass),
+                self.shaded_round.render(pass, &mut rpass),
+            );
+            let mut passes = 1;
+            if split {
+                drop(rpass);
+                let load = wgpu::LoadOp::Load;
+                let mut cpass = begin_pass(encoder, target_view, None, load, clear_color);
+                cpass.set_scissor_rect(
+                    region.pos.0 as u32,
+                    region.pos.1 as u32,
+                    region.size.0,
+                    region.size.1,
+                );
+                self.custom.render(device, pass, *region, &mut cpass);
+                drop(cpass);
 
-            self.shaded_square.render(device, pass, &mut rpass);
-            self.shaded_round.render(device, pass, &mut rpass);
-            self.custom.render(device, pass, &mut rpass);
-            self.flat_round.render(device, pass, &mut rpass);
+                rpass = begin_pass(encoder, target_view, attachment, load, clear_color);
+                rpass.set_scissor_rect(
+                    region.pos.0 as u32,
+                    region.pos.1 as u32,
+                    region.size.0,
+                    region.size.1,
+                );
+                rpass.set_stencil_reference(shapes.len() as u32);
+                passes += 2;
+            } else {
+                self.custom.render(device, pass, *region, &mut rpass);
+            }
+            let flat_round = self.flat_round.render(pass, &mut rpass);
             drop(rpass);
 
+            if let Some(stats) = self.stats.current() {
+                stats.add_region((vertices.0, vertices.1, flat_round), custom_pass, passes);
+            }
+
             load_op = wgpu::LoadOp::Load;
         }
 
+        if let Some(stats) = self.stats.current() {
+            stats.clip_regions = self.clip_regions.len() as u32;
+        }
+
         // Fonts use their own render pass(es).
         let size = self.clip_regions[0].size;
         self.glyph_brush
-            .draw_queued(device, &mut encoder, frame_view, size.0, size.1)
+            .draw_queued(device, encoder, target_view, size.0, size.1)
             .expect("glyph_brush.draw_queued");
 
-        // Keep only first clip region (which is the entire window)
-        self.clip_regions.truncate(1);
+        if let Some(pass) = self.colour_pass.as_ref() {
+            pass.render(encoder, frame_view);
+        }
 
-        encoder.finish()
+        custom_used
+    }
+
+    /// Set the transform of all built-in pipes
+    fn set_transform(&mut self, transform: Affine) {
+        self.transform = transform;
+        self.shaded_square.set_transform(transform);
+        self.shaded_round.set_transform(transform);
+        self.flat_round.set_transform(transform);
+    }
+
+    /// Snap `rect` to whole pixels under the current transform
+    ///
+    /// Returns `None` where snapping is disabled or not applicable.
+    fn snap(&self, rect: Rect) -> Option<Rect> {
+        if self.pixel_snap && self.transform != Affine::IDENTITY {
+            snap_rect(self.transform, rect)
+        } else {
+            None
+        }
+    }
+
+    /// Apply the opacity multiplier to `col`
+    #[inline]
+    fn fade(&self, mut col: Colour) -> Colour {
+        col.a *= self.opacity;
+        col
+    }
+
+    /// True if no pipe has anything queued for this `pass`
+    fn is_pass_empty(&self, pass: usize) -> bool {
+        self.shaded_square.is_empty(pass)
+            && self.shaded_round.is_empty(pass)
+            && self.custom.is_empty(pass)
+            && self.flat_round.is_empty(pass)
     }
 }
 
@@ -127,31 +439,156 @@ impl<C: CustomPipe + 'static> Draw for DrawPipe<C> {
         self
     }
 
-    fn add_clip_region(&mut self, region: Rect) -> Region {
+    #[inline]
+    fn as_rounded(&mut self) -> Option<&mut dyn DrawRounded> {
+        Some(self)
+    }
+
+    fn add_clip_region(
+        &mut self,
+        parent: Region,
+        rect: Rect,
+        background: Option<Colour>,
+    ) -> Region {
+        // Region indices are not stable across frames, thus not replayable
+        self.cache.invalidate();
+        let region = self.clip_regions[parent.0]
+            .intersection(&rect)
+            .unwrap_or(Rect::new(rect.pos, Size::ZERO));
         let pass = self.clip_regions.len();
         self.clip_regions.push(region);
+        let shapes = self.clip_shapes[parent.0].clone();
+        self.clip_shapes.push(shapes);
+        if let Some(col) = background {
+            // LoadOp::Clear would clear the whole frame, not just the scissor
+            // rect, hence we fill with an opaque rect. Since this is the first
+            // primitive of the first pipe in this pass, it is drawn first.
+            let col = self.fade(col);
+            self.shaded_square.rect(pass, region, col);
+        }
         Region(pass)
     }
 
+    fn add_clip_shape(&mut self, parent: Region, shape: ClipShape) -> Region {
+        let region = self.add_clip_region(parent, shape.bounds(), None);
+        if self.stencil.is_some() {
+            self.clip_shapes[region.0].push(shape);
+        }
+        region
+    }
+
+    fn cache_begin(&mut self, region: Region, id: WidgetId, key: u64, dirty: bool) -> bool {
+        let pass = region.0;
+        if self.cache.recording.is_some() || self.transform != Affine::IDENTITY {
+            // Only one widget may be recorded at once, and recorded geometry
+            // is only valid under the transform used when recording
+            return false;
+        }
+        if !dirty {
+            if let Some(entry) = self.cache.get(id, key) {
+                self.shaded_square.replay(pass, &entry.shaded_square);
+                self.shaded_round.replay(pass, &entry.shaded_round);
+                self.flat_round.replay(pass, &entry.flat_round);
+                let text = entry.text.clone();
+                for (rect, text, props) in text {
+                    self.text(rect, &text, props);
+                }
+                return true;
+            }
+        }
+
+        let starts = (
+            self.shaded_square.pass_len(pass),
+            self.shaded_round.pass_len(pass),
+            self.flat_round.pass_len(pass),
+        );
+        self.cache.begin(id, key, pass, starts);
+        false
+    }
+
+    fn cache_end(&mut self, id: WidgetId) {
+        match self.cache.recording.take() {
+            Some(rec) if rec.id == id && rec.valid => {
+                let pass = rec.pass;
+                let shaded_square = self.shaded_square.record(pass, rec.starts.0);
+                let shaded_round = self.shaded_round.record(pass, rec.starts.1);
+                let flat_round = self.flat_round.record(pass, rec.starts.2);
+                self.cache
+                    .insert(rec, shaded_square, shaded_round, flat_round);
+            }
+            Some(rec) if rec.id != id => self.cache.recording = Some(rec),
+            _ => (),
+        }
+    }
+
     #[inline]
-    fn rect(&mut self, pass: Region, rect: Rect, col: Colour) {
-        self.shaded_square.rect(pass.0, rect, col);
+    fn opacity(&self) -> f32 {
+        self.opacity
     }
 
     #[inline]
+    fn set_opacity(&mut self, opacity: f32) {
+        self.opacity = opacity;
+    }
+
+    #[inline]
+    fn transform(&self) -> Affine {
+        self.transform
+    }
+
+    fn push_transform(&mut self, transform: Affine) {
+        self.cache.invalidate();
+        self.transforms.push(self.transform);
+        self.set_transform(self.transform * transform);
+    }
+
+    fn pop_transform(&mut self) {
+        if let Some(transform) = self.transforms.pop() {
+            self.cache.invalidate();
+            self.set_transform(transform);
+        }
+    }
+
+    fn rect(&mut self, pass: Region, rect: Rect, col: Colour) {
+        let col = self.fade(col);
+        if let Some(rect) = self.snap(rect) {
+            // Snapped geometry is already transformed
+            self.shaded_square.set_transform(Affine::IDENTITY);
+            self.shaded_square.rect(pass.0, rect, col);
+            self.shaded_square.set_transform(self.transform);
+        } else {
+            self.shaded_square.rect(pass.0, rect, col);
+        }
+    }
+
     fn frame(&mut self, pass: Region, outer: Rect, inner: Rect, col: Colour) {
-        self.shaded_square.frame(pass.0, outer, inner, col);
+        let col = self.fade(col);
+        if let (Some(outer), Some(inner)) = (self.snap(outer), self.snap(inner)) {
+            self.shaded_square.set_transform(Affine::IDENTITY);
+            self.shaded_square.frame(pass.0, outer, inner, col);
+            self.shaded_square.set_transform(self.transform);
+        } else {
+            self.shaded_square.frame(pass.0, outer, inner, col);
+        }
+    }
+
+    #[inline]
+    fn hairline(&mut self, pass: Region, p1: Coord, p2: Coord, col: Colour) {
+        let col = self.fade(col);
+        self.shaded_square.hairline(pass.0, p1, p2, col);
     }
 }
 
 impl<C: CustomPipe + 'static> DrawRounded for DrawPipe<C> {
     #[inline]
     fn rounded_line(&mut self, pass: Region, p1: Coord, p2: Coord, radius: f32, col: Colour) {
+        let col = self.fade(col);
         self.flat_round.line(pass.0, p1, p2, radius, col);
     }
 
     #[inline]
     fn circle(&mut self, pass: Region, rect: Rect, inner_radius: f32, col: Colour) {
+        let col = self.fade(col);
         self.flat_round.circle(pass.0, rect, inner_radius, col);
     }
 
@@ -164,6 +601,7 @@ impl<C: CustomPipe + 'static> DrawRounded for DrawPipe<C> {
         inner_radius: f32,
         col: Colour,
     ) {
+        let col = self.fade(col);
         self.flat_round
             .rounded_frame(pass.0, outer, inner, inner_radius, col);
     }
@@ -172,12 +610,14 @@ impl<C: CustomPipe + 'static> DrawRounded for DrawPipe<C> {
 impl<C: CustomPipe + 'static> DrawShaded for DrawPipe<C> {
     #[inline]
     fn shaded_square(&mut self, pass: Region, rect: Rect, norm: (f32, f32), col: Colour) {
+        let col = self.fade(col);
         self.shaded_square
             .shaded_rect(pass.0, rect, Vec2::from(norm), col);
     }
 
     #[inline]
     fn shaded_circle(&mut self, pass: Region, rect: Rect, norm: (f32, f32), col: Colour) {
+        let col = self.fade(col);
         self.shaded_round
             .circle(pass.0, rect, Vec2::from(norm), col);
     }
@@ -191,6 +631,7 @@ impl<C: CustomPipe + 'static> DrawShaded for DrawPipe<C> {
         norm: (f32, f32),
         col: Colour,
     ) {
+        let col = self.fade(col);
         self.shaded_square
             .shaded_frame(pass.0, outer, inner, Vec2::from(norm), col);
     }
@@ -204,7 +645,128 @@ impl<C: CustomPipe + 'static> DrawShaded for DrawPipe<C> {
         norm: (f32, f32),
         col: Colour,
     ) {
+        let col = self.fade(col);
         self.shaded_round
             .shaded_frame(pass.0, outer, inner, Vec2::from(norm), col);
     }
 }
+
+/// Begin a render pass, clearing attachments if `load_op` is `Clear`
+fn begin_pass<'a>(
+    encoder: &'a mut wgpu::CommandEncoder,
+    target_view: &'a wgpu::TextureView,
+    depth_view: Option<&'a wgpu::TextureView>,
+    load_op: wgpu::LoadOp,
+    clear_color: wgpu::Color,
+) -> wgpu::RenderPass<'a> {
+    encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+        color_attachments: &[wgpu::RenderPassColorAttachmentDescriptor {
+            attachment: target_view,
+            resolve_target: None,
+            load_op,
+            store_op: wgpu::StoreOp::Store,
+            clear_color,
+        }],
+        depth_stencil_attachment: depth_view.map(|view| {
+            wgpu::RenderPassDepthStencilAttachmentDescriptor {
+                attachment: view,
+                depth_load_op: load_op,
+                depth_store_op: wgpu::StoreOp::Store,
+                clear_depth: 1.0,
+                stencil_load_op: load_op,
+                stencil_store_op: wgpu::StoreOp::Store,
+                clear_stencil: 0,
+            }
+        }),
+    })
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn snap_rects() {
+        let rect = Rect::new(Coord(3, 5), Size(7, 2));
+        let snap =
+            |s: f32, t: Vec2| snap_rect(Affine::translate(t) * Affine::scale(Vec2::splat(s)), rect);
+
+        // Scale factors as used for DPI 1.0, 1.25 and 2.0
+        assert_eq!(snap(1.0, Vec2::splat(0.0)), Some(rect));
+        assert_eq!(
+            snap(1.0, Vec2(0.4, 0.6)),
+            Some(Rect::new(Coord(3, 6), Size(7, 2)))
+        );
+        // Corners (3.75, 6.25) and (12.5, 8.75)
+        assert_eq!(
+            snap(1.25, Vec2::splat(0.0)),
+            Some(Rect::new(Coord(4, 6), Size(9, 3)))
+        );
+        assert_eq!(
+            snap(2.0, Vec2::splat(0.0)),
+            Some(Rect::new(Coord(6, 10), Size(14, 4)))
+        );
+
+        // Adjacent rects stay adjacent
+        let a = snap_rect(
+            Affine::scale(Vec2::splat(1.25)),
+            Rect::new(Coord(0, 0), Size(3, 1)),
+        );
+        let b = snap_rect(
+            Affine::scale(Vec2::splat(1.25)),
+            Rect::new(Coord(3, 0), Size(3, 1)),
+        );
+        let (a, b) = (a.unwrap(), b.unwrap());
+        assert_eq!(a.pos.0 + a.size.0 as i32, b.pos.0);
+
+        // Mirroring is fine; rotation is not snapped
+        assert_eq!(
+            snap_rect(Affine::scale(Vec2(-1.0, 1.0)), rect),
+            Some(Rect::new(Coord(-10, 5), Size(7, 2)))
+        );
+        assert_eq!(snap_rect(Affine::rotate(0.5), rect), None);
+    }
+
+    #[test]
+    fn render_stats() {
+        let mut stats = FrameStats::new(false);
+        assert!(stats.current().is_none());
+        stats.finish(false);
+        assert_eq!(stats.last(), None);
+
+        let mut stats = FrameStats::new(true);
+        assert_eq!(stats.last(), Some(RenderStats::default()));
+        let current = stats.current().unwrap();
+        current.add_region((6, 0, 3), false, 1);
+        current.add_region((0, 12, 0), true, 3);
+        current.clip_regions = 2;
+        current.text_sections = 4;
+        stats.finish(false);
+
+        let rendered = RenderStats {
+            shaded_square_vertices: 6,
+            shaded_round_vertices: 12,
+            flat_round_vertices: 3,
+            custom_passes: 1,
+            clip_regions: 2,
+            render_passes: 4,
+            text_sections: 4,
+            replayed: false,
+        };
+        assert_eq!(stats.last(), Some(rendered));
+        assert_eq!(rendered.vertices(), 21);
+        // Counting restarts for the next frame
+        assert_eq!(stats.current().copied(), Some(RenderStats::default()));
+
+        // A replayed frame replaces the last, even if it drew less
+        stats.current().unwrap().add_region((6, 0, 0), false, 1);
+        stats.finish(true);
+        let replayed = stats.last().unwrap();
+        assert!(replayed.replayed);
+        assert_eq!((replayed.vertices(), replayed.render_passes), (6, 1));
+
+        // ... and a rendered frame replaces a replay
+        stats.finish(false);
+        assert_eq!(stats.last(), Some(RenderStats::default()));
+    }
+}
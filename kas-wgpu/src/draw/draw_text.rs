@@ -5,35 +5,98 @@
 
 //! Text drawing API for `kas_wgpu`
 
+use std::borrow::Cow;
 use std::f32;
-use wgpu_glyph::{GlyphCruncher, HorizontalAlign, Layout, Scale, Section, VerticalAlign};
+use wgpu_glyph::{
+    GlyphCruncher, GlyphPositioner, HorizontalAlign, Layout, Scale, Section, VerticalAlign,
+};
 
+use super::emoji::{self, EmojiLayout};
+use super::text_layout::StyledLayout;
 use crate::draw::{CustomPipe, DrawPipe, Vec2};
-use kas::draw::{DrawText, Font, FontId, TextProperties};
-use kas::geom::{Coord, Rect};
+use kas::draw::{visual_order, Colour, DrawText, Font, FontId, TextProperties, TextStyle};
+use kas::geom::{Affine, Coord, Quad, Rect};
 use kas::Align;
 
-impl<C: CustomPipe + 'static> DrawText for DrawPipe<C> {
-    fn load_font(&mut self, font: Font<'static>) -> FontId {
-        FontId(self.glyph_brush.add_font(font).0)
-    }
+impl<C: CustomPipe> DrawPipe<C> {
+    /// Queue text for drawing
+    ///
+    /// Unlike [`DrawText::text`], this neither records the text nor applies
+    /// the opacity multiplier.
+    pub(crate) fn queue_text(&mut self, rect: Rect, text: &str, props: TextProperties) {
+        if let Some(stats) = self.stats.current() {
+            stats.text_sections += 1;
+        }
+        if self.precache_ascii && props.font == FontId(0) {
+            // Queue all printable ASCII, invisibly, to rasterise the glyphs
+            self.precache_ascii = false;
+            let ascii: String = (' '..='~').collect();
+            self.glyph_brush.queue(Section {
+                text: &ascii,
+                screen_position: Vec2::from(rect.pos).into(),
+                bounds: (f32::INFINITY, f32::INFINITY),
+                scale: Scale::uniform(props.scale),
+                color: [0.0; 4],
+                z: 0.0,
+                layout: Layout::default_single_line(),
+                font_id: wgpu_glyph::FontId(0),
+            });
+        }
 
-    fn text(&mut self, rect: Rect, text: &str, props: TextProperties) {
-        let bounds = Coord::from(rect.size);
+        let style = props.style.unwrap_or_default();
+
+        // Bidirectional text must be wrapped before reordering, since
+        // reordering moves words between lines
+        let wrapped;
+        let mut visual = visual_order(text);
+        if props.line_wrap && matches!(visual, Cow::Owned(_)) {
+            let bound = (f32::INFINITY, f32::INFINITY);
+            let (font, scale) = (props.font, props.scale);
+            let width = |line: &str| {
+                let line = visual_order(line);
+                self.glyph_bound(&line, font, scale, bound, false, style).0
+            };
+            wrapped = wrap_logical(text, rect.size.0 as f32, width);
+            visual = visual_order(&wrapped);
+        }
+        let text = visual.as_ref();
+        let mut bounds = Coord::from(rect.size);
 
         // TODO: support justified alignment
-        let (h_align, h_offset) = match props.align.0 {
+        let (mut h_align, h_offset) = match props.align.0 {
             Align::Begin | Align::Stretch => (HorizontalAlign::Left, 0),
             Align::Centre => (HorizontalAlign::Center, bounds.0 / 2),
             Align::End => (HorizontalAlign::Right, bounds.0),
         };
-        let (v_align, v_offset) = match props.align.1 {
+        let (mut v_align, v_offset) = match props.align.1 {
             Align::Begin | Align::Stretch => (VerticalAlign::Top, 0),
             Align::Centre => (VerticalAlign::Center, bounds.1 / 2),
             Align::End => (VerticalAlign::Bottom, bounds.1),
         };
 
-        let text_pos = rect.pos + Coord(h_offset, v_offset);
+        let mut text_pos = rect.pos + Coord(h_offset, v_offset);
+
+        let is_aligned = |align| matches!(align, Align::Centre | Align::End);
+        let aligned = is_aligned(props.align.0) || is_aligned(props.align.1);
+        if self.pixel_snap && !props.line_wrap && aligned {
+            // glyph_brush would align to a fractional position; instead we
+            // measure the text and align ourselves, rounding to whole pixels.
+            let bound = (f32::INFINITY, f32::INFINITY);
+            let size = self.glyph_bound(text, props.font, props.scale, bound, false, style);
+            let offset = |align, space: i32, size: f32| match align {
+                Align::Begin | Align::Stretch => 0,
+                Align::Centre => ((space as f32 - size) * 0.5).round() as i32,
+                Align::End => (space as f32 - size).round() as i32,
+            };
+            text_pos = rect.pos
+                + Coord(
+                    offset(props.align.0, bounds.0, size.0),
+                    offset(props.align.1, bounds.1, size.1),
+                );
+            bounds = rect.pos + bounds - text_pos;
+            h_align = HorizontalAlign::Left;
+            v_align = VerticalAlign::Top;
+        }
 
         let layout = match props.line_wrap {
             true => Layout::default_wrap(),
@@ -41,46 +104,239 @@ impl<C: CustomPipe + 'static> DrawText for DrawPipe<C> {
         };
         let layout = layout.h_align(h_align).v_align(v_align);
 
-        self.glyph_brush.queue(Section {
+        let mut color: [f32; 4] = props.col.into();
+        if self.text_gamma {
+            color[3] *= coverage_boost(props.col);
+        }
+
+        let section = Section {
             text,
             screen_position: Vec2::from(text_pos).into(),
             bounds: Vec2::from(bounds).into(),
             scale: Scale::uniform(props.scale),
-            color: props.col.into(),
+            color,
             z: 0.0,
             layout,
             font_id: wgpu_glyph::FontId(props.font.0),
-        });
+        };
+        if style.is_default() && !text.contains('\t') {
+            self.queue_section(section, layout);
+        } else {
+            self.queue_section(section, StyledLayout { layout, style });
+        }
     }
 
-    #[inline]
-    fn text_bound(
+    /// Get the emoji font identifier, adding the font on first use
+    fn emoji_font_id(&mut self) -> Option<wgpu_glyph::FontId> {
+        let (font, id) = self.emoji.as_mut()?;
+        // The emoji font is added after the theme's fonts (at least font 0)
+        if id.is_none() && !self.glyph_brush.fonts().is_empty() {
+            *id = Some(self.glyph_brush.add_font(font.font().clone()));
+        }
+        *id
+    }
+
+    /// Split the text of `section` into runs of its font and the emoji font
+    ///
+    /// Returns `None` if the emoji font is not used.
+    fn emoji_runs<'a>(
+        &mut self,
+        section: &Section<'a>,
+    ) -> Option<(Vec<(&'a str, wgpu_glyph::FontId)>, wgpu_glyph::FontId)> {
+        let id = self.emoji_font_id()?;
+        let fonts = self.glyph_brush.fonts();
+        emoji::split_runs(fonts, section.text, section.font_id, id).map(|runs| (runs, id))
+    }
+
+    /// Queue a section, using the emoji font where required
+    fn queue_section<L: GlyphPositioner>(&mut self, section: Section, layout: L) {
+        match self.emoji_runs(&section) {
+            None => self.glyph_brush.queue_custom_layout(section, &layout),
+            Some((runs, font_id)) => {
+                let layout = EmojiLayout {
+                    layout,
+                    font_id,
+                    tables: self.emoji.as_ref().and_then(|(font, _)| font.tables()),
+                };
+                let section = emoji::varied_section(&section, &runs);
+                self.glyph_brush.queue_custom_layout(section, &layout);
+            }
+        }
+    }
+
+    /// Measure the bounds of a section, using the emoji font where required
+    fn section_bounds<L: GlyphPositioner>(
+        &mut self,
+        section: Section,
+        layout: L,
+    ) -> Option<wgpu_glyph::Rect<f32>> {
+        match self.emoji_runs(&section) {
+            None => self
+                .glyph_brush
+                .glyph_bounds_custom_layout(section, &layout),
+            Some((runs, font_id)) => {
+                let layout = EmojiLayout {
+                    layout,
+                    font_id,
+                    tables: self.emoji.as_ref().and_then(|(font, _)| font.tables()),
+                };
+                let section = emoji::varied_section(&section, &runs);
+                self.glyph_brush
+                    .glyph_bounds_custom_layout(section, &layout)
+            }
+        }
+    }
+
+    fn glyph_bound(
         &mut self,
         text: &str,
         font_id: FontId,
         font_scale: f32,
         bounds: (f32, f32),
         line_wrap: bool,
+        style: TextStyle,
     ) -> (f32, f32) {
         let layout = match line_wrap {
             true => Layout::default_wrap(),
             false => Layout::default_single_line(),
         };
 
-        self.glyph_brush
-            .glyph_bounds(Section {
-                text,
-                screen_position: (0.0, 0.0),
-                bounds,
-                scale: Scale::uniform(font_scale),
-                color: Default::default(),
-                z: 0.0,
-                layout,
-                font_id: wgpu_glyph::FontId(font_id.0),
-            })
-            .map(|rect| (Vec2(rect.min.x, rect.min.y), Vec2(rect.max.x, rect.max.y)))
+        let section = Section {
+            text,
+            screen_position: (0.0, 0.0),
+            bounds,
+            scale: Scale::uniform(font_scale),
+            color: Default::default(),
+            z: 0.0,
+            layout,
+            font_id: wgpu_glyph::FontId(font_id.0),
+        };
+        let rect = if style.is_default() && !text.contains('\t') {
+            self.section_bounds(section, layout)
+        } else {
+            self.section_bounds(section, StyledLayout { layout, style })
+        };
+        rect.map(|rect| (Vec2(rect.min.x, rect.min.y), Vec2(rect.max.x, rect.max.y)))
             .map(|(min, max)| max - min)
             .unwrap_or(Vec2::splat(0.0))
             .into()
     }
 }
+
+impl<C: CustomPipe + 'static> DrawText for DrawPipe<C> {
+    fn load_font(&mut self, font: Font<'static>) -> FontId {
+        FontId(self.glyph_brush.add_font(font).0)
+    }
+
+    fn text(&mut self, mut rect: Rect, text: &str, mut props: TextProperties) {
+        if props.transform && self.transform != Affine::IDENTITY {
+            let t = self.transform;
+            let pos = t.apply(Vec2::from(rect.pos));
+            let size = Vec2::from(rect.size) * Vec2(t.x.length(), t.y.length());
+            rect = Quad(pos, pos + size).into();
+            props.scale *= t.mean_scale();
+            props.style = props.style.map(|style| style.scale(t.mean_scale()));
+        }
+        props.transform = false;
+        self.cache.text(rect, text, props);
+        props.col.a *= self.opacity;
+        self.frame_text.push((rect, text.to_string(), props));
+        self.queue_text(rect, text, props);
+    }
+
+    #[inline]
+    fn text_bound(
+        &mut self,
+        text: &str,
+        font_id: FontId,
+        font_scale: f32,
+        bounds: (f32, f32),
+        line_wrap: bool,
+    ) -> (f32, f32) {
+        let style = TextStyle::default();
+        self.glyph_bound(text, font_id, font_scale, bounds, line_wrap, style)
+    }
+
+    #[inline]
+    fn text_bound_styled(
+        &mut self,
+        text: &str,
+        font_id: FontId,
+        font_scale: f32,
+        bounds: (f32, f32),
+        line_wrap: bool,
+        style: TextStyle,
+    ) -> (f32, f32) {
+        self.glyph_bound(text, font_id, font_scale, bounds, line_wrap, style)
+    }
+
+    fn font_ascent(&mut self, font_id: FontId, font_scale: f32) -> f32 {
+        self.glyph_brush
+            .fonts()
+            .get(font_id.0)
+            .map(|font| font.v_metrics(Scale::uniform(font_scale)).ascent)
+            .unwrap_or(font_scale)
+    }
+}
+
+/// Insert line breaks into logical-order `text` such that lines fit `width`
+///
+/// Lines are broken after white-space, using `measure` to get the width of
+/// each candidate line. A word wider than `width` is put on a line of its own
+/// (which `glyph_brush` may break further).
+fn wrap_logical<F: FnMut(&str) -> f32>(text: &str, width: f32, mut measure: F) -> String {
+    let mut out = String::with_capacity(text.len() + 16);
+    for (i, para) in text.split('\n').enumerate() {
+        if i > 0 {
+            out.push('\n');
+        }
+        let mut breaks: Vec<usize> = para
+            .char_indices()
+            .zip(para.chars().skip(1))
+            .filter(|((_, c), next)| c.is_whitespace() && !next.is_whitespace())
+            .map(|((i, c), _)| i + c.len_utf8())
+            .collect();
+        breaks.push(para.len());
+
+        // Line start and the end of the last word known to fit
+        let (mut start, mut end) = (0, 0);
+        for b in breaks {
+            if end > start && measure(para[start..b].trim_end()) > width {
+                out.push_str(para[start..end].trim_end());
+                out.push('\n');
+                start = end;
+            }
+            end = b;
+        }
+        out.push_str(&para[start..]);
+    }
+    out
+}
+
+/// Alpha multiplier approximating gamma-correct coverage
+///
+/// wgpu_glyph's shader multiplies colour alpha by glyph coverage, and the
+/// result is clamped to 1 before blending. Thus scaling alpha above 1 raises
+/// partial coverage (edge pixels) while leaving full coverage unchanged. The
+/// boost is strongest for dark text and nil for white text.
+fn coverage_boost(col: Colour) -> f32 {
+    let luma = 0.2126 * col.r + 0.7152 * col.g + 0.0722 * col.b;
+    1.0 + 0.6 * (1.0 - luma.clamp(0.0, 1.0))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn wrap_before_reorder() {
+        let wrap = |text| wrap_logical(text, 7.0, |line| line.chars().count() as f32);
+        assert_eq!(wrap("ab cd ef gh"), "ab cd\nef gh");
+        assert_eq!(wrap("abcdefghi jk\nl"), "abcdefghi\njk\nl");
+
+        // Each wrapped line is reordered independently
+        let text = wrap("אב גד הו");
+        assert_eq!(text, "אב גד\nהו");
+        assert_eq!(visual_order(&text), "דג בא\nוה");
+    }
+}
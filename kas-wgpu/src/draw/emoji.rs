@@ -0,0 +1,402 @@
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License in the LICENSE-APACHE file or at:
+//     https://www.apache.org/licenses/LICENSE-2.0
+
+//! Emoji (colour glyph) support
+//!
+//! `wgpu_glyph` only renders monochrome glyph outlines. Colour fonts using
+//! the `COLR` (version 0) and `CPAL` tables describe each colour glyph as a
+//! stack of monochrome outline glyphs, each with a palette colour; we render
+//! these by substituting the layers during layout. Bitmap-only colour glyphs
+//! (`CBDT`, `sbix`) are not supported; a box is drawn in their place.
+//!
+//! Text is not shaped, thus emoji sequences (e.g. using zero-width joiners)
+//! are drawn as their component glyphs.
+
+use std::convert::TryInto;
+use std::hash::{Hash, Hasher};
+use std::io;
+use std::path::Path;
+
+use kas::draw::Colour;
+use wgpu_glyph::rusttype::{Font, GlyphId, PositionedGlyph, Rect};
+use wgpu_glyph::{
+    FontId, FontMap, GlyphPositioner, Section, SectionGeometry, SectionText, VariedSection,
+};
+
+/// Zero-width joiner
+const ZWJ: char = '\u{200D}';
+/// Variation selector requesting emoji presentation
+const VS16: char = '\u{FE0F}';
+
+// Font data is untrusted: all reads are checked, including offset arithmetic.
+
+fn u16_at(data: &[u8], pos: usize) -> Option<u16> {
+    let b = data.get(pos..pos.checked_add(2)?)?;
+    Some(u16::from_be_bytes(b.try_into().ok()?))
+}
+
+fn u32_at(data: &[u8], pos: usize) -> Option<u32> {
+    let b = data.get(pos..pos.checked_add(4)?)?;
+    Some(u32::from_be_bytes(b.try_into().ok()?))
+}
+
+/// Position `base + size * i`, if representable
+fn offset(base: usize, size: usize, i: usize) -> Option<usize> {
+    size.checked_mul(i)?.checked_add(base)
+}
+
+/// Find a table in (non-collection) font data
+fn find_table<'a>(data: &'a [u8], tag: &[u8; 4]) -> Option<&'a [u8]> {
+    let num_tables = u16_at(data, 4)? as usize;
+    for i in 0..num_tables {
+        let record = offset(12, 16, i)?;
+        if data.get(record..record.checked_add(4)?)? == tag {
+            let start = u32_at(data, record.checked_add(8)?)? as usize;
+            let len = u32_at(data, record.checked_add(12)?)? as usize;
+            return data.get(start..start.checked_add(len)?);
+        }
+    }
+    None
+}
+
+/// Read the first palette of a `CPAL` table
+fn parse_palette(cpal: &[u8]) -> Option<Vec<[f32; 4]>> {
+    let num_entries = u16_at(cpal, 2)? as usize;
+    let records = u32_at(cpal, 8)? as usize;
+    let first = u16_at(cpal, 12)? as usize;
+    (first..first.checked_add(num_entries)?)
+        .map(|i| {
+            // Records are stored as BGRA, sRGB-encoded
+            let [b, g, r, a] = u32_at(cpal, offset(records, 4, i)?)?.to_be_bytes();
+            Some(Colour::from_rgba8(r, g, b, a).into())
+        })
+        .collect()
+}
+
+/// One layer of a colour glyph
+#[derive(Clone, Copy, Debug)]
+struct Layer {
+    glyph: GlyphId,
+    /// Palette colour, or `None` to use the text colour
+    colour: Option<[f32; 4]>,
+}
+
+/// Colour glyph tables of a font (`COLR` version 0 and `CPAL`)
+#[derive(Clone, Debug, Default)]
+pub struct ColourTables {
+    /// Base glyph, first layer index and number of layers, sorted by glyph
+    base: Vec<(GlyphId, usize, usize)>,
+    layers: Vec<Layer>,
+}
+
+impl ColourTables {
+    /// Read from font data
+    ///
+    /// Returns `None` if the font has no (valid) `COLR` table.
+    pub fn parse(data: &[u8]) -> Option<Self> {
+        let colr = find_table(data, b"COLR")?;
+        let palette = find_table(data, b"CPAL")
+            .and_then(parse_palette)
+            .unwrap_or_default();
+
+        let num_base = u16_at(colr, 2)? as usize;
+        let base_offset = u32_at(colr, 4)? as usize;
+        let layer_offset = u32_at(colr, 8)? as usize;
+        let num_layers = u16_at(colr, 12)? as usize;
+
+        let mut layers = Vec::with_capacity(num_layers);
+        for i in 0..num_layers {
+            let pos = offset(layer_offset, 4, i)?;
+            let glyph = GlyphId(u16_at(colr, pos)? as u32);
+            let index = u16_at(colr, pos.checked_add(2)?)?;
+            // Index 0xFFFF (and any invalid index) uses the text colour
+            let colour = palette.get(index as usize).cloned();
+            layers.push(Layer { glyph, colour });
+        }
+
+        let mut base = Vec::with_capacity(num_base);
+        for i in 0..num_base {
+            let pos = offset(base_offset, 6, i)?;
+            let glyph = GlyphId(u16_at(colr, pos)? as u32);
+            let first = u16_at(colr, pos.checked_add(2)?)? as usize;
+            let num = u16_at(colr, pos.checked_add(4)?)? as usize;
+            // Records referencing missing layers are skipped
+            if layers.get(first..first + num).is_some() {
+                base.push((glyph, first, num));
+            }
+        }
+        base.sort_by_key(|b| b.0);
+
+        Some(ColourTables { base, layers })
+    }
+
+    /// Get the layers of `glyph`, if it is a colour glyph
+    fn layers(&self, glyph: GlyphId) -> Option<&[Layer]> {
+        let i = self.base.binary_search_by_key(&glyph, |b| b.0).ok()?;
+        let (_, first, num) = *self.base.get(i)?;
+        self.layers.get(first..first.checked_add(num)?)
+    }
+}
+
+/// A fallback font for emoji
+pub struct EmojiFont {
+    font: Font<'static>,
+    tables: Option<ColourTables>,
+}
+
+impl EmojiFont {
+    /// Load from a font file
+    ///
+    /// Font collections are not supported.
+    pub fn load(path: &Path) -> io::Result<Self> {
+        let data = std::fs::read(path)?;
+        let tables = ColourTables::parse(&data);
+        let font =
+            Font::from_bytes(data).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+        Ok(EmojiFont { font, tables })
+    }
+
+    /// The font
+    #[inline]
+    pub fn font(&self) -> &Font<'static> {
+        &self.font
+    }
+
+    /// Colour tables, if any
+    #[inline]
+    pub fn tables(&self) -> Option<&ColourTables> {
+        self.tables.as_ref()
+    }
+}
+
+/// Split `text` into runs using `font` or (for characters which `font` does
+/// not have) the `emoji` font
+///
+/// Returns `None` if the `emoji` font is not required.
+pub fn split_runs<'a>(
+    fonts: &[Font],
+    text: &'a str,
+    font: FontId,
+    emoji: FontId,
+) -> Option<Vec<(&'a str, FontId)>> {
+    let (primary, fallback) = (fonts.get(font.0)?, fonts.get(emoji.0)?);
+    let missing = |c: char| primary.glyph(c).id() == GlyphId(0);
+
+    let mut runs = vec![];
+    let mut start = 0;
+    let mut in_emoji = false;
+    for (i, c) in text.char_indices() {
+        let is_emoji = if c.is_ascii() {
+            false
+        } else if in_emoji && (c == ZWJ || c == VS16) {
+            true
+        } else {
+            missing(c) && fallback.glyph(c).id() != GlyphId(0)
+        };
+        if is_emoji != in_emoji {
+            if i > start {
+                runs.push((text.get(start..i)?, if in_emoji { emoji } else { font }));
+            }
+            start = i;
+            in_emoji = is_emoji;
+        }
+    }
+
+    if runs.is_empty() && !in_emoji {
+        return None;
+    }
+    runs.push((text.get(start..)?, if in_emoji { emoji } else { font }));
+    Some(runs)
+}
+
+/// Convert `section` to a [`VariedSection`] with the given runs of its text
+pub fn varied_section<'a>(section: &Section<'a>, runs: &[(&'a str, FontId)]) -> VariedSection<'a> {
+    let text = runs
+        .iter()
+        .map(|&(text, font_id)| SectionText {
+            text,
+            scale: section.scale,
+            color: section.color,
+            font_id,
+        })
+        .collect();
+    VariedSection {
+        screen_position: section.screen_position,
+        bounds: section.bounds,
+        z: section.z,
+        layout: section.layout,
+        text,
+    }
+}
+
+/// Layout substituting colour layers for the glyphs of the emoji font
+///
+/// Emoji font glyphs with neither colour layers nor an outline are replaced
+/// by the `.notdef` glyph (usually a box) of font 0.
+pub struct EmojiLayout<'a, L> {
+    pub layout: L,
+    pub font_id: FontId,
+    pub tables: Option<&'a ColourTables>,
+}
+
+impl<'a, L: Hash> Hash for EmojiLayout<'a, L> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        // Tables are fixed for a given font_id
+        self.layout.hash(state);
+        self.font_id.hash(state);
+    }
+}
+
+impl<'a, L: GlyphPositioner> GlyphPositioner for EmojiLayout<'a, L> {
+    fn calculate_glyphs<'font, F: FontMap<'font>>(
+        &self,
+        fonts: &F,
+        geometry: &SectionGeometry,
+        sections: &[SectionText<'_>],
+    ) -> Vec<(PositionedGlyph<'font>, [f32; 4], FontId)> {
+        let glyphs = self.layout.calculate_glyphs(fonts, geometry, sections);
+        let mut out = Vec::with_capacity(glyphs.len());
+        for (glyph, col, font_id) in glyphs {
+            if font_id != self.font_id {
+                out.push((glyph, col, font_id));
+                continue;
+            }
+
+            let (scale, pos) = (glyph.scale(), glyph.position());
+            if let Some(layers) = self.tables.and_then(|t| t.layers(glyph.id())) {
+                let font = fonts.font(font_id);
+                for layer in layers {
+                    let g = font.glyph(layer.glyph).scaled(scale).positioned(pos);
+                    let c = match layer.colour {
+                        Some(c) => [c[0], c[1], c[2], c[3] * col[3]],
+                        None => col,
+                    };
+                    out.push((g, c, font_id));
+                }
+            } else if glyph.pixel_bounding_box().is_none()
+                && glyph.unpositioned().h_metrics().advance_width > 0.0
+            {
+                let font = fonts.font(FontId(0));
+                let g = font.glyph(GlyphId(0)).scaled(scale).positioned(pos);
+                out.push((g, col, FontId(0)));
+            } else {
+                out.push((glyph, col, font_id));
+            }
+        }
+        out
+    }
+
+    fn bounds_rect(&self, geometry: &SectionGeometry) -> Rect<f32> {
+        self.layout.bounds_rect(geometry)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    /// Font data with the given tables (and no others)
+    fn font_data(tables: &[(&[u8; 4], &[u8])]) -> Vec<u8> {
+        let mut data = vec![0, 1, 0, 0];
+        data.extend(&(tables.len() as u16).to_be_bytes());
+        data.extend(&[0; 6]);
+        let mut offset = 12 + 16 * tables.len();
+        for (tag, table) in tables {
+            data.extend(tag.iter());
+            data.extend(&[0; 4]);
+            data.extend(&(offset as u32).to_be_bytes());
+            data.extend(&(table.len() as u32).to_be_bytes());
+            offset += table.len();
+        }
+        for (_, table) in tables {
+            data.extend(table.iter());
+        }
+        data
+    }
+
+    /// A `COLR` table with the given base and layer records
+    fn colr(base: &[[u16; 3]], layers: &[[u16; 2]]) -> Vec<u8> {
+        let mut data = vec![0, 0];
+        data.extend(&(base.len() as u16).to_be_bytes());
+        data.extend(&14u32.to_be_bytes());
+        data.extend(&(14 + 6 * base.len() as u32).to_be_bytes());
+        data.extend(&(layers.len() as u16).to_be_bytes());
+        for x in base.iter().flatten().chain(layers.iter().flatten()) {
+            data.extend(&x.to_be_bytes());
+        }
+        data
+    }
+
+    /// A `CPAL` table with one palette of BGRA records
+    fn cpal(records: &[[u8; 4]]) -> Vec<u8> {
+        let mut data = vec![0, 0];
+        data.extend(&(records.len() as u16).to_be_bytes());
+        data.extend(&1u16.to_be_bytes());
+        data.extend(&(records.len() as u16).to_be_bytes());
+        data.extend(&14u32.to_be_bytes());
+        data.extend(&0u16.to_be_bytes());
+        data.extend(records.iter().flatten());
+        data
+    }
+
+    fn sample() -> Vec<u8> {
+        // Glyph 5 has two layers; glyph 3 references missing layers
+        let colr = colr(&[[5, 0, 2], [3, 1, 4]], &[[10, 0], [11, 0xFFFF]]);
+        let cpal = cpal(&[[0, 0, 255, 255]]);
+        font_data(&[(b"COLR", &colr), (b"CPAL", &cpal)])
+    }
+
+    #[test]
+    fn parse_tables() {
+        let tables = ColourTables::parse(&sample()).unwrap();
+        let layers = tables.layers(GlyphId(5)).unwrap();
+        assert_eq!(layers.len(), 2);
+        assert_eq!(layers[0].glyph, GlyphId(10));
+        assert_eq!(layers[0].colour, Some([1.0, 0.0, 0.0, 1.0]));
+        assert_eq!(layers[1].glyph, GlyphId(11));
+        assert_eq!(layers[1].colour, None);
+        assert!(tables.layers(GlyphId(3)).is_none());
+        assert!(tables.layers(GlyphId(4)).is_none());
+    }
+
+    #[test]
+    fn parse_truncated() {
+        let data = sample();
+        for len in 0..data.len() {
+            if let Some(tables) = ColourTables::parse(&data[..len]) {
+                // Only a truncated CPAL table may remain, losing its colours
+                let layers = tables.layers(GlyphId(5)).unwrap();
+                assert!(layers.iter().all(|layer| layer.colour.is_none()));
+            }
+        }
+        assert!(ColourTables::parse(&[]).is_none());
+    }
+
+    #[test]
+    fn parse_malformed() {
+        let max = u32::MAX.to_be_bytes();
+        let patch = |pos: usize, bytes: &[u8]| {
+            let mut data = sample();
+            data[pos..pos + bytes.len()].copy_from_slice(bytes);
+            ColourTables::parse(&data)
+        };
+        // Table count, COLR table offset and length
+        assert!(patch(4, &[0xFF, 0xFF]).is_some());
+        assert!(patch(20, &max).is_none());
+        assert!(patch(24, &max).is_none());
+
+        // COLR base and layer offsets and counts (table at 44)
+        assert!(patch(44 + 2, &[0xFF, 0xFF]).is_none());
+        assert!(patch(44 + 4, &max).is_none());
+        assert!(patch(44 + 8, &max).is_none());
+        assert!(patch(44 + 12, &[0xFF, 0xFF]).is_none());
+
+        // CPAL entry count and record offset (table at 78): colours are lost
+        for (pos, bytes) in [(78 + 2, &[0xFF, 0xFF][..]), (78 + 8, &max[..])] {
+            let tables = patch(pos, bytes).unwrap();
+            assert_eq!(tables.layers(GlyphId(5)).unwrap()[0].colour, None);
+        }
+    }
+}
@@ -7,10 +7,11 @@
 
 use std::mem::size_of;
 
-use crate::draw::{Rgb, Vec2};
+use crate::draw::ring::{Pod, VertexRing};
+use crate::draw::{ndc_scale, Rgba, Vec2};
 use crate::shared::SharedState;
 use kas::draw::Colour;
-use kas::geom::{Coord, Rect, Size};
+use kas::geom::{Affine, Coord, Rect, Size};
 
 /// Offset relative to the size of a pixel used by the fragment shader to
 /// implement multi-sampling.
@@ -18,7 +19,13 @@ const OFFSET: f32 = 0.125;
 
 #[repr(C)]
 #[derive(Clone, Copy, Debug)]
-struct Vertex(Vec2, Rgb, f32, Vec2, Vec2);
+struct Vertex(Vec2, Rgba, f32, Vec2, Vec2);
+
+unsafe impl Pod for Vertex {}
+
+/// Vertices recorded for replay
+#[derive(Clone, Debug, Default)]
+pub struct Vertices(Vec<Vertex>);
 
 /// A pipeline for rendering rounded shapes
 pub struct FlatRound {
@@ -26,15 +33,22 @@ pub struct FlatRound {
     scale_buf: wgpu::Buffer,
     render_pipeline: wgpu::RenderPipeline,
     passes: Vec<Vec<Vertex>>,
+    ring: VertexRing,
+    transform: Option<Affine>,
 }
 
 impl FlatRound {
     /// Construct
-    pub fn new<C, T>(shared: &SharedState<C, T>, size: Size) -> Self {
+    pub fn new<C, T>(
+        shared: &SharedState<C, T>,
+        tex_format: wgpu::TextureFormat,
+        size: Size,
+        depth: bool,
+    ) -> Self {
         let device = &shared.device;
 
         type Scale = [f32; 2];
-        let scale_factor: Scale = [2.0 / size.0 as f32, 2.0 / size.1 as f32];
+        let scale_factor: Scale = ndc_scale(size);
         let scale_buf = device
             .create_buffer_mapped(
                 scale_factor.len(),
@@ -66,7 +80,7 @@ impl FlatRound {
         let render_pipeline = device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
             layout: &pipeline_layout,
             vertex_stage: wgpu::ProgrammableStageDescriptor {
-                module: &shared.shaders.vert_3122,
+                module: &shared.shaders.vert_4122,
                 entry_point: "main",
             },
             fragment_stage: Some(wgpu::ProgrammableStageDescriptor {
@@ -82,7 +96,7 @@ impl FlatRound {
             }),
             primitive_topology: wgpu::PrimitiveTopology::TriangleList,
             color_states: &[wgpu::ColorStateDescriptor {
-                format: wgpu::TextureFormat::Bgra8UnormSrgb,
+                format: tex_format,
                 color_blend: wgpu::BlendDescriptor {
                     src_factor: wgpu::BlendFactor::SrcAlpha,
                     dst_factor: wgpu::BlendFactor::OneMinusSrcAlpha,
@@ -95,7 +109,7 @@ impl FlatRound {
                 },
                 write_mask: wgpu::ColorWrite::ALL,
             }],
-            depth_stencil_state: None,
+            depth_stencil_state: Some(super::depth_stencil_state(depth)),
             index_format: wgpu::IndexFormat::Uint16,
             vertex_buffers: &[wgpu::VertexBufferDescriptor {
                 stride: size_of::<Vertex>() as wgpu::BufferAddress,
@@ -107,23 +121,23 @@ impl FlatRound {
                         shader_location: 0,
                     },
                     wgpu::VertexAttributeDescriptor {
-                        format: wgpu::VertexFormat::Float3,
+                        format: wgpu::VertexFormat::Float4,
                         offset: size_of::<Vec2>() as u64,
                         shader_location: 1,
                     },
                     wgpu::VertexAttributeDescriptor {
                         format: wgpu::VertexFormat::Float,
-                        offset: (size_of::<Vec2>() + size_of::<Rgb>()) as u64,
+                        offset: (size_of::<Vec2>() + size_of::<Rgba>()) as u64,
                         shader_location: 2,
                     },
                     wgpu::VertexAttributeDescriptor {
                         format: wgpu::VertexFormat::Float2,
-                        offset: (size_of::<Vec2>() + size_of::<Rgb>() + size_of::<f32>()) as u64,
+                        offset: (size_of::<Vec2>() + size_of::<Rgba>() + size_of::<f32>()) as u64,
                         shader_location: 3,
                     },
                     wgpu::VertexAttributeDescriptor {
                         format: wgpu::VertexFormat::Float2,
-                        offset: (2 * size_of::<Vec2>() + size_of::<Rgb>() + size_of::<f32>())
+                        offset: (2 * size_of::<Vec2>() + size_of::<Rgba>() + size_of::<f32>())
                             as u64,
                         shader_location: 4,
                     },
@@ -139,6 +153,8 @@ impl FlatRound {
             scale_buf,
             render_pipeline,
             passes: vec![],
+            ring: VertexRing::new(),
+            transform: None,
         }
     }
 
@@ -149,7 +165,7 @@ impl FlatRound {
         size: Size,
     ) {
         type Scale = [f32; 2];
-        let scale_factor: Scale = [2.0 / size.0 as f32, 2.0 / size.1 as f32];
+        let scale_factor: Scale = ndc_scale(size);
         let scale_buf = device
             .create_buffer_mapped(scale_factor.len(), wgpu::BufferUsage::COPY_SRC)
             .fill_from_slice(&scale_factor);
@@ -158,23 +174,60 @@ impl FlatRound {
         encoder.copy_buffer_to_buffer(&scale_buf, 0, &self.scale_buf, 0, byte_len);
     }
 
-    /// Render queued triangles and clear the queue
-    pub fn render(&mut self, device: &wgpu::Device, pass: usize, rpass: &mut wgpu::RenderPass) {
-        if pass >= self.passes.len() {
-            return;
-        }
-        let v = &mut self.passes[pass];
-        let buffer = device
-            .create_buffer_mapped(v.len(), wgpu::BufferUsage::VERTEX)
-            .fill_from_slice(&v);
-        let count = v.len() as u32;
+    /// Upload queued triangles for all passes and clear the queue
+    ///
+    /// This must be called before starting any render pass of the frame.
+    pub fn upload(&mut self, device: &wgpu::Device, encoder: &mut wgpu::CommandEncoder) {
+        self.ring.upload(device, encoder, &mut self.passes);
+    }
+
+    /// Number of vertices queued for `pass`
+    pub fn pass_len(&self, pass: usize) -> usize {
+        self.passes.get(pass).map(|v| v.len()).unwrap_or(0)
+    }
+
+    /// Copy vertices queued for `pass`, starting from index `start`
+    pub fn record(&self, pass: usize, start: usize) -> Vertices {
+        Vertices(
+            self.passes
+                .get(pass)
+                .map(|v| v[start..].to_vec())
+                .unwrap_or_default(),
+        )
+    }
 
+    /// Queue recorded vertices for `pass`
+    pub fn replay(&mut self, pass: usize, vertices: &Vertices) {
+        // Recorded vertices are already transformed
+        let transform = self.transform.take();
+        self.add_vertices(pass, &vertices.0);
+        self.transform = transform;
+    }
+
+    /// Set the transform applied to subsequently queued vertices
+    pub fn set_transform(&mut self, transform: Affine) {
+        self.transform = match transform == Affine::IDENTITY {
+            true => None,
+            false => Some(transform),
+        };
+    }
+
+    /// True if no triangles were uploaded for this `pass`
+    #[inline]
+    pub fn is_empty(&self, pass: usize) -> bool {
+        self.ring.is_empty(pass)
+    }
+
+    /// Render uploaded triangles, returning the number of vertices drawn
+    pub fn render(&mut self, pass: usize, rpass: &mut wgpu::RenderPass) -> u32 {
+        if self.is_empty(pass) {
+            return 0;
+        }
         rpass.set_pipeline(&self.render_pipeline);
         rpass.set_bind_group(0, &self.bind_group, &[]);
-        rpass.set_vertex_buffers(0, &[(&buffer, 0)]);
+        let count = self.ring.set_buffer(pass, rpass);
         rpass.draw(0..count, 0..1);
-
-        v.clear();
+        count
     }
 
     pub fn line(&mut self, pass: usize, p1: Coord, p2: Coord, radius: f32, col: Colour) {
@@ -192,7 +245,7 @@ impl FlatRound {
         let p1 = Vec2::from(p1);
         let p2 = Vec2::from(p2);
         let vx = p2 - p1;
-        let vx = vx * radius / (vx.0 * vx.0 + vx.1 * vx.1).sqrt();
+        let vx = vx.normalize() * radius;
         let vy = Vec2(-vx.1, vx.0);
 
         let n0 = Vec2::splat(0.0);
@@ -377,6 +430,16 @@ impl FlatRound {
             self.passes.resize(pass + 8, vec![]);
         }
 
-        self.passes[pass].extend_from_slice(slice);
+        match self.transform {
+            None => self.passes[pass].extend_from_slice(slice),
+            Some(t) => {
+                // Multi-sample offsets are relative to the size of a pixel
+                let s = Vec2(t.x.length(), t.y.length());
+                let iter = slice
+                    .iter()
+                    .map(|v| Vertex(t.apply(v.0), v.1, v.2, v.3, v.4 / s));
+                self.passes[pass].extend(iter);
+            }
+        }
     }
 }
@@ -7,25 +7,121 @@
 //!
 //! Extensions to the API of [`kas::draw`], plus some utility types.
 
+mod cache;
 mod custom;
 mod draw_pipe;
 mod draw_text;
+mod emoji;
+mod filter;
 mod flat_round;
+mod ring;
 mod shaded_round;
 mod shaded_square;
 mod shaders;
-mod vector;
+mod stencil;
+mod text_layout;
 
-use kas::geom::Rect;
+use crate::ColourFilter;
+use kas::draw::{ClipShape, TextProperties};
+use kas::geom::{Affine, Rect, Size};
+use std::sync::Arc;
 use wgpu_glyph::GlyphBrush;
 
+pub(crate) use emoji::EmojiFont;
 pub(crate) use flat_round::FlatRound;
+pub use ring::{Pod, VertexRing};
 pub(crate) use shaded_round::ShadedRound;
 pub(crate) use shaded_square::ShadedSquare;
-pub(crate) use shaders::ShaderManager;
+#[cfg(feature = "shader_reload")]
+pub(crate) use shaders::spawn_watcher;
+pub use shaders::{ShaderKind, ShaderManager};
+pub(crate) use stencil::StencilMask;
 
-pub use custom::{CustomPipe, CustomPipeBuilder, DrawCustom};
-pub use vector::{Quad, Vec2};
+pub use custom::{
+    CustomPipe, CustomPipeBuilder, CustomPipeId, DrawCustom, MultiPipe, MultiPipeBuilder,
+};
+pub use kas::geom::{Quad, Vec2};
+
+/// Scale factor mapping window coordinates to normalised device coordinates
+///
+/// The built-in pipes pass this value to their vertex shaders as a uniform
+/// and compute `gl_Position = vec4(scale * pos - 1.0, 0.0, 1.0)`, where `pos`
+/// is in window coordinates (pixels). Custom pipes may use the same mapping
+/// to share the built-in coordinate space.
+#[inline]
+pub fn ndc_scale(size: Size) -> [f32; 2] {
+    [2.0 / size.0 as f32, 2.0 / size.1 as f32]
+}
+
+/// Default format of render targets
+///
+/// Swap chains support this format on all primary backends.
+pub const DEFAULT_FORMAT: wgpu::TextureFormat = wgpu::TextureFormat::Bgra8UnormSrgb;
+
+/// True if the built-in pipes render correctly to targets of this `format`
+///
+/// Pipes (and clear colours) output linear colour values, which the target
+/// must encode. This holds for sRGB and floating-point formats, but not for
+/// other formats (e.g. `Bgra8Unorm`), on which colours would appear too dark.
+pub fn is_supported_format(format: wgpu::TextureFormat) -> bool {
+    use wgpu::TextureFormat::*;
+    matches!(
+        format,
+        Bgra8UnormSrgb | Rgba8UnormSrgb | Rgba16Float | Rgba32Float
+    )
+}
+
+/// Format of the depth attachment
+///
+/// A depth attachment is only used when [`CustomPipe::uses_depth`] returns
+/// true. Custom pipelines should then use this format in their
+/// `depth_stencil_state`.
+pub const DEPTH_FORMAT: wgpu::TextureFormat = wgpu::TextureFormat::Depth32Float;
+
+/// Format of the stencil attachment used for shaped clip regions
+///
+/// This is used only when no depth attachment is (see [`DrawPipe::uses_depth`]).
+pub(crate) const STENCIL_FORMAT: wgpu::TextureFormat = wgpu::TextureFormat::Depth24PlusStencil8;
+
+/// Stencil state which respects shaped clip regions
+///
+/// The stencil reference is set before each pass is rendered such that this
+/// test passes only within the region's clip shapes (if any).
+const CLIP_STENCIL_STATE: wgpu::StencilStateFaceDescriptor = wgpu::StencilStateFaceDescriptor {
+    compare: wgpu::CompareFunction::Equal,
+    fail_op: wgpu::StencilOperation::Keep,
+    depth_fail_op: wgpu::StencilOperation::Keep,
+    pass_op: wgpu::StencilOperation::Keep,
+};
+
+/// Depth-stencil state of built-in pipelines
+///
+/// Built-in pipes do not use depth, but must be compatible with the depth
+/// attachment when used. Otherwise, they use the stencil attachment to
+/// respect clip shapes.
+pub(crate) fn depth_stencil_state(depth: bool) -> wgpu::DepthStencilStateDescriptor {
+    if depth {
+        wgpu::DepthStencilStateDescriptor {
+            format: DEPTH_FORMAT,
+            depth_write_enabled: false,
+            depth_compare: wgpu::CompareFunction::Always,
+            stencil_front: wgpu::StencilStateFaceDescriptor::IGNORE,
+            stencil_back: wgpu::StencilStateFaceDescriptor::IGNORE,
+            stencil_read_mask: 0,
+            stencil_write_mask: 0,
+        }
+    } else {
+        wgpu::DepthStencilStateDescriptor {
+            format: STENCIL_FORMAT,
+            depth_write_enabled: false,
+            depth_compare: wgpu::CompareFunction::Always,
+            stencil_front: CLIP_STENCIL_STATE,
+            stencil_back: CLIP_STENCIL_STATE,
+            stencil_read_mask: !0,
+            stencil_write_mask: 0,
+        }
+    }
+}
 
 /// 3-part colour data
 #[repr(C)]
@@ -46,12 +142,165 @@ impl From<kas::draw::Colour> for Rgb {
     }
 }
 
+/// 4-part colour data
+#[repr(C)]
+#[derive(Clone, Copy, Debug)]
+pub(crate) struct Rgba {
+    pub r: f32,
+    pub g: f32,
+    pub b: f32,
+    pub a: f32,
+}
+
+impl From<kas::draw::Colour> for Rgba {
+    fn from(c: kas::draw::Colour) -> Self {
+        Rgba {
+            r: c.r,
+            g: c.g,
+            b: c.b,
+            a: c.a,
+        }
+    }
+}
+
+/// Statistics of a rendered frame
+///
+/// These are gathered only when enabled via [`crate::Options::render_stats`],
+/// and are available from [`crate::Toolkit::render_stats`] and
+/// [`crate::Embedded::render_stats`] after a frame is rendered.
+///
+/// Vertex counts are of the vertices drawn by each built-in pipe (three per
+/// triangle). All text is drawn by the glyph brush in a single render pass
+/// after all clip regions, thus only the number of text sections queued is
+/// counted.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct RenderStats {
+    /// Vertices drawn by the shaded-square pipe
+    pub shaded_square_vertices: u32,
+    /// Vertices drawn by the shaded-round pipe
+    pub shaded_round_vertices: u32,
+    /// Vertices drawn by the flat-round pipe
+    pub flat_round_vertices: u32,
+    /// Number of render passes in which the custom pipe drew anything
+    pub custom_passes: u32,
+    /// Number of clip regions (including the whole window)
+    pub clip_regions: u32,
+    /// Number of render passes used for clip regions
+    ///
+    /// Regions with nothing to draw are skipped, except the first.
+    pub render_passes: u32,
+    /// Number of text sections queued for the glyph brush
+    pub text_sections: u32,
+    /// True if the frame was a replay of the last frame
+    pub replayed: bool,
+}
+
+impl RenderStats {
+    /// Total vertices drawn by built-in pipes
+    pub fn vertices(&self) -> u32 {
+        self.shaded_square_vertices + self.shaded_round_vertices + self.flat_round_vertices
+    }
+
+    /// Count a clip region encoded in `passes` render passes
+    ///
+    /// `vertices` are those drawn by the shaded-square, shaded-round and
+    /// flat-round pipes respectively.
+    fn add_region(&mut self, vertices: (u32, u32, u32), custom: bool, passes: u32) {
+        self.shaded_square_vertices += vertices.0;
+        self.shaded_round_vertices += vertices.1;
+        self.flat_round_vertices += vertices.2;
+        self.custom_passes += custom as u32;
+        self.render_passes += passes;
+    }
+}
+
+/// Statistics of the frame being drawn and of the last rendered frame
+#[derive(Default)]
+struct FrameStats {
+    /// Statistics of the frame being drawn, if enabled
+    current: Option<RenderStats>,
+    last: RenderStats,
+}
+
+impl FrameStats {
+    fn new(enabled: bool) -> Self {
+        FrameStats {
+            current: if enabled {
+                Some(Default::default())
+            } else {
+                None
+            },
+            last: Default::default(),
+        }
+    }
+
+    /// Statistics of the frame being drawn, if enabled
+    fn current(&mut self) -> Option<&mut RenderStats> {
+        self.current.as_mut()
+    }
+
+    /// Finish the frame being drawn, which becomes the last frame
+    fn finish(&mut self, replayed: bool) {
+        if let Some(stats) = self.current.as_mut() {
+            stats.replayed = replayed;
+            self.last = std::mem::take(stats);
+        }
+    }
+
+    /// Statistics of the last rendered frame, if enabled
+    fn last(&self) -> Option<RenderStats> {
+        self.current.map(|_| self.last)
+    }
+}
+
+/// Clip regions and text of a rendered frame, retained for replay
+#[derive(Default)]
+struct LastFrame {
+    clip_regions: Vec<Rect>,
+    clip_shapes: Vec<Vec<ClipShape>>,
+    text: Vec<(Rect, String, TextProperties)>,
+}
+
 /// `kas-wgpu`'s implemention of [`kas::draw::Draw`] and friends
+///
+/// Each clip region is rendered in its own pass, restricted by a scissor rect.
+/// A region's background colour (see [`kas::draw::Draw::add_clip_region`]) is
+/// drawn as an opaque rect, the first primitive of the region's pass, rather
+/// than via [`wgpu::LoadOp::Clear`]: a load-op clear applies to the whole
+/// attachment regardless of the scissor rect, and would erase content drawn
+/// by earlier passes outside the region.
 pub struct DrawPipe<C> {
     clip_regions: Vec<Rect>,
+    /// Clip shapes (including inherited shapes) of each region
+    clip_shapes: Vec<Vec<ClipShape>>,
+    /// Stencil mask, present only if not [`DrawPipe::uses_depth`]
+    stencil: Option<StencilMask>,
     shaded_round: ShadedRound,
     shaded_square: ShadedSquare,
     custom: C,
     flat_round: FlatRound,
     glyph_brush: GlyphBrush<'static, ()>,
+    cache: cache::WidgetCache,
+    tex_format: wgpu::TextureFormat,
+    colour_filter: ColourFilter,
+    /// Post-process pass, only present when a colour filter is used
+    colour_pass: Option<filter::ColourPass>,
+    /// Emoji font and its identifier (added on first use)
+    emoji: Option<(Arc<EmojiFont>, Option<wgpu_glyph::FontId>)>,
+    /// True until ASCII glyphs have been queued for pre-caching
+    precache_ascii: bool,
+    /// Opacity multiplier; see [`kas::draw::Draw::set_opacity`]
+    opacity: f32,
+    /// Current transform and the stack of saved transforms; see
+    /// [`kas::draw::Draw::push_transform`]
+    transform: Affine,
+    transforms: Vec<Affine>,
+    /// Text drawn this frame (with opacity applied)
+    frame_text: Vec<(Rect, String, TextProperties)>,
+    /// The last rendered frame, if it may be replayed
+    last_frame: Option<LastFrame>,
+    pixel_snap: bool,
+    text_gamma: bool,
+    depth: bool,
+    stats: FrameStats,
 }
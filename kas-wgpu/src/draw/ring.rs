@@ -0,0 +1,169 @@
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License in the LICENSE-APACHE file or at:
+//     https://www.apache.org/licenses/LICENSE-2.0
+
+//! Ring of vertex staging buffers
+
+use log::error;
+use std::cell::Cell;
+use std::mem::size_of;
+use std::rc::Rc;
+
+/// Number of frames which may be in flight
+const FRAMES: usize = 3;
+
+/// Plain data, which may be uploaded as bytes
+///
+/// # Safety
+///
+/// Implementing types must have no padding bytes, e.g. `#[repr(C)]` structs
+/// with `f32` fields only.
+pub unsafe trait Pod: Copy {}
+
+/// A pair of staging and vertex buffers, used for one frame
+struct Slot {
+    staging: wgpu::Buffer,
+    vertices: wgpu::Buffer,
+    capacity: u64,
+}
+
+impl Slot {
+    fn new(device: &wgpu::Device, capacity: u64) -> Self {
+        let staging = device.create_buffer(&wgpu::BufferDescriptor {
+            size: capacity,
+            usage: wgpu::BufferUsage::MAP_WRITE | wgpu::BufferUsage::COPY_SRC,
+        });
+        let vertices = device.create_buffer(&wgpu::BufferDescriptor {
+            size: capacity,
+            usage: wgpu::BufferUsage::VERTEX | wgpu::BufferUsage::COPY_DST,
+        });
+        Slot {
+            staging,
+            vertices,
+            capacity,
+        }
+    }
+}
+
+/// Vertex upload via a ring of persistent staging buffers
+///
+/// Each frame, the vertices of all passes are written to the next slot of the
+/// ring and copied to that slot's vertex buffer, before any render pass
+/// starts. Buffers are only re-allocated when a frame needs more space.
+///
+/// A slot is re-used every `FRAMES` frames. Its staging buffer is mapped once
+/// the submission which last used it completes, thus usually the CPU may fill
+/// the next frame's vertices while the GPU consumes earlier frames. If that
+/// submission is still pending, `upload` blocks in `Device::poll(true)`, which
+/// waits for *all* submitted work to complete (not only that of the slot):
+/// when the CPU gets more than `FRAMES` frames ahead, the GPU is drained.
+///
+/// Custom pipes may use this for their own vertex uploads.
+#[derive(Default)]
+pub struct VertexRing {
+    slots: Vec<Slot>,
+    frame: usize,
+    /// For each pass, byte offset and vertex count
+    ranges: Vec<(u64, u32)>,
+}
+
+impl VertexRing {
+    /// Construct, without allocating buffers
+    pub fn new() -> Self {
+        VertexRing {
+            slots: vec![],
+            frame: 0,
+            ranges: vec![],
+        }
+    }
+
+    /// True if no vertices were uploaded for this `pass`
+    #[inline]
+    pub fn is_empty(&self, pass: usize) -> bool {
+        self.ranges.get(pass).map(|r| r.1 == 0).unwrap_or(true)
+    }
+
+    /// Upload vertices for each pass and clear `passes`
+    ///
+    /// Copy commands are recorded to `encoder`, which must be submitted before
+    /// (or as part of) the commands using [`VertexRing::set_buffer`].
+    pub fn upload<V: Pod>(
+        &mut self,
+        device: &wgpu::Device,
+        encoder: &mut wgpu::CommandEncoder,
+        passes: &mut [Vec<V>],
+    ) {
+        self.ranges.clear();
+        let len: usize = passes.iter().map(|v| v.len()).sum();
+        if len == 0 {
+            return;
+        }
+        let bytes = (len * size_of::<V>()) as u64;
+
+        let mut data = Vec::with_capacity(bytes as usize);
+        for v in passes.iter_mut() {
+            self.ranges.push((data.len() as u64, v.len() as u32));
+            // Safety: V: Pod has no padding, thus all bytes are initialised
+            let slice = unsafe {
+                std::slice::from_raw_parts(v.as_ptr() as *const u8, v.len() * size_of::<V>())
+            };
+            data.extend_from_slice(slice);
+            v.clear();
+        }
+
+        if self.slots.len() < FRAMES {
+            self.slots
+                .push(Slot::new(device, bytes.next_power_of_two()));
+            self.frame = self.slots.len() - 1;
+        } else {
+            self.frame = (self.frame + 1) % FRAMES;
+            if self.slots[self.frame].capacity < bytes {
+                self.slots[self.frame] = Slot::new(device, bytes.next_power_of_two());
+            }
+        }
+        let slot = &self.slots[self.frame];
+
+        let done = Rc::new(Cell::new(false));
+        let done2 = done.clone();
+        slot.staging.map_write_async(
+            0,
+            bytes,
+            move |result: wgpu::BufferMapAsyncResult<&mut [u8]>| {
+                if let Ok(mapping) = result {
+                    mapping.data.copy_from_slice(&data);
+                    done2.set(true);
+                }
+            },
+        );
+        device.poll(false);
+        if !done.get() {
+            // The GPU is still using this slot from FRAMES frames ago. This
+            // blocks until the device is idle, not only until the slot is free.
+            device.poll(true);
+        }
+        if !done.get() {
+            error!("VertexRing::upload: failed to map staging buffer");
+            self.ranges.clear();
+            return;
+        }
+        // The buffer must be unmapped before the GPU may copy from it
+        slot.staging.unmap();
+
+        encoder.copy_buffer_to_buffer(&slot.staging, 0, &slot.vertices, 0, bytes);
+    }
+
+    /// Bind the vertex buffer for `pass` and return the number of vertices
+    ///
+    /// Returns zero (without binding) if there is nothing to draw.
+    pub fn set_buffer(&self, pass: usize, rpass: &mut wgpu::RenderPass) -> u32 {
+        match self.ranges.get(pass) {
+            Some((offset, count)) if *count > 0 => {
+                let buffer = &self.slots[self.frame].vertices;
+                rpass.set_vertex_buffers(0, &[(buffer, *offset)]);
+                *count
+            }
+            _ => 0,
+        }
+    }
+}
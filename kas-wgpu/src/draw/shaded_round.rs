@@ -8,10 +8,11 @@
 use std::f32::consts::FRAC_PI_2;
 use std::mem::size_of;
 
-use crate::draw::{Rgb, Vec2};
+use crate::draw::ring::{Pod, VertexRing};
+use crate::draw::{ndc_scale, Rgba, Vec2};
 use crate::shared::SharedState;
 use kas::draw::Colour;
-use kas::geom::{Rect, Size};
+use kas::geom::{Affine, Rect, Size};
 
 /// Offset relative to the size of a pixel used by the fragment shader to
 /// implement multi-sampling.
@@ -19,7 +20,13 @@ const OFFSET: f32 = 0.125;
 
 #[repr(C)]
 #[derive(Clone, Copy, Debug)]
-struct Vertex(Vec2, Rgb, Vec2, Vec2, Vec2);
+struct Vertex(Vec2, Rgba, Vec2, Vec2, Vec2);
+
+unsafe impl Pod for Vertex {}
+
+/// Vertices recorded for replay
+#[derive(Clone, Debug, Default)]
+pub struct Vertices(Vec<Vertex>);
 
 /// A pipeline for rendering rounded shapes
 pub struct ShadedRound {
@@ -27,15 +34,23 @@ pub struct ShadedRound {
     scale_buf: wgpu::Buffer,
     render_pipeline: wgpu::RenderPipeline,
     passes: Vec<Vec<Vertex>>,
+    ring: VertexRing,
+    transform: Option<Affine>,
 }
 
 impl ShadedRound {
     /// Construct
-    pub fn new<C, T>(shared: &SharedState<C, T>, size: Size, light_norm: [f32; 3]) -> Self {
+    pub fn new<C, T>(
+        shared: &SharedState<C, T>,
+        tex_format: wgpu::TextureFormat,
+        size: Size,
+        light_norm: [f32; 3],
+        depth: bool,
+    ) -> Self {
         let device = &shared.device;
 
         type Scale = [f32; 2];
-        let scale_factor: Scale = [2.0 / size.0 as f32, 2.0 / size.1 as f32];
+        let scale_factor: Scale = ndc_scale(size);
         let scale_buf = device
             .create_buffer_mapped(
                 scale_factor.len(),
@@ -90,7 +105,7 @@ impl ShadedRound {
         let render_pipeline = device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
             layout: &pipeline_layout,
             vertex_stage: wgpu::ProgrammableStageDescriptor {
-                module: &shared.shaders.vert_3222,
+                module: &shared.shaders.vert_4222,
                 entry_point: "main",
             },
             fragment_stage: Some(wgpu::ProgrammableStageDescriptor {
@@ -106,7 +121,7 @@ impl ShadedRound {
             }),
             primitive_topology: wgpu::PrimitiveTopology::TriangleList,
             color_states: &[wgpu::ColorStateDescriptor {
-                format: wgpu::TextureFormat::Bgra8UnormSrgb,
+                format: tex_format,
                 color_blend: wgpu::BlendDescriptor {
                     src_factor: wgpu::BlendFactor::SrcAlpha,
                     dst_factor: wgpu::BlendFactor::OneMinusSrcAlpha,
@@ -119,7 +134,7 @@ impl ShadedRound {
                 },
                 write_mask: wgpu::ColorWrite::ALL,
             }],
-            depth_stencil_state: None,
+            depth_stencil_state: Some(super::depth_stencil_state(depth)),
             index_format: wgpu::IndexFormat::Uint16,
             vertex_buffers: &[wgpu::VertexBufferDescriptor {
                 stride: size_of::<Vertex>() as wgpu::BufferAddress,
@@ -131,23 +146,23 @@ impl ShadedRound {
                         shader_location: 0,
                     },
                     wgpu::VertexAttributeDescriptor {
-                        format: wgpu::VertexFormat::Float3,
+                        format: wgpu::VertexFormat::Float4,
                         offset: size_of::<Vec2>() as u64,
                         shader_location: 1,
                     },
                     wgpu::VertexAttributeDescriptor {
                         format: wgpu::VertexFormat::Float2,
-                        offset: (size_of::<Vec2>() + size_of::<Rgb>()) as u64,
+                        offset: (size_of::<Vec2>() + size_of::<Rgba>()) as u64,
                         shader_location: 2,
                     },
                     wgpu::VertexAttributeDescriptor {
                         format: wgpu::VertexFormat::Float2,
-                        offset: (2 * size_of::<Vec2>() + size_of::<Rgb>()) as u64,
+                        offset: (2 * size_of::<Vec2>() + size_of::<Rgba>()) as u64,
                         shader_location: 3,
                     },
                     wgpu::VertexAttributeDescriptor {
                         format: wgpu::VertexFormat::Float2,
-                        offset: (3 * size_of::<Vec2>() + size_of::<Rgb>()) as u64,
+                        offset: (3 * size_of::<Vec2>() + size_of::<Rgba>()) as u64,
                         shader_location: 4,
                     },
                 ],
@@ -162,6 +177,8 @@ impl ShadedRound {
             scale_buf,
             render_pipeline,
             passes: vec![],
+            ring: VertexRing::new(),
+            transform: None,
         }
     }
 
@@ -172,7 +189,7 @@ impl ShadedRound {
         size: Size,
     ) {
         type Scale = [f32; 2];
-        let scale_factor: Scale = [2.0 / size.0 as f32, 2.0 / size.1 as f32];
+        let scale_factor: Scale = ndc_scale(size);
         let scale_buf = device
             .create_buffer_mapped(scale_factor.len(), wgpu::BufferUsage::COPY_SRC)
             .fill_from_slice(&scale_factor);
@@ -181,23 +198,60 @@ impl ShadedRound {
         encoder.copy_buffer_to_buffer(&scale_buf, 0, &self.scale_buf, 0, byte_len);
     }
 
-    /// Render queued triangles and clear the queue
-    pub fn render(&mut self, device: &wgpu::Device, pass: usize, rpass: &mut wgpu::RenderPass) {
-        if pass >= self.passes.len() {
-            return;
-        }
-        let v = &mut self.passes[pass];
-        let buffer = device
-            .create_buffer_mapped(v.len(), wgpu::BufferUsage::VERTEX)
-            .fill_from_slice(&v);
-        let count = v.len() as u32;
+    /// Upload queued triangles for all passes and clear the queue
+    ///
+    /// This must be called before starting any render pass of the frame.
+    pub fn upload(&mut self, device: &wgpu::Device, encoder: &mut wgpu::CommandEncoder) {
+        self.ring.upload(device, encoder, &mut self.passes);
+    }
+
+    /// Number of vertices queued for `pass`
+    pub fn pass_len(&self, pass: usize) -> usize {
+        self.passes.get(pass).map(|v| v.len()).unwrap_or(0)
+    }
+
+    /// Copy vertices queued for `pass`, starting from index `start`
+    pub fn record(&self, pass: usize, start: usize) -> Vertices {
+        Vertices(
+            self.passes
+                .get(pass)
+                .map(|v| v[start..].to_vec())
+                .unwrap_or_default(),
+        )
+    }
 
+    /// Queue recorded vertices for `pass`
+    pub fn replay(&mut self, pass: usize, vertices: &Vertices) {
+        // Recorded vertices are already transformed
+        let transform = self.transform.take();
+        self.add_vertices(pass, &vertices.0);
+        self.transform = transform;
+    }
+
+    /// Set the transform applied to subsequently queued vertices
+    pub fn set_transform(&mut self, transform: Affine) {
+        self.transform = match transform == Affine::IDENTITY {
+            true => None,
+            false => Some(transform),
+        };
+    }
+
+    /// True if no triangles were uploaded for this `pass`
+    #[inline]
+    pub fn is_empty(&self, pass: usize) -> bool {
+        self.ring.is_empty(pass)
+    }
+
+    /// Render uploaded triangles, returning the number of vertices drawn
+    pub fn render(&mut self, pass: usize, rpass: &mut wgpu::RenderPass) -> u32 {
+        if self.is_empty(pass) {
+            return 0;
+        }
         rpass.set_pipeline(&self.render_pipeline);
         rpass.set_bind_group(0, &self.bind_group, &[]);
-        rpass.set_vertex_buffers(0, &[(&buffer, 0)]);
+        let count = self.ring.set_buffer(pass, rpass);
         rpass.draw(0..count, 0..1);
-
-        v.clear();
+        count
     }
 
     /// Bounds on input: `0 ≤ inner_radius ≤ 1`.
@@ -351,6 +405,16 @@ impl ShadedRound {
             self.passes.resize(pass + 8, vec![]);
         }
 
-        self.passes[pass].extend_from_slice(slice);
+        match self.transform {
+            None => self.passes[pass].extend_from_slice(slice),
+            Some(t) => {
+                // Multi-sample offsets are relative to the size of a pixel
+                let s = Vec2(t.x.length(), t.y.length());
+                let iter = slice
+                    .iter()
+                    .map(|v| Vertex(t.apply(v.0), v.1, v.2, v.3, v.4 / s));
+                self.passes[pass].extend(iter);
+            }
+        }
     }
 }
@@ -8,14 +8,21 @@
 use std::f32;
 use std::mem::size_of;
 
-use crate::draw::{Rgb, Vec2};
+use crate::draw::ring::{Pod, VertexRing};
+use crate::draw::{ndc_scale, Rgba, Vec2};
 use crate::shared::SharedState;
 use kas::draw::Colour;
-use kas::geom::{Rect, Size};
+use kas::geom::{Affine, Coord, Rect, Size};
 
 #[repr(C)]
 #[derive(Clone, Copy, Debug)]
-struct Vertex(Vec2, Rgb, Vec2);
+struct Vertex(Vec2, Rgba, Vec2);
+
+unsafe impl Pod for Vertex {}
+
+/// Vertices recorded for replay
+#[derive(Clone, Debug, Default)]
+pub struct Vertices(Vec<Vertex>);
 
 /// A pipeline for rendering with flat and square-corner shading
 pub struct ShadedSquare {
@@ -23,14 +30,22 @@ pub struct ShadedSquare {
     scale_buf: wgpu::Buffer,
     render_pipeline: wgpu::RenderPipeline,
     passes: Vec<Vec<Vertex>>,
+    ring: VertexRing,
+    transform: Option<Affine>,
 }
 
 impl ShadedSquare {
     /// Construct
-    pub fn new<C, T>(shared: &SharedState<C, T>, size: Size, light_norm: [f32; 3]) -> Self {
+    pub fn new<C, T>(
+        shared: &SharedState<C, T>,
+        tex_format: wgpu::TextureFormat,
+        size: Size,
+        light_norm: [f32; 3],
+        depth: bool,
+    ) -> Self {
         let device = &shared.device;
         type Scale = [f32; 2];
-        let scale_factor: Scale = [2.0 / size.0 as f32, 2.0 / size.1 as f32];
+        let scale_factor: Scale = ndc_scale(size);
         let scale_buf = device
             .create_buffer_mapped(
                 scale_factor.len(),
@@ -85,7 +100,7 @@ impl ShadedSquare {
         let render_pipeline = device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
             layout: &pipeline_layout,
             vertex_stage: wgpu::ProgrammableStageDescriptor {
-                module: &shared.shaders.vert_32,
+                module: &shared.shaders.vert_42,
                 entry_point: "main",
             },
             fragment_stage: Some(wgpu::ProgrammableStageDescriptor {
@@ -101,12 +116,20 @@ impl ShadedSquare {
             }),
             primitive_topology: wgpu::PrimitiveTopology::TriangleList,
             color_states: &[wgpu::ColorStateDescriptor {
-                format: wgpu::TextureFormat::Bgra8UnormSrgb,
-                color_blend: wgpu::BlendDescriptor::REPLACE,
-                alpha_blend: wgpu::BlendDescriptor::REPLACE,
+                format: tex_format,
+                color_blend: wgpu::BlendDescriptor {
+                    src_factor: wgpu::BlendFactor::SrcAlpha,
+                    dst_factor: wgpu::BlendFactor::OneMinusSrcAlpha,
+                    operation: wgpu::BlendOperation::Add,
+                },
+                alpha_blend: wgpu::BlendDescriptor {
+                    src_factor: wgpu::BlendFactor::One,
+                    dst_factor: wgpu::BlendFactor::OneMinusSrcAlpha,
+                    operation: wgpu::BlendOperation::Add,
+                },
                 write_mask: wgpu::ColorWrite::ALL,
             }],
-            depth_stencil_state: None,
+            depth_stencil_state: Some(super::depth_stencil_state(depth)),
             index_format: wgpu::IndexFormat::Uint16,
             vertex_buffers: &[wgpu::VertexBufferDescriptor {
                 stride: size_of::<Vertex>() as wgpu::BufferAddress,
@@ -118,13 +141,13 @@ impl ShadedSquare {
                         shader_location: 0,
                     },
                     wgpu::VertexAttributeDescriptor {
-                        format: wgpu::VertexFormat::Float3,
+                        format: wgpu::VertexFormat::Float4,
                         offset: size_of::<Vec2>() as u64,
                         shader_location: 1,
                     },
                     wgpu::VertexAttributeDescriptor {
                         format: wgpu::VertexFormat::Float2,
-                        offset: (size_of::<Vec2>() + size_of::<Rgb>()) as u64,
+                        offset: (size_of::<Vec2>() + size_of::<Rgba>()) as u64,
                         shader_location: 2,
                     },
                 ],
@@ -139,6 +162,8 @@ impl ShadedSquare {
             scale_buf,
             render_pipeline,
             passes: vec![],
+            ring: VertexRing::new(),
+            transform: None,
         }
     }
 
@@ -149,7 +174,7 @@ impl ShadedSquare {
         size: Size,
     ) {
         type Scale = [f32; 2];
-        let scale_factor: Scale = [2.0 / size.0 as f32, 2.0 / size.1 as f32];
+        let scale_factor: Scale = ndc_scale(size);
         let scale_buf = device
             .create_buffer_mapped(scale_factor.len(), wgpu::BufferUsage::COPY_SRC)
             .fill_from_slice(&scale_factor);
@@ -158,23 +183,60 @@ impl ShadedSquare {
         encoder.copy_buffer_to_buffer(&scale_buf, 0, &self.scale_buf, 0, byte_len);
     }
 
-    /// Render queued triangles and clear the queue
-    pub fn render(&mut self, device: &wgpu::Device, pass: usize, rpass: &mut wgpu::RenderPass) {
-        if pass >= self.passes.len() {
-            return;
-        }
-        let v = &mut self.passes[pass];
-        let buffer = device
-            .create_buffer_mapped(v.len(), wgpu::BufferUsage::VERTEX)
-            .fill_from_slice(&v);
-        let count = v.len() as u32;
+    /// Upload queued triangles for all passes and clear the queue
+    ///
+    /// This must be called before starting any render pass of the frame.
+    pub fn upload(&mut self, device: &wgpu::Device, encoder: &mut wgpu::CommandEncoder) {
+        self.ring.upload(device, encoder, &mut self.passes);
+    }
+
+    /// Number of vertices queued for `pass`
+    pub fn pass_len(&self, pass: usize) -> usize {
+        self.passes.get(pass).map(|v| v.len()).unwrap_or(0)
+    }
+
+    /// Copy vertices queued for `pass`, starting from index `start`
+    pub fn record(&self, pass: usize, start: usize) -> Vertices {
+        Vertices(
+            self.passes
+                .get(pass)
+                .map(|v| v[start..].to_vec())
+                .unwrap_or_default(),
+        )
+    }
+
+    /// Queue recorded vertices for `pass`
+    pub fn replay(&mut self, pass: usize, vertices: &Vertices) {
+        // Recorded vertices are already transformed
+        let transform = self.transform.take();
+        self.add_vertices(pass, &vertices.0);
+        self.transform = transform;
+    }
+
+    /// Set the transform applied to subsequently queued vertices
+    pub fn set_transform(&mut self, transform: Affine) {
+        self.transform = match transform == Affine::IDENTITY {
+            true => None,
+            false => Some(transform),
+        };
+    }
+
+    /// True if no triangles were uploaded for this `pass`
+    #[inline]
+    pub fn is_empty(&self, pass: usize) -> bool {
+        self.ring.is_empty(pass)
+    }
 
+    /// Render uploaded triangles, returning the number of vertices drawn
+    pub fn render(&mut self, pass: usize, rpass: &mut wgpu::RenderPass) -> u32 {
+        if self.is_empty(pass) {
+            return 0;
+        }
         rpass.set_pipeline(&self.render_pipeline);
         rpass.set_bind_group(0, &self.bind_group, &[]);
-        rpass.set_vertex_buffers(0, &[(&buffer, 0)]);
+        let count = self.ring.set_buffer(pass, rpass);
         rpass.draw(0..count, 0..1);
-
-        v.clear();
+        count
     }
 
     /// Add a rectangle to the buffer
@@ -201,6 +263,43 @@ impl ShadedSquare {
         ]);
     }
 
+    /// Add a hairline (one pixel wide) to the buffer
+    ///
+    /// Coordinates are of pixels, hence axis-aligned lines cover exactly the
+    /// pixels from `p1` to `p2` (inclusive).
+    pub fn hairline(&mut self, pass: usize, p1: Coord, p2: Coord, col: Colour) {
+        if p1.0 == p2.0 || p1.1 == p2.1 {
+            let (aa, bb) = (p1.min(p2), p1.max(p2));
+            let rect = Rect {
+                pos: aa,
+                size: Size((bb.0 - aa.0 + 1) as u32, (bb.1 - aa.1 + 1) as u32),
+            };
+            self.rect(pass, rect, col);
+            return;
+        }
+
+        // Diagonal: a quad one pixel wide between pixel centres, extended by
+        // half a pixel at each end
+        let c1 = Vec2::from(p1) + 0.5;
+        let c2 = Vec2::from(p2) + 0.5;
+        let vx = (c2 - c1).normalize() * 0.5;
+        let vy = Vec2(-vx.1, vx.0);
+        let (c1, c2) = (c1 - vx, c2 + vx);
+
+        let col = col.into();
+        let t = Vec2(0.0, 0.0);
+        let aa = Vertex(c1 - vy, col, t);
+        let ab = Vertex(c1 + vy, col, t);
+        let ba = Vertex(c2 - vy, col, t);
+        let bb = Vertex(c2 + vy, col, t);
+
+        #[rustfmt::skip]
+        self.add_vertices(pass, &[
+            aa, ba, ab,
+            ab, ba, bb,
+        ]);
+    }
+
     /// Add a rect to the buffer, defined by two outer corners, `aa` and `bb`.
     ///
     /// Bounds on input: `aa < cc` and `-1 ≤ norm ≤ 1`.
@@ -309,6 +408,12 @@ impl ShadedSquare {
             self.passes.resize(pass + 8, vec![]);
         }
 
-        self.passes[pass].extend_from_slice(slice);
+        match self.transform {
+            None => self.passes[pass].extend_from_slice(slice),
+            Some(t) => {
+                let iter = slice.iter().map(|v| Vertex(t.apply(v.0), v.1, v.2));
+                self.passes[pass].extend(iter);
+            }
+        }
     }
 }
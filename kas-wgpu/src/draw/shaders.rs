@@ -5,72 +5,217 @@
 
 //! Shader management
 
+use shaderc::Compiler;
 use shaderc::ShaderKind::{Fragment, Vertex};
-use shaderc::{Compiler, Error};
+use std::cell::RefCell;
 use wgpu::ShaderModule;
 
+use crate::Error;
+#[cfg(feature = "shader_reload")]
+use crate::ProxyAction;
+#[cfg(feature = "shader_reload")]
+use log::{info, warn};
+#[cfg(feature = "shader_reload")]
+use std::path::Path;
+#[cfg(feature = "shader_reload")]
+use std::time::{Duration, SystemTime};
+#[cfg(feature = "shader_reload")]
+use winit::event_loop::EventLoopProxy;
+
+/// Location of built-in shader sources
+#[cfg(feature = "shader_reload")]
+const SHADER_DIR: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/src/draw/shaders");
+
+pub use shaderc::ShaderKind;
+
 /// Shader manager
 ///
 /// For now, we embed the shader source into the binary and compile on start.
 /// Not really optimal (we could embed SPIR-V directly or load shaders from
 /// external resources), but simple to set up and use.
+///
+/// Custom pipes may compile their own shaders via [`ShaderManager::compile`].
 pub struct ShaderManager {
-    pub vert_3122: ShaderModule,
-    pub vert_32: ShaderModule,
-    pub vert_322: ShaderModule,
-    pub vert_3222: ShaderModule,
-    pub frag_flat_round: ShaderModule,
-    pub frag_shaded_square: ShaderModule,
-    pub frag_shaded_round: ShaderModule,
+    compiler: RefCell<Compiler>,
+    pub(crate) vert_4122: ShaderModule,
+    pub(crate) vert_32: ShaderModule,
+    pub(crate) vert_42: ShaderModule,
+    pub(crate) vert_4222: ShaderModule,
+    pub(crate) frag_flat_round: ShaderModule,
+    pub(crate) frag_shaded_square: ShaderModule,
+    pub(crate) frag_shaded_round: ShaderModule,
+    pub(crate) frag_stencil_mask: ShaderModule,
+    pub(crate) vert_fullscreen: ShaderModule,
+    pub(crate) frag_colour_matrix: ShaderModule,
 }
 
 impl ShaderManager {
-    pub fn new(device: &wgpu::Device) -> Result<Self, Error> {
+    pub(crate) fn new(device: &wgpu::Device) -> Result<Self, Error> {
         let mut compiler = Compiler::new().unwrap();
+        let mut compile = |source, kind, fname| -> Result<ShaderModule, Error> {
+            let artifact = compiler.compile_into_spirv(source, kind, fname, "main", None)?;
+            Ok(device.create_shader_module(artifact.as_binary()))
+        };
 
-        let fname = "shaders/scaled3122.vert";
-        let source = include_str!("shaders/scaled3122.vert");
-        let artifact = compiler.compile_into_spirv(source, Vertex, fname, "main", None)?;
-        let vert_3122 = device.create_shader_module(&artifact.as_binary());
+        let fname = "shaders/scaled4122.vert";
+        let source = include_str!("shaders/scaled4122.vert");
+        let vert_4122 = compile(source, Vertex, fname)?;
 
         let fname = "shaders/scaled32.vert";
         let source = include_str!("shaders/scaled32.vert");
-        let artifact = compiler.compile_into_spirv(source, Vertex, fname, "main", None)?;
-        let vert_32 = device.create_shader_module(&artifact.as_binary());
+        let vert_32 = compile(source, Vertex, fname)?;
 
-        let fname = "shaders/scaled322.vert";
-        let source = include_str!("shaders/scaled322.vert");
-        let artifact = compiler.compile_into_spirv(source, Vertex, fname, "main", None)?;
-        let vert_322 = device.create_shader_module(&artifact.as_binary());
+        let fname = "shaders/scaled42.vert";
+        let source = include_str!("shaders/scaled42.vert");
+        let vert_42 = compile(source, Vertex, fname)?;
 
-        let fname = "shaders/scaled3222.vert";
-        let source = include_str!("shaders/scaled3222.vert");
-        let artifact = compiler.compile_into_spirv(source, Vertex, fname, "main", None)?;
-        let vert_3222 = device.create_shader_module(&artifact.as_binary());
+        let fname = "shaders/scaled4222.vert";
+        let source = include_str!("shaders/scaled4222.vert");
+        let vert_4222 = compile(source, Vertex, fname)?;
 
         let fname = "shaders/flat_round.frag";
         let source = include_str!("shaders/flat_round.frag");
-        let artifact = compiler.compile_into_spirv(source, Fragment, fname, "main", None)?;
-        let frag_flat_round = device.create_shader_module(&artifact.as_binary());
+        let frag_flat_round = compile(source, Fragment, fname)?;
 
         let fname = "shaders/shaded_square.frag";
         let source = include_str!("shaders/shaded_square.frag");
-        let artifact = compiler.compile_into_spirv(source, Fragment, fname, "main", None)?;
-        let frag_shaded_square = device.create_shader_module(&artifact.as_binary());
+        let frag_shaded_square = compile(source, Fragment, fname)?;
 
         let fname = "shaders/shaded_round.frag";
         let source = include_str!("shaders/shaded_round.frag");
-        let artifact = compiler.compile_into_spirv(source, Fragment, fname, "main", None)?;
-        let frag_shaded_round = device.create_shader_module(&artifact.as_binary());
+        let frag_shaded_round = compile(source, Fragment, fname)?;
+
+        let fname = "shaders/stencil_mask.frag";
+        let source = include_str!("shaders/stencil_mask.frag");
+        let frag_stencil_mask = compile(source, Fragment, fname)?;
+
+        let fname = "shaders/fullscreen.vert";
+        let source = include_str!("shaders/fullscreen.vert");
+        let vert_fullscreen = compile(source, Vertex, fname)?;
+
+        let fname = "shaders/colour_matrix.frag";
+        let source = include_str!("shaders/colour_matrix.frag");
+        let frag_colour_matrix = compile(source, Fragment, fname)?;
 
         Ok(ShaderManager {
-            vert_3122,
+            compiler: RefCell::new(compiler),
+            vert_4122,
             vert_32,
-            vert_322,
-            vert_3222,
+            vert_42,
+            vert_4222,
             frag_flat_round,
             frag_shaded_square,
             frag_shaded_round,
+            frag_stencil_mask,
+            vert_fullscreen,
+            frag_colour_matrix,
         })
     }
+
+    /// Compile a GLSL shader
+    ///
+    /// The entry point must be `main`. The `name` (e.g. a file name) is used
+    /// to identify the source in error messages, which include line numbers.
+    pub fn compile(
+        &self,
+        device: &wgpu::Device,
+        source: &str,
+        kind: ShaderKind,
+        name: &str,
+    ) -> Result<ShaderModule, Error> {
+        let mut compiler = self.compiler.borrow_mut();
+        let artifact = compiler.compile_into_spirv(source, kind, name, "main", None)?;
+        Ok(device.create_shader_module(artifact.as_binary()))
+    }
+
+    /// Reload built-in shaders from [`SHADER_DIR`]
+    ///
+    /// All shaders are compiled before any module is replaced: if any shader
+    /// fails to load or compile, errors are logged and all previous modules
+    /// kept, thus pipelines never mix old and new shaders. Returns true if the
+    /// modules were replaced.
+    #[cfg(feature = "shader_reload")]
+    pub(crate) fn reload(&mut self, device: &wgpu::Device) -> bool {
+        let compiler = self.compiler.get_mut();
+        let mut failed = false;
+        let mut compile = |fname: &str, kind| {
+            let path = Path::new(SHADER_DIR).join(fname);
+            let result = std::fs::read_to_string(&path)
+                .map_err(|e| e.to_string())
+                .and_then(|source| {
+                    compiler
+                        .compile_into_spirv(&source, kind, fname, "main", None)
+                        .map_err(|e| e.to_string())
+                });
+            match result {
+                Ok(artifact) => Some(device.create_shader_module(&artifact.as_binary())),
+                Err(e) => {
+                    warn!("Failed to reload shader {}: {}", fname, e);
+                    failed = true;
+                    None
+                }
+            }
+        };
+
+        let modules = vec![
+            compile("scaled4122.vert", Vertex),
+            compile("scaled32.vert", Vertex),
+            compile("scaled42.vert", Vertex),
+            compile("scaled4222.vert", Vertex),
+            compile("flat_round.frag", Fragment),
+            compile("shaded_square.frag", Fragment),
+            compile("shaded_round.frag", Fragment),
+            compile("stencil_mask.frag", Fragment),
+            compile("fullscreen.vert", Vertex),
+            compile("colour_matrix.frag", Fragment),
+        ];
+        if failed {
+            warn!("Keeping previous shaders");
+            return false;
+        }
+
+        let mut modules = modules.into_iter().map(Option::unwrap);
+        let mut next = || modules.next().unwrap();
+        self.vert_4122 = next();
+        self.vert_32 = next();
+        self.vert_42 = next();
+        self.vert_4222 = next();
+        self.frag_flat_round = next();
+        self.frag_shaded_square = next();
+        self.frag_shaded_round = next();
+        self.frag_stencil_mask = next();
+        self.vert_fullscreen = next();
+        self.frag_colour_matrix = next();
+        true
+    }
+}
+
+/// Watch [`SHADER_DIR`] for changes
+///
+/// This spawns a thread which polls modification times, sending
+/// [`ProxyAction::ReloadShaders`] on change. The thread exits with the event
+/// loop.
+#[cfg(feature = "shader_reload")]
+pub(crate) fn spawn_watcher(proxy: EventLoopProxy<ProxyAction>) {
+    fn last_modified() -> Option<SystemTime> {
+        std::fs::read_dir(SHADER_DIR)
+            .ok()?
+            .filter_map(|entry| entry.ok()?.metadata().ok()?.modified().ok())
+            .max()
+    }
+
+    info!("Watching for shader changes in {}", SHADER_DIR);
+    std::thread::spawn(move || {
+        let mut last = last_modified();
+        loop {
+            std::thread::sleep(Duration::from_millis(500));
+            let modified = last_modified();
+            if modified != last {
+                last = modified;
+                if proxy.send_event(ProxyAction::ReloadShaders).is_err() {
+                    break;
+                }
+            }
+        }
+    });
 }
@@ -0,0 +1,26 @@
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License in the LICENSE-APACHE file or at:
+//     https://www.apache.org/licenses/LICENSE-2.0
+
+#version 450
+#extension GL_ARB_separate_shader_objects : enable
+
+precision mediump float;
+
+layout(location = 0) in vec2 b_uv;
+
+layout(location = 0) out vec4 outColor;
+
+layout(set = 0, binding = 0) uniform Locals {
+    // Rows of a 3x3 matrix applied to linear RGB (w is unused)
+    vec4 rows[3];
+};
+layout(set = 0, binding = 1) uniform texture2D t_frame;
+layout(set = 0, binding = 2) uniform sampler s_frame;
+
+void main() {
+    vec4 c = texture(sampler2D(t_frame, s_frame), b_uv);
+    vec3 rgb = vec3(dot(rows[0].rgb, c.rgb), dot(rows[1].rgb, c.rgb), dot(rows[2].rgb, c.rgb));
+    outColor = vec4(clamp(rgb, 0.0, 1.0), c.a);
+}
@@ -8,7 +8,7 @@
 
 precision mediump float;
 
-layout(location = 0) in vec3 fragColor;
+layout(location = 0) in vec4 fragColor;
 layout(location = 1) in vec2 norm2;
 
 layout(location = 0) out vec4 outColor;
@@ -21,6 +21,6 @@ layout(set = 0, binding = 1) uniform Locals {
 void main() {
     float n3 = 1.0 - sqrt(norm2.x * norm2.x + norm2.y * norm2.y);
     vec3 norm = vec3(norm2, n3);
-    vec3 c = fragColor * max(dot(norm, lightNorm), 0);
-    outColor = vec4(c, 1.0);
+    vec3 c = fragColor.rgb * max(dot(norm, lightNorm), 0);
+    outColor = vec4(c, fragColor.a);
 }
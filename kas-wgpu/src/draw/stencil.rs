@@ -0,0 +1,350 @@
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License in the LICENSE-APACHE file or at:
+//     https://www.apache.org/licenses/LICENSE-2.0
+
+//! Stencil mask pipeline (shaped clip regions)
+
+use std::mem::size_of;
+
+use crate::draw::{ndc_scale, Rgb, Vec2, STENCIL_FORMAT};
+use crate::shared::SharedState;
+use crate::window::create_depth_texture;
+use kas::draw::ClipShape;
+use kas::geom::{Rect, Size};
+
+#[repr(C)]
+#[derive(Clone, Copy, Debug)]
+struct Vertex(Vec2, Rgb, Vec2);
+
+/// A pipeline for writing clip shapes to the stencil buffer
+///
+/// Nothing is written to the colour target. This owns the stencil texture,
+/// which is sized to the window.
+pub struct StencilMask {
+    texture: (wgpu::Texture, wgpu::TextureView),
+    bind_group: wgpu::BindGroup,
+    scale_buf: wgpu::Buffer,
+    reset_pipeline: wgpu::RenderPipeline,
+    mask_pipeline: wgpu::RenderPipeline,
+}
+
+impl StencilMask {
+    /// Construct
+    pub fn new<C, T>(
+        shared: &SharedState<C, T>,
+        tex_format: wgpu::TextureFormat,
+        size: Size,
+    ) -> Self {
+        let device = &shared.device;
+
+        type Scale = [f32; 2];
+        let scale_factor: Scale = ndc_scale(size);
+        let scale_buf = device
+            .create_buffer_mapped(
+                scale_factor.len(),
+                wgpu::BufferUsage::UNIFORM | wgpu::BufferUsage::COPY_DST,
+            )
+            .fill_from_slice(&scale_factor);
+
+        let bind_group_layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            bindings: &[wgpu::BindGroupLayoutBinding {
+                binding: 0,
+                visibility: wgpu::ShaderStage::VERTEX,
+                ty: wgpu::BindingType::UniformBuffer { dynamic: false },
+            }],
+        });
+        let bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+            layout: &bind_group_layout,
+            bindings: &[wgpu::Binding {
+                binding: 0,
+                resource: wgpu::BindingResource::Buffer {
+                    buffer: &scale_buf,
+                    range: 0..(size_of::<Scale>() as u64),
+                },
+            }],
+        });
+        let pipeline_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+            bind_group_layouts: &[&bind_group_layout],
+        });
+
+        let create_pipeline = |compare, pass_op| {
+            let stencil = wgpu::StencilStateFaceDescriptor {
+                compare,
+                fail_op: wgpu::StencilOperation::Keep,
+                depth_fail_op: wgpu::StencilOperation::Keep,
+                pass_op,
+            };
+            device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+                layout: &pipeline_layout,
+                vertex_stage: wgpu::ProgrammableStageDescriptor {
+                    module: &shared.shaders.vert_32,
+                    entry_point: "main",
+                },
+                fragment_stage: Some(wgpu::ProgrammableStageDescriptor {
+                    module: &shared.shaders.frag_stencil_mask,
+                    entry_point: "main",
+                }),
+                rasterization_state: Some(wgpu::RasterizationStateDescriptor {
+                    front_face: wgpu::FrontFace::Ccw,
+                    cull_mode: wgpu::CullMode::None,
+                    depth_bias: 0,
+                    depth_bias_slope_scale: 0.0,
+                    depth_bias_clamp: 0.0,
+                }),
+                primitive_topology: wgpu::PrimitiveTopology::TriangleList,
+                color_states: &[wgpu::ColorStateDescriptor {
+                    format: tex_format,
+                    color_blend: wgpu::BlendDescriptor::REPLACE,
+                    alpha_blend: wgpu::BlendDescriptor::REPLACE,
+                    write_mask: wgpu::ColorWrite::empty(),
+                }],
+                depth_stencil_state: Some(wgpu::DepthStencilStateDescriptor {
+                    format: STENCIL_FORMAT,
+                    depth_write_enabled: false,
+                    depth_compare: wgpu::CompareFunction::Always,
+                    stencil_front: stencil.clone(),
+                    stencil_back: stencil,
+                    stencil_read_mask: !0,
+                    stencil_write_mask: !0,
+                }),
+                index_format: wgpu::IndexFormat::Uint16,
+                vertex_buffers: &[wgpu::VertexBufferDescriptor {
+                    stride: size_of::<Vertex>() as wgpu::BufferAddress,
+                    step_mode: wgpu::InputStepMode::Vertex,
+                    attributes: &[
+                        wgpu::VertexAttributeDescriptor {
+                            format: wgpu::VertexFormat::Float2,
+                            offset: 0,
+                            shader_location: 0,
+                        },
+                        wgpu::VertexAttributeDescriptor {
+                            format: wgpu::VertexFormat::Float3,
+                            offset: size_of::<Vec2>() as u64,
+                            shader_location: 1,
+                        },
+                        wgpu::VertexAttributeDescriptor {
+                            format: wgpu::VertexFormat::Float2,
+                            offset: (size_of::<Vec2>() + size_of::<Rgb>()) as u64,
+                            shader_location: 2,
+                        },
+                    ],
+                }],
+                sample_count: 1,
+                sample_mask: !0,
+                alpha_to_coverage_enabled: false,
+            })
+        };
+
+        // Reset: replace the stencil value with the reference (zero)
+        let reset_pipeline = create_pipeline(
+            wgpu::CompareFunction::Always,
+            wgpu::StencilOperation::Replace,
+        );
+        // Mask: increment where the value equals the reference
+        let mask_pipeline = create_pipeline(
+            wgpu::CompareFunction::Equal,
+            wgpu::StencilOperation::IncrementClamp,
+        );
+
+        StencilMask {
+            texture: create_depth_texture(device, size, STENCIL_FORMAT),
+            bind_group,
+            scale_buf,
+            reset_pipeline,
+            mask_pipeline,
+        }
+    }
+
+    pub fn resize(
+        &mut self,
+        device: &wgpu::Device,
+        encoder: &mut wgpu::CommandEncoder,
+        size: Size,
+    ) {
+        type Scale = [f32; 2];
+        let scale_factor: Scale = ndc_scale(size);
+        let scale_buf = device
+            .create_buffer_mapped(scale_factor.len(), wgpu::BufferUsage::COPY_SRC)
+            .fill_from_slice(&scale_factor);
+        let byte_len = size_of::<Scale>() as u64;
+
+        encoder.copy_buffer_to_buffer(&scale_buf, 0, &self.scale_buf, 0, byte_len);
+        self.texture = create_depth_texture(device, size, STENCIL_FORMAT);
+    }
+
+    /// The stencil attachment
+    pub fn view(&self) -> &wgpu::TextureView {
+        &self.texture.1
+    }
+
+    /// Write the stencil mask for a region
+    ///
+    /// The stencil is reset to zero within `rect`, then each shape in turn
+    /// increments the value where it equals the shape's index. Thus after
+    /// this call the stencil equals `shapes.len()` exactly where all shapes
+    /// intersect; draws should use this as the stencil reference.
+    pub fn render(
+        &self,
+        device: &wgpu::Device,
+        rect: Rect,
+        shapes: &[ClipShape],
+        rpass: &mut wgpu::RenderPass,
+    ) {
+        let mut v = Vec::with_capacity(6 + 54 * shapes.len());
+        add_quad(
+            &mut v,
+            Vec2::from(rect.pos),
+            Vec2::from(rect.size),
+            [0.0; 4],
+        );
+        let mut ranges = Vec::with_capacity(shapes.len());
+        for shape in shapes {
+            let start = v.len() as u32;
+            add_shape(&mut v, shape);
+            ranges.push(start..(v.len() as u32));
+        }
+
+        let buffer = device
+            .create_buffer_mapped(v.len(), wgpu::BufferUsage::VERTEX)
+            .fill_from_slice(&v);
+
+        rpass.set_bind_group(0, &self.bind_group, &[]);
+        rpass.set_vertex_buffers(0, &[(&buffer, 0)]);
+
+        rpass.set_pipeline(&self.reset_pipeline);
+        rpass.set_stencil_reference(0);
+        rpass.draw(0..6, 0..1);
+
+        rpass.set_pipeline(&self.mask_pipeline);
+        for (i, range) in ranges.into_iter().enumerate() {
+            rpass.set_stencil_reference(i as u32);
+            rpass.draw(range, 0..1);
+        }
+    }
+}
+
+/// Add an axis-aligned quad
+///
+/// The unit-circle coordinates `u` are given as `[x0, y0, x1, y1]`.
+fn add_quad(v: &mut Vec<Vertex>, pos: Vec2, size: Vec2, u: [f32; 4]) {
+    let col = Rgb {
+        r: 0.0,
+        g: 0.0,
+        b: 0.0,
+    };
+    let aa = Vertex(pos, col, Vec2(u[0], u[1]));
+    let ab = Vertex(Vec2(pos.0, pos.1 + size.1), col, Vec2(u[0], u[3]));
+    let ba = Vertex(Vec2(pos.0 + size.0, pos.1), col, Vec2(u[2], u[1]));
+    let bb = Vertex(pos + size, col, Vec2(u[2], u[3]));
+    v.extend_from_slice(&[aa, ba, bb, aa, bb, ab]);
+}
+
+/// Add a shape
+///
+/// Fragments are discarded where unit-circle coordinates have length
+/// greater than 1.
+fn add_shape(v: &mut Vec<Vertex>, shape: &ClipShape) {
+    match *shape {
+        ClipShape::Oval(rect) => {
+            add_quad(
+                v,
+                Vec2::from(rect.pos),
+                Vec2::from(rect.size),
+                [-1.0, -1.0, 1.0, 1.0],
+            );
+        }
+        ClipShape::RoundedRect(rect, radius) => {
+            let size = Vec2::from(rect.size);
+            let r = radius.max(0.0).min(0.5 * size.0).min(0.5 * size.1);
+            if r <= 0.0 {
+                add_quad(v, Vec2::from(rect.pos), size, [0.0; 4]);
+                return;
+            }
+
+            // Split into a 3x3 grid where the corner cells are rounded
+            let aa = Vec2::from(rect.pos);
+            let xs = [aa.0, aa.0 + r, aa.0 + size.0 - r, aa.0 + size.0];
+            let ys = [aa.1, aa.1 + r, aa.1 + size.1 - r, aa.1 + size.1];
+            let us = [-1.0, 0.0, 0.0, 1.0];
+            for i in 0..3 {
+                for j in 0..3 {
+                    let pos = Vec2(xs[i], ys[j]);
+                    let size = Vec2(xs[i + 1] - xs[i], ys[j + 1] - ys[j]);
+                    add_quad(v, pos, size, [us[i], us[j], us[i + 1], us[j + 1]]);
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use kas::geom::Coord;
+
+    fn vertices(shape: ClipShape) -> Vec<Vertex> {
+        let mut v = vec![];
+        add_shape(&mut v, &shape);
+        v
+    }
+
+    /// True if the mask covers `p`, as evaluated by the fragment shader
+    fn covers(v: &[Vertex], p: Vec2) -> bool {
+        v.chunks(6).any(|quad| {
+            let (a, b) = (&quad[0], &quad[2]);
+            let within = |p: f32, a: f32, b: f32| a <= p && p < b;
+            if !within(p.0, (a.0).0, (b.0).0) || !within(p.1, (a.0).1, (b.0).1) {
+                return false;
+            }
+            let lerp =
+                |p: f32, a: f32, b: f32, ua: f32, ub: f32| ua + (p - a) / (b - a) * (ub - ua);
+            let u = Vec2(
+                lerp(p.0, (a.0).0, (b.0).0, (a.2).0, (b.2).0),
+                lerp(p.1, (a.0).1, (b.0).1, (a.2).1, (b.2).1),
+            );
+            u.0 * u.0 + u.1 * u.1 <= 1.0
+        })
+    }
+
+    #[test]
+    fn oval_mask() {
+        let v = vertices(ClipShape::Oval(Rect::new(Coord(0, 0), Size(20, 10))));
+        assert_eq!(v.len(), 6);
+        assert!(covers(&v, Vec2(10.0, 5.0)));
+        assert!(covers(&v, Vec2(19.0, 5.0)));
+        assert!(covers(&v, Vec2(10.0, 0.5)));
+        assert!(!covers(&v, Vec2(2.0, 1.0)));
+        assert!(!covers(&v, Vec2(19.0, 9.0)));
+        assert!(!covers(&v, Vec2(21.0, 5.0)));
+    }
+
+    #[test]
+    fn rounded_rect_mask() {
+        let rect = Rect::new(Coord(0, 0), Size(20, 10));
+        let v = vertices(ClipShape::RoundedRect(rect, 4.0));
+        assert_eq!(v.len(), 54);
+        // Corners are cut, edges and centre are not
+        assert!(!covers(&v, Vec2(0.5, 0.5)));
+        assert!(!covers(&v, Vec2(19.5, 9.5)));
+        assert!(covers(&v, Vec2(1.5, 1.5)));
+        assert!(covers(&v, Vec2(0.5, 5.0)));
+        assert!(covers(&v, Vec2(10.0, 0.5)));
+        assert!(covers(&v, Vec2(10.0, 5.0)));
+        assert!(!covers(&v, Vec2(10.0, 10.5)));
+
+        // The radius is clamped to half the width and height
+        let pos = |v: Vec<Vertex>| v.iter().map(|v| v.0).collect::<Vec<_>>();
+        let clamped = vertices(ClipShape::RoundedRect(rect, 100.0));
+        assert_eq!(
+            pos(clamped),
+            pos(vertices(ClipShape::RoundedRect(rect, 5.0)))
+        );
+
+        // A rect without rounding covers its whole area
+        let v = vertices(ClipShape::RoundedRect(rect, 0.0));
+        assert_eq!(v.len(), 6);
+        assert!(covers(&v, Vec2(0.0, 0.0)));
+        assert!(covers(&v, Vec2(19.9, 9.9)));
+    }
+}
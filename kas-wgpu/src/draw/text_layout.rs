@@ -0,0 +1,399 @@
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License in the LICENSE-APACHE file or at:
+//     https://www.apache.org/licenses/LICENSE-2.0
+
+//! Text layout with typographic adjustments
+//!
+//! `glyph_brush`'s built-in layout cannot adjust line height or letter
+//! spacing, thus [`StyledLayout`] is used instead when a [`TextStyle`] is not
+//! the default. It is also used for text containing tabs, which advance to the
+//! next tab stop. Lines are only broken (when wrapping) after white-space.
+
+use std::hash::{Hash, Hasher};
+
+use kas::draw::{LineHeight, TextStyle};
+use wgpu_glyph::rusttype::{point, PositionedGlyph, Rect, ScaledGlyph};
+use wgpu_glyph::{
+    BuiltInLineBreaker, FontId, FontMap, GlyphPositioner, HorizontalAlign, Layout, SectionGeometry,
+    SectionText, VerticalAlign,
+};
+
+/// Vertical metrics: `(ascent, descent, line_gap, scale)`
+type Metrics = (f32, f32, f32, f32);
+
+/// A glyph placed on a line, relative to the line's start
+struct Item<'font> {
+    glyph: ScaledGlyph<'font>,
+    x: f32,
+    advance: f32,
+    /// Kerning with the previous glyph
+    kern: f32,
+    color: [f32; 4],
+    font_id: FontId,
+    metrics: Metrics,
+    space: bool,
+    tab: bool,
+}
+
+struct Line<'font> {
+    items: Vec<Item<'font>>,
+    /// Metrics of the font at the start of the line (used if empty)
+    base: Metrics,
+}
+
+impl<'font> Line<'font> {
+    fn new(base: Metrics) -> Self {
+        Line {
+            items: vec![],
+            base,
+        }
+    }
+
+    /// Vertical metrics: the maximum over all glyphs
+    fn metrics(&self) -> Metrics {
+        let mut m = match self.items.first() {
+            Some(item) => item.metrics,
+            None => return self.base,
+        };
+        for item in &self.items[1..] {
+            let n = item.metrics;
+            m = (m.0.max(n.0), m.1.min(n.1), m.2.max(n.2), m.3.max(n.3));
+        }
+        m
+    }
+
+    /// Width, excluding trailing white-space
+    fn width(&self) -> f32 {
+        self.items
+            .iter()
+            .filter(|item| !item.space)
+            .map(|item| item.x + item.advance)
+            .fold(0.0, f32::max)
+    }
+
+    /// Re-position items from the start of the line, returning the caret
+    ///
+    /// Used after wrapping, since tab stops depend on the position.
+    fn reflow(&mut self, style: &TextStyle) -> f32 {
+        let mut caret = 0.0;
+        for (i, item) in self.items.iter_mut().enumerate() {
+            if i > 0 {
+                caret += item.kern;
+            }
+            item.x = caret;
+            if item.tab {
+                let space = item.glyph.h_metrics().advance_width;
+                item.advance = style.next_tab_stop(caret, space) - caret;
+                caret += item.advance;
+            } else {
+                caret += item.advance + style.letter_spacing;
+            }
+        }
+        caret
+    }
+}
+
+/// Layout applying a [`TextStyle`]
+///
+/// Dimensions of the style are in pixels.
+#[derive(Clone, Copy, Debug)]
+pub struct StyledLayout {
+    pub layout: Layout<BuiltInLineBreaker>,
+    pub style: TextStyle,
+}
+
+impl Hash for StyledLayout {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.layout.hash(state);
+        match self.style.line_height {
+            LineHeight::Normal => 0u32.hash(state),
+            LineHeight::Relative(m) => (1u32, m.to_bits()).hash(state),
+            LineHeight::Absolute(h) => (2u32, h.to_bits()).hash(state),
+        }
+        self.style.letter_spacing.to_bits().hash(state);
+        self.style.tab_width.map(f32::to_bits).hash(state);
+    }
+}
+
+impl StyledLayout {
+    /// Break text into lines
+    fn lines<'font, F: FontMap<'font>>(
+        &self,
+        fonts: &F,
+        bound_w: f32,
+        sections: &[SectionText<'_>],
+    ) -> Vec<Line<'font>> {
+        let wrap = match self.layout {
+            Layout::SingleLine { .. } => false,
+            Layout::Wrap { .. } => true,
+        };
+        let spacing = self.style.letter_spacing;
+
+        let metrics_of = |font_id: FontId, scale| {
+            let vm = fonts.font(font_id).v_metrics(scale);
+            (vm.ascent, vm.descent, vm.line_gap, scale.y)
+        };
+        let empty_metrics = sections
+            .first()
+            .map(|s| metrics_of(s.font_id, s.scale))
+            .unwrap_or((0.0, 0.0, 0.0, 0.0));
+
+        let mut lines = vec![];
+        let mut line = Line::new(empty_metrics);
+        let mut caret = 0.0;
+        // Index of the first item after the last white-space run
+        let mut last_break: Option<usize> = None;
+
+        'outer: for section in sections {
+            let font = fonts.font(section.font_id);
+            let metrics = metrics_of(section.font_id, section.scale);
+            for c in section.text.chars() {
+                if c == '\n' {
+                    if !wrap {
+                        break 'outer;
+                    }
+                    let next = Line::new(metrics);
+                    lines.push(std::mem::replace(&mut line, next));
+                    caret = 0.0;
+                    last_break = None;
+                    continue;
+                }
+                let tab = c == '\t';
+                if c.is_control() && !tab {
+                    continue;
+                }
+
+                // A tab is positioned like a space, then widened to the stop
+                let glyph = font.glyph(if tab { ' ' } else { c }).scaled(section.scale);
+                let mut kern = 0.0;
+                if let Some(prev) = line.items.last() {
+                    if !tab
+                        && !prev.tab
+                        && prev.font_id == section.font_id
+                        && prev.glyph.scale() == section.scale
+                    {
+                        kern = font.pair_kerning(section.scale, prev.glyph.id(), glyph.id());
+                    }
+                }
+                caret += kern;
+                let mut advance = glyph.h_metrics().advance_width;
+                if tab {
+                    advance = self.style.next_tab_stop(caret, advance) - caret;
+                }
+                let space = c.is_whitespace();
+
+                if !space {
+                    if line.items.last().map(|item| item.space).unwrap_or(false) {
+                        last_break = Some(line.items.len());
+                    }
+                    if wrap && caret + advance > bound_w && !line.items.is_empty() {
+                        let split = last_break.unwrap_or(line.items.len());
+                        let mut next = Line::new(metrics);
+                        next.items = line.items.split_off(split);
+                        caret = match next.items.is_empty() {
+                            true => 0.0,
+                            false => next.reflow(&self.style) + kern,
+                        };
+                        lines.push(std::mem::replace(&mut line, next));
+                        last_break = None;
+                    }
+                }
+
+                line.items.push(Item {
+                    glyph,
+                    x: caret,
+                    advance,
+                    kern,
+                    color: section.color,
+                    font_id: section.font_id,
+                    metrics,
+                    space,
+                    tab,
+                });
+                caret += advance;
+                if !tab {
+                    caret += spacing;
+                }
+            }
+        }
+        lines.push(line);
+        lines
+    }
+}
+
+impl GlyphPositioner for StyledLayout {
+    fn calculate_glyphs<'font, F: FontMap<'font>>(
+        &self,
+        fonts: &F,
+        geometry: &SectionGeometry,
+        sections: &[SectionText<'_>],
+    ) -> Vec<(PositionedGlyph<'font>, [f32; 4], FontId)> {
+        let (h_align, v_align) = match self.layout {
+            Layout::SingleLine {
+                h_align, v_align, ..
+            } => (h_align, v_align),
+            Layout::Wrap {
+                h_align, v_align, ..
+            } => (h_align, v_align),
+        };
+        let lines = self.lines(fonts, geometry.bounds.0, sections);
+
+        // Baseline of each line, relative to the top of the first
+        let mut baselines = Vec::with_capacity(lines.len());
+        let mut prev: Option<Metrics> = None;
+        let mut y = 0.0;
+        for line in &lines {
+            let metrics = line.metrics();
+            let (ascent, _, _, scale) = metrics;
+            y += match prev {
+                None => ascent,
+                Some((_, descent, gap, _)) => {
+                    let normal = ascent - descent + gap;
+                    self.style.line_height.resolve(scale, normal)
+                }
+            };
+            baselines.push(y);
+            prev = Some(metrics);
+        }
+        let height = y - prev.map(|m| m.1).unwrap_or(0.0);
+
+        let (x0, y0) = geometry.screen_position;
+        let top = match v_align {
+            VerticalAlign::Top => y0,
+            VerticalAlign::Center => y0 - 0.5 * height,
+            VerticalAlign::Bottom => y0 - height,
+        };
+
+        let mut out = vec![];
+        for (line, baseline) in lines.into_iter().zip(baselines) {
+            let width = line.width();
+            let left = match h_align {
+                HorizontalAlign::Left => x0,
+                HorizontalAlign::Center => x0 - 0.5 * width,
+                HorizontalAlign::Right => x0 - width,
+            };
+            for item in line.items.into_iter().filter(|item| !item.space) {
+                let pos = point(left + item.x, top + baseline);
+                out.push((item.glyph.positioned(pos), item.color, item.font_id));
+            }
+        }
+        out
+    }
+
+    fn bounds_rect(&self, geometry: &SectionGeometry) -> Rect<f32> {
+        self.layout.bounds_rect(geometry)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use font_kit::{family_name::FamilyName, properties::Properties, source::SystemSource};
+    use wgpu_glyph::rusttype::{Font, Scale};
+
+    fn fonts() -> Vec<Font<'static>> {
+        let font = SystemSource::new()
+            .select_best_match(&[FamilyName::SansSerif], &Properties::new())
+            .unwrap()
+            .load()
+            .unwrap();
+        let bytes = font.copy_font_data().unwrap();
+        vec![Font::from_bytes((*bytes).clone()).unwrap()]
+    }
+
+    /// Glyph positions of `text` at scale 20, placed at the origin
+    fn positions<L: GlyphPositioner>(
+        fonts: &[Font<'static>],
+        layout: L,
+        text: &str,
+    ) -> Vec<(f32, f32)> {
+        let geometry = SectionGeometry {
+            screen_position: (0.0, 0.0),
+            bounds: (f32::INFINITY, f32::INFINITY),
+        };
+        let section = SectionText {
+            text,
+            scale: Scale::uniform(20.0),
+            ..SectionText::default()
+        };
+        let fonts = fonts.to_vec();
+        layout
+            .calculate_glyphs(&fonts, &geometry, &[section])
+            .into_iter()
+            .map(|(glyph, _, _)| (glyph.position().x, glyph.position().y))
+            .collect()
+    }
+
+    fn styled(layout: Layout<BuiltInLineBreaker>, style: TextStyle) -> StyledLayout {
+        StyledLayout { layout, style }
+    }
+
+    #[test]
+    fn default_style_matches_builtin() {
+        let fonts = fonts();
+        let layout = Layout::default_single_line();
+        let text = "Typographic kerning: AVAWA";
+        let expected = positions(&fonts, layout, text);
+        let actual = positions(&fonts, styled(layout, TextStyle::default()), text);
+        assert_eq!(actual.len(), expected.len());
+        for (a, e) in actual.iter().zip(&expected) {
+            assert!((a.0 - e.0).abs() < 0.01, "{:?} != {:?}", actual, expected);
+            assert!((a.1 - e.1).abs() < 0.01, "{:?} != {:?}", actual, expected);
+        }
+    }
+
+    #[test]
+    fn letter_spacing() {
+        let fonts = fonts();
+        let layout = Layout::default_single_line();
+        let plain = positions(&fonts, styled(layout, TextStyle::default()), "abcd");
+        let style = TextStyle {
+            letter_spacing: 3.0,
+            ..TextStyle::default()
+        };
+        let spaced = positions(&fonts, styled(layout, style), "abcd");
+        assert_eq!(spaced.len(), 4);
+        for (i, (s, p)) in spaced.iter().zip(&plain).enumerate() {
+            assert!((s.0 - p.0 - 3.0 * i as f32).abs() < 0.01);
+            assert_eq!(s.1, p.1);
+        }
+    }
+
+    #[test]
+    fn line_height() {
+        let fonts = fonts();
+        let layout = Layout::default_wrap();
+        let step = |line_height| {
+            let style = TextStyle {
+                line_height,
+                ..TextStyle::default()
+            };
+            let pos = positions(&fonts, styled(layout, style), "a\nb");
+            assert_eq!(pos.len(), 2);
+            assert_eq!(pos[0].0, pos[1].0);
+            pos[1].1 - pos[0].1
+        };
+        assert!((step(LineHeight::Absolute(30.0)) - 30.0).abs() < 0.01);
+        assert!((step(LineHeight::Relative(1.5)) - 30.0).abs() < 0.01);
+
+        // The natural line height matches glyph_brush's layout
+        let pos = positions(&fonts, layout, "a\nb");
+        assert!((step(LineHeight::Normal) - (pos[1].1 - pos[0].1)).abs() < 0.01);
+    }
+
+    #[test]
+    fn tab_stops() {
+        let fonts = fonts();
+        let layout = Layout::default_single_line();
+        let style = TextStyle {
+            tab_width: Some(50.0),
+            ..TextStyle::default()
+        };
+        let pos = positions(&fonts, styled(layout, style), "a\tb\tc");
+        assert_eq!(pos.len(), 3);
+        assert_eq!(pos[0].0, 0.0);
+        assert!((pos[1].0 - 50.0).abs() < 0.01);
+        assert!((pos[2].0 - 100.0).abs() < 0.01);
+    }
+}
@@ -0,0 +1,413 @@
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License in the LICENSE-APACHE file or at:
+//     https://www.apache.org/licenses/LICENSE-2.0
+
+//! Rendering to an externally-managed target
+
+use log::{debug, warn};
+use std::time::Instant;
+
+use kas::access::{AccessEvent, AccessNode};
+use kas::event::{Callback, CursorIcon, InputEvent, ManagerState, UpdateHandle};
+use kas::geom::Size;
+use kas::{TkAction, WindowId};
+use kas_theme::{Theme, Window as _};
+
+use crate::draw::{CustomPipeBuilder, DrawPipe, RenderStats, DEPTH_FORMAT};
+use crate::shared::{PendingAction, SharedState};
+use crate::window::{create_depth_texture, draw_frame, FrameTarget, TkWindow};
+use crate::{ColourFilter, Error, Options};
+
+/// Description of an externally-managed render target
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct EmbedTarget {
+    /// Texture format of target views
+    pub format: wgpu::TextureFormat,
+    /// Initial size, in physical pixels
+    pub size: Size,
+    /// Initial DPI factor (ignored if [`Options::dpi_factor`] is set)
+    pub dpi_factor: f64,
+}
+
+/// A widget tree drawn to an externally-managed target
+///
+/// This allows a KAS UI to be embedded within another `wgpu` application,
+/// which retains ownership of its window and event loop. The host passes
+/// input via [`Embedded::handle_event`] (see [`InputEvent`]), reports size
+/// changes via [`Embedded::resize`], and calls [`Embedded::draw`] with a
+/// target texture view when [`Embedded::needs_redraw`].
+///
+/// The device and queue are owned by this object but remain accessible via
+/// [`Embedded::device`] and [`Embedded::queue`].
+///
+/// Functionality requiring a toolkit (new windows, update channels,
+/// [`crate::ToolkitProxy`]) is not available; the cursor icon requested by
+/// widgets is available via [`Embedded::cursor_icon`].
+pub struct Embedded<CB: CustomPipeBuilder, T: Theme<DrawPipe<CB::Pipe>>> {
+    shared: SharedState<CB, T>,
+    id: WindowId,
+    widget: Box<dyn kas::Window>,
+    mgr: ManagerState,
+    draw_pipe: DrawPipe<CB::Pipe>,
+    theme_window: T::Window,
+    size: Size,
+    /// DPI factor reported by the host
+    host_dpi_factor: f64,
+    /// DPI factor used in place of the host's, if any
+    dpi_override: Option<f64>,
+    dpi_factor: f64,
+    depth: Option<(wgpu::Texture, wgpu::TextureView)>,
+    /// Command buffers to submit with the next frame
+    pending_cmds: Vec<wgpu::CommandBuffer>,
+    cursor_icon: CursorIcon,
+    needs_redraw: bool,
+    closed: bool,
+}
+
+impl<CB: CustomPipeBuilder, T: Theme<DrawPipe<CB::Pipe>>> Embedded<CB, T> {
+    /// Construct
+    ///
+    /// The `device` and `queue` should be created by the host application.
+    /// The `custom` parameter accepts a custom draw pipe (see
+    /// [`CustomPipeBuilder`]); pass `()` if you don't have one. The `target`
+    /// format and size must match the texture views later passed to
+    /// [`Embedded::draw`]. [`Options`] relating to adapter selection and
+    /// windows are ignored.
+    pub fn new(
+        device: wgpu::Device,
+        queue: wgpu::Queue,
+        custom: CB,
+        mut theme: T,
+        options: Options,
+        widget: Box<dyn kas::Window>,
+        target: EmbedTarget,
+    ) -> Result<Self, Error> {
+        let size = target.size;
+        if !crate::draw::is_supported_format(target.format) {
+            warn!(
+                "Embedded: target format {:?} is not supported; colours may be incorrect",
+                target.format
+            );
+        }
+        let light_dir = crate::configure_theme(&mut theme, &options);
+        let mut shared = SharedState::with_device(custom, theme, options, device, queue, None)?;
+        if let Some(dir) = light_dir {
+            shared.light_dir = dir;
+        }
+        let dpi_override = shared.dpi_override;
+        let dpi_factor = dpi_override.unwrap_or(target.dpi_factor);
+
+        let mut draw_pipe = DrawPipe::new(&mut shared, target.format, size);
+        shared.theme.init(&mut draw_pipe);
+        let depth = if draw_pipe.uses_depth() {
+            Some(create_depth_texture(&shared.device, size, DEPTH_FORMAT))
+        } else {
+            None
+        };
+        let theme_window = shared.theme.new_window(&mut draw_pipe, dpi_factor as f32);
+
+        let mut mgr = ManagerState::new(dpi_factor);
+        mgr.set_access_prefs(shared.access_prefs);
+        let id = shared.next_window_id();
+
+        let mut embedded = Embedded {
+            shared,
+            id,
+            widget,
+            mgr,
+            draw_pipe,
+            theme_window,
+            size,
+            host_dpi_factor: target.dpi_factor,
+            dpi_override,
+            dpi_factor,
+            depth,
+            pending_cmds: vec![],
+            cursor_icon: CursorIcon::Default,
+            needs_redraw: true,
+            closed: false,
+        };
+
+        let mut tkw = TkWindow::embedded(&mut embedded.shared, &mut embedded.cursor_icon);
+        let mut mgr = embedded.mgr.manager(&mut tkw);
+        mgr.send_action(TkAction::Reconfigure);
+        for (i, condition) in embedded.widget.callbacks() {
+            if condition == Callback::Start {
+                embedded.widget.trigger_callback(i, &mut mgr);
+            }
+        }
+        let action = mgr.unwrap_action();
+        embedded.apply(action);
+
+        Ok(embedded)
+    }
+
+    /// Access the device
+    pub fn device(&self) -> &wgpu::Device {
+        &self.shared.device
+    }
+
+    /// Access the queue
+    pub fn queue(&mut self) -> &mut wgpu::Queue {
+        &mut self.shared.queue
+    }
+
+    /// Access the theme
+    pub fn theme(&mut self) -> &mut T {
+        &mut self.shared.theme
+    }
+
+    /// The current target size
+    pub fn size(&self) -> Size {
+        self.size
+    }
+
+    /// The cursor icon requested by widgets
+    pub fn cursor_icon(&self) -> CursorIcon {
+        self.cursor_icon
+    }
+
+    /// True if [`Embedded::draw`] should be called
+    ///
+    /// This is the case when widgets requested a redraw or the tree was
+    /// reconfigured or resized since the last draw. It is accurate after each
+    /// method call (e.g. after handling a batch of events). Animated widgets
+    /// request redraws from [`Embedded::update_timer`]; see
+    /// [`Embedded::next_timer`].
+    #[inline]
+    pub fn needs_redraw(&self) -> bool {
+        self.needs_redraw
+    }
+
+    /// Request that the next [`Embedded::draw`] re-draws the widget tree
+    ///
+    /// This is only required if the host changed something affecting the
+    /// output other than via methods of this type.
+    pub fn redraw(&mut self) {
+        self.needs_redraw = true;
+    }
+
+    /// Statistics of the last frame drawn, if enabled
+    ///
+    /// Statistics are only gathered when enabled via
+    /// [`Options::render_stats`].
+    #[inline]
+    pub fn render_stats(&self) -> Option<RenderStats> {
+        self.draw_pipe.render_stats()
+    }
+
+    /// Set a handler for accessibility notifications
+    ///
+    /// See [`crate::Toolkit::set_access_handler`].
+    pub fn set_access_handler<F>(&mut self, f: F)
+    where
+        F: FnMut(WindowId, &AccessNode, AccessEvent) + 'static,
+    {
+        self.shared.access_handler = Some(Box::new(f));
+    }
+
+    /// Build the accessibility tree of the embedded widgets
+    pub fn access_tree(&self) -> AccessNode {
+        AccessNode::build(self.widget.as_widget(), &self.mgr)
+    }
+
+    /// True if the widget tree requested closure
+    ///
+    /// The host decides what to do; further use remains possible.
+    pub fn close_requested(&self) -> bool {
+        self.closed
+    }
+
+    /// The next time at which [`Embedded::update_timer`] should be called
+    #[inline]
+    pub fn next_timer(&self) -> Option<Instant> {
+        self.mgr.next_resume()
+    }
+
+    /// Handle an input event
+    ///
+    /// Coordinates are in physical pixels relative to the target.
+    pub fn handle_event(&mut self, event: InputEvent) {
+        let mut tkw = TkWindow::embedded(&mut self.shared, &mut self.cursor_icon);
+        let mut mgr = self.mgr.manager(&mut tkw);
+        let _ = mgr.handle_synthetic(&mut *self.widget, event);
+        let action = mgr.unwrap_action();
+        self.apply(action);
+    }
+
+    /// Update timed widgets
+    ///
+    /// Call this once the time given by [`Embedded::next_timer`] is reached.
+    pub fn update_timer(&mut self) {
+        let mut tkw = TkWindow::embedded(&mut self.shared, &mut self.cursor_icon);
+        let mut mgr = self.mgr.manager(&mut tkw);
+        mgr.update_timer(&mut *self.widget);
+        let action = mgr.unwrap_action();
+        self.apply(action);
+    }
+
+    /// Trigger widgets subscribed to `handle`
+    pub fn trigger_update(&mut self, handle: UpdateHandle, payload: u64) {
+        let mut tkw = TkWindow::embedded(&mut self.shared, &mut self.cursor_icon);
+        let mut mgr = self.mgr.manager(&mut tkw);
+        mgr.update_handle(&mut *self.widget, handle, payload);
+        let action = mgr.unwrap_action();
+        self.apply(action);
+    }
+
+    /// Set the target size
+    ///
+    /// Texture views later passed to [`Embedded::draw`] must have this size.
+    pub fn resize(&mut self, size: Size) {
+        if size == self.size {
+            return;
+        }
+        debug!("Resizing embedded target to size={:?}", size);
+        self.size = size;
+        let mut size_handle = unsafe { self.theme_window.size_handle(&mut self.draw_pipe) };
+        self.widget.resize(&mut size_handle, size);
+        drop(size_handle);
+
+        if self.depth.is_some() {
+            self.depth = Some(create_depth_texture(
+                &self.shared.device,
+                size,
+                DEPTH_FORMAT,
+            ));
+        }
+        let buf = self.draw_pipe.resize(&self.shared.device, size);
+        self.pending_cmds.push(buf);
+        self.needs_redraw = true;
+    }
+
+    /// Set the DPI factor
+    ///
+    /// This is ignored while the DPI factor is overridden (see
+    /// [`Options::dpi_factor`] and [`kas::event::Manager::set_dpi_override`]).
+    pub fn set_dpi_factor(&mut self, dpi_factor: f64) {
+        self.host_dpi_factor = dpi_factor;
+        self.update_dpi_factor();
+    }
+
+    /// Set the colour filter
+    ///
+    /// See [`Options::colour_filter`].
+    pub fn set_colour_filter(&mut self, filter: ColourFilter) {
+        self.shared.colour_filter = filter;
+        self.draw_pipe.set_colour_filter(&self.shared, filter);
+        self.needs_redraw = true;
+    }
+
+    /// Draw to the given target
+    ///
+    /// The target is cleared to the theme's background colour (or to
+    /// transparent with [`Options::transparent`]), then the widget tree is
+    /// drawn. Commands are submitted to the queue before returning.
+    ///
+    /// If nothing changed since the last call (i.e. not
+    /// [`Embedded::needs_redraw`]), the command stream of the last frame is
+    /// replayed without re-drawing widgets, where possible.
+    pub fn draw(&mut self, view: &wgpu::TextureView) {
+        let target = FrameTarget {
+            view,
+            depth_view: self.depth.as_ref().map(|(_, view)| view),
+            size: self.size,
+            replay: !self.needs_redraw,
+        };
+        draw_frame(
+            &mut self.shared,
+            &mut self.draw_pipe,
+            &mut self.theme_window,
+            &*self.widget,
+            &mut self.mgr,
+            &mut self.pending_cmds,
+            target,
+        );
+        self.needs_redraw = false;
+    }
+
+    fn update_dpi_factor(&mut self) {
+        let dpi_factor = self.dpi_override.unwrap_or(self.host_dpi_factor);
+        if dpi_factor == self.dpi_factor {
+            return;
+        }
+        self.dpi_factor = dpi_factor;
+        self.mgr.set_dpi_factor(dpi_factor);
+        self.theme_resize();
+    }
+
+    fn theme_resize(&mut self) {
+        self.shared
+            .theme
+            .update_window(&mut self.theme_window, self.dpi_factor as f32);
+        let mut size_handle = unsafe { self.theme_window.size_handle(&mut self.draw_pipe) };
+        self.widget.resize(&mut size_handle, self.size);
+        self.mgr.redraw_all();
+        self.needs_redraw = true;
+    }
+
+    fn reconfigure(&mut self) {
+        let mut size_handle = unsafe { self.theme_window.size_handle(&mut self.draw_pipe) };
+        self.widget.resize(&mut size_handle, self.size);
+        drop(size_handle);
+        let mut tkw = TkWindow::embedded(&mut self.shared, &mut self.cursor_icon);
+        self.mgr.configure(&mut tkw, &mut *self.widget);
+        self.needs_redraw = true;
+    }
+
+    /// Apply an action, then pending actions
+    fn apply(&mut self, action: TkAction) {
+        match action {
+            TkAction::None => (),
+            TkAction::Redraw => self.needs_redraw = true,
+            TkAction::RegionMoved => {
+                self.mgr.region_moved(&mut *self.widget);
+                self.needs_redraw = true;
+            }
+            TkAction::Reconfigure => self.reconfigure(),
+            TkAction::RequestClose => {
+                let mut tkw = TkWindow::embedded(&mut self.shared, &mut self.cursor_icon);
+                let mut mgr = self.mgr.manager(&mut tkw);
+                mgr.request_close(&mut *self.widget);
+                let action = mgr.unwrap_action();
+                // The resolved action is Close or actions the request subsumed
+                return self.apply(action);
+            }
+            TkAction::Close | TkAction::CloseAll => self.closed = true,
+        }
+
+        let pending = std::mem::take(&mut self.shared.pending);
+        for pending in pending {
+            match pending {
+                PendingAction::AddWindow(..) => {
+                    warn!("Embedded: adding windows is not supported");
+                }
+                PendingAction::CloseWindow(id) => {
+                    if id == self.id {
+                        self.closed = true;
+                    }
+                }
+                PendingAction::SetDpiOverride(_, factor) => {
+                    self.dpi_override = factor;
+                    self.update_dpi_factor();
+                }
+                PendingAction::AccessNotify(_, event) => {
+                    if let Some(handler) = self.shared.access_handler.as_mut() {
+                        let tree = AccessNode::build(self.widget.as_widget(), &self.mgr);
+                        handler(self.id, &tree, event);
+                    }
+                }
+                PendingAction::ThemeResize => self.theme_resize(),
+                PendingAction::RedrawAll => {
+                    self.mgr.redraw_all();
+                    self.needs_redraw = true;
+                }
+                PendingAction::Update(handle, payload) => self.trigger_update(handle, payload),
+                PendingAction::UpdateData(_) => {
+                    warn!("Embedded: update channels are not supported");
+                }
+            }
+        }
+    }
+}
@@ -10,6 +10,7 @@ use smallvec::SmallVec;
 use std::collections::HashMap;
 use std::time::Instant;
 
+use winit::error::OsError;
 use winit::event::{Event, StartCause};
 use winit::event_loop::{ControlFlow, EventLoopWindowTarget};
 use winit::window as ww;
@@ -17,9 +18,9 @@ use winit::window as ww;
 use kas::TkAction;
 use kas_theme::Theme;
 
-use crate::draw::{CustomPipeBuilder, DrawPipe};
+use crate::draw::{CustomPipeBuilder, DrawPipe, RenderStats};
 use crate::shared::{PendingAction, SharedState};
-use crate::{ProxyAction, Window, WindowId};
+use crate::{ProxyAction, Window, WindowGeometry, WindowId, WindowPosition};
 
 /// Event-loop data structure (i.e. all run-time state)
 pub(crate) struct Loop<CB: CustomPipeBuilder, T: Theme<DrawPipe<CB::Pipe>>> {
@@ -27,26 +28,93 @@ pub(crate) struct Loop<CB: CustomPipeBuilder, T: Theme<DrawPipe<CB::Pipe>>> {
     windows: HashMap<ww::WindowId, Window<CB::Pipe, T::Window>>,
     /// Translates our WindowId to winit's
     id_map: HashMap<WindowId, ww::WindowId>,
+    /// Modal windows, mapped to the parent window they block
+    modals: HashMap<ww::WindowId, ww::WindowId>,
     /// Shared data passed from Toolkit
-    shared: SharedState<CB, T>,
+    pub(crate) shared: SharedState<CB, T>,
     /// Timer resumes: (time, window index)
     resumes: Vec<(Instant, ww::WindowId)>,
+    /// Windows added by the toolkit but not yet initialised
+    uninit: Vec<ww::WindowId>,
 }
 
 impl<CB: CustomPipeBuilder, T: Theme<DrawPipe<CB::Pipe>>> Loop<CB, T> {
-    pub(crate) fn new(
-        mut windows: Vec<(WindowId, Window<CB::Pipe, T::Window>)>,
-        shared: SharedState<CB, T>,
-    ) -> Self {
-        let id_map = windows.iter().map(|(id, w)| (*id, w.window.id())).collect();
+    pub(crate) fn new(shared: SharedState<CB, T>) -> Self {
         Loop {
-            windows: windows.drain(..).map(|(_, w)| (w.window.id(), w)).collect(),
-            id_map,
+            windows: HashMap::new(),
+            id_map: HashMap::new(),
+            modals: HashMap::new(),
             shared,
             resumes: vec![],
+            uninit: vec![],
         }
     }
 
+    /// Add a window (from outside the event loop)
+    ///
+    /// The window is initialised on the next event-loop iteration.
+    pub(crate) fn add_window(
+        &mut self,
+        elwt: &EventLoopWindowTarget<ProxyAction>,
+        widget: Box<dyn kas::Window>,
+        position: WindowPosition,
+        geometry: Option<&WindowGeometry>,
+    ) -> Result<WindowId, OsError> {
+        let windows = &self.windows;
+        let parent = match position {
+            WindowPosition::CentreOnParent(parent) => self
+                .id_map
+                .get(&parent)
+                .and_then(|wid| windows.get(wid))
+                .map(|w| &w.window),
+            _ => None,
+        };
+        let window = Window::new(&mut self.shared, elwt, widget, position, parent, geometry)?;
+        let id = self.shared.next_window_id();
+        let wid = window.window.id();
+        self.id_map.insert(id, wid);
+        self.windows.insert(wid, window);
+        self.uninit.push(wid);
+        Ok(id)
+    }
+
+    /// Make window `id` modal over `parent`
+    pub(crate) fn set_modal(&mut self, id: WindowId, parent: WindowId) {
+        if let (Some(wid), Some(parent)) = (self.id_map.get(&id), self.id_map.get(&parent)) {
+            if let Some(window) = self.windows.get(wid) {
+                window.window.set_always_on_top(true);
+            }
+            self.modals.insert(*wid, *parent);
+        }
+    }
+
+    /// Get the `winit` window for `id`
+    pub(crate) fn winit_window(&self, id: WindowId) -> Option<&ww::Window> {
+        let wid = self.id_map.get(&id)?;
+        self.windows.get(wid).map(|w| &w.window)
+    }
+
+    /// Get rendering statistics of the window `id`
+    pub(crate) fn render_stats(&self, id: WindowId) -> Option<RenderStats> {
+        let wid = self.id_map.get(&id)?;
+        self.windows.get(wid)?.render_stats()
+    }
+
+    /// True if no windows remain
+    pub(crate) fn is_empty(&self) -> bool {
+        self.windows.is_empty()
+    }
+
+    /// True if any window has a pending redraw
+    pub(crate) fn needs_redraw(&self) -> bool {
+        self.windows.values().any(|w| w.needs_redraw())
+    }
+
+    /// The earliest timer resume time
+    pub(crate) fn next_timer(&self) -> Option<Instant> {
+        self.resumes.iter().map(|item| item.0).min()
+    }
+
     pub(crate) fn handle(
         &mut self,
         event: Event<ProxyAction>,
@@ -58,38 +126,47 @@ impl<CB: CustomPipeBuilder, T: Theme<DrawPipe<CB::Pipe>>> Loop<CB, T> {
         // In most cases actions.len() is 0 or 1.
         let mut actions = SmallVec::<[_; 2]>::new();
         let mut have_new_resumes = false;
-        let add_resume = |resumes: &mut Vec<(Instant, ww::WindowId)>, instant, window_id| {
-            if let Some(i) = resumes
-                .iter()
-                .enumerate()
-                .find(|item| (item.1).1 == window_id)
-                .map(|item| item.0)
-            {
-                resumes[i].0 = instant;
-            } else {
-                resumes.push((instant, window_id));
-            }
-        };
 
         match event {
             WindowEvent { window_id, event } => {
+                if is_input(&event) && self.modals.values().any(|id| *id == window_id) {
+                    // Window is blocked by a modal
+                    return;
+                }
                 if let Some(window) = self.windows.get_mut(&window_id) {
                     let (action, resume) = window.handle_event(&mut self.shared, event);
                     actions.push((window_id, action));
-                    if let Some(instant) = resume {
-                        add_resume(&mut self.resumes, instant, window_id);
-                        have_new_resumes = true;
-                    }
+                    set_resume(&mut self.resumes, resume, window_id);
+                    have_new_resumes = true;
                 }
             }
 
             DeviceEvent { .. } => return, // windows handle local input; we do not handle global input
             UserEvent(action) => match action {
+                ProxyAction::AddWindow(id, widget) => {
+                    self.shared
+                        .pending
+                        .push(PendingAction::AddWindow(id, widget, None));
+                }
                 ProxyAction::Close(id) => {
                     if let Some(id) = self.id_map.get(&id) {
                         actions.push((*id, TkAction::Close));
                     }
                 }
+                ProxyAction::Redraw(id) => {
+                    if let Some(id) = self.id_map.get(&id) {
+                        actions.push((*id, TkAction::Redraw));
+                    }
+                }
+                #[cfg(feature = "shader_reload")]
+                ProxyAction::ReloadShaders => {
+                    debug!("Reloading shaders");
+                    if self.shared.shaders.reload(&self.shared.device) {
+                        for window in self.windows.values_mut() {
+                            window.reload_shaders(&self.shared);
+                        }
+                    }
+                }
                 ProxyAction::CloseAll => {
                     if let Some(id) = self.windows.keys().next() {
                         // Any id will do; if we have no windows we close anyway!
@@ -101,6 +178,15 @@ impl<CB: CustomPipeBuilder, T: Theme<DrawPipe<CB::Pipe>>> Loop<CB, T> {
                         .pending
                         .push(PendingAction::Update(handle, payload));
                 }
+                ProxyAction::UpdateData(handle) => {
+                    self.shared.pending.push(PendingAction::UpdateData(handle));
+                }
+                ProxyAction::SetColourFilter(filter) => {
+                    self.shared.colour_filter = filter;
+                    for window in self.windows.values_mut() {
+                        window.set_colour_filter(&self.shared);
+                    }
+                }
             },
 
             NewEvents(cause) => {
@@ -108,33 +194,18 @@ impl<CB: CustomPipeBuilder, T: Theme<DrawPipe<CB::Pipe>>> Loop<CB, T> {
                 *control_flow = ControlFlow::Wait;
                 have_new_resumes = true;
 
+                for id in self.uninit.drain(..) {
+                    if let Some(window) = self.windows.get_mut(&id) {
+                        let action = window.init(&mut self.shared);
+                        actions.push((id, action));
+                    }
+                }
+
                 match cause {
                     StartCause::ResumeTimeReached {
                         requested_resume, ..
                     } => {
                         debug!("Wakeup: timer (requested: {:?})", requested_resume);
-
-                        let item = self
-                            .resumes
-                            .first()
-                            .cloned()
-                            .unwrap_or_else(|| panic!("timer wakeup without resume"));
-                        assert_eq!(item.0, requested_resume);
-
-                        let resume = if let Some(w) = self.windows.get_mut(&item.1) {
-                            let (action, resume) = w.update_timer(&mut self.shared);
-                            actions.push((item.1, action));
-                            resume
-                        } else {
-                            // presumably, some window with active timers was removed
-                            None
-                        };
-
-                        if let Some(instant) = resume {
-                            self.resumes[0].0 = instant;
-                        } else {
-                            self.resumes.remove(0);
-                        }
                     }
                     StartCause::WaitCancelled { .. } => {
                         // This event serves no purpose?
@@ -144,14 +215,26 @@ impl<CB: CustomPipeBuilder, T: Theme<DrawPipe<CB::Pipe>>> Loop<CB, T> {
                         // We use this to check pending actions after removing windows
                     }
                     StartCause::Init => {
+                        // Windows are initialised above. (With run_return,
+                        // this cause is seen each time the loop is entered.)
                         debug!("Wakeup: init");
-
-                        for (id, window) in self.windows.iter_mut() {
-                            let action = window.init(&mut self.shared);
-                            actions.push((*id, action));
-                        }
                     }
                 }
+
+                // Resume all windows with a timer due, whatever the cause:
+                // the schedule may have changed since the wakeup was
+                // requested, and Toolkit::pump_events only starts with Init.
+                for id in due(&self.resumes, Instant::now()) {
+                    let resume = if let Some(w) = self.windows.get_mut(&id) {
+                        let (action, resume) = w.update_timer(&mut self.shared);
+                        actions.push((id, action));
+                        resume
+                    } else {
+                        // presumably, some window with active timers was removed
+                        None
+                    };
+                    set_resume(&mut self.resumes, resume, id);
+                }
             }
 
             RedrawRequested(id) => {
@@ -166,11 +249,33 @@ impl<CB: CustomPipeBuilder, T: Theme<DrawPipe<CB::Pipe>>> Loop<CB, T> {
         // Create and init() any new windows.
         while let Some(pending) = self.shared.pending.pop() {
             match pending {
-                PendingAction::AddWindow(id, widget) => {
+                PendingAction::AddWindow(id, widget, parent) => {
                     debug!("Adding window {}", widget.title());
-                    match Window::new(&mut self.shared, elwt, widget) {
+                    let (position, parent_window) = match parent {
+                        Some(parent) => {
+                            let parent_id = self.id_map.iter().find(|(_, w)| **w == parent);
+                            let position = match parent_id {
+                                Some((id, _)) => WindowPosition::CentreOnParent(*id),
+                                None => WindowPosition::Centre,
+                            };
+                            (position, self.windows.get(&parent).map(|w| &w.window))
+                        }
+                        None => (WindowPosition::Default, None),
+                    };
+                    match Window::new(
+                        &mut self.shared,
+                        elwt,
+                        widget,
+                        position,
+                        parent_window,
+                        None,
+                    ) {
                         Ok(mut window) => {
                             let wid = window.window.id();
+                            if let Some(parent) = parent {
+                                window.window.set_always_on_top(true);
+                                self.modals.insert(wid, parent);
+                            }
 
                             let action = window.init(&mut self.shared);
                             actions.push((wid, action));
@@ -188,6 +293,23 @@ impl<CB: CustomPipeBuilder, T: Theme<DrawPipe<CB::Pipe>>> Loop<CB, T> {
                         actions.push((*id, TkAction::Close));
                     }
                 }
+                PendingAction::SetDpiOverride(Some(id), factor) => {
+                    if let Some(window) = self.windows.get_mut(&id) {
+                        window.set_dpi_override(&self.shared, factor);
+                    }
+                }
+                // Only used by embedded targets
+                PendingAction::SetDpiOverride(None, _) => (),
+                PendingAction::AccessNotify(Some(wid), event) => {
+                    let id = self.id_map.iter().find(|(_, w)| **w == wid);
+                    let handler = self.shared.access_handler.as_mut();
+                    if let (Some((id, _)), Some(window), Some(handler)) =
+                        (id, self.windows.get(&wid), handler)
+                    {
+                        handler(*id, &window.access_tree(), event);
+                    }
+                }
+                PendingAction::AccessNotify(None, _) => (),
                 PendingAction::ThemeResize => {
                     for (_, window) in self.windows.iter_mut() {
                         window.theme_resize(&self.shared);
@@ -195,53 +317,86 @@ impl<CB: CustomPipeBuilder, T: Theme<DrawPipe<CB::Pipe>>> Loop<CB, T> {
                 }
                 PendingAction::RedrawAll => {
                     for (_, window) in self.windows.iter_mut() {
-                        window.window.request_redraw();
+                        window.redraw_all();
                     }
                 }
                 PendingAction::Update(handle, payload) => {
                     for (id, window) in self.windows.iter_mut() {
-                        let action = window.update_handle(&mut self.shared, handle, payload);
+                        let (action, resume) =
+                            window.update_handle(&mut self.shared, handle, payload);
                         actions.push((*id, action));
+                        set_resume(&mut self.resumes, resume, *id);
+                    }
+                    have_new_resumes = true;
+                }
+                PendingAction::UpdateData(handle) => {
+                    let (values, connected) = match self.shared.update_channels.get(&handle) {
+                        Some(drain) => drain(),
+                        None => (vec![], true),
+                    };
+                    if !connected {
+                        // All senders are dropped and all values are drained
+                        self.shared.update_channels.remove(&handle);
+                    }
+                    for data in values {
+                        for (id, window) in self.windows.iter_mut() {
+                            let (action, resume) =
+                                window.update_data(&mut self.shared, handle, data.clone());
+                            actions.push((*id, action));
+                            set_resume(&mut self.resumes, resume, *id);
+                        }
+                        have_new_resumes = true;
                     }
                 }
             }
         }
 
         while let Some((id, action)) = actions.pop() {
+            #[cfg(feature = "trace_events")]
+            trace!(target: "kas::event", "Window {:?}: {:?}", id, action);
             match action {
                 TkAction::None => (),
                 TkAction::Redraw => {
-                    self.windows.get(&id).map(|w| w.window.request_redraw());
+                    if let Some(window) = self.windows.get(&id) {
+                        window.request_redraw();
+                    }
                 }
                 TkAction::RegionMoved => {
                     if let Some(window) = self.windows.get_mut(&id) {
                         window.handle_moved();
-                        window.window.request_redraw();
+                        window.request_redraw();
                     }
                 }
                 TkAction::Reconfigure => {
                     if let Some(window) = self.windows.get_mut(&id) {
-                        if let Some(instant) = window.reconfigure(&mut self.shared) {
-                            add_resume(&mut self.resumes, instant, id);
-                            have_new_resumes = true;
-                        }
+                        let resume = window.reconfigure(&mut self.shared);
+                        set_resume(&mut self.resumes, resume, id);
+                        have_new_resumes = true;
+                    }
+                }
+                TkAction::RequestClose => {
+                    if let Some(window) = self.windows.get_mut(&id) {
+                        let action = window.request_close(&mut self.shared);
+                        actions.push((id, action));
                     }
                 }
                 TkAction::Close => {
-                    if let Some(window) = self.windows.remove(&id) {
-                        if window.handle_closure(&mut self.shared) == TkAction::CloseAll {
+                    if let Some(action) = self.close_window(id) {
+                        if action == TkAction::CloseAll {
                             actions.push((id, TkAction::CloseAll));
                         }
+                        // Removing a modal re-enables its parent. Modals over
+                        // this window are closed with it.
+                        self.modals.remove(&id);
+                        for (modal, _) in self.modals.iter().filter(|(_, parent)| **parent == id) {
+                            actions.push((*modal, TkAction::Close));
+                        }
                         // Wake immediately in order to evaluate pending actions:
                         *control_flow = ControlFlow::Poll;
                     }
                 }
                 TkAction::CloseAll => {
-                    for (_id, window) in self.windows.drain() {
-                        let _ = window.handle_closure(&mut self.shared);
-                        // Pending actions are not evaluated; this is ok.
-                    }
-                    self.id_map.clear();
+                    self.close_all();
                     *control_flow = ControlFlow::Exit;
                 }
             }
@@ -263,3 +418,143 @@ impl<CB: CustomPipeBuilder, T: Theme<DrawPipe<CB::Pipe>>> Loop<CB, T> {
         }
     }
 }
+
+impl<CB: CustomPipeBuilder, T: Theme<DrawPipe<CB::Pipe>>> Loop<CB, T> {
+    /// Close a window, running its callbacks
+    ///
+    /// The window's final callback (if any) is run before the window is
+    /// dropped. Returns `None` if the window does not exist (e.g. because it
+    /// was already closed), otherwise the action returned by callbacks.
+    fn close_window(&mut self, id: ww::WindowId) -> Option<TkAction> {
+        let window = self.windows.remove(&id)?;
+        self.report_geometry(id, &window);
+        self.resumes.retain(|item| item.1 != id);
+        self.uninit.retain(|wid| *wid != id);
+        self.id_map.retain(|_, wid| *wid != id);
+        Some(window.handle_closure(&mut self.shared))
+    }
+
+    /// Close all windows
+    ///
+    /// Modal windows are closed before their parents; otherwise windows are
+    /// closed in the order they were opened. Actions returned by callbacks
+    /// are not evaluated.
+    pub(crate) fn close_all(&mut self) {
+        let mut order: Vec<_> = self.id_map.iter().map(|(id, wid)| (*id, *wid)).collect();
+        order.sort_by_key(|item| item.0);
+        while !order.is_empty() {
+            // Modals cannot form a cycle, so some window has no open modal
+            let modals = &self.modals;
+            let index = order
+                .iter()
+                .position(|(_, wid)| !modals.values().any(|parent| parent == wid))
+                .unwrap_or(0);
+            let (_, wid) = order.remove(index);
+            self.modals.remove(&wid);
+            let _ = self.close_window(wid);
+        }
+
+        // All windows should have an id, but make sure:
+        let remaining: Vec<_> = self.windows.keys().cloned().collect();
+        for wid in remaining {
+            let _ = self.close_window(wid);
+        }
+        self.modals.clear();
+    }
+
+    /// Report the geometry of a closing window to the geometry handler, if any
+    fn report_geometry(&mut self, wid: ww::WindowId, window: &Window<CB::Pipe, T::Window>) {
+        if let Some(handler) = self.shared.geometry_handler.as_mut() {
+            if let Some((id, _)) = self.id_map.iter().find(|(_, w)| **w == wid) {
+                handler(*id, window.geometry());
+            }
+        }
+    }
+}
+
+impl<CB: CustomPipeBuilder, T: Theme<DrawPipe<CB::Pipe>>> Drop for Loop<CB, T> {
+    fn drop(&mut self) {
+        // Windows remain if the toolkit is dropped after run_return or
+        // pump_events without closing them; their callbacks must still run.
+        self.close_all();
+    }
+}
+
+/// True if `event` is user input (which is blocked by modal windows)
+fn is_input(event: &winit::event::WindowEvent) -> bool {
+    use winit::event::WindowEvent::*;
+    matches!(
+        event,
+        CloseRequested
+            | DroppedFile(_)
+            | HoveredFile(_)
+            | ReceivedCharacter(_)
+            | KeyboardInput { .. }
+            | CursorMoved { .. }
+            | CursorEntered { .. }
+            | MouseWheel { .. }
+            | MouseInput { .. }
+            | TouchpadPressure { .. }
+            | AxisMotion { .. }
+            | Touch(_)
+    )
+}
+
+/// Set (or with `None`, remove) the resume time of `id`
+fn set_resume<Id: PartialEq>(resumes: &mut Vec<(Instant, Id)>, resume: Option<Instant>, id: Id) {
+    let index = resumes.iter().position(|item| item.1 == id);
+    match (index, resume) {
+        (Some(i), Some(instant)) => resumes[i].0 = instant,
+        (Some(i), None) => {
+            resumes.remove(i);
+        }
+        (None, Some(instant)) => resumes.push((instant, id)),
+        (None, None) => (),
+    }
+}
+
+/// Ids of all `resumes` which are due at `now`
+fn due<Id: Copy>(resumes: &[(Instant, Id)], now: Instant) -> SmallVec<[Id; 2]> {
+    resumes
+        .iter()
+        .filter(|item| item.0 <= now)
+        .map(|item| item.1)
+        .collect()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use std::time::Duration;
+
+    #[test]
+    fn timer_schedule() {
+        let now = Instant::now();
+        let late = now + Duration::from_secs(10);
+        let soon = now + Duration::from_millis(1);
+        let mut resumes = vec![];
+        set_resume(&mut resumes, Some(late), 0);
+        assert!(due(&resumes, now).is_empty());
+
+        // A sooner timer must bring the next resume forward
+        set_resume(&mut resumes, Some(soon), 1);
+        resumes.sort_by_key(|item| item.0);
+        assert_eq!(resumes, [(soon, 1), (late, 0)]);
+        assert_eq!(due(&resumes, soon).as_slice(), [1]);
+
+        // Re-scheduling a window replaces its resume
+        set_resume(&mut resumes, Some(now), 0);
+        resumes.sort_by_key(|item| item.0);
+        assert_eq!(resumes, [(now, 0), (soon, 1)]);
+        assert_eq!(due(&resumes, now).as_slice(), [0]);
+        assert_eq!(due(&resumes, late).as_slice(), [0, 1]);
+
+        // Once a timer is done, its window is removed from the schedule
+        set_resume(&mut resumes, None, 0);
+        assert_eq!(resumes, [(soon, 1)]);
+        set_resume(&mut resumes, None, 0);
+        assert_eq!(resumes, [(soon, 1)]);
+        set_resume(&mut resumes, None, 1);
+        assert!(resumes.is_empty());
+    }
+}
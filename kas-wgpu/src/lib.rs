@@ -15,24 +15,36 @@
 #![cfg_attr(feature = "gat", feature(generic_associated_types))]
 
 pub mod draw;
+mod embed;
 mod event_loop;
 pub mod options;
 mod shared;
 mod window;
 
+use std::any::Any;
+use std::collections::VecDeque;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Instant;
 use std::{error, fmt};
 
+use kas::access::{AccessEvent, AccessNode};
 use kas::event::UpdateHandle;
+use kas::widget::{dialog_channel, DialogHandle, DialogSender};
 use kas::WindowId;
 use kas_theme::Theme;
+use log::warn;
 use winit::error::OsError;
 use winit::event_loop::{EventLoop, EventLoopProxy};
 
-use crate::draw::{CustomPipeBuilder, DrawPipe};
-use crate::shared::SharedState;
+use crate::draw::{CustomPipeBuilder, DrawPipe, RenderStats};
+use crate::shared::{SharedState, WindowIdFactory};
 use window::Window;
 
-pub use options::Options;
+pub use embed::{EmbedTarget, Embedded};
+pub use window::{ParseGeometryError, WindowGeometry, WindowPosition};
+
+pub use options::{AdapterInfo, ColourFilter, GlyphCacheOptions, Options, OptionsBuilder};
 
 pub use kas;
 pub use kas_theme as theme;
@@ -50,9 +62,14 @@ pub enum Error {
     ///
     /// This can be a driver/configuration issue or hardware limitation. Note
     /// that for now, `wgpu` only supports DX11, DX12, Vulkan and Metal.
-    NoAdapter,
-    #[doc(hidden)]
-    /// Shaders failed to compile (likely internal issue)
+    ///
+    /// The parameter is the set of backends requested (see
+    /// [`Options::backends`]); no fallback to other backends is attempted.
+    NoAdapter(wgpu::BackendBit),
+    /// Shader compilation failed
+    ///
+    /// For built-in shaders this is likely an internal issue. The message
+    /// includes the shader name and line number of any errors.
     ShaderCompilation(shaderc::Error),
     /// OS error during window creation
     Window(OsError),
@@ -61,7 +78,11 @@ pub enum Error {
 impl fmt::Display for Error {
     fn fmt(&self, f: &mut fmt::Formatter) -> Result<(), fmt::Error> {
         match self {
-            Error::NoAdapter => write!(f, "no suitable graphics adapter found"),
+            Error::NoAdapter(backends) => write!(
+                f,
+                "no suitable graphics adapter found for backend {:?}",
+                backends
+            ),
             Error::ShaderCompilation(e) => write!(f, "shader compilation failed: {}", e),
             Error::Window(e) => write!(f, "window creation error: {}", e),
         }
@@ -84,9 +105,28 @@ impl From<shaderc::Error> for Error {
 
 /// Builds a toolkit over a `winit::event_loop::EventLoop`.
 pub struct Toolkit<CB: CustomPipeBuilder, T: Theme<DrawPipe<CB::Pipe>>> {
+    // Note: windows (in state) are closed on drop, and must be dropped
+    // before the event loop.
+    state: event_loop::Loop<CB, T>,
     el: EventLoop<ProxyAction>,
-    windows: Vec<(WindowId, Window<CB::Pipe, T::Window>)>,
-    shared: SharedState<CB, T>,
+}
+
+/// Apply theme-related options to `theme`
+///
+/// Returns the configured light direction, if any.
+fn configure_theme<D, T: Theme<D>>(theme: &mut T, options: &Options) -> Option<(f32, f32)> {
+    let config = options.load_theme_config();
+    let _ = theme.apply_config(&config);
+    if options.high_contrast {
+        let _ = theme.set_high_contrast(true);
+    }
+    config.light_direction.filter(|dir| {
+        let valid = dir.0 >= 0.0 && dir.0 < std::f32::consts::FRAC_PI_2;
+        if !valid {
+            warn!("Theme config: light_direction angle out of range");
+        }
+        valid
+    })
 }
 
 impl<T: Theme<DrawPipe<()>> + 'static> Toolkit<(), T> {
@@ -107,11 +147,15 @@ impl<CB: CustomPipeBuilder + 'static, T: Theme<DrawPipe<CB::Pipe>> + 'static> To
     ///
     /// The [`Options`] parameter allows direct specification of toolkit
     /// options; usually, these are provided by [`Options::from_env`].
-    pub fn new_custom(custom: CB, theme: T, options: Options) -> Result<Self, Error> {
+    pub fn new_custom(custom: CB, mut theme: T, options: Options) -> Result<Self, Error> {
+        let light_dir = configure_theme(&mut theme, &options);
+        let mut shared = SharedState::new(custom, theme, options)?;
+        if let Some(dir) = light_dir {
+            shared.light_dir = dir;
+        }
         Ok(Toolkit {
             el: EventLoop::with_user_event(),
-            windows: vec![],
-            shared: SharedState::new(custom, theme, options)?,
+            state: event_loop::Loop::new(shared),
         })
     }
 
@@ -126,24 +170,277 @@ impl<CB: CustomPipeBuilder + 'static, T: Theme<DrawPipe<CB::Pipe>> + 'static> To
 
     /// Add a boxed window directly
     pub fn add_boxed(&mut self, widget: Box<dyn kas::Window>) -> Result<WindowId, Error> {
-        let win = Window::new(&mut self.shared, &self.el, widget)?;
-        let id = self.shared.next_window_id();
-        self.windows.push((id, win));
+        self.add_boxed_at(widget, WindowPosition::Default)
+    }
+
+    /// Add a window with the given initial position
+    ///
+    /// If the resulting window would not be on any monitor, the OS chooses
+    /// the position instead.
+    pub fn add_at<W: kas::Window + 'static>(
+        &mut self,
+        window: W,
+        position: WindowPosition,
+    ) -> Result<WindowId, Error> {
+        self.add_boxed_at(Box::new(window), position)
+    }
+
+    fn add_boxed_at(
+        &mut self,
+        widget: Box<dyn kas::Window>,
+        position: WindowPosition,
+    ) -> Result<WindowId, Error> {
+        Ok(self.state.add_window(&self.el, widget, position, None)?)
+    }
+
+    /// Add a window with a saved geometry
+    ///
+    /// The window is given the size, position and maximized state of
+    /// `geometry`, as previously reported via
+    /// [`Toolkit::set_geometry_handler`]. If the position would be entirely
+    /// off-screen (e.g. because a monitor was unplugged), it is clamped onto
+    /// the primary monitor.
+    pub fn add_with_geometry<W: kas::Window + 'static>(
+        &mut self,
+        window: W,
+        geometry: WindowGeometry,
+    ) -> Result<WindowId, Error> {
+        let widget = Box::new(window);
+        let position = WindowPosition::Default;
+        Ok(self
+            .state
+            .add_window(&self.el, widget, position, Some(&geometry))?)
+    }
+
+    /// Set a handler for the geometry of closing windows
+    ///
+    /// The handler is called with the final geometry of each window just
+    /// before it is closed (including when all windows close together),
+    /// allowing the application to save it for [`Toolkit::add_with_geometry`].
+    pub fn set_geometry_handler<F: FnMut(WindowId, WindowGeometry) + 'static>(&mut self, f: F) {
+        self.state.shared.geometry_handler = Some(Box::new(f));
+    }
+
+    /// Set a handler for accessibility notifications
+    ///
+    /// `kas_wgpu` does not include a platform accessibility adapter; instead,
+    /// for each [`AccessEvent`] reported via [`kas::event::Manager::access_notify`],
+    /// the handler is called with the window's identifier, its current
+    /// accessibility tree and the event, allowing an adapter to be connected.
+    /// Without a handler, notifications are ignored and no tree is built.
+    pub fn set_access_handler<F>(&mut self, f: F)
+    where
+        F: FnMut(WindowId, &AccessNode, AccessEvent) + 'static,
+    {
+        self.state.shared.access_handler = Some(Box::new(f));
+    }
+
+    /// Assume ownership of and display a modal window
+    ///
+    /// The new window is modal over `parent`: until it is closed, `parent`
+    /// ignores user input and the new window is kept on top (initially centred
+    /// over `parent`). When the modal window closes, `parent` is re-enabled.
+    ///
+    /// Modal windows may also be opened during event handling via
+    /// [`kas::event::Manager::add_modal`].
+    pub fn add_modal<W: kas::Window + 'static>(
+        &mut self,
+        parent: WindowId,
+        window: W,
+    ) -> Result<WindowId, Error> {
+        let position = WindowPosition::CentreOnParent(parent);
+        let id = self.add_boxed_at(Box::new(window), position)?;
+        self.state.set_modal(id, parent);
         Ok(id)
     }
 
+    /// Assume ownership of and display a modal dialog, returning a handle to
+    /// its result
+    ///
+    /// The closure `f` constructs the dialog from a [`DialogSender`]; the
+    /// dialog is then added as with [`Toolkit::add_modal`]. This is the
+    /// toolkit equivalent of [`kas::event::Manager::add_dialog`].
+    pub fn add_dialog<R, W, F>(
+        &mut self,
+        parent: WindowId,
+        f: F,
+    ) -> Result<(WindowId, DialogHandle<R>), Error>
+    where
+        W: kas::Window + 'static,
+        F: FnOnce(DialogSender<R>) -> W,
+    {
+        let (sender, handle) = dialog_channel();
+        let id = self.add_modal(parent, f(sender))?;
+        Ok((id, handle))
+    }
+
+    /// Access the `winit` window for `id`
+    ///
+    /// This allows use of `winit` functionality not otherwise exposed by the
+    /// toolkit. Returns `None` if there is no window with this `id`.
+    ///
+    /// Note that the toolkit is not notified of changes made through this
+    /// handle: in particular, changing the window size may desync the swap
+    /// chain until the next resize event.
+    pub fn with_winit_window<R, F: FnOnce(&winit::window::Window) -> R>(
+        &self,
+        id: WindowId,
+        f: F,
+    ) -> Option<R> {
+        self.state.winit_window(id).map(f)
+    }
+
+    /// Information about the graphics adapter in use
+    ///
+    /// This is also logged (at info level) on construction.
+    pub fn adapter_info(&self) -> &AdapterInfo {
+        // Always set when the toolkit requested the adapter
+        self.state.shared.adapter_info.as_ref().unwrap()
+    }
+
+    /// Statistics of the last frame drawn by window `id`
+    ///
+    /// Returns `None` if there is no window with this `id` or if statistics
+    /// are not enabled (see [`Options::render_stats`]).
+    pub fn render_stats(&self, id: WindowId) -> Option<RenderStats> {
+        self.state.render_stats(id)
+    }
+
+    /// Create an update channel
+    ///
+    /// Values of type `T` sent via the returned [`UpdateSender`] (usually from
+    /// another thread) are delivered to all widgets subscribed to the returned
+    /// [`UpdateHandle`] (see [`kas::event::Manager::update_on_handle`]) as
+    /// [`kas::event::Action::UpdateData`].
+    ///
+    /// The `policy` controls how values are queued when sent faster than the
+    /// UI processes them.
+    pub fn create_update_channel<U: Send + Sync + 'static>(
+        &mut self,
+        policy: UpdatePolicy,
+    ) -> (UpdateSender<U>, UpdateHandle) {
+        let handle = UpdateHandle::new();
+        let queue = Arc::new(Mutex::new(VecDeque::new()));
+        let senders = Arc::new(AtomicUsize::new(1));
+        let (q2, s2) = (queue.clone(), senders.clone());
+        let drain = move || -> (Vec<Arc<dyn Any + Send + Sync>>, bool) {
+            // Check for senders first: a value may be sent before disconnecting
+            let connected = s2.load(Ordering::SeqCst) > 0;
+            let mut queue = q2.lock().unwrap_or_else(|e| e.into_inner());
+            let values = queue.drain(..).map(|v: U| Arc::new(v) as _).collect();
+            (values, connected)
+        };
+        self.state
+            .shared
+            .update_channels
+            .insert(handle, Box::new(drain));
+        let sender = UpdateSender {
+            proxy: self.el.create_proxy(),
+            queue,
+            senders,
+            policy,
+            handle,
+        };
+        (sender, handle)
+    }
+
     /// Create a proxy which can be used to update the UI from another thread
     pub fn create_proxy(&self) -> ToolkitProxy {
         ToolkitProxy {
             proxy: self.el.create_proxy(),
+            window_id_factory: self.state.shared.window_id_factory.clone(),
         }
     }
 
     /// Run the main loop.
+    ///
+    /// With the `shader_reload` feature, built-in shaders are reloaded from
+    /// the source directory whenever changed.
     pub fn run(self) -> ! {
-        let mut el = event_loop::Loop::new(self.windows, self.shared);
+        #[cfg(feature = "shader_reload")]
+        draw::spawn_watcher(self.el.create_proxy());
+
+        let mut state = self.state;
+        self.el
+            .run(move |event, elwt, control_flow| state.handle(event, elwt, control_flow))
+    }
+
+    /// Run the main loop until all windows are closed, then return
+    ///
+    /// This is an alternative to [`Toolkit::run`] for applications which
+    /// must regain control after the UI closes. It is only available on
+    /// desktop platforms (Windows, MacOS, Linux and BSDs).
+    ///
+    /// Note: on some platforms the loop may block during OS operations such
+    /// as window resizing. (The `shader_reload` feature has no effect here.)
+    #[cfg(any(
+        target_os = "windows",
+        target_os = "macos",
+        target_os = "linux",
+        target_os = "dragonfly",
+        target_os = "freebsd",
+        target_os = "netbsd",
+        target_os = "openbsd"
+    ))]
+    pub fn run_return(&mut self) {
+        use winit::platform::desktop::EventLoopExtDesktop;
+        let state = &mut self.state;
         self.el
-            .run(move |event, elwt, control_flow| el.handle(event, elwt, control_flow))
+            .run_return(|event, elwt, control_flow| state.handle(event, elwt, control_flow));
+    }
+
+    /// Process pending events, then return
+    ///
+    /// This handles all events currently queued (including redraws and due
+    /// timers) without waiting for new events, allowing the toolkit to be
+    /// driven from a loop controlled by the host application. It should be
+    /// called regularly (e.g. once per host frame).
+    ///
+    /// Returns false once all windows are closed.
+    ///
+    /// Availability is as for [`Toolkit::run_return`].
+    #[cfg(any(
+        target_os = "windows",
+        target_os = "macos",
+        target_os = "linux",
+        target_os = "dragonfly",
+        target_os = "freebsd",
+        target_os = "netbsd",
+        target_os = "openbsd"
+    ))]
+    pub fn pump_events(&mut self) -> bool {
+        use winit::event::Event;
+        use winit::event_loop::ControlFlow;
+        use winit::platform::desktop::EventLoopExtDesktop;
+        let state = &mut self.state;
+        self.el.run_return(|event, elwt, control_flow| {
+            let last = matches!(event, Event::RedrawEventsCleared);
+            state.handle(event, elwt, control_flow);
+            if last {
+                *control_flow = ControlFlow::Exit;
+            }
+        });
+        !state.is_empty()
+    }
+
+    /// True if any window has a pending redraw
+    ///
+    /// This reflects redraws requested by widgets (including due to
+    /// reconfiguration) which have not yet been drawn. When driving the
+    /// toolkit via [`Toolkit::pump_events`], redraws are usually handled
+    /// before it returns; a true value indicates that pumping again will draw.
+    #[inline]
+    pub fn needs_redraw(&self) -> bool {
+        self.state.needs_redraw()
+    }
+
+    /// The time of the next timer update (if any)
+    ///
+    /// Animated widgets schedule timer updates. A host loop using
+    /// [`Toolkit::pump_events`] should pump again no later than this.
+    #[inline]
+    pub fn next_timer(&self) -> Option<Instant> {
+        self.state.next_timer()
     }
 }
 
@@ -152,14 +449,28 @@ impl<CB: CustomPipeBuilder + 'static, T: Theme<DrawPipe<CB::Pipe>> + 'static> To
 /// Created by [`Toolkit::create_proxy`].
 pub struct ToolkitProxy {
     proxy: EventLoopProxy<ProxyAction>,
+    window_id_factory: WindowIdFactory,
 }
 
 /// Error type returned by [`ToolkitProxy`] functions.
 ///
 /// This error occurs only if the [`Toolkit`] already terminated.
+#[derive(Debug)]
 pub struct ClosedError;
 
 impl ToolkitProxy {
+    /// Add a window
+    ///
+    /// The window is constructed by the event loop; any error is logged. Note
+    /// that, since the window is sent to the UI thread, it must be `Send`.
+    pub fn add_window(&self, widget: Box<dyn kas::Window + Send>) -> Result<WindowId, ClosedError> {
+        let id = self.window_id_factory.make_next();
+        self.proxy
+            .send_event(ProxyAction::AddWindow(id, widget))
+            .map_err(|_| ClosedError)?;
+        Ok(id)
+    }
+
     /// Close a specific window.
     pub fn close(&self, id: WindowId) -> Result<(), ClosedError> {
         self.proxy
@@ -168,23 +479,122 @@ impl ToolkitProxy {
     }
 
     /// Close all windows and terminate the UI.
+    ///
+    /// Each window's final callback is run exactly once, with modal windows
+    /// closed before their parents and otherwise in the order windows were
+    /// opened.
     pub fn close_all(&self) -> Result<(), ClosedError> {
         self.proxy
             .send_event(ProxyAction::CloseAll)
             .map_err(|_| ClosedError)
     }
 
+    /// Redraw a specific window
+    ///
+    /// This does nothing if the window does not exist (or was closed).
+    pub fn redraw(&self, id: WindowId) -> Result<(), ClosedError> {
+        self.proxy
+            .send_event(ProxyAction::Redraw(id))
+            .map_err(|_| ClosedError)
+    }
+
     /// Trigger an update handle
     pub fn trigger_update(&self, handle: UpdateHandle, payload: u64) -> Result<(), ClosedError> {
         self.proxy
             .send_event(ProxyAction::Update(handle, payload))
             .map_err(|_| ClosedError)
     }
+
+    /// Set the colour filter of all windows
+    ///
+    /// See [`Options::colour_filter`].
+    pub fn set_colour_filter(&self, filter: ColourFilter) -> Result<(), ClosedError> {
+        self.proxy
+            .send_event(ProxyAction::SetColourFilter(filter))
+            .map_err(|_| ClosedError)
+    }
+}
+
+/// Queueing policy of an update channel
+///
+/// See [`Toolkit::create_update_channel`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum UpdatePolicy {
+    /// Only the latest value not yet delivered is kept
+    KeepLatest,
+    /// All values are queued and delivered in order
+    QueueAll,
+}
+
+/// The sending half of an update channel
+///
+/// Created by [`Toolkit::create_update_channel`]. The channel is removed once
+/// all senders are dropped and remaining values are delivered.
+pub struct UpdateSender<U> {
+    proxy: EventLoopProxy<ProxyAction>,
+    queue: Arc<Mutex<VecDeque<U>>>,
+    /// Number of senders, for disconnection
+    senders: Arc<AtomicUsize>,
+    policy: UpdatePolicy,
+    handle: UpdateHandle,
+}
+
+impl<U> Clone for UpdateSender<U> {
+    fn clone(&self) -> Self {
+        self.senders.fetch_add(1, Ordering::SeqCst);
+        UpdateSender {
+            proxy: self.proxy.clone(),
+            queue: self.queue.clone(),
+            senders: self.senders.clone(),
+            policy: self.policy,
+            handle: self.handle,
+        }
+    }
+}
+
+impl<U> Drop for UpdateSender<U> {
+    fn drop(&mut self) {
+        if self.senders.fetch_sub(1, Ordering::SeqCst) == 1 {
+            // Wake the event loop to remove the channel; this fails only if
+            // the loop has already exited.
+            let _ = self.proxy.send_event(ProxyAction::UpdateData(self.handle));
+        }
+    }
+}
+
+impl<U> UpdateSender<U> {
+    /// Send a value
+    ///
+    /// The event loop is woken only when the queue was empty; values sent
+    /// before the UI processes the queue are coalesced according to the
+    /// channel's [`UpdatePolicy`].
+    pub fn send(&self, value: U) -> Result<(), ClosedError> {
+        let mut queue = self.queue.lock().unwrap_or_else(|e| e.into_inner());
+        let wake = queue.is_empty();
+        if self.policy == UpdatePolicy::KeepLatest {
+            queue.clear();
+        }
+        queue.push_back(value);
+        drop(queue);
+
+        if wake {
+            self.proxy
+                .send_event(ProxyAction::UpdateData(self.handle))
+                .map_err(|_| ClosedError)?;
+        }
+        Ok(())
+    }
 }
 
 #[derive(Debug)]
 enum ProxyAction {
+    AddWindow(WindowId, Box<dyn kas::Window + Send>),
     CloseAll,
     Close(WindowId),
+    Redraw(WindowId),
+    #[cfg(feature = "shader_reload")]
+    ReloadShaders,
     Update(UpdateHandle, u64),
+    UpdateData(UpdateHandle),
+    SetColourFilter(ColourFilter),
 }
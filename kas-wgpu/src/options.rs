@@ -5,16 +5,217 @@
 
 //! Options
 
+use crate::draw::EmojiFont;
+use kas::access::AccessPrefs;
+use kas_theme::ThemeConfig;
 use log::warn;
 use std::env::var;
-pub use wgpu::{BackendBit, PowerPreference};
+use std::fmt;
+use std::path::PathBuf;
+use std::sync::Arc;
+
+pub use wgpu::{BackendBit, PowerPreference, PresentMode, TextureFormat};
+
+/// Glyph cache options
+///
+/// Rasterised glyphs are stored in a texture atlas (one per window). When
+/// the atlas is full, glyphs not used by the current frame are evicted
+/// (least-recently-used first); the atlas only grows when a single frame
+/// needs more glyphs than fit. A larger initial size avoids re-uploading
+/// glyphs after growth at the cost of video memory, while larger tolerances
+/// let differently scaled or positioned glyphs share cache entries at some
+/// cost to rendering quality.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct GlyphCacheOptions {
+    /// Initial atlas size in pixels. Default value: `(256, 256)`.
+    pub initial_size: (u32, u32),
+    /// Maximum difference in scale for reuse of a cached glyph. Default
+    /// value: 0.5.
+    pub scale_tolerance: f32,
+    /// Maximum difference in sub-pixel position for reuse of a cached glyph.
+    /// Values of 1.0 or greater mean "don't care". Default value: 0.1.
+    pub position_tolerance: f32,
+    /// Whether to cache printable ASCII glyphs of the default font up-front.
+    /// Default value: false.
+    ///
+    /// When enabled, these glyphs are rasterised alongside the first text
+    /// drawn in the default font, at the same scale. This moves work from
+    /// later frames to the first frame.
+    pub precache_ascii: bool,
+}
+
+impl Default for GlyphCacheOptions {
+    fn default() -> Self {
+        GlyphCacheOptions {
+            initial_size: (256, 256),
+            scale_tolerance: 0.5,
+            position_tolerance: 0.1,
+            precache_ascii: false,
+        }
+    }
+}
+
+/// Colour filter applied to the rendered frame
+///
+/// Filters other than `None` simulate colour vision deficiencies (or remove
+/// colour entirely), allowing developers to check that a UI does not rely on
+/// colour alone. Simulations use the matrices of Machado, Oliveira and
+/// Fernandes (2009) at full severity, applied in linear RGB.
+///
+/// Filtering is a post-process over the whole frame, including text. When
+/// `None`, no extra pass or texture is used.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum ColourFilter {
+    /// No filter
+    #[default]
+    None,
+    /// Simulate protanopia (no red cones)
+    Protanopia,
+    /// Simulate deuteranopia (no green cones)
+    Deuteranopia,
+    /// Simulate tritanopia (no blue cones)
+    Tritanopia,
+    /// Convert to greyscale (luminance)
+    Greyscale,
+}
+
+impl ColourFilter {
+    /// Matrix applied to linear RGB, as rows (`w` components are unused)
+    ///
+    /// Returns `None` for [`ColourFilter::None`].
+    pub fn matrix(self) -> Option<[[f32; 4]; 3]> {
+        Some(match self {
+            ColourFilter::None => return None,
+            ColourFilter::Protanopia => [
+                [0.152286, 1.052583, -0.204868, 0.0],
+                [0.114503, 0.786281, 0.099216, 0.0],
+                [-0.003882, -0.048116, 1.051998, 0.0],
+            ],
+            ColourFilter::Deuteranopia => [
+                [0.367322, 0.860646, -0.227968, 0.0],
+                [0.280085, 0.672501, 0.047413, 0.0],
+                [-0.011820, 0.042940, 0.968881, 0.0],
+            ],
+            ColourFilter::Tritanopia => [
+                [1.255528, -0.076749, -0.178779, 0.0],
+                [-0.078411, 0.930809, 0.147602, 0.0],
+                [0.004733, 0.691367, 0.303900, 0.0],
+            ],
+            ColourFilter::Greyscale => {
+                let y = [0.2126, 0.7152, 0.0722, 0.0];
+                [y, y, y]
+            }
+        })
+    }
+}
+
+/// Information about the selected graphics adapter
+///
+/// See [`crate::Toolkit::adapter_info`].
+#[derive(Clone, Debug)]
+pub struct AdapterInfo {
+    /// Adapter name, PCI identifiers and device type
+    pub info: wgpu::AdapterInfo,
+    /// The backend in use (a single flag, unless it could not be determined)
+    pub backend: BackendBit,
+}
 
 /// Toolkit options
 pub struct Options {
     /// Adapter power preference. Default value: low power.
+    ///
+    /// On systems with both integrated and discrete graphics, `LowPower`
+    /// usually selects the integrated and `HighPerformance` the discrete
+    /// adapter.
     pub power_preference: PowerPreference,
     /// Adapter backend. Default value: PRIMARY (Vulkan/Metal/DX12).
+    ///
+    /// A single adapter is requested from all selected backends, chosen
+    /// according to [`Options::power_preference`]. If no adapter is available
+    /// for these backends, toolkit construction fails with
+    /// [`crate::Error::NoAdapter`]; other backends are not tried.
     pub backends: BackendBit,
+    /// Presentation mode of window surfaces. Default value: `Vsync`.
+    ///
+    /// `NoVsync` presents frames immediately, which may cause tearing.
+    pub present_mode: PresentMode,
+    /// Preferred format of window surfaces. Default value:
+    /// [`crate::draw::DEFAULT_FORMAT`].
+    ///
+    /// This may be set to use e.g. `Rgba8UnormSrgb` or the HDR-capable
+    /// `Rgba16Float`. Formats not satisfying
+    /// [`crate::draw::is_supported_format`] are replaced by the default
+    /// (with a warning), as are formats rejected by a window's surface (when
+    /// creating that window; later windows then use the default directly).
+    /// Only the default is supported on all primary backends.
+    pub surface_format: TextureFormat,
+    /// Whether windows have OS decorations (title bar, borders). Default
+    /// value: true.
+    pub decorations: bool,
+    /// Whether windows have a transparent background. Default value: false.
+    ///
+    /// When enabled, windows are cleared to transparent instead of the theme's
+    /// clear colour. Support depends on the platform and compositor.
+    pub transparent: bool,
+    /// Whether to snap text and rects to whole pixels. Default value: true.
+    ///
+    /// Centred and end-aligned single-line text is otherwise positioned at
+    /// fractional pixel offsets, which may look blurry. Likewise, rects and
+    /// frames drawn under a scaling or fractional translation (see
+    /// [`kas::draw::Draw::push_transform`]) are rounded to whole pixels,
+    /// unless rotated. Disable for smooth animation.
+    pub pixel_snap: bool,
+    /// DPI factor override. Default value: `None`.
+    ///
+    /// When set, windows use this DPI factor (scale factor) instead of that
+    /// reported by the monitor. This is intended for deterministic testing
+    /// and screenshots. Windows may also override this via
+    /// [`kas::event::Manager::set_dpi_override`].
+    pub dpi_factor: Option<f64>,
+    /// Whether to correct text coverage for gamma. Default value: false.
+    ///
+    /// Glyph coverage is otherwise blended linearly, which can make dark text
+    /// on a light background look thin, especially at small sizes. When
+    /// enabled, partial coverage of dark text is strengthened to approximate
+    /// gamma-correct blending; light text is unchanged. Sub-pixel (LCD)
+    /// anti-aliasing is not supported.
+    pub text_gamma: bool,
+    /// Whether to use high-contrast colours. Default value: false.
+    ///
+    /// The windowing system does not currently report this preference, so it
+    /// must be set here (or via the environment).
+    pub high_contrast: bool,
+    /// Whether to reduce non-essential animation. Default value: false.
+    ///
+    /// As with `high_contrast`, this is not currently read from the OS.
+    pub reduced_motion: bool,
+    /// Colour filter. Default value: [`ColourFilter::None`].
+    ///
+    /// This may be changed at run-time via
+    /// [`crate::ToolkitProxy::set_colour_filter`].
+    pub colour_filter: ColourFilter,
+    /// Glyph cache configuration
+    pub glyph_cache: GlyphCacheOptions,
+    /// Whether to gather rendering statistics. Default value: false.
+    ///
+    /// When enabled, vertex and pass counts of each frame are gathered; see
+    /// [`crate::draw::RenderStats`]. When disabled, no statistics are kept.
+    pub render_stats: bool,
+    /// Theme configuration file. Default value: `None`.
+    ///
+    /// When set, a [`ThemeConfig`] is read from this file on start and
+    /// applied to the theme; the light direction is used for shaded drawing.
+    /// If the file cannot be read, a warning is logged and the theme's
+    /// defaults are used. Requires the `theme_config` feature (not enabled
+    /// by default); without it the file is ignored with a warning.
+    pub theme_config: Option<PathBuf>,
+    /// Emoji font file. Default value: `None`.
+    ///
+    /// When set, characters missing from the theme's font are drawn using
+    /// this font where it has them. Colour glyphs (`COLR` and `CPAL` tables)
+    /// are drawn in colour; bitmap-only glyphs are drawn as a box. If the
+    /// file cannot be read, a warning is logged.
+    pub emoji_font: Option<PathBuf>,
 }
 
 impl Options {
@@ -23,6 +224,20 @@ impl Options {
         Options {
             power_preference: PowerPreference::LowPower,
             backends: BackendBit::PRIMARY,
+            present_mode: PresentMode::Vsync,
+            surface_format: crate::draw::DEFAULT_FORMAT,
+            decorations: true,
+            transparent: false,
+            pixel_snap: true,
+            dpi_factor: None,
+            text_gamma: false,
+            high_contrast: false,
+            reduced_motion: false,
+            colour_filter: ColourFilter::None,
+            glyph_cache: GlyphCacheOptions::default(),
+            render_stats: false,
+            theme_config: None,
+            emoji_font: None,
         }
     }
 
@@ -32,15 +247,15 @@ impl Options {
     ///
     /// ### Power preference
     ///
-    /// The `KAS_POWER_PREFERENCE` variable supports:
+    /// The `KAS_POWER_PREFERENCE` variable (or its alias `KAS_POWER`) supports:
     ///
     /// -   `Default`
-    /// -   `LowPower`
-    /// -   `HighPerformance`
+    /// -   `LowPower` or `Low`
+    /// -   `HighPerformance` or `High`
     ///
     /// ### Backend
     ///
-    /// The `KAS_BACKENDS` variable supports:
+    /// The `KAS_BACKENDS` variable (or its alias `KAS_BACKEND`) supports:
     ///
     /// -   `Vulkan`
     /// -   `GL`
@@ -49,49 +264,573 @@ impl Options {
     /// -   `DX12`
     /// -   `PRIMARY`: any of Vulkan, Metal or DX12
     /// -   `SECONDARY`: any of GL or DX11
+    ///
+    /// ### Present mode
+    ///
+    /// The `KAS_PRESENT_MODE` variable supports `Vsync` and `NoVsync`.
+    ///
+    /// ### Surface format
+    ///
+    /// The `KAS_SURFACE_FORMAT` variable supports:
+    ///
+    /// -   `BGRA8`: `Bgra8UnormSrgb`
+    /// -   `RGBA8`: `Rgba8UnormSrgb`
+    /// -   `RGBA16F`: `Rgba16Float`
+    ///
+    /// ### Pixel snapping
+    ///
+    /// The `KAS_PIXEL_SNAP` variable supports `TRUE` and `FALSE`.
+    ///
+    /// ### DPI factor
+    ///
+    /// The `KAS_DPI_FACTOR` variable may be set to a positive number to
+    /// override the DPI factor of all windows.
+    ///
+    /// ### Text gamma
+    ///
+    /// The `KAS_TEXT_GAMMA` variable supports `TRUE` and `FALSE`.
+    ///
+    /// ### Accessibility
+    ///
+    /// The `KAS_HIGH_CONTRAST` and `KAS_REDUCED_MOTION` variables support
+    /// `TRUE` and `FALSE`.
+    ///
+    /// ### Colour filter
+    ///
+    /// The `KAS_COLOUR_FILTER` variable supports `None`, `Protanopia`,
+    /// `Deuteranopia`, `Tritanopia` and `Greyscale`.
+    ///
+    /// ### Rendering statistics
+    ///
+    /// The `KAS_RENDER_STATS` variable supports `TRUE` and `FALSE`.
+    ///
+    /// ### Theme configuration
+    ///
+    /// The `KAS_THEME_CONFIG` variable may be set to the path of a theme
+    /// configuration file; see [`ThemeConfig`].
+    ///
+    /// ### Emoji font
+    ///
+    /// The `KAS_EMOJI_FONT` variable may be set to the path of a font file;
+    /// see [`Options::emoji_font`].
+    ///
+    /// Values not set by the environment are left at their defaults. To
+    /// layer programmatic configuration on top, use
+    /// [`OptionsBuilder::from_env`].
     pub fn from_env() -> Self {
         let mut options = Options::new();
 
-        if let Ok(mut v) = var("KAS_POWER_PREFERENCE") {
-            v.make_ascii_uppercase();
-            options.power_preference = match v.as_str() {
-                "DEFAULT" => PowerPreference::Default,
-                "LOWPOWER" => PowerPreference::LowPower,
-                "HIGHPERFORMANCE" => PowerPreference::HighPerformance,
-                other => {
-                    warn!(
-                        "Unexpected environment value: KAS_POWER_PREFERENCE={}",
-                        other
-                    );
-                    options.power_preference
-                }
+        if let Some((name, v)) = var_any(&["KAS_POWER_PREFERENCE", "KAS_POWER"]) {
+            let values = [
+                ("DEFAULT", PowerPreference::Default),
+                ("LOWPOWER", PowerPreference::LowPower),
+                ("LOW", PowerPreference::LowPower),
+                ("HIGHPERFORMANCE", PowerPreference::HighPerformance),
+                ("HIGH", PowerPreference::HighPerformance),
+            ];
+            if let Some(p) = parse_enum(name, &v, &values) {
+                options.power_preference = p;
+            }
+        }
+
+        if let Some((name, v)) = var_any(&["KAS_BACKENDS", "KAS_BACKEND"]) {
+            let values = [
+                ("VULKAN", BackendBit::VULKAN),
+                ("GL", BackendBit::GL),
+                ("METAL", BackendBit::METAL),
+                ("DX11", BackendBit::DX11),
+                ("DX12", BackendBit::DX12),
+                ("PRIMARY", BackendBit::PRIMARY),
+                ("SECONDARY", BackendBit::SECONDARY),
+            ];
+            if let Some(backends) = parse_enum(name, &v, &values) {
+                options.backends = backends;
             }
         }
 
-        if let Ok(mut v) = var("KAS_BACKENDS") {
-            v.make_ascii_uppercase();
-            options.backends = match v.as_str() {
-                "VULKAN" => BackendBit::VULKAN,
-                "GL" => BackendBit::GL,
-                "METAL" => BackendBit::METAL,
-                "DX11" => BackendBit::DX11,
-                "DX12" => BackendBit::DX12,
-                "PRIMARY" => BackendBit::PRIMARY,
-                "SECONDARY" => BackendBit::SECONDARY,
-                other => {
-                    warn!("Unexpected environment value: KAS_BACKENDS={}", other);
-                    options.backends
-                }
+        let values = [
+            ("VSYNC", PresentMode::Vsync),
+            ("NOVSYNC", PresentMode::NoVsync),
+        ];
+        if let Some(mode) = parse_env_enum("KAS_PRESENT_MODE", &values) {
+            options.present_mode = mode;
+        }
+
+        let values = [
+            ("BGRA8", TextureFormat::Bgra8UnormSrgb),
+            ("RGBA8", TextureFormat::Rgba8UnormSrgb),
+            ("RGBA16F", TextureFormat::Rgba16Float),
+        ];
+        if let Some(format) = parse_env_enum("KAS_SURFACE_FORMAT", &values) {
+            options.surface_format = format;
+        }
+
+        if let Some(snap) = parse_env_bool("KAS_PIXEL_SNAP") {
+            options.pixel_snap = snap;
+        }
+
+        if let Ok(v) = var("KAS_DPI_FACTOR") {
+            match v.parse::<f64>() {
+                Ok(factor) if factor > 0.0 => options.dpi_factor = Some(factor),
+                _ => warn!("Unexpected environment value: KAS_DPI_FACTOR={}", v),
             }
         }
 
+        if let Some(gamma) = parse_env_bool("KAS_TEXT_GAMMA") {
+            options.text_gamma = gamma;
+        }
+
+        if let Some(high_contrast) = parse_env_bool("KAS_HIGH_CONTRAST") {
+            options.high_contrast = high_contrast;
+        }
+
+        if let Some(reduced_motion) = parse_env_bool("KAS_REDUCED_MOTION") {
+            options.reduced_motion = reduced_motion;
+        }
+
+        let values = [
+            ("NONE", ColourFilter::None),
+            ("PROTANOPIA", ColourFilter::Protanopia),
+            ("DEUTERANOPIA", ColourFilter::Deuteranopia),
+            ("TRITANOPIA", ColourFilter::Tritanopia),
+            ("GREYSCALE", ColourFilter::Greyscale),
+        ];
+        if let Some(filter) = parse_env_enum("KAS_COLOUR_FILTER", &values) {
+            options.colour_filter = filter;
+        }
+
+        if let Some(stats) = parse_env_bool("KAS_RENDER_STATS") {
+            options.render_stats = stats;
+        }
+
+        if let Some(path) = std::env::var_os("KAS_THEME_CONFIG") {
+            options.theme_config = Some(path.into());
+        }
+
+        if let Some(path) = std::env::var_os("KAS_EMOJI_FONT") {
+            options.emoji_font = Some(path.into());
+        }
+
         options
     }
 
-    pub(crate) fn adapter_options(&self) -> wgpu::RequestAdapterOptions {
-        wgpu::RequestAdapterOptions {
+    /// Construct a builder, starting from default values
+    #[inline]
+    pub fn builder() -> OptionsBuilder {
+        OptionsBuilder(Options::new())
+    }
+
+    /// Load the theme configuration, if any
+    pub(crate) fn load_theme_config(&self) -> ThemeConfig {
+        let path = match self.theme_config.as_ref() {
+            Some(path) => path,
+            None => return ThemeConfig::default(),
+        };
+        #[cfg(feature = "theme_config")]
+        match ThemeConfig::load(path) {
+            Ok(config) => config,
+            Err(e) => {
+                warn!("{} ({})", e, path.display());
+                ThemeConfig::default()
+            }
+        }
+        #[cfg(not(feature = "theme_config"))]
+        {
+            warn!(
+                "Ignoring theme config {}: feature theme_config is not enabled",
+                path.display()
+            );
+            ThemeConfig::default()
+        }
+    }
+
+    /// Load the emoji font, if any
+    pub(crate) fn load_emoji_font(&self) -> Option<Arc<EmojiFont>> {
+        let path = self.emoji_font.as_ref()?;
+        match EmojiFont::load(path) {
+            Ok(font) => Some(Arc::new(font)),
+            Err(e) => {
+                warn!("Unable to load emoji font: {} ({})", e, path.display());
+                None
+            }
+        }
+    }
+
+    pub(crate) fn access_prefs(&self) -> AccessPrefs {
+        AccessPrefs {
+            high_contrast: self.high_contrast,
+            reduced_motion: self.reduced_motion,
+        }
+    }
+
+    /// Request an adapter, returning it with its backend
+    pub(crate) fn request_adapter(&self) -> Option<(wgpu::Adapter, BackendBit)> {
+        // Request from all backends at once: wgpu applies the power
+        // preference across backends (and aborts if a requested set of
+        // backends has no adapter, so we must not request them one by one).
+        let options = wgpu::RequestAdapterOptions {
             power_preference: self.power_preference,
             backends: self.backends,
+        };
+        let adapter = wgpu::Adapter::request(&options)?;
+        let backend = backend_from_debug(&format!("{:?}", adapter)).unwrap_or(self.backends);
+        Some((adapter, backend))
+    }
+}
+
+/// Get the backend of an adapter from its debug representation
+///
+/// wgpu 0.4 has no API for this, but the adapter's debug representation
+/// includes its id, which ends with the backend name.
+fn backend_from_debug(repr: &str) -> Option<BackendBit> {
+    let name = repr.trim_end_matches(|c: char| !c.is_alphanumeric());
+    let name = &name[name.rfind(|c: char| !c.is_alphanumeric())? + 1..];
+    Some(match name {
+        "Vulkan" => BackendBit::VULKAN,
+        "Metal" => BackendBit::METAL,
+        "Dx12" => BackendBit::DX12,
+        "Dx11" => BackendBit::DX11,
+        "Gl" => BackendBit::GL,
+        _ => return None,
+    })
+}
+
+impl Default for Options {
+    fn default() -> Self {
+        Options::new()
+    }
+}
+
+/// Error returned by [`OptionsBuilder::build`]
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum OptionsError {
+    /// The DPI factor is not a positive, finite number
+    InvalidDpiFactor(f64),
+    /// No backend is enabled
+    NoBackends,
+    /// The surface format is not supported by the draw pipes
+    ///
+    /// See [`crate::draw::is_supported_format`].
+    UnsupportedSurfaceFormat(TextureFormat),
+}
+
+impl fmt::Display for OptionsError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            OptionsError::InvalidDpiFactor(factor) => write!(f, "invalid DPI factor: {}", factor),
+            OptionsError::NoBackends => write!(f, "no backend enabled"),
+            OptionsError::UnsupportedSurfaceFormat(format) => {
+                write!(f, "unsupported surface format: {:?}", format)
+            }
         }
     }
 }
+
+impl std::error::Error for OptionsError {}
+
+/// Builder for [`Options`]
+///
+/// Construct via [`Options::builder`] (starting from default values) or
+/// [`OptionsBuilder::from_env`], then call setters and finally
+/// [`OptionsBuilder::build`]. Setters correspond to fields of [`Options`];
+/// see those for documentation.
+///
+/// ```no_run
+/// use kas_wgpu::options::{Options, PowerPreference};
+///
+/// let options = Options::builder()
+///     .power_preference(PowerPreference::HighPerformance)
+///     .dpi_factor(1.0)
+///     .build()
+///     .unwrap();
+/// ```
+#[must_use]
+#[derive(Default)]
+pub struct OptionsBuilder(Options);
+
+impl OptionsBuilder {
+    /// Construct, starting from values read from the environment
+    ///
+    /// See [`Options::from_env`].
+    #[inline]
+    pub fn from_env() -> Self {
+        OptionsBuilder(Options::from_env())
+    }
+
+    /// Set the adapter power preference
+    #[inline]
+    pub fn power_preference(mut self, power_preference: PowerPreference) -> Self {
+        self.0.power_preference = power_preference;
+        self
+    }
+
+    /// Set the adapter backends
+    #[inline]
+    pub fn backends(mut self, backends: BackendBit) -> Self {
+        self.0.backends = backends;
+        self
+    }
+
+    /// Set the presentation mode
+    #[inline]
+    pub fn present_mode(mut self, present_mode: PresentMode) -> Self {
+        self.0.present_mode = present_mode;
+        self
+    }
+
+    /// Set the preferred surface format
+    #[inline]
+    pub fn surface_format(mut self, surface_format: TextureFormat) -> Self {
+        self.0.surface_format = surface_format;
+        self
+    }
+
+    /// Set whether windows have OS decorations
+    #[inline]
+    pub fn decorations(mut self, decorations: bool) -> Self {
+        self.0.decorations = decorations;
+        self
+    }
+
+    /// Set whether windows have a transparent background
+    #[inline]
+    pub fn transparent(mut self, transparent: bool) -> Self {
+        self.0.transparent = transparent;
+        self
+    }
+
+    /// Set whether to snap text and rects to whole pixels
+    #[inline]
+    pub fn pixel_snap(mut self, pixel_snap: bool) -> Self {
+        self.0.pixel_snap = pixel_snap;
+        self
+    }
+
+    /// Force the DPI factor of all windows
+    #[inline]
+    pub fn dpi_factor(mut self, dpi_factor: f64) -> Self {
+        self.0.dpi_factor = Some(dpi_factor);
+        self
+    }
+
+    /// Set whether to correct text coverage for gamma
+    #[inline]
+    pub fn text_gamma(mut self, text_gamma: bool) -> Self {
+        self.0.text_gamma = text_gamma;
+        self
+    }
+
+    /// Set whether to use high-contrast colours
+    #[inline]
+    pub fn high_contrast(mut self, high_contrast: bool) -> Self {
+        self.0.high_contrast = high_contrast;
+        self
+    }
+
+    /// Set whether to reduce non-essential animation
+    #[inline]
+    pub fn reduced_motion(mut self, reduced_motion: bool) -> Self {
+        self.0.reduced_motion = reduced_motion;
+        self
+    }
+
+    /// Set the colour filter
+    #[inline]
+    pub fn colour_filter(mut self, colour_filter: ColourFilter) -> Self {
+        self.0.colour_filter = colour_filter;
+        self
+    }
+
+    /// Set glyph cache options
+    #[inline]
+    pub fn glyph_cache(mut self, glyph_cache: GlyphCacheOptions) -> Self {
+        self.0.glyph_cache = glyph_cache;
+        self
+    }
+
+    /// Set whether to gather rendering statistics
+    #[inline]
+    pub fn render_stats(mut self, render_stats: bool) -> Self {
+        self.0.render_stats = render_stats;
+        self
+    }
+
+    /// Set the theme configuration file
+    #[inline]
+    pub fn theme_config(mut self, path: impl Into<PathBuf>) -> Self {
+        self.0.theme_config = Some(path.into());
+        self
+    }
+
+    /// Set the emoji font file
+    #[inline]
+    pub fn emoji_font(mut self, path: impl Into<PathBuf>) -> Self {
+        self.0.emoji_font = Some(path.into());
+        self
+    }
+
+    /// Validate and build
+    ///
+    /// Unlike values read from the environment (which are ignored with a
+    /// warning when invalid), invalid values are reported as an error.
+    pub fn build(self) -> Result<Options, OptionsError> {
+        let options = self.0;
+        if let Some(factor) = options.dpi_factor {
+            if !(factor > 0.0 && factor.is_finite()) {
+                return Err(OptionsError::InvalidDpiFactor(factor));
+            }
+        }
+        if options.backends.is_empty() {
+            return Err(OptionsError::NoBackends);
+        }
+        if !crate::draw::is_supported_format(options.surface_format) {
+            let format = options.surface_format;
+            return Err(OptionsError::UnsupportedSurfaceFormat(format));
+        }
+        Ok(options)
+    }
+}
+
+/// Match `value` of the variable `name` against `values`
+///
+/// Matching is case-insensitive; keys of `values` must be upper case. An
+/// unexpected value is reported as a warning and ignored.
+fn parse_enum<T: Copy>(name: &str, value: &str, values: &[(&str, T)]) -> Option<T> {
+    let upper = value.to_ascii_uppercase();
+    let result = values
+        .iter()
+        .find(|(key, _)| *key == upper)
+        .map(|(_, v)| *v);
+    if result.is_none() {
+        warn!("Unexpected environment value: {}={}", name, value);
+    }
+    result
+}
+
+/// Read the variable `name`, if set, and match against `values`
+///
+/// See [`parse_enum`].
+fn parse_env_enum<T: Copy>(name: &str, values: &[(&str, T)]) -> Option<T> {
+    let value = var(name).ok()?;
+    parse_enum(name, &value, values)
+}
+
+/// Read the variable `name`, if set, as `TRUE` or `FALSE`
+fn parse_env_bool(name: &str) -> Option<bool> {
+    parse_env_enum(name, &[("TRUE", true), ("FALSE", false)])
+}
+
+/// Read the first set variable of `names`, returning its name and value
+fn var_any(names: &[&'static str]) -> Option<(&'static str, String)> {
+    names
+        .iter()
+        .filter_map(|name| var(name).ok().map(|v| (*name, v)))
+        .next()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn build_valid() {
+        let options = Options::builder().dpi_factor(1.5).build().unwrap();
+        assert_eq!(options.dpi_factor, Some(1.5));
+        assert!(Options::builder().build().is_ok());
+
+        let format = TextureFormat::Rgba16Float;
+        let options = Options::builder().surface_format(format).build().unwrap();
+        assert_eq!(options.surface_format, format);
+    }
+
+    #[test]
+    fn build_invalid_dpi_factor() {
+        let build = |factor| Options::builder().dpi_factor(factor).build().err();
+        assert_eq!(build(0.0), Some(OptionsError::InvalidDpiFactor(0.0)));
+        assert_eq!(build(-1.0), Some(OptionsError::InvalidDpiFactor(-1.0)));
+        let inf = f64::INFINITY;
+        assert_eq!(build(inf), Some(OptionsError::InvalidDpiFactor(inf)));
+        assert!(matches!(
+            build(f64::NAN),
+            Some(OptionsError::InvalidDpiFactor(f)) if f.is_nan()
+        ));
+    }
+
+    #[test]
+    fn build_invalid() {
+        let result = Options::builder()
+            .backends(BackendBit::empty())
+            .build()
+            .err();
+        assert_eq!(result, Some(OptionsError::NoBackends));
+
+        let format = TextureFormat::Bgra8Unorm;
+        let result = Options::builder().surface_format(format).build().err();
+        assert_eq!(result, Some(OptionsError::UnsupportedSurfaceFormat(format)));
+    }
+
+    #[test]
+    fn adapter_backend() {
+        let backend = backend_from_debug;
+        assert_eq!(
+            backend("Adapter { id: (0, 1, Vulkan) }"),
+            Some(BackendBit::VULKAN)
+        );
+        assert_eq!(
+            backend("Adapter { id: (2, 1, Dx11) }"),
+            Some(BackendBit::DX11)
+        );
+        assert_eq!(backend("Adapter { id: (0, 1, Empty) }"), None);
+        assert_eq!(backend(""), None);
+    }
+
+    #[test]
+    fn parse_values() {
+        let values = [
+            ("NONE", ColourFilter::None),
+            ("GREYSCALE", ColourFilter::Greyscale),
+        ];
+        let parse = |value| parse_enum("KAS_COLOUR_FILTER", value, &values);
+        assert_eq!(parse("NONE"), Some(ColourFilter::None));
+        assert_eq!(parse("Greyscale"), Some(ColourFilter::Greyscale));
+        assert_eq!(parse("greyscale"), Some(ColourFilter::Greyscale));
+        assert_eq!(parse("sometimes"), None);
+        assert_eq!(parse(""), None);
+    }
+
+    #[test]
+    fn parse_env() {
+        // This variable is not read by the toolkit, thus safe to set here
+        let name = "KAS_TEST_PARSE_ENV_BOOL";
+        std::env::remove_var(name);
+        assert_eq!(parse_env_bool(name), None);
+        std::env::set_var(name, "true");
+        assert_eq!(parse_env_bool(name), Some(true));
+        std::env::set_var(name, "FALSE");
+        assert_eq!(parse_env_bool(name), Some(false));
+        std::env::set_var(name, "1");
+        assert_eq!(parse_env_bool(name), None);
+        std::env::remove_var(name);
+    }
+
+    #[test]
+    fn load_theme_config() {
+        let path = std::env::temp_dir().join("kas-wgpu-test-theme-config.toml");
+        std::fs::write(&path, "font_size = 12.0\n\n[metrics]\nmargin = 1.0\n").unwrap();
+        let options = Options::builder().theme_config(&path).build().unwrap();
+        let config = options.load_theme_config();
+        std::fs::remove_file(&path).unwrap();
+
+        #[cfg(feature = "theme_config")]
+        {
+            assert_eq!(config.font_size, Some(12.0));
+            assert_eq!(config.margin, Some(1.0));
+        }
+        #[cfg(not(feature = "theme_config"))]
+        assert_eq!(config, ThemeConfig::default());
+
+        // A missing file is not an error
+        let options = Options::builder().theme_config(&path).build().unwrap();
+        assert_eq!(options.load_theme_config(), ThemeConfig::default());
+    }
+}
@@ -6,15 +6,29 @@
 //! Shared state
 
 use log::{info, warn};
+use std::any::Any;
+use std::collections::HashMap;
 use std::num::NonZeroU32;
-
-use crate::draw::ShaderManager;
-use crate::{Error, Options, WindowId};
+use std::sync::atomic::{AtomicU32, Ordering};
+use std::sync::Arc;
+
+use crate::draw::{is_supported_format, EmojiFont, ShaderManager, DEFAULT_FORMAT};
+use crate::{
+    AdapterInfo, ColourFilter, Error, GlyphCacheOptions, Options, WindowGeometry, WindowId,
+};
+use kas::access::{AccessEvent, AccessNode, AccessPrefs};
 use kas::event::UpdateHandle;
 
 #[cfg(feature = "clipboard")]
 use clipboard::{ClipboardContext, ClipboardProvider};
 
+/// Drains the queue of an update channel, also returning whether any sender
+/// remains connected
+pub type UpdateDrain = Box<dyn Fn() -> (Vec<Arc<dyn Any + Send + Sync>>, bool)>;
+
+/// Receives accessibility notifications with the window's tree
+pub type AccessHandler = Box<dyn FnMut(WindowId, &AccessNode, AccessEvent)>;
+
 /// State shared between windows
 pub struct SharedState<C, T> {
     #[cfg(feature = "clipboard")]
@@ -25,28 +39,66 @@ pub struct SharedState<C, T> {
     pub custom: C,
     pub theme: T,
     pub pending: Vec<PendingAction>,
-    window_id: u32,
+    pub window_id_factory: WindowIdFactory,
+    /// Preferred surface format (supported by the draw pipes)
+    pub surface_format: wgpu::TextureFormat,
+    pub present_mode: wgpu::PresentMode,
+    pub decorations: bool,
+    pub transparent: bool,
+    pub pixel_snap: bool,
+    pub dpi_override: Option<f64>,
+    pub text_gamma: bool,
+    pub colour_filter: ColourFilter,
+    pub access_prefs: AccessPrefs,
+    pub glyph_cache: GlyphCacheOptions,
+    pub render_stats: bool,
+    /// Fallback font for emoji
+    pub emoji_font: Option<Arc<EmojiFont>>,
+    /// Adapter information (unknown for an externally created device)
+    pub adapter_info: Option<AdapterInfo>,
+    /// Light direction for shaded drawing; see [`kas_theme::ThemeConfig`]
+    pub light_dir: (f32, f32),
+    /// Called with the geometry of each window on closure
+    pub geometry_handler: Option<Box<dyn FnMut(WindowId, WindowGeometry)>>,
+    /// Called with the access tree on each accessibility notification
+    pub access_handler: Option<AccessHandler>,
+    /// For each update channel, a function draining its queue
+    pub update_channels: HashMap<UpdateHandle, UpdateDrain>,
+}
+
+/// Allocates window identifiers
+///
+/// This is shared with [`crate::ToolkitProxy`], allowing windows to be added
+/// from other threads.
+#[derive(Clone, Default)]
+pub struct WindowIdFactory(Arc<AtomicU32>);
+
+impl WindowIdFactory {
+    pub fn make_next(&self) -> WindowId {
+        let id = self.0.fetch_add(1, Ordering::Relaxed) + 1;
+        WindowId::new(NonZeroU32::new(id).unwrap())
+    }
 }
 
 impl<C, T> SharedState<C, T> {
     /// Construct
     pub fn new(custom: C, theme: T, options: Options) -> Result<Self, Error> {
-        #[cfg(feature = "clipboard")]
-        let clipboard = match ClipboardContext::new() {
-            Ok(cb) => Some(cb),
-            Err(e) => {
-                warn!("Unable to open clipboard: {:?}", e);
-                None
-            }
+        let (adapter, backend) = match options.request_adapter() {
+            Some(result) => result,
+            None => return Err(Error::NoAdapter(options.backends)),
         };
-
-        let adapter_options = options.adapter_options();
-
-        let adapter = match wgpu::Adapter::request(&adapter_options) {
-            Some(a) => a,
-            None => return Err(Error::NoAdapter),
+        let adapter_info = AdapterInfo {
+            info: adapter.get_info(),
+            backend,
         };
-        info!("Using graphics adapter: {}", adapter.get_info().name);
+        info!(
+            "Using graphics adapter: {} ({:?}, backend {:?}; requested backend {:?}, power preference {:?})",
+            adapter_info.info.name,
+            adapter_info.info.device_type,
+            backend,
+            options.backends,
+            options.power_preference
+        );
 
         let (device, queue) = adapter.request_device(&wgpu::DeviceDescriptor {
             extensions: wgpu::Extensions {
@@ -55,8 +107,38 @@ impl<C, T> SharedState<C, T> {
             limits: wgpu::Limits::default(),
         });
 
+        Self::with_device(custom, theme, options, device, queue, Some(adapter_info))
+    }
+
+    /// Construct using an existing device
+    pub fn with_device(
+        custom: C,
+        theme: T,
+        options: Options,
+        device: wgpu::Device,
+        queue: wgpu::Queue,
+        adapter_info: Option<AdapterInfo>,
+    ) -> Result<Self, Error> {
+        #[cfg(feature = "clipboard")]
+        let clipboard = match ClipboardContext::new() {
+            Ok(cb) => Some(cb),
+            Err(e) => {
+                warn!("Unable to open clipboard: {:?}", e);
+                None
+            }
+        };
+
         let shaders = ShaderManager::new(&device)?;
 
+        let mut surface_format = options.surface_format;
+        if !is_supported_format(surface_format) {
+            warn!(
+                "Surface format {:?} is not supported by draw pipes; using {:?}",
+                surface_format, DEFAULT_FORMAT
+            );
+            surface_format = DEFAULT_FORMAT;
+        }
+
         Ok(SharedState {
             #[cfg(feature = "clipboard")]
             clipboard,
@@ -66,13 +148,29 @@ impl<C, T> SharedState<C, T> {
             custom,
             theme,
             pending: vec![],
-            window_id: 0,
+            window_id_factory: Default::default(),
+            surface_format,
+            present_mode: options.present_mode,
+            decorations: options.decorations,
+            transparent: options.transparent,
+            pixel_snap: options.pixel_snap,
+            dpi_override: options.dpi_factor,
+            text_gamma: options.text_gamma,
+            colour_filter: options.colour_filter,
+            access_prefs: options.access_prefs(),
+            glyph_cache: options.glyph_cache,
+            render_stats: options.render_stats,
+            emoji_font: options.load_emoji_font(),
+            adapter_info,
+            light_dir: (0.3, 0.4),
+            geometry_handler: None,
+            access_handler: None,
+            update_channels: HashMap::new(),
         })
     }
 
     pub fn next_window_id(&mut self) -> WindowId {
-        self.window_id += 1;
-        WindowId::new(NonZeroU32::new(self.window_id).unwrap())
+        self.window_id_factory.make_next()
     }
 
     #[cfg(not(feature = "clipboard"))]
@@ -108,9 +206,19 @@ impl<C, T> SharedState<C, T> {
 }
 
 pub enum PendingAction {
-    AddWindow(WindowId, Box<dyn kas::Window>),
+    /// Add a window, optionally modal over the given parent
+    AddWindow(
+        WindowId,
+        Box<dyn kas::Window>,
+        Option<winit::window::WindowId>,
+    ),
     CloseWindow(WindowId),
+    /// Set the DPI factor override of a window (`None`: an embedded target)
+    SetDpiOverride(Option<winit::window::WindowId>, Option<f64>),
+    /// Report an accessibility notification (`None`: an embedded target)
+    AccessNotify(Option<winit::window::WindowId>, AccessEvent),
     ThemeResize,
     RedrawAll,
     Update(UpdateHandle, u64),
+    UpdateData(UpdateHandle),
 }
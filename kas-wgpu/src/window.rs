@@ -5,23 +5,209 @@
 
 //! `Window` and `WindowList` types
 
-use log::{debug, info, trace};
+use log::{debug, info, trace, warn};
+use std::any::Any;
+use std::cell::Cell;
+use std::fmt;
 use std::marker::PhantomData;
+use std::str::FromStr;
+use std::sync::Arc;
 use std::time::Instant;
 
+use kas::access::{AccessEvent, AccessNode};
 use kas::event::{Callback, CursorIcon, ManagerState, UpdateHandle};
 use kas::geom::{Coord, Rect, Size};
 use kas::{ThemeAction, ThemeApi, TkAction, WindowId};
 use kas_theme::Theme;
-use winit::dpi::PhysicalSize;
+use winit::dpi::{PhysicalPosition, PhysicalSize};
 use winit::error::OsError;
 use winit::event::WindowEvent;
 use winit::event_loop::EventLoopWindowTarget;
 
-use crate::draw::{CustomPipe, CustomPipeBuilder, DrawPipe};
+use crate::draw::{
+    CustomPipe, CustomPipeBuilder, DrawPipe, RenderStats, DEFAULT_FORMAT, DEPTH_FORMAT,
+};
 use crate::shared::{PendingAction, SharedState};
 use crate::ProxyAction;
 
+/// Initial position of a window
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum WindowPosition {
+    /// Let the OS choose a position
+    #[default]
+    Default,
+    /// Centre on the monitor the window opens on
+    Centre,
+    /// Centre over another window
+    ///
+    /// If the parent window does not exist, this acts like [`WindowPosition::Centre`].
+    CentreOnParent(WindowId),
+    /// Place the top-left corner of the window at the given position
+    ///
+    /// Coordinates are in physical pixels, relative to the desktop.
+    At(Coord),
+}
+
+impl WindowPosition {
+    /// Resolve to a coordinate
+    ///
+    /// `parent` must be the window referred to by `CentreOnParent`, if any.
+    /// Returns `None` where the OS should choose or the result would not be
+    /// (partly) on any monitor.
+    fn resolve(
+        self,
+        window: &winit::window::Window,
+        parent: Option<&winit::window::Window>,
+    ) -> Option<Coord> {
+        let size = window.outer_size();
+        let centre_in = |pos: PhysicalPosition<i32>, area: PhysicalSize<u32>| {
+            Coord(
+                pos.x + (area.width as i32 - size.width as i32) / 2,
+                pos.y + (area.height as i32 - size.height as i32) / 2,
+            )
+        };
+        let centre = || {
+            let monitor = window.current_monitor();
+            centre_in(monitor.position(), monitor.size())
+        };
+
+        let coord = match self {
+            WindowPosition::Default => return None,
+            WindowPosition::Centre => centre(),
+            WindowPosition::CentreOnParent(_) => {
+                match parent.and_then(|p| p.outer_position().ok().map(|pos| (pos, p.outer_size())))
+                {
+                    Some((pos, area)) => centre_in(pos, area),
+                    None => centre(),
+                }
+            }
+            WindowPosition::At(coord) => coord,
+        };
+
+        if on_monitor(window, coord, size) {
+            Some(coord)
+        } else {
+            None
+        }
+    }
+}
+
+/// True if a window at `coord` with `size` is (partly) on any monitor
+fn on_monitor(window: &winit::window::Window, coord: Coord, size: PhysicalSize<u32>) -> bool {
+    window.available_monitors().any(|m| {
+        let (pos, area) = (m.position(), m.size());
+        coord.0 + size.width as i32 > pos.x
+            && coord.0 < pos.x + area.width as i32
+            && coord.1 + size.height as i32 > pos.y
+            && coord.1 < pos.y + area.height as i32
+    })
+}
+
+/// Window geometry, for saving and restoring across sessions
+///
+/// The toolkit reports the geometry of each window on closure (see
+/// [`crate::Toolkit::set_geometry_handler`]) and accepts a geometry when
+/// opening a window (see [`crate::Toolkit::add_with_geometry`]). Persistence
+/// is left to the application; for this purpose the type may be converted
+/// to and from a string with `to_string` and `parse`, using the format
+/// `WIDTHxHEIGHT[+X+Y][ maximized]` (e.g. `800x600+20-5`).
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct WindowGeometry {
+    /// Inner size, in physical pixels
+    pub size: Size,
+    /// Outer position (top-left corner) in physical pixels, if known
+    pub position: Option<Coord>,
+    /// Whether the window is maximized
+    ///
+    /// The windowing system does not report this state, so it is estimated
+    /// from whether the window fills its monitor.
+    pub maximized: bool,
+}
+
+impl fmt::Display for WindowGeometry {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}x{}", self.size.0, self.size.1)?;
+        if let Some(pos) = self.position {
+            write!(f, "{:+}{:+}", pos.0, pos.1)?;
+        }
+        if self.maximized {
+            write!(f, " maximized")?;
+        }
+        Ok(())
+    }
+}
+
+/// Error parsing a [`WindowGeometry`]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ParseGeometryError;
+
+impl fmt::Display for ParseGeometryError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "invalid window geometry")
+    }
+}
+
+impl std::error::Error for ParseGeometryError {}
+
+impl FromStr for WindowGeometry {
+    type Err = ParseGeometryError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim();
+        let (s, maximized) = match s.strip_suffix("maximized") {
+            Some(s) => (s.trim_end(), true),
+            None => (s, false),
+        };
+        let (size, pos) = match s.find(&['+', '-'][..]) {
+            Some(i) => (&s[..i], Some(&s[i..])),
+            None => (s, None),
+        };
+        let x = size.find('x').ok_or(ParseGeometryError)?;
+        let w = size[..x].parse().map_err(|_| ParseGeometryError)?;
+        let h = size[x + 1..].parse().map_err(|_| ParseGeometryError)?;
+        let position = match pos {
+            Some(pos) => {
+                // Split at the sign of the second number
+                let i = pos[1..].find(&['+', '-'][..]).ok_or(ParseGeometryError)? + 1;
+                let x = pos[..i].parse().map_err(|_| ParseGeometryError)?;
+                let y = pos[i..].parse().map_err(|_| ParseGeometryError)?;
+                Some(Coord(x, y))
+            }
+            None => None,
+        };
+        Ok(WindowGeometry {
+            size: Size(w, h),
+            position,
+            maximized,
+        })
+    }
+}
+
+impl WindowGeometry {
+    /// Get the restore position for `window`
+    ///
+    /// If the saved position would place the window entirely off-screen (e.g.
+    /// because a monitor was unplugged), it is clamped onto the primary
+    /// monitor.
+    fn resolve(&self, window: &winit::window::Window) -> Option<Coord> {
+        let coord = self.position?;
+        let size = window.outer_size();
+        if on_monitor(window, coord, size) {
+            return Some(coord);
+        }
+        let monitor = window.primary_monitor();
+        let (pos, area) = (monitor.position(), monitor.size());
+        let clamp = |v: i32, min: i32, len: u32, area_len: u32| {
+            let max = min + area_len as i32 - len as i32;
+            v.min(max).max(min)
+        };
+        Some(Coord(
+            clamp(coord.0, pos.x, size.width, area.width),
+            clamp(coord.1, pos.y, size.height, area.height),
+        ))
+    }
+}
+
 /// Per-window data
 pub(crate) struct Window<C: CustomPipe, TW> {
     widget: Box<dyn kas::Window>,
@@ -31,8 +217,19 @@ pub(crate) struct Window<C: CustomPipe, TW> {
     surface: wgpu::Surface,
     sc_desc: wgpu::SwapChainDescriptor,
     swap_chain: wgpu::SwapChain,
+    /// Depth texture and view, if required by the custom pipe
+    depth: Option<(wgpu::Texture, wgpu::TextureView)>,
     draw_pipe: DrawPipe<C>,
     theme_window: TW,
+    /// DPI factor used in place of the window's scale factor, if any
+    dpi_override: Option<f64>,
+    /// Command buffers to submit with the next frame
+    pending_cmds: Vec<wgpu::CommandBuffer>,
+    /// True if the next frame may differ from the last
+    ///
+    /// Redraws requested by the OS (e.g. on expose) without this set replay
+    /// the last frame.
+    frame_changed: Cell<bool>,
 }
 
 // Public functions, for use by the toolkit
@@ -42,30 +239,61 @@ impl<C: CustomPipe, TW: kas_theme::Window<DrawPipe<C>> + 'static> Window<C, TW>
         shared: &mut SharedState<CB, T>,
         elwt: &EventLoopWindowTarget<ProxyAction>,
         widget: Box<dyn kas::Window>,
+        position: WindowPosition,
+        parent: Option<&winit::window::Window>,
+        geometry: Option<&WindowGeometry>,
     ) -> Result<Self, OsError> {
-        let window = winit::window::Window::new(elwt)?;
-        window.set_title(widget.title());
+        // The window is hidden until positioned
+        let mut builder = winit::window::WindowBuilder::new()
+            .with_title(widget.title())
+            .with_visible(false)
+            .with_decorations(shared.decorations)
+            .with_transparent(shared.transparent);
+        if let Some(geom) = geometry {
+            let size = PhysicalSize::new(geom.size.0, geom.size.1);
+            builder = builder.with_inner_size(size).with_maximized(geom.maximized);
+        }
+        let window = builder.build(elwt)?;
+        let coord = match geometry {
+            Some(geom) => geom.resolve(&window),
+            None => position.resolve(&window, parent),
+        };
+        if let Some(coord) = coord {
+            window.set_outer_position(coord);
+        }
+        window.set_visible(true);
 
-        let dpi_factor = window.scale_factor();
+        let dpi_override = shared.dpi_override;
+        let dpi_factor = dpi_override.unwrap_or_else(|| window.scale_factor());
         let size: Size = window.inner_size().into();
         info!("Constucted new window with size {:?}", size);
 
         let surface = wgpu::Surface::create(&window);
 
-        let sc_desc = wgpu::SwapChainDescriptor {
+        // SharedState::new already replaced formats unsupported by draw pipes
+        let mut sc_desc = wgpu::SwapChainDescriptor {
             usage: wgpu::TextureUsage::OUTPUT_ATTACHMENT,
-            format: wgpu::TextureFormat::Bgra8UnormSrgb,
+            format: shared.surface_format,
             width: size.0,
             height: size.1,
-            present_mode: wgpu::PresentMode::Vsync,
+            present_mode: shared.present_mode,
         };
-        let swap_chain = shared.device.create_swap_chain(&surface, &sc_desc);
+        let swap_chain = create_swap_chain(&shared.device, &surface, &mut sc_desc);
+        // Avoid retrying a rejected format for later windows
+        shared.surface_format = sc_desc.format;
+        debug!("Using surface format {:?}", sc_desc.format);
 
         let mut draw_pipe = DrawPipe::new(shared, sc_desc.format, size);
         shared.theme.init(&mut draw_pipe);
+        let depth = if draw_pipe.uses_depth() {
+            Some(create_depth_texture(&shared.device, size, DEPTH_FORMAT))
+        } else {
+            None
+        };
         let theme_window = shared.theme.new_window(&mut draw_pipe, dpi_factor as f32);
 
-        let mgr = ManagerState::new(dpi_factor);
+        let mut mgr = ManagerState::new(dpi_factor);
+        mgr.set_access_prefs(shared.access_prefs);
 
         Ok(Window {
             widget,
@@ -74,8 +302,12 @@ impl<C: CustomPipe, TW: kas_theme::Window<DrawPipe<C>> + 'static> Window<C, TW>
             surface,
             sc_desc,
             swap_chain,
+            depth,
             draw_pipe,
             theme_window,
+            dpi_override,
+            pending_cmds: vec![],
+            frame_changed: Cell::new(true),
         })
     }
 
@@ -118,7 +350,7 @@ impl<C: CustomPipe, TW: kas_theme::Window<DrawPipe<C>> + 'static> Window<C, TW>
         self.window.set_max_inner_size(max);
         let mut tkw = TkWindow::new(&self.window, shared);
         self.mgr.configure(&mut tkw, &mut *self.widget);
-        self.window.request_redraw();
+        self.request_redraw();
 
         self.mgr.next_resume()
     }
@@ -128,7 +360,8 @@ impl<C: CustomPipe, TW: kas_theme::Window<DrawPipe<C>> + 'static> Window<C, TW>
         shared: &SharedState<CB, T>,
     ) {
         debug!("Applying theme resize");
-        let scale_factor = self.window.scale_factor() as f32;
+        self.mgr.redraw_all();
+        let scale_factor = self.dpi_factor() as f32;
         shared
             .theme
             .update_window(&mut self.theme_window, scale_factor);
@@ -137,9 +370,34 @@ impl<C: CustomPipe, TW: kas_theme::Window<DrawPipe<C>> + 'static> Window<C, TW>
         let (min, max) = self.widget.resize(&mut size_handle, size);
         self.window.set_min_inner_size(min);
         self.window.set_max_inner_size(max);
+        self.request_redraw();
+    }
+
+    /// Request a redraw, noting that the frame may have changed
+    ///
+    /// All redraw requests by the toolkit must use this method.
+    pub fn request_redraw(&self) {
+        self.frame_changed.set(true);
         self.window.request_redraw();
     }
 
+    /// True if a redraw was requested but not yet drawn
+    #[inline]
+    pub fn needs_redraw(&self) -> bool {
+        self.frame_changed.get()
+    }
+
+    /// Build the accessibility tree of the window's widgets
+    pub fn access_tree(&self) -> AccessNode {
+        AccessNode::build(self.widget.as_widget(), &self.mgr)
+    }
+
+    /// Redraw the whole window, including widgets with cached geometry
+    pub fn redraw_all(&mut self) {
+        self.mgr.redraw_all();
+        self.request_redraw();
+    }
+
     /// Handle an event
     ///
     /// Return true to remove the window
@@ -156,6 +414,7 @@ impl<C: CustomPipe, TW: kas_theme::Window<DrawPipe<C>> + 'static> Window<C, TW>
                 new_inner_size,
             } => {
                 // Note: API allows us to set new window size here.
+                let scale_factor = self.dpi_override.unwrap_or(scale_factor);
                 shared
                     .theme
                     .update_window(&mut self.theme_window, scale_factor as f32);
@@ -173,6 +432,46 @@ impl<C: CustomPipe, TW: kas_theme::Window<DrawPipe<C>> + 'static> Window<C, TW>
         (action, self.mgr.next_resume())
     }
 
+    /// Set or clear the DPI factor override
+    ///
+    /// Applies the new factor, resizing and redrawing the window.
+    pub fn set_dpi_override<CB: CustomPipeBuilder<Pipe = C>, T: Theme<DrawPipe<C>, Window = TW>>(
+        &mut self,
+        shared: &SharedState<CB, T>,
+        factor: Option<f64>,
+    ) {
+        if factor == self.dpi_override {
+            return;
+        }
+        self.dpi_override = factor;
+        self.mgr.set_dpi_factor(self.dpi_factor());
+        self.theme_resize(shared);
+    }
+
+    /// Get rendering statistics of the last frame, if enabled
+    pub fn render_stats(&self) -> Option<RenderStats> {
+        self.draw_pipe.render_stats()
+    }
+
+    /// Get the current window geometry
+    pub fn geometry(&self) -> WindowGeometry {
+        let size = self.window.inner_size();
+        let outer = self.window.outer_size();
+        let monitor = self.window.current_monitor().size();
+        // Allow for panels (e.g. a taskbar) covering part of the monitor
+        let maximized =
+            outer.width >= monitor.width && outer.height as f32 >= 0.9 * monitor.height as f32;
+        WindowGeometry {
+            size: Size(size.width, size.height),
+            position: self
+                .window
+                .outer_position()
+                .ok()
+                .map(|pos| Coord(pos.x, pos.y)),
+            maximized,
+        }
+    }
+
     pub fn handle_moved(&mut self) {
         self.mgr.region_moved(&mut *self.widget);
     }
@@ -199,6 +498,17 @@ impl<C: CustomPipe, TW: kas_theme::Window<DrawPipe<C>> + 'static> Window<C, TW>
         mgr.unwrap_action()
     }
 
+    /// Handle [`TkAction::RequestClose`], calling the window's close hook
+    pub fn request_close<CB: CustomPipeBuilder<Pipe = C>, T: Theme<DrawPipe<C>>>(
+        &mut self,
+        shared: &mut SharedState<CB, T>,
+    ) -> TkAction {
+        let mut tkw = TkWindow::new(&self.window, shared);
+        let mut mgr = self.mgr.manager(&mut tkw);
+        mgr.request_close(&mut *self.widget);
+        mgr.unwrap_action()
+    }
+
     pub fn update_timer<CB: CustomPipeBuilder<Pipe = C>, T: Theme<DrawPipe<C>>>(
         &mut self,
         shared: &mut SharedState<CB, T>,
@@ -214,16 +524,50 @@ impl<C: CustomPipe, TW: kas_theme::Window<DrawPipe<C>> + 'static> Window<C, TW>
         shared: &mut SharedState<CB, T>,
         handle: UpdateHandle,
         payload: u64,
-    ) -> TkAction {
+    ) -> (TkAction, Option<Instant>) {
         let mut tkw = TkWindow::new(&self.window, shared);
         let mut mgr = self.mgr.manager(&mut tkw);
         mgr.update_handle(&mut *self.widget, handle, payload);
-        mgr.unwrap_action()
+        (mgr.unwrap_action(), self.mgr.next_resume())
+    }
+
+    pub fn update_data<CB: CustomPipeBuilder<Pipe = C>, T: Theme<DrawPipe<C>>>(
+        &mut self,
+        shared: &mut SharedState<CB, T>,
+        handle: UpdateHandle,
+        data: Arc<dyn Any + Send + Sync>,
+    ) -> (TkAction, Option<Instant>) {
+        let mut tkw = TkWindow::new(&self.window, shared);
+        let mut mgr = self.mgr.manager(&mut tkw);
+        mgr.update_data(&mut *self.widget, handle, data);
+        (mgr.unwrap_action(), self.mgr.next_resume())
+    }
+
+    /// Apply the colour filter of `shared` and redraw
+    pub fn set_colour_filter<CB, T>(&mut self, shared: &SharedState<CB, T>) {
+        self.draw_pipe
+            .set_colour_filter(shared, shared.colour_filter);
+        self.request_redraw();
+    }
+}
+
+#[cfg(feature = "shader_reload")]
+impl<C: CustomPipe, TW: kas_theme::Window<DrawPipe<C>> + 'static> Window<C, TW> {
+    /// Rebuild pipelines after shaders were reloaded
+    pub fn reload_shaders<CB, T>(&mut self, shared: &SharedState<CB, T>) {
+        self.draw_pipe.reload_shaders(shared);
+        self.request_redraw();
     }
 }
 
 // Internal functions
 impl<C: CustomPipe, TW: kas_theme::Window<DrawPipe<C>> + 'static> Window<C, TW> {
+    /// The DPI factor in use: the override if set, otherwise the monitor's
+    fn dpi_factor(&self) -> f64 {
+        self.dpi_override
+            .unwrap_or_else(|| self.window.scale_factor())
+    }
+
     fn do_resize<CB: CustomPipeBuilder<Pipe = C>, T: Theme<DrawPipe<C>, Window = TW>>(
         &mut self,
         shared: &mut SharedState<CB, T>,
@@ -239,46 +583,176 @@ impl<C: CustomPipe, TW: kas_theme::Window<DrawPipe<C>> + 'static> Window<C, TW>
         self.widget.resize(&mut size_handle, size);
         drop(size_handle);
 
-        let buf = self.draw_pipe.resize(&shared.device, size);
-        shared.queue.submit(&[buf]);
+        self.recreate_swap_chain(shared, size);
+
+        TkAction::Redraw
+    }
 
+    /// Recreate the swap chain with the given size
+    ///
+    /// This resizes the draw pipes (including any custom pipe) and recreates
+    /// the depth texture, if any. It is currently called only on resize since
+    /// `wgpu` does not report lost surfaces; any other recreation of the swap
+    /// chain should also use this method.
+    ///
+    /// Resize commands are not submitted immediately but batched with the
+    /// next frame's draw commands.
+    fn recreate_swap_chain<CB: CustomPipeBuilder<Pipe = C>, T: Theme<DrawPipe<C>, Window = TW>>(
+        &mut self,
+        shared: &mut SharedState<CB, T>,
+        size: Size,
+    ) {
         self.sc_desc.width = size.0;
         self.sc_desc.height = size.1;
         self.swap_chain = shared
             .device
             .create_swap_chain(&self.surface, &self.sc_desc);
+        if self.depth.is_some() {
+            self.depth = Some(create_depth_texture(&shared.device, size, DEPTH_FORMAT));
+        }
 
-        TkAction::Redraw
+        let buf = self.draw_pipe.resize(&shared.device, size);
+        self.pending_cmds.push(buf);
     }
 
     pub(crate) fn do_draw<CB: CustomPipeBuilder<Pipe = C>, T: Theme<DrawPipe<C>, Window = TW>>(
         &mut self,
         shared: &mut SharedState<CB, T>,
     ) {
-        trace!("Drawing window");
-        let size = Size(self.sc_desc.width, self.sc_desc.height);
-        let rect = Rect {
-            pos: Coord::ZERO,
-            size,
-        };
-        let mut draw_handle = unsafe {
-            shared
-                .theme
-                .draw_handle(&mut self.draw_pipe, &mut self.theme_window, rect)
+        let frame = self.swap_chain.get_next_texture();
+        let target = FrameTarget {
+            view: &frame.view,
+            depth_view: self.depth.as_ref().map(|(_, view)| view),
+            size: Size(self.sc_desc.width, self.sc_desc.height),
+            replay: !self.frame_changed.replace(false),
         };
-        self.widget.draw(&mut draw_handle, &self.mgr);
-        drop(draw_handle);
+        draw_frame(
+            shared,
+            &mut self.draw_pipe,
+            &mut self.theme_window,
+            &*self.widget,
+            &mut self.mgr,
+            &mut self.pending_cmds,
+            target,
+        );
+    }
+}
 
-        let frame = self.swap_chain.get_next_texture();
-        let clear_color = to_wgpu_color(shared.theme.clear_colour());
-        let buf = self
-            .draw_pipe
-            .render(&mut shared.device, &frame.view, clear_color);
-        shared.queue.submit(&[buf]);
+/// A render target for [`draw_frame`]
+pub(crate) struct FrameTarget<'a> {
+    pub view: &'a wgpu::TextureView,
+    pub depth_view: Option<&'a wgpu::TextureView>,
+    pub size: Size,
+    /// True if the last frame may be replayed
+    pub replay: bool,
+}
+
+/// Draw `widget` to `target`, submitting `pending_cmds` with the frame
+///
+/// The last frame is replayed instead where `target.replay` is set, there
+/// are no pending commands and the draw pipe supports this.
+pub(crate) fn draw_frame<CB: CustomPipeBuilder, T: Theme<DrawPipe<CB::Pipe>>>(
+    shared: &mut SharedState<CB, T>,
+    draw_pipe: &mut DrawPipe<CB::Pipe>,
+    theme_window: &mut T::Window,
+    widget: &dyn kas::Window,
+    mgr: &mut ManagerState,
+    pending_cmds: &mut Vec<wgpu::CommandBuffer>,
+    target: FrameTarget,
+) {
+    let clear_color = if shared.transparent {
+        wgpu::Color::TRANSPARENT
+    } else {
+        to_wgpu_color(shared.theme.clear_colour())
+    };
+
+    if target.replay && pending_cmds.is_empty() {
+        // If the last frame cannot be replayed we draw as normal
+        let device = &mut shared.device;
+        let replay = draw_pipe.replay(device, target.view, target.depth_view, clear_color);
+        if let Some(buf) = replay {
+            trace!("Replaying last frame");
+            shared.queue.submit(&[buf]);
+            return;
+        }
     }
+
+    trace!("Drawing frame");
+    let rect = Rect {
+        pos: Coord::ZERO,
+        size: target.size,
+    };
+    let mut draw_handle = unsafe { shared.theme.draw_handle(draw_pipe, theme_window, rect) };
+    widget.draw(&mut draw_handle, mgr);
+    drop(draw_handle);
+    mgr.draw_complete();
+
+    let buf = draw_pipe.render(
+        &mut shared.device,
+        target.view,
+        target.depth_view,
+        clear_color,
+    );
+    pending_cmds.push(buf);
+    shared.queue.submit(pending_cmds);
+    pending_cmds.clear();
 }
 
-fn to_wgpu_color(c: kas::draw::Colour) -> wgpu::Color {
+/// Create a depth (or depth-stencil) texture of the given `format`
+pub(crate) fn create_depth_texture(
+    device: &wgpu::Device,
+    size: Size,
+    format: wgpu::TextureFormat,
+) -> (wgpu::Texture, wgpu::TextureView) {
+    let texture = device.create_texture(&wgpu::TextureDescriptor {
+        size: wgpu::Extent3d {
+            width: size.0,
+            height: size.1,
+            depth: 1,
+        },
+        array_layer_count: 1,
+        mip_level_count: 1,
+        sample_count: 1,
+        dimension: wgpu::TextureDimension::D2,
+        format,
+        usage: wgpu::TextureUsage::OUTPUT_ATTACHMENT,
+    });
+    let view = texture.create_default_view();
+    (texture, view)
+}
+
+/// Create a swap chain, falling back to [`DEFAULT_FORMAT`]
+///
+/// `wgpu` does not support querying the formats supported by a surface;
+/// instead swap-chain creation panics on an unsupported format. We catch this
+/// and retry with [`DEFAULT_FORMAT`] (updating `desc`), which all surfaces
+/// support.
+fn create_swap_chain(
+    device: &wgpu::Device,
+    surface: &wgpu::Surface,
+    desc: &mut wgpu::SwapChainDescriptor,
+) -> wgpu::SwapChain {
+    if desc.format != DEFAULT_FORMAT {
+        let result = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+            device.create_swap_chain(surface, desc)
+        }));
+        match result {
+            Ok(swap_chain) => return swap_chain,
+            Err(_) => warn!(
+                "Surface does not support format {:?}; using {:?}",
+                desc.format, DEFAULT_FORMAT
+            ),
+        }
+        desc.format = DEFAULT_FORMAT;
+    }
+    device.create_swap_chain(surface, desc)
+}
+
+/// Convert a colour to a `wgpu` clear colour
+///
+/// Both are linear, thus this is correct for any target satisfying
+/// [`crate::draw::is_supported_format`].
+pub(crate) fn to_wgpu_color(c: kas::draw::Colour) -> wgpu::Color {
     wgpu::Color {
         r: c.r as f64,
         g: c.g as f64,
@@ -287,8 +761,17 @@ fn to_wgpu_color(c: kas::draw::Colour) -> wgpu::Color {
     }
 }
 
-struct TkWindow<'a, CB, T> {
-    window: &'a winit::window::Window,
+/// The surface a [`TkWindow`] belongs to
+enum TkTarget<'a> {
+    /// A winit window
+    Window(&'a winit::window::Window),
+    /// An embedded target, with its requested cursor icon
+    Embedded(&'a mut CursorIcon),
+}
+
+/// Implementation of [`kas::TkWindow`], shared with [`crate::Embedded`]
+pub(crate) struct TkWindow<'a, CB, T> {
+    target: TkTarget<'a>,
     shared: &'a mut SharedState<CB, T>,
     _phantom: PhantomData<CB>,
 }
@@ -296,11 +779,30 @@ struct TkWindow<'a, CB, T> {
 impl<'a, CB, T> TkWindow<'a, CB, T> {
     fn new(window: &'a winit::window::Window, shared: &'a mut SharedState<CB, T>) -> Self {
         TkWindow {
-            window,
+            target: TkTarget::Window(window),
             shared,
             _phantom: Default::default(),
         }
     }
+
+    /// Construct for an embedded target, storing the requested cursor icon
+    pub(crate) fn embedded(
+        shared: &'a mut SharedState<CB, T>,
+        cursor_icon: &'a mut CursorIcon,
+    ) -> Self {
+        TkWindow {
+            target: TkTarget::Embedded(cursor_icon),
+            shared,
+            _phantom: Default::default(),
+        }
+    }
+
+    fn window_id(&self) -> Option<winit::window::WindowId> {
+        match &self.target {
+            TkTarget::Window(window) => Some(window.id()),
+            TkTarget::Embedded(_) => None,
+        }
+    }
 }
 
 impl<'a, CB: CustomPipeBuilder, T: Theme<DrawPipe<CB::Pipe>>> kas::TkWindow
@@ -316,7 +818,16 @@ impl<'a, CB: CustomPipeBuilder, T: Theme<DrawPipe<CB::Pipe>>> kas::TkWindow
         let id = self.shared.next_window_id();
         self.shared
             .pending
-            .push(PendingAction::AddWindow(id, widget));
+            .push(PendingAction::AddWindow(id, widget, None));
+        id
+    }
+
+    fn add_modal(&mut self, widget: Box<dyn kas::Window>) -> WindowId {
+        let id = self.shared.next_window_id();
+        let parent = self.window_id();
+        self.shared
+            .pending
+            .push(PendingAction::AddWindow(id, widget, parent));
         id
     }
 
@@ -350,6 +861,88 @@ impl<'a, CB: CustomPipeBuilder, T: Theme<DrawPipe<CB::Pipe>>> kas::TkWindow
 
     #[inline]
     fn set_cursor_icon(&mut self, icon: CursorIcon) {
-        self.window.set_cursor_icon(icon);
+        match &mut self.target {
+            TkTarget::Window(window) => window.set_cursor_icon(icon),
+            TkTarget::Embedded(cursor_icon) => **cursor_icon = icon,
+        }
+    }
+
+    #[inline]
+    fn set_ime_position(&mut self, coord: Coord) {
+        if let TkTarget::Window(window) = &self.target {
+            window.set_ime_position(coord);
+        }
+    }
+
+    fn access_notify(&mut self, event: AccessEvent) {
+        // The tree is built when the notification is handled, and only if
+        // there is a handler to receive it
+        if self.shared.access_handler.is_some() {
+            let id = self.window_id();
+            self.shared
+                .pending
+                .push(PendingAction::AccessNotify(id, event));
+        }
+    }
+
+    fn set_dpi_override(&mut self, factor: Option<f64>) {
+        let id = self.window_id();
+        self.shared
+            .pending
+            .push(PendingAction::SetDpiOverride(id, factor));
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn geometry_round_trip() {
+        let geometries = [
+            WindowGeometry {
+                size: Size(800, 600),
+                position: None,
+                maximized: false,
+            },
+            WindowGeometry {
+                size: Size(800, 600),
+                position: Some(Coord(20, 5)),
+                maximized: false,
+            },
+            WindowGeometry {
+                size: Size(1024, 768),
+                position: Some(Coord(-1920, -30)),
+                maximized: true,
+            },
+            WindowGeometry {
+                size: Size(640, 480),
+                position: None,
+                maximized: true,
+            },
+        ];
+        for geometry in geometries.iter() {
+            let s = geometry.to_string();
+            assert_eq!(s.parse::<WindowGeometry>(), Ok(*geometry), "{}", s);
+        }
+    }
+
+    #[test]
+    fn geometry_parse() {
+        let parse = |s: &str| s.parse::<WindowGeometry>();
+        assert_eq!(parse("800x600+20-5").unwrap().position, Some(Coord(20, -5)));
+        assert_eq!(parse("800x600-20+5").unwrap().position, Some(Coord(-20, 5)));
+        let geometry = parse(" 800x600 maximized ").unwrap();
+        assert_eq!(geometry.size, Size(800, 600));
+        assert_eq!(geometry.position, None);
+        assert!(geometry.maximized);
+        assert!(parse("1024x768-10-10maximized").unwrap().maximized);
+
+        assert_eq!(parse(""), Err(ParseGeometryError));
+        assert_eq!(parse("800"), Err(ParseGeometryError));
+        assert_eq!(parse("800x"), Err(ParseGeometryError));
+        assert_eq!(parse("800x600+20"), Err(ParseGeometryError));
+        assert_eq!(parse("-800x600"), Err(ParseGeometryError));
+        assert_eq!(parse("800x600 minimized"), Err(ParseGeometryError));
     }
 }
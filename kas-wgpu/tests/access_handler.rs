@@ -0,0 +1,41 @@
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License in the LICENSE-APACHE file or at:
+//     https://www.apache.org/licenses/LICENSE-2.0
+
+//! Accessibility notifications passed to `Toolkit::set_access_handler`
+//!
+//! These require a display and graphics adapter; without these they are
+//! skipped.
+
+mod common;
+
+use std::cell::RefCell;
+use std::rc::Rc;
+
+use kas::access::{AccessEvent, Role};
+use kas::widget::{Label, Window};
+
+#[test]
+fn tree_reported_on_configure() {
+    let new_toolkit = || kas_wgpu::Toolkit::new(kas_theme::FlatTheme::new()).unwrap();
+    if !common::probe("tree_reported_on_configure", || drop(new_toolkit())) {
+        return;
+    }
+
+    let mut toolkit = new_toolkit();
+
+    let reports = Rc::new(RefCell::new(vec![]));
+    let r2 = reports.clone();
+    toolkit.set_access_handler(move |id, tree, event| {
+        r2.borrow_mut().push((id, tree.role, event));
+    });
+
+    let id = toolkit
+        .add(Window::new("access", Label::new("text")))
+        .unwrap();
+    assert!(toolkit.pump_events());
+
+    let reports = reports.borrow();
+    assert!(reports.contains(&(id, Role::Window, AccessEvent::TreeChanged)));
+}
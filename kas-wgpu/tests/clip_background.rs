@@ -0,0 +1,80 @@
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License in the LICENSE-APACHE file or at:
+//     https://www.apache.org/licenses/LICENSE-2.0
+
+//! Clip region background tests
+//!
+//! These require a graphics adapter, thus are skipped where none is available.
+
+mod common;
+
+use kas::draw::{Colour, DrawHandle, SizeHandle};
+use kas::event::ManagerState;
+use kas::geom::{Coord, Rect, Size};
+use kas::layout::{AxisInfo, SizeRules};
+use kas::widget::Window;
+use kas::{Layout, Widget};
+use kas_wgpu::{EmbedTarget, Embedded, Options};
+
+/// A widget drawing an (otherwise empty) clip region
+#[derive(Clone, Debug, kas::macros::Widget)]
+#[handler]
+struct Panel {
+    #[core]
+    core: kas::CoreData,
+    region: Rect,
+    background: Option<Colour>,
+}
+
+impl Layout for Panel {
+    fn size_rules(&mut self, _: &mut dyn SizeHandle, _: AxisInfo) -> SizeRules {
+        SizeRules::EMPTY
+    }
+
+    fn draw(&self, draw_handle: &mut dyn DrawHandle, _: &ManagerState) {
+        let (region, offset, draw) = draw_handle.draw_device();
+        draw.add_clip_region(region, self.region + offset, self.background);
+    }
+}
+
+impl Widget for Panel {}
+
+#[test]
+fn clip_region_background() {
+    let options = Options::from_env();
+    let (device, queue) = match common::device("clip_region_background", &options) {
+        Some(device) => device,
+        None => return,
+    };
+
+    let panel = Panel {
+        core: Default::default(),
+        region: Rect::new(Coord(16, 8), Size(16, 16)),
+        background: Some(Colour::new(1.0, 0.0, 0.0)),
+    };
+    let target = EmbedTarget {
+        format: kas_wgpu::draw::DEFAULT_FORMAT,
+        size: Size(64, 32),
+        dpi_factor: 1.0,
+    };
+    let theme = kas_theme::FlatTheme::new();
+    let window = Box::new(Window::new("test", panel));
+    let mut embedded = Embedded::new(device, queue, (), theme, options, window, target)
+        .expect("failed to construct");
+
+    let pixels = common::render(&mut embedded);
+    let pixel = |x: usize, y: usize| pixels[y * 64 + x];
+    let red = [0, 0, 255, 255]; // BGRA
+
+    // The region, including its edges, is filled with its background
+    for (x, y) in [(16, 8), (31, 8), (24, 16), (16, 23), (31, 23)].iter() {
+        assert_eq!(pixel(*x, *y), red, "at ({}, {})", x, y);
+    }
+    // Outside the region, the clear colour is untouched
+    let clear = pixel(0, 0);
+    assert_ne!(clear, red);
+    for (x, y) in [(15, 8), (32, 8), (24, 7), (24, 24), (63, 31)].iter() {
+        assert_eq!(pixel(*x, *y), clear, "at ({}, {})", x, y);
+    }
+}
@@ -0,0 +1,81 @@
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License in the LICENSE-APACHE file or at:
+//     https://www.apache.org/licenses/LICENSE-2.0
+
+//! Shaped clip region tests
+//!
+//! These require a graphics adapter, thus are skipped where none is available.
+
+mod common;
+
+use kas::draw::{ClipShape, Colour, DrawHandle, SizeHandle};
+use kas::event::ManagerState;
+use kas::geom::{Coord, Rect, Size};
+use kas::layout::{AxisInfo, SizeRules};
+use kas::widget::Window;
+use kas::{Layout, Widget};
+use kas_wgpu::{EmbedTarget, Embedded, Options};
+
+/// A widget filling the intersection of two nested clip shapes
+#[derive(Clone, Debug, kas::macros::Widget)]
+#[handler]
+struct Shapes {
+    #[core]
+    core: kas::CoreData,
+    outer: ClipShape,
+    inner: ClipShape,
+}
+
+impl Layout for Shapes {
+    fn size_rules(&mut self, _: &mut dyn SizeHandle, _: AxisInfo) -> SizeRules {
+        SizeRules::EMPTY
+    }
+
+    fn draw(&self, draw_handle: &mut dyn DrawHandle, _: &ManagerState) {
+        let (region, _, draw) = draw_handle.draw_device();
+        let outer = draw.add_clip_shape(region, self.outer);
+        let inner = draw.add_clip_shape(outer, self.inner);
+        let rect = Rect::new(Coord::ZERO, Size(64, 32));
+        draw.rect(inner, rect, Colour::new(1.0, 0.0, 0.0));
+    }
+}
+
+impl Widget for Shapes {}
+
+#[test]
+fn nested_clip_shapes() {
+    let options = Options::from_env();
+    let (device, queue) = match common::device("nested_clip_shapes", &options) {
+        Some(device) => device,
+        None => return,
+    };
+
+    // An oval filling the target, intersected with its right half
+    let shapes = Shapes {
+        core: Default::default(),
+        outer: ClipShape::Oval(Rect::new(Coord(0, 0), Size(64, 32))),
+        inner: ClipShape::RoundedRect(Rect::new(Coord(32, 0), Size(32, 32)), 0.0),
+    };
+    let target = EmbedTarget {
+        format: kas_wgpu::draw::DEFAULT_FORMAT,
+        size: Size(64, 32),
+        dpi_factor: 1.0,
+    };
+    let theme = kas_theme::FlatTheme::new();
+    let window = Box::new(Window::new("test", shapes));
+    let mut embedded = Embedded::new(device, queue, (), theme, options, window, target)
+        .expect("failed to construct");
+
+    let pixels = common::render(&mut embedded);
+    let pixel = |x: usize, y: usize| pixels[y * 64 + x];
+    let red = [0, 0, 255, 255]; // BGRA
+
+    for (x, y) in [(33, 16), (48, 16), (62, 16), (48, 4), (48, 27)].iter() {
+        assert_eq!(pixel(*x, *y), red, "at ({}, {})", x, y);
+    }
+    // Inside only one of the shapes
+    for (x, y) in [(16, 16), (30, 16), (62, 1), (62, 30)].iter() {
+        assert_ne!(pixel(*x, *y), red, "at ({}, {})", x, y);
+    }
+}
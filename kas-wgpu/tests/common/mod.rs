@@ -0,0 +1,133 @@
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License in the LICENSE-APACHE file or at:
+//     https://www.apache.org/licenses/LICENSE-2.0
+
+//! Helpers shared by tests requiring a graphics adapter
+#![allow(dead_code)]
+
+use std::env;
+use std::process::{Command, Stdio};
+use std::sync::{Arc, Mutex};
+
+use kas_theme::Theme;
+use kas_wgpu::draw::{CustomPipeBuilder, DrawPipe};
+use kas_wgpu::{wgpu, Embedded, Options};
+
+const PROBE_VAR: &str = "KAS_TEST_PROBE";
+
+/// Check that `probe` succeeds, running it in a child process
+///
+/// Where no graphics adapter is available, requesting one aborts the process
+/// (wgpu panics within an `extern "C"` function, thus the panic cannot be
+/// caught). A test requiring an adapter (or a display) should call this first
+/// with its own name and a `probe` acquiring these resources: the test is
+/// re-run in a child process which calls `probe`, then exits. If this returns
+/// false, the test should be skipped.
+pub fn probe<F: FnOnce()>(test: &str, probe: F) -> bool {
+    if env::var_os(PROBE_VAR).is_some() {
+        probe();
+        std::process::exit(0);
+    }
+
+    let status = Command::new(env::current_exe().unwrap())
+        .args([test, "--exact", "--test-threads=1"])
+        .env(PROBE_VAR, "1")
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .status();
+    let success = matches!(status, Ok(status) if status.success());
+    if !success {
+        eprintln!("{}: skipped (no graphics adapter or display)", test);
+    }
+    success
+}
+
+/// Request a device and queue
+pub fn request_device(options: &Options) -> (wgpu::Device, wgpu::Queue) {
+    let adapter = wgpu::Adapter::request(&wgpu::RequestAdapterOptions {
+        power_preference: options.power_preference,
+        backends: options.backends,
+    })
+    .expect("no graphics adapter");
+    adapter.request_device(&wgpu::DeviceDescriptor {
+        extensions: wgpu::Extensions {
+            anisotropic_filtering: false,
+        },
+        limits: wgpu::Limits::default(),
+    })
+}
+
+/// Request a device and queue, or `None` if no adapter is available
+///
+/// The `test` name is passed to [`probe`].
+pub fn device(test: &str, options: &Options) -> Option<(wgpu::Device, wgpu::Queue)> {
+    if !probe(test, || drop(request_device(options))) {
+        return None;
+    }
+    Some(request_device(options))
+}
+
+/// Draw `embedded` to a new texture and read back its pixels
+///
+/// The target format must be [`kas_wgpu::draw::DEFAULT_FORMAT`] (BGRA) and
+/// the width a multiple of 64 pixels. Pixels are returned row by row.
+pub fn render<CB, T>(embedded: &mut Embedded<CB, T>) -> Vec<[u8; 4]>
+where
+    CB: CustomPipeBuilder,
+    T: Theme<DrawPipe<CB::Pipe>>,
+{
+    let size = embedded.size();
+    assert_eq!(size.0 % 64, 0, "row pitch must be a multiple of 256 bytes");
+    let extent = wgpu::Extent3d {
+        width: size.0,
+        height: size.1,
+        depth: 1,
+    };
+    let texture = embedded.device().create_texture(&wgpu::TextureDescriptor {
+        size: extent,
+        array_layer_count: 1,
+        mip_level_count: 1,
+        sample_count: 1,
+        dimension: wgpu::TextureDimension::D2,
+        format: kas_wgpu::draw::DEFAULT_FORMAT,
+        usage: wgpu::TextureUsage::OUTPUT_ATTACHMENT | wgpu::TextureUsage::COPY_SRC,
+    });
+    embedded.draw(&texture.create_default_view());
+
+    let len = u64::from(size.0 * size.1 * 4);
+    let buffer = embedded.device().create_buffer(&wgpu::BufferDescriptor {
+        size: len,
+        usage: wgpu::BufferUsage::MAP_READ | wgpu::BufferUsage::COPY_DST,
+    });
+    let desc = wgpu::CommandEncoderDescriptor { todo: 0 };
+    let mut encoder = embedded.device().create_command_encoder(&desc);
+    encoder.copy_texture_to_buffer(
+        wgpu::TextureCopyView {
+            texture: &texture,
+            mip_level: 0,
+            array_layer: 0,
+            origin: wgpu::Origin3d::ZERO,
+        },
+        wgpu::BufferCopyView {
+            buffer: &buffer,
+            offset: 0,
+            row_pitch: size.0 * 4,
+            image_height: size.1,
+        },
+        extent,
+    );
+    embedded.queue().submit(&[encoder.finish()]);
+
+    let pixels = Arc::new(Mutex::new(vec![]));
+    let result = pixels.clone();
+    buffer.map_read_async(0, len, move |mapping: wgpu::BufferMapAsyncResult<&[u8]>| {
+        let data = mapping.expect("failed to map buffer").data;
+        let mut pixels = result.lock().unwrap();
+        pixels.extend(data.chunks_exact(4).map(|p| [p[0], p[1], p[2], p[3]]));
+    });
+    embedded.device().poll(true);
+    let pixels = pixels.lock().unwrap().clone();
+    assert_eq!(pixels.len(), (size.0 * size.1) as usize);
+    pixels
+}
@@ -0,0 +1,75 @@
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License in the LICENSE-APACHE file or at:
+//     https://www.apache.org/licenses/LICENSE-2.0
+
+//! Custom pipe resize tests
+//!
+//! These require a graphics adapter; without one they are skipped.
+
+mod common;
+
+use std::sync::{Arc, Mutex};
+
+use kas::geom::{Rect, Size};
+use kas::widget::{Label, Window};
+use kas_wgpu::draw::{CustomPipe, CustomPipeBuilder, ShaderManager};
+use kas_wgpu::{wgpu, EmbedTarget, Embedded, Options};
+
+/// A pipe recording each size it is built or resized with
+struct Recorder(Arc<Mutex<Vec<Size>>>);
+
+impl CustomPipeBuilder for Recorder {
+    type Pipe = Recorder;
+
+    fn build(
+        &mut self,
+        _: &wgpu::Device,
+        _: &ShaderManager,
+        _: wgpu::TextureFormat,
+        size: Size,
+    ) -> Self::Pipe {
+        self.0.lock().unwrap().push(size);
+        Recorder(self.0.clone())
+    }
+}
+
+impl CustomPipe for Recorder {
+    type Param = ();
+
+    fn resize(&mut self, _: &wgpu::Device, _: &mut wgpu::CommandEncoder, size: Size) {
+        self.0.lock().unwrap().push(size);
+    }
+
+    fn invoke(&mut self, _: usize, _: Rect, _: Self::Param) {}
+
+    fn render(&mut self, _: &wgpu::Device, _: usize, _: Rect, _: &mut wgpu::RenderPass) {}
+}
+
+#[test]
+fn resize_each_size_change() {
+    let options = Options::from_env();
+    let (device, queue) = match common::device("resize_each_size_change", &options) {
+        Some(result) => result,
+        None => return,
+    };
+
+    let sizes = Arc::new(Mutex::new(vec![]));
+    let target = EmbedTarget {
+        format: kas_wgpu::draw::DEFAULT_FORMAT,
+        size: Size(200, 100),
+        dpi_factor: 1.0,
+    };
+    let theme = kas_theme::FlatTheme::new();
+    let window = Box::new(Window::new("test", Label::new("test")));
+    let custom = Recorder(sizes.clone());
+    let mut embedded = Embedded::new(device, queue, custom, theme, options, window, target)
+        .expect("failed to construct");
+    assert_eq!(*sizes.lock().unwrap(), [Size(200, 100)]);
+
+    embedded.resize(Size(300, 150));
+    embedded.resize(Size(300, 150)); // unchanged: no call
+    embedded.resize(Size(200, 100));
+    let expected = [Size(200, 100), Size(300, 150), Size(200, 100)];
+    assert_eq!(*sizes.lock().unwrap(), expected);
+}
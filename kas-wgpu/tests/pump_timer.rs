@@ -0,0 +1,77 @@
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License in the LICENSE-APACHE file or at:
+//     https://www.apache.org/licenses/LICENSE-2.0
+
+//! Timer tests driving the loop via `Toolkit::pump_events`
+//!
+//! These require a display and graphics adapter; without these they are
+//! skipped.
+
+mod common;
+
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::{Duration, Instant};
+
+use kas::draw::{DrawHandle, SizeHandle};
+use kas::event::{Manager, ManagerState};
+use kas::layout::{AxisInfo, SizeRules};
+use kas::widget::Window;
+use kas::{Layout, Widget, WidgetCore};
+
+static TICKS: AtomicUsize = AtomicUsize::new(0);
+
+/// A widget counting timer updates, three in total
+#[derive(Clone, Debug, Default, kas::macros::Widget)]
+#[handler]
+struct Ticker {
+    #[core]
+    core: kas::CoreData,
+}
+
+impl Layout for Ticker {
+    fn size_rules(&mut self, _: &mut dyn SizeHandle, _: AxisInfo) -> SizeRules {
+        SizeRules::fixed(10)
+    }
+
+    fn draw(&self, _: &mut dyn DrawHandle, _: &ManagerState) {}
+}
+
+impl Widget for Ticker {
+    fn configure(&mut self, mgr: &mut Manager) {
+        mgr.update_on_timer(Duration::from_millis(10), self.id());
+    }
+
+    fn update_timer(&mut self, _: &mut Manager) -> Option<Duration> {
+        match TICKS.fetch_add(1, Ordering::SeqCst) {
+            0 | 1 => Some(Duration::from_millis(10)),
+            _ => None,
+        }
+    }
+}
+
+#[test]
+fn timers_fire_when_pumped() {
+    let new_toolkit = || kas_wgpu::Toolkit::new(kas_theme::FlatTheme::new()).unwrap();
+    if !common::probe("timers_fire_when_pumped", || drop(new_toolkit())) {
+        return;
+    }
+
+    let mut toolkit = new_toolkit();
+    toolkit
+        .add(Window::new("timer", Ticker::default()))
+        .unwrap();
+
+    let end = Instant::now() + Duration::from_secs(5);
+    while TICKS.load(Ordering::SeqCst) < 3 && Instant::now() < end {
+        assert!(toolkit.pump_events());
+        if let Some(time) = toolkit.next_timer() {
+            std::thread::sleep(time.saturating_duration_since(Instant::now()));
+        }
+    }
+    assert_eq!(TICKS.load(Ordering::SeqCst), 3);
+
+    // No further timer is scheduled
+    assert!(toolkit.pump_events());
+    assert_eq!(toolkit.next_timer(), None);
+}
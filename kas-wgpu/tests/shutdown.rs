@@ -0,0 +1,78 @@
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License in the LICENSE-APACHE file or at:
+//     https://www.apache.org/licenses/LICENSE-2.0
+
+//! Window shutdown tests
+//!
+//! These require a display and graphics adapter; without these they are
+//! skipped.
+
+mod common;
+
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use kas::widget::{Label, Window};
+
+static CLOSED: [AtomicUsize; 4] = [
+    AtomicUsize::new(0),
+    AtomicUsize::new(0),
+    AtomicUsize::new(0),
+    AtomicUsize::new(0),
+];
+
+fn closed() -> [usize; 4] {
+    let mut counts = [0; 4];
+    for (count, closed) in counts.iter_mut().zip(CLOSED.iter()) {
+        *count = closed.load(Ordering::SeqCst);
+    }
+    counts
+}
+
+/// Window with a final callback counting closures, indexed by title
+fn window(n: usize) -> Window<Label> {
+    let mut window = Window::new(n.to_string(), Label::new("test"));
+    window.set_final_callback(&|w, _| {
+        let n: usize = w.title().parse().unwrap();
+        CLOSED[n].fetch_add(1, Ordering::SeqCst);
+    });
+    window
+}
+
+#[test]
+fn final_callbacks() {
+    let new_toolkit = || kas_wgpu::Toolkit::new(kas_theme::FlatTheme::new()).unwrap();
+    if !common::probe("final_callbacks", || drop(new_toolkit())) {
+        return;
+    }
+
+    let mut toolkit = new_toolkit();
+    let proxy = toolkit.create_proxy();
+
+    let w0 = toolkit.add(window(0)).unwrap();
+    let w1 = toolkit.add(window(1)).unwrap();
+    assert!(toolkit.pump_events());
+
+    proxy.close(w0).unwrap();
+    proxy.close(w0).unwrap(); // closing twice has no effect
+    assert!(toolkit.pump_events());
+    assert_eq!(closed(), [1, 0, 0, 0]);
+
+    // Closing the last window terminates the loop
+    proxy.close(w1).unwrap();
+    assert!(!toolkit.pump_events());
+    assert_eq!(closed(), [1, 1, 0, 0]);
+
+    toolkit.add(window(2)).unwrap();
+    toolkit.add(window(3)).unwrap();
+    assert!(toolkit.pump_events());
+
+    proxy.close_all().unwrap();
+    assert!(!toolkit.pump_events());
+    assert_eq!(closed(), [1, 1, 1, 1]);
+
+    // Callbacks do not run again, even on drop
+    assert!(!toolkit.pump_events());
+    drop(toolkit);
+    assert_eq!(closed(), [1, 1, 1, 1]);
+}
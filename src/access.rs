@@ -0,0 +1,196 @@
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License in the LICENSE-APACHE file or at:
+//     https://www.apache.org/licenses/LICENSE-2.0
+
+//! Accessibility support
+//!
+//! KAS exposes a minimal, platform-neutral model of the widget tree for use by
+//! assistive technologies (e.g. screen readers). Each widget describes itself
+//! via [`Widget::access_node`]; toolkits build the tree with
+//! [`AccessNode::build`] and pass it to a platform adapter. Changes are pushed
+//! to the toolkit as [`AccessEvent`]s via [`Manager::access_notify`].
+//!
+//! `kas_wgpu` includes no platform adapter; it passes the tree and each event
+//! to a handler set by the application (`Toolkit::set_access_handler`).
+//!
+//! [`Manager::access_notify`]: crate::event::Manager::access_notify
+
+use crate::event::ManagerState;
+use crate::{Widget, WidgetId};
+
+/// The role of a widget, as reported to assistive technologies
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Role {
+    /// Role is not known or not applicable
+    Unknown,
+    /// A container of other widgets
+    Group,
+    /// A top-level window
+    Window,
+    /// Static text
+    Label,
+    /// A push-button
+    Button,
+    /// A check box (a toggle)
+    CheckBox,
+    /// A radio button (one of a group of exclusive toggles)
+    RadioButton,
+    /// An editable text field
+    TextEntry,
+    /// A scroll bar
+    ScrollBar,
+}
+
+/// Accessibility description of a widget
+///
+/// This is constructed via [`Widget::access_node`], which should set the
+/// role and any name, value or checked state. The remaining state and
+/// `children` are filled by [`AccessNode::build`].
+#[derive(Clone, Debug, PartialEq)]
+pub struct AccessNode {
+    /// Widget identifier
+    pub id: WidgetId,
+    /// Widget role
+    pub role: Role,
+    /// Name (e.g. the text of a label or button)
+    pub name: Option<String>,
+    /// Value (e.g. the contents of a text entry)
+    pub value: Option<String>,
+    /// Checked state, for toggles
+    pub checked: Option<bool>,
+    /// Whether the widget has keyboard or character focus
+    pub focused: bool,
+    /// Whether the widget is hidden
+    pub hidden: bool,
+    /// Child nodes
+    pub children: Vec<AccessNode>,
+}
+
+impl AccessNode {
+    /// Construct a node with the given role and no name or value
+    pub fn new(id: WidgetId, role: Role) -> Self {
+        AccessNode {
+            id,
+            role,
+            name: None,
+            value: None,
+            checked: None,
+            focused: false,
+            hidden: false,
+            children: vec![],
+        }
+    }
+
+    /// Set the name
+    pub fn with_name<S: ToString>(mut self, name: S) -> Self {
+        self.name = Some(name.to_string());
+        self
+    }
+
+    /// Set the value
+    pub fn with_value<S: ToString>(mut self, value: S) -> Self {
+        self.value = Some(value.to_string());
+        self
+    }
+
+    /// Set the checked state
+    pub fn with_checked(mut self, checked: bool) -> Self {
+        self.checked = Some(checked);
+        self
+    }
+
+    /// Build the accessibility tree for a widget and its children
+    ///
+    /// Focus state is taken from `mgr`. Children of hidden widgets are marked
+    /// hidden.
+    pub fn build(widget: &dyn Widget, mgr: &ManagerState) -> AccessNode {
+        Self::build_inner(widget, mgr, false)
+    }
+
+    fn build_inner(widget: &dyn Widget, mgr: &ManagerState, hidden: bool) -> AccessNode {
+        let id = widget.id();
+        let mut node = widget.access_node();
+        node.id = id;
+        node.focused = mgr.key_focus(id) || mgr.char_focus(id);
+        node.hidden = hidden || !widget.is_visible();
+        node.children = (0..widget.len())
+            .filter_map(|i| widget.get(i))
+            .map(|w| Self::build_inner(w, mgr, node.hidden))
+            .collect();
+        node
+    }
+}
+
+/// User accessibility preferences
+///
+/// These are provided by the toolkit (from OS settings where available,
+/// otherwise from toolkit options) and may be queried by widgets via
+/// [`Manager::access_prefs`].
+///
+/// [`Manager::access_prefs`]: crate::event::Manager::access_prefs
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct AccessPrefs {
+    /// Prefer increased contrast
+    ///
+    /// Themes are notified via [`ThemeApi::set_high_contrast`].
+    ///
+    /// [`ThemeApi::set_high_contrast`]: crate::ThemeApi::set_high_contrast
+    pub high_contrast: bool,
+    /// Prefer reduced motion
+    ///
+    /// Widgets should avoid non-essential animation (e.g. spinners and
+    /// indeterminate progress indicators) and not schedule animation frames
+    /// for it, showing a static representation instead. Widgets activated via
+    /// the keyboard are then shown depressed only while the key is held.
+    pub reduced_motion: bool,
+}
+
+/// Accessibility notifications
+///
+/// These are sent to the toolkit via [`Manager::access_notify`], which should
+/// forward them to the platform adapter (if any).
+///
+/// [`Manager::access_notify`]: crate::event::Manager::access_notify
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum AccessEvent {
+    /// The widget tree was (re)configured; the whole tree should be rebuilt
+    TreeChanged,
+    /// Focus moved to the given widget (or was removed)
+    FocusChanged(Option<WidgetId>),
+    /// The name, value or checked state of a widget changed
+    ValueChanged(WidgetId),
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::event::{Handler, VoidMsg};
+    use crate::testing::Harness;
+    use crate::widget::{CheckBoxBare, EditBox, Label, Row};
+    use crate::WidgetCore;
+
+    #[test]
+    fn access_tree() {
+        let mut row = Row::new(vec![
+            Box::new(Label::new("name")) as Box<dyn Handler<Msg = VoidMsg>>,
+            Box::new(EditBox::new("value").with_autofocus(true)),
+            Box::new(CheckBoxBare::new().state(true)),
+        ]);
+        let mut h = Harness::new(&mut row);
+        row.get_mut(2).unwrap().set_visible(&mut h.manager(), false);
+
+        let tree = AccessNode::build(&row, h.state());
+        assert_eq!(tree.role, Role::Group);
+        let nodes = &tree.children;
+        assert_eq!(nodes.len(), 3);
+        assert_eq!(nodes[0].role, Role::Label);
+        assert_eq!(nodes[0].name.as_deref(), Some("name"));
+        assert_eq!(nodes[1].role, Role::TextEntry);
+        assert_eq!(nodes[1].value.as_deref(), Some("value"));
+        assert!(nodes[1].focused && !nodes[0].focused);
+        assert_eq!(nodes[2].role, Role::CheckBox);
+        assert_eq!(nodes[2].checked, Some(true));
+        assert!(nodes[2].hidden);
+    }
+}
@@ -16,6 +16,9 @@ pub trait HasBool {
     fn get_bool(&self) -> bool;
 
     /// Set the widget's state
+    ///
+    /// This is a no-op (and does not request a redraw) if the state is
+    /// unchanged.
     fn set_bool(&mut self, mgr: &mut Manager, state: bool);
 }
 
@@ -30,16 +33,34 @@ pub trait HasText {
     fn get_text(&self) -> &str;
 
     /// Set the widget's text.
+    ///
+    /// This is a no-op (and does not request a redraw) if the text is
+    /// unchanged.
     fn set_text<T: ToString>(&mut self, mgr: &mut Manager, text: T)
     where
         Self: Sized,
     {
-        self.set_string(mgr, text.to_string());
+        let text = text.to_string();
+        if self.get_text() != text {
+            self.set_string(mgr, text);
+        }
+    }
+
+    /// Set the widget's text, if changed.
+    ///
+    /// This compares `text` with the current text, and only if different
+    /// calls [`HasText::set_string`]. Use this to avoid allocations and redraws
+    /// for frequently updated text.
+    fn set_str(&mut self, mgr: &mut Manager, text: &str) {
+        if self.get_text() != text {
+            self.set_string(mgr, text.to_string());
+        }
     }
 
     /// Set the widget's text (string only).
     ///
-    /// This method is for implementation.
+    /// This method is for implementation. It always replaces the text and
+    /// requests a redraw.
     fn set_string(&mut self, mgr: &mut Manager, text: String);
 }
 
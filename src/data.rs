@@ -10,7 +10,9 @@ use std::fmt;
 use std::num::NonZeroU32;
 use std::u32;
 
+use crate::draw::WidgetStyle;
 use crate::geom::{Rect, Size};
+use crate::layout::Margins;
 
 /// Widget identifier
 ///
@@ -73,10 +75,36 @@ impl fmt::Display for WidgetId {
 /// Common widget data
 ///
 /// All widgets should embed a `#[core] core: CoreData` field.
-#[derive(Clone, Default, Debug)]
+#[derive(Clone, Debug)]
 pub struct CoreData {
     pub rect: Rect,
     pub id: WidgetId,
+    /// Margin override (see [`crate::Widget::with_margins`])
+    pub margins: Option<Margins>,
+    /// Hidden widgets are not sized, drawn or found (see
+    /// [`crate::WidgetCore::set_visible`])
+    pub hidden: bool,
+    /// Opacity, in the range `[0, 1]` (see [`crate::WidgetCore::set_opacity`])
+    pub opacity: f32,
+    /// Whether input passes through the widget while fully transparent (see
+    /// [`crate::WidgetCore::set_pass_through`])
+    pub pass_through: bool,
+    /// Style override (see [`crate::Widget::with_style`])
+    pub style: WidgetStyle,
+}
+
+impl Default for CoreData {
+    fn default() -> Self {
+        CoreData {
+            rect: Default::default(),
+            id: Default::default(),
+            margins: None,
+            hidden: false,
+            opacity: 1.0,
+            pass_through: false,
+            style: WidgetStyle::DEFAULT,
+        }
+    }
 }
 
 /// Alignment of contents
@@ -103,11 +131,61 @@ impl Default for Align {
     }
 }
 
+/// Horizontal layout direction
+///
+/// With [`TextDirection::Rtl`], horizontal layouts place their first child on
+/// the right and [`Align::Begin`] / [`Align::End`] are swapped for text.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum TextDirection {
+    /// Left-to-right
+    Ltr,
+    /// Right-to-left (e.g. Arabic and Hebrew)
+    Rtl,
+}
+
+/// Default direction: Ltr
+impl Default for TextDirection {
+    fn default() -> Self {
+        TextDirection::Ltr
+    }
+}
+
+impl TextDirection {
+    /// True if right-to-left
+    #[inline]
+    pub fn is_rtl(self) -> bool {
+        self == TextDirection::Rtl
+    }
+
+    /// Resolve horizontal alignment for this direction
+    ///
+    /// For right-to-left, [`Align::Begin`] and [`Align::End`] are swapped,
+    /// thus the result is relative to left-to-right layout.
+    #[inline]
+    pub fn halign(self, align: Align) -> Align {
+        match (self, align) {
+            (TextDirection::Rtl, Align::Begin) => Align::End,
+            (TextDirection::Rtl, Align::End) => Align::Begin,
+            (_, align) => align,
+        }
+    }
+}
+
 /// Partial alignment information provided by the parent
 #[derive(Debug, Default)]
 pub struct AlignHints {
     pub horiz: Option<Align>,
     pub vert: Option<Align>,
+    /// Baseline position, relative to the top of the assigned rect
+    ///
+    /// Where set, widgets reporting a baseline (see
+    /// [`SizeRules::with_baseline`]) should place their first text baseline
+    /// at this position (if it fits) in place of vertical alignment.
+    ///
+    /// [`SizeRules::with_baseline`]: crate::layout::SizeRules::with_baseline
+    pub baseline: Option<u32>,
+    /// Layout direction, inherited from the parent
+    pub direction: TextDirection,
 }
 
 impl AlignHints {
@@ -116,7 +194,34 @@ impl AlignHints {
 
     /// Construct with optional horiz. and vert. alignment
     pub const fn new(horiz: Option<Align>, vert: Option<Align>) -> Self {
-        Self { horiz, vert }
+        Self {
+            horiz,
+            vert,
+            baseline: None,
+            direction: TextDirection::Ltr,
+        }
+    }
+
+    /// Construct hints for a child, inheriting the layout direction
+    #[inline]
+    pub fn child(&self) -> Self {
+        let mut hints = AlignHints::NONE;
+        hints.direction = self.direction;
+        hints
+    }
+
+    /// Offset of content with the given `baseline` and height `ideal`,
+    /// within a rect of height `height`
+    ///
+    /// Returns `None` if there is no baseline hint or the content would not
+    /// fit when aligned to it.
+    pub fn baseline_offset(&self, baseline: Option<u32>, ideal: u32, height: u32) -> Option<u32> {
+        let offset = self.baseline?.checked_sub(baseline?)?;
+        if offset + ideal <= height {
+            Some(offset)
+        } else {
+            None
+        }
     }
 
     /// Complete via defaults and ideal size information
@@ -5,7 +5,13 @@
 
 //! Colour type and theming
 
+use std::fmt;
+
 /// Standard colour description
+///
+/// Components are in linear RGB space (values from `0.0` to `1.0`). Note that
+/// 8-bit colour values (e.g. CSS hex codes) are usually encoded in sRGB space;
+/// constructors taking 8-bit components convert from sRGB to linear.
 #[derive(Clone, Copy, Debug, Default, PartialEq)]
 pub struct Colour {
     pub r: f32,
@@ -24,10 +30,220 @@ impl Colour {
     pub const fn grey(s: f32) -> Self {
         Colour::new(s, s, s)
     }
+
+    /// Construct from sRGB-encoded 8-bit components
+    pub fn from_rgb8(r: u8, g: u8, b: u8) -> Self {
+        Colour::from_rgba8(r, g, b, 255)
+    }
+
+    /// Construct from sRGB-encoded 8-bit components with alpha
+    ///
+    /// Alpha is not gamma-encoded, thus is merely scaled to `0.0 ..= 1.0`.
+    pub fn from_rgba8(r: u8, g: u8, b: u8, a: u8) -> Self {
+        Colour {
+            r: srgb_to_linear(r as f32 / 255.0),
+            g: srgb_to_linear(g as f32 / 255.0),
+            b: srgb_to_linear(b as f32 / 255.0),
+            a: a as f32 / 255.0,
+        }
+    }
+
+    /// Convert to sRGB-encoded 8-bit components with alpha
+    ///
+    /// This is the inverse of [`Colour::from_rgba8`]. Components are clamped
+    /// to `0.0 ..= 1.0` and rounded.
+    pub fn to_rgba8(self) -> [u8; 4] {
+        let byte = |c: f32| (c.clamp(0.0, 1.0) * 255.0).round() as u8;
+        [
+            byte(linear_to_srgb(self.r)),
+            byte(linear_to_srgb(self.g)),
+            byte(linear_to_srgb(self.b)),
+            byte(self.a),
+        ]
+    }
+
+    /// Construct from hue, saturation and value
+    ///
+    /// As is conventional, HSV describes sRGB-encoded components: this matches
+    /// the HSV values reported by most software for the same colour. The hue
+    /// is in degrees (wrapping), saturation and value are in `0.0 ..= 1.0`.
+    pub fn from_hsv(hue: f32, sat: f32, val: f32) -> Self {
+        let h = hue.rem_euclid(360.0) / 60.0;
+        let c = val * sat;
+        let x = c * (1.0 - (h % 2.0 - 1.0).abs());
+        let (r, g, b) = match h as u32 {
+            0 => (c, x, 0.0),
+            1 => (x, c, 0.0),
+            2 => (0.0, c, x),
+            3 => (0.0, x, c),
+            4 => (x, 0.0, c),
+            _ => (c, 0.0, x),
+        };
+        let m = val - c;
+        Colour::new(
+            srgb_to_linear(r + m),
+            srgb_to_linear(g + m),
+            srgb_to_linear(b + m),
+        )
+    }
+
+    /// Convert to hue, saturation and value
+    ///
+    /// This is the inverse of [`Colour::from_hsv`]; alpha is ignored. The hue
+    /// of a grey is 0.
+    pub fn to_hsv(self) -> (f32, f32, f32) {
+        let r = linear_to_srgb(self.r);
+        let g = linear_to_srgb(self.g);
+        let b = linear_to_srgb(self.b);
+        let max = r.max(g).max(b);
+        let delta = max - r.min(g).min(b);
+        let hue = if delta <= 0.0 {
+            0.0
+        } else if max == r {
+            60.0 * ((g - b) / delta).rem_euclid(6.0)
+        } else if max == g {
+            60.0 * ((b - r) / delta + 2.0)
+        } else {
+            60.0 * ((r - g) / delta + 4.0)
+        };
+        let sat = if max > 0.0 { delta / max } else { 0.0 };
+        (hue, sat, max)
+    }
+
+    /// Format as a hex colour code
+    ///
+    /// The format is `#RRGGBB`, or `#RRGGBBAA` if not opaque, using sRGB
+    /// components (see [`Colour::to_rgba8`]). The result may be parsed with
+    /// [`Colour::from_hex`].
+    pub fn to_hex(self) -> String {
+        let [r, g, b, a] = self.to_rgba8();
+        if a == 255 {
+            format!("#{:02x}{:02x}{:02x}", r, g, b)
+        } else {
+            format!("#{:02x}{:02x}{:02x}{:02x}", r, g, b, a)
+        }
+    }
+
+    /// Parse a hex colour code
+    ///
+    /// Supported formats are `#RGB`, `#RRGGBB` and `#RRGGBBAA`; the leading
+    /// `#` is optional. Components are interpreted as sRGB (see
+    /// [`Colour::from_rgba8`]).
+    pub fn from_hex(s: &str) -> Result<Self, ParseColourError> {
+        let s = s.strip_prefix('#').unwrap_or(s);
+        // Note: from_str_radix alone would accept a sign, e.g. "+f"
+        if !s.chars().all(|c| c.is_ascii_hexdigit()) {
+            return Err(ParseColourError::InvalidDigit);
+        }
+        let digit = |i: usize| -> Result<u8, ParseColourError> {
+            u8::from_str_radix(&s[i..i + 1], 16).map_err(|_| ParseColourError::InvalidDigit)
+        };
+        let byte = |i: usize| -> Result<u8, ParseColourError> {
+            u8::from_str_radix(&s[i..i + 2], 16).map_err(|_| ParseColourError::InvalidDigit)
+        };
+        match s.len() {
+            3 => Ok(Colour::from_rgb8(
+                digit(0)? * 0x11,
+                digit(1)? * 0x11,
+                digit(2)? * 0x11,
+            )),
+            6 => Ok(Colour::from_rgb8(byte(0)?, byte(2)?, byte(4)?)),
+            8 => Ok(Colour::from_rgba8(byte(0)?, byte(2)?, byte(4)?, byte(6)?)),
+            _ => Err(ParseColourError::InvalidLength),
+        }
+    }
+}
+
+/// Convert an sRGB-encoded component to linear
+fn srgb_to_linear(c: f32) -> f32 {
+    if c <= 0.04045 {
+        c / 12.92
+    } else {
+        ((c + 0.055) / 1.055).powf(2.4)
+    }
+}
+
+/// Convert a linear component to sRGB encoding
+fn linear_to_srgb(c: f32) -> f32 {
+    if c <= 0.003_130_8 {
+        c * 12.92
+    } else {
+        1.055 * c.powf(1.0 / 2.4) - 0.055
+    }
+}
+
+/// Error type returned by [`Colour::from_hex`]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ParseColourError {
+    /// Input length is not 3, 6 or 8 hex digits
+    InvalidLength,
+    /// Input contains a non-hexadecimal character
+    InvalidDigit,
+}
+
+impl fmt::Display for ParseColourError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ParseColourError::InvalidLength => {
+                write!(f, "colour code must have 3, 6 or 8 hex digits")
+            }
+            ParseColourError::InvalidDigit => write!(f, "invalid hex digit in colour code"),
+        }
+    }
 }
 
+impl std::error::Error for ParseColourError {}
+
 impl From<Colour> for [f32; 4] {
     fn from(c: Colour) -> Self {
         [c.r, c.g, c.b, c.a]
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn from_hex() {
+        assert_eq!(Colour::from_hex("#000"), Ok(Colour::grey(0.0)));
+        assert_eq!(Colour::from_hex("#ffffff"), Ok(Colour::grey(1.0)));
+        assert_eq!(Colour::from_hex("f0f"), Colour::from_hex("#ff00ff"));
+        assert_eq!(
+            Colour::from_hex("#ff000080").map(|c| c.a),
+            Ok(128.0 / 255.0)
+        );
+        assert_eq!(
+            Colour::from_hex("#1234"),
+            Err(ParseColourError::InvalidLength)
+        );
+        assert_eq!(
+            Colour::from_hex("#12345g"),
+            Err(ParseColourError::InvalidDigit)
+        );
+        assert_eq!(
+            Colour::from_hex("#+f+f+f"),
+            Err(ParseColourError::InvalidDigit)
+        );
+    }
+
+    #[test]
+    fn round_trip() {
+        for code in &["#000000", "#ffffff", "#12ab9f", "#808080", "#ff000080"] {
+            assert_eq!(Colour::from_hex(code).unwrap().to_hex(), *code);
+        }
+        assert_eq!(Colour::from_hsv(120.0, 1.0, 1.0).to_hex(), "#00ff00");
+        assert_eq!(Colour::from_hsv(-60.0, 1.0, 1.0).to_hex(), "#ff00ff");
+
+        // HSV must not drift from the sRGB value
+        for r in (0..=255).step_by(15) {
+            for g in (0..=255).step_by(17) {
+                for b in (0..=255).step_by(51) {
+                    let c = Colour::from_rgb8(r, g, b);
+                    let (h, s, v) = c.to_hsv();
+                    assert_eq!(Colour::from_hsv(h, s, v).to_rgba8(), [r, g, b, 255]);
+                }
+            }
+        }
+    }
+}
@@ -7,7 +7,7 @@
 
 use std::ops::{Deref, DerefMut};
 
-use kas::draw::{Draw, Region};
+use kas::draw::{Draw, Region, TextStyle, WidgetStyle};
 use kas::event::HighlightState;
 use kas::geom::{Coord, Rect, Size};
 use kas::layout::{AxisInfo, SizeRules};
@@ -62,6 +62,33 @@ pub trait SizeHandle {
     /// Sizing requirements of [`DrawHandle::text`].
     fn text_bound(&mut self, text: &str, class: TextClass, axis: AxisInfo) -> SizeRules;
 
+    /// Get a size bound on text laid out with a [`TextStyle`]
+    ///
+    /// Sizing requirements of [`DrawHandle::text_styled`]. Dimensions of
+    /// `style` are in logical pixels (scaled by the DPI factor by the theme).
+    ///
+    /// The default implementation ignores `style`.
+    fn text_bound_styled(
+        &mut self,
+        text: &str,
+        class: TextClass,
+        _style: TextStyle,
+        axis: AxisInfo,
+    ) -> SizeRules {
+        self.text_bound(text, class, axis)
+    }
+
+    /// Measure the size of some text
+    ///
+    /// Returns the size of `text` as drawn by [`DrawHandle::text`] with the
+    /// given `class`, in physical pixels (thus accounting for the DPI factor).
+    /// The height is at least one [`SizeHandle::line_height`].
+    ///
+    /// If `max_width` is given and `class` wraps text (as for
+    /// [`TextClass::Label`] and [`TextClass::EditMulti`]), text is wrapped to
+    /// this width; otherwise lines are broken only at explicit line breaks.
+    fn measure_text(&mut self, text: &str, class: TextClass, max_width: Option<u32>) -> Size;
+
     /// Size of the sides of a button.
     ///
     /// Includes each side (as in `outer_frame`), minus the content area (to be added separately).
@@ -122,7 +149,11 @@ pub trait DrawHandle {
     /// are subtracted by `offset`).
     ///
     /// All content drawn by the new region is clipped to the given `rect`
-    /// (in the current coordinate space, i.e. not translated by `offset`).
+    /// (in the current coordinate space, i.e. not translated by `offset`),
+    /// intersected with the current clip region.
+    ///
+    /// The new region is only valid within `f`; drawing via `self` after this
+    /// method returns continues to target the parent region.
     fn clip_region(&mut self, rect: Rect, offset: Coord, f: &mut dyn FnMut(&mut dyn DrawHandle));
 
     /// Target area for drawing
@@ -142,6 +173,24 @@ pub trait DrawHandle {
     /// The dimensions required for this text may be queried with [`SizeHandle::text_bound`].
     fn text(&mut self, rect: Rect, text: &str, class: TextClass, align: (Align, Align));
 
+    /// Draw some text with adjusted line height and letter spacing
+    ///
+    /// The dimensions required for this text may be queried with
+    /// [`SizeHandle::text_bound_styled`]. Dimensions of `style` are in logical
+    /// pixels.
+    ///
+    /// The default implementation ignores `style`.
+    fn text_styled(
+        &mut self,
+        rect: Rect,
+        text: &str,
+        class: TextClass,
+        _style: TextStyle,
+        align: (Align, Align),
+    ) {
+        self.text(rect, text, class, align)
+    }
+
     /// Draw button sides, background and margin-area highlight
     fn button(&mut self, rect: Rect, highlights: HighlightState);
 
@@ -167,6 +216,26 @@ pub trait DrawHandle {
     /// -   `dir`: direction of bar
     /// -   `highlights`: highlighting information
     fn scrollbar(&mut self, rect: Rect, h_rect: Rect, dir: Direction, highlights: HighlightState);
+
+    /// Get the style override of the widget being drawn
+    ///
+    /// The default implementation returns the default style.
+    #[inline]
+    fn style(&self) -> WidgetStyle {
+        WidgetStyle::DEFAULT
+    }
+
+    /// Set the style override of the widget being drawn
+    ///
+    /// This is called by [`kas::layout::child_draw`] before drawing a widget
+    /// whose style differs from the current style, and again afterwards to
+    /// restore the previous style. Themes should apply the style to
+    /// subsequent drawing operations, falling back to their defaults for
+    /// hints they do not support.
+    ///
+    /// The default implementation ignores the style.
+    #[inline]
+    fn set_style(&mut self, _style: WidgetStyle) {}
 }
 
 impl<S: SizeHandle> SizeHandle for Box<S> {
@@ -186,6 +255,18 @@ impl<S: SizeHandle> SizeHandle for Box<S> {
     fn text_bound(&mut self, text: &str, class: TextClass, axis: AxisInfo) -> SizeRules {
         self.deref_mut().text_bound(text, class, axis)
     }
+    fn text_bound_styled(
+        &mut self,
+        text: &str,
+        class: TextClass,
+        style: TextStyle,
+        axis: AxisInfo,
+    ) -> SizeRules {
+        self.deref_mut().text_bound_styled(text, class, style, axis)
+    }
+    fn measure_text(&mut self, text: &str, class: TextClass, max_width: Option<u32>) -> Size {
+        self.deref_mut().measure_text(text, class, max_width)
+    }
 
     fn button_surround(&self) -> (Size, Size) {
         self.deref().button_surround()
@@ -226,6 +307,18 @@ where
     fn text_bound(&mut self, text: &str, class: TextClass, axis: AxisInfo) -> SizeRules {
         self.deref_mut().text_bound(text, class, axis)
     }
+    fn text_bound_styled(
+        &mut self,
+        text: &str,
+        class: TextClass,
+        style: TextStyle,
+        axis: AxisInfo,
+    ) -> SizeRules {
+        self.deref_mut().text_bound_styled(text, class, style, axis)
+    }
+    fn measure_text(&mut self, text: &str, class: TextClass, max_width: Option<u32>) -> Size {
+        self.deref_mut().measure_text(text, class, max_width)
+    }
 
     fn button_surround(&self) -> (Size, Size) {
         self.deref().button_surround()
@@ -261,6 +354,17 @@ impl<H: DrawHandle> DrawHandle for Box<H> {
     fn text(&mut self, rect: Rect, text: &str, class: TextClass, align: (Align, Align)) {
         self.deref_mut().text(rect, text, class, align)
     }
+    fn text_styled(
+        &mut self,
+        rect: Rect,
+        text: &str,
+        class: TextClass,
+        style: TextStyle,
+        align: (Align, Align),
+    ) {
+        self.deref_mut()
+            .text_styled(rect, text, class, style, align)
+    }
     fn button(&mut self, rect: Rect, highlights: HighlightState) {
         self.deref_mut().button(rect, highlights)
     }
@@ -276,6 +380,12 @@ impl<H: DrawHandle> DrawHandle for Box<H> {
     fn scrollbar(&mut self, rect: Rect, h_rect: Rect, dir: Direction, highlights: HighlightState) {
         self.deref_mut().scrollbar(rect, h_rect, dir, highlights)
     }
+    fn style(&self) -> WidgetStyle {
+        self.deref().style()
+    }
+    fn set_style(&mut self, style: WidgetStyle) {
+        self.deref_mut().set_style(style)
+    }
 }
 
 #[cfg(feature = "stack_dst")]
@@ -298,6 +408,17 @@ where
     fn text(&mut self, rect: Rect, text: &str, class: TextClass, align: (Align, Align)) {
         self.deref_mut().text(rect, text, class, align)
     }
+    fn text_styled(
+        &mut self,
+        rect: Rect,
+        text: &str,
+        class: TextClass,
+        style: TextStyle,
+        align: (Align, Align),
+    ) {
+        self.deref_mut()
+            .text_styled(rect, text, class, style, align)
+    }
     fn button(&mut self, rect: Rect, highlights: HighlightState) {
         self.deref_mut().button(rect, highlights)
     }
@@ -313,4 +434,10 @@ where
     fn scrollbar(&mut self, rect: Rect, h_rect: Rect, dir: Direction, highlights: HighlightState) {
         self.deref_mut().scrollbar(rect, h_rect, dir, highlights)
     }
+    fn style(&self) -> WidgetStyle {
+        self.deref().style()
+    }
+    fn set_style(&mut self, style: WidgetStyle) {
+        self.deref_mut().set_style(style)
+    }
 }
@@ -35,6 +35,9 @@
 //! routines. Toolkits must implement support for [`Draw`] while other
 //! extensions are optional; toolkits may also provide their own extensions.
 //!
+//! [`Painter`] wraps these traits in a path-based API (as used by
+//! [`kas::widget::Canvas`]).
+//!
 //! ### Low-level interface
 //!
 //! There is no universal graphics API, hence none is provided by this crate.
@@ -43,15 +46,21 @@
 
 mod colour;
 mod handle;
+mod painter;
+mod style;
 mod text;
 
 use std::any::Any;
 
-use crate::geom::{Coord, Rect};
+use crate::geom::{Affine, Coord, Rect, Size};
+use crate::WidgetId;
 
-pub use colour::Colour;
+pub use colour::{Colour, ParseColourError};
 pub use handle::{DrawHandle, SizeHandle, TextClass};
-pub use text::{DrawText, Font, FontId, TextProperties};
+pub use painter::Painter;
+pub use style::{Emphasis, WidgetStyle};
+pub(crate) use text::caret_mark;
+pub use text::{visual_order, DrawText, Font, FontId, LineHeight, TextProperties, TextStyle};
 
 /// Type returned by [`Draw::add_clip_region`].
 ///
@@ -59,6 +68,31 @@ pub use text::{DrawText, Font, FontId, TextProperties};
 #[derive(Copy, Clone, Default)]
 pub struct Region(pub usize);
 
+/// A non-rectangular clip shape
+///
+/// Used by [`Draw::add_clip_shape`]. Only the shapes below are supported;
+/// clipping to arbitrary paths is not.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum ClipShape {
+    /// A rectangle with corners rounded to the given radius
+    ///
+    /// The radius is clamped to half the width and height of the rect.
+    RoundedRect(Rect, f32),
+    /// An axis-aligned oval (or circle) inscribed in the rect
+    Oval(Rect),
+}
+
+impl ClipShape {
+    /// The bounding rect
+    #[inline]
+    pub fn bounds(&self) -> Rect {
+        match self {
+            ClipShape::RoundedRect(rect, _) => *rect,
+            ClipShape::Oval(rect) => *rect,
+        }
+    }
+}
+
 /// Base abstraction over drawing
 ///
 /// All draw operations target some region identified by a handle of type
@@ -75,10 +109,100 @@ pub trait Draw: Any {
     /// toolkit-specific API.
     fn as_any_mut(&mut self) -> &mut dyn Any;
 
+    /// Access the [`DrawRounded`] extension, if implemented
+    ///
+    /// This allows use of rounded shapes via `&mut dyn Draw` (e.g. by
+    /// [`Painter`]). The default implementation returns `None`.
+    fn as_rounded(&mut self) -> Option<&mut dyn DrawRounded> {
+        None
+    }
+
     /// Add a clip region
     ///
+    /// The new region is the intersection of `rect` with the `parent` region,
+    /// thus drawing in a nested region never escapes the parent's bounds.
+    ///
+    /// If a `background` colour is given, the new region is filled with this
+    /// colour before any other content of the region is drawn. Otherwise the
+    /// region is transparent over the parent's content.
+    ///
     /// Clip regions are cleared each frame and so must be recreated on demand.
-    fn add_clip_region(&mut self, region: Rect) -> Region;
+    fn add_clip_region(&mut self, parent: Region, rect: Rect, background: Option<Colour>)
+        -> Region;
+
+    /// Add a clip region with non-rectangular shape
+    ///
+    /// As with [`Draw::add_clip_region`], the new region is the intersection
+    /// of `shape` with the `parent` region; nested shapes intersect.
+    ///
+    /// Implementations without support for shaped clipping may clip to the
+    /// shape's bounding rect instead; the default implementation does this.
+    fn add_clip_shape(&mut self, parent: Region, shape: ClipShape) -> Region {
+        self.add_clip_region(parent, shape.bounds(), None)
+    }
+
+    /// Begin drawing widget `id`, replaying cached geometry if possible
+    ///
+    /// If geometry was recorded for `id` under the same `key` and `dirty` is
+    /// false, the implementation may replay this geometry into `region` and
+    /// return true, in which case the widget is not drawn. Otherwise this
+    /// returns false and the implementation may record geometry until
+    /// [`Draw::cache_end`] is called with the same `id`.
+    ///
+    /// Widgets should not call this directly; see
+    /// [`kas::layout::child_draw`]. The default implementation does no
+    /// caching and always returns false.
+    fn cache_begin(&mut self, _region: Region, _id: WidgetId, _key: u64, _dirty: bool) -> bool {
+        false
+    }
+
+    /// End drawing widget `id`; see [`Draw::cache_begin`]
+    fn cache_end(&mut self, _id: WidgetId) {}
+
+    /// Get the opacity multiplier
+    ///
+    /// This is 1 except while drawing a partially transparent widget (see
+    /// [`kas::WidgetCore::set_opacity`]).
+    fn opacity(&self) -> f32 {
+        1.0
+    }
+
+    /// Set the opacity multiplier
+    ///
+    /// The alpha component of all subsequently drawn colours (including text)
+    /// is multiplied by `opacity`, which is in the range `[0, 1]`.
+    ///
+    /// Widgets should not call this directly; see
+    /// [`kas::layout::child_draw`]. Implementations without alpha blending
+    /// may ignore this; the default implementation does so.
+    fn set_opacity(&mut self, _opacity: f32) {}
+
+    /// Get the current transform
+    ///
+    /// This is the identity transform unless [`Draw::push_transform`] is used.
+    fn transform(&self) -> Affine {
+        Affine::IDENTITY
+    }
+
+    /// Push a transform, applied to subsequently drawn geometry
+    ///
+    /// The new transform applies `transform`, then the current transform;
+    /// thus nested transforms compose. The transform is removed by
+    /// [`Draw::pop_transform`]; all transforms are removed at the end of each
+    /// frame.
+    ///
+    /// Transforms apply to the vertices of drawn shapes, thus (for example) a
+    /// rotated [`Draw::rect`] is drawn as a rotated rectangle. Clip regions
+    /// are not transformed, nor is shading (e.g. of [`DrawShaded`] shapes),
+    /// which remains relative to the untransformed shape. Text is only
+    /// transformed when [`TextProperties::transform`] is set.
+    ///
+    /// Implementations without support for transforms may ignore this; the
+    /// default implementation does so.
+    fn push_transform(&mut self, _transform: Affine) {}
+
+    /// Remove the transform last added by [`Draw::push_transform`]
+    fn pop_transform(&mut self) {}
 
     /// Draw a rectangle of uniform colour
     fn rect(&mut self, region: Region, rect: Rect, col: Colour);
@@ -87,6 +211,39 @@ pub trait Draw: Any {
     ///
     /// The frame is defined by the area inside `outer` and not inside `inner`.
     fn frame(&mut self, region: Region, outer: Rect, inner: Rect, col: Colour);
+
+    /// Draw a hairline of uniform colour
+    ///
+    /// This draws a line of width one device pixel between the pixels at `p1`
+    /// and `p2` (inclusive). Horizontal and vertical hairlines cover exactly
+    /// one row or column of pixels; other lines are not anti-aliased.
+    ///
+    /// For thicker lines, use [`Draw::rect`] or [`DrawRounded::rounded_line`].
+    ///
+    /// The default implementation draws a "staircase" of rects: one for each
+    /// run of pixels along the major axis.
+    fn hairline(&mut self, region: Region, p1: Coord, p2: Coord, col: Colour) {
+        let (dx, dy) = (p2.0 - p1.0, p2.1 - p1.1);
+        let steps = dx.abs().max(dy.abs());
+        // Offset of pixel i along an axis of length d, rounded to nearest
+        let offset = |d: i32, i: i32| (2 * d * i + steps).div_euclid(2 * steps.max(1));
+
+        let mut run = |a: Coord, b: Coord| {
+            let (a, b) = (a.min(b), a.max(b));
+            let size = Size((b.0 - a.0 + 1) as u32, (b.1 - a.1 + 1) as u32);
+            self.rect(region, Rect::new(a, size), col);
+        };
+        let (mut a, mut b) = (p1, p1);
+        for i in 1..=steps {
+            let p = Coord(p1.0 + offset(dx, i), p1.1 + offset(dy, i));
+            if p.0 != a.0 && p.1 != a.1 {
+                run(a, b);
+                a = p;
+            }
+            b = p;
+        }
+        run(a, b);
+    }
 }
 
 /// Drawing commands for rounded shapes
@@ -175,3 +332,53 @@ pub trait DrawShaded: Draw {
         col: Colour,
     );
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    /// Records each rect drawn (for frames, the outer rect)
+    #[derive(Default)]
+    struct Rects(Vec<Rect>);
+
+    impl Draw for Rects {
+        fn as_any_mut(&mut self) -> &mut dyn Any {
+            self
+        }
+        fn add_clip_region(&mut self, _: Region, _: Rect, _: Option<Colour>) -> Region {
+            Region(1)
+        }
+        fn rect(&mut self, _: Region, rect: Rect, _: Colour) {
+            self.0.push(rect);
+        }
+        fn frame(&mut self, _: Region, outer: Rect, _: Rect, _: Colour) {
+            self.0.push(outer);
+        }
+    }
+
+    fn hairline(p1: Coord, p2: Coord) -> Vec<Rect> {
+        let mut draw = Rects::default();
+        draw.hairline(Region(0), p1, p2, Colour::grey(0.0));
+        draw.0
+    }
+
+    #[test]
+    fn hairline_default() {
+        let rect = |x, y, w, h| Rect::new(Coord(x, y), Size(w, h));
+        assert_eq!(hairline(Coord(3, 4), Coord(3, 4)), [rect(3, 4, 1, 1)]);
+        assert_eq!(hairline(Coord(7, 4), Coord(2, 4)), [rect(2, 4, 6, 1)]);
+        assert_eq!(hairline(Coord(2, 9), Coord(2, 4)), [rect(2, 4, 1, 6)]);
+
+        // A shallow line is drawn as horizontal runs
+        let runs = [rect(0, 0, 1, 1), rect(1, 1, 2, 1), rect(3, 2, 2, 1)];
+        assert_eq!(hairline(Coord(0, 0), Coord(4, 2)), runs);
+        let runs = [rect(4, 0, 1, 1), rect(2, 1, 2, 1), rect(0, 2, 2, 1)];
+        assert_eq!(hairline(Coord(4, 0), Coord(0, 2)), runs);
+
+        // A steep line is drawn as vertical runs
+        let runs = [rect(0, 0, 1, 1), rect(1, 1, 1, 1), rect(2, 2, 1, 1)];
+        assert_eq!(hairline(Coord(0, 0), Coord(2, 2)), runs);
+        let runs = [rect(0, 0, 1, 1), rect(1, 1, 1, 2), rect(2, 3, 1, 2)];
+        assert_eq!(hairline(Coord(0, 0), Coord(2, 4)), runs);
+    }
+}
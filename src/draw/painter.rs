@@ -0,0 +1,498 @@
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License in the LICENSE-APACHE file or at:
+//     https://www.apache.org/licenses/LICENSE-2.0
+
+//! Path-based drawing
+
+use std::cmp::Ordering;
+
+use super::{Colour, Draw, Region};
+use crate::geom::{Affine, Coord, Rect, Size, Vec2};
+
+/// Control point offset approximating a quarter circle by a cubic curve
+const KAPPA: f32 = 0.552_284_8;
+
+/// A sub-path, in draw coordinates
+#[derive(Clone, Debug)]
+struct SubPath {
+    points: Vec<Vec2>,
+    closed: bool,
+}
+
+/// A path-based painter over a [`Draw`] device
+///
+/// Paths are constructed with [`Painter::move_to`], [`Painter::line_to`] and
+/// [`Painter::curve_to`], then drawn with [`Painter::fill`] or
+/// [`Painter::stroke`], each of which consumes the path.
+///
+/// Points are mapped by the current transform when added to the path.
+/// Initially this maps `(0, 0)` to the top-left corner of the painter's rect,
+/// with units of physical pixels; it may be adjusted with
+/// [`Painter::translate`] and friends and saved and restored with
+/// [`Painter::push_transform`] and [`Painter::pop_transform`]. (This stack is
+/// independent of [`Draw::push_transform`], which applies to the painter's
+/// output as to any other drawing.)
+///
+/// Fills use [`Draw::rect`] (one rect per span of each run of equal pixel
+/// rows) and are not anti-aliased. Strokes use [`DrawRounded::rounded_line`]
+/// where available (see [`Draw::as_rounded`]), otherwise hairlines. All
+/// drawing is clipped to the painter's region.
+///
+/// [`DrawRounded::rounded_line`]: super::DrawRounded::rounded_line
+pub struct Painter<'a> {
+    draw: &'a mut dyn Draw,
+    region: Region,
+    rect: Rect,
+    dpi_factor: f32,
+    colour: Colour,
+    line_width: f32,
+    transform: Affine,
+    stack: Vec<Affine>,
+    path: Vec<SubPath>,
+}
+
+impl<'a> Painter<'a> {
+    /// Construct, drawing to `rect` of `region`
+    ///
+    /// The colour is initially black and the line width 1.
+    pub fn new(draw: &'a mut dyn Draw, region: Region, rect: Rect, dpi_factor: f32) -> Self {
+        Painter {
+            draw,
+            region,
+            rect,
+            dpi_factor,
+            colour: Colour::grey(0.0),
+            line_width: 1.0,
+            transform: Affine::translate(rect.pos.into()),
+            stack: vec![],
+            path: vec![],
+        }
+    }
+
+    /// Size of the painted area, in physical pixels
+    #[inline]
+    pub fn size(&self) -> Size {
+        self.rect.size
+    }
+
+    /// The window's DPI factor
+    #[inline]
+    pub fn dpi_factor(&self) -> f32 {
+        self.dpi_factor
+    }
+
+    /// Access the draw device, region and rect directly
+    ///
+    /// Coordinates passed to the draw device are not transformed.
+    #[inline]
+    pub fn draw_device(&mut self) -> (&mut dyn Draw, Region, Rect) {
+        (&mut *self.draw, self.region, self.rect)
+    }
+
+    /// Set the colour used by subsequent fills and strokes
+    #[inline]
+    pub fn set_colour(&mut self, colour: Colour) {
+        self.colour = colour;
+    }
+
+    /// Set the line width used by subsequent strokes
+    ///
+    /// This is scaled by the transform at the time of the stroke.
+    #[inline]
+    pub fn set_line_width(&mut self, width: f32) {
+        self.line_width = width;
+    }
+
+    /// The current transform, mapping to draw coordinates
+    #[inline]
+    pub fn transform(&self) -> Affine {
+        self.transform
+    }
+
+    /// Save the current transform, then apply `transform` to it
+    ///
+    /// Subsequent points are mapped by `transform`, then the prior transform.
+    pub fn push_transform(&mut self, transform: Affine) {
+        self.stack.push(self.transform);
+        self.transform = self.transform * transform;
+    }
+
+    /// Restore the transform saved by the last [`Painter::push_transform`]
+    ///
+    /// Does nothing if the stack is empty.
+    pub fn pop_transform(&mut self) {
+        if let Some(transform) = self.stack.pop() {
+            self.transform = transform;
+        }
+    }
+
+    /// Translate the current transform
+    #[inline]
+    pub fn translate(&mut self, v: impl Into<Vec2>) {
+        self.transform = self.transform * Affine::translate(v.into());
+    }
+
+    /// Scale the current transform
+    #[inline]
+    pub fn scale(&mut self, s: impl Into<Vec2>) {
+        self.transform = self.transform * Affine::scale(s.into());
+    }
+
+    /// Rotate the current transform by `angle` radians (clockwise)
+    #[inline]
+    pub fn rotate(&mut self, angle: f32) {
+        self.transform = self.transform * Affine::rotate(angle);
+    }
+
+    /// The current point, in draw coordinates
+    fn current(&self) -> Option<Vec2> {
+        self.path.last().map(|sub| match sub.closed {
+            true => sub.points[0],
+            false => *sub.points.last().unwrap(),
+        })
+    }
+
+    fn start_sub_path(&mut self, p: Vec2) {
+        self.path.push(SubPath {
+            points: vec![p],
+            closed: false,
+        });
+    }
+
+    /// Push `p` (in draw coordinates) to the open sub-path
+    fn push_point(&mut self, p: Vec2) {
+        match self.path.last() {
+            None => return self.start_sub_path(p),
+            Some(sub) if sub.closed => {
+                // Continue from the start of the closed sub-path
+                let start = sub.points[0];
+                self.start_sub_path(start);
+            }
+            Some(_) => (),
+        }
+        self.path.last_mut().unwrap().points.push(p);
+    }
+
+    /// Begin a new sub-path at `p`
+    pub fn move_to(&mut self, p: impl Into<Vec2>) {
+        let p = self.transform.apply(p.into());
+        self.start_sub_path(p);
+    }
+
+    /// Add a straight line to `p`
+    ///
+    /// If there is no current point, this is equivalent to
+    /// [`Painter::move_to`].
+    pub fn line_to(&mut self, p: impl Into<Vec2>) {
+        let p = self.transform.apply(p.into());
+        self.push_point(p);
+    }
+
+    /// Add a cubic Bézier curve to `p`, with control points `c1` and `c2`
+    ///
+    /// The curve is approximated by line segments. If there is no current
+    /// point, the curve starts at `c1`.
+    pub fn curve_to(&mut self, c1: impl Into<Vec2>, c2: impl Into<Vec2>, p: impl Into<Vec2>) {
+        let c1 = self.transform.apply(c1.into());
+        let c2 = self.transform.apply(c2.into());
+        let p = self.transform.apply(p.into());
+        let p0 = match self.current() {
+            Some(p0) => p0,
+            None => {
+                self.start_sub_path(c1);
+                c1
+            }
+        };
+
+        // Approximately one segment per 4 pixels of control polygon
+        let len = (c1 - p0).length() + (c2 - c1).length() + (p - c2).length();
+        let n = (len / 4.0).ceil().clamp(1.0, 256.0) as u32;
+        for i in 1..n {
+            let t = i as f32 / n as f32;
+            let u = 1.0 - t;
+            let q = p0 * (u * u * u) + c1 * (3.0 * u * u * t) + c2 * (3.0 * u * t * t);
+            self.push_point(q + p * (t * t * t));
+        }
+        self.push_point(p);
+    }
+
+    /// Close the current sub-path with a line to its start
+    pub fn close_path(&mut self) {
+        if let Some(sub) = self.path.last_mut() {
+            sub.closed = true;
+        }
+    }
+
+    /// Add a closed rectangle with corner `pos`
+    pub fn rect(&mut self, pos: impl Into<Vec2>, size: impl Into<Vec2>) {
+        let (pos, size) = (pos.into(), size.into());
+        self.move_to(pos);
+        self.line_to(pos + Vec2(size.0, 0.0));
+        self.line_to(pos + size);
+        self.line_to(pos + Vec2(0.0, size.1));
+        self.close_path();
+    }
+
+    /// Add a closed circle
+    pub fn circle(&mut self, centre: impl Into<Vec2>, radius: f32) {
+        let c = centre.into();
+        let k = KAPPA * radius;
+        self.move_to(c + Vec2(radius, 0.0));
+        for &(x, y) in &[(0.0, 1.0), (-1.0, 0.0), (0.0, -1.0), (1.0, 0.0)] {
+            // Direction of the previous point (and of the tangent at end)
+            let (px, py) = (y, -x);
+            let end = c + Vec2(x, y) * radius;
+            let c1 = c + Vec2(px, py) * radius + Vec2(x, y) * k;
+            let c2 = end + Vec2(px, py) * k;
+            self.curve_to(c1, c2, end);
+        }
+        self.close_path();
+    }
+
+    /// Fill the path (using the non-zero winding rule), then clear it
+    ///
+    /// All sub-paths are implicitly closed.
+    pub fn fill(&mut self) {
+        let path = std::mem::take(&mut self.path);
+        let mut edges = vec![];
+        for sub in &path {
+            let points = &sub.points;
+            for (i, a) in points.iter().enumerate() {
+                let b = points[(i + 1) % points.len()];
+                if a.1 != b.1 {
+                    edges.push((*a, b));
+                }
+            }
+        }
+        if edges.is_empty() {
+            return;
+        }
+
+        let (mut min_y, mut max_y) = (f32::INFINITY, f32::NEG_INFINITY);
+        for (a, _) in &edges {
+            min_y = min_y.min(a.1);
+            max_y = max_y.max(a.1);
+        }
+        let (x0, y0) = (self.rect.pos.0, self.rect.pos.1);
+        let (x1, y1) = (x0 + self.rect.size.0 as i32, y0 + self.rect.size.1 as i32);
+        let top = (min_y.floor() as i32).max(y0);
+        let bottom = (max_y.ceil() as i32).min(y1);
+
+        // Spans of the current run of equal rows, and its first row
+        let mut run: Option<(i32, Vec<(i32, i32)>)> = None;
+        let mut crossings = vec![];
+        for y in top..bottom {
+            // Sample each row at its centre
+            let ys = y as f32 + 0.5;
+            crossings.clear();
+            for (a, b) in &edges {
+                let (lo, hi, dir) = if a.1 < b.1 { (a, b, 1) } else { (b, a, -1) };
+                if lo.1 <= ys && ys < hi.1 {
+                    let x = lo.0 + (ys - lo.1) / (hi.1 - lo.1) * (hi.0 - lo.0);
+                    crossings.push((x, dir));
+                }
+            }
+            crossings.sort_by(|a, b| a.0.partial_cmp(&b.0).unwrap_or(Ordering::Equal));
+
+            let mut spans = vec![];
+            let (mut winding, mut start) = (0, 0.0);
+            for (x, dir) in &crossings {
+                if winding == 0 {
+                    start = *x;
+                }
+                winding += dir;
+                if winding == 0 {
+                    let a = (start.round() as i32).max(x0);
+                    let b = (x.round() as i32).min(x1);
+                    if b > a {
+                        spans.push((a, b));
+                    }
+                }
+            }
+
+            match run {
+                Some((_, ref prev)) if *prev == spans => (),
+                _ => {
+                    if let Some((start, prev)) = run.replace((y, spans)) {
+                        self.fill_spans(start, y, &prev);
+                    }
+                }
+            }
+        }
+        if let Some((start, spans)) = run {
+            self.fill_spans(start, bottom, &spans);
+        }
+    }
+
+    /// Fill `spans` over rows `y0..y1`
+    fn fill_spans(&mut self, y0: i32, y1: i32, spans: &[(i32, i32)]) {
+        for (x0, x1) in spans {
+            let size = Size((x1 - x0) as u32, (y1 - y0) as u32);
+            let rect = Rect::new(Coord(*x0, y0), size);
+            self.draw.rect(self.region, rect, self.colour);
+        }
+    }
+
+    /// Stroke the path, then clear it
+    ///
+    /// Lines have rounded ends and joins.
+    pub fn stroke(&mut self) {
+        let path = std::mem::take(&mut self.path);
+        let radius = 0.5 * self.line_width * self.transform.mean_scale();
+        let (region, col) = (self.region, self.colour);
+        let coord = |p: Vec2| Coord(p.0.round() as i32, p.1.round() as i32);
+        for sub in &path {
+            let points = &sub.points;
+            let mut segments: Vec<_> = points.windows(2).map(|w| (w[0], w[1])).collect();
+            if sub.closed && points.len() > 2 {
+                segments.push((points[points.len() - 1], points[0]));
+            }
+            for (a, b) in segments {
+                match self.draw.as_rounded() {
+                    Some(draw) => draw.rounded_line(region, coord(a), coord(b), radius, col),
+                    None => self.draw.hairline(region, coord(a), coord(b), col),
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use std::any::Any;
+
+    /// A draw device recording rects, frames and hairlines
+    #[derive(Default)]
+    struct Recorder {
+        rects: Vec<Rect>,
+        frames: Vec<(Rect, Rect)>,
+        lines: Vec<(Coord, Coord)>,
+    }
+
+    impl Draw for Recorder {
+        fn as_any_mut(&mut self) -> &mut dyn Any {
+            self
+        }
+        fn add_clip_region(&mut self, _: Region, _: Rect, _: Option<Colour>) -> Region {
+            Region(1)
+        }
+        fn rect(&mut self, _: Region, rect: Rect, _: Colour) {
+            self.rects.push(rect);
+        }
+        fn frame(&mut self, _: Region, outer: Rect, inner: Rect, _: Colour) {
+            self.frames.push((outer, inner));
+        }
+        fn hairline(&mut self, _: Region, p1: Coord, p2: Coord, _: Colour) {
+            self.lines.push((p1, p2));
+        }
+    }
+
+    fn paint<F: FnOnce(&mut Painter)>(f: F) -> Recorder {
+        let mut draw = Recorder::default();
+        let rect = Rect::new(Coord(10, 20), Size(100, 50));
+        f(&mut Painter::new(&mut draw, Region(0), rect, 1.0));
+        draw
+    }
+
+    #[test]
+    fn fill_spans() {
+        let draw = paint(|p| {
+            p.rect((2.0, 3.0), (10.0, 5.0));
+            p.fill();
+        });
+        assert_eq!(draw.rects, [Rect::new(Coord(12, 23), Size(10, 5))]);
+        assert!(draw.frames.is_empty());
+
+        // Two sub-paths: an L shape of two runs, one of two spans
+        let draw = paint(|p| {
+            p.rect((0.0, 0.0), (4.0, 2.0));
+            p.rect((0.0, 2.0), (1.0, 2.0));
+            p.rect((3.0, 2.0), (1.0, 2.0));
+            p.fill();
+        });
+        let expected = [
+            Rect::new(Coord(10, 20), Size(4, 2)),
+            Rect::new(Coord(10, 22), Size(1, 2)),
+            Rect::new(Coord(13, 22), Size(1, 2)),
+        ];
+        assert_eq!(draw.rects, expected);
+
+        // The path is consumed
+        let draw = paint(|p| {
+            p.rect((0.0, 0.0), (4.0, 2.0));
+            p.fill();
+            p.fill();
+        });
+        assert_eq!(draw.rects.len(), 1);
+    }
+
+    #[test]
+    fn fill_clipped() {
+        let draw = paint(|p| {
+            p.rect((-5.0, 40.0), (200.0, 20.0));
+            p.fill();
+        });
+        assert_eq!(draw.rects, [Rect::new(Coord(10, 60), Size(100, 10))]);
+
+        let draw = paint(|p| {
+            p.rect((-10.0, -10.0), (5.0, 5.0));
+            p.fill();
+        });
+        assert!(draw.rects.is_empty());
+    }
+
+    #[test]
+    fn stroke_segments() {
+        let draw = paint(|p| {
+            p.move_to((0.0, 0.0));
+            p.line_to((10.0, 0.0));
+            p.line_to((10.0, 10.0));
+            p.stroke();
+
+            p.rect((1.0, 1.0), (2.0, 2.0));
+            p.stroke();
+        });
+        let expected = [
+            (Coord(10, 20), Coord(20, 20)),
+            (Coord(20, 20), Coord(20, 30)),
+            (Coord(11, 21), Coord(13, 21)),
+            (Coord(13, 21), Coord(13, 23)),
+            (Coord(13, 23), Coord(11, 23)),
+            (Coord(11, 23), Coord(11, 21)),
+        ];
+        assert_eq!(draw.lines, expected);
+    }
+
+    #[test]
+    fn nested_transforms() {
+        let draw = paint(|p| {
+            p.translate((5.0, 0.0));
+            p.push_transform(Affine::scale(Vec2(2.0, 2.0)));
+            p.push_transform(Affine::translate(Vec2(1.0, 1.0)));
+            p.rect((0.0, 0.0), (1.0, 1.0));
+            p.fill();
+
+            p.pop_transform();
+            p.rect((0.0, 0.0), (1.0, 1.0));
+            p.fill();
+
+            p.pop_transform();
+            p.rect((0.0, 0.0), (1.0, 1.0));
+            p.fill();
+
+            // Popping an empty stack does nothing
+            p.pop_transform();
+            p.rect((1.0, 0.0), (1.0, 1.0));
+            p.fill();
+        });
+        let expected = [
+            Rect::new(Coord(17, 22), Size(2, 2)),
+            Rect::new(Coord(15, 20), Size(2, 2)),
+            Rect::new(Coord(15, 20), Size(1, 1)),
+            Rect::new(Coord(16, 20), Size(1, 1)),
+        ];
+        assert_eq!(draw.rects, expected);
+    }
+}
@@ -0,0 +1,129 @@
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License in the LICENSE-APACHE file or at:
+//     https://www.apache.org/licenses/LICENSE-2.0
+
+//! Per-widget style overrides
+
+use super::Colour;
+
+/// Emphasis of a widget
+///
+/// Themes choose how to present each emphasis; further variants may be added,
+/// thus themes should ignore variants they do not support.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum Emphasis {
+    /// The main action of a group, usually drawn in an accent colour
+    Primary,
+    /// A destructive action, usually drawn in red
+    Danger,
+}
+
+/// Style override of a single widget
+///
+/// This is a set of hints, each of which defaults to `None` (use the theme's
+/// default). Themes apply hints where meaningful and ignore the rest; an
+/// explicit colour takes precedence over emphasis.
+///
+/// The style applies only to the widget it is set on (see
+/// [`kas::Widget::with_style`]), not to its children. (Composite widgets such
+/// as [`kas::widget::CheckBox`] may forward their style to their parts.)
+#[derive(Copy, Clone, Debug, Default, PartialEq)]
+#[non_exhaustive]
+pub struct WidgetStyle {
+    /// Emphasis hint
+    pub emphasis: Option<Emphasis>,
+    /// Colour of the widget's body (e.g. a button face or check mark)
+    pub colour: Option<Colour>,
+    /// Colour of text
+    pub text_colour: Option<Colour>,
+}
+
+impl WidgetStyle {
+    /// The default style (no overrides)
+    pub const DEFAULT: WidgetStyle = WidgetStyle {
+        emphasis: None,
+        colour: None,
+        text_colour: None,
+    };
+
+    /// Construct, with no overrides
+    #[inline]
+    pub fn new() -> Self {
+        WidgetStyle::DEFAULT
+    }
+
+    /// Set the emphasis hint
+    #[inline]
+    pub fn emphasis(mut self, emphasis: Emphasis) -> Self {
+        self.emphasis = Some(emphasis);
+        self
+    }
+
+    /// Set the body colour
+    #[inline]
+    pub fn colour(mut self, colour: Colour) -> Self {
+        self.colour = Some(colour);
+        self
+    }
+
+    /// Set the text colour
+    #[inline]
+    pub fn text_colour(mut self, colour: Colour) -> Self {
+        self.text_colour = Some(colour);
+        self
+    }
+
+    /// True if no override is set
+    #[inline]
+    pub fn is_default(&self) -> bool {
+        *self == WidgetStyle::DEFAULT
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::event::Handler;
+    use crate::geom::Size;
+    use crate::layout;
+    use crate::testing::{Harness, RecordingHandle, TextSizeHandle};
+    use crate::widget::{CheckBox, Row, TextButton};
+    use crate::{Layout, Widget};
+
+    #[test]
+    fn style_scope() {
+        let red = WidgetStyle::new().colour(Colour::new(1.0, 0.0, 0.0));
+        let primary = WidgetStyle::new().emphasis(Emphasis::Primary);
+        let mut row = Row::new(vec![
+            Box::new(CheckBox::new_on(|_| (), "check").with_style(red))
+                as Box<dyn Handler<Msg = ()>>,
+            Box::new(TextButton::new("a", ()).with_style(primary)),
+            Box::new(TextButton::new("b", ())),
+            Box::new(Row::new(vec![TextButton::new("c", ())]).with_style(primary)),
+        ]);
+        let mut h = Harness::new(&mut row);
+        layout::solve(&mut row, &mut TextSizeHandle, Size(300, 20));
+        let mut draw = RecordingHandle::default();
+        row.draw(&mut draw, h.state());
+
+        let items: Vec<_> = draw.items.iter().map(|item| (item.0, item.2)).collect();
+        let default = WidgetStyle::DEFAULT;
+        let expected = [
+            // CheckBox forwards its style to the mark and label
+            ("checkbox", red),
+            ("text", red),
+            // A leaf widget draws with its own style
+            ("button", primary),
+            ("text", primary),
+            // ... which is restored afterwards
+            ("button", default),
+            ("text", default),
+            // A parent's style does not reach its children
+            ("button", default),
+            ("text", default),
+        ];
+        assert_eq!(items, expected);
+    }
+}
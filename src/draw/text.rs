@@ -7,6 +7,8 @@
 
 pub use rusttype::Font;
 
+use std::borrow::Cow;
+
 use super::{Colour, Draw};
 use crate::geom::Rect;
 use crate::Align;
@@ -21,6 +23,85 @@ use crate::Align;
 #[derive(Copy, Clone, Debug, Default, PartialEq, Eq, Hash)]
 pub struct FontId(pub usize);
 
+/// Line height of text
+///
+/// This is the distance between the baselines of consecutive lines. The
+/// position of the first line is not affected.
+#[derive(Copy, Clone, Debug, Default, PartialEq)]
+pub enum LineHeight {
+    /// The font's natural line height
+    #[default]
+    Normal,
+    /// A multiple of the font scale
+    Relative(f32),
+    /// A height in pixels
+    Absolute(f32),
+}
+
+impl LineHeight {
+    /// Resolve to a height in pixels
+    ///
+    /// Requires the font scale and the font's natural line height.
+    pub fn resolve(self, scale: f32, normal: f32) -> f32 {
+        match self {
+            LineHeight::Normal => normal,
+            LineHeight::Relative(m) => m * scale,
+            LineHeight::Absolute(h) => h,
+        }
+    }
+}
+
+/// Typographic adjustments to text layout
+#[derive(Copy, Clone, Debug, Default, PartialEq)]
+pub struct TextStyle {
+    /// Line height
+    pub line_height: LineHeight,
+    /// Extra space between characters, in pixels (may be negative)
+    pub letter_spacing: f32,
+    /// Distance between tab stops, in pixels
+    ///
+    /// A tab character (`'\t'`) advances to the next multiple of this width,
+    /// measured from the start of the line. If `None`, the width of eight
+    /// spaces is used.
+    pub tab_width: Option<f32>,
+}
+
+impl TextStyle {
+    /// True if this is the default style (no adjustments)
+    pub fn is_default(&self) -> bool {
+        *self == TextStyle::default()
+    }
+
+    /// Scale pixel dimensions by `factor`
+    ///
+    /// Widgets specify styles in logical pixels; themes use this to convert
+    /// to physical pixels by the DPI factor.
+    pub fn scale(self, factor: f32) -> Self {
+        let line_height = match self.line_height {
+            LineHeight::Absolute(h) => LineHeight::Absolute(h * factor),
+            other => other,
+        };
+        TextStyle {
+            line_height,
+            letter_spacing: self.letter_spacing * factor,
+            tab_width: self.tab_width.map(|w| w * factor),
+        }
+    }
+
+    /// Position of the next tab stop after `x`
+    ///
+    /// Positions are relative to the start of the line. `space` is the
+    /// advance of a space, used when [`TextStyle::tab_width`] is `None`.
+    pub fn next_tab_stop(&self, x: f32, space: f32) -> f32 {
+        let width = self.tab_width.unwrap_or(8.0 * space);
+        if width > 0.0 {
+            ((x / width).floor() + 1.0) * width
+        } else {
+            x
+        }
+    }
+}
+
 /// Text properties for use by [`DrawText::text`]
 #[derive(Copy, Clone, Debug, Default, PartialEq)]
 pub struct TextProperties {
@@ -35,6 +116,17 @@ pub struct TextProperties {
     pub align: (Align, Align),
     /// True if text should automatically be line-wrapped
     pub line_wrap: bool,
+    /// Line height and letter spacing (in pixels)
+    ///
+    /// If `None`, text is laid out with the font's natural metrics.
+    pub style: Option<TextStyle>,
+    /// True if text should honour the current draw transform
+    ///
+    /// See [`kas::draw::Draw::push_transform`]. Text may be translated and
+    /// scaled but is never rotated or skewed: the position of the text rect
+    /// is transformed and its size and the font scale are multiplied by the
+    /// transform's scale factors.
+    pub transform: bool,
 }
 
 /// Abstraction over text rendering
@@ -71,4 +163,234 @@ pub trait DrawText: Draw {
         bounds: (f32, f32),
         line_wrap: bool,
     ) -> (f32, f32);
+
+    /// Calculate size bound on text laid out with a [`TextStyle`]
+    ///
+    /// As [`DrawText::text_bound`], for text drawn with
+    /// [`TextProperties::style`] set to `Some(style)`.
+    ///
+    /// The default implementation ignores `style`.
+    fn text_bound_styled(
+        &mut self,
+        text: &str,
+        font_id: FontId,
+        font_scale: f32,
+        bounds: (f32, f32),
+        line_wrap: bool,
+        _style: TextStyle,
+    ) -> (f32, f32) {
+        self.text_bound(text, font_id, font_scale, bounds, line_wrap)
+    }
+
+    /// Get the font ascent
+    ///
+    /// This is the distance from the top of a line of text to its baseline,
+    /// for the given font and scale.
+    ///
+    /// The default implementation approximates this as 80% of `font_scale`,
+    /// which is close for most fonts.
+    fn font_ascent(&mut self, _font_id: FontId, font_scale: f32) -> f32 {
+        0.8 * font_scale
+    }
+}
+
+/// Reorder `text` from logical to visual (left-to-right) order for display
+///
+/// This is a simplified form of the Unicode Bidirectional Algorithm: the
+/// paragraph direction is taken from the first strong character, neutral
+/// characters between two runs of the same direction take that direction
+/// (otherwise the paragraph direction), and runs are reversed by level.
+/// Explicit embeddings and bracket pairs are not supported; the directional
+/// marks U+200E and U+200F are supported and removed from the output. Each
+/// line is processed independently, thus wrapped text should have line breaks
+/// inserted before reordering.
+///
+/// Text without right-to-left characters is returned unchanged.
+pub fn visual_order(text: &str) -> Cow<'_, str> {
+    if !text.chars().any(is_rtl) {
+        return Cow::Borrowed(text);
+    }
+
+    let mut out = String::with_capacity(text.len());
+    for (i, line) in text.split('\n').enumerate() {
+        if i > 0 {
+            out.push('\n');
+        }
+        visual_line(line, &mut out);
+    }
+    Cow::Owned(out)
+}
+
+#[derive(Clone, Copy, PartialEq)]
+enum BidiClass {
+    Ltr,
+    Rtl,
+    Neutral,
+}
+
+const LRM: char = '\u{200E}';
+const RLM: char = '\u{200F}';
+
+fn is_rtl(c: char) -> bool {
+    match c as u32 {
+        0x0590..=0x08FF | 0xFB1D..=0xFDFF | 0xFE70..=0xFEFF => true,
+        0x10800..=0x10FFF | 0x1E800..=0x1EFFF => true,
+        _ => c == RLM,
+    }
+}
+
+/// Directional mark to place after a caret at the end of `text`
+///
+/// A caret is neutral, thus [`visual_order`] would place it according to the
+/// paragraph direction. Followed by this mark, it is instead placed on the
+/// trailing side of the last character. Returns `None` if `text` has no
+/// right-to-left characters.
+pub(crate) fn caret_mark(text: &str) -> Option<char> {
+    if !text.chars().any(is_rtl) {
+        return None;
+    }
+    let mut classes = text.chars().rev().map(bidi_class);
+    match classes.find(|class| *class != BidiClass::Neutral) {
+        Some(BidiClass::Rtl) => Some(RLM),
+        _ => Some(LRM),
+    }
+}
+
+fn bidi_class(c: char) -> BidiClass {
+    if is_rtl(c) {
+        BidiClass::Rtl
+    } else if c.is_alphanumeric() || c == LRM {
+        BidiClass::Ltr
+    } else {
+        BidiClass::Neutral
+    }
+}
+
+fn visual_line(line: &str, out: &mut String) {
+    let mut chars: Vec<char> = line.chars().collect();
+    let classes: Vec<BidiClass> = chars.iter().map(|c| bidi_class(*c)).collect();
+    let para = classes
+        .iter()
+        .cloned()
+        .find(|class| *class != BidiClass::Neutral)
+        .unwrap_or(BidiClass::Ltr);
+    let level = |class| match (para, class) {
+        (BidiClass::Rtl, BidiClass::Ltr) => 2,
+        (_, BidiClass::Rtl) => 1,
+        (BidiClass::Rtl, _) => 1,
+        _ => 0,
+    };
+
+    let len = chars.len();
+    let mut levels = vec![0u8; len];
+    let mut i = 0;
+    while i < len {
+        if classes[i] != BidiClass::Neutral {
+            levels[i] = level(classes[i]);
+            i += 1;
+            continue;
+        }
+        let start = i;
+        while i < len && classes[i] == BidiClass::Neutral {
+            i += 1;
+        }
+        let before = if start > 0 { classes[start - 1] } else { para };
+        let after = if i < len { classes[i] } else { para };
+        let class = if before == after { before } else { para };
+        for l in &mut levels[start..i] {
+            *l = level(class);
+        }
+    }
+
+    // Reverse each maximal run at or above each level, highest first
+    let max = levels.iter().cloned().max().unwrap_or(0);
+    for l in (1..=max).rev() {
+        let mut i = 0;
+        while i < len {
+            if levels[i] < l {
+                i += 1;
+                continue;
+            }
+            let start = i;
+            while i < len && levels[i] >= l {
+                i += 1;
+            }
+            chars[start..i].reverse();
+            levels[start..i].reverse();
+        }
+    }
+
+    out.extend(chars.into_iter().filter(|c| *c != LRM && *c != RLM));
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn bidi_visual_order() {
+        assert!(matches!(visual_order("abc def"), Cow::Borrowed(_)));
+        assert_eq!(visual_order("abc אבג def"), "abc גבא def");
+        assert_eq!(visual_order("אב cd"), "cd בא");
+        assert_eq!(visual_order("אב 12."), ".12 בא");
+        assert_eq!(visual_order("אב\nab גד"), "בא\nab דג");
+        assert_eq!(visual_order("\u{200F}ab."), ".ab");
+    }
+
+    #[test]
+    fn bidi_caret() {
+        let with_caret = |text: &str| {
+            let mut text = text.to_string();
+            let mark = caret_mark(&text);
+            text.push('|');
+            text.extend(mark);
+            visual_order(&text).into_owned()
+        };
+        assert_eq!(caret_mark("abc"), None);
+        assert_eq!(with_caret("abc"), "abc|");
+        assert_eq!(with_caret("abc אבג"), "abc |גבא");
+        assert_eq!(with_caret("אב cd"), "cd| בא");
+        assert_eq!(with_caret("אב."), "|.בא");
+    }
+
+    #[test]
+    fn text_style_scale() {
+        let style = TextStyle {
+            line_height: LineHeight::Absolute(20.0),
+            letter_spacing: 1.5,
+            tab_width: Some(40.0),
+        };
+        assert!(!style.is_default());
+        let scaled = style.scale(2.0);
+        assert_eq!(scaled.line_height, LineHeight::Absolute(40.0));
+        assert_eq!(scaled.letter_spacing, 3.0);
+        assert_eq!(scaled.line_height.resolve(16.0, 18.0), 40.0);
+        assert_eq!(scaled.tab_width, Some(80.0));
+
+        let relative = TextStyle {
+            line_height: LineHeight::Relative(1.5),
+            ..TextStyle::default()
+        };
+        assert_eq!(relative.scale(2.0), relative);
+        assert_eq!(relative.line_height.resolve(16.0, 18.0), 24.0);
+        assert_eq!(LineHeight::Normal.resolve(16.0, 18.0), 18.0);
+        assert!(TextStyle::default().is_default());
+    }
+
+    #[test]
+    fn tab_stops() {
+        let style = TextStyle {
+            tab_width: Some(40.0),
+            ..TextStyle::default()
+        };
+        assert_eq!(style.next_tab_stop(0.0, 5.0), 40.0);
+        assert_eq!(style.next_tab_stop(39.5, 5.0), 40.0);
+        assert_eq!(style.next_tab_stop(40.0, 5.0), 80.0);
+        assert_eq!(TextStyle::default().next_tab_stop(13.0, 5.0), 40.0);
+        let none = TextStyle {
+            tab_width: Some(0.0),
+            ..TextStyle::default()
+        };
+        assert_eq!(none.next_tab_stop(13.0, 5.0), 13.0);
+    }
 }
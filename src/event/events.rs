@@ -5,10 +5,13 @@
 
 //! Event handling: events
 
-use super::MouseButton;
+use std::any::Any;
+use std::sync::Arc;
+
+use super::{MouseButton, UpdateHandle, VirtualKeyCode};
 
 use crate::geom::Coord;
-use crate::WidgetId;
+use crate::{WidgetCore, WidgetId};
 
 /// High-level events addressed to a widget by [`WidgetId`]
 #[derive(Clone, Debug)]
@@ -17,11 +20,45 @@ pub enum Action {
     Activate,
     /// Widget receives a character of text input
     ReceivedCharacter(char),
-    /// A mouse or touchpad scroll event
-    Scroll(ScrollDelta),
+    /// A mouse or touchpad scroll event, with the cursor coordinate
+    ///
+    /// The state of keyboard modifiers (e.g. for ctrl+scroll zoom) is available
+    /// from [`Manager::modifiers`](super::Manager::modifiers).
+    Scroll(ScrollDelta, Coord),
+    /// A context menu was requested at the given coordinate
+    ///
+    /// This is generated from a press of the secondary (right) mouse button by
+    /// [`Manager::handle_generic`](super::Manager::handle_generic). Widgets
+    /// not supporting a context menu should return this as unhandled, thus
+    /// allowing a parent to handle it.
+    ContextMenu(Coord),
+    /// A press is being dragged to the given coordinate, requesting scrolling
+    ///
+    /// Widgets supporting drag-and-drop may return this as unhandled while
+    /// dragging. A parent [`ScrollRegion`] then scrolls when the coordinate is
+    /// near or beyond the edge of its viewport; other widgets should return
+    /// this as unhandled.
+    ///
+    /// [`ScrollRegion`]: crate::widget::ScrollRegion
+    AutoScroll(Coord),
+    /// An update was triggered on a subscribed handle, with the given payload
+    ///
+    /// See [`Manager::update_on_handle`](super::Manager::update_on_handle).
+    Update(UpdateHandle, u64),
+    /// Data was sent over an update channel on a subscribed handle
+    ///
+    /// The data may be accessed via `<dyn Any>::downcast_ref`. Update channels
+    /// are provided by the toolkit.
+    UpdateData(UpdateHandle, Arc<dyn Any + Send + Sync>),
 }
 
 /// Low-level events addressed to a widget by [`WidgetId`] or coordinate.
+///
+/// Coordinates are in physical pixels relative to the window (the same space
+/// as [`WidgetCore::rect`]), translated by any parent [`ScrollRegion`]. Use
+/// [`Event::local_coord`] to get a coordinate relative to the widget.
+///
+/// [`ScrollRegion`]: crate::widget::ScrollRegion
 #[derive(Clone, Debug)]
 pub enum Event {
     Action(Action),
@@ -43,7 +80,8 @@ pub enum Event {
     /// Received only given a [press grab](super::Manager::request_press_grab).
     ///
     /// When `end_id == None`, this is a "cancelled press": the end of the press
-    /// is outside the application window.
+    /// is outside the application window, or the press was cancelled (e.g. a
+    /// mouse press, by pressing `Escape`).
     PressEnd {
         source: PressSource,
         end_id: Option<WidgetId>,
@@ -51,6 +89,69 @@ pub enum Event {
     },
 }
 
+impl Event {
+    /// Get the coordinate associated with this event, if any
+    pub fn coord(&self) -> Option<Coord> {
+        match self {
+            Event::Action(Action::Scroll(_, coord))
+            | Event::Action(Action::ContextMenu(coord))
+            | Event::Action(Action::AutoScroll(coord))
+            | Event::PressStart { coord, .. }
+            | Event::PressMove { coord, .. }
+            | Event::PressEnd { coord, .. } => Some(*coord),
+            Event::Action(_) => None,
+        }
+    }
+
+    /// Get the coordinate associated with this event relative to the
+    /// position of `widget`, if any
+    ///
+    /// This is the event's coordinate minus `widget.rect().pos`, thus
+    /// `Coord::ZERO` is the top-left corner of the widget.
+    #[inline]
+    pub fn local_coord<W: WidgetCore + ?Sized>(&self, widget: &W) -> Option<Coord> {
+        self.coord().map(|coord| coord - widget.rect().pos)
+    }
+
+    /// Translate the event's coordinate (if any) by `offset`
+    pub fn translate(self, offset: Coord) -> Self {
+        match self {
+            Event::Action(Action::Scroll(delta, coord)) => {
+                Event::Action(Action::Scroll(delta, coord + offset))
+            }
+            Event::Action(Action::ContextMenu(coord)) => {
+                Event::Action(Action::ContextMenu(coord + offset))
+            }
+            Event::Action(Action::AutoScroll(coord)) => {
+                Event::Action(Action::AutoScroll(coord + offset))
+            }
+            Event::PressStart { source, coord } => Event::PressStart {
+                source,
+                coord: coord + offset,
+            },
+            Event::PressMove {
+                source,
+                coord,
+                delta,
+            } => Event::PressMove {
+                source,
+                coord: coord + offset,
+                delta,
+            },
+            Event::PressEnd {
+                source,
+                end_id,
+                coord,
+            } => Event::PressEnd {
+                source,
+                end_id,
+                coord: coord + offset,
+            },
+            event @ Event::Action(_) => event,
+        }
+    }
+}
+
 /// Source of `EventChild::Press`
 #[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
 pub enum PressSource {
@@ -72,6 +173,8 @@ impl PressSource {
 }
 
 /// Type used by [`Action::Scroll`]
+///
+/// Pixel deltas are in physical pixels.
 #[derive(Clone, Copy, Debug)]
 pub enum ScrollDelta {
     /// Scroll a given number of lines
@@ -79,3 +182,142 @@ pub enum ScrollDelta {
     /// Scroll a given number of pixels
     PixelDelta(Coord),
 }
+
+/// State of the keyboard modifiers
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub struct Modifiers {
+    /// The "shift" key
+    pub shift: bool,
+    /// The "control" key
+    pub ctrl: bool,
+    /// The "alt" key
+    pub alt: bool,
+    /// The "logo" key ("windows" key on PC, "command" key on Mac)
+    pub logo: bool,
+}
+
+#[cfg(feature = "winit")]
+impl From<winit::event::ModifiersState> for Modifiers {
+    #[inline]
+    fn from(state: winit::event::ModifiersState) -> Self {
+        Modifiers {
+            shift: state.shift(),
+            ctrl: state.ctrl(),
+            alt: state.alt(),
+            logo: state.logo(),
+        }
+    }
+}
+
+/// A toolkit-independent input event
+///
+/// Toolkits translate native input events to this type for dispatch via
+/// [`Manager::handle_synthetic`](super::Manager::handle_synthetic). User code
+/// may construct these directly in order to inject synthetic input (e.g. for
+/// testing).
+#[derive(Clone, Debug)]
+pub enum InputEvent {
+    /// Text input
+    ReceivedCharacter(char),
+    /// A key was pressed: `(scancode, virtual_keycode)`
+    KeyPressed(u32, VirtualKeyCode),
+    /// A key was released: `scancode`
+    KeyReleased(u32),
+    /// The mouse cursor moved to the given coordinate
+    CursorMoved(Coord),
+    /// The mouse cursor left the window
+    CursorLeft,
+    /// A mouse wheel or touchpad scroll event
+    MouseWheel(ScrollDelta),
+    /// A mouse button was pressed
+    MousePressed(MouseButton),
+    /// A mouse button was released
+    MouseReleased(MouseButton),
+    /// A touch started: `(touch_id, coord)`
+    TouchStart(u64, Coord),
+    /// A touch moved: `(touch_id, coord)`
+    TouchMove(u64, Coord),
+    /// A touch ended: `(touch_id, coord)`
+    TouchEnd(u64, Coord),
+    /// A touch was cancelled: `(touch_id, coord)`
+    TouchCancel(u64, Coord),
+    /// The state of keyboard modifiers changed
+    ModifiersChanged(Modifiers),
+}
+
+#[cfg(all(test, feature = "winit"))]
+mod test {
+    use super::*;
+    use crate::draw::{DrawHandle, SizeHandle};
+    use crate::event::{Handler, Manager, ManagerState, Response, VoidMsg};
+    use crate::geom::{Rect, Size};
+    use crate::layout::{AxisInfo, SizeRules};
+    use crate::macros::Widget;
+    use crate::testing::Harness;
+    use crate::{CoreData, Layout, TkAction};
+    use winit::dpi::{LogicalPosition, PhysicalPosition};
+    use winit::event::{DeviceId, ModifiersState, MouseScrollDelta, TouchPhase, WindowEvent};
+
+    #[test]
+    fn scroll_local_coord() {
+        #[derive(Clone, Debug, Default, Widget)]
+        #[widget]
+        struct Canvas {
+            #[core]
+            core: CoreData,
+            scroll: Option<(ScrollDelta, Coord, bool)>,
+        }
+
+        impl Layout for Canvas {
+            fn size_rules(&mut self, _: &mut dyn SizeHandle, _: AxisInfo) -> SizeRules {
+                SizeRules::EMPTY
+            }
+            fn draw(&self, _: &mut dyn DrawHandle, _: &ManagerState) {}
+        }
+
+        impl Handler for Canvas {
+            type Msg = VoidMsg;
+            fn handle_event(&mut self, mgr: &mut Manager, event: Event) -> Response<VoidMsg> {
+                let local = event.local_coord(self).unwrap();
+                match event {
+                    Event::Action(Action::Scroll(delta, _)) => {
+                        self.scroll = Some((delta, local, mgr.modifiers().ctrl));
+                        Response::None
+                    }
+                    event => Response::Unhandled(event),
+                }
+            }
+        }
+
+        let mut widget = Canvas::default();
+        let mut h = Harness::with_dpi(2.0, &mut widget);
+        widget.core_data_mut().rect = Rect::new(Coord(20, 10), Size(100, 100));
+
+        // Cursor positions are physical while pixel deltas are logical
+        #[allow(deprecated)]
+        let move_to = |x, y| WindowEvent::CursorMoved {
+            device_id: unsafe { DeviceId::dummy() },
+            position: PhysicalPosition::new(x, y),
+            modifiers: ModifiersState::empty(),
+        };
+        #[allow(deprecated)]
+        let wheel = WindowEvent::MouseWheel {
+            device_id: unsafe { DeviceId::dummy() },
+            delta: MouseScrollDelta::PixelDelta(LogicalPosition::new(3.0, -4.0)),
+            phase: TouchPhase::Moved,
+            modifiers: ModifiersState::CTRL,
+        };
+        let action = h.manager().handle_winit(&mut widget, move_to(50.0, 40.0));
+        assert_eq!(action, TkAction::None);
+        let action = h.manager().handle_winit(&mut widget, wheel);
+        assert_eq!(action, TkAction::None);
+        match widget.scroll {
+            Some((ScrollDelta::PixelDelta(d), local, ctrl)) => {
+                assert_eq!(d, Coord(6, -8));
+                assert_eq!(local, Coord(30, 30));
+                assert!(ctrl);
+            }
+            r => panic!("unexpected: {:?}", r),
+        }
+    }
+}
@@ -7,8 +7,11 @@
 
 use std::time::Duration;
 
+use crate::access::AccessNode;
 use crate::draw::{DrawHandle, SizeHandle};
-use crate::event::{Action, Event, Manager, ManagerState, Response, UpdateHandle};
+use crate::event::{
+    Action, Event, Manager, ManagerState, MouseButton, PressSource, Response, UpdateHandle,
+};
 use crate::geom::{Coord, Rect};
 use crate::layout::{AxisInfo, SizeRules};
 use crate::{AlignHints, CoreData, Layout, Widget, WidgetCore, WidgetId};
@@ -46,11 +49,30 @@ pub trait Handler: Widget {
         Response::Unhandled(Event::Action(action))
     }
 
+    /// Handle an event addressed to this widget or not handled by a child.
+    ///
+    /// This is called by [`Manager::handle_generic`] for all events it does
+    /// not consume itself (including those translated to actions). Parent
+    /// widgets with a manual implementation of [`Handler::handle`] should
+    /// also pass events returned by a child via [`Response::Unhandled`] to
+    /// this method, allowing e.g. scroll and press events to be intercepted.
+    ///
+    /// The default implementation forwards [`Event::Action`] to
+    /// [`Handler::handle_action`] and returns other events via
+    /// [`Response::Unhandled`].
+    #[inline]
+    fn handle_event(&mut self, mgr: &mut Manager, event: Event) -> Response<Self::Msg> {
+        match event {
+            Event::Action(action) => self.handle_action(mgr, action),
+            event => Response::Unhandled(event),
+        }
+    }
+
     /// Handle a low-level event.
     ///
     /// Most non-parent widgets will not need to implement this method manually.
     /// The default implementation (which wraps [`Manager::handle_generic`])
-    /// forwards high-level events via [`Handler::handle_action`].
+    /// forwards events via [`Handler::handle_event`].
     ///
     /// Parent widgets should forward events to the appropriate child widget,
     /// translating event coordinates where applicable. Unused events should be
@@ -77,6 +99,10 @@ impl<M> Handler for Box<dyn Handler<Msg = M>> {
         self.as_mut().handle_action(mgr, action)
     }
 
+    fn handle_event(&mut self, mgr: &mut Manager, event: Event) -> Response<Self::Msg> {
+        self.as_mut().handle_event(mgr, event)
+    }
+
     fn handle(&mut self, mgr: &mut Manager, id: WidgetId, event: Event) -> Response<Self::Msg> {
         self.as_mut().handle(mgr, id, event)
     }
@@ -98,6 +124,14 @@ impl<M> Widget for Box<dyn Handler<Msg = M>> {
     fn allow_focus(&self) -> bool {
         self.as_ref().allow_focus()
     }
+
+    fn autofocus(&self) -> bool {
+        self.as_ref().autofocus()
+    }
+
+    fn access_node(&self) -> AccessNode {
+        self.as_ref().access_node()
+    }
 }
 
 impl<M> Layout for Box<dyn Handler<Msg = M>> {
@@ -175,6 +209,11 @@ impl<M: 'static> Clone for Box<dyn Handler<Msg = M>> {
 
 impl<'a> Manager<'a> {
     /// Generic handler for low-level events passed to leaf widgets
+    ///
+    /// If [`Handler::activation_via_press`] is true, press events are
+    /// translated to [`Action::Activate`]. A right-click is translated to
+    /// [`Action::ContextMenu`]. The resulting actions and all other events are
+    /// passed to [`Handler::handle_event`].
     pub fn handle_generic<W>(
         widget: &mut W,
         mgr: &mut Manager,
@@ -185,19 +224,129 @@ impl<'a> Manager<'a> {
     {
         let activable = widget.activation_via_press();
         match event {
-            Event::Action(action) => widget.handle_action(mgr, action),
             Event::PressStart { source, coord } if activable && source.is_primary() => {
                 mgr.request_press_grab(source, widget.as_widget(), coord, None);
                 Response::None
             }
+            Event::PressStart {
+                source: PressSource::Mouse(MouseButton::Right),
+                coord,
+            } => widget.handle_event(mgr, Event::Action(Action::ContextMenu(coord))),
             Event::PressMove { .. } if activable => {
                 // We don't need these events, but they should not be considered *unhandled*
                 Response::None
             }
             Event::PressEnd { end_id, .. } if activable && end_id == Some(widget.id()) => {
-                widget.handle_action(mgr, Action::Activate)
+                widget.handle_event(mgr, Event::Action(Action::Activate))
             }
-            ev @ _ => Response::Unhandled(ev),
+            event => widget.handle_event(mgr, event),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::event::{InputEvent, ScrollDelta, VoidMsg};
+    use crate::geom::Size;
+    use crate::macros::Widget;
+    use crate::testing::{self, Harness};
+    use crate::widget::Filler;
+    use crate::LayoutData;
+
+    #[test]
+    fn handle_event_override() {
+        #[derive(Clone, Debug, Default, Widget)]
+        #[widget]
+        struct Intercept {
+            #[core]
+            core: CoreData,
+            scrolled: u32,
+            context_menu: u32,
+        }
+
+        impl Layout for Intercept {
+            fn size_rules(&mut self, _: &mut dyn SizeHandle, _: AxisInfo) -> SizeRules {
+                SizeRules::EMPTY
+            }
+            fn draw(&self, _: &mut dyn DrawHandle, _: &ManagerState) {}
+        }
+
+        impl Handler for Intercept {
+            type Msg = VoidMsg;
+            fn handle_event(&mut self, _: &mut Manager, event: Event) -> Response<VoidMsg> {
+                match event {
+                    Event::Action(Action::Scroll(..)) => self.scrolled += 1,
+                    Event::Action(Action::ContextMenu(_)) => self.context_menu += 1,
+                    event => return Response::Unhandled(event),
+                }
+                Response::None
+            }
+        }
+
+        let mut widget = Intercept::default();
+        let mut h = Harness::new(&mut widget);
+        widget.core_data_mut().rect = Rect::new(Coord(0, 0), Size(10, 10));
+
+        let mut mgr = h.manager();
+        for ev in [
+            InputEvent::CursorMoved(Coord(5, 5)),
+            InputEvent::MouseWheel(ScrollDelta::LineDelta(0.0, 1.0)),
+            InputEvent::MousePressed(MouseButton::Right),
+        ] {
+            assert!(mgr.handle_synthetic(&mut widget, ev).is_none());
+        }
+        assert_eq!((widget.scrolled, widget.context_menu), (1, 1));
+    }
+
+    #[test]
+    fn derived_parent_handle_event() {
+        #[derive(Clone, Debug, Widget)]
+        #[widget]
+        #[layout(single)]
+        #[handler(event = on_event)]
+        struct Parent {
+            #[core]
+            core: CoreData,
+            #[layout_data]
+            layout_data: <Self as LayoutData>::Data,
+            #[widget]
+            child: Filler,
+            scrolled: u32,
+            context_menu: u32,
+        }
+
+        impl Parent {
+            fn on_event(&mut self, _: &mut Manager, event: Event) -> Response<VoidMsg> {
+                match event {
+                    Event::Action(Action::Scroll(..)) => self.scrolled += 1,
+                    Event::Action(Action::ContextMenu(_)) => self.context_menu += 1,
+                    event => return Response::Unhandled(event),
+                }
+                Response::None
+            }
+        }
+
+        let mut widget = Parent {
+            core: Default::default(),
+            layout_data: Default::default(),
+            child: Filler::new(),
+            scrolled: 0,
+            context_menu: 0,
+        };
+        let mut h = Harness::new(&mut widget);
+        testing::solve(&mut widget, Size(10, 10));
+        assert_eq!(widget.child.rect(), widget.rect());
+
+        // Events not handled by the child reach the parent's handle_event
+        let mut mgr = h.manager();
+        for ev in [
+            InputEvent::CursorMoved(Coord(5, 5)),
+            InputEvent::MouseWheel(ScrollDelta::LineDelta(0.0, 1.0)),
+            InputEvent::MousePressed(MouseButton::Right),
+        ] {
+            assert!(mgr.handle_synthetic(&mut widget, ev).is_none());
         }
+        assert_eq!((widget.scrolled, widget.context_menu), (1, 1));
     }
 }
@@ -7,11 +7,15 @@
 
 use log::trace;
 use smallvec::SmallVec;
-use std::collections::HashMap;
+use std::any::Any;
+use std::collections::{HashMap, HashSet};
+use std::sync::Arc;
 use std::time::{Duration, Instant};
 
 use super::*;
+use crate::access::{AccessEvent, AccessPrefs};
 use crate::geom::Coord;
+use crate::widget::{dialog_channel, DialogHandle, DialogSender};
 use crate::{ThemeAction, ThemeApi, TkAction, TkWindow, Widget, WidgetId, WindowId};
 
 /// Highlighting state of a widget
@@ -43,6 +47,11 @@ impl HighlightState {
     }
 }
 
+/// Minimum time a widget is shown depressed after keyboard activation
+///
+/// Without this, a quick key press and release might not be visible.
+const KEY_FLASH: Duration = Duration::from_millis(100);
+
 #[derive(Clone, Debug)]
 struct TouchEvent {
     touch_id: u64,
@@ -69,17 +78,29 @@ pub struct ManagerState {
     key_focus: Option<WidgetId>,
     hover: Option<WidgetId>,
     hover_icon: CursorIcon,
+    modifiers: Modifiers,
     key_events: SmallVec<[(u32, WidgetId); 10]>,
+    /// Widgets shown depressed until the given time, after key activation
+    key_flash: SmallVec<[(Instant, WidgetId); 2]>,
     last_mouse_coord: Coord,
     mouse_grab: Option<(WidgetId, MouseButton)>,
     touch_grab: SmallVec<[TouchEvent; 10]>,
     accel_keys: HashMap<VirtualKeyCode, WidgetId>,
+    default_button: Option<WidgetId>,
+    cancel_button: Option<WidgetId>,
+    access_prefs: AccessPrefs,
+    redraw_ids: HashSet<WidgetId>,
+    redraw_all: bool,
+    /// Actions subsumed by a pending [`TkAction::RequestClose`]
+    close_subsumed: TkAction,
 
     time_start: Instant,
     time_updates: Vec<(Instant, WidgetId)>,
     // TODO(opt): consider other containers, e.g. C++ multimap
     // or sorted Vec with binary search yielding a range
     handle_updates: HashMap<UpdateHandle, Vec<WidgetId>>,
+    widget_state: HashMap<WidgetId, Arc<dyn Any + Send + Sync>>,
+    shortcuts: Shortcuts,
 }
 
 /// Toolkit API
@@ -96,15 +117,26 @@ impl ManagerState {
             key_focus: None,
             hover: None,
             hover_icon: CursorIcon::Default,
+            modifiers: Modifiers::default(),
             key_events: Default::default(),
-            last_mouse_coord: Coord::ZERO,
+            key_flash: Default::default(),
+            // Set a fake coordinate off the window until the cursor enters
+            last_mouse_coord: Coord(-1, -1),
             mouse_grab: None,
             touch_grab: Default::default(),
             accel_keys: HashMap::new(),
+            default_button: None,
+            cancel_button: None,
+            access_prefs: AccessPrefs::default(),
+            redraw_ids: HashSet::new(),
+            redraw_all: true,
+            close_subsumed: TkAction::None,
 
             time_start: Instant::now(),
             time_updates: vec![],
             handle_updates: HashMap::new(),
+            widget_state: HashMap::new(),
+            shortcuts: Shortcuts::new(),
         }
     }
 
@@ -112,10 +144,7 @@ impl ManagerState {
     ///
     /// This should be called by the toolkit on the widget tree when the window
     /// is created (before or after resizing).
-    pub fn configure<W>(&mut self, tkw: &mut dyn TkWindow, widget: &mut W)
-    where
-        W: Widget + Handler<Msg = VoidMsg> + ?Sized,
-    {
+    pub fn configure<W: Widget + ?Sized>(&mut self, tkw: &mut dyn TkWindow, widget: &mut W) {
         // Re-assigning WidgetIds might invalidate state; to avoid this we map
         // existing ids to new ids
         let mut map = HashMap::new();
@@ -123,22 +152,52 @@ impl ManagerState {
 
         // We re-set these instead of remapping:
         self.accel_keys.clear();
+        self.redraw_ids.clear();
+        self.redraw_all = true;
+        self.default_button = None;
+        self.cancel_button = None;
         self.time_updates.clear();
         self.handle_updates.clear();
+        self.key_flash.clear();
+
+        // Widgets may request focus while being configured; such requests take
+        // precedence over the remapped old focus.
+        let old_char_focus = self.char_focus.take();
+        let old_key_focus = self.key_focus.take();
 
-        let coord = self.last_mouse_coord;
+        // Stored state is moved to the new id before the widget is configured;
+        // state of widgets no longer present is dropped.
+        let mut old_state = std::mem::take(&mut self.widget_state);
+
+        let mut autofocus = vec![];
         let mut mgr = self.manager(tkw);
         widget.walk_mut(&mut |widget| {
             map.insert(widget.id(), id);
+            if !old_state.is_empty() {
+                if let Some(state) = old_state.remove(&widget.id()) {
+                    mgr.mgr.widget_state.insert(id, state);
+                }
+            }
             widget.core_data_mut().id = id;
             widget.configure(&mut mgr);
+            if widget.allow_focus() && widget.autofocus() {
+                autofocus.push(id);
+            }
             id = id.next();
         });
 
-        self.hover = widget.find_id(coord);
-
-        self.char_focus = self.char_focus.and_then(|id| map.get(&id).cloned());
-        self.key_focus = self.key_focus.and_then(|id| map.get(&id).cloned());
+        if self.char_focus.is_none() {
+            self.char_focus = old_char_focus.and_then(|id| map.get(&id).cloned());
+        }
+        if self.key_focus.is_none() {
+            self.key_focus = old_key_focus.and_then(|id| map.get(&id).cloned());
+        }
+        if self.key_focus.is_none() {
+            // Hidden widgets (or children of hidden widgets) are skipped
+            let widget = widget.as_widget();
+            self.key_focus = autofocus.into_iter().find(|id| is_focusable(widget, *id));
+        }
+        tkw.access_notify(AccessEvent::TreeChanged);
         self.mouse_grab = self
             .mouse_grab
             .and_then(|(id, b)| map.get(&id).map(|id| (*id, b)));
@@ -176,6 +235,9 @@ impl ManagerState {
         do_map!(self.key_events, |elt: (u32, WidgetId)| map
             .get(&elt.1)
             .map(|id| (elt.0, *id)));
+
+        // Reconfiguring implies TkAction::RegionMoved
+        self.region_moved(widget);
     }
 
     pub fn region_moved<W: Widget + ?Sized>(&mut self, widget: &mut W) {
@@ -189,16 +251,48 @@ impl ManagerState {
         }
     }
 
+    /// Set user accessibility preferences
+    ///
+    /// The toolkit should reconfigure the window after changing preferences,
+    /// since widgets may read these during [`Widget::configure`].
+    #[inline]
+    pub fn set_access_prefs(&mut self, prefs: AccessPrefs) {
+        self.access_prefs = prefs;
+    }
+
     /// Set the DPI factor. Must be updated for correct event translation by
     /// [`Manager::handle_winit`].
     #[inline]
     pub fn set_dpi_factor(&mut self, dpi_factor: f64) {
         self.dpi_factor = dpi_factor;
+        self.redraw_all = true;
     }
 
     /// Get the next resume time
     pub fn next_resume(&self) -> Option<Instant> {
-        self.time_updates.first().map(|time| time.0)
+        let flash = self.key_flash.iter().map(|flash| flash.0).min();
+        match (self.time_updates.first().map(|time| time.0), flash) {
+            (Some(a), Some(b)) => Some(a.min(b)),
+            (a, b) => a.or(b),
+        }
+    }
+
+    /// Mark all widgets as requiring a redraw
+    ///
+    /// The toolkit should call this when the appearance of widgets may change
+    /// without notification, e.g. after a theme change.
+    #[inline]
+    pub fn redraw_all(&mut self) {
+        self.redraw_all = true;
+    }
+
+    /// Clear redraw requests
+    ///
+    /// The toolkit should call this after drawing a frame.
+    #[inline]
+    pub fn draw_complete(&mut self) {
+        self.redraw_ids.clear();
+        self.redraw_all = false;
     }
 
     /// Construct a [`Manager`] referring to this state
@@ -214,6 +308,37 @@ impl ManagerState {
 
 /// Public API (around event manager state)
 impl ManagerState {
+    /// Get the state of keyboard modifiers
+    ///
+    /// This is updated before dispatch of each input event, thus is accurate
+    /// for the event currently being handled.
+    #[inline]
+    pub fn modifiers(&self) -> Modifiers {
+        self.modifiers
+    }
+
+    /// Get user accessibility preferences
+    #[inline]
+    pub fn access_prefs(&self) -> AccessPrefs {
+        self.access_prefs
+    }
+
+    /// Get the DPI factor of the window
+    ///
+    /// This is the ratio of physical pixels to logical pixels.
+    #[inline]
+    pub fn dpi_factor(&self) -> f64 {
+        self.dpi_factor
+    }
+
+    /// Check whether a widget requested a redraw since the last frame
+    ///
+    /// See [`Manager::redraw`].
+    #[inline]
+    pub fn is_dirty(&self, w_id: WidgetId) -> bool {
+        self.redraw_all || self.redraw_ids.contains(&w_id)
+    }
+
     /// Get the complete highlight state
     pub fn highlight_state(&self, w_id: WidgetId) -> HighlightState {
         HighlightState {
@@ -239,10 +364,15 @@ impl ManagerState {
     /// Get whether the widget is under the mouse or finger
     #[inline]
     pub fn is_hovered(&self, w_id: WidgetId) -> bool {
-        self.hover == Some(w_id)
+        self.hover == Some(w_id) || self.touch_grab.iter().any(|t| t.cur_id == Some(w_id))
     }
 
     /// Check whether the given widget is visually depressed
+    ///
+    /// Widgets activated via the keyboard are depressed while the key is held
+    /// and for a short time after activation (unless
+    /// [`AccessPrefs::reduced_motion`] is set), such that a quick key press
+    /// is visible.
     #[inline]
     pub fn is_depressed(&self, w_id: WidgetId) -> bool {
         for (_, id) in &self.key_events {
@@ -250,6 +380,9 @@ impl ManagerState {
                 return true;
             }
         }
+        if self.key_flash.iter().any(|flash| flash.1 == w_id) {
+            return true;
+        }
         if let Some(grab) = self.mouse_grab {
             if grab.0 == w_id && self.hover == Some(w_id) {
                 return true;
@@ -310,8 +443,9 @@ impl<'a> Manager<'a> {
     /// Subscribe to an update handle
     ///
     /// All widgets subscribed to an update handle will have their
-    /// [`Widget::update_handle`] method called when [`Manager::trigger_update`]
-    /// is called with the corresponding handle.
+    /// [`Widget::update_handle`] method called and receive [`Action::Update`]
+    /// when [`Manager::trigger_update`] is called with the corresponding
+    /// handle (or when triggered from another thread via the toolkit).
     ///
     /// This should be called from [`Widget::configure`].
     pub fn update_on_handle(&mut self, handle: UpdateHandle, w_id: WidgetId) {
@@ -323,11 +457,32 @@ impl<'a> Manager<'a> {
     }
 
     /// Notify that a widget must be redrawn
+    ///
+    /// Widgets must call this whenever their appearance changes (other than
+    /// due to a change of rect or [`HighlightState`]), since the toolkit may
+    /// otherwise replay geometry cached from a previous frame.
+    #[inline]
+    pub fn redraw(&mut self, id: WidgetId) {
+        self.mgr.redraw_ids.insert(id);
+        self.request_redraw();
+    }
+
+    /// Request a redraw without invalidating cached widget geometry
+    ///
+    /// This is used for changes to [`HighlightState`], which cached geometry
+    /// accounts for.
     #[inline]
-    pub fn redraw(&mut self, _id: WidgetId) {
-        // Theoretically, notifying by WidgetId allows selective redrawing
-        // (damage events). This is not yet implemented.
-        self.send_action(TkAction::Redraw);
+    fn request_redraw(&mut self) {
+        self.add_action(TkAction::Redraw);
+    }
+
+    /// Accumulate `action`, remembering any subsumed by a close request
+    fn add_action(&mut self, action: TkAction) {
+        let (lo, hi) = (self.action.min(action), self.action.max(action));
+        if hi == TkAction::RequestClose && lo < hi {
+            self.mgr.close_subsumed = self.mgr.close_subsumed.max(lo);
+        }
+        self.action = hi;
     }
 
     /// Notify that a [`TkAction`] action should happen
@@ -339,7 +494,11 @@ impl<'a> Manager<'a> {
     /// affect the UI after a reconfigure action.
     #[inline]
     pub fn send_action(&mut self, action: TkAction) {
-        self.action = self.action.max(action);
+        trace_event!("Action: {:?}", action);
+        if action == TkAction::Redraw {
+            self.mgr.redraw_all = true;
+        }
+        self.add_action(action);
     }
 
     /// Add a window
@@ -354,6 +513,38 @@ impl<'a> Manager<'a> {
         self.tkw.add_window(widget)
     }
 
+    /// Add a modal window
+    ///
+    /// The new window is modal over the current window: until it is closed,
+    /// the current window ignores user input. This is intended for dialogs
+    /// (see [`ConfirmDialog`](crate::widget::ConfirmDialog) and
+    /// [`InputDialog`](crate::widget::InputDialog)).
+    #[inline]
+    pub fn add_modal(&mut self, widget: Box<dyn kas::Window>) -> WindowId {
+        self.tkw.add_modal(widget)
+    }
+
+    /// Add a modal dialog, returning a handle to its result
+    ///
+    /// The closure `f` constructs the dialog from a [`DialogSender`]; the
+    /// dialog is then added as with [`Manager::add_modal`]. For example:
+    /// ```nocompile
+    /// let (id, handle) = mgr.add_dialog(|sender| {
+    ///     ConfirmDialog::new("Quit", "Really quit?", sender.into_callback())
+    /// });
+    /// ```
+    ///
+    /// See [`DialogHandle`] for usage of the result.
+    pub fn add_dialog<T, W, F>(&mut self, f: F) -> (WindowId, DialogHandle<T>)
+    where
+        W: kas::Window + 'static,
+        F: FnOnce(DialogSender<T>) -> W,
+    {
+        let (sender, handle) = dialog_channel();
+        let id = self.add_modal(Box::new(f(sender)));
+        (id, handle)
+    }
+
     /// Close a window
     #[inline]
     pub fn close_window(&mut self, id: WindowId) {
@@ -384,6 +575,16 @@ impl<'a> Manager<'a> {
         self.tkw.set_clipboard(content)
     }
 
+    /// Override the window's DPI factor
+    ///
+    /// This is intended for testing and screenshots: when `Some`, the given
+    /// factor is used instead of the monitor's. `None` restores the monitor's
+    /// factor. See also [`TkWindow::set_dpi_override`].
+    #[inline]
+    pub fn set_dpi_override(&mut self, factor: Option<f64>) {
+        self.tkw.set_dpi_override(factor);
+    }
+
     /// Adjust the theme
     #[inline]
     pub fn adjust_theme<F: FnMut(&mut dyn ThemeApi) -> ThemeAction>(&mut self, mut f: F) {
@@ -393,6 +594,23 @@ impl<'a> Manager<'a> {
 
 /// Public API (around event manager state)
 impl<'a> Manager<'a> {
+    /// Get the state of keyboard modifiers
+    ///
+    /// See [`ManagerState::modifiers`].
+    #[inline]
+    pub fn modifiers(&self) -> Modifiers {
+        self.mgr.modifiers
+    }
+
+    /// Get user accessibility preferences
+    ///
+    /// Animated widgets should check [`AccessPrefs::reduced_motion`] before
+    /// scheduling animation frames via [`Manager::update_on_timer`].
+    #[inline]
+    pub fn access_prefs(&self) -> AccessPrefs {
+        self.mgr.access_prefs
+    }
+
     /// Adds an accelerator key for a widget
     ///
     /// If this key is pressed when the window has focus and no widget has a
@@ -404,6 +622,72 @@ impl<'a> Manager<'a> {
         self.mgr.accel_keys.insert(key, id);
     }
 
+    /// Set the default button
+    ///
+    /// If `Return` or `Enter` is pressed when no widget has keyboard or
+    /// character focus, the given widget will receive an [`Action::Activate`]
+    /// event.
+    ///
+    /// This should be set from [`Widget::configure`].
+    #[inline]
+    pub fn set_default_button(&mut self, id: WidgetId) {
+        self.mgr.default_button = Some(id);
+    }
+
+    /// Set the cancel button
+    ///
+    /// If `Escape` is pressed, the given widget will receive an
+    /// [`Action::Activate`] event (after any character focus is cleared).
+    ///
+    /// This should be set from [`Widget::configure`].
+    #[inline]
+    pub fn set_cancel_button(&mut self, id: WidgetId) {
+        self.mgr.cancel_button = Some(id);
+    }
+
+    /// Store state for widget `id`, replacing any prior state
+    ///
+    /// Stored state persists across reconfigures: the entry follows the
+    /// widget to its new identifier, and is dropped once the widget is no
+    /// longer part of the window. A widget may thus stash state here (e.g.
+    /// whenever it changes) and restore it from [`Manager::stored_state`]
+    /// during [`Widget::configure`]. Widgets not using this pay nothing.
+    ///
+    /// Does nothing if `id` has not been assigned (i.e. before configure).
+    pub fn store_state<T: Any + Send + Sync>(&mut self, id: WidgetId, state: T) {
+        if id != WidgetId::default() {
+            self.mgr.widget_state.insert(id, Arc::new(state));
+        }
+    }
+
+    /// Get the state stored for widget `id`, if any and of type `T`
+    pub fn stored_state<T: Any + Send + Sync>(&self, id: WidgetId) -> Option<&T> {
+        self.mgr
+            .widget_state
+            .get(&id)
+            .and_then(|state| state.downcast_ref())
+    }
+
+    /// Remove the state stored for widget `id`
+    pub fn clear_state(&mut self, id: WidgetId) {
+        self.mgr.widget_state.remove(&id);
+    }
+
+    /// Access the window's keyboard shortcut map
+    #[inline]
+    pub fn shortcuts(&self) -> &Shortcuts {
+        &self.mgr.shortcuts
+    }
+
+    /// Access the window's keyboard shortcut map mutably
+    ///
+    /// Key presses matching a shortcut are intercepted before dispatch to
+    /// widgets; see [`Shortcuts`].
+    #[inline]
+    pub fn shortcuts_mut(&mut self) -> &mut Shortcuts {
+        &mut self.mgr.shortcuts
+    }
+
     /// Request character-input focus
     ///
     /// If successful, [`Action::ReceivedCharacter`] events are sent to this
@@ -416,6 +700,67 @@ impl<'a> Manager<'a> {
         }
         self.mgr.char_focus = Some(id);
         self.redraw(id);
+        self.access_notify(AccessEvent::FocusChanged(Some(id)));
+    }
+
+    /// Get the widget with keyboard focus, if any
+    ///
+    /// This is the widget navigated to via the Tab key (or set via
+    /// [`Manager::set_key_focus`]). It receives activation via Enter/Space and
+    /// is highlighted by the theme.
+    #[inline]
+    pub fn key_focus(&self) -> Option<WidgetId> {
+        self.mgr.key_focus
+    }
+
+    /// Get the widget with character-input focus, if any
+    ///
+    /// See [`Manager::request_char_focus`].
+    #[inline]
+    pub fn char_focus(&self) -> Option<WidgetId> {
+        self.mgr.char_focus
+    }
+
+    /// Set keyboard focus to the widget with the given `id`
+    ///
+    /// The `widget` parameter should be the root of a widget tree containing
+    /// the target (usually a window), as for [`Manager::send_action_to`].
+    ///
+    /// Returns false and leaves focus unchanged if no widget with this `id`
+    /// is found, the widget does not accept focus (see
+    /// [`Widget::allow_focus`]), or the widget is hidden.
+    pub fn set_key_focus<W: Widget + ?Sized>(&mut self, widget: &mut W, id: WidgetId) -> bool {
+        if !is_focusable(widget.as_widget(), id) {
+            return false;
+        }
+        if self.mgr.key_focus != Some(id) {
+            if let Some(old_id) = self.mgr.key_focus {
+                self.redraw(old_id);
+            }
+            self.mgr.key_focus = Some(id);
+            self.redraw(id);
+            self.access_notify(AccessEvent::FocusChanged(Some(id)));
+        }
+        true
+    }
+
+    /// Notify assistive technologies of a change
+    ///
+    /// Widgets should send [`AccessEvent::ValueChanged`] when any property
+    /// reported by [`Widget::access_node`] changes. Focus and configuration
+    /// changes are reported automatically.
+    #[inline]
+    pub fn access_notify(&mut self, event: AccessEvent) {
+        self.tkw.access_notify(event);
+    }
+
+    /// Set the position of the IME candidate window
+    ///
+    /// Widgets accepting text input should call this when receiving character
+    /// focus. The `coord` is in window coordinates.
+    #[inline]
+    pub fn set_ime_position(&mut self, coord: Coord) {
+        self.tkw.set_ime_position(coord);
     }
 
     /// Request a mouse grab on the given `source`
@@ -465,8 +810,9 @@ impl<'a> Manager<'a> {
         }
 
         if widget.allow_focus() {
-            if self.mgr.key_focus.is_some() {
+            if self.mgr.key_focus.is_some() && self.mgr.key_focus != Some(w_id) {
                 self.mgr.key_focus = Some(w_id);
+                self.access_notify(AccessEvent::FocusChanged(Some(w_id)));
             }
             self.mgr.char_focus = None;
         }
@@ -474,15 +820,60 @@ impl<'a> Manager<'a> {
         self.redraw(w_id);
         true
     }
+
+    /// Send an action to the widget with the given `id`
+    ///
+    /// The `widget` parameter should be the root of a configured widget tree
+    /// (usually a window). The action is delivered from the root, exactly as
+    /// for input events; this allows automation and tests to target a
+    /// specific widget (e.g. to activate a button). Use
+    /// [`WidgetCore::find`](crate::WidgetCore::find) to look up a widget by id.
+    ///
+    /// Widget identifiers are assigned by
+    /// [`ManagerState::configure`] and remain stable across reconfigures so
+    /// long as the shape of the widget tree is unchanged.
+    ///
+    /// Returns [`Response::Unhandled`] if no widget with this `id` exists.
+    pub fn send_action_to<W>(
+        &mut self,
+        widget: &mut W,
+        id: WidgetId,
+        action: Action,
+    ) -> Response<<W as Handler>::Msg>
+    where
+        W: Handler + ?Sized,
+    {
+        if widget.find(id).is_none() {
+            return Response::unhandled_action(action);
+        }
+        self.send_event(widget, id, Event::Action(action))
+    }
 }
 
 /// Internal methods
 impl<'a> Manager<'a> {
-    #[cfg(feature = "winit")]
+    /// Send an event to widget `id`, via the root `widget`
+    fn send_event<W>(&mut self, widget: &mut W, id: WidgetId, event: Event) -> Response<W::Msg>
+    where
+        W: Handler + ?Sized,
+    {
+        trace_event!("Sending {:?} to widget {}", event, id);
+        let response = widget.handle(self, id, event);
+        #[cfg(feature = "trace_events")]
+        match &response {
+            Response::None => trace!(target: "kas::event", "Widget {} handled event", id),
+            Response::Unhandled(ev) => {
+                trace!(target: "kas::event", "Widget {} returned {:?}", id, ev)
+            }
+            Response::Msg(_) => trace!(target: "kas::event", "Widget {} returned a message", id),
+        }
+        response
+    }
+
     fn set_hover<W: Widget + ?Sized>(&mut self, widget: &mut W, w_id: Option<WidgetId>) {
         if self.mgr.hover != w_id {
             self.mgr.hover = w_id;
-            self.send_action(TkAction::Redraw);
+            self.request_redraw();
 
             if let Some(id) = w_id {
                 let icon = widget
@@ -499,8 +890,16 @@ impl<'a> Manager<'a> {
         }
     }
 
-    #[cfg(feature = "winit")]
     fn add_key_event(&mut self, scancode: u32, id: WidgetId) {
+        if !self.mgr.access_prefs.reduced_motion {
+            // Repeated activation restarts the flash
+            let end = Instant::now() + KEY_FLASH;
+            match self.mgr.key_flash.iter_mut().find(|flash| flash.1 == id) {
+                Some(flash) => flash.0 = end,
+                None => self.mgr.key_flash.push((end, id)),
+            }
+        }
+
         for item in &self.mgr.key_events {
             if item.1 == id {
                 return;
@@ -511,7 +910,6 @@ impl<'a> Manager<'a> {
         self.redraw(id);
     }
 
-    #[cfg(feature = "winit")]
     fn remove_key_event(&mut self, scancode: u32) {
         let r = 'outer: loop {
             for (i, item) in self.mgr.key_events.iter().enumerate() {
@@ -527,12 +925,10 @@ impl<'a> Manager<'a> {
         self.mgr.key_events.remove(r);
     }
 
-    #[cfg(feature = "winit")]
     fn mouse_grab(&self) -> Option<(WidgetId, MouseButton)> {
         self.mgr.mouse_grab
     }
 
-    #[cfg(feature = "winit")]
     fn end_mouse_grab(&mut self, button: MouseButton) {
         if let Some(grab) = self.mgr.mouse_grab {
             if grab.1 == button {
@@ -554,7 +950,6 @@ impl<'a> Manager<'a> {
         })
     }
 
-    #[cfg(feature = "winit")]
     fn remove_touch(&mut self, touch_id: u64) -> Option<TouchEvent> {
         let len = self.mgr.touch_grab.len();
         for i in 0..len {
@@ -568,7 +963,6 @@ impl<'a> Manager<'a> {
         None
     }
 
-    #[cfg(feature = "winit")]
     fn next_key_focus(&mut self, widget: &mut dyn Widget) {
         let mut id = self.mgr.key_focus.unwrap_or(WidgetId::FIRST);
         let end = widget.id();
@@ -579,23 +973,47 @@ impl<'a> Manager<'a> {
             }
 
             // TODO(opt): incorporate walk/find logic
-            if widget.find(id).map(|w| w.allow_focus()).unwrap_or(false) {
-                self.send_action(TkAction::Redraw);
+            if is_focusable(widget, id) {
+                self.request_redraw();
                 self.mgr.key_focus = Some(id);
+                self.access_notify(AccessEvent::FocusChanged(Some(id)));
                 return;
             }
         }
     }
 
-    #[cfg(feature = "winit")]
     fn unset_key_focus(&mut self) {
         if let Some(id) = self.mgr.key_focus {
             self.redraw(id);
+            self.access_notify(AccessEvent::FocusChanged(None));
         }
         self.mgr.key_focus = None;
     }
 }
 
+/// Whether the widget `id` within `widget` accepts keyboard focus
+///
+/// This is as [`Widget::find`], but also fails if the target or any parent of
+/// the target is hidden.
+fn is_focusable(widget: &dyn Widget, id: WidgetId) -> bool {
+    if id > widget.id() || !widget.is_visible() {
+        return false;
+    } else if id == widget.id() {
+        return widget.allow_focus();
+    }
+
+    for i in 0..widget.len() {
+        if let Some(w) = widget.get(i) {
+            if id > w.id() {
+                continue;
+            }
+            return is_focusable(w, id);
+        }
+        break;
+    }
+    false
+}
+
 /// Toolkit API
 #[cfg_attr(not(feature = "internal_doc"), doc(hidden))]
 impl<'a> Manager<'a> {
@@ -604,10 +1022,38 @@ impl<'a> Manager<'a> {
         self.action
     }
 
+    /// Resolve a [`TkAction::RequestClose`] action
+    ///
+    /// Calls [`kas::Window::on_close`], then sends [`TkAction::Close`] unless
+    /// closure was vetoed. On veto, actions subsumed by the request (those of
+    /// lower precedence sent since the request) are sent instead.
+    pub fn request_close<W: kas::Window + ?Sized>(&mut self, window: &mut W) {
+        let close = window.on_close(self);
+        if self.action == TkAction::RequestClose {
+            // Requests made by the hook itself are redundant
+            self.action = TkAction::None;
+        }
+        let subsumed = std::mem::replace(&mut self.mgr.close_subsumed, TkAction::None);
+        self.send_action(match close {
+            true => TkAction::Close,
+            false => subsumed,
+        });
+    }
+
     /// Update widgets due to timer
     pub fn update_timer<W: Widget + ?Sized>(&mut self, widget: &mut W) {
         let now = Instant::now();
 
+        let mut i = 0;
+        while i < self.mgr.key_flash.len() {
+            if self.mgr.key_flash[i].0 <= now {
+                let (_, id) = self.mgr.key_flash.remove(i);
+                self.redraw(id);
+            } else {
+                i += 1;
+            }
+        }
+
         // assumption: time_updates are sorted
         let mut i = 0;
         while i < self.mgr.time_updates.len() {
@@ -631,19 +1077,50 @@ impl<'a> Manager<'a> {
     }
 
     /// Update widgets due to handle
-    pub fn update_handle<W: Widget + ?Sized>(
-        &mut self,
-        widget: &mut W,
-        handle: UpdateHandle,
-        payload: u64,
-    ) {
+    ///
+    /// Each widget subscribed to `handle` has its [`Widget::update_handle`]
+    /// method called, then receives [`Action::Update`] through its handler.
+    /// Widgets changed by the update must request a redraw themselves (e.g.
+    /// via [`Manager::redraw`]); others are not redrawn.
+    pub fn update_handle<W>(&mut self, widget: &mut W, handle: UpdateHandle, payload: u64)
+    where
+        W: Widget + Handler<Msg = VoidMsg> + ?Sized,
+    {
         // NOTE: to avoid borrow conflict, we must clone values!
         if let Some(mut values) = self.mgr.handle_updates.get(&handle).cloned() {
             for w_id in values.drain(..) {
                 trace!("Updating widget {} via {:?}", w_id, handle);
                 if let Some(w) = widget.find_mut(w_id) {
                     w.update_handle(self, handle, payload);
+                } else {
+                    continue;
                 }
+                let ev = Event::Action(Action::Update(handle, payload));
+                let _ = self.send_event(widget, w_id, ev);
+            }
+        }
+    }
+
+    /// Deliver data from an update channel to widgets subscribed to `handle`
+    ///
+    /// Each subscribed widget receives [`Action::UpdateData`] through its
+    /// handler. A redraw is requested if any widget is subscribed; otherwise
+    /// this is a no-op.
+    pub fn update_data<W>(
+        &mut self,
+        widget: &mut W,
+        handle: UpdateHandle,
+        data: Arc<dyn Any + Send + Sync>,
+    ) where
+        W: Widget + Handler<Msg = VoidMsg> + ?Sized,
+    {
+        // NOTE: to avoid borrow conflict, we must clone values!
+        if let Some(mut values) = self.mgr.handle_updates.get(&handle).cloned() {
+            for w_id in values.drain(..) {
+                trace!("Sending data to widget {} via {:?}", w_id, handle);
+                let ev = Event::Action(Action::UpdateData(handle, data.clone()));
+                let _ = self.send_event(widget, w_id, ev);
+                self.redraw(w_id);
             }
         }
     }
@@ -653,6 +1130,10 @@ impl<'a> Manager<'a> {
     /// Note that some event types are not *does not* handled, since for these
     /// events the toolkit must take direct action anyway:
     /// `Resized(size)`, `RedrawRequested`, `HiDpiFactorChanged(factor)`.
+    ///
+    /// Input events are translated to [`InputEvent`] and dispatched as by
+    /// [`Manager::handle_synthetic`]. Key presses are first looked up in the
+    /// window's [`Shortcuts`] map.
     #[cfg(feature = "winit")]
     pub fn handle_winit<W>(mut self, widget: &mut W, event: winit::event::WindowEvent) -> TkAction
     where
@@ -661,56 +1142,203 @@ impl<'a> Manager<'a> {
         use winit::event::{ElementState, MouseScrollDelta, TouchPhase, WindowEvent::*};
         trace!("Event: {:?}", event);
 
-        let response = match event {
+        // Modifier state is reported with input events (with winit 0.21, the
+        // ModifiersChanged event is a device event and may lag behind).
+        #[allow(deprecated)]
+        let modifiers = match event {
+            KeyboardInput { input, .. } => Some(input.modifiers),
+            CursorMoved { modifiers, .. } => Some(modifiers),
+            MouseWheel { modifiers, .. } => Some(modifiers),
+            MouseInput { modifiers, .. } => Some(modifiers),
+            _ => None,
+        };
+        if let Some(modifiers) = modifiers {
+            self.mgr.modifiers = modifiers.into();
+        }
+
+        let event = match event {
             // Resized(size) [handled by toolkit]
             // Moved(position)
             CloseRequested => {
-                self.send_action(TkAction::Close);
-                Response::None
+                self.send_action(TkAction::RequestClose);
+                None
             }
             // Destroyed
             // DroppedFile(PathBuf),
             // HoveredFile(PathBuf),
             // HoveredFileCancelled,
             ReceivedCharacter(c) if c != '\u{1b}' /* escape */ => {
+                Some(InputEvent::ReceivedCharacter(c))
+            }
+            // Focused(bool),
+            KeyboardInput { input, is_synthetic, .. } => {
+                match (input.state, input.virtual_keycode) {
+                    (ElementState::Pressed, Some(vkey)) if !is_synthetic => {
+                        Some(InputEvent::KeyPressed(input.scancode, vkey))
+                    }
+                    (ElementState::Released, _) => Some(InputEvent::KeyReleased(input.scancode)),
+                    _ => None,
+                }
+            }
+            CursorMoved { position, .. } => Some(InputEvent::CursorMoved(position.into())),
+            // CursorEntered { .. },
+            CursorLeft { .. } => Some(InputEvent::CursorLeft),
+            MouseWheel { delta, .. } => Some(InputEvent::MouseWheel(match delta {
+                MouseScrollDelta::LineDelta(x, y) => ScrollDelta::LineDelta(x, y),
+                MouseScrollDelta::PixelDelta(pos) => {
+                    ScrollDelta::PixelDelta(Coord::from_logical(pos, self.mgr.dpi_factor))
+                }
+            })),
+            MouseInput { state, button, .. } => Some(match state {
+                ElementState::Pressed => InputEvent::MousePressed(button),
+                ElementState::Released => InputEvent::MouseReleased(button),
+            }),
+            // TouchpadPressure { pressure: f32, stage: i64, },
+            // AxisMotion { axis: AxisId, value: f64, },
+            // RedrawRequested [handled by toolkit]
+            Touch(touch) => {
+                let coord = touch.location.into();
+                Some(match touch.phase {
+                    TouchPhase::Started => InputEvent::TouchStart(touch.id, coord),
+                    TouchPhase::Moved => InputEvent::TouchMove(touch.id, coord),
+                    TouchPhase::Ended => InputEvent::TouchEnd(touch.id, coord),
+                    TouchPhase::Cancelled => InputEvent::TouchCancel(touch.id, coord),
+                })
+            }
+            // HiDpiFactorChanged(factor) [handled by toolkit]
+            _ => None,
+        };
+
+        if let Some(event) = event {
+            match self.handle_synthetic(widget, event) {
+                Response::None => (),
+                Response::Unhandled(_) => {
+                    // we can safely ignore unhandled events here
+                }
+                Response::Msg(_) => unreachable!(),
+            };
+        }
+
+        trace_event!("Resulting action: {:?}", self.action);
+        self.unwrap_action()
+    }
+}
+
+/// Input handling
+impl<'a> Manager<'a> {
+    /// Handle an input event
+    ///
+    /// Toolkits translate native input to [`InputEvent`] and call this method
+    /// (see [`Manager::handle_winit`]). It may also be used to inject
+    /// synthetic input, for example to test a widget tree without a display:
+    /// the `widget` should be a configured widget tree (see
+    /// [`ManagerState::configure`]). The response from `widget` is returned;
+    /// any resulting [`TkAction`] may be retrieved from the manager.
+    pub fn handle_synthetic<W>(
+        &mut self,
+        widget: &mut W,
+        event: InputEvent,
+    ) -> Response<<W as Handler>::Msg>
+    where
+        W: Handler + ?Sized,
+    {
+        match event {
+            InputEvent::ReceivedCharacter(c) => {
                 if let Some(id) = self.mgr.char_focus {
                     let ev = Event::Action(Action::ReceivedCharacter(c));
-                    widget.handle(&mut self, id, ev)
+                    self.send_event(widget, id, ev)
                 } else {
                     Response::None
                 }
             }
-            // Focused(bool),
-            KeyboardInput { input, is_synthetic, .. } => {
-                let char_focus = self.mgr.char_focus.is_some();
-                match (input.scancode, input.state, input.virtual_keycode) {
-                    (_, ElementState::Pressed, Some(vkey)) if char_focus && !is_synthetic => match vkey {
+            InputEvent::KeyPressed(scancode, vkey) => {
+                // Shortcuts take precedence over all other key handling
+                let shortcut = Shortcut {
+                    modifiers: self.mgr.modifiers,
+                    key: vkey,
+                };
+                let vkey = match self.mgr.shortcuts.get(shortcut).cloned() {
+                    None => vkey,
+                    Some(ShortcutAction::Key(vkey)) => vkey,
+                    Some(ShortcutAction::Consume) => return Response::None,
+                    Some(ShortcutAction::Action(action)) => {
+                        return match self.mgr.char_focus.or(self.mgr.key_focus) {
+                            Some(id) => self.send_event(widget, id, Event::Action(action)),
+                            None => Response::None,
+                        };
+                    }
+                    Some(ShortcutAction::Update(handle, payload)) => {
+                        self.trigger_update(handle, payload);
+                        return Response::None;
+                    }
+                };
+
+                if vkey == VirtualKeyCode::Escape {
+                    if let Some((grab_id, button)) = self.mouse_grab() {
+                        // Escape cancels a mouse press
+                        let ev = Event::PressEnd {
+                            source: PressSource::Mouse(button),
+                            end_id: None,
+                            coord: self.mgr.last_mouse_coord,
+                        };
+                        let r = self.send_event(widget, grab_id, ev);
+                        self.end_mouse_grab(button);
+                        return r;
+                    }
+                }
+
+                if self.mgr.char_focus.is_some() {
+                    match vkey {
                         VirtualKeyCode::Escape => {
                             if let Some(id) = self.mgr.char_focus {
                                 self.redraw(id);
                             }
                             self.mgr.char_focus = None;
-                            Response::None
+                            if let Some(id) = self.mgr.cancel_button {
+                                self.add_key_event(scancode, id);
+
+                                let ev = Event::Action(Action::Activate);
+                                self.send_event(widget, id, ev)
+                            } else {
+                                Response::None
+                            }
                         }
                         _ => Response::None,
-                    },
-                    (scancode, ElementState::Pressed, Some(vkey)) if !char_focus && !is_synthetic => match vkey {
+                    }
+                } else {
+                    match vkey {
                         VirtualKeyCode::Tab => {
                             self.next_key_focus(widget.as_widget_mut());
                             Response::None
                         }
+                        VirtualKeyCode::Space if self.mgr.key_focus.is_some() => {
+                            let id = self.mgr.key_focus.unwrap();
+                            self.add_key_event(scancode, id);
+
+                            let ev = Event::Action(Action::Activate);
+                            self.send_event(widget, id, ev)
+                        }
                         VirtualKeyCode::Return | VirtualKeyCode::NumpadEnter => {
-                            if let Some(id) = self.mgr.key_focus {
+                            if let Some(id) = self.mgr.key_focus.or(self.mgr.default_button) {
                                 // Add to key_events for visual feedback
                                 self.add_key_event(scancode, id);
 
                                 let ev = Event::Action(Action::Activate);
-                                widget.handle(&mut self, id, ev)
-                            } else { Response::None }
+                                self.send_event(widget, id, ev)
+                            } else {
+                                Response::None
+                            }
                         }
                         VirtualKeyCode::Escape => {
                             self.unset_key_focus();
-                            Response::None
+                            if let Some(id) = self.mgr.cancel_button {
+                                self.add_key_event(scancode, id);
+
+                                let ev = Event::Action(Action::Activate);
+                                self.send_event(widget, id, ev)
+                            } else {
+                                Response::None
+                            }
                         }
                         vkey @ _ => {
                             if let Some(id) = self.mgr.accel_keys.get(&vkey).cloned() {
@@ -718,31 +1346,35 @@ impl<'a> Manager<'a> {
                                 self.add_key_event(scancode, id);
 
                                 let ev = Event::Action(Action::Activate);
-                                widget.handle(&mut self, id, ev)
-                            } else { Response::None }
+                                self.send_event(widget, id, ev)
+                            } else {
+                                Response::None
+                            }
                         }
-                    },
-                    (scancode, ElementState::Released, _) => {
-                        self.remove_key_event(scancode);
-                        Response::None
                     }
-                    _ => Response::None,
                 }
             }
-            CursorMoved {
-                position,
-                ..
-            } => {
-                let coord = position.into();
-
+            InputEvent::ModifiersChanged(modifiers) => {
+                self.mgr.modifiers = modifiers;
+                Response::None
+            }
+            InputEvent::KeyReleased(scancode) => {
+                self.remove_key_event(scancode);
+                Response::None
+            }
+            InputEvent::CursorMoved(coord) => {
                 // Update hovered widget
                 self.set_hover(widget, widget.find_id(coord));
 
                 let r = if let Some((grab_id, button)) = self.mouse_grab() {
                     let source = PressSource::Mouse(button);
                     let delta = coord - self.mgr.last_mouse_coord;
-                    let ev = Event::PressMove { source, coord, delta };
-                    widget.handle(&mut self, grab_id, ev)
+                    let ev = Event::PressMove {
+                        source,
+                        coord,
+                        delta,
+                    };
+                    self.send_event(widget, grab_id, ev)
                 } else {
                     // We don't forward move events without a grab
                     Response::None
@@ -751,53 +1383,46 @@ impl<'a> Manager<'a> {
                 self.mgr.last_mouse_coord = coord;
                 r
             }
-            // CursorEntered { .. },
-            CursorLeft { .. } => {
+            InputEvent::CursorLeft => {
                 // Set a fake coordinate off the window
                 self.mgr.last_mouse_coord = Coord(-1, -1);
                 self.set_hover(widget, None);
                 Response::None
             }
-            MouseWheel { delta, .. } => {
-                let action = Action::Scroll(match delta {
-                    MouseScrollDelta::LineDelta(x, y) => ScrollDelta::LineDelta(x, y),
-                    MouseScrollDelta::PixelDelta(pos) =>
-                        ScrollDelta::PixelDelta(Coord::from_logical(pos, self.mgr.dpi_factor)),
-                });
+            InputEvent::MouseWheel(delta) => {
                 if let Some(id) = self.mgr.hover {
-                    widget.handle(&mut self, id, Event::Action(action))
+                    let coord = self.mgr.last_mouse_coord;
+                    let ev = Event::Action(Action::Scroll(delta, coord));
+                    self.send_event(widget, id, ev)
                 } else {
                     Response::None
                 }
             }
-            MouseInput {
-                state,
-                button,
-                ..
-            } => {
+            InputEvent::MousePressed(button) | InputEvent::MouseReleased(button) => {
+                let pressed = matches!(event, InputEvent::MousePressed(_));
                 let coord = self.mgr.last_mouse_coord;
                 let source = PressSource::Mouse(button);
 
                 if let Some((grab_id, _)) = self.mouse_grab() {
                     // Mouse grab active: send events there
-                    let ev = match state {
-                        ElementState::Pressed => Event::PressStart { source, coord },
-                        ElementState::Released => Event::PressEnd {
+                    let ev = match pressed {
+                        true => Event::PressStart { source, coord },
+                        false => Event::PressEnd {
                             source,
                             end_id: self.mgr.hover,
                             coord,
                         },
                     };
-                    let r = widget.handle(&mut self, grab_id, ev);
-                    if state == ElementState::Released {
+                    let r = self.send_event(widget, grab_id, ev);
+                    if !pressed {
                         self.end_mouse_grab(button);
                     }
                     r
                 } else if let Some(id) = self.mgr.hover {
                     // No mouse grab but have a hover target
-                    if state == ElementState::Pressed {
+                    if pressed {
                         let ev = Event::PressStart { source, coord };
-                        widget.handle(&mut self, id, ev)
+                        self.send_event(widget, id, ev)
                     } else {
                         Response::None
                     }
@@ -805,96 +1430,471 @@ impl<'a> Manager<'a> {
                     Response::None
                 }
             }
-            // TouchpadPressure { pressure: f32, stage: i64, },
-            // AxisMotion { axis: AxisId, value: f64, },
-            // RedrawRequested [handled by toolkit]
-            Touch(touch) => {
-                let source = PressSource::Touch(touch.id);
-                let coord = touch.location.into();
-                match touch.phase {
-                    TouchPhase::Started => {
-                        if let Some(id) = widget.find_id(coord) {
-                            let ev = Event::PressStart { source, coord };
-                            widget.handle(&mut self, id, ev)
-                        } else {
-                            Response::None
-                        }
-                    }
-                    TouchPhase::Moved => {
-                        // NOTE: calling widget.handle twice appears
-                        // to be unavoidable (as with CursorMoved)
-                        let cur_id = widget.find_id(coord);
-
-                        let r = self.get_touch(touch.id).map(|grab| {
-                            let id = grab.start_id;
-                            let action = Event::PressMove {
-                                source,
-                                coord,
-                                delta: coord - grab.coord,
-                            };
-                            // Only when 'depressed' status changes:
-                            let redraw = grab.cur_id != cur_id &&
-                                (grab.cur_id == Some(grab.start_id) || cur_id == Some(grab.start_id));
-
-                            grab.cur_id = cur_id;
-                            grab.coord = coord;
-
-                            (id, action, redraw)
-                        });
-
-                        if let Some((id, action, redraw)) = r {
-                            if redraw {
-                                self.send_action(TkAction::Redraw);
-                            }
-                            widget.handle(&mut self, id, action)
-                        } else {
-                            Response::None
-                        }
-                    }
-                    TouchPhase::Ended => {
-                        if let Some(grab) = self.remove_touch(touch.id) {
-                            let action = Event::PressEnd {
-                                source,
-                                end_id: grab.cur_id,
-                                coord,
-                            };
-                            if let Some(cur_id) = grab.cur_id {
-                                self.redraw(cur_id);
-                            }
-                            widget.handle(&mut self, grab.start_id, action)
-                        } else {
-                            Response::None
-                        }
+            InputEvent::TouchStart(touch_id, coord) => {
+                if let Some(id) = widget.find_id(coord) {
+                    let source = PressSource::Touch(touch_id);
+                    let ev = Event::PressStart { source, coord };
+                    self.send_event(widget, id, ev)
+                } else {
+                    Response::None
+                }
+            }
+            InputEvent::TouchMove(touch_id, coord) => {
+                // NOTE: calling widget.handle twice appears
+                // to be unavoidable (as with CursorMoved)
+                let cur_id = widget.find_id(coord);
+
+                let r = self.get_touch(touch_id).map(|grab| {
+                    let id = grab.start_id;
+                    let action = Event::PressMove {
+                        source: PressSource::Touch(touch_id),
+                        coord,
+                        delta: coord - grab.coord,
+                    };
+                    // Only when 'hover' or 'depressed' status changes:
+                    let redraw = grab.cur_id != cur_id;
+
+                    grab.cur_id = cur_id;
+                    grab.coord = coord;
+
+                    (id, action, redraw)
+                });
+
+                if let Some((id, action, redraw)) = r {
+                    if redraw {
+                        self.request_redraw();
                     }
-                    TouchPhase::Cancelled => {
-                        if let Some(grab) = self.remove_touch(touch.id) {
-                            let action = Event::PressEnd {
-                                source,
-                                end_id: None,
-                                coord,
-                            };
-                            if let Some(cur_id) = grab.cur_id {
-                                self.redraw(cur_id);
-                            }
-                            widget.handle(&mut self, grab.start_id, action)
-                        } else {
-                            Response::None
-                        }
+                    self.send_event(widget, id, action)
+                } else {
+                    Response::None
+                }
+            }
+            InputEvent::TouchEnd(touch_id, coord) | InputEvent::TouchCancel(touch_id, coord) => {
+                if let Some(grab) = self.remove_touch(touch_id) {
+                    let end_id = match event {
+                        InputEvent::TouchEnd(..) => grab.cur_id,
+                        _ => None,
+                    };
+                    let action = Event::PressEnd {
+                        source: PressSource::Touch(touch_id),
+                        end_id,
+                        coord,
+                    };
+                    if let Some(cur_id) = grab.cur_id {
+                        self.redraw(cur_id);
                     }
+                    self.send_event(widget, grab.start_id, action)
+                } else {
+                    Response::None
                 }
             }
-            // HiDpiFactorChanged(factor) [handled by toolkit]
-            _ => Response::None,
-        };
+        }
+    }
+}
 
-        match response {
-            Response::None => (),
-            Response::Unhandled(_) => {
-                // we can safely ignore unhandled events here
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::geom::{Rect, Size};
+    use crate::testing::Harness;
+    use crate::widget::{CheckBoxBare, Row, Spacer};
+    use crate::WidgetCore;
+
+    #[test]
+    fn synthetic_click() {
+        let mut widget = CheckBoxBare::new_on(|state| state);
+        let mut h = Harness::new(&mut widget);
+
+        let mut mgr = h.manager();
+        let coord = Coord(5, 5);
+        let r = mgr.handle_synthetic(&mut widget, InputEvent::CursorMoved(coord));
+        assert!(r.is_none());
+        let r = mgr.handle_synthetic(&mut widget, InputEvent::MousePressed(MouseButton::Left));
+        assert!(r.is_none());
+        let r = mgr.handle_synthetic(&mut widget, InputEvent::MouseReleased(MouseButton::Left));
+        assert!(matches!(r, Response::Msg(true)));
+    }
+
+    #[test]
+    fn synthetic_touch() {
+        let mut widget = CheckBoxBare::new_on(|state| state);
+        let mut h = Harness::new(&mut widget);
+
+        let mut mgr = h.manager();
+        let coord = Coord(5, 5);
+        let r = mgr.handle_synthetic(&mut widget, InputEvent::TouchStart(1, coord));
+        assert!(r.is_none());
+        let r = mgr.handle_synthetic(&mut widget, InputEvent::TouchEnd(1, coord));
+        assert!(matches!(r, Response::Msg(true)));
+    }
+
+    #[test]
+    fn default_button() {
+        let mut widget = CheckBoxBare::new_on(|state| state);
+        let mut h = Harness::new(&mut widget);
+
+        let mut mgr = h.manager();
+        let ev = || InputEvent::KeyPressed(0, VirtualKeyCode::Return);
+        let r = mgr.handle_synthetic(&mut widget, ev());
+        assert!(r.is_none());
+
+        mgr.set_default_button(widget.id());
+        let r = mgr.handle_synthetic(&mut widget, ev());
+        assert!(matches!(r, Response::Msg(true)));
+    }
+
+    #[test]
+    fn shortcuts() {
+        let mut widget = CheckBoxBare::new_on(|state| state);
+        let mut h = Harness::new(&mut widget);
+
+        let mut mgr = h.manager();
+        let id = widget.id();
+        mgr.add_accel_key(VirtualKeyCode::A, id);
+        let (a, b) = (VirtualKeyCode::A, VirtualKeyCode::B);
+        let press = |key| InputEvent::KeyPressed(0, key);
+
+        // Consumed shortcuts are not passed to widgets
+        mgr.shortcuts_mut()
+            .insert(Shortcut::key(a), ShortcutAction::Consume);
+        assert!(mgr.handle_synthetic(&mut widget, press(a)).is_none());
+
+        // Translated shortcuts are dispatched as the new key
+        mgr.shortcuts_mut()
+            .insert(Shortcut::key(b), ShortcutAction::Key(a));
+        let r = mgr.handle_synthetic(&mut widget, press(b));
+        assert!(matches!(r, Response::Msg(true)));
+
+        // Unmapped keys (including modified keys) work as usual
+        mgr.shortcuts_mut().remove(Shortcut::key(a));
+        let r = mgr.handle_synthetic(&mut widget, press(a));
+        assert!(matches!(r, Response::Msg(false)));
+        mgr.shortcuts_mut()
+            .insert(Shortcut::ctrl(a), ShortcutAction::Consume);
+        let r = mgr.handle_synthetic(&mut widget, press(a));
+        assert!(matches!(r, Response::Msg(true)));
+    }
+
+    #[test]
+    fn send_action_merge() {
+        use TkAction::*;
+        let actions = [
+            None,
+            Redraw,
+            RegionMoved,
+            Reconfigure,
+            RequestClose,
+            Close,
+            CloseAll,
+        ];
+        // The expected result of sending actions[i] then actions[j]: the more
+        // significant action wins, regardless of order
+        let merged = [
+            [
+                None,
+                Redraw,
+                RegionMoved,
+                Reconfigure,
+                RequestClose,
+                Close,
+                CloseAll,
+            ],
+            [
+                Redraw,
+                Redraw,
+                RegionMoved,
+                Reconfigure,
+                RequestClose,
+                Close,
+                CloseAll,
+            ],
+            [
+                RegionMoved,
+                RegionMoved,
+                RegionMoved,
+                Reconfigure,
+                RequestClose,
+                Close,
+                CloseAll,
+            ],
+            [
+                Reconfigure,
+                Reconfigure,
+                Reconfigure,
+                Reconfigure,
+                RequestClose,
+                Close,
+                CloseAll,
+            ],
+            [
+                RequestClose,
+                RequestClose,
+                RequestClose,
+                RequestClose,
+                RequestClose,
+                Close,
+                CloseAll,
+            ],
+            [Close, Close, Close, Close, Close, Close, CloseAll],
+            [
+                CloseAll, CloseAll, CloseAll, CloseAll, CloseAll, CloseAll, CloseAll,
+            ],
+        ];
+        let mut h = Harness::new(&mut Spacer::default());
+        for (i, a) in actions.iter().enumerate() {
+            for (j, b) in actions.iter().enumerate() {
+                let mut mgr = h.manager();
+                mgr.send_action(*a);
+                mgr.send_action(*b);
+                let action = mgr.unwrap_action();
+                assert_eq!(action, merged[i][j], "sending {:?} then {:?}", a, b);
             }
-            Response::Msg(_) => unreachable!(),
-        };
+        }
 
-        self.unwrap_action()
+        // Selective redraws never override a pending action
+        let mut mgr = h.manager();
+        mgr.send_action(Close);
+        mgr.redraw(WidgetId::FIRST);
+        assert_eq!(mgr.unwrap_action(), Close);
+    }
+
+    #[test]
+    fn close_request() {
+        use crate::widget::{Label, Window};
+        use std::sync::atomic::{AtomicUsize, Ordering};
+
+        static CALLS: AtomicUsize = AtomicUsize::new(0);
+        let mut window = Window::new("Title", Label::new("text"));
+        window.set_on_close(&|_, _| {
+            CALLS.fetch_add(1, Ordering::SeqCst);
+            false
+        });
+        let mut h = Harness::new(&mut window);
+
+        // A vetoed request does not close the window
+        let mut mgr = h.manager();
+        mgr.send_action(TkAction::Redraw);
+        mgr.send_action(TkAction::RequestClose);
+        mgr.send_action(TkAction::RegionMoved);
+        assert_eq!(mgr.unwrap_action(), TkAction::RequestClose);
+
+        // ... but keeps actions subsumed by the request (the toolkit resolves
+        // the request with a new manager)
+        let mut mgr = h.manager();
+        mgr.request_close(&mut window);
+        assert_eq!(mgr.unwrap_action(), TkAction::RegionMoved);
+        assert_eq!(CALLS.load(Ordering::SeqCst), 1);
+
+        // Without other actions, a vetoed request does nothing
+        let mut mgr = h.manager();
+        mgr.send_action(TkAction::RequestClose);
+        mgr.request_close(&mut window);
+        assert_eq!(mgr.unwrap_action(), TkAction::None);
+        assert_eq!(CALLS.load(Ordering::SeqCst), 2);
+
+        // A forced close overrides a request and skips the hook
+        let mut mgr = h.manager();
+        mgr.send_action(TkAction::RequestClose);
+        mgr.send_action(TkAction::Close);
+        assert_eq!(mgr.unwrap_action(), TkAction::Close);
+        assert_eq!(CALLS.load(Ordering::SeqCst), 2);
+
+        // Without a hook, requests are granted
+        let mut window = Window::new("Title", Label::new("text"));
+        h.configure(&mut window);
+        let mut mgr = h.manager();
+        mgr.request_close(&mut window);
+        assert_eq!(mgr.unwrap_action(), TkAction::Close);
+    }
+
+    #[test]
+    fn update_subscription() {
+        use crate::class::HasText;
+        use crate::widget::{Bound, Label};
+
+        let binding = Binding::new("a".to_string());
+        let mut widget = Bound::new(Label::new(""), binding.clone(), |w, mgr, v| {
+            w.set_text(mgr, v)
+        });
+        let mut h = Harness::new(&mut widget);
+        let id = widget.inner().id();
+        h.state().draw_complete();
+
+        // Neither an unsubscribed handle nor an unchanged widget is redrawn
+        let mut mgr = h.manager();
+        mgr.update_handle(&mut widget, UpdateHandle::new(), 0);
+        mgr.update_handle(&mut widget, binding.handle(), 0);
+        assert_eq!(mgr.unwrap_action(), TkAction::None);
+        assert!(!h.state().is_dirty(id));
+
+        // A widget changed by the update requests its own redraw
+        let mut mgr = h.manager();
+        assert!(binding.set(&mut mgr, "b".to_string()));
+        mgr.update_handle(&mut widget, binding.handle(), 0);
+        assert_eq!(mgr.unwrap_action(), TkAction::Redraw);
+        assert!(h.state().is_dirty(id));
+        assert_eq!(widget.inner().get_text(), "b");
+    }
+
+    #[test]
+    fn update_payload() {
+        use crate::draw::{DrawHandle, SizeHandle};
+        use crate::layout::{AxisInfo, SizeRules};
+        use crate::macros::Widget;
+        use crate::{CoreData, Layout};
+
+        #[derive(Clone, Debug, Default, Widget)]
+        #[widget]
+        struct Receiver {
+            #[core]
+            core: CoreData,
+            updates: Vec<(UpdateHandle, u64)>,
+        }
+
+        impl Layout for Receiver {
+            fn size_rules(&mut self, _: &mut dyn SizeHandle, _: AxisInfo) -> SizeRules {
+                SizeRules::EMPTY
+            }
+            fn draw(&self, _: &mut dyn DrawHandle, _: &ManagerState) {}
+        }
+
+        impl Handler for Receiver {
+            type Msg = VoidMsg;
+            fn handle_action(&mut self, _: &mut Manager, action: Action) -> Response<VoidMsg> {
+                match action {
+                    Action::Update(handle, payload) => {
+                        self.updates.push((handle, payload));
+                        Response::None
+                    }
+                    a => Response::unhandled_action(a),
+                }
+            }
+        }
+
+        let mut widget = Receiver::default();
+        let mut h = Harness::new(&mut widget);
+        let (handle, other) = (UpdateHandle::new(), UpdateHandle::new());
+        let mut mgr = h.manager();
+        mgr.update_on_handle(handle, widget.id());
+
+        // Only subscribed handles are delivered, with their payload
+        mgr.update_handle(&mut widget, other, 1);
+        assert!(widget.updates.is_empty());
+        mgr.update_handle(&mut widget, handle, 7);
+        mgr.update_handle(&mut widget, handle, 8);
+        assert_eq!(widget.updates, [(handle, 7), (handle, 8)]);
+    }
+
+    #[test]
+    fn redraw_tracking() {
+        use crate::class::HasText;
+        use crate::widget::{Bound, Label};
+
+        let binding = Binding::new(());
+        let mut widget = Bound::new(Label::new("a"), binding, |_, _, _| ());
+        let mut h = Harness::new(&mut widget);
+        let (id0, id1) = (widget.inner().id(), widget.id());
+        assert!(h.state().is_dirty(id0) && h.state().is_dirty(id1));
+        h.state().draw_complete();
+        assert!(!h.state().is_dirty(id0) && !h.state().is_dirty(id1));
+
+        // Changing text marks only that widget dirty
+        let mut mgr = h.manager();
+        widget.inner_mut().set_text(&mut mgr, "a");
+        assert!(!mgr.mgr.is_dirty(id0));
+        widget.inner_mut().set_text(&mut mgr, "b");
+        assert!(mgr.mgr.is_dirty(id0) && !mgr.mgr.is_dirty(id1));
+        assert_eq!(mgr.unwrap_action(), TkAction::Redraw);
+        h.state().draw_complete();
+
+        // An explicit redraw action invalidates everything
+        let mut mgr = h.manager();
+        mgr.send_action(TkAction::Redraw);
+        assert!(mgr.mgr.is_dirty(id0) && mgr.mgr.is_dirty(id1));
+    }
+
+    #[test]
+    fn static_window_idle() {
+        use crate::class::{HasBool, HasText};
+        use crate::widget::Label;
+
+        let mut widget = CheckBoxBare::new_on(|state| state);
+        let mut label = Label::new("text");
+        let mut h = Harness::new(&mut widget);
+        widget.core_data_mut().rect = Rect::new(Coord(0, 0), Size(10, 10));
+        let id = widget.id();
+
+        // Opening the window requires exactly one draw
+        assert!(h.state().is_dirty(id));
+        h.state().draw_complete();
+        assert!(!h.state().is_dirty(id));
+
+        // Events and updates not changing state should not request a redraw
+        let mut mgr = h.manager();
+        for ev in [
+            InputEvent::CursorMoved(Coord(5, 5)),
+            InputEvent::CursorMoved(Coord(6, 5)),
+            InputEvent::ModifiersChanged(Default::default()),
+        ] {
+            assert!(mgr.handle_synthetic(&mut widget, ev).is_none());
+        }
+        widget.set_bool(&mut mgr, false);
+        label.set_text(&mut mgr, "text");
+        assert_eq!(mgr.unwrap_action(), TkAction::None);
+        assert!(!mgr.mgr.is_dirty(id));
+        assert_eq!(mgr.mgr.next_resume(), None);
+
+        widget.set_bool(&mut mgr, true);
+        assert_eq!(mgr.unwrap_action(), TkAction::Redraw);
+    }
+
+    #[test]
+    fn multi_touch() {
+        fn toggle(state: bool) -> bool {
+            state
+        }
+        let mut row = Row::new(vec![
+            CheckBoxBare::new_on(toggle),
+            CheckBoxBare::new_on(toggle),
+        ]);
+        let mut h = Harness::new(&mut row);
+        row.core_data_mut().rect = Rect::new(Coord(0, 0), Size(20, 10));
+        row.get_mut(0).unwrap().core_data_mut().rect = Rect::new(Coord(0, 0), Size(10, 10));
+        row.get_mut(1).unwrap().core_data_mut().rect = Rect::new(Coord(10, 0), Size(10, 10));
+        let id0 = row.get(0).unwrap().id();
+        let id1 = row.get(1).unwrap().id();
+
+        let mut mgr = h.manager();
+        let (c0, c1) = (Coord(5, 5), Coord(15, 5));
+        assert!(mgr
+            .handle_synthetic(&mut row, InputEvent::TouchStart(1, c0))
+            .is_none());
+        assert!(mgr
+            .handle_synthetic(&mut row, InputEvent::TouchStart(2, c1))
+            .is_none());
+        assert!(mgr.mgr.is_depressed(id0) && mgr.mgr.is_depressed(id1));
+        assert!(mgr.mgr.hover.is_none());
+
+        let r = mgr.handle_synthetic(&mut row, InputEvent::TouchEnd(2, c1));
+        assert!(matches!(r, Response::Msg(true)));
+        let r = mgr.handle_synthetic(&mut row, InputEvent::TouchEnd(1, c0));
+        assert!(matches!(r, Response::Msg(true)));
+        assert!(!mgr.mgr.is_hovered(id0) && !mgr.mgr.is_hovered(id1));
+    }
+
+    #[test]
+    fn context_menu_unhandled() {
+        let mut widget = CheckBoxBare::new_on(|state| state);
+        let mut h = Harness::new(&mut widget);
+
+        let mut mgr = h.manager();
+        let coord = Coord(5, 5);
+        let r = mgr.handle_synthetic(&mut widget, InputEvent::CursorMoved(coord));
+        assert!(r.is_none());
+        let r = mgr.handle_synthetic(&mut widget, InputEvent::MousePressed(MouseButton::Right));
+        match r {
+            Response::Unhandled(Event::Action(Action::ContextMenu(c))) => assert_eq!(c, coord),
+            r => panic!("unexpected response: {:?}", r),
+        }
     }
 }
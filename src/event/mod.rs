@@ -54,6 +54,14 @@
 //!
 //! [`WidgetId`]: crate::WidgetId
 
+// Log event dispatch; compiled out without feature "trace_events"
+macro_rules! trace_event {
+    ($($arg:tt)*) => {
+        #[cfg(feature = "trace_events")]
+        log::trace!(target: "kas::event", $($arg)*);
+    };
+}
+
 mod callback;
 #[cfg(not(feature = "winit"))]
 mod enums;
@@ -61,6 +69,8 @@ mod events;
 mod handler;
 mod manager;
 mod response;
+mod search;
+mod shortcuts;
 mod update;
 
 use std::fmt::Debug;
@@ -78,7 +88,9 @@ pub use events::*;
 pub use handler::Handler;
 pub use manager::{HighlightState, Manager, ManagerState};
 pub use response::Response;
-pub use update::UpdateHandle;
+pub use search::TypeSearch;
+pub use shortcuts::{Shortcut, ShortcutAction, Shortcuts};
+pub use update::{Binding, UpdateHandle};
 
 /// A void message
 ///
@@ -88,8 +100,17 @@ pub use update::UpdateHandle;
 /// Custom message types are required to implement `From<VoidMsg>`. The
 /// [`derive(VoidMsg)`](../macros/index.html#the-derivevoidmsg-macro)
 /// macro may be used for this purpose.
+///
+/// Widgets which never produce a message should use `VoidMsg` as their
+/// [`Handler::Msg`] type, not `()`. Where `()` is used as a message type (e.g.
+/// to signal "something happened"), a conversion from `VoidMsg` is provided.
 #[derive(Clone, Debug)]
 pub struct VoidMsg;
 
+impl From<VoidMsg> for () {
+    #[inline]
+    fn from(_: VoidMsg) -> Self {}
+}
+
 /// Alias for `Response<VoidMsg>`
 pub type VoidResponse = Response<VoidMsg>;
@@ -5,7 +5,7 @@
 
 //! Event handling: Response type
 
-use super::{Action, Event};
+use super::{Action, Event, VoidMsg};
 
 /// Response type from [`Handler::handle`].
 ///
@@ -89,8 +89,35 @@ impl<M> Response<M> {
     }
 }
 
+impl Response<VoidMsg> {
+    /// Construct [`Response::None`] for a [`VoidMsg`] handler
+    ///
+    /// Shorthand for `Response::<VoidMsg>::None`, usable where the type cannot
+    /// be inferred.
+    #[inline]
+    pub fn void() -> Self {
+        Response::None
+    }
+}
+
 impl<M> From<M> for Response<M> {
     fn from(msg: M) -> Self {
         Response::Msg(msg)
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::event::VoidResponse;
+
+    #[test]
+    fn void_response() {
+        assert!(Response::void().is_none());
+
+        let r: Response<()> = Response::from(VoidResponse::None);
+        assert!(r.is_none());
+        let r: Response<()> = VoidResponse::Msg(VoidMsg).into();
+        assert!(matches!(r, Response::Msg(())));
+    }
+}
@@ -0,0 +1,281 @@
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License in the LICENSE-APACHE file or at:
+//     https://www.apache.org/licenses/LICENSE-2.0
+
+//! Type-to-select support
+
+use std::time::{Duration, Instant};
+
+/// Incremental type-to-select search
+///
+/// Widgets presenting a list of items (e.g. a drop-down list) may use this to
+/// let the user jump to an item by typing its start. Each typed character is
+/// appended to a search prefix; the first item matching the prefix is
+/// selected. The prefix is reset when no character is typed for
+/// [`TypeSearch::timeout`].
+///
+/// Typing the same character repeatedly (as the whole prefix) cycles through
+/// the items starting with that character. Searches start from the current
+/// item and wrap around. Matching is case-insensitive unless enabled via
+/// [`TypeSearch::case_sensitive`].
+///
+/// A widget should forward [`Event::Action(Action::ReceivedCharacter(c))`]
+/// (while it has character focus) to [`TypeSearch::push`], and the
+/// `Backspace` key to [`TypeSearch::pop`], then select the returned index.
+/// After each of these it should schedule a timer update (see
+/// [`Manager::update_on_timer`]) after [`TypeSearch::idle_timeout`] and
+/// forward [`Widget::update_timer`] to [`TypeSearch::update_timer`].
+///
+/// All methods take the current time, `now`, as a parameter; widgets should
+/// pass [`Instant::now`].
+///
+/// [`Event::Action(Action::ReceivedCharacter(c))`]: super::Action::ReceivedCharacter
+/// [`Manager::update_on_timer`]: super::Manager::update_on_timer
+/// [`Widget::update_timer`]: crate::Widget::update_timer
+#[derive(Clone, Debug)]
+pub struct TypeSearch {
+    prefix: String,
+    last: Option<Instant>,
+    timeout: Duration,
+    case_sensitive: bool,
+}
+
+impl Default for TypeSearch {
+    fn default() -> Self {
+        TypeSearch::new()
+    }
+}
+
+impl TypeSearch {
+    /// Default idle time after which the prefix is reset
+    pub const TIMEOUT: Duration = Duration::from_millis(1000);
+
+    /// Construct
+    pub fn new() -> Self {
+        TypeSearch {
+            prefix: String::new(),
+            last: None,
+            timeout: Self::TIMEOUT,
+            case_sensitive: false,
+        }
+    }
+
+    /// Set the idle time after which the prefix is reset
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    /// Set whether matching is case-sensitive
+    pub fn case_sensitive(mut self, case_sensitive: bool) -> Self {
+        self.case_sensitive = case_sensitive;
+        self
+    }
+
+    /// The idle time after which the prefix is reset
+    pub fn idle_timeout(&self) -> Duration {
+        self.timeout
+    }
+
+    /// The current search prefix
+    pub fn prefix(&self) -> &str {
+        &self.prefix
+    }
+
+    /// Reset the search prefix
+    pub fn clear(&mut self) {
+        self.prefix.clear();
+        self.last = None;
+    }
+
+    /// Reset the prefix if idle for the timeout
+    ///
+    /// Returns the remaining time until the prefix would be reset, if any.
+    /// This is suitable as the result of [`Widget::update_timer`].
+    ///
+    /// [`Widget::update_timer`]: crate::Widget::update_timer
+    pub fn update_timer(&mut self, now: Instant) -> Option<Duration> {
+        let end = self.last? + self.timeout;
+        if now >= end {
+            self.clear();
+            None
+        } else {
+            Some(end - now)
+        }
+    }
+
+    /// Append `c` to the prefix and search
+    ///
+    /// The `items` are the labels of all items, in order; `current` is the
+    /// index of the selected item (if any). Returns the index of the item to
+    /// select, if any matches.
+    pub fn push<'a, I>(
+        &mut self,
+        now: Instant,
+        c: char,
+        items: I,
+        current: Option<usize>,
+    ) -> Option<usize>
+    where
+        I: IntoIterator<Item = &'a str>,
+        I::IntoIter: Clone,
+    {
+        // In case the timer was not delivered yet
+        self.update_timer(now);
+        self.last = Some(now);
+
+        let repeat = !self.prefix.is_empty() && self.prefix.chars().all(|p| p == c);
+        if repeat {
+            // Cycle through items starting with c
+            return self.find(items, current.map(|i| i + 1));
+        }
+        self.prefix.push(c);
+        if self.prefix.chars().count() == 1 {
+            // A new search starts after the current item
+            self.find(items, current.map(|i| i + 1))
+        } else {
+            self.find(items, current)
+        }
+    }
+
+    /// Remove the last character of the prefix and search again
+    ///
+    /// Parameters are as for [`TypeSearch::push`]. Returns `None` if the
+    /// prefix becomes empty.
+    pub fn pop<'a, I>(&mut self, now: Instant, items: I, current: Option<usize>) -> Option<usize>
+    where
+        I: IntoIterator<Item = &'a str>,
+        I::IntoIter: Clone,
+    {
+        self.update_timer(now);
+        self.last = Some(now);
+        self.prefix.pop();
+        if self.prefix.is_empty() {
+            return None;
+        }
+        // Include the current item, which matched the longer prefix
+        self.find(items, current)
+    }
+
+    /// Find the first item matching the prefix, starting at `start`
+    fn find<'a, I>(&self, items: I, start: Option<usize>) -> Option<usize>
+    where
+        I: IntoIterator<Item = &'a str>,
+        I::IntoIter: Clone,
+    {
+        let items = items.into_iter();
+        let len = items.clone().count();
+        if len == 0 {
+            return None;
+        }
+        let start = start.unwrap_or(0) % len;
+        items
+            .enumerate()
+            .cycle()
+            .skip(start)
+            .take(len)
+            .find(|(_, item)| self.matches(item))
+            .map(|(i, _)| i)
+    }
+
+    fn matches(&self, item: &str) -> bool {
+        let mut chars = item.chars();
+        for p in self.prefix.chars() {
+            let c = match chars.next() {
+                Some(c) => c,
+                None => return false,
+            };
+            let eq = if self.case_sensitive {
+                c == p
+            } else {
+                c.to_lowercase().eq(p.to_lowercase())
+            };
+            if !eq {
+                return false;
+            }
+        }
+        true
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    const ITEMS: [&str; 5] = ["apple", "Banana", "blueberry", "Cherry", "avocado"];
+
+    #[test]
+    fn case_insensitive() {
+        let t = Instant::now();
+        let mut search = TypeSearch::new();
+        assert_eq!(search.push(t, 'c', ITEMS.iter().cloned(), None), Some(3));
+        search.clear();
+        assert_eq!(search.push(t, 'B', ITEMS.iter().cloned(), None), Some(1));
+        assert_eq!(search.push(t, 'L', ITEMS.iter().cloned(), Some(1)), Some(2));
+        assert_eq!(search.prefix(), "BL");
+
+        let mut search = TypeSearch::new().case_sensitive(true);
+        assert_eq!(search.push(t, 'b', ITEMS.iter().cloned(), None), Some(2));
+        search.clear();
+        assert_eq!(search.push(t, 'c', ITEMS.iter().cloned(), None), None);
+    }
+
+    #[test]
+    fn wrap_around() {
+        let t = Instant::now();
+        let mut search = TypeSearch::new();
+        // A new search starts after the current item and wraps
+        assert_eq!(search.push(t, 'a', ITEMS.iter().cloned(), Some(3)), Some(4));
+        search.clear();
+        assert_eq!(search.push(t, 'a', ITEMS.iter().cloned(), Some(4)), Some(0));
+        // Repeating the character cycles through matches
+        assert_eq!(search.push(t, 'a', ITEMS.iter().cloned(), Some(0)), Some(4));
+        assert_eq!(search.push(t, 'a', ITEMS.iter().cloned(), Some(4)), Some(0));
+        assert_eq!(search.prefix(), "a");
+    }
+
+    #[test]
+    fn backspace() {
+        let t = Instant::now();
+        let mut search = TypeSearch::new();
+        assert_eq!(search.push(t, 'b', ITEMS.iter().cloned(), None), Some(1));
+        assert_eq!(search.push(t, 'l', ITEMS.iter().cloned(), Some(1)), Some(2));
+        assert_eq!(search.push(t, 'x', ITEMS.iter().cloned(), Some(2)), None);
+        assert_eq!(search.pop(t, ITEMS.iter().cloned(), Some(2)), Some(2));
+        assert_eq!(search.prefix(), "bl");
+        assert_eq!(search.pop(t, ITEMS.iter().cloned(), Some(2)), Some(2));
+        assert_eq!(search.prefix(), "b");
+        assert_eq!(search.pop(t, ITEMS.iter().cloned(), Some(2)), None);
+        assert_eq!(search.prefix(), "");
+    }
+
+    #[test]
+    fn idle_reset() {
+        let t = Instant::now();
+        let timeout = Duration::from_millis(500);
+        let mut search = TypeSearch::new().timeout(timeout);
+        assert_eq!(search.idle_timeout(), timeout);
+        assert_eq!(search.update_timer(t), None);
+
+        let step = Duration::from_millis(300);
+        assert_eq!(search.push(t, 'b', ITEMS.iter().cloned(), None), Some(1));
+        // Typing within the timeout extends the search
+        let t = t + step;
+        assert_eq!(search.update_timer(t), Some(timeout - step));
+        assert_eq!(search.push(t, 'l', ITEMS.iter().cloned(), Some(1)), Some(2));
+        assert_eq!(search.update_timer(t + step), Some(timeout - step));
+        assert_eq!(search.prefix(), "bl");
+
+        // The timer resets the prefix
+        assert_eq!(search.update_timer(t + timeout), None);
+        assert_eq!(search.prefix(), "");
+
+        // As does typing after the timeout if the timer was not delivered
+        let t = t + timeout;
+        assert_eq!(search.push(t, 'b', ITEMS.iter().cloned(), None), Some(1));
+        let t = t + 2 * timeout;
+        assert_eq!(search.push(t, 'c', ITEMS.iter().cloned(), Some(1)), Some(3));
+        assert_eq!(search.prefix(), "c");
+    }
+}
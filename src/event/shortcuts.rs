@@ -0,0 +1,120 @@
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License in the LICENSE-APACHE file or at:
+//     https://www.apache.org/licenses/LICENSE-2.0
+
+//! Window-level keyboard shortcuts
+
+use std::collections::HashMap;
+
+use super::{Action, Modifiers, UpdateHandle, VirtualKeyCode};
+
+/// A key combination: a key plus the modifiers held
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct Shortcut {
+    pub modifiers: Modifiers,
+    pub key: VirtualKeyCode,
+}
+
+impl Shortcut {
+    /// A key without modifiers
+    #[inline]
+    pub fn key(key: VirtualKeyCode) -> Self {
+        Shortcut {
+            modifiers: Modifiers::default(),
+            key,
+        }
+    }
+
+    /// A key with the control modifier
+    #[inline]
+    pub fn ctrl(key: VirtualKeyCode) -> Self {
+        let modifiers = Modifiers {
+            ctrl: true,
+            ..Default::default()
+        };
+        Shortcut { modifiers, key }
+    }
+
+    /// A key with the alt modifier
+    #[inline]
+    pub fn alt(key: VirtualKeyCode) -> Self {
+        let modifiers = Modifiers {
+            alt: true,
+            ..Default::default()
+        };
+        Shortcut { modifiers, key }
+    }
+}
+
+/// The result of an intercepted [`Shortcut`]
+#[derive(Clone, Debug)]
+pub enum ShortcutAction {
+    /// Consume the key press without further effect
+    Consume,
+    /// Translate to a press of another key, which is then dispatched as usual
+    ///
+    /// The translated key is not itself looked up in the shortcut map.
+    Key(VirtualKeyCode),
+    /// Consume the key press, sending an [`Action`] to the widget with
+    /// character focus, or if none the widget with key focus
+    ///
+    /// Does nothing if no widget has focus.
+    Action(Action),
+    /// Consume the key press, triggering an update on the given handle with
+    /// the given payload (see [`Manager::trigger_update`])
+    ///
+    /// This may be used to deliver application messages.
+    ///
+    /// [`Manager::trigger_update`]: super::Manager::trigger_update
+    Update(UpdateHandle, u64),
+}
+
+/// A map of keyboard shortcuts
+///
+/// Each window's event manager holds a shortcut map (see
+/// [`Manager::shortcuts_mut`]), consulted on each key press before the event
+/// is dispatched to widgets. Keys not in the map are dispatched as usual, thus
+/// widget accelerator keys and keyboard navigation continue to work.
+///
+/// The map persists across window reconfigures.
+///
+/// [`Manager::shortcuts_mut`]: super::Manager::shortcuts_mut
+#[derive(Clone, Debug, Default)]
+pub struct Shortcuts {
+    map: HashMap<Shortcut, ShortcutAction>,
+}
+
+impl Shortcuts {
+    /// Construct an empty map
+    #[inline]
+    pub fn new() -> Self {
+        Shortcuts::default()
+    }
+
+    /// True if the map is empty
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.map.is_empty()
+    }
+
+    /// Map `shortcut` to `action`, returning the previous action (if any)
+    pub fn insert(&mut self, shortcut: Shortcut, action: ShortcutAction) -> Option<ShortcutAction> {
+        self.map.insert(shortcut, action)
+    }
+
+    /// Remove `shortcut` from the map, returning its action (if any)
+    pub fn remove(&mut self, shortcut: Shortcut) -> Option<ShortcutAction> {
+        self.map.remove(&shortcut)
+    }
+
+    /// Look up the action of `shortcut`
+    pub fn get(&self, shortcut: Shortcut) -> Option<&ShortcutAction> {
+        self.map.get(&shortcut)
+    }
+
+    /// Remove all shortcuts
+    pub fn clear(&mut self) {
+        self.map.clear();
+    }
+}
@@ -3,11 +3,17 @@
 // You may obtain a copy of the License in the LICENSE-APACHE file or at:
 //     https://www.apache.org/licenses/LICENSE-2.0
 
-//! Event handling: updates
+//! Event handling: updates and bindings
 
+use std::cell::RefCell;
+use std::fmt;
 use std::num::NonZeroU32;
+use std::rc::Rc;
 use std::sync::atomic::{AtomicU32, Ordering};
 
+use super::Manager;
+use crate::WidgetId;
+
 /// An update handle
 ///
 /// Update handles are used to trigger an update event on all widgets which are
@@ -35,3 +41,102 @@ impl UpdateHandle {
         }
     }
 }
+
+/// An observable value shared between widgets
+///
+/// A binding holds a value (shared between all clones of the binding) and an
+/// [`UpdateHandle`]. Widgets may subscribe to the handle via
+/// [`Manager::update_on_handle`] (or use [`crate::widget::Bound`]); when the
+/// value is changed via [`Binding::set`], all subscribers receive an update
+/// via [`Widget::update_handle`].
+///
+/// Setting a value equal to the current value does nothing, thus bindings
+/// which update each other (directly or indirectly) do not cause an infinite
+/// loop, so long as the updates converge.
+///
+/// [`Widget::update_handle`]: crate::Widget::update_handle
+pub struct Binding<T> {
+    // The inner Rc allows access without holding a borrow (see `with`)
+    value: Rc<RefCell<Rc<T>>>,
+    handle: UpdateHandle,
+}
+
+impl<T> Clone for Binding<T> {
+    fn clone(&self) -> Self {
+        Binding {
+            value: self.value.clone(),
+            handle: self.handle,
+        }
+    }
+}
+
+impl<T: fmt::Debug> fmt::Debug for Binding<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "Binding {{ value: {:?}, handle: {:?} }}",
+            self.value.borrow(),
+            self.handle
+        )
+    }
+}
+
+impl<T: PartialEq> Binding<T> {
+    /// Construct with an initial value
+    pub fn new(value: T) -> Self {
+        Binding {
+            value: Rc::new(RefCell::new(Rc::new(value))),
+            handle: UpdateHandle::new(),
+        }
+    }
+
+    /// Get the update handle
+    #[inline]
+    pub fn handle(&self) -> UpdateHandle {
+        self.handle
+    }
+
+    /// Get a copy of the value
+    #[inline]
+    pub fn get(&self) -> T
+    where
+        T: Clone,
+    {
+        T::clone(&self.value.borrow())
+    }
+
+    /// Access the value by reference
+    ///
+    /// The binding is not borrowed while `f` runs, thus `f` may call
+    /// [`Binding::set`]; `f` still sees the value from before any such call.
+    #[inline]
+    pub fn with<R, F: FnOnce(&T) -> R>(&self, f: F) -> R {
+        let value = self.value.borrow().clone();
+        f(&value)
+    }
+
+    /// Set the value
+    ///
+    /// If `value` differs from the current value, the value is replaced and
+    /// an update is triggered for all subscribers (see
+    /// [`Manager::trigger_update`]), and true is returned. Otherwise, this is
+    /// a no-op returning false.
+    pub fn set(&self, mgr: &mut Manager, value: T) -> bool {
+        if **self.value.borrow() == value {
+            return false;
+        }
+        *self.value.borrow_mut() = Rc::new(value);
+        mgr.trigger_update(self.handle, 0);
+        true
+    }
+
+    /// Subscribe widget `id` to updates
+    ///
+    /// This should be called from [`Widget::configure`].
+    ///
+    /// [`Widget::configure`]: crate::Widget::configure
+    #[inline]
+    pub fn subscribe(&self, mgr: &mut Manager, id: WidgetId) {
+        mgr.update_on_handle(self.handle, id);
+    }
+}
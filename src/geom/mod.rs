@@ -4,12 +4,21 @@
 //     https://www.apache.org/licenses/LICENSE-2.0
 
 //! Geometry data types
+//!
+//! This module, like the [`layout`](crate::layout) solvers, has no
+//! dependency on windowing or graphics libraries (the `winit` feature only
+//! adds conversions). It may thus be used for layout computations without a
+//! GPU or display, e.g. in a build script or a headless server.
+
+mod vector;
+
+pub use vector::{Affine, Quad, Vec2};
 
 #[cfg(feature = "winit")]
 use winit::dpi::{LogicalPosition, PhysicalPosition, PhysicalSize, Pixel};
 
 /// An `(x, y)` coordinate.
-#[derive(Copy, Clone, PartialEq, Eq, Debug, Default)]
+#[derive(Copy, Clone, PartialEq, Eq, Hash, Debug, Default)]
 pub struct Coord(pub i32, pub i32);
 
 impl Coord {
@@ -43,6 +52,14 @@ impl Coord {
     }
 }
 
+#[cfg(feature = "winit")]
+impl From<Coord> for winit::dpi::Position {
+    #[inline]
+    fn from(coord: Coord) -> winit::dpi::Position {
+        winit::dpi::Position::Physical((coord.0, coord.1).into())
+    }
+}
+
 impl From<(i32, i32)> for Coord {
     #[inline]
     fn from(coord: (i32, i32)) -> Coord {
@@ -111,7 +128,7 @@ impl std::ops::AddAssign<Size> for Coord {
 }
 
 /// A `(w, h)` size.
-#[derive(Copy, Clone, PartialEq, Eq, Debug, Default)]
+#[derive(Copy, Clone, PartialEq, Eq, Hash, Debug, Default)]
 pub struct Size(pub u32, pub u32);
 
 impl Size {
@@ -222,7 +239,7 @@ impl std::ops::SubAssign for Size {
 }
 
 /// A rectangular region.
-#[derive(Clone, Copy, Default, Debug)]
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Default, Debug)]
 pub struct Rect {
     pub pos: Coord,
     pub size: Size,
@@ -253,6 +270,22 @@ impl Rect {
         let size = Size(w, h);
         Rect { pos, size }
     }
+
+    /// Get the intersection of two rects
+    ///
+    /// Returns `None` if the rects do not overlap.
+    pub fn intersection(&self, rhs: &Rect) -> Option<Rect> {
+        let pos = self.pos.max(rhs.pos);
+        let end1 = self.pos + self.size;
+        let end2 = rhs.pos + rhs.size;
+        let end = end1.min(end2);
+        if pos.0 < end.0 && pos.1 < end.1 {
+            let size = Size((end.0 - pos.0) as u32, (end.1 - pos.1) as u32);
+            Some(Rect { pos, size })
+        } else {
+            None
+        }
+    }
 }
 
 impl std::ops::Add<Coord> for Rect {
@@ -280,3 +313,52 @@ impl std::ops::Sub<Coord> for Rect {
         }
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn rect_intersection() {
+        let parent = Rect::new(Coord(10, 10), Size(100, 50));
+        let child = Rect::new(Coord(60, 0), Size(100, 30));
+        let expected = Rect::new(Coord(60, 10), Size(50, 20));
+        assert_eq!(parent.intersection(&child), Some(expected));
+        assert_eq!(child.intersection(&parent), Some(expected));
+
+        let inner = Rect::new(Coord(20, 20), Size(10, 10));
+        assert_eq!(parent.intersection(&inner), Some(inner));
+
+        let outside = Rect::new(Coord(110, 10), Size(10, 10));
+        assert_eq!(parent.intersection(&outside), None);
+    }
+
+    #[test]
+    fn rect_intersection_edges() {
+        let rect = Rect::new(Coord(10, 10), Size(100, 50));
+        assert_eq!(rect.intersection(&rect), Some(rect));
+
+        // Rects sharing only an edge or a corner do not overlap
+        let right = Rect::new(Coord(110, 10), Size(20, 50));
+        let below = Rect::new(Coord(10, 60), Size(100, 20));
+        let corner = Rect::new(Coord(110, 60), Size(10, 10));
+        for other in &[right, below, corner] {
+            assert_eq!(rect.intersection(other), None);
+            assert_eq!(other.intersection(&rect), None);
+        }
+
+        // One pixel of overlap is enough
+        let overlap = Rect::new(Coord(109, 59), Size(10, 10));
+        let expected = Rect::new(Coord(109, 59), Size(1, 1));
+        assert_eq!(rect.intersection(&overlap), Some(expected));
+
+        // Empty rects never intersect, even when inside another rect
+        let empty = Rect::new(Coord(20, 20), Size::ZERO);
+        let line = Rect::new(Coord(20, 20), Size(30, 0));
+        for other in &[empty, line] {
+            assert_eq!(rect.intersection(other), None);
+            assert_eq!(other.intersection(&rect), None);
+            assert_eq!(other.intersection(other), None);
+        }
+    }
+}
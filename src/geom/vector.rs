@@ -0,0 +1,471 @@
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License in the LICENSE-APACHE file or at:
+//     https://www.apache.org/licenses/LICENSE-2.0
+
+//! Vector types
+//!
+//! These types use `f32` dimensions, as used by drawing operations.
+
+use super::{Coord, Rect, Size};
+use std::ops::{Add, Div, Mul, Neg, Sub};
+
+/// Axis-aligned 2D cuboid, specified via two corners
+///
+/// Typically it is expected that `self.0.le(self.1)`. As with [`Rect`], the
+/// first corner is included in the quad while the second is not; thus quads
+/// which merely touch along an edge do not intersect.
+#[repr(C)]
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Quad(pub Vec2, pub Vec2);
+
+impl Quad {
+    /// Make self smaller by `value` on all sides (i.e. add to `self.0` and
+    /// subtract from `self.1`).
+    #[inline]
+    pub fn shrink(&mut self, value: f32) {
+        self.0 = self.0 + value;
+        self.1 = self.1 - value;
+    }
+
+    /// Get the size
+    #[inline]
+    pub fn size(&self) -> Vec2 {
+        self.1 - self.0
+    }
+
+    /// True if the quad has zero (or negative) area
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        !self.0.lt(self.1)
+    }
+
+    /// Check whether the given point is contained within this quad
+    #[inline]
+    pub fn contains(&self, p: Vec2) -> bool {
+        self.0.le(p) && p.lt(self.1)
+    }
+
+    /// Get the intersection of two quads
+    ///
+    /// Returns `None` if the quads do not overlap (including where they only
+    /// touch along an edge or at a corner).
+    #[inline]
+    pub fn intersection(&self, rhs: &Quad) -> Option<Quad> {
+        let quad = Quad(self.0.max(rhs.0), self.1.min(rhs.1));
+        if quad.is_empty() {
+            None
+        } else {
+            Some(quad)
+        }
+    }
+
+    /// Get the smallest quad containing both `self` and `rhs`
+    #[inline]
+    pub fn union(&self, rhs: &Quad) -> Quad {
+        Quad(self.0.min(rhs.0), self.1.max(rhs.1))
+    }
+}
+
+impl From<Quad> for Rect {
+    /// Get the smallest [`Rect`] containing the quad
+    ///
+    /// Corners are rounded outwards to integer coordinates.
+    #[inline]
+    fn from(quad: Quad) -> Rect {
+        let Quad(a, b) = quad;
+        let pos = Coord(a.0.floor() as i32, a.1.floor() as i32);
+        let end = Coord(b.0.ceil() as i32, b.1.ceil() as i32).max(pos);
+        let size = Size((end.0 - pos.0) as u32, (end.1 - pos.1) as u32);
+        Rect { pos, size }
+    }
+}
+
+impl From<Rect> for Quad {
+    #[inline]
+    fn from(rect: Rect) -> Quad {
+        let pos = Vec2::from(rect.pos);
+        let size = Vec2::from(rect.size);
+        Quad(pos, pos + size)
+    }
+}
+
+/// 2D vector
+///
+/// Usually used as either a coordinate or a difference of coordinates, but
+/// may have some other uses.
+///
+/// Vectors are partially ordered and support component-wise comparison via
+/// methods like `lhs.lt(rhs)`. The `PartialOrd` trait is not implemented since
+/// it implements `lhs ≤ rhs` as `lhs < rhs || lhs == rhs` which is wrong for
+/// vectors (consider for `lhs = (0, 1), rhs = (1, 1)`).
+#[repr(C)]
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Vec2(pub f32, pub f32);
+
+impl Vec2 {
+    /// Constructs a new instance with each element initialized to `value`.
+    #[inline]
+    pub const fn splat(value: f32) -> Self {
+        Vec2(value, value)
+    }
+
+    /// Return the minimum, componentwise
+    #[inline]
+    pub fn min(self, other: Self) -> Self {
+        Vec2(self.0.min(other.0), self.1.min(other.1))
+    }
+
+    /// Return the maximum, componentwise
+    #[inline]
+    pub fn max(self, other: Self) -> Self {
+        Vec2(self.0.max(other.0), self.1.max(other.1))
+    }
+
+    /// For each component, return `±1` with the same sign as `self`.
+    #[inline]
+    pub fn sign(self) -> Self {
+        let one = 1f32;
+        Vec2(one.copysign(self.0), one.copysign(self.1))
+    }
+
+    /// True when for all components, `lhs < rhs`
+    #[inline]
+    pub fn lt(self, rhs: Self) -> bool {
+        self.0 < rhs.0 && self.1 < rhs.1
+    }
+
+    /// True when for all components, `lhs ≤ rhs`
+    #[inline]
+    pub fn le(self, rhs: Self) -> bool {
+        self.0 <= rhs.0 && self.1 <= rhs.1
+    }
+
+    /// True when for all components, `lhs ≥ rhs`
+    #[inline]
+    pub fn ge(self, rhs: Self) -> bool {
+        self.0 >= rhs.0 && self.1 >= rhs.1
+    }
+
+    /// True when for all components, `lhs > rhs`
+    #[inline]
+    pub fn gt(self, rhs: Self) -> bool {
+        self.0 > rhs.0 && self.1 > rhs.1
+    }
+
+    /// Dot product
+    #[inline]
+    pub fn dot(self, rhs: Self) -> f32 {
+        self.0 * rhs.0 + self.1 * rhs.1
+    }
+
+    /// Euclidean length
+    #[inline]
+    pub fn length(self) -> f32 {
+        self.dot(self).sqrt()
+    }
+
+    /// Scale to unit length
+    ///
+    /// A zero-length vector is returned unchanged.
+    #[inline]
+    pub fn normalize(self) -> Self {
+        let len = self.length();
+        if len > 0.0 {
+            self / len
+        } else {
+            self
+        }
+    }
+}
+
+impl Neg for Vec2 {
+    type Output = Vec2;
+    #[inline]
+    fn neg(self) -> Self::Output {
+        Vec2(-self.0, -self.1)
+    }
+}
+
+impl Add<Vec2> for Vec2 {
+    type Output = Vec2;
+    #[inline]
+    fn add(self, rhs: Vec2) -> Self::Output {
+        Vec2(self.0 + rhs.0, self.1 + rhs.1)
+    }
+}
+
+impl Add<f32> for Vec2 {
+    type Output = Vec2;
+    #[inline]
+    fn add(self, rhs: f32) -> Self::Output {
+        Vec2(self.0 + rhs, self.1 + rhs)
+    }
+}
+
+impl Sub<Vec2> for Vec2 {
+    type Output = Vec2;
+    #[inline]
+    fn sub(self, rhs: Vec2) -> Self::Output {
+        Vec2(self.0 - rhs.0, self.1 - rhs.1)
+    }
+}
+
+impl Sub<f32> for Vec2 {
+    type Output = Vec2;
+    #[inline]
+    fn sub(self, rhs: f32) -> Self::Output {
+        Vec2(self.0 - rhs, self.1 - rhs)
+    }
+}
+
+impl Mul<Vec2> for Vec2 {
+    type Output = Vec2;
+    #[inline]
+    fn mul(self, rhs: Vec2) -> Self::Output {
+        Vec2(self.0 * rhs.0, self.1 * rhs.1)
+    }
+}
+
+impl Mul<f32> for Vec2 {
+    type Output = Vec2;
+    #[inline]
+    fn mul(self, rhs: f32) -> Self::Output {
+        Vec2(self.0 * rhs, self.1 * rhs)
+    }
+}
+
+impl Div<Vec2> for Vec2 {
+    type Output = Vec2;
+    #[inline]
+    fn div(self, rhs: Vec2) -> Self::Output {
+        Vec2(self.0 / rhs.0, self.1 / rhs.1)
+    }
+}
+
+impl Div<f32> for Vec2 {
+    type Output = Vec2;
+    #[inline]
+    fn div(self, rhs: f32) -> Self::Output {
+        Vec2(self.0 / rhs, self.1 / rhs)
+    }
+}
+
+impl From<(f32, f32)> for Vec2 {
+    #[inline]
+    fn from(arg: (f32, f32)) -> Self {
+        Vec2(arg.0, arg.1)
+    }
+}
+
+impl From<Vec2> for (f32, f32) {
+    #[inline]
+    fn from(v: Vec2) -> Self {
+        (v.0, v.1)
+    }
+}
+
+impl From<Coord> for Vec2 {
+    #[inline]
+    fn from(arg: Coord) -> Self {
+        Vec2(arg.0 as f32, arg.1 as f32)
+    }
+}
+
+impl From<Size> for Vec2 {
+    #[inline]
+    fn from(arg: Size) -> Self {
+        Vec2(arg.0 as f32, arg.1 as f32)
+    }
+}
+
+/// 2D affine transform
+///
+/// Maps point `p` to `x * p.0 + y * p.1 + translation`; that is, `x` and `y`
+/// are the images of the unit axes. The product `a * b` applies `b` first,
+/// then `a`.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Affine {
+    pub x: Vec2,
+    pub y: Vec2,
+    pub translation: Vec2,
+}
+
+impl Default for Affine {
+    fn default() -> Self {
+        Affine::IDENTITY
+    }
+}
+
+impl Affine {
+    /// The identity transform
+    pub const IDENTITY: Affine = Affine {
+        x: Vec2(1.0, 0.0),
+        y: Vec2(0.0, 1.0),
+        translation: Vec2(0.0, 0.0),
+    };
+
+    /// A translation
+    #[inline]
+    pub fn translate(v: Vec2) -> Self {
+        Affine {
+            translation: v,
+            ..Affine::IDENTITY
+        }
+    }
+
+    /// A scaling about the origin, per axis
+    #[inline]
+    pub fn scale(s: Vec2) -> Self {
+        Affine {
+            x: Vec2(s.0, 0.0),
+            y: Vec2(0.0, s.1),
+            translation: Vec2(0.0, 0.0),
+        }
+    }
+
+    /// A rotation about the origin by `angle` radians
+    ///
+    /// Since the y-axis points down, positive angles rotate clockwise on
+    /// screen.
+    #[inline]
+    pub fn rotate(angle: f32) -> Self {
+        let (sin, cos) = angle.sin_cos();
+        Affine {
+            x: Vec2(cos, sin),
+            y: Vec2(-sin, cos),
+            translation: Vec2(0.0, 0.0),
+        }
+    }
+
+    /// Transform a point
+    #[inline]
+    pub fn apply(&self, p: Vec2) -> Vec2 {
+        self.apply_vector(p) + self.translation
+    }
+
+    /// Transform a difference of points (ignoring translation)
+    #[inline]
+    pub fn apply_vector(&self, v: Vec2) -> Vec2 {
+        self.x * v.0 + self.y * v.1
+    }
+
+    /// Determinant of the linear part
+    #[inline]
+    pub fn determinant(&self) -> f32 {
+        self.x.0 * self.y.1 - self.y.0 * self.x.1
+    }
+
+    /// True if this is a translation (possibly by zero)
+    #[inline]
+    pub fn is_translation(&self) -> bool {
+        self.x == Vec2(1.0, 0.0) && self.y == Vec2(0.0, 1.0)
+    }
+
+    /// Geometric mean of the scale factors along each axis
+    ///
+    /// This is the factor by which areas scale, square-rooted; it is used to
+    /// scale lengths such as line widths.
+    #[inline]
+    pub fn mean_scale(&self) -> f32 {
+        self.determinant().abs().sqrt()
+    }
+}
+
+impl Mul<Affine> for Affine {
+    type Output = Affine;
+    #[inline]
+    fn mul(self, rhs: Affine) -> Affine {
+        Affine {
+            x: self.apply_vector(rhs.x),
+            y: self.apply_vector(rhs.y),
+            translation: self.apply(rhs.translation),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn vec2_ops() {
+        let a = Vec2(3.0, 4.0);
+        assert_eq!(a * 2.0, Vec2(6.0, 8.0));
+        assert_eq!(a / 2.0, Vec2(1.5, 2.0));
+        assert_eq!(a * Vec2(2.0, 0.5), Vec2(6.0, 2.0));
+        assert_eq!(a / Vec2(3.0, 2.0), Vec2(1.0, 2.0));
+        assert_eq!(-a, Vec2(-3.0, -4.0));
+        assert_eq!(a.dot(Vec2(1.0, -1.0)), -1.0);
+        assert_eq!(a.length(), 5.0);
+    }
+
+    #[test]
+    fn vec2_normalize() {
+        assert_eq!(Vec2(3.0, 4.0).normalize(), Vec2(0.6, 0.8));
+        assert_eq!(Vec2(0.0, -2.0).normalize(), Vec2(0.0, -1.0));
+        assert_eq!(Vec2::splat(0.0).normalize(), Vec2::splat(0.0));
+    }
+
+    #[test]
+    fn quad_intersection() {
+        let a = Quad(Vec2(0.0, 0.0), Vec2(10.0, 10.0));
+        let b = Quad(Vec2(5.0, -5.0), Vec2(15.0, 5.0));
+        let expected = Quad(Vec2(5.0, 0.0), Vec2(10.0, 5.0));
+        assert_eq!(a.intersection(&b), Some(expected));
+        assert_eq!(b.intersection(&a), Some(expected));
+        assert_eq!(a.union(&b), Quad(Vec2(0.0, -5.0), Vec2(15.0, 10.0)));
+
+        // Touching edges and corners do not intersect
+        let right = Quad(Vec2(10.0, 0.0), Vec2(20.0, 10.0));
+        let corner = Quad(Vec2(10.0, 10.0), Vec2(20.0, 20.0));
+        assert_eq!(a.intersection(&right), None);
+        assert_eq!(a.intersection(&corner), None);
+        assert_eq!(a.union(&corner), Quad(Vec2(0.0, 0.0), Vec2(20.0, 20.0)));
+
+        let outside = Quad(Vec2(20.0, 20.0), Vec2(30.0, 30.0));
+        assert_eq!(a.intersection(&outside), None);
+        let empty = Quad(Vec2(2.0, 2.0), Vec2(2.0, 8.0));
+        assert!(empty.is_empty());
+        assert_eq!(a.intersection(&empty), None);
+    }
+
+    #[test]
+    fn quad_contains() {
+        let a = Quad(Vec2(0.0, 0.0), Vec2(10.0, 10.0));
+        assert!(a.contains(Vec2(0.0, 0.0)));
+        assert!(a.contains(Vec2(9.5, 0.5)));
+        assert!(!a.contains(Vec2(10.0, 5.0)));
+        assert!(!a.contains(Vec2(5.0, 10.0)));
+        assert!(!a.contains(Vec2(-0.5, 5.0)));
+    }
+
+    #[test]
+    fn affine_compose() {
+        let t = Affine::translate(Vec2(10.0, 5.0));
+        let s = Affine::scale(Vec2(2.0, 3.0));
+        let p = Vec2(1.0, 1.0);
+        assert_eq!((t * s).apply(p), Vec2(12.0, 8.0));
+        assert_eq!((s * t).apply(p), Vec2(22.0, 18.0));
+        assert_eq!((t * s).apply(p), t.apply(s.apply(p)));
+        assert_eq!(Affine::IDENTITY * t, t);
+        assert_eq!((t * s).determinant(), 6.0);
+        assert!(t.is_translation());
+        assert!(!s.is_translation());
+
+        let r = Affine::rotate(std::f32::consts::FRAC_PI_2);
+        let q = r.apply(Vec2(1.0, 0.0));
+        assert!((q - Vec2(0.0, 1.0)).length() < 1e-6);
+        assert!((r.mean_scale() - 1.0).abs() < 1e-6);
+        assert_eq!(r.apply_vector(Vec2(0.0, 0.0)), Vec2(0.0, 0.0));
+    }
+
+    #[test]
+    fn quad_rect_conversion() {
+        let rect = Rect::new(Coord(3, -2), Size(10, 4));
+        assert_eq!(Rect::from(Quad::from(rect)), rect);
+
+        let quad = Quad(Vec2(0.5, 1.0), Vec2(2.25, 3.0));
+        assert_eq!(Rect::from(quad), Rect::new(Coord(0, 1), Size(3, 2)));
+    }
+}
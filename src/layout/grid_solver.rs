@@ -168,11 +168,8 @@ where
                 let end = span.1 as usize;
                 let ind = span.2 as usize;
 
-                let sum = (start..end)
-                    .map(|n| storage.width_ref()[n])
-                    .fold(SizeRules::EMPTY, |x, y| x + y);
-                storage.width_mut()[start]
-                    .set_at_least_op_sub(self.col_span_rules.as_ref()[ind], sum);
+                self.col_span_rules.as_ref()[ind]
+                    .distribute_over(&mut storage.width_mut()[start..end]);
             }
 
             rules = storage.width_ref()[0..cols]
@@ -186,11 +183,8 @@ where
                 let end = span.1 as usize;
                 let ind = span.2 as usize;
 
-                let sum = (start..end)
-                    .map(|n| storage.height_ref()[n])
-                    .fold(SizeRules::EMPTY, |x, y| x + y);
-                storage.height_mut()[start]
-                    .set_at_least_op_sub(self.row_span_rules.as_ref()[ind], sum);
+                self.row_span_rules.as_ref()[ind]
+                    .distribute_over(&mut storage.height_mut()[start..end]);
             }
 
             rules = storage.height_ref()[0..rows]
@@ -6,6 +6,27 @@
 //! Layout solver
 //!
 //! This is only of interest if building a custom widget with children.
+//!
+//! The size rules and their solvers are pure computations, usable without
+//! any widget, window or graphics device. For example, to distribute space
+//! between three columns:
+//! ```
+//! use kas::layout::{SizeRules, StretchPolicy};
+//!
+//! let mut rules = vec![
+//!     SizeRules::fixed(100),
+//!     SizeRules::new(50, 100, StretchPolicy::LowUtility),
+//!     SizeRules::new(50, 100, StretchPolicy::Filler),
+//! ];
+//! // The solver takes the sum of all rules as an extra, final element
+//! let total = rules.iter().fold(SizeRules::EMPTY, |sum, r| sum.append(*r));
+//! assert_eq!(total.min_size(), 200);
+//! rules.push(total);
+//!
+//! let mut widths = [0; 3];
+//! SizeRules::solve_seq(&mut widths, &rules, 400);
+//! assert_eq!(widths, [100, 200, 100]);
+//! ```
 
 mod grid_solver;
 mod row_solver;
@@ -21,7 +42,10 @@ pub use grid_solver::{GridChildInfo, GridSetter, GridSolver};
 pub use row_solver::{RowPositionSolver, RowSetter, RowSolver};
 pub use single_solver::{SingleSetter, SingleSolver};
 pub use size_rules::{Margins, SizeRules, StretchPolicy};
-pub use sizer::{solve, RulesSetter, RulesSolver};
+pub use sizer::{
+    child_draw, child_find_id, child_rect_dir, child_set_rect, child_size_rules, solve, solve_with,
+    RulesSetter, RulesSolver,
+};
 pub use storage::{
     DynGridStorage, DynRowStorage, FixedGridStorage, FixedRowStorage, GridStorage, RowStorage,
     RowTemp, Storage,
@@ -9,7 +9,7 @@ use std::marker::PhantomData;
 
 use super::{AxisInfo, Margins, RowStorage, RowTemp, RulesSetter, RulesSolver, SizeRules};
 use crate::geom::{Coord, Rect};
-use crate::{Directional, Widget};
+use crate::{Directional, TextDirection, Widget};
 
 /// A [`RulesSolver`] for rows (and, without loss of generality, for columns).
 ///
@@ -90,9 +90,13 @@ impl<T: RowTemp, S: RowStorage> RulesSolver for RowSolver<T, S> {
         let cols = storage.as_ref().len() - 1;
         if !self.axis_is_vertical {
             storage.as_mut()[cols] = self.rules;
+        } else if self.axis.is_vertical() {
+            // Horizontal row: children are aligned on this baseline
+            storage.set_baseline(self.rules.baseline());
         }
 
-        self.rules
+        // Rows do not report a baseline to their parent
+        self.rules.without_baseline()
     }
 }
 
@@ -106,6 +110,7 @@ impl<T: RowTemp, S: RowStorage> RulesSolver for RowSolver<T, S> {
 pub struct RowSetter<D, T: RowTemp, S: RowStorage> {
     crect: Rect,
     inter: u32,
+    baseline: Option<u32>,
     widths: T,
     direction: D,
     _s: PhantomData<S>,
@@ -131,9 +136,15 @@ impl<D: Directional, T: RowTemp, S: RowStorage> RowSetter<D, T, S> {
 
         SizeRules::solve_seq(widths.as_mut(), storage.as_ref(), width);
 
+        let baseline = match dim.0.is_horizontal() {
+            true => storage.baseline(),
+            false => None,
+        };
+
         RowSetter {
             crect,
             inter,
+            baseline,
             widths,
             direction: dim.0,
             _s: Default::default(),
@@ -155,6 +166,10 @@ impl<D: Directional, T: RowTemp, S: RowStorage> RulesSetter for RowSetter<D, T,
         }
         self.crect
     }
+
+    fn baseline(&self) -> Option<u32> {
+        self.baseline
+    }
 }
 
 /// Allows efficient implementations of `draw` / event handlers based on the
@@ -166,16 +181,35 @@ impl<D: Directional, T: RowTemp, S: RowStorage> RulesSetter for RowSetter<D, T,
 #[derive(Clone, Copy, Debug)]
 pub struct RowPositionSolver<D: Directional> {
     direction: D,
+    reversed: bool,
 }
 
 impl<D: Directional> RowPositionSolver<D> {
     /// Construct with given directionality
     pub fn new(direction: D) -> Self {
-        RowPositionSolver { direction }
+        RowPositionSolver {
+            direction,
+            reversed: false,
+        }
+    }
+
+    /// Set the layout direction
+    ///
+    /// For horizontal rows laid out right-to-left (see
+    /// [`crate::layout::child_rect_dir`]), children are positioned from right
+    /// to left. This has no effect on vertical layouts.
+    pub fn with_text_direction(mut self, direction: TextDirection) -> Self {
+        self.reversed = direction.is_rtl() && self.direction.is_horizontal();
+        self
     }
 
     fn binary_search<W: Widget>(self, widgets: &[W], coord: Coord) -> Result<usize, usize> {
-        if self.direction.is_horizontal() {
+        if self.reversed {
+            // Search on mirrored coordinates: the key is the negated right edge
+            widgets.binary_search_by_key(&(-coord.0 - 1), |w| {
+                -(w.rect().pos.0 + w.rect().size.0 as i32)
+            })
+        } else if self.direction.is_horizontal() {
             widgets.binary_search_by_key(&coord.0, |w| w.rect().pos.0)
         } else {
             widgets.binary_search_by_key(&coord.1, |w| w.rect().pos.1)
@@ -201,11 +235,17 @@ impl<D: Directional> RowPositionSolver<D> {
 
     /// Call `f` on each child intersecting the given `rect`
     pub fn for_children<W: Widget, F: FnMut(&W)>(self, widgets: &[W], rect: Rect, mut f: F) {
-        let start = match self.binary_search(widgets, rect.pos) {
+        // The first child is the one containing the leading corner of `rect`
+        let first = if self.reversed {
+            Coord(rect.pos.0 + rect.size.0 as i32 - 1, rect.pos.1)
+        } else {
+            rect.pos
+        };
+        let start = match self.binary_search(widgets, first) {
             Ok(i) => i,
             Err(i) if i > 0 => {
                 let j = i - 1;
-                if widgets[j].rect().contains(rect.pos) {
+                if widgets[j].rect().contains(first) {
                     j
                 } else {
                     i
@@ -218,7 +258,11 @@ impl<D: Directional> RowPositionSolver<D> {
 
         for i in start..widgets.len() {
             let child = &widgets[i];
-            if self.direction.is_horizontal() {
+            if self.reversed {
+                if child.rect().pos.0 + child.rect().size.0 as i32 <= rect.pos.0 {
+                    break;
+                }
+            } else if self.direction.is_horizontal() {
                 if child.rect().pos.0 >= end.0 {
                     break;
                 }
@@ -83,13 +83,40 @@ impl Default for StretchPolicy {
 ///
 /// This struct conveys properties such as the minimum size and preferred size
 /// of the widgets being queried.
-#[derive(Copy, Clone, Debug, Default)]
+///
+/// Custom widgets may construct rules via [`SizeRules::fixed`],
+/// [`SizeRules::flex`] or [`SizeRules::empty`] (or use those reported by the
+/// [`SizeHandle`]). Rules for multiple children are combined like a row
+/// does: [`SizeRules::append`] (or `+`) for children placed one after
+/// another along the axis, and [`SizeRules::max`] for children placed side
+/// by side across the axis (or on top of each other).
+///
+/// [`SizeHandle`]: crate::draw::SizeHandle
+///
+/// Additionally, a stretch *weight* (default: 1) controls the share of excess
+/// space received relative to siblings with the same stretch policy; see
+/// [`SizeRules::solve_seq`].
+///
+/// Rules for the vertical axis may report a text baseline, allowing
+/// horizontal rows to align children on a common baseline; see
+/// [`SizeRules::with_baseline`].
+#[derive(Copy, Clone, Debug)]
 pub struct SizeRules {
     // minimum good size
     a: u32,
     // ideal size; b >= a
     b: u32,
     stretch: StretchPolicy,
+    // stretch weight; weight >= 1
+    weight: u32,
+    // position of first baseline relative to top, when at ideal size
+    baseline: Option<u32>,
+}
+
+impl Default for SizeRules {
+    fn default() -> Self {
+        SizeRules::EMPTY
+    }
 }
 
 impl SizeRules {
@@ -98,8 +125,19 @@ impl SizeRules {
         a: 0,
         b: 0,
         stretch: StretchPolicy::Fixed,
+        weight: 1,
+        baseline: None,
     };
 
+    /// Empty (zero size)
+    ///
+    /// This is the identity for both [`SizeRules::append`] and
+    /// [`SizeRules::max`] (except that the stretch weight is at least 1).
+    #[inline]
+    pub fn empty() -> Self {
+        SizeRules::EMPTY
+    }
+
     /// A fixed size
     #[inline]
     pub fn fixed(size: u32) -> Self {
@@ -107,6 +145,8 @@ impl SizeRules {
             a: size,
             b: size,
             stretch: StretchPolicy::Fixed,
+            weight: 1,
+            baseline: None,
         }
     }
 
@@ -122,19 +162,103 @@ impl SizeRules {
             a: min,
             b: ideal.max(min),
             stretch,
+            weight: 1,
+            baseline: None,
         }
     }
 
+    /// A flexible size
+    ///
+    /// Requires at least `min`, prefers `ideal` and may stretch beyond this
+    /// according to `stretch`. This is equivalent to [`SizeRules::new`].
+    #[inline]
+    pub fn flex(min: u32, ideal: u32, stretch: StretchPolicy) -> Self {
+        SizeRules::new(min, ideal, stretch)
+    }
+
+    /// Set the stretch weight
+    ///
+    /// Excess space is shared between siblings with the highest stretch
+    /// policy in proportion to their weight. The default weight is 1; a
+    /// weight of 0 is treated as 1.
+    #[inline]
+    pub fn with_weight(mut self, weight: u32) -> Self {
+        self.weight = weight.max(1);
+        self
+    }
+
+    /// Set the text baseline
+    ///
+    /// For vertical rules of widgets displaying text, this is the distance
+    /// from the top of the widget to the first text baseline when the widget
+    /// has its ideal size. Widgets without text should not set a baseline;
+    /// these are aligned as usual (e.g. centred).
+    ///
+    /// Baselines are combined by [`SizeRules::max`] (as used for the
+    /// vertical axis of horizontal rows); other operations (e.g. addition)
+    /// remove the baseline.
+    #[inline]
+    pub fn with_baseline(mut self, baseline: u32) -> Self {
+        self.baseline = Some(baseline);
+        self
+    }
+
     /// Use the maximum size of `self` and `rhs`.
+    ///
+    /// This combines rules for content sharing the same space along this
+    /// axis, e.g. the heights of children in a row.
+    ///
+    /// If both have a baseline, the ideal size is increased where
+    /// necessary to fit both when aligned on a common baseline.
     #[inline]
     pub fn max(self, rhs: Self) -> SizeRules {
+        let mut b = self.b.max(rhs.b);
+        let baseline = match (self.baseline, rhs.baseline) {
+            (Some(x), Some(y)) => {
+                let ascent = x.max(y);
+                b = b.max(ascent + (self.b - x.min(self.b)).max(rhs.b - y.min(rhs.b)));
+                Some(ascent)
+            }
+            (x, y) => x.or(y),
+        };
         SizeRules {
             a: self.a.max(rhs.a),
-            b: self.b.max(rhs.b),
+            b,
             stretch: self.stretch.max(rhs.stretch),
+            weight: self.combine_weight(rhs),
+            baseline,
         }
     }
 
+    /// Rules for `self` followed by `rhs` along this axis
+    ///
+    /// This combines rules for content placed sequentially, e.g. the widths
+    /// of children in a row; it is equivalent to `self + rhs`. Margins may be
+    /// included by appending [`SizeRules::fixed`] rules.
+    #[inline]
+    pub fn append(self, rhs: Self) -> SizeRules {
+        self + rhs
+    }
+
+    // Weight of combined rules: that of the rules with the higher stretch
+    // policy, or the maximum if policies are equal
+    #[inline]
+    fn combine_weight(self, rhs: Self) -> u32 {
+        if self.stretch > rhs.stretch {
+            self.weight
+        } else if self.stretch < rhs.stretch {
+            rhs.weight
+        } else {
+            self.weight.max(rhs.weight)
+        }
+    }
+
+    // True if empty (zero ideal size) and fixed: such elements collapse
+    #[inline]
+    fn is_collapsible(self) -> bool {
+        self.b == 0 && self.stretch == StretchPolicy::Fixed
+    }
+
     /// Get the minimum size
     #[inline]
     pub fn min_size(self) -> u32 {
@@ -147,6 +271,31 @@ impl SizeRules {
         self.b
     }
 
+    /// Get the stretch policy
+    #[inline]
+    pub fn stretch(self) -> StretchPolicy {
+        self.stretch
+    }
+
+    /// Get the stretch weight
+    #[inline]
+    pub fn weight(self) -> u32 {
+        self.weight
+    }
+
+    /// Get the text baseline, if any
+    #[inline]
+    pub fn baseline(self) -> Option<u32> {
+        self.baseline
+    }
+
+    /// Remove any baseline
+    #[inline]
+    pub fn without_baseline(mut self) -> Self {
+        self.baseline = None;
+        self
+    }
+
     /// Like `self = self.max(x - y)` but handling negative values correctly
     // TODO: switch to i32?
     pub fn set_at_least_op_sub(&mut self, x: Self, y: Self) {
@@ -159,6 +308,45 @@ impl SizeRules {
         self.b = self.a.max(self.b);
     }
 
+    /// Distribute the rules of a spanning cell over the spanned tracks
+    ///
+    /// The minimum and ideal sizes of `rules` are increased (if necessary) such
+    /// that their sum is at least that of `self`, dividing any excess equally.
+    /// If no track has a stretch policy at least that of `self`, each track's
+    /// policy is raised to that of `self`.
+    #[cfg_attr(not(feature = "internal_doc"), doc(hidden))]
+    pub fn distribute_over(self, rules: &mut [Self]) {
+        let n = rules.len() as u32;
+        if n == 0 {
+            return;
+        }
+
+        let sum = rules.iter().fold(0, |sum, r| sum + r.a);
+        if self.a > sum {
+            let excess = self.a - sum;
+            let (per_elt, extra) = (excess / n, excess % n);
+            for (i, r) in rules.iter_mut().enumerate() {
+                r.a += per_elt + if (i as u32) < extra { 1 } else { 0 };
+                r.b = r.b.max(r.a);
+            }
+        }
+
+        let sum = rules.iter().fold(0, |sum, r| sum + r.b);
+        if self.b > sum {
+            let excess = self.b - sum;
+            let (per_elt, extra) = (excess / n, excess % n);
+            for (i, r) in rules.iter_mut().enumerate() {
+                r.b += per_elt + if (i as u32) < extra { 1 } else { 0 };
+            }
+        }
+
+        if rules.iter().all(|r| r.stretch < self.stretch) {
+            for r in rules.iter_mut() {
+                r.stretch = self.stretch;
+            }
+        }
+    }
+
     /// Reduce the minimum size
     ///
     /// If `min` is greater than the current minimum size, this has no effect.
@@ -172,7 +360,27 @@ impl SizeRules {
     /// Given a sequence of width / height `rules` from children (including a
     /// final value which is the total) and a `target` size, find an appropriate
     /// size for each child width / height.
-    #[cfg_attr(not(feature = "internal_doc"), doc(hidden))]
+    ///
+    /// The result is deterministic, depending only on `rules` and `target`:
+    ///
+    /// 1.  If `target` is at least the total ideal size, each child receives
+    ///     its ideal size. The excess is divided between the children with
+    ///     the highest [`StretchPolicy`] in proportion to their weight (see
+    ///     [`SizeRules::with_weight`]). As an exception, where this policy is
+    ///     `Fixed`, children with zero ideal size do not receive excess (so
+    ///     that empty widgets collapse), unless all children are empty.
+    /// 2.  If `target` is between the total minimum and ideal sizes, each child
+    ///     is shrunk from its ideal size towards its minimum in proportion to
+    ///     the difference between these, thus children of fixed size keep their
+    ///     size. If `rules[N]` exceeds the sum of children (e.g. due to a
+    ///     spanning cell), empty fixed-size children again do not receive
+    ///     the difference, unless all children are empty.
+    /// 3.  If `target` is less than the total minimum size, the largest
+    ///     children are shrunk first, until all children are equally sized;
+    ///     these then shrink together.
+    ///
+    /// In each case, where the division is not exact, the remaining pixels are
+    /// given one each to eligible children, in order, from the first.
     // TODO (const generics):
     // fn solve_seq<const N: usize>(out: &mut [u32; N], rules: &[Self; N + 1], target: u32)
     pub fn solve_seq(out: &mut [u32], rules: &[Self], target: u32) {
@@ -189,57 +397,82 @@ impl SizeRules {
                 out[i] = rules[i].b;
             }
 
+            // Empty fixed-size elements collapse (do not receive excess
+            // space), unless there is nothing else to stretch.
             let highest_stretch = rules[N].stretch;
-            let count = (0..N)
-                .filter(|i| rules[*i].stretch == highest_stretch)
-                .count() as u32;
+            let skip_empty =
+                highest_stretch == StretchPolicy::Fixed && (0..N).any(|i| rules[i].b > 0);
+            let eligible =
+                |i: usize| rules[i].stretch == highest_stretch && (rules[i].b > 0 || !skip_empty);
+            let total_weight: u64 = (0..N)
+                .filter(|i| eligible(*i))
+                .map(|i| rules[i].weight as u64)
+                .sum();
             let excess = target - rules[N].b;
-            let per_elt = excess / count;
-            let mut extra = excess - count * per_elt;
+            let mut extra = excess;
             for i in 0..N {
-                if rules[i].stretch == highest_stretch {
-                    out[i] += per_elt;
-                    if extra > 0 {
-                        out[i] += 1;
-                        extra -= 1;
-                    }
+                if eligible(i) {
+                    let add = (excess as u64 * rules[i].weight as u64 / total_weight) as u32;
+                    out[i] += add;
+                    extra -= add;
+                }
+            }
+            for (i, size) in out.iter_mut().enumerate() {
+                if extra == 0 {
+                    break;
+                }
+                if eligible(i) {
+                    *size += 1;
+                    extra -= 1;
                 }
             }
         } else if target >= rules[N].a {
-            // At or over minimum: distribute extra relative to preferences.
-            // TODO: perhaps this should not use the minimum except as a minimum?
-
-            let target_rel = target - rules[N].a;
-            let pref_rel = rules[N].b - rules[N].a;
+            // At or over minimum: shrink from ideal relative to preferences.
+            let target_rel = (target - rules[N].a) as u64;
+            let pref_rel = (rules[N].b - rules[N].a) as u64;
             let mut sum = 0;
 
-            if pref_rel > 0 {
-                let x = target_rel as f64 / pref_rel as f64;
-
-                for n in 0..N {
-                    // This will round down:
-                    let r = rules[n];
-                    let size = r.a + (x * (r.b - r.a) as f64) as u32;
-                    out[n] = size;
-                    sum += size;
-                }
-            } else {
-                // special case: pref_rel == 0
-                let add = target_rel / N as u32;
-                for n in 0..N {
-                    let size = rules[n].a + add;
-                    out[n] = size;
-                    sum += size;
+            for n in 0..N {
+                let r = rules[n];
+                let mut size = r.a;
+                // This will round down:
+                if let Some(add) = (target_rel * (r.b - r.a) as u64).checked_div(pref_rel) {
+                    size += add as u32;
                 }
+                out[n] = size;
+                sum += size;
             }
 
             // The above may round down, which may leave us a little short.
             assert!(sum <= target);
-            let rem = target - sum;
-            assert!(rem as usize <= N);
-            // Distribute to first rem. sizes.
-            for n in 0..(rem as usize) {
-                out[n] += 1;
+            let mut rem = target - sum;
+            // Distribute to first children under their ideal size, then (if
+            // rules[N] exceeds the sum of children) to any, except that empty
+            // fixed-size children collapse unless all children are empty.
+            for n in 0..N {
+                if rem == 0 {
+                    break;
+                }
+                if out[n] < rules[n].b {
+                    out[n] += 1;
+                    rem -= 1;
+                }
+            }
+            if rem > 0 {
+                let skip_empty = (0..N).any(|n| !rules[n].is_collapsible());
+                let eligible = |n: usize| !(skip_empty && rules[n].is_collapsible());
+                let count = (0..N).filter(|n| eligible(*n)).count() as u32;
+                let per_elt = rem / count;
+                let mut extra = rem - count * per_elt;
+                for (n, size) in out.iter_mut().enumerate() {
+                    if eligible(n) {
+                        *size += per_elt;
+                        if extra > 0 {
+                            *size += 1;
+                            extra -= 1;
+                        }
+                    }
+                }
             }
         } else {
             // Under minimum: reduce maximum allowed size.
@@ -266,11 +499,11 @@ impl SizeRules {
                 let step = (excess / num_equal).min(largest - next_largest);
                 if step == 0 {
                     for n in 0..N {
+                        if excess == 0 {
+                            break;
+                        }
                         if out[n] == largest {
                             out[n] -= 1;
-                            if excess == 0 {
-                                break;
-                            }
                             excess -= 1;
                         }
                     }
@@ -308,6 +541,8 @@ impl std::ops::Add<SizeRules> for SizeRules {
             a: self.a + rhs.a,
             b: self.b + rhs.b,
             stretch: self.stretch.max(rhs.stretch),
+            weight: self.combine_weight(rhs),
+            baseline: None,
         }
     }
 }
@@ -321,6 +556,8 @@ impl std::ops::Add<u32> for SizeRules {
             a: self.a + rhs,
             b: self.b + rhs,
             stretch: self.stretch,
+            weight: self.weight,
+            baseline: None,
         }
     }
 }
@@ -328,11 +565,7 @@ impl std::ops::Add<u32> for SizeRules {
 impl std::ops::AddAssign for SizeRules {
     #[inline]
     fn add_assign(&mut self, rhs: Self) {
-        *self = Self {
-            a: self.a + rhs.a,
-            b: self.b + rhs.b,
-            stretch: self.stretch.max(rhs.stretch),
-        };
+        *self = *self + rhs;
     }
 }
 
@@ -345,6 +578,182 @@ impl std::ops::Mul<u32> for SizeRules {
             a: self.a * rhs,
             b: self.b * rhs,
             stretch: self.stretch,
+            weight: self.weight,
+            baseline: None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn distribute_span() {
+        let mut rules = [SizeRules::fixed(10), SizeRules::EMPTY];
+        SizeRules::new(30, 41, StretchPolicy::LowUtility).distribute_over(&mut rules);
+        assert_eq!((rules[0].a, rules[0].b), (20, 26));
+        assert_eq!((rules[1].a, rules[1].b), (10, 15));
+        assert!(rules.iter().all(|r| r.stretch == StretchPolicy::LowUtility));
+
+        // Spans already satisfied do not affect tracks
+        let mut rules = [SizeRules::fixed(10), SizeRules::fixed(10)];
+        SizeRules::fixed(15).distribute_over(&mut rules);
+        assert_eq!((rules[0].a, rules[1].a), (10, 10));
+    }
+
+    #[test]
+    fn empty_collapse() {
+        let rules = [
+            SizeRules::fixed(10),
+            SizeRules::EMPTY,
+            SizeRules::fixed(10),
+            SizeRules::fixed(20),
+        ];
+        let mut out = [0; 3];
+        SizeRules::solve_seq(&mut out, &rules, 31);
+        assert_eq!(out, [16, 0, 15]);
+
+        let rules = [SizeRules::EMPTY, SizeRules::EMPTY, SizeRules::EMPTY];
+        let mut out = [0; 2];
+        SizeRules::solve_seq(&mut out, &rules, 4);
+        assert_eq!(out, [2, 2]);
+
+        // Total exceeding the sum of children (as with a spanning cell)
+        let rules = [
+            SizeRules::EMPTY,
+            SizeRules::fixed(10),
+            SizeRules::EMPTY,
+            SizeRules::fixed(14),
+        ];
+        let mut out = [0; 3];
+        SizeRules::solve_seq(&mut out, &rules, 14);
+        assert_eq!(out, [0, 14, 0]);
+    }
+
+    #[test]
+    fn filler_stretch() {
+        let filler = SizeRules::new(0, 0, StretchPolicy::Filler);
+        let mut rules = [
+            SizeRules::fixed(10),
+            filler,
+            SizeRules::fixed(10),
+            filler,
+            SizeRules::EMPTY,
+        ];
+        rules[4] = rules[0..4].iter().fold(SizeRules::EMPTY, |x, y| x + *y);
+        let mut out = [0; 4];
+        SizeRules::solve_seq(&mut out, &rules, 50);
+        assert_eq!(out, [10, 15, 10, 15]);
+    }
+
+    fn solve(rules: &[SizeRules], target: u32) -> Vec<u32> {
+        let mut rules = rules.to_vec();
+        let total = rules.iter().fold(SizeRules::EMPTY, |x, y| x + *y);
+        rules.push(total);
+        let mut out = vec![0; rules.len() - 1];
+        SizeRules::solve_seq(&mut out, &rules, target);
+        assert_eq!(out.iter().sum::<u32>(), target);
+        out
+    }
+
+    #[test]
+    fn surplus_by_weight() {
+        let filler = SizeRules::new(0, 0, StretchPolicy::Filler);
+        let rules = [SizeRules::fixed(10), filler, filler.with_weight(3)];
+        assert_eq!(solve(&rules, 50), [10, 10, 30]);
+        assert_eq!(solve(&rules, 51), [10, 11, 30]);
+
+        // Only the highest stretch policy receives surplus
+        let rules = [
+            SizeRules::fixed(20),
+            SizeRules::new(10, 30, StretchPolicy::LowUtility),
+            SizeRules::new(10, 30, StretchPolicy::Filler).with_weight(5),
+        ];
+        assert_eq!(solve(&rules, 100), [20, 50, 30]);
+    }
+
+    #[test]
+    fn shrink_to_min() {
+        // Between minimum and ideal: fixed sizes are kept
+        let rules = [
+            SizeRules::fixed(20),
+            SizeRules::new(10, 30, StretchPolicy::Filler),
+            SizeRules::new(0, 10, StretchPolicy::Filler),
+        ];
+        assert_eq!(solve(&rules, 60), [20, 30, 10]);
+        assert_eq!(solve(&rules, 45), [20, 20, 5]);
+        assert_eq!(solve(&rules, 46), [20, 21, 5]);
+        assert_eq!(solve(&rules, 30), [20, 10, 0]);
+
+        // Under minimum: the largest shrink first
+        let rules = [
+            SizeRules::fixed(30),
+            SizeRules::fixed(10),
+            SizeRules::fixed(20),
+        ];
+        assert_eq!(solve(&rules, 50), [20, 10, 20]);
+        assert_eq!(solve(&rules, 45), [17, 10, 18]);
+        assert_eq!(solve(&rules, 27), [9, 9, 9]);
+    }
+
+    #[test]
+    fn combine_weight() {
+        let filler = SizeRules::new(0, 0, StretchPolicy::Filler);
+        let rules = SizeRules::fixed(10).with_weight(4) + filler.with_weight(2);
+        assert_eq!(rules.weight(), 2);
+        let rules = rules + filler.with_weight(3);
+        assert_eq!(rules.weight(), 3);
+        assert_eq!(filler.max(filler.with_weight(0)).weight(), 1);
+    }
+
+    #[test]
+    fn baseline() {
+        let label = SizeRules::new(20, 24, StretchPolicy::Filler).with_baseline(15);
+        let edit = SizeRules::fixed(26).with_baseline(20);
+        let rules = label.max(edit);
+        assert_eq!((rules.a, rules.b), (26, 29));
+        assert_eq!(rules.baseline(), Some(20));
+
+        // Non-text widgets do not affect the baseline
+        let rules = rules.max(SizeRules::fixed(40));
+        assert_eq!((rules.a, rules.b), (40, 40));
+        assert_eq!(rules.baseline(), Some(20));
+
+        assert_eq!((label + edit).baseline(), None);
+    }
+
+    #[test]
+    fn combinators() {
+        let a = SizeRules::fixed(10);
+        let b = SizeRules::flex(5, 20, StretchPolicy::LowUtility);
+        let c = SizeRules::flex(8, 4, StretchPolicy::Filler).with_weight(2);
+        assert_eq!((c.min_size(), c.ideal_size()), (8, 8));
+
+        // Width of a row: children and the margin between them in sequence
+        let width = a.append(SizeRules::fixed(2)).append(b);
+        assert_eq!((width.min_size(), width.ideal_size()), (17, 32));
+        assert_eq!(width.stretch(), StretchPolicy::LowUtility);
+        assert_eq!(width.weight(), 1);
+
+        // Height of a row: children side by side
+        let height = a.max(b).max(c);
+        assert_eq!((height.min_size(), height.ideal_size()), (10, 20));
+        assert_eq!(height.stretch(), StretchPolicy::LowUtility);
+
+        // Empty rules are the identity for both combinators
+        for r in [a, b, c].iter() {
+            let appended = SizeRules::empty().append(*r);
+            let maxed = r.max(SizeRules::empty());
+            for x in [appended, maxed].iter() {
+                assert_eq!(
+                    (x.min_size(), x.ideal_size()),
+                    (r.min_size(), r.ideal_size())
+                );
+                assert_eq!((x.stretch(), x.weight()), (r.stretch(), r.weight()));
+            }
         }
+        let sum = [a, b, c].iter().fold(SizeRules::empty(), |acc, r| acc + *r);
+        assert_eq!((sum.min_size(), sum.ideal_size()), (23, 38));
     }
 }
@@ -6,15 +6,18 @@
 //! Layout solver
 
 use log::trace;
+use std::collections::hash_map::DefaultHasher;
 use std::fmt;
+use std::hash::{Hash, Hasher};
 
 use super::{AxisInfo, SizeRules};
-use crate::draw::SizeHandle;
+use crate::draw::{DrawHandle, SizeHandle};
+use crate::event::ManagerState;
 use crate::geom::{Coord, Rect, Size};
 use crate::{
     AlignHints,
     Direction::{Horizontal, Vertical},
-    Widget,
+    TextDirection, Widget, WidgetId,
 };
 
 /// A [`SizeRules`] solver for layouts
@@ -64,6 +67,195 @@ pub trait RulesSetter {
 
     /// Called once for each child. For most layouts the order is important.
     fn child_rect(&mut self, child_info: Self::ChildInfo) -> Rect;
+
+    /// Common baseline of children, if any
+    ///
+    /// This is relative to the top of each child's rect and should be passed
+    /// to children via [`AlignHints::baseline`] unless a vertical alignment
+    /// is specified.
+    fn baseline(&self) -> Option<u32> {
+        None
+    }
+}
+
+/// Get the [`SizeRules`] of a `child` widget, including margins
+///
+/// Hidden widgets have [`SizeRules::EMPTY`].
+///
+/// Layout implementations should use this in place of calling
+/// [`Layout::size_rules`] directly on children.
+///
+/// [`Layout::size_rules`]: crate::Layout::size_rules
+pub fn child_size_rules<W: Widget + ?Sized>(
+    child: &mut W,
+    size_handle: &mut dyn SizeHandle,
+    axis: AxisInfo,
+) -> SizeRules {
+    if !child.is_visible() {
+        return SizeRules::EMPTY;
+    }
+    let rules = child.size_rules(size_handle, axis);
+    match child.core_data().margins {
+        Some(m) => {
+            let first = axis.extract_size(m.first);
+            let outer = rules + first + axis.extract_size(m.last);
+            match rules.baseline() {
+                Some(baseline) => outer.with_baseline(baseline + first),
+                None => outer,
+            }
+        }
+        None => rules,
+    }
+}
+
+/// Set the `rect` of a `child` widget, excluding margins
+///
+/// Hidden widgets and their descendants are assigned an empty rect.
+///
+/// Layout implementations should use this in place of calling
+/// [`Layout::set_rect`] directly on children.
+///
+/// [`Layout::set_rect`]: crate::Layout::set_rect
+pub fn child_set_rect<W: Widget + ?Sized>(
+    child: &mut W,
+    size_handle: &mut dyn SizeHandle,
+    mut rect: Rect,
+    mut align: AlignHints,
+) {
+    if !child.is_visible() {
+        // Hidden widgets (including descendants) get an empty rect
+        let rect = Rect::new(rect.pos, Size::ZERO);
+        child.walk_mut(&mut |w| w.core_data_mut().rect = rect);
+        return;
+    }
+    if let Some(m) = child.core_data().margins {
+        let first = m.first.min(rect.size);
+        align.baseline = align.baseline.and_then(|b| b.checked_sub(first.1));
+        rect.pos += first;
+        rect.size -= first;
+        rect.size -= m.last.min(rect.size);
+    }
+    child.set_rect(size_handle, rect, align);
+}
+
+/// Draw a `child` widget, replaying cached geometry where possible
+///
+/// Hidden and fully transparent widgets are not drawn. The opacity of
+/// partially transparent widgets is applied to the draw device (see
+/// [`Draw::set_opacity`]) while drawing the widget and its children. The
+/// widget's style override (if any) is passed to the draw handle (see
+/// [`DrawHandle::set_style`]) while drawing the widget, but not its children.
+///
+/// Geometry of widgets without children may be cached by the draw device
+/// (see [`Draw::cache_begin`]). Cached geometry is replayed only while the
+/// widget's rect, draw offset and [`HighlightState`] are unchanged and the
+/// widget has not requested a redraw via [`Manager::redraw`] since the last
+/// frame. Widgets must therefore call [`Manager::redraw`] whenever their
+/// appearance changes for any other reason (e.g. new text).
+///
+/// Layout implementations should use this in place of calling
+/// [`Layout::draw`] directly on children.
+///
+/// [`Draw::cache_begin`]: crate::draw::Draw::cache_begin
+/// [`Draw::set_opacity`]: crate::draw::Draw::set_opacity
+/// [`HighlightState`]: crate::event::HighlightState
+/// [`Manager::redraw`]: crate::event::Manager::redraw
+/// [`Layout::draw`]: crate::Layout::draw
+pub fn child_draw<W: Widget + ?Sized>(
+    child: &W,
+    draw_handle: &mut dyn DrawHandle,
+    mgr: &ManagerState,
+) {
+    if !child.is_visible() || child.opacity() <= 0.0 {
+        return;
+    }
+
+    // A style override applies to the child only, not its descendants
+    let style = child.style();
+    let outer_style = draw_handle.style();
+    if style == outer_style {
+        return draw_faded(child, draw_handle, mgr);
+    }
+    draw_handle.set_style(style);
+    draw_faded(child, draw_handle, mgr);
+    draw_handle.set_style(outer_style);
+}
+
+fn draw_faded<W: Widget + ?Sized>(child: &W, draw_handle: &mut dyn DrawHandle, mgr: &ManagerState) {
+    let opacity = child.opacity();
+    if opacity >= 1.0 {
+        return draw_cached(child, draw_handle, mgr);
+    }
+
+    let outer = draw_handle.draw_device().2.opacity();
+    draw_handle.draw_device().2.set_opacity(outer * opacity);
+    draw_cached(child, draw_handle, mgr);
+    draw_handle.draw_device().2.set_opacity(outer);
+}
+
+fn draw_cached<W: Widget + ?Sized>(
+    child: &W,
+    draw_handle: &mut dyn DrawHandle,
+    mgr: &ManagerState,
+) {
+    if child.len() != 0 {
+        return child.draw(draw_handle, mgr);
+    }
+
+    let id = child.id();
+    let (region, offset, draw) = draw_handle.draw_device();
+    let key = draw_cache_key(child, offset, draw.opacity(), mgr);
+    if !draw.cache_begin(region, id, key, mgr.is_dirty(id)) {
+        child.draw(draw_handle, mgr);
+        draw_handle.draw_device().2.cache_end(id);
+    }
+}
+
+/// Find the widget at `coord` within a `child` widget
+///
+/// Returns `None` if `child` is hidden, is fully transparent and passes input
+/// through (see [`WidgetCore::set_pass_through`]) or `coord` is not within
+/// its rect, otherwise defers to [`Layout::find_id`].
+///
+/// Layout implementations should use this in place of calling
+/// [`Layout::find_id`] directly on children.
+///
+/// [`Layout::find_id`]: crate::Layout::find_id
+/// [`WidgetCore::set_pass_through`]: crate::WidgetCore::set_pass_through
+#[inline]
+pub fn child_find_id<W: Widget + ?Sized>(child: &W, coord: Coord) -> Option<WidgetId> {
+    if !child.is_visible() || !child.rect().contains(coord) {
+        return None;
+    }
+    if child.opacity() <= 0.0 && child.core_data().pass_through {
+        return None;
+    }
+    child.find_id(coord)
+}
+
+/// Key identifying the cached geometry of a widget
+pub(crate) fn draw_cache_key<W: Widget + ?Sized>(
+    child: &W,
+    offset: Coord,
+    opacity: f32,
+    mgr: &ManagerState,
+) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    let highlights = mgr.highlight_state(child.id());
+    (child.rect(), offset, opacity.to_bits(), highlights).hash(&mut hasher);
+    hasher.finish()
+}
+
+/// Mirror a child's `rect` horizontally within `outer` for right-to-left layout
+///
+/// Where `direction` is left-to-right, `rect` is returned unchanged.
+pub fn child_rect_dir(rect: Rect, outer: Rect, direction: TextDirection) -> Rect {
+    if !direction.is_rtl() {
+        return rect;
+    }
+    let mut rect = rect;
+    rect.pos.0 = 2 * outer.pos.0 + outer.size.0 as i32 - rect.pos.0 - rect.size.0 as i32;
+    rect
 }
 
 /// Solve `widget` for `SizeRules` on both axes, horizontal first.
@@ -73,6 +265,18 @@ pub fn solve<L: Widget>(
     widget: &mut L,
     size_handle: &mut dyn SizeHandle,
     size: Size,
+) -> (Size, Size) {
+    solve_with(widget, size_handle, size, AlignHints::NONE)
+}
+
+/// Solve `widget`, as with [`solve`], passing `align` to the widget
+///
+/// This allows, for example, a layout direction to be set.
+pub fn solve_with<L: Widget>(
+    widget: &mut L,
+    size_handle: &mut dyn SizeHandle,
+    size: Size,
+    align: AlignHints,
 ) -> (Size, Size) {
     // We call size_rules not because we want the result, but because our
     // spec requires that we do so before calling set_rect.
@@ -80,7 +284,7 @@ pub fn solve<L: Widget>(
     let h = widget.size_rules(size_handle, AxisInfo::new(Vertical, Some(size.0)));
 
     let pos = Coord(0, 0);
-    widget.set_rect(size_handle, Rect { pos, size }, AlignHints::NONE);
+    widget.set_rect(size_handle, Rect { pos, size }, align);
 
     trace!(
         "Layout solution for size={:?} has rules {:?}, {:?} and hierarchy:{}",
@@ -115,3 +319,72 @@ impl<'a> fmt::Display for WidgetHeirarchy<'a> {
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::event::{InputEvent, MouseButton};
+    use crate::testing::{Harness, RecordingHandle};
+    use crate::widget::{CheckBoxBare, Label};
+    use crate::WidgetCore;
+
+    #[test]
+    fn draw_cache_key() {
+        let mut widget = CheckBoxBare::new_on(|state| state);
+        let mut h = Harness::new(&mut widget);
+        h.state().draw_complete();
+
+        // Changes of rect, offset, opacity and highlight state change the key
+        let w = &mut widget;
+        let key0 = super::draw_cache_key(w, Coord::ZERO, 1.0, h.state());
+        let key = |w: &CheckBoxBare<_>, offset, opacity, h: &mut Harness| {
+            super::draw_cache_key(w, offset, opacity, h.state())
+        };
+        assert_eq!(key(w, Coord::ZERO, 1.0, &mut h), key0);
+        assert_ne!(key(w, Coord(0, 1), 1.0, &mut h), key0);
+        assert_ne!(key(w, Coord::ZERO, 0.5, &mut h), key0);
+        w.core_data_mut().rect = Rect::new(Coord::ZERO, Size(10, 10));
+        let key1 = key(w, Coord::ZERO, 1.0, &mut h);
+        assert_ne!(key1, key0);
+        let ev = InputEvent::MousePressed(MouseButton::Left);
+        let _ = h.manager().handle_synthetic(w, ev);
+        assert!(h.state().is_depressed(w.id()));
+        assert_ne!(key(w, Coord::ZERO, 1.0, &mut h), key1);
+    }
+
+    #[test]
+    fn draw_cache_replay() {
+        let mut label = Label::new("a");
+        let mut h = Harness::new(&mut label);
+        let id = label.id();
+        let mut draw = RecordingHandle::default();
+
+        // Widgets are dirty after configuration
+        child_draw(&label, &mut draw, h.state());
+        h.state().draw_complete();
+        assert_eq!(draw.items.len(), 1);
+        assert!(draw.draw.replayed.is_empty());
+
+        // An unchanged widget is replayed
+        child_draw(&label, &mut draw, h.state());
+        h.state().draw_complete();
+        assert_eq!(draw.items.len(), 1);
+        assert_eq!(draw.draw.replayed, [id]);
+
+        // Manager::redraw invalidates the cached geometry for one frame
+        h.manager().redraw(id);
+        child_draw(&label, &mut draw, h.state());
+        h.state().draw_complete();
+        assert_eq!(draw.items.len(), 2);
+        assert_eq!(draw.draw.replayed, [id]);
+        child_draw(&label, &mut draw, h.state());
+        assert_eq!(draw.items.len(), 2);
+        assert_eq!(draw.draw.replayed, [id, id]);
+
+        // As does a change of rect
+        label.core_data_mut().rect = Rect::new(Coord(1, 0), Size(10, 10));
+        child_draw(&label, &mut draw, h.state());
+        assert_eq!(draw.items.len(), 3);
+        assert_eq!(draw.draw.replayed, [id, id]);
+    }
+}
@@ -23,6 +23,10 @@ pub trait RowStorage: sealed::Sealed + Clone {
     fn as_mut(&mut self) -> &mut [SizeRules];
     #[doc(hidden)]
     fn set_len(&mut self, len: usize);
+    #[doc(hidden)]
+    fn baseline(&self) -> Option<u32>;
+    #[doc(hidden)]
+    fn set_baseline(&mut self, baseline: Option<u32>);
 }
 
 /// Fixed-length row storage
@@ -31,6 +35,7 @@ pub trait RowStorage: sealed::Sealed + Clone {
 #[derive(Clone, Debug, Default)]
 pub struct FixedRowStorage<S: Clone> {
     rules: S,
+    baseline: Option<u32>,
 }
 
 impl<S: Clone> Storage for FixedRowStorage<S> {}
@@ -48,12 +53,19 @@ where
     fn set_len(&mut self, len: usize) {
         assert_eq!(self.rules.as_ref().len(), len);
     }
+    fn baseline(&self) -> Option<u32> {
+        self.baseline
+    }
+    fn set_baseline(&mut self, baseline: Option<u32>) {
+        self.baseline = baseline;
+    }
 }
 
 /// Variable-length row storage
 #[derive(Clone, Debug, Default)]
 pub struct DynRowStorage {
     rules: Vec<SizeRules>,
+    baseline: Option<u32>,
 }
 
 impl Storage for DynRowStorage {}
@@ -68,6 +80,12 @@ impl RowStorage for DynRowStorage {
     fn set_len(&mut self, len: usize) {
         self.rules.resize(len, SizeRules::EMPTY);
     }
+    fn baseline(&self) -> Option<u32> {
+        self.baseline
+    }
+    fn set_baseline(&mut self, baseline: Option<u32>) {
+        self.baseline = baseline;
+    }
 }
 
 /// Temporary storage type.
@@ -26,10 +26,13 @@ extern crate self as kas; // required for reliable self-reference in kas_macros
 
 // internal modules:
 mod data;
+#[cfg(test)]
+mod testing;
 mod toolkit;
 mod traits;
 
 // public implementations:
+pub mod access;
 pub mod class;
 pub mod draw;
 pub mod event;
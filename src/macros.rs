@@ -21,11 +21,8 @@
 //! because procedural macros must be defined in a special crate. The
 //! `kas-macros` crate should not be used directly.
 //!
-//! Note further that these macros require gated functionality only available
-//! in nightly `rustc` builds:
-//! ```
-//! #![feature(proc_macro_hygiene)]
-//! ```
+//! These macros are usable with stable `rustc`. With the `nightly` feature,
+//! some additional warnings (e.g. about incorrect field types) are reported.
 //!
 //! [`make_widget`]: #the-make_widget-macro
 //! [`derive(Widget)`]: #the-derivewidget-macro
@@ -50,10 +47,10 @@
 //! use kas::event::{Handler, VoidMsg};
 //! use kas::{CoreData, LayoutData, Widget};
 //!
+//! #[derive(Clone, Debug, Widget)]
 //! #[widget]
 //! #[layout(single)]
 //! #[handler(generics = <> where W: Handler<Msg = VoidMsg>)]
-//! #[derive(Clone, Debug, Widget)]
 //! struct WrapperWidget<W: Widget> {
 //!     #[core] core: CoreData,
 //!     #[widget] child: W,
@@ -110,6 +107,9 @@
 //!     specified, this type defaults to [`kas::event::VoidMsg`]
 //! -   (optional) `substitutions = TUPLE` — a tuple of subsitutions for type
 //!     generics, for example: `(T1 = MyType, T2 = some::other::Type)`
+//! -   (optional) `event = f` — the name `f` of a method implementing
+//!     [`Handler::handle_event`] with signature
+//!     `fn f(&mut self, mgr: &mut Manager, event: Event) -> Response<Msg>`
 //! -   (optional): `generics = < X, Y, ... > where CONDS`
 //!     (`where CONDS` is optional, and if present must be the last argument)
 //!
@@ -163,8 +163,20 @@
 //!
 //! -   `handler = f` — the name `f` of a utility method defined on this type
 //!
-//! If there is no `handler` parameter, the child widget's [`Handler::Msg`] type
-//! should convert into the parent's [`Handler::Msg`] type via `From`.
+//! For trivial conversions, a mapping may be given instead of a handler
+//! method. This is an expression (usually a closure or an enum variant)
+//! converting the child's message to the parent's [`Handler::Msg`] type.
+//! Other responses are passed up unchanged, except that events not handled
+//! by the child are passed to the parent's [`Handler::handle_event`].
+//! Within [`make_widget`] where the child's type is not given, the closure
+//! argument should be typed (or the child's message type specified with
+//! `-> M`):
+//!
+//! -   `map = |msg: M| ...` — convert the child's message
+//!
+//! If there is neither a `handler` nor a `map` parameter, the child widget's
+//! [`Handler::Msg`] type should convert into the parent's [`Handler::Msg`]
+//! type via `From`.
 //!
 //!
 //! ### Examples
@@ -181,10 +193,10 @@
 //! #[derive(Debug)]
 //! enum ChildMessage { A }
 //!
+//! #[derive(Debug, Widget)]
 //! #[widget]
 //! #[layout(vertical)]
 //! #[handler(generics = <> where W: Handler<Msg = ChildMessage>)]
-//! #[derive(Debug, Widget)]
 //! struct MyWidget<W: Widget> {
 //!     #[core] core: CoreData,
 //!     #[layout_data] layout_data: <Self as LayoutData>::Data,
@@ -214,7 +226,6 @@
 //! identifiers omitted. It's easiest to study an example:
 //!
 //! ```rust
-//! # #![feature(proc_macro_hygiene)]
 //! # use kas::event::{VoidResponse, VoidMsg, Manager};
 //! # use kas::macros::make_widget;
 //! # use kas::widget::Label;
@@ -276,6 +287,43 @@
 //! #[widget] display: impl HasText = EditBox::new("editable"),
 //! ```
 //!
+//! ### Generated type and accessors
+//!
+//! The macro expands to a block defining a struct type, `AnonWidget`, and
+//! evaluating to an instance of this type. Fields with a name are real fields
+//! of this struct, accessible as `self.name` within `impl` blocks and as
+//! `widget.name` within the module invoking the macro.
+//!
+//! Additionally, each named child widget `name` has accessor methods
+//! `fn name(&self) -> &T` and `fn name_mut(&mut self) -> &mut T`, where `T` is
+//! the field's type (possibly a generic parameter, as above). These are
+//! omitted where an `impl` block already defines a method of the same name.
+//! Anonymous (`_`) children have no accessors.
+//!
+//! The type name is only visible within the macro expansion. To store the
+//! widget elsewhere, use a generic type parameter (e.g. `W: Widget`), `impl`
+//! trait syntax for function return types, or a box:
+//!
+//! ```
+//! # use kas::event::{Handler, VoidMsg};
+//! # use kas::macros::make_widget;
+//! # use kas::widget::Label;
+//! # use kas::class::HasText;
+//! fn make_display() -> Box<dyn Handler<Msg = VoidMsg>> {
+//!     let display = make_widget! {
+//!         #[widget]
+//!         #[layout(single)]
+//!         #[handler(msg = VoidMsg)]
+//!         struct {
+//!             #[widget] label: Label = Label::new("0"),
+//!         }
+//!     };
+//!     assert_eq!(display.label().get_text(), "0");
+//!     Box::new(display)
+//! }
+//! # make_display();
+//! ```
+//!
 //! ### Implementations
 //!
 //! Now, back to the example above, we see attributes and an `impl` block:
@@ -309,11 +357,9 @@
 //! impl Trait { ... }
 //! ```
 //!
-//! ### Example
+//! ### Examples
 //!
 //! ```
-//! #![feature(proc_macro_hygiene)]
-//!
 //! use kas::macros::{make_widget};
 //! use kas::widget::TextButton;
 //!
@@ -334,6 +380,30 @@
 //! };
 //! ```
 //!
+//! Messages from children may be mapped instead of requiring a common type:
+//!
+//! ```
+//! use kas::macros::make_widget;
+//! use kas::widget::{CheckBox, TextButton};
+//!
+//! #[derive(Clone, Debug)]
+//! enum Form {
+//!     Submit,
+//!     Agree(bool),
+//! }
+//!
+//! let form = make_widget! {
+//!     #[widget]
+//!     #[layout(vertical)]
+//!     #[handler(msg = Form)]
+//!     struct {
+//!         #[widget(map = |agree: bool| Form::Agree(agree))] _ =
+//!             CheckBox::new_on(|state| state, "I agree"),
+//!         #[widget(map = |_: ()| Form::Submit)] _ = TextButton::new("Submit", ()),
+//!     }
+//! };
+//! ```
+//!
 //!
 //! ## The `derive(VoidMsg)` macro
 //!
@@ -358,5 +428,75 @@
 //! [`LayoutData`]: crate::LayoutData
 //! [`Handler`]: crate::event::Handler
 //! [`Handler::Msg`]: crate::event::Handler::Msg
+//! [`Handler::handle_event`]: crate::event::Handler::handle_event
 
 pub use kas_macros::{make_widget, VoidMsg, Widget};
+
+#[cfg(test)]
+mod test {
+    use super::make_widget;
+    use crate::event::{Action, Event, Handler, Response, VoidMsg};
+    use crate::geom::{Coord, Rect, Size};
+    use crate::testing::{self, Harness};
+    use crate::widget::{Filler, TextButton};
+    use crate::{Layout, WidgetCore};
+
+    #[test]
+    fn accessors_named_like_core_methods() {
+        // Accessors `id()` and `rect()` must not affect generated code
+        let mut widget = make_widget! {
+            #[widget]
+            #[layout(horizontal, frame)]
+            #[handler(msg = VoidMsg)]
+            struct {
+                #[widget] id: Filler = Filler::new(),
+                #[widget] rect: Filler = Filler::new(),
+            }
+        };
+        let _ = Harness::new(&mut widget);
+        testing::solve(&mut widget, Size(20, 10));
+
+        let rect = Rect::new(Coord::ZERO, Size(20, 10));
+        assert_eq!(WidgetCore::rect(&widget), rect);
+        let first = WidgetCore::rect(widget.id());
+        assert_eq!(first.pos, Coord::ZERO);
+        let id = widget.find_id(Coord::ZERO);
+        assert_eq!(id, Some(WidgetCore::id(widget.id())));
+        assert_eq!(widget.find_id(Coord(30, 5)), None);
+    }
+
+    #[test]
+    fn map_child_messages() {
+        #[derive(Clone, Debug)]
+        enum Msg {
+            Mapped(u32),
+            Plain,
+        }
+
+        let mut widget = make_widget! {
+            #[widget]
+            #[layout(horizontal)]
+            #[handler(msg = Msg)]
+            struct {
+                #[widget(map = |n: u32| Msg::Mapped(n))] mapped = TextButton::new("a", 7u32),
+                #[widget] unmapped = TextButton::new("b", Msg::Plain),
+            }
+        };
+        let mut h = Harness::new(&mut widget);
+        let mut mgr = h.manager();
+
+        let id = widget.mapped.id();
+        let r = widget.handle(&mut mgr, id, Event::Action(Action::Activate));
+        assert!(matches!(r, Response::Msg(Msg::Mapped(7))));
+
+        // Messages of children without a mapping bubble up unchanged
+        let id = widget.unmapped.id();
+        let r = widget.handle(&mut mgr, id, Event::Action(Action::Activate));
+        assert!(matches!(r, Response::Msg(Msg::Plain)));
+
+        // Other responses pass through
+        let id = widget.mapped.id();
+        let r = widget.handle(&mut mgr, id, Event::Action(Action::ReceivedCharacter('x')));
+        assert!(matches!(r, Response::Unhandled(_)));
+    }
+}
@@ -0,0 +1,256 @@
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License in the LICENSE-APACHE file or at:
+//     https://www.apache.org/licenses/LICENSE-2.0
+
+//! Test harness: widgets without a toolkit
+
+use std::any::Any;
+use std::collections::HashMap;
+use std::num::NonZeroU32;
+
+use crate::draw::{Colour, Draw, DrawHandle, Region, SizeHandle, TextClass, WidgetStyle};
+use crate::event::{CursorIcon, HighlightState, Manager, ManagerState, UpdateHandle};
+use crate::geom::{Coord, Rect, Size};
+use crate::layout::{self, AxisInfo, SizeRules};
+use crate::{Align, Direction, ThemeAction, ThemeApi, TkWindow, Widget, WidgetId, WindowId};
+
+/// A toolkit window which does nothing
+pub struct NullWindow;
+
+impl TkWindow for NullWindow {
+    fn add_window(&mut self, _: Box<dyn crate::Window>) -> WindowId {
+        WindowId::new(NonZeroU32::new(1).unwrap())
+    }
+    fn add_modal(&mut self, _: Box<dyn crate::Window>) -> WindowId {
+        WindowId::new(NonZeroU32::new(1).unwrap())
+    }
+    fn close_window(&mut self, _: WindowId) {}
+    fn trigger_update(&mut self, _: UpdateHandle, _: u64) {}
+    fn get_clipboard(&mut self) -> Option<String> {
+        None
+    }
+    fn set_clipboard(&mut self, _: String) {}
+    fn adjust_theme(&mut self, _: &mut dyn FnMut(&mut dyn ThemeApi) -> ThemeAction) {}
+    fn set_cursor_icon(&mut self, _: CursorIcon) {}
+    fn set_ime_position(&mut self, _: Coord) {}
+}
+
+/// A size handle: all sizes are zero, except scrollbar width
+pub struct NullSizeHandle;
+
+impl SizeHandle for NullSizeHandle {
+    fn outer_frame(&self) -> (Size, Size) {
+        (Size::ZERO, Size::ZERO)
+    }
+    fn inner_margin(&self) -> Size {
+        Size::ZERO
+    }
+    fn outer_margin(&self) -> Size {
+        Size::ZERO
+    }
+    fn line_height(&self, _: TextClass) -> u32 {
+        0
+    }
+    fn measure_text(&mut self, _: &str, _: TextClass, _: Option<u32>) -> Size {
+        Size::ZERO
+    }
+    fn text_bound(&mut self, _: &str, _: TextClass, _: AxisInfo) -> SizeRules {
+        SizeRules::EMPTY
+    }
+    fn button_surround(&self) -> (Size, Size) {
+        (Size::ZERO, Size::ZERO)
+    }
+    fn edit_surround(&self) -> (Size, Size) {
+        (Size::ZERO, Size::ZERO)
+    }
+    fn checkbox(&self) -> Size {
+        Size::ZERO
+    }
+    fn radiobox(&self) -> Size {
+        Size::ZERO
+    }
+    fn scrollbar(&self) -> (u32, u32, u32) {
+        (4, 0, 0)
+    }
+}
+
+/// A size handle for text: each char is 8×20 with a baseline at 15
+///
+/// Edit boxes have a frame of 2 at each side, 5 above and 1 below; all other
+/// sizes are zero.
+pub struct TextSizeHandle;
+
+impl SizeHandle for TextSizeHandle {
+    fn outer_frame(&self) -> (Size, Size) {
+        (Size::ZERO, Size::ZERO)
+    }
+    fn inner_margin(&self) -> Size {
+        Size::ZERO
+    }
+    fn outer_margin(&self) -> Size {
+        Size::ZERO
+    }
+    fn line_height(&self, _: TextClass) -> u32 {
+        20
+    }
+    fn measure_text(&mut self, text: &str, _: TextClass, _: Option<u32>) -> Size {
+        Size(8 * text.chars().count() as u32, 20)
+    }
+    fn text_bound(&mut self, text: &str, _: TextClass, axis: AxisInfo) -> SizeRules {
+        match axis.is_horizontal() {
+            true => SizeRules::fixed(8 * text.chars().count() as u32),
+            false => SizeRules::fixed(20).with_baseline(15),
+        }
+    }
+    fn button_surround(&self) -> (Size, Size) {
+        (Size::ZERO, Size::ZERO)
+    }
+    fn edit_surround(&self) -> (Size, Size) {
+        (Size(2, 5), Size(2, 1))
+    }
+    fn checkbox(&self) -> Size {
+        Size::ZERO
+    }
+    fn radiobox(&self) -> Size {
+        Size::ZERO
+    }
+    fn scrollbar(&self) -> (u32, u32, u32) {
+        (0, 0, 0)
+    }
+}
+
+/// A draw device which draws nothing, but caches widget geometry
+///
+/// Geometry of a widget is replayed (see [`Draw::cache_begin`]) where its key
+/// is unchanged since it was last drawn and it is not dirty.
+#[derive(Default)]
+pub struct CacheDraw {
+    keys: HashMap<WidgetId, u64>,
+    /// Each widget whose geometry was replayed
+    pub replayed: Vec<WidgetId>,
+}
+
+impl Draw for CacheDraw {
+    fn as_any_mut(&mut self) -> &mut dyn Any {
+        self
+    }
+    fn add_clip_region(&mut self, _: Region, _: Rect, _: Option<Colour>) -> Region {
+        Region::default()
+    }
+    fn rect(&mut self, _: Region, _: Rect, _: Colour) {}
+    fn frame(&mut self, _: Region, _: Rect, _: Rect, _: Colour) {}
+    fn cache_begin(&mut self, _: Region, id: WidgetId, key: u64, dirty: bool) -> bool {
+        if !dirty && self.keys.get(&id) == Some(&key) {
+            self.replayed.push(id);
+            return true;
+        }
+        self.keys.insert(id, key);
+        false
+    }
+}
+
+/// A draw handle recording the elements drawn
+///
+/// The target is a large rect at the origin. Clip regions are not recorded:
+/// their contents are drawn directly. Widgets whose geometry is replayed by
+/// the draw device are not drawn, thus not recorded.
+#[derive(Default)]
+pub struct RecordingHandle {
+    /// The draw device
+    pub draw: CacheDraw,
+    style: WidgetStyle,
+    /// Each element drawn: the element name, its rect and the style in use
+    pub items: Vec<(&'static str, Rect, WidgetStyle)>,
+    /// Each text drawn: its rect, text and alignment
+    pub text: Vec<(Rect, String, (Align, Align))>,
+}
+
+impl RecordingHandle {
+    fn push(&mut self, name: &'static str, rect: Rect) {
+        self.items.push((name, rect, self.style));
+    }
+}
+
+impl DrawHandle for RecordingHandle {
+    fn draw_device(&mut self) -> (Region, Coord, &mut dyn Draw) {
+        (Region::default(), Coord::ZERO, &mut self.draw)
+    }
+    fn clip_region(&mut self, _: Rect, _: Coord, f: &mut dyn FnMut(&mut dyn DrawHandle)) {
+        f(self);
+    }
+    fn target_rect(&self) -> Rect {
+        Rect::new(Coord::ZERO, Size(1 << 16, 1 << 16))
+    }
+    fn outer_frame(&mut self, rect: Rect) {
+        self.push("outer_frame", rect);
+    }
+    fn text(&mut self, rect: Rect, text: &str, _: TextClass, align: (Align, Align)) {
+        self.push("text", rect);
+        self.text.push((rect, text.to_string(), align));
+    }
+    fn button(&mut self, rect: Rect, _: HighlightState) {
+        self.push("button", rect);
+    }
+    fn edit_box(&mut self, rect: Rect, _: HighlightState) {
+        self.push("edit_box", rect);
+    }
+    fn checkbox(&mut self, rect: Rect, _: bool, _: HighlightState) {
+        self.push("checkbox", rect);
+    }
+    fn radiobox(&mut self, rect: Rect, _: bool, _: HighlightState) {
+        self.push("radiobox", rect);
+    }
+    fn scrollbar(&mut self, rect: Rect, _: Rect, _: Direction, _: HighlightState) {
+        self.push("scrollbar", rect);
+    }
+    fn style(&self) -> WidgetStyle {
+        self.style
+    }
+    fn set_style(&mut self, style: WidgetStyle) {
+        self.style = style;
+    }
+}
+
+/// Event-manager state for a widget tree, with a [`NullWindow`]
+pub struct Harness {
+    tkw: NullWindow,
+    state: ManagerState,
+}
+
+impl Harness {
+    /// Construct (DPI factor 1) and configure `widget`
+    pub fn new<W: Widget + ?Sized>(widget: &mut W) -> Self {
+        Harness::with_dpi(1.0, widget)
+    }
+
+    /// Construct with the given DPI factor and configure `widget`
+    pub fn with_dpi<W: Widget + ?Sized>(dpi_factor: f64, widget: &mut W) -> Self {
+        let mut harness = Harness {
+            tkw: NullWindow,
+            state: ManagerState::new(dpi_factor),
+        };
+        harness.configure(widget);
+        harness
+    }
+
+    /// Configure `widget` (again)
+    pub fn configure<W: Widget + ?Sized>(&mut self, widget: &mut W) {
+        self.state.configure(&mut self.tkw, widget);
+    }
+
+    /// Access the event manager
+    pub fn manager(&mut self) -> Manager<'_> {
+        self.state.manager(&mut self.tkw)
+    }
+
+    /// Access the manager state
+    pub fn state(&mut self) -> &mut ManagerState {
+        &mut self.state
+    }
+}
+
+/// Solve layout of `widget` for `size`, using a [`NullSizeHandle`]
+pub fn solve<W: Widget>(widget: &mut W, size: Size) {
+    layout::solve(widget, &mut NullSizeHandle, size);
+}
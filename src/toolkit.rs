@@ -16,7 +16,9 @@
 
 use std::num::NonZeroU32;
 
+use crate::access::AccessEvent;
 use crate::event::{CursorIcon, UpdateHandle};
+use crate::geom::Coord;
 use crate::{ThemeAction, ThemeApi};
 
 /// Identifier for a window added to a toolkit
@@ -36,18 +38,27 @@ impl WindowId {
 }
 
 /// Toolkit actions needed after event handling, if any.
+///
+/// Actions are totally ordered by significance, as given by their explicit
+/// discriminants. Each action implies all actions of lower significance
+/// (except that [`TkAction::RequestClose`] may be vetoed, in which case the
+/// actions it subsumed are applied instead). Pending actions are therefore
+/// merged via `max` (see [`Manager::send_action`]): the most significant
+/// action wins and nothing is lost.
+///
+/// [`Manager::send_action`]: crate::event::Manager::send_action
 #[must_use]
 #[derive(Debug, Clone, Copy, Eq, PartialEq, Ord, PartialOrd)]
 pub enum TkAction {
     /// No action needed
-    None,
+    None = 0,
     /// Whole window requires redrawing
     ///
     /// Note that [`Manager::redraw`] can instead be used for more selective
     /// redrawing, if supported by the toolkit.
     ///
     /// [`Manager::redraw`]: crate::event::Manager::redraw
-    Redraw,
+    Redraw = 1,
     /// Some widgets within a region moved
     ///
     /// This action should be emitted when e.g. a scroll-region is moved or
@@ -56,7 +67,7 @@ pub enum TkAction {
     ///
     /// This implies that a redraw is required.
     // NOTE: one could specify a Rect here, but there's not much advantage
-    RegionMoved,
+    RegionMoved = 2,
     /// Whole window requires reconfiguring (implies redrawing)
     ///
     /// *Configuring* widgets assigns [`WidgetId`] identifiers, updates
@@ -64,11 +75,23 @@ pub enum TkAction {
     ///
     /// [`WidgetId`]: crate::WidgetId
     /// [`event::Manager`]: crate::event::Manager
-    Reconfigure,
-    /// Window should be closed
-    Close,
+    Reconfigure = 3,
+    /// Window should be closed, unless vetoed
+    ///
+    /// The toolkit calls [`Window::on_close`], which may veto closure (e.g. to
+    /// ask the user for confirmation). This is the action sent when the user
+    /// asks to close the window via the window manager.
+    ///
+    /// [`Window::on_close`]: crate::Window::on_close
+    RequestClose = 4,
+    /// Window should be closed unconditionally
+    ///
+    /// [`Window::on_close`] is not called.
+    ///
+    /// [`Window::on_close`]: crate::Window::on_close
+    Close = 5,
     /// All windows should close (toolkit exit)
-    CloseAll,
+    CloseAll = 6,
 }
 
 /// Toolkit-specific window management and style interface.
@@ -85,6 +108,12 @@ pub trait TkWindow {
     /// processing, albeit without error handling.
     fn add_window(&mut self, widget: Box<dyn kas::Window>) -> WindowId;
 
+    /// Add a modal window
+    ///
+    /// The new window is modal over the current window: the current window
+    /// ignores input until the new window is closed.
+    fn add_modal(&mut self, widget: Box<dyn kas::Window>) -> WindowId;
+
     /// Close a window
     fn close_window(&mut self, id: WindowId);
 
@@ -108,6 +137,21 @@ pub trait TkWindow {
 
     /// Set the mouse cursor
     fn set_cursor_icon(&mut self, icon: CursorIcon);
+
+    /// Set the position of the IME candidate window
+    fn set_ime_position(&mut self, coord: Coord);
+
+    /// Notify the platform accessibility adapter of a change
+    ///
+    /// Toolkits without accessibility support may ignore this.
+    fn access_notify(&mut self, _event: AccessEvent) {}
+
+    /// Override the window's DPI factor
+    ///
+    /// When `Some`, the given factor is used instead of that reported by the
+    /// windowing system; `None` restores the reported factor. The window is
+    /// resized and redrawn. Toolkits may ignore this.
+    fn set_dpi_override(&mut self, _factor: Option<f64>) {}
 }
 
 #[cfg(test)]
@@ -118,7 +162,34 @@ mod test {
     fn action_precedence() {
         assert!(TkAction::None < TkAction::Redraw);
         assert!(TkAction::Redraw < TkAction::Reconfigure);
-        assert!(TkAction::Reconfigure < TkAction::Close);
+        assert!(TkAction::Reconfigure < TkAction::RequestClose);
+        assert!(TkAction::RequestClose < TkAction::Close);
         assert!(TkAction::Close < TkAction::CloseAll);
     }
+
+    #[test]
+    fn action_merge() {
+        use TkAction::*;
+        // In order of increasing significance
+        let actions = [
+            None,
+            Redraw,
+            RegionMoved,
+            Reconfigure,
+            RequestClose,
+            Close,
+            CloseAll,
+        ];
+        for (i, a) in actions.iter().enumerate() {
+            assert_eq!(*a as usize, i);
+        }
+
+        assert_eq!(Close.max(Redraw), Close);
+        assert_eq!(Redraw.max(Close), Close);
+        assert_eq!(RegionMoved.max(Redraw), RegionMoved);
+        assert_eq!(Reconfigure.max(RegionMoved), Reconfigure);
+        assert_eq!(RequestClose.max(Reconfigure), RequestClose);
+        assert_eq!(RequestClose.max(Close), Close);
+        assert_eq!(CloseAll.max(Close), CloseAll);
+    }
 }
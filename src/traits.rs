@@ -9,11 +9,12 @@ use std::fmt;
 use std::ops::DerefMut;
 use std::time::Duration;
 
-use crate::draw::{DrawHandle, SizeHandle};
+use crate::access::{AccessNode, Role};
+use crate::draw::{DrawHandle, SizeHandle, WidgetStyle};
 use crate::event::{Callback, CursorIcon, Handler, Manager, ManagerState, UpdateHandle, VoidMsg};
 use crate::geom::{Coord, Rect, Size};
-use crate::layout::{self, AxisInfo, SizeRules};
-use crate::{AlignHints, CoreData, WidgetId};
+use crate::layout::{self, AxisInfo, Margins, SizeRules};
+use crate::{AlignHints, CoreData, TkAction, WidgetId};
 
 /// Support trait for cloning boxed unsized objects
 #[cfg_attr(not(feature = "internal_doc"), doc(hidden))]
@@ -55,6 +56,87 @@ pub trait WidgetCore: fmt::Debug {
         self.core_data().rect
     }
 
+    /// Get whether the widget is visible
+    ///
+    /// Note that a widget may also be hidden due to a hidden parent.
+    #[inline]
+    fn is_visible(&self) -> bool {
+        !self.core_data().hidden
+    }
+
+    /// Show or hide the widget
+    ///
+    /// Hidden widgets remain in the widget tree but take no space in the
+    /// parent's layout, are not drawn and do not receive input via
+    /// coordinate-based hit-testing or keyboard navigation. This differs from
+    /// a disabled widget, which remains visible.
+    ///
+    /// Triggers a [reconfigure action](Manager::send_action) if the visibility
+    /// changes.
+    fn set_visible(&mut self, mgr: &mut Manager, visible: bool) {
+        if self.core_data().hidden == visible {
+            self.core_data_mut().hidden = !visible;
+            mgr.send_action(TkAction::Reconfigure);
+        }
+    }
+
+    /// Get the widget's opacity
+    ///
+    /// This is in the range `[0, 1]` where 1 (the default) is opaque.
+    #[inline]
+    fn opacity(&self) -> f32 {
+        self.core_data().opacity
+    }
+
+    /// Set the widget's opacity
+    ///
+    /// The opacity applies to everything drawn by the widget and its
+    /// children, multiplying the alpha component of colours, and may be
+    /// animated by calling this repeatedly (e.g. from
+    /// [`Widget::update_timer`]). Fully transparent widgets (`opacity == 0`)
+    /// are not drawn at all; see also [`WidgetCore::set_pass_through`].
+    ///
+    /// The value is clamped to `[0, 1]` (NaN is treated as 0). Requests a
+    /// redraw if the opacity changes.
+    fn set_opacity(&mut self, mgr: &mut Manager, opacity: f32) {
+        let opacity = if opacity.is_nan() {
+            0.0
+        } else {
+            opacity.clamp(0.0, 1.0)
+        };
+        if self.core_data().opacity != opacity {
+            self.core_data_mut().opacity = opacity;
+            mgr.redraw(self.id());
+        }
+    }
+
+    /// Set whether input passes through the widget while fully transparent
+    ///
+    /// By default (`false`), a widget with an opacity of zero is not drawn but
+    /// otherwise behaves normally, including receiving mouse and touch input.
+    /// If `true`, such a widget is skipped by coordinate-based hit-testing
+    /// (thus input reaches widgets beneath it).
+    #[inline]
+    fn set_pass_through(&mut self, pass_through: bool) {
+        self.core_data_mut().pass_through = pass_through;
+    }
+
+    /// Get the widget's style override
+    #[inline]
+    fn style(&self) -> WidgetStyle {
+        self.core_data().style
+    }
+
+    /// Set the widget's style override
+    ///
+    /// See [`Widget::with_style`]. Requests a redraw if the style changes.
+    fn set_style(&mut self, mgr: &mut Manager, style: WidgetStyle) {
+        if self.core_data().style != style {
+            self.core_data_mut().style = style;
+            mgr.redraw(self.id());
+        }
+    }
+
     /// Get the name of the widget struct
     fn widget_name(&self) -> &'static str;
 
@@ -193,6 +275,10 @@ pub trait Layout: WidgetCore {
     /// (same behaviour as with events addressed by coordinate).
     /// The only case `None` should be expected is when `coord` is outside the
     /// initial widget's region; however this is not guaranteed.
+    ///
+    /// Implementations should test children via [`layout::child_find_id`],
+    /// testing the top-most (last drawn) children first. To hit-test a widget
+    /// tree, use [`Widget::find_at`] instead.
     #[inline]
     fn find_id(&self, _coord: Coord) -> Option<WidgetId> {
         Some(self.id())
@@ -221,9 +307,9 @@ pub trait Layout: WidgetCore {
 /// use kas::macros::Widget;
 /// use kas::{CoreData, LayoutData, Widget};
 ///
+/// #[derive(Clone, Debug, Widget)]
 /// #[widget]
 /// #[layout(single, frame)]
-/// #[derive(Clone, Debug, Widget)]
 /// pub struct Frame<W: Widget> {
 ///     #[core] core: CoreData,
 ///     #[layout_data] layout_data: <Self as LayoutData>::Data,
@@ -265,22 +351,103 @@ pub trait Widget: Layout {
     /// A user-defined payload is passed. Interpretation of this payload is
     /// user-defined and unfortunately not type safe.
     ///
-    /// This method being called does not imply a redraw.
+    /// After this method is called, the widget's handler additionally receives
+    /// [`Action::Update`], allowing a message to be emitted. The widget is not
+    /// redrawn unless it requests this, e.g. via [`Manager::redraw`].
+    ///
+    /// [`Action::Update`]: crate::event::Action::Update
     ///
     /// [`update_on_handle`]: Manager::update_on_handle
     fn update_handle(&mut self, _mgr: &mut Manager, _handle: UpdateHandle, _payload: u64) {}
 
+    /// Find the widget at a coordinate
+    ///
+    /// Returns the top-most visible widget at `coord`, or `None` if `coord`
+    /// is outside of `self` or `self` is hidden. Where widgets overlap (e.g.
+    /// within a [`Stack`]), the last drawn wins. Coordinates of widgets
+    /// within a scroll region are translated by the scroll offset.
+    ///
+    /// This is the same traversal used to target events from coordinate
+    /// sources (mouse pointer, touch events), except that the event manager
+    /// calls [`Layout::find_id`] on the window directly, thus events outside
+    /// of the window's rect are still sent to the window. This method should
+    /// not be overridden.
+    ///
+    /// [`Stack`]: crate::widget::Stack
+    fn find_at(&self, coord: Coord) -> Option<WidgetId> {
+        layout::child_find_id(self, coord)
+    }
+
     /// Is this widget navigable via Tab key?
     fn allow_focus(&self) -> bool {
         false
     }
 
+    /// Should this widget receive keyboard focus when the window opens?
+    ///
+    /// This is only respected where [`Widget::allow_focus`] also returns
+    /// true. If multiple widgets request autofocus, the first (in id order)
+    /// is used. Focus set during [`Widget::configure`] or retained from a
+    /// previous configuration takes precedence.
+    fn autofocus(&self) -> bool {
+        false
+    }
+
+    /// Describe this widget for assistive technologies
+    ///
+    /// This should set the role and, where applicable, the name, value and
+    /// checked state; focus, visibility and children are determined by
+    /// [`AccessNode::build`]. The default implementation reports a
+    /// [`Role::Group`] for widgets with children and [`Role::Unknown`]
+    /// otherwise.
+    ///
+    /// Widgets should call [`Manager::access_notify`] when any reported
+    /// property changes.
+    fn access_node(&self) -> AccessNode {
+        let role = if self.len() > 0 {
+            Role::Group
+        } else {
+            Role::Unknown
+        };
+        AccessNode::new(self.id(), role)
+    }
+
     /// Which cursor icon should be used on hover?
     ///
     /// Where no specific icon should be used, return [`CursorIcon::Default`].
+    /// Disabled widgets should also return [`CursorIcon::Default`].
+    ///
+    /// The icon is updated when the hovered widget changes, and only if it
+    /// differs from the current icon.
     fn cursor_icon(&self) -> CursorIcon {
         CursorIcon::Default
     }
+    /// Set margins around this widget
+    ///
+    /// By default, widgets have no margins of their own. When set, the margins
+    /// are added to the widget's [`SizeRules`] by the parent's layout solver
+    /// and excluded from the widget's [`Rect`]. The `inter` field is ignored.
+    fn with_margins(mut self, margins: Margins) -> Self
+    where
+        Self: Sized,
+    {
+        self.core_data_mut().margins = Some(margins);
+        self
+    }
+
+    /// Set a style override for this widget
+    ///
+    /// This adjusts the appearance of this widget only (not its children),
+    /// for example to draw a button as the primary action. The theme consults
+    /// the style when drawing (see [`DrawHandle::set_style`]); hints not
+    /// supported by the theme are ignored.
+    fn with_style(mut self, style: WidgetStyle) -> Self
+    where
+        Self: Sized,
+    {
+        self.core_data_mut().style = style;
+        self
+    }
 }
 
 /// Trait to describe the type needed by the layout implementation.
@@ -329,6 +496,17 @@ pub trait Window: Widget + Handler<Msg = VoidMsg> {
 
     /// Trigger a callback (see `iter_callbacks`).
     fn trigger_callback(&mut self, index: usize, mgr: &mut Manager);
+
+    /// Handle a request to close the window
+    ///
+    /// This is called on [`TkAction::RequestClose`]. Return `false` to veto
+    /// closure; the window may then e.g. open a confirmation dialog, and
+    /// close itself later via [`TkAction::Close`].
+    ///
+    /// The default implementation returns `true`.
+    fn on_close(&mut self, _mgr: &mut Manager) -> bool {
+        true
+    }
 }
 
 /// Return value of [`ThemeApi`] functions
@@ -374,6 +552,15 @@ pub trait ThemeApi {
     fn set_theme(&mut self, _theme: &str) -> ThemeAction {
         ThemeAction::None
     }
+
+    /// Enable or disable high-contrast mode
+    ///
+    /// While enabled, the theme should use a high-contrast colour scheme;
+    /// disabling restores the previous scheme. Themes without high-contrast
+    /// support may do nothing.
+    fn set_high_contrast(&mut self, _high_contrast: bool) -> ThemeAction {
+        ThemeAction::None
+    }
 }
 
 impl<T: ThemeApi> ThemeApi for Box<T> {
@@ -386,4 +573,39 @@ impl<T: ThemeApi> ThemeApi for Box<T> {
     fn set_theme(&mut self, theme: &str) -> ThemeAction {
         self.deref_mut().set_theme(theme)
     }
+    fn set_high_contrast(&mut self, high_contrast: bool) -> ThemeAction {
+        self.deref_mut().set_high_contrast(high_contrast)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::testing::Harness;
+    use crate::widget::Label;
+
+    #[test]
+    fn set_opacity() {
+        let mut widget = Label::new("a");
+        let mut h = Harness::new(&mut widget);
+        let id = widget.id();
+        h.state().draw_complete();
+
+        // Unchanged opacity does not request a redraw
+        let mut mgr = h.manager();
+        widget.set_opacity(&mut mgr, 1.0);
+        assert_eq!(mgr.unwrap_action(), TkAction::None);
+        assert!(!h.state().is_dirty(id));
+
+        // Opacity is clamped and changes request a redraw of the widget
+        let mut mgr = h.manager();
+        widget.set_opacity(&mut mgr, -0.5);
+        assert_eq!(widget.opacity(), 0.0);
+        assert_eq!(mgr.unwrap_action(), TkAction::Redraw);
+        assert!(h.state().is_dirty(id));
+        widget.set_opacity(&mut h.manager(), 2.0);
+        assert_eq!(widget.opacity(), 1.0);
+        widget.set_opacity(&mut h.manager(), f32::NAN);
+        assert_eq!(widget.opacity(), 0.0);
+    }
 }
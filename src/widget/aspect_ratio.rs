@@ -0,0 +1,142 @@
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License in the LICENSE-APACHE file or at:
+//     https://www.apache.org/licenses/LICENSE-2.0
+
+//! Aspect-ratio preserving wrapper
+
+use crate::draw::{DrawHandle, SizeHandle};
+use crate::event::{Event, Handler, Manager, ManagerState, Response};
+use crate::geom::{Coord, Rect, Size};
+use crate::layout::{self, AxisInfo, SizeRules};
+use crate::macros::Widget;
+use crate::{AlignHints, CoreData, Layout, Widget, WidgetCore, WidgetId};
+
+/// A wrapper keeping its child at a fixed aspect ratio
+///
+/// The child is assigned the largest [`Rect`] of the given ratio which fits
+/// within the allocated area, centred. The remaining (letterbox) area is not
+/// drawn and does not respond to events.
+#[derive(Clone, Debug, Widget)]
+#[widget]
+pub struct AspectRatio<W: Widget> {
+    #[core]
+    core: CoreData,
+    ratio: f32,
+    ideal_width: u32,
+    #[widget]
+    child: W,
+}
+
+impl<W: Widget> AspectRatio<W> {
+    /// Construct around a child widget with the given `ratio` (width / height)
+    ///
+    /// The `ratio` is clamped to the range `[1/1000, 1000]`; if NaN, a ratio
+    /// of 1 is used.
+    #[inline]
+    pub fn new(child: W, ratio: f32) -> Self {
+        let ratio = match ratio.is_nan() {
+            true => 1.0,
+            false => ratio.clamp(1e-3, 1e3),
+        };
+        AspectRatio {
+            core: Default::default(),
+            ratio,
+            ideal_width: 0,
+            child,
+        }
+    }
+
+    /// Access inner widget directly
+    #[inline]
+    pub fn inner(&self) -> &W {
+        &self.child
+    }
+
+    /// Access inner widget directly
+    #[inline]
+    pub fn inner_mut(&mut self) -> &mut W {
+        &mut self.child
+    }
+}
+
+impl<W: Widget> Layout for AspectRatio<W> {
+    fn size_rules(&mut self, size_handle: &mut dyn SizeHandle, axis: AxisInfo) -> SizeRules {
+        let rules = layout::child_size_rules(&mut self.child, size_handle, axis);
+        if axis.is_horizontal() {
+            self.ideal_width = rules.ideal_size();
+            rules
+        } else {
+            // Prefer the height matching the (given or ideal) width
+            let width = axis.other().unwrap_or(self.ideal_width);
+            let height = (width as f32 / self.ratio).round() as u32;
+            let min = rules.min_size();
+            SizeRules::new(min, height.max(min), rules.stretch()).with_weight(rules.weight())
+        }
+    }
+
+    fn set_rect(&mut self, size_handle: &mut dyn SizeHandle, rect: Rect, align: AlignHints) {
+        self.core.rect = rect;
+        let height = (rect.size.0 as f32 / self.ratio).round() as u32;
+        let size = if height <= rect.size.1 {
+            Size(rect.size.0, height)
+        } else {
+            let width = (rect.size.1 as f32 * self.ratio).round() as u32;
+            Size(width.min(rect.size.0), rect.size.1)
+        };
+        let excess = rect.size - size;
+        let pos = rect.pos + Coord((excess.0 / 2) as i32, (excess.1 / 2) as i32);
+        layout::child_set_rect(&mut self.child, size_handle, Rect { pos, size }, align);
+    }
+
+    fn find_id(&self, coord: Coord) -> Option<WidgetId> {
+        layout::child_find_id(&self.child, coord)
+    }
+
+    fn draw(&self, draw_handle: &mut dyn DrawHandle, mgr: &ManagerState) {
+        layout::child_draw(&self.child, draw_handle, mgr);
+    }
+}
+
+impl<W: Widget + Handler> Handler for AspectRatio<W> {
+    type Msg = <W as Handler>::Msg;
+
+    fn handle(&mut self, mgr: &mut Manager, id: WidgetId, event: Event) -> Response<Self::Msg> {
+        if id <= self.child.id() {
+            self.child.handle(mgr, id, event)
+        } else {
+            debug_assert!(id == self.id(), "Handler::handle: bad WidgetId");
+            Response::Unhandled(event)
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::testing::{solve, Harness};
+    use crate::widget::Filler;
+
+    fn child_rect(ratio: f32) -> Rect {
+        let mut widget = AspectRatio::new(Filler::maximise(), ratio);
+        let _ = Harness::new(&mut widget);
+        solve(&mut widget, Size(100, 100));
+        widget.inner().rect()
+    }
+
+    #[test]
+    fn letterbox() {
+        let rect = child_rect(2.0);
+        assert_eq!(rect, Rect::new(Coord(0, 25), Size(100, 50)));
+        let rect = child_rect(0.5);
+        assert_eq!(rect, Rect::new(Coord(25, 0), Size(50, 100)));
+    }
+
+    #[test]
+    fn invalid_ratio() {
+        assert_eq!(child_rect(f32::NAN).size, Size(100, 100));
+        assert_eq!(child_rect(0.0).size, Size(0, 100));
+        assert_eq!(child_rect(-1.0).size, Size(0, 100));
+        assert_eq!(child_rect(f32::INFINITY).size, Size(100, 0));
+    }
+}
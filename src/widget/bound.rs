@@ -0,0 +1,172 @@
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License in the LICENSE-APACHE file or at:
+//     https://www.apache.org/licenses/LICENSE-2.0
+
+//! Wrapper binding a widget to an observable value
+
+use std::fmt::{self, Debug};
+
+use crate::event::{Binding, Event, Handler, Manager, Response, UpdateHandle};
+use crate::macros::Widget;
+use crate::{CoreData, LayoutData, Widget, WidgetCore, WidgetId};
+
+/// A wrapper updating a child widget from a [`Binding`]
+///
+/// When configured, the wrapper subscribes to the binding. Whenever the
+/// binding's value changes, the closure `f` is called with the child widget
+/// and the new value; the window is redrawn afterwards. The closure is also
+/// called on configuration, thus the child need not be constructed with the
+/// initial value.
+///
+/// The closure should only update the child (e.g. via
+/// [`HasText::set_string`]). It may also set bindings, including its own;
+/// since setting an unchanged value has no effect, cycles terminate once
+/// values converge.
+///
+/// [`HasText::set_string`]: crate::class::HasText::set_string
+#[derive(Clone, Widget)]
+#[layout(single)]
+pub struct Bound<W: Widget, T: PartialEq, F: Fn(&mut W, &mut Manager, &T)> {
+    #[core]
+    core: CoreData,
+    #[layout_data]
+    layout_data: <Self as LayoutData>::Data,
+    #[widget]
+    child: W,
+    binding: Binding<T>,
+    f: F,
+}
+
+impl<W: Widget, T: PartialEq, F: Fn(&mut W, &mut Manager, &T)> Debug for Bound<W, T, F> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "Bound {{ core: {:?}, child: {:?}, handle: {:?}, ... }}",
+            self.core,
+            self.child,
+            self.binding.handle()
+        )
+    }
+}
+
+impl<W: Widget, T: PartialEq, F: Fn(&mut W, &mut Manager, &T)> Bound<W, T, F> {
+    /// Construct
+    #[inline]
+    pub fn new(child: W, binding: Binding<T>, f: F) -> Self {
+        Bound {
+            core: Default::default(),
+            layout_data: Default::default(),
+            child,
+            binding,
+            f,
+        }
+    }
+
+    /// Get the binding
+    #[inline]
+    pub fn binding(&self) -> &Binding<T> {
+        &self.binding
+    }
+
+    /// Access inner widget directly
+    #[inline]
+    pub fn inner(&self) -> &W {
+        &self.child
+    }
+
+    /// Access inner widget directly
+    #[inline]
+    pub fn inner_mut(&mut self) -> &mut W {
+        &mut self.child
+    }
+
+    fn update(&mut self, mgr: &mut Manager) {
+        let (child, f) = (&mut self.child, &self.f);
+        self.binding.with(|value| f(child, mgr, value));
+    }
+}
+
+impl<W: Widget, T: PartialEq, F: Fn(&mut W, &mut Manager, &T)> Widget for Bound<W, T, F> {
+    fn configure(&mut self, mgr: &mut Manager) {
+        self.binding.subscribe(mgr, self.id());
+        self.update(mgr);
+    }
+
+    fn update_handle(&mut self, mgr: &mut Manager, handle: UpdateHandle, _: u64) {
+        if handle == self.binding.handle() {
+            self.update(mgr);
+        }
+    }
+}
+
+impl<W, T, F> Handler for Bound<W, T, F>
+where
+    W: Widget + Handler,
+    T: PartialEq,
+    F: Fn(&mut W, &mut Manager, &T),
+{
+    type Msg = <W as Handler>::Msg;
+
+    fn handle(&mut self, mgr: &mut Manager, id: WidgetId, event: Event) -> Response<Self::Msg> {
+        if id <= self.child.id() {
+            self.child.handle(mgr, id, event)
+        } else {
+            debug_assert!(id == self.id(), "Handler::handle: bad WidgetId");
+            Response::Unhandled(event)
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::class::HasText;
+    use crate::testing::Harness;
+    use crate::widget::Label;
+
+    #[test]
+    fn binding_update() {
+        let binding = Binding::new("a".to_string());
+        let mut widget = Bound::new(Label::new(""), binding.clone(), |w, mgr, v| {
+            w.set_text(mgr, v)
+        });
+        let mut h = Harness::new(&mut widget);
+        assert_eq!(widget.inner().get_text(), "a");
+
+        let mut mgr = h.manager();
+        assert!(!binding.set(&mut mgr, "a".to_string()));
+        assert!(binding.set(&mut mgr, "b".to_string()));
+        assert_eq!(binding.get(), "b");
+        mgr.update_handle(&mut widget, binding.handle(), 0);
+        assert_eq!(widget.inner().get_text(), "b");
+    }
+
+    #[test]
+    fn binding_cycle() {
+        // The closure normalises the value by setting its own binding
+        let binding = Binding::new("A".to_string());
+        let b2 = binding.clone();
+        let mut widget = Bound::new(Label::new(""), binding.clone(), move |w, mgr, v| {
+            w.set_text(mgr, v);
+            b2.set(mgr, v.to_lowercase());
+        });
+        let mut h = Harness::new(&mut widget);
+        assert_eq!(widget.inner().get_text(), "A");
+        assert_eq!(binding.get(), "a");
+
+        // Deliver updates until the values converge
+        let mut mgr = h.manager();
+        mgr.update_handle(&mut widget, binding.handle(), 0);
+        assert_eq!(widget.inner().get_text(), "a");
+        assert!(binding.set(&mut mgr, "B".to_string()));
+        mgr.update_handle(&mut widget, binding.handle(), 0);
+        assert_eq!(
+            (widget.inner().get_text(), binding.get()),
+            ("B", "b".into())
+        );
+        mgr.update_handle(&mut widget, binding.handle(), 0);
+        assert_eq!(widget.inner().get_text(), "b");
+        assert!(!binding.set(&mut mgr, "b".to_string()));
+    }
+}
@@ -8,9 +8,10 @@
 use smallvec::SmallVec;
 use std::fmt::Debug;
 
+use crate::access::{AccessEvent, AccessNode, Role};
 use crate::class::HasText;
 use crate::draw::{DrawHandle, SizeHandle, TextClass};
-use crate::event::{Action, Handler, Manager, ManagerState, Response, VirtualKeyCode};
+use crate::event::{Action, CursorIcon, Handler, Manager, ManagerState, Response, VirtualKeyCode};
 use crate::geom::{Coord, Rect};
 use crate::layout::{AxisInfo, SizeRules};
 use crate::macros::Widget;
@@ -38,6 +39,14 @@ impl<M: Clone + Debug> Widget for TextButton<M> {
     fn allow_focus(&self) -> bool {
         true
     }
+
+    fn access_node(&self) -> AccessNode {
+        AccessNode::new(self.id(), Role::Button).with_name(&self.label)
+    }
+
+    fn cursor_icon(&self) -> CursorIcon {
+        CursorIcon::Hand
+    }
 }
 
 impl<M: Clone + Debug> Layout for TextButton<M> {
@@ -137,6 +146,7 @@ impl<M: Clone + Debug> HasText for TextButton<M> {
     fn set_string(&mut self, mgr: &mut Manager, text: String) {
         self.label = text;
         mgr.redraw(self.id());
+        mgr.access_notify(AccessEvent::ValueChanged(self.id()));
     }
 }
 
@@ -155,3 +165,56 @@ impl<M: Clone + Debug> Handler for TextButton<M> {
         }
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::access::AccessPrefs;
+    use crate::event::InputEvent;
+    use crate::testing::Harness;
+    use std::time::{Duration, Instant};
+
+    #[test]
+    fn key_flash() {
+        let mut button = TextButton::new("a", ()).with_keys(&[VirtualKeyCode::A]);
+        let mut h = Harness::new(&mut button);
+        let id = button.id();
+
+        let tap = |h: &mut Harness, button: &mut TextButton<()>| {
+            let mut mgr = h.manager();
+            let ev = InputEvent::KeyPressed(1, VirtualKeyCode::A);
+            assert!(matches!(
+                mgr.handle_synthetic(button, ev),
+                Response::Msg(())
+            ));
+            let ev = InputEvent::KeyReleased(1);
+            assert!(mgr.handle_synthetic(button, ev).is_none());
+        };
+
+        // A quick press remains visible
+        tap(&mut h, &mut button);
+        assert!(h.state().is_depressed(id));
+        let first = h.state().next_resume().unwrap();
+
+        // A repeat restarts the flash instead of queuing another
+        std::thread::sleep(Duration::from_millis(1));
+        tap(&mut h, &mut button);
+        let end = h.state().next_resume().unwrap();
+        assert!(end > first);
+
+        let now = Instant::now();
+        if end > now {
+            std::thread::sleep(end - now);
+        }
+        h.manager().update_timer(&mut button);
+        assert!(!h.state().is_depressed(id));
+        assert!(h.state().next_resume().is_none());
+
+        h.state().set_access_prefs(AccessPrefs {
+            reduced_motion: true,
+            ..Default::default()
+        });
+        tap(&mut h, &mut button);
+        assert!(!h.state().is_depressed(id));
+    }
+}
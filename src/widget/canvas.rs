@@ -0,0 +1,118 @@
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License in the LICENSE-APACHE file or at:
+//     https://www.apache.org/licenses/LICENSE-2.0
+
+//! Canvas widget
+
+use std::fmt::{self, Debug};
+
+use crate::draw::{Colour, DrawHandle, Painter, SizeHandle};
+use crate::event::ManagerState;
+use crate::geom::{Coord, Size};
+use crate::layout::{AxisInfo, SizeRules, StretchPolicy};
+use crate::macros::Widget;
+use crate::{CoreData, Layout};
+
+/// A widget drawn via a [`Painter`]
+///
+/// Whenever the widget is drawn, the closure `f` is called with a [`Painter`]
+/// targetting the widget's rect. Painter coordinates are in physical pixels
+/// relative to the widget's top-left corner; all drawing is clipped to the
+/// widget's rect.
+///
+/// For lower-level drawing, see [`CustomDraw`].
+///
+/// Drawn geometry may be cached between frames; call [`Manager::redraw`]
+/// with this widget's id whenever the output of `f` would change.
+///
+/// ```
+/// use kas::draw::Colour;
+/// use kas::geom::{Size, Vec2};
+/// use kas::widget::Canvas;
+///
+/// let widget = Canvas::new(Size(100, 100), |painter| {
+///     let size = Vec2::from(painter.size());
+///     painter.set_colour(Colour::new(0.2, 0.4, 0.8));
+///     painter.circle(size * 0.5, 0.4 * size.0.min(size.1));
+///     painter.fill();
+///
+///     painter.set_colour(Colour::grey(0.1));
+///     painter.set_line_width(2.0 * painter.dpi_factor());
+///     painter.move_to((0.0, size.1));
+///     painter.curve_to((0.0, 0.0), (size.0, size.1), (size.0, 0.0));
+///     painter.stroke();
+/// })
+/// .with_background(Colour::grey(1.0));
+/// ```
+///
+/// [`CustomDraw`]: crate::widget::CustomDraw
+/// [`Manager::redraw`]: crate::event::Manager::redraw
+#[derive(Clone, Widget)]
+#[widget]
+#[handler]
+pub struct Canvas<F: Fn(&mut Painter)> {
+    #[core]
+    core: CoreData,
+    min_size: Size,
+    policy: StretchPolicy,
+    background: Option<Colour>,
+    f: F,
+}
+
+impl<F: Fn(&mut Painter)> Debug for Canvas<F> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "Canvas {{ core: {:?}, min_size: {:?}, policy: {:?}, background: {:?}, ... }}",
+            self.core, self.min_size, self.policy, self.background
+        )
+    }
+}
+
+impl<F: Fn(&mut Painter)> Layout for Canvas<F> {
+    fn size_rules(&mut self, _: &mut dyn SizeHandle, axis: AxisInfo) -> SizeRules {
+        let size = axis.extract_size(self.min_size);
+        SizeRules::new(size, size, self.policy)
+    }
+
+    fn draw(&self, draw_handle: &mut dyn DrawHandle, mgr: &ManagerState) {
+        let dpi_factor = mgr.dpi_factor() as f32;
+        draw_handle.clip_region(self.core.rect, Coord::ZERO, &mut |handle| {
+            let (region, offset, draw) = handle.draw_device();
+            let rect = self.core.rect + offset;
+            if let Some(col) = self.background {
+                draw.rect(region, rect, col);
+            }
+            let mut painter = Painter::new(draw, region, rect, dpi_factor);
+            (self.f)(&mut painter);
+        });
+    }
+}
+
+impl<F: Fn(&mut Painter)> Canvas<F> {
+    /// Construct with a minimum size (in physical pixels) and paint closure
+    ///
+    /// The widget may stretch according to [`StretchPolicy::Filler`].
+    pub fn new(min_size: Size, f: F) -> Self {
+        Canvas {
+            core: Default::default(),
+            min_size,
+            policy: StretchPolicy::Filler,
+            background: None,
+            f,
+        }
+    }
+
+    /// Set the stretch policy
+    pub fn with_policy(mut self, policy: StretchPolicy) -> Self {
+        self.policy = policy;
+        self
+    }
+
+    /// Fill the background with `col` before painting
+    pub fn with_background(mut self, col: Colour) -> Self {
+        self.background = Some(col);
+        self
+    }
+}
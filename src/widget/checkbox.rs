@@ -8,8 +8,9 @@
 use std::fmt::{self, Debug};
 
 use super::Label;
+use crate::access::{AccessEvent, AccessNode, Role};
 use crate::class::HasBool;
-use crate::draw::{DrawHandle, SizeHandle};
+use crate::draw::{DrawHandle, SizeHandle, WidgetStyle};
 use crate::event::{Action, Handler, Manager, ManagerState, Response, VoidMsg};
 use crate::geom::Rect;
 use crate::layout::{AxisInfo, SizeRules};
@@ -39,6 +40,10 @@ impl<OT: 'static> Widget for CheckBoxBare<OT> {
     fn allow_focus(&self) -> bool {
         true
     }
+
+    fn access_node(&self) -> AccessNode {
+        AccessNode::new(self.id(), Role::CheckBox).with_checked(self.state)
+    }
 }
 
 impl<OT: 'static> Layout for CheckBoxBare<OT> {
@@ -118,8 +123,11 @@ impl<H> HasBool for CheckBoxBare<H> {
     }
 
     fn set_bool(&mut self, mgr: &mut Manager, state: bool) {
-        self.state = state;
-        mgr.redraw(self.id());
+        if state != self.state {
+            self.state = state;
+            mgr.redraw(self.id());
+            mgr.access_notify(AccessEvent::ValueChanged(self.id()));
+        }
     }
 }
 
@@ -136,6 +144,7 @@ impl Handler for CheckBoxBare<()> {
             Action::Activate => {
                 self.state = !self.state;
                 mgr.redraw(self.id());
+                mgr.access_notify(AccessEvent::ValueChanged(self.id()));
                 Response::None
             }
             a @ _ => Response::unhandled_action(a),
@@ -156,6 +165,7 @@ impl<M, H: Fn(bool) -> M> Handler for CheckBoxBare<H> {
             Action::Activate => {
                 self.state = !self.state;
                 mgr.redraw(self.id());
+                mgr.access_notify(AccessEvent::ValueChanged(self.id()));
                 ((self.on_toggle)(self.state)).into()
             }
             a @ _ => Response::unhandled_action(a),
@@ -165,11 +175,10 @@ impl<M, H: Fn(bool) -> M> Handler for CheckBoxBare<H> {
 
 /// A checkable box with optional label
 // TODO: use a generic wrapper for CheckBox and RadioBox?
+#[derive(Clone, Default, Widget)]
 #[layout(horizontal, area=checkbox)]
-#[widget]
 #[handler(substitutions = (OT = ()))]
 #[handler(msg = M, generics = <M: From<VoidMsg>> where OT: Fn(bool) -> M)]
-#[derive(Clone, Default, Widget)]
 pub struct CheckBox<OT: 'static> {
     #[core]
     core: CoreData,
@@ -181,6 +190,22 @@ pub struct CheckBox<OT: 'static> {
     label: Label,
 }
 
+impl<OT: 'static> Widget for CheckBox<OT> {
+    /// Set a style override
+    ///
+    /// The style is also applied to the inner check box and label, which draw the
+    /// check mark and text.
+    fn with_style(mut self, style: WidgetStyle) -> Self
+    where
+        Self: Sized,
+    {
+        self.core.style = style;
+        self.checkbox = self.checkbox.with_style(style);
+        self.label = self.label.with_style(style);
+        self
+    }
+}
+
 impl<H> Debug for CheckBox<H> {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         write!(
@@ -0,0 +1,427 @@
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License in the LICENSE-APACHE file or at:
+//     https://www.apache.org/licenses/LICENSE-2.0
+
+//! Colour picker
+
+use std::fmt::{self, Debug};
+
+use super::EditBox;
+use crate::class::HasText;
+use crate::draw::{Colour, Draw, DrawHandle, Region, SizeHandle, TextClass};
+use crate::event::{Event, Handler, Manager, ManagerState, PressSource, Response, VoidMsg};
+use crate::geom::{Coord, Rect, Size};
+use crate::layout::{AxisInfo, SizeRules, StretchPolicy};
+use crate::macros::{VoidMsg, Widget};
+use crate::{CoreData, Layout, WidgetCore, WidgetId};
+
+#[derive(Clone, Debug, VoidMsg)]
+enum PickerMsg {
+    Hue(f32),
+    SatVal(f32, f32),
+    HexEdit,
+    HexActivate,
+}
+
+/// Minimum length of the hue strip and square, in lines of text
+const MIN_LINES: u32 = 8;
+
+/// Draw `rect` as a grid of `(nx, ny)` cells, coloured by `f(x, y)` where
+/// `x` and `y` are the relative positions of cell centres
+fn draw_cells<F>(draw: &mut dyn Draw, region: Region, rect: Rect, nx: u32, ny: u32, f: F)
+where
+    F: Fn(f32, f32) -> Colour,
+{
+    let Size(w, h) = rect.size;
+    let edge = |i: u32, n: u32, len: u32| (i as u64 * len as u64 / n as u64) as i32;
+    for j in 0..ny {
+        let (y0, y1) = (edge(j, ny, h), edge(j + 1, ny, h));
+        let y = (j as f32 + 0.5) / ny as f32;
+        for i in 0..nx {
+            let (x0, x1) = (edge(i, nx, w), edge(i + 1, nx, w));
+            let x = (i as f32 + 0.5) / nx as f32;
+            let pos = rect.pos + Coord(x0, y0);
+            let size = Size((x1 - x0) as u32, (y1 - y0) as u32);
+            draw.rect(region, Rect::new(pos, size), f(x, y));
+        }
+    }
+}
+
+/// Draw a marker frame of half-width `r` and thickness 1 around `centre`
+fn draw_marker(draw: &mut dyn Draw, region: Region, centre: Coord, r: Coord, col: Colour) {
+    let outer = Rect::new(centre - r, Size(2 * r.0 as u32 + 1, 2 * r.1 as u32 + 1));
+    let inner = Rect::new(outer.pos + Coord(1, 1), outer.size - Size(2, 2));
+    draw.frame(region, outer, inner, col);
+}
+
+/// Position of `coord` relative to `rect` along each axis, in `0.0 ..= 1.0`
+fn rel_pos(rect: Rect, coord: Coord) -> (f32, f32) {
+    let Coord(x, y) = coord - rect.pos;
+    let rel = |p: i32, len: u32| (p as f32 / (len.max(2) - 1) as f32).clamp(0.0, 1.0);
+    (rel(x, rect.size.0), rel(y, rect.size.1))
+}
+
+/// Vertical hue strip
+#[derive(Clone, Debug, Default, Widget)]
+#[widget]
+struct HueStrip {
+    #[core]
+    core: CoreData,
+    hue: f32,
+    press_source: Option<PressSource>,
+}
+
+impl Layout for HueStrip {
+    fn size_rules(&mut self, size_handle: &mut dyn SizeHandle, axis: AxisInfo) -> SizeRules {
+        let line = size_handle.line_height(TextClass::Label);
+        if axis.is_horizontal() {
+            SizeRules::fixed(line)
+        } else {
+            SizeRules::new(MIN_LINES * line, MIN_LINES * line, StretchPolicy::Filler)
+        }
+    }
+
+    fn draw(&self, draw_handle: &mut dyn DrawHandle, _: &ManagerState) {
+        let (region, offset, draw) = draw_handle.draw_device();
+        let rect = self.core.rect + offset;
+        let n = (rect.size.1 / 2).clamp(1, 120);
+        draw_cells(draw, region, rect, 1, n, |_, y| {
+            Colour::from_hsv(360.0 * y, 1.0, 1.0)
+        });
+
+        let y = (self.hue / 360.0 * (rect.size.1.max(1) - 1) as f32).round() as i32;
+        let centre = rect.pos + Coord(rect.size.0 as i32 / 2, y);
+        let r = Coord(rect.size.0 as i32 / 2, 1);
+        draw_marker(draw, region, centre, r, Colour::grey(0.0));
+    }
+}
+
+impl Handler for HueStrip {
+    type Msg = PickerMsg;
+
+    fn handle(&mut self, mgr: &mut Manager, _: WidgetId, event: Event) -> Response<PickerMsg> {
+        let coord = match event {
+            Event::PressStart { source, coord } => {
+                if !mgr.request_press_grab(source, self, coord, None) {
+                    return Response::None;
+                }
+                self.press_source = Some(source);
+                coord
+            }
+            Event::PressMove { source, coord, .. } if Some(source) == self.press_source => coord,
+            Event::PressEnd { source, .. } if Some(source) == self.press_source => {
+                self.press_source = None;
+                return Response::None;
+            }
+            e => return Manager::handle_generic(self, mgr, e),
+        };
+        let hue = 360.0 * rel_pos(self.core.rect, coord).1;
+        if hue == self.hue {
+            return Response::None;
+        }
+        Response::Msg(PickerMsg::Hue(hue))
+    }
+}
+
+/// Saturation-value square
+#[derive(Clone, Debug, Default, Widget)]
+#[widget]
+struct SatValSquare {
+    #[core]
+    core: CoreData,
+    hue: f32,
+    sat: f32,
+    val: f32,
+    press_source: Option<PressSource>,
+}
+
+impl Layout for SatValSquare {
+    fn size_rules(&mut self, size_handle: &mut dyn SizeHandle, _: AxisInfo) -> SizeRules {
+        let len = MIN_LINES * size_handle.line_height(TextClass::Label);
+        SizeRules::new(len, len, StretchPolicy::Filler)
+    }
+
+    fn draw(&self, draw_handle: &mut dyn DrawHandle, _: &ManagerState) {
+        let (region, offset, draw) = draw_handle.draw_device();
+        let rect = self.core.rect + offset;
+        let nx = (rect.size.0 / 4).clamp(1, 48);
+        let ny = (rect.size.1 / 4).clamp(1, 48);
+        let hue = self.hue;
+        draw_cells(draw, region, rect, nx, ny, |x, y| {
+            Colour::from_hsv(hue, x, 1.0 - y)
+        });
+
+        let Size(w, h) = rect.size;
+        let x = (self.sat * (w.max(1) - 1) as f32).round() as i32;
+        let y = ((1.0 - self.val) * (h.max(1) - 1) as f32).round() as i32;
+        let col = Colour::grey(if self.val > 0.5 { 0.0 } else { 1.0 });
+        draw_marker(draw, region, rect.pos + Coord(x, y), Coord(3, 3), col);
+    }
+}
+
+impl Handler for SatValSquare {
+    type Msg = PickerMsg;
+
+    fn handle(&mut self, mgr: &mut Manager, _: WidgetId, event: Event) -> Response<PickerMsg> {
+        let coord = match event {
+            Event::PressStart { source, coord } => {
+                if !mgr.request_press_grab(source, self, coord, None) {
+                    return Response::None;
+                }
+                self.press_source = Some(source);
+                coord
+            }
+            Event::PressMove { source, coord, .. } if Some(source) == self.press_source => coord,
+            Event::PressEnd { source, .. } if Some(source) == self.press_source => {
+                self.press_source = None;
+                return Response::None;
+            }
+            e => return Manager::handle_generic(self, mgr, e),
+        };
+        let (sat, y) = rel_pos(self.core.rect, coord);
+        let val = 1.0 - y;
+        if (sat, val) == (self.sat, self.val) {
+            return Response::None;
+        }
+        Response::Msg(PickerMsg::SatVal(sat, val))
+    }
+}
+
+type HexBox = EditBox<fn(&str) -> PickerMsg, fn(&str) -> PickerMsg>;
+
+/// A colour picker
+///
+/// This widget allows selection of a colour via a saturation-value square and
+/// a hue strip, which may be dragged, and a hex colour code entry (see
+/// [`Colour::from_hex`]). The closure `f` is called with the new colour on
+/// each change, and the result is returned from the event handler.
+///
+/// The selection is held as hue, saturation and value (see
+/// [`Colour::from_hsv`]), thus the hue is retained while the colour is grey.
+/// Entering a hex code only adjusts the selection when the code describes a
+/// different colour, thus switching between controls does not cause drift.
+/// Alpha is preserved, and may be adjusted via the hex code.
+#[derive(Clone, Widget)]
+#[layout(grid)]
+#[widget]
+#[handler(msg = M, generics = <M: From<VoidMsg>> where F: Fn(Colour) -> M)]
+pub struct ColourPicker<F: 'static> {
+    #[core]
+    core: CoreData,
+    #[layout_data]
+    layout_data: <Self as kas::LayoutData>::Data,
+    #[widget(col = 0, row = 0, handler = handle_msg)]
+    sat_val: SatValSquare,
+    #[widget(col = 1, row = 0, handler = handle_msg)]
+    hue: HueStrip,
+    #[widget(col = 0, row = 1, cspan = 2, handler = handle_msg)]
+    hex: HexBox,
+    alpha: f32,
+    on_change: F,
+}
+
+impl<F> Debug for ColourPicker<F> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "ColourPicker {{ core: {:?}, sat_val: {:?}, hue: {:?}, hex: {:?}, alpha: {:?}, ... }}",
+            self.core, self.sat_val, self.hue, self.hex, self.alpha
+        )
+    }
+}
+
+impl<M, F: Fn(Colour) -> M> ColourPicker<F> {
+    /// Construct, with an initial colour and a closure `f` called on change
+    pub fn new(colour: Colour, f: F) -> Self {
+        fn edit(_: &str) -> PickerMsg {
+            PickerMsg::HexEdit
+        }
+        fn activate(_: &str) -> PickerMsg {
+            PickerMsg::HexActivate
+        }
+        let (hue, sat, val) = colour.to_hsv();
+        ColourPicker {
+            core: Default::default(),
+            layout_data: Default::default(),
+            sat_val: SatValSquare {
+                hue,
+                sat,
+                val,
+                ..Default::default()
+            },
+            hue: HueStrip {
+                hue,
+                ..Default::default()
+            },
+            hex: EditBox::new(colour.to_hex())
+                .on_activate(activate as fn(&str) -> PickerMsg)
+                .on_edit(edit as fn(&str) -> PickerMsg),
+            alpha: colour.a,
+            on_change: f,
+        }
+    }
+
+    fn handle_msg(&mut self, mgr: &mut Manager, msg: PickerMsg) -> Response<M> {
+        let (hue, sat, val) = match msg {
+            PickerMsg::Hue(hue) => (hue, self.sat_val.sat, self.sat_val.val),
+            PickerMsg::SatVal(sat, val) => (self.hue.hue, sat, val),
+            PickerMsg::HexEdit => {
+                let colour = match Colour::from_hex(self.hex.get_text()) {
+                    Ok(colour) => colour,
+                    Err(_) => return Response::None,
+                };
+                if colour.to_rgba8() == self.colour().to_rgba8() {
+                    return Response::None;
+                }
+                self.set_from(mgr, colour, false);
+                return (self.on_change)(self.colour()).into();
+            }
+            PickerMsg::HexActivate => {
+                let hex = self.colour().to_hex();
+                if self.hex.get_text() != hex {
+                    self.hex.set_string(mgr, hex);
+                }
+                return Response::None;
+            }
+        };
+        self.set_hsv(mgr, hue, sat, val, true);
+        (self.on_change)(self.colour()).into()
+    }
+}
+
+impl<F> ColourPicker<F> {
+    /// Get the current colour
+    pub fn colour(&self) -> Colour {
+        let sv = &self.sat_val;
+        let mut colour = Colour::from_hsv(sv.hue, sv.sat, sv.val);
+        colour.a = self.alpha;
+        colour
+    }
+
+    /// Get the current hue (in degrees), saturation and value
+    ///
+    /// See [`Colour::from_hsv`].
+    pub fn hsv(&self) -> (f32, f32, f32) {
+        let sv = &self.sat_val;
+        (sv.hue, sv.sat, sv.val)
+    }
+
+    /// Set the colour
+    ///
+    /// The closure passed on construction is not called.
+    pub fn set_colour(&mut self, mgr: &mut Manager, colour: Colour) {
+        self.set_from(mgr, colour, true);
+    }
+
+    /// Set from `colour`, keeping hue (and saturation) where `colour` does
+    /// not define these
+    fn set_from(&mut self, mgr: &mut Manager, colour: Colour, update_hex: bool) {
+        let (mut hue, mut sat, val) = colour.to_hsv();
+        if sat == 0.0 || val == 0.0 {
+            hue = self.sat_val.hue;
+        }
+        if val == 0.0 {
+            sat = self.sat_val.sat;
+        }
+        self.alpha = colour.a;
+        self.set_hsv(mgr, hue, sat, val, update_hex);
+    }
+
+    fn set_hsv(&mut self, mgr: &mut Manager, hue: f32, sat: f32, val: f32, update_hex: bool) {
+        self.sat_val.hue = hue;
+        self.sat_val.sat = sat;
+        self.sat_val.val = val;
+        self.hue.hue = hue;
+        mgr.redraw(self.sat_val.id());
+        mgr.redraw(self.hue.id());
+
+        if update_hex {
+            let hex = self.colour().to_hex();
+            if self.hex.get_text() != hex {
+                self.hex.set_string(mgr, hex);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::event::{Action, InputEvent, MouseButton};
+    use crate::layout;
+    use crate::testing::{Harness, TextSizeHandle};
+
+    #[derive(Debug, VoidMsg)]
+    struct Changed(Colour);
+
+    type Picker = ColourPicker<fn(Colour) -> Changed>;
+
+    #[test]
+    fn hex_keeps_hue() {
+        let mut picker: Picker = ColourPicker::new(Colour::grey(1.0), Changed);
+        let mut h = Harness::new(&mut picker);
+        layout::solve(&mut picker, &mut TextSizeHandle, Size(200, 200));
+        let (sv_rect, hue_rect) = (picker.sat_val.rect(), picker.hue.rect());
+        let hex_id = picker.hex.id();
+        let mut mgr = h.manager();
+
+        let mut drag = |picker: &mut Picker, from: Coord, to: Coord| {
+            let events = vec![
+                InputEvent::CursorMoved(from),
+                InputEvent::MousePressed(MouseButton::Left),
+                InputEvent::CursorMoved(to),
+                InputEvent::MouseReleased(MouseButton::Left),
+            ];
+            let mut last = None;
+            for ev in events {
+                if let Response::Msg(Changed(colour)) = mgr.handle_synthetic(picker, ev) {
+                    last = Some(colour);
+                }
+            }
+            let colour = last.expect("drag changes colour");
+            assert_eq!(colour.to_rgba8(), picker.colour().to_rgba8());
+            assert_eq!(picker.hex.get_text(), colour.to_hex());
+        };
+
+        let centre = hue_rect.pos.0 + hue_rect.size.0 as i32 / 2;
+        let third = hue_rect.size.1 as i32 / 3;
+        drag(
+            &mut picker,
+            hue_rect.pos + Coord(0, 0),
+            Coord(centre, third),
+        );
+        let Size(w, h) = sv_rect.size;
+        let quarter = Coord(w as i32 / 4, h as i32 / 4);
+        drag(&mut picker, sv_rect.pos, sv_rect.pos + quarter);
+        let (hue, sat, val) = picker.hsv();
+        assert!(100.0 < hue && hue < 140.0);
+        assert!(0.0 < sat && sat < 1.0 && 0.0 < val && val < 1.0);
+
+        let mut type_hex = |picker: &mut Picker, hex: &str| {
+            let mut send = |c| mgr.send_action_to(picker, hex_id, Action::ReceivedCharacter(c));
+            assert!(send('\u{7f}').is_none());
+            let mut last = None;
+            for c in hex.chars() {
+                if let Response::Msg(Changed(colour)) = send(c) {
+                    last = Some(colour);
+                }
+            }
+            let colour = last.expect("hex edit changes colour");
+            assert_eq!(colour, Colour::from_hex(hex).unwrap());
+            // The hex code is not rewritten while editing
+            assert_eq!(picker.hex.get_text(), hex);
+        };
+
+        // Black keeps hue and saturation
+        type_hex(&mut picker, "000");
+        assert_eq!(picker.hsv(), (hue, sat, 0.0));
+
+        // Grey keeps hue
+        type_hex(&mut picker, "888");
+        let (grey_hue, grey_sat, grey_val) = picker.hsv();
+        assert_eq!((grey_hue, grey_sat), (hue, 0.0));
+        assert!((grey_val - Colour::from_hex("888").unwrap().to_hsv().2).abs() < 1e-6);
+        assert_eq!(picker.colour().to_hex(), "#888888");
+    }
+}
@@ -0,0 +1,194 @@
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License in the LICENSE-APACHE file or at:
+//     https://www.apache.org/licenses/LICENSE-2.0
+
+//! Size-constrained wrapper
+
+use crate::draw::{DrawHandle, SizeHandle};
+use crate::event::{Event, Handler, Manager, ManagerState, Response};
+use crate::geom::{Coord, Rect, Size};
+use crate::layout::{self, AxisInfo, SizeRules, StretchPolicy};
+use crate::macros::Widget;
+use crate::{Align, AlignHints, CoreData, Layout, Widget, WidgetCore, WidgetId};
+
+/// A wrapper imposing minimum and/or maximum size constraints on a child
+///
+/// The minimum size is added to the child's [`SizeRules`]. If a maximum size is
+/// set, the wrapper does not stretch: its ideal size is the maximum if the
+/// child may stretch, otherwise the child's ideal size (limited by the
+/// maximum). Where more space is available, the child is aligned within it
+/// (by default, centred). Where the maximum is less than the child's own
+/// minimum size, the minimum takes precedence.
+///
+/// The wrapper may also override the child's stretch weight (see
+/// [`SizeRules::with_weight`]).
+#[derive(Clone, Debug, Default, Widget)]
+#[widget]
+pub struct Constrained<W: Widget> {
+    #[core]
+    core: CoreData,
+    min_size: Size,
+    max_size: Option<Size>,
+    weight: Option<u32>,
+    child_min: Size,
+    #[widget]
+    child: W,
+}
+
+impl<W: Widget> Constrained<W> {
+    /// Construct around a child widget (without constraints)
+    #[inline]
+    pub fn new(child: W) -> Self {
+        Constrained {
+            core: Default::default(),
+            min_size: Size::ZERO,
+            max_size: None,
+            weight: None,
+            child_min: Size::ZERO,
+            child,
+        }
+    }
+
+    /// Set the minimum size
+    #[inline]
+    pub fn with_min_size(mut self, size: Size) -> Self {
+        self.min_size = size;
+        self
+    }
+
+    /// Set the maximum size
+    #[inline]
+    pub fn with_max_size(mut self, size: Size) -> Self {
+        self.max_size = Some(size);
+        self
+    }
+
+    /// Set the stretch weight
+    #[inline]
+    pub fn with_weight(mut self, weight: u32) -> Self {
+        self.weight = Some(weight);
+        self
+    }
+
+    /// Access inner widget directly
+    #[inline]
+    pub fn inner(&self) -> &W {
+        &self.child
+    }
+
+    /// Access inner widget directly
+    #[inline]
+    pub fn inner_mut(&mut self) -> &mut W {
+        &mut self.child
+    }
+}
+
+impl<W: Widget> Layout for Constrained<W> {
+    fn size_rules(&mut self, size_handle: &mut dyn SizeHandle, axis: AxisInfo) -> SizeRules {
+        let rules = layout::child_size_rules(&mut self.child, size_handle, axis);
+        if axis.is_horizontal() {
+            self.child_min.0 = rules.min_size();
+        } else {
+            self.child_min.1 = rules.min_size();
+        }
+        let min = rules.min_size().max(axis.extract_size(self.min_size));
+        let mut ideal = rules.ideal_size().max(min);
+        let mut stretch = rules.stretch();
+        if let Some(max) = self.max_size {
+            let max = axis.extract_size(max);
+            if stretch != StretchPolicy::Fixed {
+                ideal = max;
+            }
+            ideal = ideal.min(max).max(min);
+            stretch = StretchPolicy::Fixed;
+        }
+        SizeRules::new(min, ideal, stretch).with_weight(self.weight.unwrap_or(rules.weight()))
+    }
+
+    fn set_rect(&mut self, size_handle: &mut dyn SizeHandle, rect: Rect, align: AlignHints) {
+        self.core.rect = rect;
+        let mut child_rect = rect;
+        if let Some(max) = self.max_size {
+            // The child's minimum takes precedence over our maximum
+            let ideal = rect.size.min(max.max(self.child_min));
+            let non_stretch = |align| match align {
+                None | Some(Align::Stretch) => Align::Centre,
+                Some(align) => align,
+            };
+            let align = AlignHints::new(
+                Some(non_stretch(align.horiz)),
+                Some(non_stretch(align.vert)),
+            );
+            child_rect = align
+                .complete(Align::Centre, Align::Centre, ideal)
+                .apply(rect);
+        }
+        layout::child_set_rect(&mut self.child, size_handle, child_rect, align);
+    }
+
+    fn find_id(&self, coord: Coord) -> Option<WidgetId> {
+        layout::child_find_id(&self.child, coord).or(Some(self.id()))
+    }
+
+    fn draw(&self, draw_handle: &mut dyn DrawHandle, mgr: &ManagerState) {
+        layout::child_draw(&self.child, draw_handle, mgr);
+    }
+}
+
+impl<W: Widget + Handler> Handler for Constrained<W> {
+    type Msg = <W as Handler>::Msg;
+
+    fn handle(&mut self, mgr: &mut Manager, id: WidgetId, event: Event) -> Response<Self::Msg> {
+        if id <= self.child.id() {
+            self.child.handle(mgr, id, event)
+        } else {
+            debug_assert!(id == self.id(), "Handler::handle: bad WidgetId");
+            Response::Unhandled(event)
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::testing::{self, NullSizeHandle};
+    use crate::widget::{Filler, Spacer};
+    use crate::Direction::{Horizontal, Vertical};
+
+    type Rules = (u32, u32, StretchPolicy);
+
+    /// Get (min, ideal, stretch) for each axis
+    fn rules<W: Widget>(widget: &mut Constrained<W>) -> [Rules; 2] {
+        let mut get = |dir| {
+            let rules = widget.size_rules(&mut NullSizeHandle, AxisInfo::new(dir, None));
+            (rules.min_size(), rules.ideal_size(), rules.stretch())
+        };
+        [get(Horizontal), get(Vertical)]
+    }
+
+    #[test]
+    fn max_size() {
+        use StretchPolicy::Fixed;
+
+        // A stretchy child is limited to the maximum
+        let mut w = Constrained::new(Filler::maximise()).with_max_size(Size(20, 10));
+        assert_eq!(rules(&mut w), [(0, 20, Fixed), (0, 10, Fixed)]);
+        testing::solve(&mut w, Size(100, 100));
+        assert_eq!(w.inner().rect(), Rect::new(Coord(40, 45), Size(20, 10)));
+
+        // A fixed-size child keeps its ideal size
+        let mut w = Constrained::new(Spacer::new(Size(15, 5))).with_max_size(Size(20, 10));
+        assert_eq!(rules(&mut w), [(15, 15, Fixed), (5, 5, Fixed)]);
+    }
+
+    #[test]
+    fn max_below_child_min() {
+        use StretchPolicy::Fixed;
+
+        let mut w = Constrained::new(Spacer::new(Size(30, 30))).with_max_size(Size(10, 10));
+        assert_eq!(rules(&mut w), [(30, 30, Fixed), (30, 30, Fixed)]);
+        testing::solve(&mut w, Size(50, 50));
+        assert_eq!(w.inner().rect(), Rect::new(Coord(10, 10), Size(30, 30)));
+    }
+}
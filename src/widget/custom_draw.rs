@@ -0,0 +1,106 @@
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License in the LICENSE-APACHE file or at:
+//     https://www.apache.org/licenses/LICENSE-2.0
+
+//! Custom-drawn widget
+
+use std::fmt::{self, Debug};
+
+use crate::draw::{Draw, DrawHandle, Region, SizeHandle};
+use crate::event::ManagerState;
+use crate::geom::{Rect, Size};
+use crate::layout::{AxisInfo, SizeRules, StretchPolicy};
+use crate::macros::Widget;
+use crate::{CoreData, Layout};
+
+/// A widget drawn by a closure
+///
+/// This allows one-off custom drawing without implementing a theme or a draw
+/// pipe. Whenever the widget is drawn, the closure `f` is called with:
+///
+/// -   the draw device
+/// -   the current clip [`Region`] (thus drawing is clipped, e.g. when within
+///     a [`ScrollRegion`])
+/// -   the widget's rect, translated to draw coordinates
+/// -   the window's DPI factor, for crisp output
+///
+/// Methods of [`Draw`] may be used directly. Toolkit extensions such as
+/// [`DrawRounded`] and [`DrawShaded`] are available by downcasting the draw
+/// device via [`Draw::as_any_mut`] (e.g. to `kas_wgpu::draw::DrawPipe<()>`).
+///
+/// Drawn geometry may be cached between frames; call [`Manager::redraw`]
+/// with this widget's id whenever the output of `f` would change.
+///
+/// ```
+/// use kas::draw::Colour;
+/// use kas::geom::Size;
+/// use kas::widget::CustomDraw;
+///
+/// let widget = CustomDraw::new(Size(100, 20), |draw, region, rect, dpi_factor| {
+///     let inner = rect.shrink((2.0 * dpi_factor).round() as u32);
+///     draw.frame(region, rect, inner, Colour::grey(0.3));
+/// });
+/// ```
+///
+/// [`ScrollRegion`]: crate::widget::ScrollRegion
+/// [`DrawRounded`]: crate::draw::DrawRounded
+/// [`DrawShaded`]: crate::draw::DrawShaded
+/// [`Manager::redraw`]: crate::event::Manager::redraw
+#[derive(Clone, Widget)]
+#[widget]
+#[handler]
+pub struct CustomDraw<F: Fn(&mut dyn Draw, Region, Rect, f32)> {
+    #[core]
+    core: CoreData,
+    min_size: Size,
+    policy: StretchPolicy,
+    f: F,
+}
+
+impl<F: Fn(&mut dyn Draw, Region, Rect, f32)> Debug for CustomDraw<F> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "CustomDraw {{ core: {:?}, min_size: {:?}, policy: {:?}, ... }}",
+            self.core, self.min_size, self.policy
+        )
+    }
+}
+
+impl<F: Fn(&mut dyn Draw, Region, Rect, f32)> Layout for CustomDraw<F> {
+    fn size_rules(&mut self, _: &mut dyn SizeHandle, axis: AxisInfo) -> SizeRules {
+        let size = axis.extract_size(self.min_size);
+        SizeRules::new(size, size, self.policy)
+    }
+
+    fn draw(&self, draw_handle: &mut dyn DrawHandle, mgr: &ManagerState) {
+        let (region, offset, draw) = draw_handle.draw_device();
+        (self.f)(
+            draw,
+            region,
+            self.core.rect + offset,
+            mgr.dpi_factor() as f32,
+        );
+    }
+}
+
+impl<F: Fn(&mut dyn Draw, Region, Rect, f32)> CustomDraw<F> {
+    /// Construct with a minimum size (in physical pixels) and draw closure
+    ///
+    /// The widget may stretch according to [`StretchPolicy::Filler`].
+    pub fn new(min_size: Size, f: F) -> Self {
+        CustomDraw {
+            core: Default::default(),
+            min_size,
+            policy: StretchPolicy::Filler,
+            f,
+        }
+    }
+
+    /// Set the stretch policy
+    pub fn with_policy(mut self, policy: StretchPolicy) -> Self {
+        self.policy = policy;
+        self
+    }
+}
@@ -8,24 +8,36 @@
 //! KAS dialog boxes are pre-configured windows, usually allowing some
 //! customisation.
 
+use std::fmt::{self, Debug};
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::{Arc, Mutex, MutexGuard};
+use std::task::{Context, Poll, Waker};
+
+use crate::class::HasText;
 use crate::draw::SizeHandle;
-use crate::event::{Callback, Manager, Response, VoidMsg};
+use crate::event::{Callback, Manager, Response, UpdateHandle, VoidMsg};
 use crate::geom::Size;
 use crate::layout;
 use crate::macros::{VoidMsg, Widget};
-use crate::widget::{Label, TextButton};
-use crate::{CoreData, TkAction, Window};
+use crate::widget::{EditBox, Label, TextButton};
+use crate::{CoreData, TkAction, Widget, WidgetCore, Window};
 
 #[derive(Clone, Debug, VoidMsg)]
 enum DialogButton {
     Close,
+    Yes,
+    No,
+    Cancel,
+    Ok,
 }
 
 /// A simple message box.
-#[widget]
+///
+/// Both `Enter` and `Escape` close the message box.
+#[derive(Clone, Debug, Widget)]
 #[layout(vertical)]
 #[handler]
-#[derive(Clone, Debug, Widget)]
 pub struct MessageBox {
     #[core]
     core: CoreData,
@@ -52,11 +64,19 @@ impl MessageBox {
     fn handle_button(&mut self, mgr: &mut Manager, msg: DialogButton) -> Response<VoidMsg> {
         match msg {
             DialogButton::Close => mgr.send_action(TkAction::Close),
+            _ => (),
         };
         Response::None
     }
 }
 
+impl Widget for MessageBox {
+    fn configure(&mut self, mgr: &mut Manager) {
+        mgr.set_default_button(self.button.id());
+        mgr.set_cancel_button(self.button.id());
+    }
+}
+
 impl Window for MessageBox {
     fn title(&self) -> &str {
         &self.title
@@ -80,3 +100,443 @@ impl Window for MessageBox {
     }
     fn trigger_callback(&mut self, _index: usize, _: &mut Manager) {}
 }
+
+/// A confirmation dialog with "Yes", "No" and "Cancel" buttons
+///
+/// When a button is pressed, the closure passed on construction is called
+/// with the user's choice — `Some(true)` for "Yes", `Some(false)` for "No" or
+/// `None` for "Cancel" — then the dialog is closed.
+///
+/// `Enter` selects "Yes" and `Escape` selects "Cancel". Note that the closure
+/// is not called if the window is closed by other means.
+#[derive(Clone, Widget)]
+#[layout(grid)]
+#[handler(generics = <> where F: Fn(&mut Manager, Option<bool>))]
+pub struct ConfirmDialog<F: 'static> {
+    #[core]
+    core: CoreData,
+    #[layout_data]
+    layout_data: <Self as kas::LayoutData>::Data,
+    title: String,
+    #[widget(col = 0, row = 0, cspan = 3)]
+    label: Label,
+    #[widget(col = 0, row = 1, handler = handle_button)]
+    yes: TextButton<DialogButton>,
+    #[widget(col = 1, row = 1, handler = handle_button)]
+    no: TextButton<DialogButton>,
+    #[widget(col = 2, row = 1, handler = handle_button)]
+    cancel: TextButton<DialogButton>,
+    on_result: F,
+}
+
+impl<F> Debug for ConfirmDialog<F> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "ConfirmDialog {{ core: {:?}, title: {:?}, label: {:?}, ... }}",
+            self.core, self.title, self.label
+        )
+    }
+}
+
+impl<F: Fn(&mut Manager, Option<bool>)> ConfirmDialog<F> {
+    /// Construct, with a closure `f` which is called with the result
+    pub fn new<T: ToString, M: ToString>(title: T, message: M, f: F) -> Self {
+        ConfirmDialog {
+            core: Default::default(),
+            layout_data: Default::default(),
+            title: title.to_string(),
+            label: Label::new(message),
+            yes: TextButton::new("Yes", DialogButton::Yes),
+            no: TextButton::new("No", DialogButton::No),
+            cancel: TextButton::new("Cancel", DialogButton::Cancel),
+            on_result: f,
+        }
+    }
+
+    fn handle_button(&mut self, mgr: &mut Manager, msg: DialogButton) -> Response<VoidMsg> {
+        let result = match msg {
+            DialogButton::Yes => Some(true),
+            DialogButton::No => Some(false),
+            _ => None,
+        };
+        (self.on_result)(mgr, result);
+        mgr.send_action(TkAction::Close);
+        Response::None
+    }
+}
+
+impl<F: 'static> Widget for ConfirmDialog<F> {
+    fn configure(&mut self, mgr: &mut Manager) {
+        mgr.set_default_button(self.yes.id());
+        mgr.set_cancel_button(self.cancel.id());
+    }
+}
+
+impl<F: Fn(&mut Manager, Option<bool>) + 'static> Window for ConfirmDialog<F> {
+    fn title(&self) -> &str {
+        &self.title
+    }
+
+    fn resize(
+        &mut self,
+        size_handle: &mut dyn SizeHandle,
+        size: Size,
+    ) -> (Option<Size>, Option<Size>) {
+        let (min, max) = layout::solve(self, size_handle, size);
+        (Some(min), Some(max))
+    }
+
+    // doesn't support callbacks, so doesn't need to do anything here
+    fn callbacks(&self) -> Vec<(usize, Callback)> {
+        Vec::new()
+    }
+    fn final_callback(&self) -> Option<&'static dyn Fn(Box<dyn kas::Window>, &mut Manager)> {
+        None
+    }
+    fn trigger_callback(&mut self, _index: usize, _: &mut Manager) {}
+}
+
+/// A dialog requesting a line of text input
+///
+/// When the dialog is confirmed (via "Ok" or by pressing `Enter`), the closure
+/// passed on construction is called with `Some(text)`; when cancelled (via
+/// "Cancel" or `Escape`) it is called with `None`. The dialog is then closed.
+///
+/// The text entry has character focus when the dialog is opened. Note that the
+/// closure is not called if the window is closed by other means.
+#[derive(Clone, Widget)]
+#[layout(grid)]
+#[handler(generics = <> where F: Fn(&mut Manager, Option<String>))]
+pub struct InputDialog<F: 'static> {
+    #[core]
+    core: CoreData,
+    #[layout_data]
+    layout_data: <Self as kas::LayoutData>::Data,
+    title: String,
+    #[widget(col = 0, row = 0, cspan = 2)]
+    label: Label,
+    #[widget(col = 0, row = 1, cspan = 2, handler = handle_button)]
+    entry: EditBox<fn(&str) -> DialogButton>,
+    #[widget(col = 0, row = 2, handler = handle_button)]
+    ok: TextButton<DialogButton>,
+    #[widget(col = 1, row = 2, handler = handle_button)]
+    cancel: TextButton<DialogButton>,
+    on_result: F,
+}
+
+impl<F> Debug for InputDialog<F> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "InputDialog {{ core: {:?}, title: {:?}, entry: {:?}, ... }}",
+            self.core, self.title, self.entry
+        )
+    }
+}
+
+impl<F: Fn(&mut Manager, Option<String>)> InputDialog<F> {
+    /// Construct, with a `prompt`, initial `text` and a closure `f` which is
+    /// called with the result
+    pub fn new<T: ToString, P: ToString, S: Into<String>>(
+        title: T,
+        prompt: P,
+        text: S,
+        f: F,
+    ) -> Self {
+        fn activate(_: &str) -> DialogButton {
+            DialogButton::Ok
+        }
+        InputDialog {
+            core: Default::default(),
+            layout_data: Default::default(),
+            title: title.to_string(),
+            label: Label::new(prompt),
+            entry: EditBox::new(text).on_activate(activate as fn(&str) -> DialogButton),
+            ok: TextButton::new("Ok", DialogButton::Ok),
+            cancel: TextButton::new("Cancel", DialogButton::Cancel),
+            on_result: f,
+        }
+    }
+
+    fn handle_button(&mut self, mgr: &mut Manager, msg: DialogButton) -> Response<VoidMsg> {
+        let result = match msg {
+            DialogButton::Ok => Some(self.entry.get_text().to_string()),
+            _ => None,
+        };
+        (self.on_result)(mgr, result);
+        mgr.send_action(TkAction::Close);
+        Response::None
+    }
+}
+
+impl<F: 'static> Widget for InputDialog<F> {
+    fn configure(&mut self, mgr: &mut Manager) {
+        mgr.set_default_button(self.ok.id());
+        mgr.set_cancel_button(self.cancel.id());
+        self.entry.request_char_focus(mgr);
+    }
+}
+
+impl<F: Fn(&mut Manager, Option<String>) + 'static> Window for InputDialog<F> {
+    fn title(&self) -> &str {
+        &self.title
+    }
+
+    fn resize(
+        &mut self,
+        size_handle: &mut dyn SizeHandle,
+        size: Size,
+    ) -> (Option<Size>, Option<Size>) {
+        let (min, max) = layout::solve(self, size_handle, size);
+        (Some(min), Some(max))
+    }
+
+    // doesn't support callbacks, so doesn't need to do anything here
+    fn callbacks(&self) -> Vec<(usize, Callback)> {
+        Vec::new()
+    }
+    fn final_callback(&self) -> Option<&'static dyn Fn(Box<dyn kas::Window>, &mut Manager)> {
+        None
+    }
+    fn trigger_callback(&mut self, _index: usize, _: &mut Manager) {}
+}
+
+/// Error returned by a [`DialogHandle`] when no result is available
+///
+/// This occurs when the dialog is closed without sending a result (e.g. when
+/// closed by the window manager) or when the result was already taken.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct DialogClosed;
+
+enum ResultState<T> {
+    Pending(Option<Waker>),
+    Ready(T),
+    Closed,
+}
+
+fn lock<T>(state: &Mutex<ResultState<T>>) -> MutexGuard<'_, ResultState<T>> {
+    // A poisoned lock is still usable; we never leave the state inconsistent
+    state.lock().unwrap_or_else(|e| e.into_inner())
+}
+
+/// Construct a channel for the result of a dialog
+///
+/// The [`DialogSender`] should be passed to the dialog (usually via
+/// [`DialogSender::into_callback`]) while the [`DialogHandle`] is kept by the
+/// opener. See also [`Manager::add_dialog`].
+pub fn dialog_channel<T>() -> (DialogSender<T>, DialogHandle<T>) {
+    let state = Arc::new(Mutex::new(ResultState::Pending(None)));
+    let update = UpdateHandle::new();
+    let sender = DialogSender {
+        state: state.clone(),
+        update,
+    };
+    (sender, DialogHandle { state, update })
+}
+
+/// The sending half of a dialog result channel
+///
+/// Constructed by [`dialog_channel`]. If this is dropped without sending a
+/// value, the [`DialogHandle`] resolves to [`DialogClosed`].
+pub struct DialogSender<T> {
+    state: Arc<Mutex<ResultState<T>>>,
+    update: UpdateHandle,
+}
+
+impl<T> Debug for DialogSender<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "DialogSender {{ update: {:?}, ... }}", self.update)
+    }
+}
+
+impl<T> DialogSender<T> {
+    /// Send the result
+    ///
+    /// This resolves the [`DialogHandle`] and triggers its update handle (see
+    /// [`DialogHandle::update_handle`]). Only the first value sent is kept.
+    pub fn send(&self, mgr: &mut Manager, value: T) {
+        let mut state = lock(&self.state);
+        if let ResultState::Pending(waker) = &mut *state {
+            let waker = waker.take();
+            *state = ResultState::Ready(value);
+            // Release the lock first: the waker may poll the handle
+            drop(state);
+            if let Some(waker) = waker {
+                waker.wake();
+            }
+            mgr.trigger_update(self.update, 0);
+        }
+    }
+
+    /// Convert into a closure, suitable for constructing a dialog
+    ///
+    /// For example:
+    /// ```nocompile
+    /// ConfirmDialog::new("Quit", "Really quit?", sender.into_callback())
+    /// ```
+    pub fn into_callback(self) -> impl Fn(&mut Manager, T) {
+        move |mgr: &mut Manager, value| self.send(mgr, value)
+    }
+}
+
+impl<T> Drop for DialogSender<T> {
+    fn drop(&mut self) {
+        let mut state = lock(&self.state);
+        if let ResultState::Pending(waker) = &mut *state {
+            let waker = waker.take();
+            *state = ResultState::Closed;
+            drop(state);
+            if let Some(waker) = waker {
+                waker.wake();
+            }
+        }
+    }
+}
+
+/// Handle to the result of a dialog
+///
+/// Constructed by [`dialog_channel`]. The result may be polled via
+/// [`DialogHandle::try_result`] (for example, on update via
+/// [`DialogHandle::update_handle`]) or awaited, since this type implements
+/// [`Future`].
+///
+/// Dropping the handle does not affect the dialog; its result is discarded.
+pub struct DialogHandle<T> {
+    state: Arc<Mutex<ResultState<T>>>,
+    update: UpdateHandle,
+}
+
+impl<T> Debug for DialogHandle<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "DialogHandle {{ update: {:?}, ... }}", self.update)
+    }
+}
+
+impl<T> DialogHandle<T> {
+    /// Get the update handle triggered when a result is sent
+    ///
+    /// Widgets may subscribe to this via [`Manager::update_on_handle`] in order
+    /// to be notified of the result. Note that this is not triggered when the
+    /// dialog is closed without a result.
+    #[inline]
+    pub fn update_handle(&self) -> UpdateHandle {
+        self.update
+    }
+
+    /// Try taking the result
+    ///
+    /// Returns `None` while the dialog is open, otherwise the result. Once
+    /// taken, subsequent calls return `Some(Err(DialogClosed))`.
+    pub fn try_result(&mut self) -> Option<Result<T, DialogClosed>> {
+        let mut state = lock(&self.state);
+        match std::mem::replace(&mut *state, ResultState::Closed) {
+            ResultState::Pending(waker) => {
+                *state = ResultState::Pending(waker);
+                None
+            }
+            ResultState::Ready(value) => Some(Ok(value)),
+            ResultState::Closed => Some(Err(DialogClosed)),
+        }
+    }
+}
+
+impl<T> Future for DialogHandle<T> {
+    type Output = Result<T, DialogClosed>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context) -> Poll<Self::Output> {
+        let mut state = lock(&self.state);
+        match std::mem::replace(&mut *state, ResultState::Closed) {
+            ResultState::Pending(_) => {
+                *state = ResultState::Pending(Some(cx.waker().clone()));
+                Poll::Pending
+            }
+            ResultState::Ready(value) => Poll::Ready(Ok(value)),
+            ResultState::Closed => Poll::Ready(Err(DialogClosed)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::event::{InputEvent, VirtualKeyCode};
+    use crate::testing::Harness;
+    use std::cell::RefCell;
+    use std::rc::Rc;
+
+    #[test]
+    fn input_dialog_keys() {
+        let result = Rc::new(RefCell::new(None));
+        let r2 = result.clone();
+        let mut dialog = InputDialog::new("Title", "Prompt", "abc", move |_: &mut Manager, r| {
+            *r2.borrow_mut() = Some(r)
+        });
+        let mut h = Harness::new(&mut dialog);
+        assert!(h.manager().char_focus().is_some());
+
+        let mut mgr = h.manager();
+        let r = mgr.handle_synthetic(&mut dialog, InputEvent::ReceivedCharacter('d'));
+        assert!(r.is_none());
+        let r = mgr.handle_synthetic(&mut dialog, InputEvent::ReceivedCharacter('\r'));
+        assert!(r.is_none());
+        assert_eq!(result.borrow_mut().take(), Some(Some("abcd".to_string())));
+        assert_eq!(mgr.unwrap_action(), TkAction::Close);
+
+        let ev = InputEvent::KeyPressed(0, VirtualKeyCode::Escape);
+        assert!(mgr.handle_synthetic(&mut dialog, ev).is_none());
+        assert_eq!(result.borrow_mut().take(), Some(None));
+    }
+
+    #[test]
+    fn dialog_result() {
+        let (sender, mut handle) = dialog_channel();
+        let mut dialog = ConfirmDialog::new("Title", "Message", sender.into_callback());
+        let mut h = Harness::new(&mut dialog);
+        assert_eq!(handle.try_result(), None);
+
+        let mut mgr = h.manager();
+        let ev = InputEvent::KeyPressed(0, VirtualKeyCode::Return);
+        assert!(mgr.handle_synthetic(&mut dialog, ev).is_none());
+        assert_eq!(handle.try_result(), Some(Ok(Some(true))));
+        assert_eq!(handle.try_result(), Some(Err(DialogClosed)));
+
+        // Closing without a result resolves the handle
+        let (sender, mut handle) = dialog_channel::<Option<bool>>();
+        let dialog = ConfirmDialog::new("Title", "Message", sender.into_callback());
+        drop(dialog);
+        assert_eq!(handle.try_result(), Some(Err(DialogClosed)));
+    }
+
+    #[test]
+    fn dialog_wake_unlocked() {
+        use std::sync::atomic::{AtomicUsize, Ordering};
+        use std::task::Wake;
+
+        /// Counts wakes on which the result state was not locked
+        struct Probe(Arc<Mutex<ResultState<bool>>>, AtomicUsize);
+        impl Wake for Probe {
+            fn wake(self: Arc<Self>) {
+                if self.0.try_lock().is_ok() {
+                    self.1.fetch_add(1, Ordering::SeqCst);
+                }
+            }
+        }
+
+        let mut widget = crate::widget::Label::new("");
+        let mut h = Harness::new(&mut widget);
+        for send in [true, false].iter() {
+            let (sender, mut handle) = dialog_channel::<bool>();
+            let probe = Arc::new(Probe(handle.state.clone(), AtomicUsize::new(0)));
+            let waker = Waker::from(probe.clone());
+            let mut cx = Context::from_waker(&waker);
+            assert!(Pin::new(&mut handle).poll(&mut cx).is_pending());
+
+            match send {
+                true => sender.send(&mut h.manager(), true),
+                false => drop(sender),
+            }
+            assert_eq!(probe.1.load(Ordering::SeqCst), 1);
+        }
+    }
+}
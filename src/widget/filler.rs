@@ -3,10 +3,11 @@
 // You may obtain a copy of the License in the LICENSE-APACHE file or at:
 //     https://www.apache.org/licenses/LICENSE-2.0
 
-//! Filler widget
+//! Filler and spacer widgets
 
 use crate::draw::{DrawHandle, SizeHandle};
 use crate::event::ManagerState;
+use crate::geom::Size;
 use crate::layout::{AxisInfo, SizeRules, StretchPolicy};
 use crate::macros::Widget;
 use crate::{CoreData, Layout};
@@ -14,19 +15,20 @@ use crate::{CoreData, Layout};
 /// A space filler
 ///
 /// This widget has zero minimum size but can expand according to the given
-/// stretch policy.
+/// stretch policy and weight.
+#[derive(Clone, Debug, Default, Widget)]
 #[widget]
 #[handler]
-#[derive(Clone, Debug, Default, Widget)]
 pub struct Filler {
     #[core]
     core: CoreData,
     policy: StretchPolicy,
+    weight: u32,
 }
 
 impl Layout for Filler {
     fn size_rules(&mut self, _: &mut dyn SizeHandle, _: AxisInfo) -> SizeRules {
-        SizeRules::new(0, 0, self.policy)
+        SizeRules::new(0, 0, self.policy).with_weight(self.weight)
     }
 
     fn draw(&self, _: &mut dyn DrawHandle, _: &ManagerState) {}
@@ -48,6 +50,46 @@ impl Filler {
         Filler {
             core: Default::default(),
             policy,
+            weight: 1,
+        }
+    }
+
+    /// Set the stretch weight
+    ///
+    /// See [`SizeRules::with_weight`].
+    pub fn with_weight(mut self, weight: u32) -> Self {
+        self.weight = weight;
+        self
+    }
+}
+
+/// A fixed-size spacer
+///
+/// This widget has a fixed size (in pixels) and does not stretch. It may be
+/// used to insert a gap between widgets.
+#[derive(Clone, Debug, Default, Widget)]
+#[widget]
+#[handler]
+pub struct Spacer {
+    #[core]
+    core: CoreData,
+    size: Size,
+}
+
+impl Layout for Spacer {
+    fn size_rules(&mut self, _: &mut dyn SizeHandle, axis: AxisInfo) -> SizeRules {
+        SizeRules::fixed(axis.extract_size(self.size))
+    }
+
+    fn draw(&self, _: &mut dyn DrawHandle, _: &ManagerState) {}
+}
+
+impl Spacer {
+    /// Construct a spacer of the given `size`
+    pub fn new(size: Size) -> Self {
+        Spacer {
+            core: Default::default(),
+            size,
         }
     }
 }
@@ -13,7 +13,7 @@ use crate::geom::Coord;
 use crate::layout::{
     self, AxisInfo, Margins, RowPositionSolver, RulesSetter, RulesSolver, SizeRules,
 };
-use crate::{AlignHints, Directional, Horizontal, Vertical};
+use crate::{AlignHints, Directional, Horizontal, TextDirection, Vertical};
 use crate::{CoreData, Layout, TkAction, Widget, WidgetCore, WidgetId};
 use kas::geom::Rect;
 
@@ -77,6 +77,8 @@ pub struct List<D: Directional, W: Widget> {
     widgets: Vec<W>,
     data: layout::DynRowStorage,
     direction: D,
+    text_direction: Option<TextDirection>,
+    resolved_direction: TextDirection,
 }
 
 // We implement this manually, because the derive implementation cannot handle
@@ -143,14 +145,16 @@ impl<D: Directional, W: Widget> Layout for List<D, W> {
         );
         for (n, child) in self.widgets.iter_mut().enumerate() {
             solver.for_child(&mut self.data, n, |axis| {
-                child.size_rules(size_handle, axis)
+                layout::child_size_rules(child, size_handle, axis)
             });
         }
         solver.finish(&mut self.data, iter::empty(), iter::empty())
     }
 
-    fn set_rect(&mut self, size_handle: &mut dyn SizeHandle, rect: Rect, _: AlignHints) {
+    fn set_rect(&mut self, size_handle: &mut dyn SizeHandle, rect: Rect, align: AlignHints) {
         self.core.rect = rect;
+        let text_direction = self.text_direction.unwrap_or(align.direction);
+        self.resolved_direction = text_direction;
         let mut setter = layout::RowSetter::<D, Vec<u32>, _>::new(
             rect,
             Margins::ZERO,
@@ -159,15 +163,19 @@ impl<D: Directional, W: Widget> Layout for List<D, W> {
         );
 
         for (n, child) in self.widgets.iter_mut().enumerate() {
-            let align = AlignHints::default();
-            child.set_rect(size_handle, setter.child_rect(n), align);
+            let mut align = AlignHints::NONE;
+            align.baseline = setter.baseline();
+            align.direction = text_direction;
+            let child_rect = layout::child_rect_dir(setter.child_rect(n), rect, text_direction);
+            layout::child_set_rect(child, size_handle, child_rect, align);
         }
     }
 
     fn find_id(&self, coord: Coord) -> Option<WidgetId> {
-        let solver = RowPositionSolver::new(self.direction);
+        let solver =
+            RowPositionSolver::new(self.direction).with_text_direction(self.resolved_direction);
         if let Some(child) = solver.find_child(&self.widgets, coord) {
-            return child.find_id(coord);
+            return layout::child_find_id(child, coord);
         }
 
         // We should return Some(self), but hit a borrow check error.
@@ -176,9 +184,10 @@ impl<D: Directional, W: Widget> Layout for List<D, W> {
     }
 
     fn draw(&self, draw_handle: &mut dyn DrawHandle, mgr: &ManagerState) {
-        let solver = RowPositionSolver::new(self.direction);
+        let solver =
+            RowPositionSolver::new(self.direction).with_text_direction(self.resolved_direction);
         solver.for_children(&self.widgets, draw_handle.target_rect(), |w| {
-            w.draw(draw_handle, mgr)
+            layout::child_draw(w, draw_handle, mgr)
         });
     }
 }
@@ -209,6 +218,8 @@ impl<D: Directional + Default, W: Widget> List<D, W> {
             widgets,
             data: Default::default(),
             direction: Default::default(),
+            text_direction: None,
+            resolved_direction: TextDirection::Ltr,
         }
     }
 }
@@ -221,9 +232,30 @@ impl<D: Directional, W: Widget> List<D, W> {
             widgets,
             data: Default::default(),
             direction,
+            text_direction: None,
+            resolved_direction: TextDirection::Ltr,
         }
     }
 
+    /// Set the layout direction (inline)
+    ///
+    /// By default (`None`), the direction is inherited from the parent (see
+    /// [`TextDirection`]). This applies to this list and its children.
+    pub fn with_text_direction(mut self, direction: Option<TextDirection>) -> Self {
+        self.text_direction = direction;
+        self
+    }
+
+    /// Set the layout direction
+    ///
+    /// See [`List::with_text_direction`].
+    ///
+    /// Triggers a [reconfigure action](Manager::send_action).
+    pub fn set_text_direction(&mut self, mgr: &mut Manager, direction: Option<TextDirection>) {
+        self.text_direction = direction;
+        mgr.send_action(TkAction::Reconfigure);
+    }
+
     /// True if there are no child widgets
     pub fn is_empty(&self) -> bool {
         self.widgets.is_empty()
@@ -313,6 +345,21 @@ impl<D: Directional, W: Widget> List<D, W> {
         widget
     }
 
+    /// Move the child at position `from` to position `to`
+    ///
+    /// Other children between these positions are shifted by one place.
+    /// Panics if either index is out of bounds.
+    ///
+    /// Triggers a [reconfigure action](Manager::send_action) if `from != to`.
+    pub fn reorder(&mut self, mgr: &mut Manager, from: usize, to: usize) {
+        assert!(to < self.widgets.len());
+        if from != to {
+            let widget = self.widgets.remove(from);
+            self.widgets.insert(to, widget);
+            mgr.send_action(TkAction::Reconfigure);
+        }
+    }
+
     /// Append child widgets from an iterator
     ///
     /// Triggers a [reconfigure action](Manager::send_action) if any widgets
@@ -357,3 +404,83 @@ impl<D: Directional, W: Widget> List<D, W> {
         }
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::geom::{Rect, Size};
+    use crate::testing::{self, Harness};
+    use crate::widget::Spacer;
+
+    #[test]
+    fn hidden_reflow() {
+        let spacer = Spacer::new(Size(10, 10));
+        let mut row = Row::new(vec![spacer.clone(), spacer.clone(), spacer]);
+        let mut h = Harness::new(&mut row);
+        testing::solve(&mut row, Size(30, 10));
+        assert_eq!(row.get(2).unwrap().rect().pos, Coord(20, 0));
+
+        let mut mgr = h.manager();
+        row.get_mut(0).unwrap().set_visible(&mut mgr, false);
+        assert_eq!(mgr.unwrap_action(), TkAction::Reconfigure);
+        let mut mgr = h.manager();
+        row.get_mut(0).unwrap().set_visible(&mut mgr, false);
+        assert_eq!(mgr.unwrap_action(), TkAction::None);
+
+        testing::solve(&mut row, Size(20, 10));
+        assert_eq!(row.get(0).unwrap().rect().size, Size::ZERO);
+        assert_eq!(row.get(1).unwrap().rect().pos, Coord(0, 0));
+        assert_eq!(row.get(2).unwrap().rect().pos, Coord(10, 0));
+        assert_eq!(row.find_id(Coord(5, 5)), Some(row.get(1).unwrap().id()));
+    }
+
+    #[test]
+    fn rtl_mirrored() {
+        let children = vec![Spacer::new(Size(10, 10)), Spacer::new(Size(20, 10))];
+        let mut row = Row::new(children).with_text_direction(Some(TextDirection::Rtl));
+        let _ = Harness::new(&mut row);
+        testing::solve(&mut row, Size(30, 10));
+
+        // Children are placed from the right, in order
+        let rect = |i| row.get(i).unwrap().rect();
+        assert_eq!(rect(0), Rect::new(Coord(20, 0), Size(10, 10)));
+        assert_eq!(rect(1), Rect::new(Coord(0, 0), Size(20, 10)));
+        assert_eq!(row.find_id(Coord(25, 5)), Some(row.get(0).unwrap().id()));
+        assert_eq!(row.find_id(Coord(5, 5)), Some(row.get(1).unwrap().id()));
+
+        // Switching to left-to-right restores the usual order
+        let mut h = Harness::new(&mut row);
+        row.set_text_direction(&mut h.manager(), Some(TextDirection::Ltr));
+        testing::solve(&mut row, Size(30, 10));
+        let rect = |i| row.get(i).unwrap().rect();
+        assert_eq!(rect(0), Rect::new(Coord(0, 0), Size(10, 10)));
+        assert_eq!(rect(1), Rect::new(Coord(10, 0), Size(20, 10)));
+    }
+
+    #[test]
+    fn stored_state() {
+        let mut row = Row::new(vec![Spacer::default(), Spacer::default()]);
+        let mut h = Harness::new(&mut row);
+
+        let mut mgr = h.manager();
+        let (id0, id1) = (row.get(0).unwrap().id(), row.get(1).unwrap().id());
+        mgr.store_state(id0, 0u32);
+        mgr.store_state(id1, 1u32);
+        assert_eq!(mgr.stored_state::<u32>(id1), Some(&1));
+        assert_eq!(mgr.stored_state::<i32>(id1), None);
+
+        // State follows the widget to its new id; removed widgets lose state
+        row.insert(&mut mgr, 0, Spacer::default());
+        row.insert(&mut mgr, 0, Spacer::default());
+        row.remove(&mut mgr, 2);
+        assert_eq!(mgr.unwrap_action(), TkAction::Reconfigure);
+        h.configure(&mut row);
+        let mgr = h.manager();
+        let id = row.get(2).unwrap().id();
+        assert_ne!(id, id1);
+        assert_eq!(mgr.stored_state::<u32>(id), Some(&1));
+        for i in 0..2 {
+            assert_eq!(mgr.stored_state::<u32>(row.get(i).unwrap().id()), None);
+        }
+    }
+}
@@ -8,24 +8,43 @@
 //! KAS provides these common widgets for convenience, although there is no
 //! reason they cannot be implemented in user code.
 
+mod aspect_ratio;
+mod bound;
 mod button;
+mod canvas;
 mod checkbox;
+mod colour_picker;
+mod constrained;
+mod custom_draw;
 mod dialog;
 mod filler;
 mod list;
 mod radiobox;
+mod reorder;
 mod scroll;
 mod scrollbar;
+mod split;
+mod stack;
 mod text;
 mod window;
 
+pub use aspect_ratio::AspectRatio;
+pub use bound::Bound;
 pub use button::TextButton;
+pub use canvas::Canvas;
 pub use checkbox::{CheckBox, CheckBoxBare};
-pub use dialog::MessageBox;
-pub use filler::Filler;
+pub use colour_picker::ColourPicker;
+pub use constrained::Constrained;
+pub use custom_draw::CustomDraw;
+pub use dialog::{dialog_channel, DialogClosed, DialogHandle, DialogSender};
+pub use dialog::{ConfirmDialog, InputDialog, MessageBox};
+pub use filler::{Filler, Spacer};
 pub use list::{BoxColumn, BoxList, BoxRow, Column, List, Row};
 pub use radiobox::{RadioBox, RadioBoxBare};
+pub use reorder::{Reorder, ReorderColumn, ReorderList, ReorderRow};
 pub use scroll::ScrollRegion;
 pub use scrollbar::ScrollBar;
+pub use split::{HSplitPane, SplitPane, VSplitPane};
+pub use stack::Stack;
 pub use text::{EditBox, Label};
 pub use window::Window;
@@ -9,8 +9,9 @@ use std::convert::TryFrom;
 use std::fmt::{self, Debug};
 
 use super::Label;
+use crate::access::{AccessEvent, AccessNode, Role};
 use crate::class::HasBool;
-use crate::draw::{DrawHandle, SizeHandle};
+use crate::draw::{DrawHandle, SizeHandle, WidgetStyle};
 use crate::event::{Action, Handler, Manager, ManagerState, Response, UpdateHandle, VoidMsg};
 use crate::geom::Rect;
 use crate::layout::{AxisInfo, SizeRules};
@@ -48,12 +49,17 @@ impl<OT: 'static> Widget for RadioBoxBare<OT> {
         if state != self.state {
             self.state = state;
             mgr.redraw(self.id());
+            mgr.access_notify(AccessEvent::ValueChanged(self.id()));
         }
     }
 
     fn allow_focus(&self) -> bool {
         true
     }
+
+    fn access_node(&self) -> AccessNode {
+        AccessNode::new(self.id(), Role::RadioButton).with_checked(self.state)
+    }
 }
 
 impl<OT: 'static> Layout for RadioBoxBare<OT> {
@@ -142,8 +148,12 @@ impl<H> HasBool for RadioBoxBare<H> {
     }
 
     fn set_bool(&mut self, mgr: &mut Manager, state: bool) {
+        if state == self.state {
+            return;
+        }
         self.state = state;
         mgr.redraw(self.id());
+        mgr.access_notify(AccessEvent::ValueChanged(self.id()));
         if state {
             mgr.trigger_update(self.handle, self.id().into());
         }
@@ -164,6 +174,7 @@ impl Handler for RadioBoxBare<()> {
                 if !self.state {
                     self.state = true;
                     mgr.redraw(self.id());
+                    mgr.access_notify(AccessEvent::ValueChanged(self.id()));
                     mgr.trigger_update(self.handle, self.id().into());
                 }
                 Response::None
@@ -187,6 +198,7 @@ impl<M, H: Fn(WidgetId) -> M> Handler for RadioBoxBare<H> {
                 if !self.state {
                     self.state = true;
                     mgr.redraw(self.id());
+                    mgr.access_notify(AccessEvent::ValueChanged(self.id()));
                     mgr.trigger_update(self.handle, self.id().into());
                     ((self.on_activate)(self.id())).into()
                 } else {
@@ -199,11 +211,10 @@ impl<M, H: Fn(WidgetId) -> M> Handler for RadioBoxBare<H> {
 }
 
 /// A radiobox with optional label
+#[derive(Clone, Widget)]
 #[layout(horizontal, area=radiobox)]
-#[widget]
 #[handler(substitutions = (OT = ()))]
 #[handler(msg = M, generics = <M: From<VoidMsg>> where OT: Fn(WidgetId) -> M)]
-#[derive(Clone, Widget)]
 pub struct RadioBox<OT: 'static> {
     #[core]
     core: CoreData,
@@ -215,6 +226,22 @@ pub struct RadioBox<OT: 'static> {
     label: Label,
 }
 
+impl<OT: 'static> Widget for RadioBox<OT> {
+    /// Set a style override
+    ///
+    /// The style is also applied to the inner radio box and label, which draw the
+    /// radio mark and text.
+    fn with_style(mut self, style: WidgetStyle) -> Self
+    where
+        Self: Sized,
+    {
+        self.core.style = style;
+        self.radiobox = self.radiobox.with_style(style);
+        self.label = self.label.with_style(style);
+        self
+    }
+}
+
 impl<H> Debug for RadioBox<H> {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         write!(
@@ -0,0 +1,422 @@
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License in the LICENSE-APACHE file or at:
+//     https://www.apache.org/licenses/LICENSE-2.0
+
+//! Reorderable list
+
+use std::fmt::{self, Debug};
+
+use super::List;
+use crate::draw::{DrawHandle, SizeHandle};
+use crate::event::{
+    Action, CursorIcon, Event, Handler, Manager, ManagerState, PressSource, Response,
+};
+use crate::geom::{Coord, Rect, Size};
+use crate::layout::{self, AxisInfo, SizeRules};
+use crate::macros::{VoidMsg, Widget};
+use crate::{AlignHints, Directional, Horizontal, Vertical};
+use crate::{CoreData, Layout, Widget, WidgetCore, WidgetId};
+
+/// Distance a press must move before an item is lifted, in pixels
+const DRAG_THRESHOLD: i32 = 4;
+
+/// A reorderable row widget
+///
+/// See documentation of [`ReorderList`] type.
+pub type ReorderRow<W, F> = ReorderList<Horizontal, W, F>;
+
+/// A reorderable column widget
+///
+/// See documentation of [`ReorderList`] type.
+pub type ReorderColumn<W, F> = ReorderList<Vertical, W, F>;
+
+/// Message of a [`ReorderList`]: the item at `from` was moved to `to`
+///
+/// Both are indices into the list; `to` is the new index of the moved item.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, VoidMsg)]
+pub struct Reorder {
+    pub from: usize,
+    pub to: usize,
+}
+
+#[derive(Clone, Debug)]
+struct Drag {
+    source: PressSource,
+    from: usize,
+    start: Coord,
+    coord: Coord,
+    lifted: bool,
+    to: usize,
+}
+
+/// A [`List`] whose items may be reordered by drag-and-drop
+///
+/// Pressing an item (with the primary mouse button or touch) and dragging
+/// lifts the item, which then follows the cursor while a bar marks the drop
+/// position. Releasing moves the item (see [`List::reorder`]), then the
+/// closure `f` is called with a [`Reorder`] message and its result is returned
+/// from the event handler. Pressing `Escape` while dragging (or releasing
+/// outside the window) cancels the drag, leaving the order unchanged.
+///
+/// Only presses not handled by the item itself start a drag; for example
+/// labels may be dragged while buttons may not.
+///
+/// Within a [`ScrollRegion`], dragging near the edge of the viewport scrolls
+/// the region (see [`Action::AutoScroll`]). Scrolling continues while the
+/// cursor rests near the edge.
+///
+/// [`ScrollRegion`]: crate::widget::ScrollRegion
+#[derive(Clone, Widget)]
+#[widget]
+pub struct ReorderList<D: Directional, W: Widget, F: 'static> {
+    #[core]
+    core: CoreData,
+    direction: D,
+    #[widget]
+    list: List<D, W>,
+    drag: Option<Drag>,
+    on_reorder: F,
+}
+
+impl<D: Directional, W: Widget, F> Debug for ReorderList<D, W, F> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "ReorderList {{ core: {:?}, list: {:?}, drag: {:?}, ... }}",
+            self.core, self.list, self.drag
+        )
+    }
+}
+
+impl<D: Directional + Default, W: Widget, M, F: Fn(Reorder) -> M> ReorderList<D, W, F> {
+    /// Construct, with a closure `f` called on reorder
+    pub fn new(widgets: Vec<W>, f: F) -> Self {
+        ReorderList::new_with_direction(D::default(), widgets, f)
+    }
+}
+
+impl<D: Directional, W: Widget, M, F: Fn(Reorder) -> M> ReorderList<D, W, F> {
+    /// Construct with explicit direction, with a closure `f` called on reorder
+    pub fn new_with_direction(direction: D, widgets: Vec<W>, f: F) -> Self {
+        ReorderList {
+            core: Default::default(),
+            direction,
+            list: List::new_with_direction(direction, widgets),
+            drag: None,
+            on_reorder: f,
+        }
+    }
+}
+
+impl<D: Directional, W: Widget, F> ReorderList<D, W, F> {
+    /// Access the list directly
+    #[inline]
+    pub fn inner(&self) -> &List<D, W> {
+        &self.list
+    }
+
+    /// Access the list directly
+    ///
+    /// Modifying the list cancels any drag in progress.
+    #[inline]
+    pub fn inner_mut(&mut self) -> &mut List<D, W> {
+        self.drag = None;
+        &mut self.list
+    }
+
+    /// Update the drop position of a lifted item and request scrolling
+    fn drag_to<M>(&mut self, mgr: &mut Manager, coord: Coord) -> Response<M> {
+        let from = self.drag.as_ref().unwrap().from;
+        let to = self.drop_index(from, coord);
+        self.drag.as_mut().unwrap().to = to;
+        mgr.redraw(self.id());
+        Response::unhandled_action(Action::AutoScroll(coord))
+    }
+
+    fn child_rect(&self, index: usize) -> Rect {
+        self.list.get(index).map(|w| w.rect()).unwrap_or_default()
+    }
+
+    /// Extract the component along the list's axis
+    fn along(&self, coord: Coord) -> i32 {
+        match self.direction.is_vertical() {
+            false => coord.0,
+            true => coord.1,
+        }
+    }
+
+    /// True if list order matches coordinate order (not right-to-left)
+    fn is_forward(&self) -> bool {
+        let len = self.list.len();
+        len < 2 || self.along(self.child_rect(0).pos) <= self.along(self.child_rect(len - 1).pos)
+    }
+
+    /// Index at which the item `from` would be dropped, given `coord`
+    fn drop_index(&self, from: usize, coord: Coord) -> usize {
+        let p = self.along(coord);
+        let forward = self.is_forward();
+        (0..self.list.len())
+            .filter(|i| *i != from)
+            .filter(|i| {
+                let rect = self.child_rect(*i);
+                let centre = self.along(rect.pos) + self.along(Coord::from(rect.size)) / 2;
+                (centre < p) == forward
+            })
+            .count()
+    }
+
+    /// Rect of the bar marking the drop position
+    fn drop_marker(&self, drag: &Drag, thickness: u32) -> Rect {
+        let rest: Vec<usize> = (0..self.list.len()).filter(|i| *i != drag.from).collect();
+        let forward = self.is_forward();
+        let rect = self.core.rect;
+        // Position of the leading edge of the item at drop position
+        let p = match rest.get(drag.to) {
+            Some(i) => {
+                let r = self.child_rect(*i);
+                match forward {
+                    true => self.along(r.pos),
+                    false => self.along(r.pos + Coord::from(r.size)),
+                }
+            }
+            None => match rest.last() {
+                Some(i) => {
+                    let r = self.child_rect(*i);
+                    match forward {
+                        true => self.along(r.pos + Coord::from(r.size)),
+                        false => self.along(r.pos),
+                    }
+                }
+                None => self.along(rect.pos),
+            },
+        };
+        let p = p - (thickness / 2) as i32;
+        match self.direction.is_vertical() {
+            false => Rect::new(Coord(p, rect.pos.1), Size(thickness, rect.size.1)),
+            true => Rect::new(Coord(rect.pos.0, p), Size(rect.size.0, thickness)),
+        }
+    }
+}
+
+impl<D: Directional, W: Widget, F> Layout for ReorderList<D, W, F> {
+    fn size_rules(&mut self, size_handle: &mut dyn SizeHandle, axis: AxisInfo) -> SizeRules {
+        layout::child_size_rules(&mut self.list, size_handle, axis)
+    }
+
+    fn set_rect(&mut self, size_handle: &mut dyn SizeHandle, rect: Rect, align: AlignHints) {
+        self.core.rect = rect;
+        layout::child_set_rect(&mut self.list, size_handle, rect, align);
+    }
+
+    fn find_id(&self, coord: Coord) -> Option<WidgetId> {
+        self.list.find_id(coord).or(Some(self.id()))
+    }
+
+    fn draw(&self, draw_handle: &mut dyn DrawHandle, mgr: &ManagerState) {
+        let drag = match self.drag {
+            Some(ref drag) if drag.lifted => drag,
+            _ => return layout::child_draw(&self.list, draw_handle, mgr),
+        };
+
+        let target = draw_handle.target_rect();
+        for i in (0..self.list.len()).filter(|i| *i != drag.from) {
+            let child = self.list.get(i).unwrap();
+            if child.rect().intersection(&target).is_some() {
+                layout::child_draw(child, draw_handle, mgr);
+            }
+        }
+
+        let thickness = (2.0 * mgr.dpi_factor()).round().max(2.0) as u32;
+        draw_handle.outer_frame(self.drop_marker(drag, thickness));
+
+        // Draw the lifted item offset to follow the cursor
+        if let Some(child) = self.list.get(drag.from) {
+            draw_handle.clip_region(self.core.rect, drag.start - drag.coord, &mut |handle| {
+                layout::child_draw(child, handle, mgr)
+            });
+        }
+    }
+}
+
+impl<D, W, M, F> Handler for ReorderList<D, W, F>
+where
+    D: Directional,
+    W: Widget + Handler,
+    M: From<<W as Handler>::Msg>,
+    F: Fn(Reorder) -> M,
+{
+    type Msg = M;
+
+    fn handle_event(&mut self, mgr: &mut Manager, event: Event) -> Response<M> {
+        match event {
+            Event::PressMove { source, coord, .. } => {
+                let drag = match self.drag {
+                    Some(ref mut drag) if drag.source == source => drag,
+                    _ => return Response::None,
+                };
+                drag.coord = coord;
+                let d = coord - drag.start;
+                if !drag.lifted && d.0.abs().max(d.1.abs()) >= DRAG_THRESHOLD {
+                    drag.lifted = true;
+                }
+                if !drag.lifted {
+                    return Response::None;
+                }
+                self.drag_to(mgr, coord)
+            }
+            Event::Action(Action::AutoScroll(coord)) => {
+                // Re-sent by a scroll region: the content moved under the cursor
+                match self.drag {
+                    Some(ref mut drag) if drag.lifted => {
+                        drag.coord = coord;
+                        self.drag_to(mgr, coord)
+                    }
+                    _ => Response::None,
+                }
+            }
+            Event::PressEnd { source, end_id, .. } => {
+                let drag = match self.drag.take() {
+                    Some(drag) if drag.source == source => drag,
+                    drag => {
+                        self.drag = drag;
+                        return Response::None;
+                    }
+                };
+                if !drag.lifted {
+                    return Response::None;
+                }
+                mgr.redraw(self.id());
+                if end_id.is_none() || drag.to == drag.from {
+                    // Cancelled or unchanged
+                    return Response::None;
+                }
+                self.list.reorder(mgr, drag.from, drag.to);
+                let msg = Reorder {
+                    from: drag.from,
+                    to: drag.to,
+                };
+                Response::Msg((self.on_reorder)(msg))
+            }
+            e => Response::Unhandled(e),
+        }
+    }
+
+    fn handle(&mut self, mgr: &mut Manager, id: WidgetId, event: Event) -> Response<M> {
+        if id > self.list.id() {
+            return self.handle_event(mgr, event);
+        }
+        match self.list.handle(mgr, id, event) {
+            Response::Unhandled(Event::PressStart { source, coord })
+                if source.is_primary() && self.drag.is_none() =>
+            {
+                let list = &self.list;
+                let index = (0..list.len()).find(|i| id <= list.get(*i).unwrap().id());
+                let from = match index {
+                    Some(from) => from,
+                    None => return Response::Unhandled(Event::PressStart { source, coord }),
+                };
+                if !mgr.request_press_grab(source, self, coord, Some(CursorIcon::Grabbing)) {
+                    return Response::None;
+                }
+                self.drag = Some(Drag {
+                    source,
+                    from,
+                    start: coord,
+                    coord,
+                    lifted: false,
+                    to: from,
+                });
+                Response::None
+            }
+            r => Response::from(r),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::event::{InputEvent, MouseButton, VirtualKeyCode};
+    use crate::testing::{self, Harness};
+    use crate::widget::{ScrollRegion, Spacer};
+    use std::time::Instant;
+
+    #[test]
+    fn reorder_drag() {
+        let spacers = (1..=3).map(|i| Spacer::new(Size(10, 10 * i))).collect();
+        let mut list = ReorderColumn::new(spacers, |r: Reorder| r);
+        let mut h = Harness::new(&mut list);
+        testing::solve(&mut list, Size(10, 60));
+
+        let height =
+            |list: &ReorderColumn<Spacer, _>, i| list.inner().get(i).unwrap().rect().size.1;
+        let mut drag = |list: &mut _, y0, y1, cancel| {
+            let mut mgr = h.manager();
+            let ev = InputEvent::CursorMoved(Coord(5, y0));
+            assert!(mgr.handle_synthetic(list, ev).is_none());
+            let ev = InputEvent::MousePressed(MouseButton::Left);
+            assert!(mgr.handle_synthetic(list, ev).is_none());
+            // Without a scroll region, the auto-scroll request is unhandled
+            let ev = InputEvent::CursorMoved(Coord(5, y1));
+            let r = mgr.handle_synthetic(list, ev);
+            assert!(matches!(
+                r,
+                Response::Unhandled(Event::Action(Action::AutoScroll(_)))
+            ));
+            if cancel {
+                let ev = InputEvent::KeyPressed(0, VirtualKeyCode::Escape);
+                assert!(mgr.handle_synthetic(list, ev).is_none());
+            }
+            mgr.handle_synthetic(list, InputEvent::MouseReleased(MouseButton::Left))
+        };
+
+        // Escape cancels the drag
+        assert!(drag(&mut list, 5, 55, true).is_none());
+        assert_eq!(height(&list, 0), 10);
+
+        let r = drag(&mut list, 5, 55, false);
+        assert!(matches!(r, Response::Msg(Reorder { from: 0, to: 2 })));
+        assert_eq!(height(&list, 0), 20);
+        assert_eq!(height(&list, 2), 10);
+    }
+
+    #[test]
+    fn reorder_auto_scroll() {
+        let spacers = (0..6).map(|_| Spacer::new(Size(10, 20))).collect();
+        let mut region = ScrollRegion::new(ReorderColumn::new(spacers, |r: Reorder| r));
+        let mut h = Harness::new(&mut region);
+        testing::solve(&mut region, Size(10, 40));
+        assert_eq!(region.max_offset(), Coord(0, 80));
+
+        let tick = |h: &mut Harness, region: &mut ScrollRegion<_>| {
+            let time = h.state().next_resume().expect("timer scheduled");
+            std::thread::sleep(time.saturating_duration_since(Instant::now()));
+            h.manager().update_timer(region);
+        };
+
+        // Dragging 5 pixels beyond the bottom edge scrolls by 5
+        let mut mgr = h.manager();
+        let ev = InputEvent::CursorMoved(Coord(5, 5));
+        assert!(mgr.handle_synthetic(&mut region, ev).is_none());
+        let ev = InputEvent::MousePressed(MouseButton::Left);
+        assert!(mgr.handle_synthetic(&mut region, ev).is_none());
+        let ev = InputEvent::CursorMoved(Coord(5, 45));
+        assert!(mgr.handle_synthetic(&mut region, ev).is_none());
+        assert_eq!(region.offset(), Coord(0, 5));
+
+        // Scrolling continues while the cursor rests beyond the edge
+        tick(&mut h, &mut region);
+        assert_eq!(region.offset(), Coord(0, 10));
+        tick(&mut h, &mut region);
+        assert_eq!(region.offset(), Coord(0, 15));
+
+        // ... and stops once the drag ends
+        let ev = InputEvent::MouseReleased(MouseButton::Left);
+        let r = h.manager().handle_synthetic(&mut region, ev);
+        assert!(matches!(r, Response::Msg(Reorder { from: 0, .. })));
+        tick(&mut h, &mut region);
+        assert_eq!(region.offset(), Coord(0, 15));
+        assert_eq!(h.state().next_resume(), None);
+    }
+}
@@ -6,6 +6,7 @@
 //! Scroll region
 
 use std::fmt::Debug;
+use std::time::Duration;
 
 use super::ScrollBar;
 use crate::draw::{DrawHandle, SizeHandle, TextClass};
@@ -13,23 +14,30 @@ use crate::event::{
     Action, CursorIcon, Event, Handler, Manager, ManagerState, Response, ScrollDelta,
 };
 use crate::geom::{Coord, Rect, Size};
-use crate::layout::{AxisInfo, SizeRules};
+use crate::layout::{self, AxisInfo, SizeRules};
 use crate::macros::Widget;
 use crate::{AlignHints, Horizontal, Vertical};
 use crate::{CoreData, Layout, TkAction, Widget, WidgetCore, WidgetId};
 
+/// Interval between steps of auto-scrolling while the cursor rests near an edge
+const AUTO_SCROLL_INTERVAL: Duration = Duration::from_millis(50);
+
 /// A scrollable region
 ///
-/// This region supports scrolling via mouse wheel and drag.
+/// This region supports scrolling via mouse wheel and drag, and scrolls
+/// automatically while a child widget drags near its edge (see
+/// [`Action::AutoScroll`]). Auto-scrolling continues on a timer while the
+/// cursor rests near the edge: the region re-sends [`Action::AutoScroll`] to
+/// the dragging widget until it is handled or the region can scroll no
+/// further.
 /// Optionally, it can have scroll bars (see [`ScrollRegion::show_bars`] and
 /// [`ScrollRegion::with_bars`]).
 ///
 /// Scroll regions translate their contents by an `offset`, which has a
 /// minimum value of [`Coord::ZERO`] and a maximum value of
 /// [`ScrollRegion::max_offset`].
-#[widget]
 #[derive(Clone, Debug, Default, Widget)]
-pub struct ScrollRegion<W: Widget> {
+pub struct ScrollRegion<W: Widget + Handler> {
     #[core]
     core: CoreData,
     min_child_size: Size,
@@ -39,6 +47,7 @@ pub struct ScrollRegion<W: Widget> {
     scroll_rate: f32,
     auto_bars: bool,
     show_bars: (bool, bool),
+    auto_scroll: Option<(WidgetId, Coord)>,
     #[widget]
     horiz_bar: ScrollBar<Horizontal>,
     #[widget]
@@ -47,7 +56,7 @@ pub struct ScrollRegion<W: Widget> {
     child: W,
 }
 
-impl<W: Widget> ScrollRegion<W> {
+impl<W: Widget + Handler> ScrollRegion<W> {
     /// Construct a new scroll region around a child widget
     #[inline]
     pub fn new(child: W) -> Self {
@@ -60,6 +69,7 @@ impl<W: Widget> ScrollRegion<W> {
             scroll_rate: 30.0,
             auto_bars: false,
             show_bars: (false, false),
+            auto_scroll: None,
             horiz_bar: ScrollBar::new(),
             vert_bar: ScrollBar::new(),
             child,
@@ -131,9 +141,9 @@ impl<W: Widget> ScrollRegion<W> {
     }
 }
 
-impl<W: Widget> Layout for ScrollRegion<W> {
+impl<W: Widget + Handler> Layout for ScrollRegion<W> {
     fn size_rules(&mut self, size_handle: &mut dyn SizeHandle, axis: AxisInfo) -> SizeRules {
-        let mut rules = self.child.size_rules(size_handle, axis);
+        let mut rules = layout::child_size_rules(&mut self.child, size_handle, axis);
         if axis.is_horizontal() {
             self.min_child_size.0 = rules.min_size();
         } else {
@@ -152,7 +162,7 @@ impl<W: Widget> Layout for ScrollRegion<W> {
         }
     }
 
-    fn set_rect(&mut self, size_handle: &mut dyn SizeHandle, rect: Rect, _: AlignHints) {
+    fn set_rect(&mut self, size_handle: &mut dyn SizeHandle, rect: Rect, align: AlignHints) {
         self.core.rect = rect;
         // We use simplified layout code here
         let pos = rect.pos;
@@ -174,8 +184,7 @@ impl<W: Widget> Layout for ScrollRegion<W> {
 
         let child_size = self.inner_size.max(self.min_child_size);
         let child_rect = Rect::new(pos, child_size);
-        self.child
-            .set_rect(size_handle, child_rect, AlignHints::NONE);
+        layout::child_set_rect(&mut self.child, size_handle, child_rect, align.child());
         self.max_offset = Coord::from(child_size) - Coord::from(self.inner_size);
         self.offset = self.offset.max(Coord::ZERO).min(self.max_offset);
 
@@ -198,62 +207,111 @@ impl<W: Widget> Layout for ScrollRegion<W> {
     }
 
     fn find_id(&self, coord: Coord) -> Option<WidgetId> {
-        if self.horiz_bar.rect().contains(coord) {
-            self.horiz_bar.find_id(coord)
-        } else if self.vert_bar.rect().contains(coord) {
-            self.vert_bar.find_id(coord)
+        let viewport = Rect {
+            pos: self.core.rect.pos,
+            size: self.inner_size,
+        };
+        if self.show_bars.0 && self.horiz_bar.rect().contains(coord) {
+            layout::child_find_id(&self.horiz_bar, coord)
+        } else if self.show_bars.1 && self.vert_bar.rect().contains(coord) {
+            layout::child_find_id(&self.vert_bar, coord)
+        } else if viewport.contains(coord) {
+            // Content outside the viewport is clipped, thus not hit
+            layout::child_find_id(&self.child, coord + self.offset).or(Some(self.id()))
         } else {
-            self.child.find_id(coord + self.offset)
+            Some(self.id())
         }
     }
 
     fn draw(&self, draw_handle: &mut dyn DrawHandle, mgr: &ManagerState) {
         if self.show_bars.0 {
-            self.horiz_bar.draw(draw_handle, mgr);
+            layout::child_draw(&self.horiz_bar, draw_handle, mgr);
         }
         if self.show_bars.1 {
-            self.vert_bar.draw(draw_handle, mgr);
+            layout::child_draw(&self.vert_bar, draw_handle, mgr);
         }
         let rect = Rect {
             pos: self.core.rect.pos,
             size: self.inner_size,
         };
         draw_handle.clip_region(rect, self.offset, &mut |handle| {
-            self.child.draw(handle, mgr)
+            layout::child_draw(&self.child, handle, mgr)
         });
     }
 }
 
+impl<W: Widget + Handler> Widget for ScrollRegion<W> {
+    fn update_timer(&mut self, mgr: &mut Manager) -> Option<Duration> {
+        // Re-send the last auto-scroll request: the content has moved under
+        // the cursor, and the dragging widget may request further scrolling.
+        let (id, coord) = self.auto_scroll.take()?;
+        let _ = self.handle(mgr, id, Event::Action(Action::AutoScroll(coord)));
+        self.auto_scroll.map(|_| AUTO_SCROLL_INTERVAL)
+    }
+}
+
 impl<W: Widget + Handler> Handler for ScrollRegion<W> {
     type Msg = <W as Handler>::Msg;
 
-    fn handle(&mut self, mgr: &mut Manager, id: WidgetId, event: Event) -> Response<Self::Msg> {
-        let unhandled = |w: &mut Self, mgr: &mut Manager, event| match event {
-            Event::Action(Action::Scroll(delta)) => {
+    fn handle_event(&mut self, mgr: &mut Manager, event: Event) -> Response<Self::Msg> {
+        match event {
+            Event::Action(Action::Scroll(delta, coord)) => {
                 let d = match delta {
-                    ScrollDelta::LineDelta(x, y) => {
-                        Coord((-w.scroll_rate * x) as i32, (w.scroll_rate * y) as i32)
-                    }
+                    ScrollDelta::LineDelta(x, y) => Coord(
+                        (-self.scroll_rate * x) as i32,
+                        (self.scroll_rate * y) as i32,
+                    ),
                     ScrollDelta::PixelDelta(d) => d,
                 };
-                if w.set_offset(mgr, w.offset - d) {
-                    w.horiz_bar.set_value(mgr, w.offset.0 as u32);
-                    w.vert_bar.set_value(mgr, w.offset.1 as u32);
+                if self.set_offset(mgr, self.offset - d) {
+                    self.horiz_bar.set_value(mgr, self.offset.0 as u32);
+                    self.vert_bar.set_value(mgr, self.offset.1 as u32);
+                    Response::None
+                } else {
+                    Response::unhandled_action(Action::Scroll(delta, coord))
+                }
+            }
+            Event::Action(Action::AutoScroll(coord)) => {
+                // Scroll by the distance into (or beyond) an edge margin
+                let margin = (self.scroll_rate / 3.0) as i32;
+                let pos = self.core.rect.pos;
+                let end = pos + Coord::from(self.inner_size);
+                let dist = |c: i32, a: i32, b: i32| {
+                    if b - a <= 2 * margin {
+                        0
+                    } else if c < a + margin {
+                        c - (a + margin)
+                    } else if c > b - margin {
+                        c - (b - margin)
+                    } else {
+                        0
+                    }
+                };
+                let d = Coord(dist(coord.0, pos.0, end.0), dist(coord.1, pos.1, end.1));
+                if d != Coord::ZERO && self.set_offset(mgr, self.offset + d) {
+                    self.horiz_bar.set_value(mgr, self.offset.0 as u32);
+                    self.vert_bar.set_value(mgr, self.offset.1 as u32);
+                    if self.auto_scroll.is_some() {
+                        mgr.update_on_timer(AUTO_SCROLL_INTERVAL, self.id());
+                    }
                     Response::None
                 } else {
-                    Response::unhandled_action(Action::Scroll(delta))
+                    self.auto_scroll = None;
+                    Response::unhandled_action(Action::AutoScroll(coord))
                 }
             }
             Event::PressStart { source, coord } if source.is_primary() => {
-                mgr.request_press_grab(source, w, coord, Some(CursorIcon::Grabbing));
+                mgr.request_press_grab(source, self, coord, Some(CursorIcon::Grabbing));
                 Response::None
             }
             e @ _ => Response::Unhandled(e),
-        };
+        }
+    }
 
+    fn handle(&mut self, mgr: &mut Manager, id: WidgetId, event: Event) -> Response<Self::Msg> {
         if id <= self.horiz_bar.id() {
             return match Response::<Self::Msg>::try_from(self.horiz_bar.handle(mgr, id, event)) {
-                Ok(Response::Unhandled(event)) => unhandled(self, mgr, event),
+                Ok(Response::Unhandled(event)) => self.handle_event(mgr, event),
                 Ok(r) => r,
                 Err(msg) => {
                     self.set_offset(mgr, Coord(msg as i32, self.offset.1));
@@ -262,7 +320,7 @@ impl<W: Widget + Handler> Handler for ScrollRegion<W> {
             };
         } else if id <= self.vert_bar.id() {
             return match Response::<Self::Msg>::try_from(self.vert_bar.handle(mgr, id, event)) {
-                Ok(Response::Unhandled(event)) => unhandled(self, mgr, event),
+                Ok(Response::Unhandled(event)) => self.handle_event(mgr, event),
                 Ok(r) => r,
                 Err(msg) => {
                     self.set_offset(mgr, Coord(self.offset.0, msg as i32));
@@ -286,36 +344,51 @@ impl<W: Widget + Handler> Handler for ScrollRegion<W> {
             };
         }
 
-        let event = match event {
-            a @ Event::Action(_) => a,
-            Event::PressStart { source, coord } => Event::PressStart {
-                source,
-                coord: coord + self.offset,
-            },
-            Event::PressMove {
-                source,
-                coord,
-                delta,
-            } => Event::PressMove {
-                source,
-                coord: coord + self.offset,
-                delta,
-            },
-            Event::PressEnd {
-                source,
-                end_id,
-                coord,
-            } => Event::PressEnd {
-                source,
-                end_id,
-                coord: coord + self.offset,
-            },
-        };
-
+        let event = event.translate(self.offset);
         match self.child.handle(mgr, id, event) {
             Response::None => Response::None,
-            Response::Unhandled(event) => unhandled(self, mgr, event),
+            Response::Unhandled(event) => {
+                let event = event.translate(Coord::ZERO - self.offset);
+                self.auto_scroll = match event {
+                    Event::Action(Action::AutoScroll(coord)) => Some((id, coord)),
+                    _ => None,
+                };
+                self.handle_event(mgr, event)
+            }
             e @ _ => e,
         }
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::layout::Margins;
+    use crate::testing::{self, Harness};
+    use crate::widget::Spacer;
+
+    #[test]
+    fn scroll_child_margins() {
+        let child = Spacer::new(Size(20, 20)).with_margins(Margins::uniform(5, 0));
+        let mut region = ScrollRegion::new(child);
+        let _h = Harness::new(&mut region);
+        testing::solve(&mut region, Size(20, 20));
+
+        // Margins are part of the scrollable content but not the child's rect
+        assert_eq!(region.max_offset(), Coord(10, 10));
+        assert_eq!(region.inner().rect(), Rect::new(Coord(5, 5), Size(20, 20)));
+    }
+
+    #[test]
+    fn scroll_hidden_child() {
+        let mut region = ScrollRegion::new(Spacer::new(Size(20, 20)));
+        let mut h = Harness::new(&mut region);
+        region.inner_mut().set_visible(&mut h.manager(), false);
+        h.configure(&mut region);
+        testing::solve(&mut region, Size(10, 10));
+
+        // A hidden child reserves no space, thus there is nothing to scroll
+        assert_eq!(region.max_offset(), Coord::ZERO);
+        assert_eq!(region.inner().rect().size, Size::ZERO);
+    }
+}
@@ -7,18 +7,18 @@
 
 use std::fmt::Debug;
 
+use crate::access::{AccessEvent, AccessNode, Role};
 use crate::draw::{DrawHandle, SizeHandle};
 use crate::event::{CursorIcon, Event, Handler, Manager, ManagerState, PressSource, Response};
 use crate::geom::Rect;
 use crate::layout::{AxisInfo, SizeRules, StretchPolicy};
 use crate::macros::Widget;
-use crate::{AlignHints, CoreData, Directional, Layout, WidgetCore, WidgetId};
+use crate::{AlignHints, CoreData, Directional, Layout, Widget, WidgetCore, WidgetId};
 
 /// A scroll bar
 ///
 /// Scroll bars allow user-input of a value between 0 and a defined maximum,
 /// and allow the size of the handle to be specified.
-#[widget]
 #[derive(Clone, Debug, Default, Widget)]
 pub struct ScrollBar<D: Directional> {
     #[core]
@@ -106,6 +106,7 @@ impl<D: Directional> ScrollBar<D> {
         if value != self.value {
             self.value = value;
             mgr.redraw(self.id());
+            mgr.access_notify(AccessEvent::ValueChanged(self.id()));
         }
     }
 
@@ -151,12 +152,19 @@ impl<D: Directional> ScrollBar<D> {
         if value != self.value {
             self.value = value;
             mgr.redraw(self.id());
+            mgr.access_notify(AccessEvent::ValueChanged(self.id()));
             return true;
         }
         false
     }
 }
 
+impl<D: Directional> Widget for ScrollBar<D> {
+    fn access_node(&self) -> AccessNode {
+        AccessNode::new(self.id(), Role::ScrollBar).with_value(self.value)
+    }
+}
+
 impl<D: Directional> Layout for ScrollBar<D> {
     fn size_rules(&mut self, size_handle: &mut dyn SizeHandle, axis: AxisInfo) -> SizeRules {
         let (thickness, _, min_len) = size_handle.scrollbar();
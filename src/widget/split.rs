@@ -0,0 +1,397 @@
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License in the LICENSE-APACHE file or at:
+//     https://www.apache.org/licenses/LICENSE-2.0
+
+//! Resizable split pane
+
+use std::time::{Duration, Instant};
+
+use crate::draw::{DrawHandle, SizeHandle};
+use crate::event::{CursorIcon, Event, Handler, Manager, ManagerState, PressSource, Response};
+use crate::geom::{Coord, Rect};
+use crate::layout::{self, AxisInfo, SizeRules};
+use crate::macros::Widget;
+use crate::{AlignHints, Direction, Directional, Horizontal, TextDirection, Vertical};
+use crate::{CoreData, Layout, TkAction, Widget, WidgetCore, WidgetId};
+
+/// Maximum interval between presses of a double-click
+const DOUBLE_CLICK: Duration = Duration::from_millis(500);
+/// Maximum distance between presses of a double-click, in pixels
+const DOUBLE_CLICK_DIST: i32 = 4;
+
+/// A pane split left-right
+///
+/// See documentation of [`SplitPane`] type.
+pub type HSplitPane<W1, W2> = SplitPane<Horizontal, W1, W2>;
+
+/// A pane split top-bottom
+///
+/// See documentation of [`SplitPane`] type.
+pub type VSplitPane<W1, W2> = SplitPane<Vertical, W1, W2>;
+
+/// Two widgets separated by a draggable divider
+///
+/// The widgets are arranged along direction `D`; the divider may be dragged
+/// to resize them. The split is described by a *ratio*: the fraction of
+/// available space (excluding the divider) given to the first widget.
+/// Double-clicking the divider resets the ratio to its default (see
+/// [`SplitPane::with_ratio`]).
+///
+/// Neither widget is made smaller than its minimum size unless the pane is
+/// too small for both, in which case space is shared in proportion to their
+/// minimum sizes. Widgets never overlap.
+///
+/// Split panes may be nested; both widgets must have the same message type.
+#[derive(Clone, Debug, Widget)]
+pub struct SplitPane<D: Directional, W1: Widget, W2: Widget> {
+    #[core]
+    core: CoreData,
+    direction: D,
+    #[widget]
+    first: W1,
+    #[widget]
+    second: W2,
+    ratio: f32,
+    default_ratio: f32,
+    /// Minimum sizes of both widgets along the split axis
+    min: (u32, u32),
+    divider: Rect,
+    rtl: bool,
+    press_source: Option<PressSource>,
+    press_offset: i32,
+    last_press: Option<(Instant, Coord)>,
+}
+
+impl<D: Directional + Default, W1: Widget, W2: Widget> SplitPane<D, W1, W2> {
+    /// Construct, with an even split
+    pub fn new(first: W1, second: W2) -> Self {
+        SplitPane::new_with_direction(D::default(), first, second)
+    }
+}
+
+impl<D: Directional, W1: Widget, W2: Widget> SplitPane<D, W1, W2> {
+    /// Construct with explicit direction, with an even split
+    pub fn new_with_direction(direction: D, first: W1, second: W2) -> Self {
+        SplitPane {
+            core: Default::default(),
+            direction,
+            first,
+            second,
+            ratio: 0.5,
+            default_ratio: 0.5,
+            min: (0, 0),
+            divider: Rect::default(),
+            rtl: false,
+            press_source: None,
+            press_offset: 0,
+            last_press: None,
+        }
+    }
+
+    /// Set the initial and default ratio
+    ///
+    /// The `ratio` is clamped to the range `0.0..=1.0`.
+    #[inline]
+    pub fn with_ratio(mut self, ratio: f32) -> Self {
+        self.ratio = ratio.clamp(0.0, 1.0);
+        self.default_ratio = self.ratio;
+        self
+    }
+
+    /// Get the current ratio
+    ///
+    /// This is the fraction of available space requested for the first
+    /// widget; the actual split may differ due to size constraints.
+    #[inline]
+    pub fn ratio(&self) -> f32 {
+        self.ratio
+    }
+
+    /// Set the ratio
+    ///
+    /// The `ratio` is clamped to the range `0.0..=1.0`.
+    pub fn set_ratio(&mut self, mgr: &mut Manager, ratio: f32) {
+        let ratio = ratio.clamp(0.0, 1.0);
+        if ratio != self.ratio {
+            self.ratio = ratio;
+            mgr.send_action(TkAction::Reconfigure);
+        }
+    }
+
+    /// Reset the ratio to its default
+    #[inline]
+    pub fn reset_ratio(&mut self, mgr: &mut Manager) {
+        self.set_ratio(mgr, self.default_ratio);
+    }
+
+    /// Access the first widget
+    #[inline]
+    pub fn first(&self) -> &W1 {
+        &self.first
+    }
+
+    /// Access the first widget mutably
+    #[inline]
+    pub fn first_mut(&mut self) -> &mut W1 {
+        &mut self.first
+    }
+
+    /// Access the second widget
+    #[inline]
+    pub fn second(&self) -> &W2 {
+        &self.second
+    }
+
+    /// Access the second widget mutably
+    #[inline]
+    pub fn second_mut(&mut self) -> &mut W2 {
+        &mut self.second
+    }
+
+    /// Extract the component along the split axis
+    fn along(&self, coord: Coord) -> i32 {
+        match self.direction.is_vertical() {
+            false => coord.0,
+            true => coord.1,
+        }
+    }
+
+    /// Length of the first widget at `ratio`, given `avail` space for both
+    fn split(&self, ratio: f32, avail: u32) -> u32 {
+        let (min1, min2) = self.min;
+        if min1 + min2 > avail {
+            // Too small for both: share in proportion to minimum size
+            let len = u64::from(min1) * u64::from(avail) / u64::from(min1 + min2);
+            return len as u32;
+        }
+        let len = (ratio * avail as f32).round() as u32;
+        len.clamp(min1, avail - min2)
+    }
+
+    /// Space available to both widgets
+    fn avail(&self) -> u32 {
+        let len = self.along(Coord::from(self.core.rect.size)) as u32;
+        let divider = self.along(Coord::from(self.divider.size)) as u32;
+        len.saturating_sub(divider)
+    }
+}
+
+impl<D: Directional, W1: Widget, W2: Widget> Widget for SplitPane<D, W1, W2> {
+    fn cursor_icon(&self) -> CursorIcon {
+        // We are only hovered when the cursor is over the divider
+        match self.direction.is_vertical() {
+            false => CursorIcon::ColResize,
+            true => CursorIcon::RowResize,
+        }
+    }
+}
+
+impl<D: Directional, W1: Widget, W2: Widget> Layout for SplitPane<D, W1, W2> {
+    fn size_rules(&mut self, size_handle: &mut dyn SizeHandle, axis: AxisInfo) -> SizeRules {
+        let (thickness, _, _) = size_handle.scrollbar();
+        let dir = match axis.is_vertical() {
+            false => Direction::Horizontal,
+            true => Direction::Vertical,
+        };
+        if self.direction.is_vertical() == axis.is_vertical() {
+            let rules1 = layout::child_size_rules(&mut self.first, size_handle, axis);
+            let rules2 = layout::child_size_rules(&mut self.second, size_handle, axis);
+            self.min = (rules1.min_size(), rules2.min_size());
+            rules1 + thickness + rules2
+        } else if let Some(len) = axis.other() {
+            let len1 = self.split(self.ratio, len.saturating_sub(thickness));
+            let len2 = len.saturating_sub(thickness + len1);
+            let axis1 = AxisInfo::new(dir, Some(len1));
+            let rules1 = layout::child_size_rules(&mut self.first, size_handle, axis1);
+            let axis2 = AxisInfo::new(dir, Some(len2));
+            let rules2 = layout::child_size_rules(&mut self.second, size_handle, axis2);
+            rules1.max(rules2)
+        } else {
+            let rules1 = layout::child_size_rules(&mut self.first, size_handle, axis);
+            let rules2 = layout::child_size_rules(&mut self.second, size_handle, axis);
+            rules1.max(rules2)
+        }
+    }
+
+    fn set_rect(&mut self, size_handle: &mut dyn SizeHandle, rect: Rect, align: AlignHints) {
+        self.core.rect = rect;
+        let (thickness, _, _) = size_handle.scrollbar();
+        let len = self.along(Coord::from(rect.size)) as u32;
+        let thickness = thickness.min(len);
+        let len1 = self.split(self.ratio, len - thickness);
+        let len2 = len - thickness - len1;
+
+        let (mut rect1, mut divider, mut rect2) = (rect, rect, rect);
+        if self.direction.is_vertical() {
+            rect1.size.1 = len1;
+            divider.pos.1 += len1 as i32;
+            divider.size.1 = thickness;
+            rect2.pos.1 = divider.pos.1 + thickness as i32;
+            rect2.size.1 = len2;
+        } else {
+            rect1.size.0 = len1;
+            divider.pos.0 += len1 as i32;
+            divider.size.0 = thickness;
+            rect2.pos.0 = divider.pos.0 + thickness as i32;
+            rect2.size.0 = len2;
+        }
+
+        let direction = match self.direction.is_vertical() {
+            false => align.direction,
+            true => TextDirection::Ltr,
+        };
+        self.rtl = direction.is_rtl();
+        self.divider = layout::child_rect_dir(divider, rect, direction);
+
+        let mut children: [(&mut dyn Widget, Rect); 2] =
+            [(&mut self.first, rect1), (&mut self.second, rect2)];
+        for (child, child_rect) in children.iter_mut() {
+            let mut align = AlignHints::NONE;
+            align.direction = direction;
+            let child_rect = layout::child_rect_dir(*child_rect, rect, direction);
+            layout::child_set_rect(&mut **child, size_handle, child_rect, align);
+        }
+    }
+
+    fn find_id(&self, coord: Coord) -> Option<WidgetId> {
+        layout::child_find_id(&self.first, coord)
+            .or_else(|| layout::child_find_id(&self.second, coord))
+            .or(Some(self.id()))
+    }
+
+    fn draw(&self, draw_handle: &mut dyn DrawHandle, mgr: &ManagerState) {
+        let target = draw_handle.target_rect();
+        if self.first.rect().intersection(&target).is_some() {
+            layout::child_draw(&self.first, draw_handle, mgr);
+        }
+        if self.second.rect().intersection(&target).is_some() {
+            layout::child_draw(&self.second, draw_handle, mgr);
+        }
+
+        // The divider is drawn as a scroll bar handle perpendicular to the axis
+        let dir = match self.direction.is_vertical() {
+            false => Direction::Vertical,
+            true => Direction::Horizontal,
+        };
+        let hl = mgr.highlight_state(self.id());
+        draw_handle.scrollbar(self.divider, self.divider, dir, hl);
+    }
+}
+
+impl<D, W1, W2> Handler for SplitPane<D, W1, W2>
+where
+    D: Directional,
+    W1: Widget + Handler,
+    W2: Widget + Handler<Msg = <W1 as Handler>::Msg>,
+{
+    type Msg = <W1 as Handler>::Msg;
+
+    fn handle(&mut self, mgr: &mut Manager, id: WidgetId, event: Event) -> Response<Self::Msg> {
+        if id <= self.first.id() {
+            return self.first.handle(mgr, id, event);
+        } else if id <= self.second.id() {
+            return self.second.handle(mgr, id, event);
+        }
+        debug_assert!(id == self.id(), "Handler::handle: bad WidgetId");
+
+        match event {
+            Event::PressStart { source, coord } if source.is_primary() => {
+                let now = Instant::now();
+                if let Some((time, pos)) = self.last_press.take() {
+                    let d = coord - pos;
+                    if now - time <= DOUBLE_CLICK && d.0.abs().max(d.1.abs()) <= DOUBLE_CLICK_DIST {
+                        self.reset_ratio(mgr);
+                        return Response::None;
+                    }
+                }
+                self.last_press = Some((now, coord));
+                if !mgr.request_press_grab(source, self, coord, Some(self.cursor_icon())) {
+                    return Response::None;
+                }
+                self.press_source = Some(source);
+                self.press_offset = self.along(self.divider.pos) - self.along(coord);
+                Response::None
+            }
+            Event::PressMove { source, coord, .. } if Some(source) == self.press_source => {
+                let avail = self.avail();
+                if avail == 0 {
+                    return Response::None;
+                }
+                let pos = self.along(coord) + self.press_offset;
+                let len1 = match self.rtl {
+                    false => pos - self.along(self.core.rect.pos),
+                    true => {
+                        let end = self.core.rect.pos.0 + self.core.rect.size.0 as i32;
+                        end - pos - self.divider.size.0 as i32
+                    }
+                };
+                // Clamp to the constraints of both widgets, as when laying out
+                let ratio = len1.max(0) as f32 / avail as f32;
+                let len1 = self.split(ratio.min(1.0), avail);
+                self.set_ratio(mgr, len1 as f32 / avail as f32);
+                Response::None
+            }
+            Event::PressEnd { source, .. } if Some(source) == self.press_source => {
+                self.press_source = None;
+                Response::None
+            }
+            e => Manager::handle_generic(self, mgr, e),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::event::{InputEvent, MouseButton};
+    use crate::geom::Size;
+    use crate::testing::{self, Harness};
+    use crate::widget::Spacer;
+
+    #[test]
+    fn split_pane_drag() {
+        let mut split = HSplitPane::new(Spacer::new(Size(10, 10)), Spacer::new(Size(20, 10)));
+        let mut h = Harness::new(&mut split);
+        testing::solve(&mut split, Size(104, 10));
+        let width = |split: &HSplitPane<Spacer, Spacer>| {
+            let (w1, w2) = (split.first().rect().size.0, split.second().rect().size.0);
+            assert_eq!(split.second().rect().pos.0, (w1 + 4) as i32);
+            (w1, w2)
+        };
+        assert_eq!(width(&split), (50, 50));
+
+        let mut drag = |split: &mut _, x0, x1| {
+            let mut mgr = h.manager();
+            for ev in [
+                InputEvent::CursorMoved(Coord(x0, 5)),
+                InputEvent::MousePressed(MouseButton::Left),
+                InputEvent::CursorMoved(Coord(x1, 5)),
+                InputEvent::MouseReleased(MouseButton::Left),
+            ] {
+                assert!(mgr.handle_synthetic(split, ev).is_none());
+            }
+        };
+
+        drag(&mut split, 51, 21);
+        assert_eq!(split.ratio(), 0.2);
+        testing::solve(&mut split, Size(104, 10));
+        assert_eq!(width(&split), (20, 80));
+
+        // The first widget is clamped to its minimum size
+        drag(&mut split, 21, 1);
+        testing::solve(&mut split, Size(104, 10));
+        assert_eq!(width(&split), (10, 90));
+
+        // Double-click resets the ratio
+        drag(&mut split, 11, 11);
+        drag(&mut split, 11, 11);
+        assert_eq!(split.ratio(), 0.5);
+
+        // Too small for both: widgets shrink but do not overlap
+        testing::solve(&mut split, Size(34, 10));
+        assert_eq!(width(&split), (10, 20));
+        testing::solve(&mut split, Size(24, 10));
+        assert_eq!(width(&split), (6, 14));
+    }
+}
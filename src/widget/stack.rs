@@ -0,0 +1,266 @@
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License in the LICENSE-APACHE file or at:
+//     https://www.apache.org/licenses/LICENSE-2.0
+
+//! Stack (overlay) widget
+
+use crate::draw::{DrawHandle, SizeHandle};
+use crate::event::{Event, Handler, Manager, ManagerState, Response};
+use crate::geom::{Coord, Rect};
+use crate::layout::{self, AxisInfo, SizeRules};
+use crate::{AlignHints, CoreData, Layout, TkAction, Widget, WidgetCore, WidgetId};
+
+/// A stack of widgets, drawn on top of each other
+///
+/// All children are assigned the full area of the stack and are drawn in
+/// order, thus the last child is on top. The stack's [`SizeRules`] are the
+/// maximum over all children.
+///
+/// Hit-testing proceeds from the top child downwards. A press which is not
+/// handled by the child under the pointer is passed to children below.
+#[derive(Clone, Default, Debug)]
+pub struct Stack<W: Widget> {
+    core: CoreData,
+    widgets: Vec<W>,
+}
+
+// We implement this manually, because the derive implementation cannot handle
+// vectors of child widgets.
+impl<W: Widget> WidgetCore for Stack<W> {
+    #[inline]
+    fn core_data(&self) -> &CoreData {
+        &self.core
+    }
+    #[inline]
+    fn core_data_mut(&mut self) -> &mut CoreData {
+        &mut self.core
+    }
+
+    #[inline]
+    fn widget_name(&self) -> &'static str {
+        "Stack"
+    }
+
+    #[inline]
+    fn as_widget(&self) -> &dyn Widget {
+        self
+    }
+    #[inline]
+    fn as_widget_mut(&mut self) -> &mut dyn Widget {
+        self
+    }
+
+    #[inline]
+    fn len(&self) -> usize {
+        self.widgets.len()
+    }
+    #[inline]
+    fn get(&self, index: usize) -> Option<&dyn Widget> {
+        self.widgets.get(index).map(|w| w.as_widget())
+    }
+    #[inline]
+    fn get_mut(&mut self, index: usize) -> Option<&mut dyn Widget> {
+        self.widgets.get_mut(index).map(|w| w.as_widget_mut())
+    }
+
+    fn walk(&self, f: &mut dyn FnMut(&dyn Widget)) {
+        for child in &self.widgets {
+            child.walk(f);
+        }
+        f(self)
+    }
+    fn walk_mut(&mut self, f: &mut dyn FnMut(&mut dyn Widget)) {
+        for child in &mut self.widgets {
+            child.walk_mut(f);
+        }
+        f(self)
+    }
+}
+
+impl<W: Widget> Widget for Stack<W> {}
+
+impl<W: Widget> Layout for Stack<W> {
+    fn size_rules(&mut self, size_handle: &mut dyn SizeHandle, axis: AxisInfo) -> SizeRules {
+        let mut rules = SizeRules::EMPTY;
+        for child in &mut self.widgets {
+            rules = rules.max(layout::child_size_rules(child, size_handle, axis));
+        }
+        rules
+    }
+
+    fn set_rect(&mut self, size_handle: &mut dyn SizeHandle, rect: Rect, align: AlignHints) {
+        self.core.rect = rect;
+        for child in &mut self.widgets {
+            layout::child_set_rect(child, size_handle, rect, align.child());
+        }
+    }
+
+    fn find_id(&self, coord: Coord) -> Option<WidgetId> {
+        for child in self.widgets.iter().rev() {
+            if let Some(id) = layout::child_find_id(child, coord) {
+                return Some(id);
+            }
+        }
+        Some(self.id())
+    }
+
+    fn draw(&self, draw_handle: &mut dyn DrawHandle, mgr: &ManagerState) {
+        for child in self.widgets.iter() {
+            layout::child_draw(child, draw_handle, mgr);
+        }
+    }
+}
+
+impl<W: Widget + Handler> Handler for Stack<W> {
+    type Msg = <W as Handler>::Msg;
+
+    fn handle(&mut self, mgr: &mut Manager, id: WidgetId, event: Event) -> Response<Self::Msg> {
+        let index = match self.widgets.iter().position(|child| id <= child.id()) {
+            Some(index) => index,
+            None => {
+                debug_assert!(id == self.id(), "Handler::handle: bad WidgetId");
+                return Response::Unhandled(event);
+            }
+        };
+
+        let mut r = self.widgets[index].handle(mgr, id, event);
+        // Pass unhandled presses down the stack
+        for child in self.widgets[0..index].iter_mut().rev() {
+            let coord = match r {
+                Response::Unhandled(Event::PressStart { coord, .. }) => coord,
+                _ => break,
+            };
+            if let Some(id) = layout::child_find_id(child, coord) {
+                if let Response::Unhandled(event) = r {
+                    r = child.handle(mgr, id, event);
+                }
+            }
+        }
+        r
+    }
+}
+
+impl<W: Widget> Stack<W> {
+    /// Construct a new instance
+    ///
+    /// Widgets are drawn in order, thus the last widget is on top.
+    pub fn new(widgets: Vec<W>) -> Self {
+        Stack {
+            core: Default::default(),
+            widgets,
+        }
+    }
+
+    /// True if there are no child widgets
+    pub fn is_empty(&self) -> bool {
+        self.widgets.is_empty()
+    }
+
+    /// Returns the number of child widgets
+    pub fn len(&self) -> usize {
+        self.widgets.len()
+    }
+
+    /// Remove all child widgets
+    ///
+    /// Triggers a [reconfigure action](Manager::send_action) if any widget is
+    /// removed.
+    pub fn clear(&mut self, mgr: &mut Manager) {
+        if !self.widgets.is_empty() {
+            mgr.send_action(TkAction::Reconfigure);
+        }
+        self.widgets.clear();
+    }
+
+    /// Append a child widget (on top)
+    ///
+    /// Triggers a [reconfigure action](Manager::send_action).
+    pub fn push(&mut self, mgr: &mut Manager, widget: W) {
+        self.widgets.push(widget);
+        mgr.send_action(TkAction::Reconfigure);
+    }
+
+    /// Remove the top child widget
+    ///
+    /// Returns `None` if there are no children.
+    ///
+    /// Triggers a [reconfigure action](Manager::send_action) if any widget is
+    /// removed.
+    pub fn pop(&mut self, mgr: &mut Manager) -> Option<W> {
+        if !self.widgets.is_empty() {
+            mgr.send_action(TkAction::Reconfigure);
+        }
+        self.widgets.pop()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::class::HasBool;
+    use crate::event::InputEvent;
+    use crate::geom::Size;
+    use crate::testing::Harness;
+    use crate::widget::{CheckBoxBare, Label};
+
+    #[test]
+    fn stack_hit_test() {
+        fn toggle(state: bool) -> bool {
+            state
+        }
+        let mut stack = Stack::new(vec![
+            CheckBoxBare::new_on(toggle),
+            CheckBoxBare::new_on(toggle).state(true),
+        ]);
+        let mut h = Harness::new(&mut stack);
+        let rect = Rect::new(Coord(0, 0), Size(10, 10));
+        stack.core_data_mut().rect = rect;
+        stack.get_mut(0).unwrap().core_data_mut().rect = rect;
+        stack.get_mut(1).unwrap().core_data_mut().rect = rect;
+
+        let coord = Coord(5, 5);
+        assert_eq!(stack.find_id(coord), Some(stack.get(1).unwrap().id()));
+
+        let mut mgr = h.manager();
+        let r = mgr.handle_synthetic(&mut stack, InputEvent::TouchStart(1, coord));
+        assert!(r.is_none());
+        let r = mgr.handle_synthetic(&mut stack, InputEvent::TouchEnd(1, coord));
+        assert!(matches!(r, Response::Msg(false)));
+
+        // A hidden page passes input through to the page below
+        stack.get_mut(1).unwrap().set_visible(&mut mgr, false);
+        assert_eq!(mgr.unwrap_action(), TkAction::Reconfigure);
+        h.configure(&mut stack);
+        assert_eq!(stack.find_id(coord), Some(stack.get(0).unwrap().id()));
+        let mut mgr = h.manager();
+        let r = mgr.handle_synthetic(&mut stack, InputEvent::TouchStart(1, coord));
+        assert!(r.is_none());
+        let r = mgr.handle_synthetic(&mut stack, InputEvent::TouchEnd(1, coord));
+        assert!(matches!(r, Response::Msg(true)));
+        assert!(stack.widgets[0].get_bool() && !stack.widgets[1].get_bool());
+    }
+
+    #[test]
+    fn find_at_overlap() {
+        let mut stack = Stack::new(vec![Label::new("below"), Label::new("above")]);
+        let mut h = Harness::new(&mut stack);
+        let rect = Rect::new(Coord(0, 0), Size(10, 10));
+        stack.core_data_mut().rect = rect;
+        stack.get_mut(0).unwrap().core_data_mut().rect = rect;
+        stack.get_mut(1).unwrap().core_data_mut().rect = Rect::new(Coord(5, 0), Size(5, 10));
+        let (id0, id1) = (stack.get(0).unwrap().id(), stack.get(1).unwrap().id());
+
+        // The top-most (last drawn) widget wins where widgets overlap
+        assert_eq!(stack.find_at(Coord(7, 5)), Some(id1));
+        assert_eq!(stack.find_at(Coord(2, 5)), Some(id0));
+        assert_eq!(stack.find_at(Coord(10, 5)), None);
+
+        // Hidden widgets are excluded
+        let mut mgr = h.manager();
+        stack.get_mut(1).unwrap().set_visible(&mut mgr, false);
+        assert_eq!(stack.find_at(Coord(7, 5)), Some(id0));
+        stack.set_visible(&mut mgr, false);
+        assert_eq!(stack.find_at(Coord(7, 5)), None);
+    }
+}
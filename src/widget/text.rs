@@ -6,47 +6,67 @@
 //! Text widgets
 
 use std::fmt::{self, Debug};
+use std::time::Duration;
 
+use crate::access::{AccessEvent, AccessNode, Role};
 use crate::class::{Editable, HasText};
-use crate::draw::{DrawHandle, SizeHandle, TextClass};
+use crate::draw::{self, DrawHandle, LineHeight, SizeHandle, TextClass, TextStyle};
 use crate::event::{Action, CursorIcon, Handler, Manager, ManagerState, Response, VoidMsg};
 use crate::layout::{AxisInfo, SizeRules};
 use crate::macros::Widget;
-use crate::{Align, AlignHints, CoreData, Layout, Widget, WidgetCore};
-use kas::geom::Rect;
+use crate::{Align, AlignHints, CoreData, Layout, TextDirection, Widget, WidgetCore};
+use kas::geom::{Coord, Rect};
 
 /// A simple text label
-#[widget]
-#[handler]
 #[derive(Clone, Default, Debug, Widget)]
+#[handler]
 pub struct Label {
     #[core]
     core: CoreData,
     align: (Align, Align),
+    baseline: Option<u32>,
+    text_rect: Rect,
+    style: TextStyle,
     text: String,
 }
 
+impl Widget for Label {
+    fn access_node(&self) -> AccessNode {
+        AccessNode::new(self.id(), Role::Label).with_name(&self.text)
+    }
+}
+
 impl Layout for Label {
     fn size_rules(&mut self, size_handle: &mut dyn SizeHandle, axis: AxisInfo) -> SizeRules {
-        let rules = size_handle.text_bound(&self.text, TextClass::Label, axis);
+        let rules = size_handle.text_bound_styled(&self.text, TextClass::Label, self.style, axis);
         if axis.is_horizontal() {
             self.core_data_mut().rect.size.0 = rules.ideal_size();
         } else {
             self.core_data_mut().rect.size.1 = rules.ideal_size();
+            self.baseline = rules.baseline();
         }
         rules
     }
 
     fn set_rect(&mut self, _size_handle: &mut dyn SizeHandle, rect: Rect, align: AlignHints) {
-        self.align = (
-            align.horiz.unwrap_or(Align::Begin),
-            align.vert.unwrap_or(Align::Centre),
-        );
+        let ideal = self.rect().size.1;
+        self.text_rect = rect;
+        let valign = match align.baseline_offset(self.baseline, ideal, rect.size.1) {
+            Some(offset) => {
+                self.text_rect.pos.1 += offset as i32;
+                self.text_rect.size.1 -= offset;
+                Align::Begin
+            }
+            None => align.vert.unwrap_or(Align::Centre),
+        };
+        let halign = align.direction.halign(align.horiz.unwrap_or(Align::Begin));
+        self.align = (halign, valign);
         self.core_data_mut().rect = rect;
     }
 
     fn draw(&self, draw_handle: &mut dyn DrawHandle, _: &ManagerState) {
-        draw_handle.text(self.core.rect, &self.text, TextClass::Label, self.align);
+        let class = TextClass::Label;
+        draw_handle.text_styled(self.text_rect, &self.text, class, self.style, self.align);
     }
 }
 
@@ -56,9 +76,35 @@ impl Label {
         Label {
             core: Default::default(),
             align: Default::default(),
+            baseline: None,
+            text_rect: Default::default(),
+            style: Default::default(),
             text: text.to_string(),
         }
     }
+
+    /// Set the line height
+    ///
+    /// [`LineHeight::Absolute`] is in logical pixels.
+    pub fn line_height(mut self, line_height: LineHeight) -> Self {
+        self.style.line_height = line_height;
+        self
+    }
+
+    /// Set extra space between characters, in logical pixels
+    pub fn letter_spacing(mut self, spacing: f32) -> Self {
+        self.style.letter_spacing = spacing;
+        self
+    }
+
+    /// Set the distance between tab stops, in logical pixels
+    ///
+    /// Tab characters advance to the next tab stop, allowing columns to be
+    /// aligned. By default, tab stops are eight spaces apart.
+    pub fn tab_width(mut self, width: f32) -> Self {
+        self.style.tab_width = Some(width);
+        self
+    }
 }
 
 impl<T> From<T> for Label
@@ -69,6 +115,9 @@ where
         Label {
             core: Default::default(),
             align: Default::default(),
+            baseline: None,
+            text_rect: Default::default(),
+            style: Default::default(),
             text: String::from(text),
         }
     }
@@ -82,6 +131,7 @@ impl HasText for Label {
     fn set_string(&mut self, mgr: &mut Manager, text: String) {
         self.text = text;
         mgr.redraw(self.id());
+        mgr.access_notify(AccessEvent::ValueChanged(self.id()));
     }
 }
 
@@ -100,21 +150,43 @@ impl Default for LastEdit {
     }
 }
 
+/// Interval between toggles of the text caret
+const CARET_BLINK: Duration = Duration::from_millis(500);
+
+/// Result of an edit operation
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum EditAction {
+    None,
+    Activate,
+    Edit,
+}
+
 /// An editable, single-line text box.
+///
+/// This widget may optionally have handlers for activation (see
+/// [`EditBox::on_activate`]) and edits (see [`EditBox::on_edit`]). Both are
+/// passed the current text and should return the same message type.
 #[derive(Clone, Default, Widget)]
-pub struct EditBox<H: 'static> {
+pub struct EditBox<A: 'static = (), E: 'static = ()> {
     #[core]
     core: CoreData,
     text_rect: Rect,
+    baseline: Option<u32>,
+    direction: TextDirection,
     editable: bool,
     multi_line: bool,
+    autofocus: bool,
+    caret_hidden: bool,
+    caret_hold: bool,
+    style: TextStyle,
     text: String,
     old_state: Option<String>,
     last_edit: LastEdit,
-    on_activate: H,
+    on_activate: A,
+    on_edit: E,
 }
 
-impl<H> Debug for EditBox<H> {
+impl<A, E> Debug for EditBox<A, E> {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         write!(
             f,
@@ -124,17 +196,48 @@ impl<H> Debug for EditBox<H> {
     }
 }
 
-impl<H: 'static> Widget for EditBox<H> {
+impl<A: 'static, E: 'static> Widget for EditBox<A, E> {
+    fn configure(&mut self, _: &mut Manager) {
+        // Scheduled updates are cleared, so the caret must not stay hidden
+        self.caret_hidden = false;
+    }
+
+    fn update_timer(&mut self, mgr: &mut Manager) -> Option<Duration> {
+        if mgr.char_focus() != Some(self.id()) {
+            self.caret_hidden = false;
+            return None;
+        }
+        if self.caret_hold {
+            self.caret_hold = false;
+        } else {
+            self.caret_hidden = !self.caret_hidden;
+            mgr.redraw(self.id());
+        }
+        Some(CARET_BLINK)
+    }
+
     fn allow_focus(&self) -> bool {
         true
     }
 
+    fn autofocus(&self) -> bool {
+        self.autofocus
+    }
+
+    fn access_node(&self) -> AccessNode {
+        AccessNode::new(self.id(), Role::TextEntry).with_value(&self.text)
+    }
+
     fn cursor_icon(&self) -> CursorIcon {
-        CursorIcon::Text
+        if self.editable {
+            CursorIcon::Text
+        } else {
+            CursorIcon::Default
+        }
     }
 }
 
-impl<H: 'static> Layout for EditBox<H> {
+impl<A: 'static, E: 'static> Layout for EditBox<A, E> {
     fn size_rules(&mut self, size_handle: &mut dyn SizeHandle, axis: AxisInfo) -> SizeRules {
         let class = if self.multi_line {
             TextClass::EditMulti
@@ -143,26 +246,47 @@ impl<H: 'static> Layout for EditBox<H> {
         };
         let sides = size_handle.edit_surround();
         let margin = size_handle.inner_margin();
-        let rules = SizeRules::fixed(axis.extract_size(sides.0 + sides.1 + margin))
-            + size_handle.text_bound(&self.text, class, axis);
+        let text_rules = size_handle.text_bound_styled(&self.text, class, self.style, axis);
+        let mut rules =
+            SizeRules::fixed(axis.extract_size(sides.0 + sides.1 + margin)) + text_rules;
         if axis.is_horizontal() {
             self.core_data_mut().rect.size.0 = rules.ideal_size();
         } else {
             self.core_data_mut().rect.size.1 = rules.ideal_size();
+            // Text is drawn at the top of text_rect
+            self.baseline = match self.multi_line {
+                false => text_rules.baseline().map(|b| b + sides.0 .1),
+                true => None,
+            };
+            if let Some(baseline) = self.baseline {
+                rules = rules.with_baseline(baseline);
+            }
         }
         rules
     }
 
     fn set_rect(&mut self, size_handle: &mut dyn SizeHandle, rect: Rect, align: AlignHints) {
-        let valign = if self.multi_line {
-            Align::Stretch
-        } else {
-            Align::Centre
+        let ideal = self.rect().size;
+        let rect = match align.baseline_offset(self.baseline, ideal.1, rect.size.1) {
+            Some(offset) => {
+                let mut rect = align
+                    .complete(Align::Stretch, Align::Begin, ideal)
+                    .apply(rect);
+                rect.pos.1 += offset as i32;
+                rect.size.1 = ideal.1;
+                rect
+            }
+            None => {
+                let valign = if self.multi_line {
+                    Align::Stretch
+                } else {
+                    Align::Centre
+                };
+                align.complete(Align::Stretch, valign, ideal).apply(rect)
+            }
         };
-        let rect = align
-            .complete(Align::Stretch, valign, self.rect().size)
-            .apply(rect);
 
+        self.direction = align.direction;
         let sides = size_handle.edit_surround();
         self.text_rect = Rect {
             pos: rect.pos + sides.0,
@@ -179,33 +303,45 @@ impl<H: 'static> Layout for EditBox<H> {
         };
         let highlights = mgr.highlight_state(self.id());
         draw_handle.edit_box(self.core.rect, highlights);
-        let align = (Align::Begin, Align::Begin);
+        let align = (self.direction.halign(Align::Begin), Align::Begin);
         let mut text = &self.text;
         let mut _string;
-        if highlights.char_focus {
+        if highlights.char_focus && !self.caret_hidden {
             _string = self.text.clone();
+            // The caret follows the last char, in that char's direction
+            let mark = draw::caret_mark(&_string);
             _string.push('|');
+            _string.extend(mark);
             text = &_string;
         }
-        draw_handle.text(self.text_rect, text, class, align);
+        draw_handle.text_styled(self.text_rect, text, class, self.style, align);
     }
 }
 
-impl EditBox<()> {
+impl EditBox<(), ()> {
     /// Construct an `EditBox` with the given inital `text`.
     pub fn new<S: Into<String>>(text: S) -> Self {
         EditBox {
             core: Default::default(),
             text_rect: Default::default(),
+            baseline: None,
+            direction: TextDirection::Ltr,
             editable: true,
             multi_line: false,
+            autofocus: false,
+            caret_hidden: false,
+            caret_hold: false,
+            style: Default::default(),
             text: text.into(),
             old_state: None,
             last_edit: LastEdit::None,
             on_activate: (),
+            on_edit: (),
         }
     }
+}
 
+impl<E> EditBox<(), E> {
     /// Set the event handler to be called on activation.
     ///
     /// The closure `f` is called when the `EditBox` is activated (when the
@@ -213,21 +349,60 @@ impl EditBox<()> {
     ///
     /// Technically, this consumes `self` and reconstructs another `EditBox`
     /// with a different parameterisation.
-    pub fn on_activate<R, H: Fn(&str) -> R>(self, f: H) -> EditBox<H> {
+    pub fn on_activate<R, A: Fn(&str) -> R>(self, f: A) -> EditBox<A, E> {
         EditBox {
             core: self.core,
             text_rect: self.text_rect,
+            baseline: self.baseline,
+            direction: self.direction,
             editable: self.editable,
             multi_line: self.multi_line,
+            autofocus: self.autofocus,
+            caret_hidden: self.caret_hidden,
+            caret_hold: self.caret_hold,
+            style: self.style,
             text: self.text,
             old_state: self.old_state,
             last_edit: self.last_edit,
             on_activate: f,
+            on_edit: self.on_edit,
         }
     }
 }
 
-impl<H> EditBox<H> {
+impl<A> EditBox<A, ()> {
+    /// Set the event handler to be called on edit.
+    ///
+    /// The closure `f` is called with the new text whenever the text is
+    /// changed by the user (including by paste, deletion and undo, but not
+    /// via [`HasText::set_string`]). Its result is returned from the event
+    /// handler. Actions with nothing to do (e.g. backspace on empty text or
+    /// pasting an empty clipboard) do not count as edits.
+    ///
+    /// Technically, this consumes `self` and reconstructs another `EditBox`
+    /// with a different parameterisation.
+    pub fn on_edit<R, E: Fn(&str) -> R>(self, f: E) -> EditBox<A, E> {
+        EditBox {
+            core: self.core,
+            text_rect: self.text_rect,
+            baseline: self.baseline,
+            direction: self.direction,
+            editable: self.editable,
+            multi_line: self.multi_line,
+            autofocus: self.autofocus,
+            caret_hidden: self.caret_hidden,
+            caret_hold: self.caret_hold,
+            style: self.style,
+            text: self.text,
+            old_state: self.old_state,
+            last_edit: self.last_edit,
+            on_activate: self.on_activate,
+            on_edit: f,
+        }
+    }
+}
+
+impl<A, E> EditBox<A, E> {
     /// Set whether this `EditBox` is editable.
     pub fn editable(mut self, editable: bool) -> Self {
         self.editable = editable;
@@ -240,9 +415,53 @@ impl<H> EditBox<H> {
         self
     }
 
-    fn received_char(&mut self, mgr: &mut Manager, c: char) -> bool {
+    /// Set whether this `EditBox` takes keyboard focus when the window opens
+    pub fn with_autofocus(mut self, autofocus: bool) -> Self {
+        self.autofocus = autofocus;
+        self
+    }
+
+    /// Set the line height
+    ///
+    /// [`LineHeight::Absolute`] is in logical pixels.
+    pub fn line_height(mut self, line_height: LineHeight) -> Self {
+        self.style.line_height = line_height;
+        self
+    }
+
+    /// Set extra space between characters, in logical pixels
+    pub fn letter_spacing(mut self, spacing: f32) -> Self {
+        self.style.letter_spacing = spacing;
+        self
+    }
+
+    pub(crate) fn request_char_focus(&mut self, mgr: &mut Manager) {
+        mgr.request_char_focus(self.id());
+        // Place any IME candidate window just below the edit box
+        let rect = self.core.rect;
+        mgr.set_ime_position(rect.pos + Coord(0, rect.size.1 as i32));
+        self.reset_caret(mgr);
+    }
+
+    /// Show the caret and restart blinking
+    ///
+    /// The caret stays solid for at least one blink interval, keeping it
+    /// visible while typing. Blinking is disabled under reduced motion.
+    fn reset_caret(&mut self, mgr: &mut Manager) {
+        if self.caret_hidden {
+            self.caret_hidden = false;
+            mgr.redraw(self.id());
+        }
+        if !mgr.access_prefs().reduced_motion {
+            self.caret_hold = true;
+            mgr.update_on_timer(CARET_BLINK, self.id());
+        }
+    }
+
+    fn received_char(&mut self, mgr: &mut Manager, c: char) -> EditAction {
+        self.reset_caret(mgr);
         if !self.editable {
-            return false;
+            return EditAction::None;
         }
 
         // TODO: Text selection and editing (see Unicode std. section 5.11)
@@ -253,37 +472,43 @@ impl<H> EditBox<H> {
                 '\u{03}' /* copy */ => {
                     // we don't yet have selection support, so just copy everything
                     mgr.set_clipboard(self.text.clone());
+                    return EditAction::None;
                 }
                 '\u{08}' /* backspace */  => {
+                    if self.text.is_empty() {
+                        return EditAction::None;
+                    }
                     if self.last_edit != LastEdit::Backspace {
                         self.old_state = Some(self.text.clone());
                         self.last_edit = LastEdit::Backspace;
                     }
                     self.text.pop();
                 }
-                '\u{09}' /* tab */ => (),
-                '\u{0A}' /* line feed */ => (),
-                '\u{0B}' /* vertical tab */ => (),
-                '\u{0C}' /* form feed */ => (),
-                '\u{0D}' /* carriage return (\r) */ => return true,
+                '\u{09}' /* tab */ => return EditAction::None,
+                '\u{0A}' /* line feed */ => return EditAction::None,
+                '\u{0B}' /* vertical tab */ => return EditAction::None,
+                '\u{0C}' /* form feed */ => return EditAction::None,
+                '\u{0D}' /* carriage return (\r) */ => return EditAction::Activate,
                 '\u{16}' /* paste */ => {
+                    let content = mgr.get_clipboard().unwrap_or_default();
+                    // We cut the content short on control characters and
+                    // ignore them (preventing line-breaks and ignoring any
+                    // actions such as recursive-paste).
+                    let mut end = content.len();
+                    for (i, b) in content.as_bytes().iter().cloned().enumerate() {
+                        if b < 0x20 || (0x7f..=0x9f).contains(&b) {
+                            end = i;
+                            break;
+                        }
+                    }
+                    if end == 0 {
+                        return EditAction::None;
+                    }
                     if self.last_edit != LastEdit::Paste {
                         self.old_state = Some(self.text.clone());
                         self.last_edit = LastEdit::Paste;
                     }
-                    if let Some(content) = mgr.get_clipboard() {
-                        // We cut the content short on control characters and
-                        // ignore them (preventing line-breaks and ignoring any
-                        // actions such as recursive-paste).
-                        let mut end = content.len();
-                        for (i, b) in content.as_bytes().iter().cloned().enumerate() {
-                            if b < 0x20 || (b >= 0x7f && b <= 0x9f) {
-                                end = i;
-                                break;
-                            }
-                        }
-                        self.text.push_str(&content[0..end]);
-                    }
+                    self.text.push_str(&content[0..end]);
                 }
                 '\u{1A}' /* undo and redo */ => {
                     // TODO: maintain full edit history (externally?)
@@ -291,17 +516,22 @@ impl<H> EditBox<H> {
                     if let Some(state) = self.old_state.as_mut() {
                         std::mem::swap(state, &mut self.text);
                         self.last_edit = LastEdit::None;
+                    } else {
+                        return EditAction::None;
                     }
                 }
-                '\u{1B}' /* escape */ => (),
+                '\u{1B}' /* escape */ => return EditAction::None,
                 '\u{7f}' /* delete */ => {
+                    if self.text.is_empty() {
+                        return EditAction::None;
+                    }
                     if self.last_edit != LastEdit::Clear {
                         self.old_state = Some(self.text.clone());
                         self.last_edit = LastEdit::Clear;
                     }
                     self.text.clear();
                 }
-                _ => (),
+                _ => return EditAction::None,
             };
         } else {
             if self.last_edit != LastEdit::Insert {
@@ -311,11 +541,12 @@ impl<H> EditBox<H> {
             self.text.push(c);
         }
         mgr.redraw(self.id());
-        false
+        mgr.access_notify(AccessEvent::ValueChanged(self.id()));
+        EditAction::Edit
     }
 }
 
-impl<H> HasText for EditBox<H> {
+impl<A, E> HasText for EditBox<A, E> {
     fn get_text(&self) -> &str {
         &self.text
     }
@@ -323,10 +554,11 @@ impl<H> HasText for EditBox<H> {
     fn set_string(&mut self, mgr: &mut Manager, text: String) {
         self.text = text;
         mgr.redraw(self.id());
+        mgr.access_notify(AccessEvent::ValueChanged(self.id()));
     }
 }
 
-impl<H> Editable for EditBox<H> {
+impl<A, E> Editable for EditBox<A, E> {
     fn is_editable(&self) -> bool {
         self.editable
     }
@@ -336,7 +568,7 @@ impl<H> Editable for EditBox<H> {
     }
 }
 
-impl Handler for EditBox<()> {
+impl Handler for EditBox<(), ()> {
     type Msg = VoidMsg;
 
     #[inline]
@@ -347,7 +579,7 @@ impl Handler for EditBox<()> {
     fn handle_action(&mut self, mgr: &mut Manager, action: Action) -> Response<VoidMsg> {
         match action {
             Action::Activate => {
-                mgr.request_char_focus(self.id());
+                self.request_char_focus(mgr);
                 Response::None
             }
             Action::ReceivedCharacter(c) => {
@@ -359,7 +591,7 @@ impl Handler for EditBox<()> {
     }
 }
 
-impl<M, H: Fn(&str) -> M> Handler for EditBox<H> {
+impl<M, A: Fn(&str) -> M> Handler for EditBox<A, ()> {
     type Msg = M;
 
     #[inline]
@@ -370,17 +602,197 @@ impl<M, H: Fn(&str) -> M> Handler for EditBox<H> {
     fn handle_action(&mut self, mgr: &mut Manager, action: Action) -> Response<M> {
         match action {
             Action::Activate => {
-                mgr.request_char_focus(self.id());
+                self.request_char_focus(mgr);
                 Response::None
             }
-            Action::ReceivedCharacter(c) => {
-                if self.received_char(mgr, c) {
-                    ((self.on_activate)(&self.text)).into()
-                } else {
-                    Response::None
-                }
+            Action::ReceivedCharacter(c) => match self.received_char(mgr, c) {
+                EditAction::Activate => ((self.on_activate)(&self.text)).into(),
+                _ => Response::None,
+            },
+            a => Response::unhandled_action(a),
+        }
+    }
+}
+
+impl<M, E: Fn(&str) -> M> Handler for EditBox<(), E> {
+    type Msg = M;
+
+    #[inline]
+    fn activation_via_press(&self) -> bool {
+        true
+    }
+
+    fn handle_action(&mut self, mgr: &mut Manager, action: Action) -> Response<M> {
+        match action {
+            Action::Activate => {
+                self.request_char_focus(mgr);
+                Response::None
             }
+            Action::ReceivedCharacter(c) => match self.received_char(mgr, c) {
+                EditAction::Edit => ((self.on_edit)(&self.text)).into(),
+                _ => Response::None,
+            },
+            a => Response::unhandled_action(a),
+        }
+    }
+}
+
+impl<M, A: Fn(&str) -> M, E: Fn(&str) -> M> Handler for EditBox<A, E> {
+    type Msg = M;
+
+    #[inline]
+    fn activation_via_press(&self) -> bool {
+        true
+    }
+
+    fn handle_action(&mut self, mgr: &mut Manager, action: Action) -> Response<M> {
+        match action {
+            Action::Activate => {
+                self.request_char_focus(mgr);
+                Response::None
+            }
+            Action::ReceivedCharacter(c) => match self.received_char(mgr, c) {
+                EditAction::None => Response::None,
+                EditAction::Activate => ((self.on_activate)(&self.text)).into(),
+                EditAction::Edit => ((self.on_edit)(&self.text)).into(),
+            },
             a @ _ => Response::unhandled_action(a),
         }
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::event::{Action, InputEvent, VirtualKeyCode};
+    use crate::geom::Size;
+    use crate::layout;
+    use crate::testing::{Harness, RecordingHandle, TextSizeHandle};
+    use crate::widget::{Row, Spacer};
+    use crate::{TkAction, WidgetId};
+
+    #[test]
+    fn set_key_focus() {
+        let mut row = Row::new(vec![
+            Box::new(EditBox::new("a")) as Box<dyn Handler<Msg = VoidMsg>>,
+            Box::new(EditBox::new("b").with_autofocus(true)),
+            Box::new(Spacer::default()),
+        ]);
+        let mut h = Harness::new(&mut row);
+        let ids: Vec<WidgetId> = (0..3).map(|i| row.get(i).unwrap().id()).collect();
+
+        let mut mgr = h.manager();
+        assert_eq!(mgr.key_focus(), Some(ids[1]));
+        assert!(mgr.set_key_focus(&mut row, ids[0]));
+        assert_eq!(mgr.key_focus(), Some(ids[0]));
+        assert_eq!(mgr.unwrap_action(), TkAction::Redraw);
+
+        // Non-focusable and unknown widgets are rejected
+        assert!(!mgr.set_key_focus(&mut row, ids[2]));
+        let unknown = row.id().next();
+        assert!(!mgr.set_key_focus(&mut row, unknown));
+        assert_eq!(mgr.key_focus(), Some(ids[0]));
+
+        // Hidden widgets and children of hidden widgets are rejected
+        row.get_mut(1).unwrap().set_visible(&mut mgr, false);
+        assert!(!mgr.set_key_focus(&mut row, ids[1]));
+        row.get_mut(1).unwrap().set_visible(&mut mgr, true);
+        row.set_visible(&mut mgr, false);
+        assert!(!mgr.set_key_focus(&mut row, ids[1]));
+        assert_eq!(mgr.key_focus(), Some(ids[0]));
+    }
+
+    #[test]
+    fn autofocus_hidden() {
+        let mut row = Row::new(vec![
+            EditBox::new("a").with_autofocus(true),
+            EditBox::new("b").with_autofocus(true),
+        ]);
+        row.get_mut(0).unwrap().core_data_mut().hidden = true;
+        let mut h = Harness::new(&mut row);
+        assert_eq!(h.manager().key_focus(), Some(row.get(1).unwrap().id()));
+
+        // Children of a hidden widget are not focused either
+        row.core_data_mut().hidden = true;
+        let mut h = Harness::new(&mut row);
+        assert_eq!(h.manager().key_focus(), None);
+    }
+
+    #[test]
+    fn caret_blink() {
+        let mut row = Row::new(vec![EditBox::new("text"), EditBox::new("other")]);
+        let mut h = Harness::new(&mut row);
+        let (id, other) = (row.get(0).unwrap().id(), row.get(1).unwrap().id());
+
+        let mut mgr = h.manager();
+        let _ = mgr.send_action_to(&mut row, id, Action::Activate);
+        assert_eq!(mgr.char_focus(), Some(id));
+        assert!(h.state().next_resume().is_some());
+        h.state().draw_complete();
+
+        // The caret is held solid for one interval, then toggles
+        let mut mgr = h.manager();
+        assert!(row.get_mut(0).unwrap().update_timer(&mut mgr).is_some());
+        assert_eq!(mgr.unwrap_action(), TkAction::None);
+        assert!(row.get_mut(0).unwrap().update_timer(&mut mgr).is_some());
+        assert_eq!(mgr.unwrap_action(), TkAction::Redraw);
+
+        // Only the edit box is redrawn
+        assert!(h.state().is_dirty(id));
+        assert!(!h.state().is_dirty(other) && !h.state().is_dirty(row.id()));
+        h.state().draw_complete();
+
+        // Blinking stops when focus is lost
+        let mut mgr = h.manager();
+        let ev = InputEvent::KeyPressed(1, VirtualKeyCode::Escape);
+        assert!(mgr.handle_synthetic(&mut row, ev).is_none());
+        assert_eq!(mgr.char_focus(), None);
+        assert!(row.get_mut(0).unwrap().update_timer(&mut mgr).is_none());
+    }
+
+    #[test]
+    fn row_baseline() {
+        let mut row = Row::new(vec![
+            Box::new(Label::new("name")) as Box<dyn Handler<Msg = VoidMsg>>,
+            Box::new(EditBox::new("value")),
+        ]);
+        let mut h = Harness::new(&mut row);
+        layout::solve(&mut row, &mut TextSizeHandle, Size(200, 60));
+        let mut draw = RecordingHandle::default();
+        row.draw(&mut draw, h.state());
+
+        // Text is drawn from the top of its rect, thus baselines match if
+        // the tops match. The edit box's text is inside its frame.
+        let (label, edit) = (&draw.text[0], &draw.text[1]);
+        assert_eq!((label.1.as_str(), edit.1.as_str()), ("name", "value"));
+        assert_eq!((label.2 .1, edit.2 .1), (Align::Begin, Align::Begin));
+        let edit_box = draw.items.iter().find(|item| item.0 == "edit_box").unwrap();
+        assert_eq!(edit.0.pos.1, edit_box.1.pos.1 + 5);
+        assert_eq!(label.0.pos.1, edit.0.pos.1);
+    }
+
+    #[test]
+    fn on_edit() {
+        let mut edit = EditBox::new("ab").on_edit(|text| text.to_string());
+        let mut h = Harness::new(&mut edit);
+        let id = edit.id();
+        let mut mgr = h.manager();
+        let mut send = |c| match mgr.send_action_to(&mut edit, id, Action::ReceivedCharacter(c)) {
+            Response::Msg(text) => Some(text),
+            _ => None,
+        };
+
+        assert_eq!(send('c'), Some("abc".to_string()));
+        assert_eq!(send('\u{08}'), Some("ab".to_string()));
+        assert_eq!(send('\u{7f}'), Some("".to_string()));
+        assert_eq!(send('\u{09}'), None);
+
+        // Nothing to delete: no edit
+        assert_eq!(send('\u{08}'), None);
+        assert_eq!(send('\u{7f}'), None);
+        assert_eq!(send('\u{16}'), None); // empty clipboard
+
+        // Undo restores the text deleted above
+        assert_eq!(send('\u{1A}'), Some("ab".to_string()));
+    }
+}
@@ -7,17 +7,20 @@
 
 use std::fmt::{self, Debug};
 
+use crate::access::{AccessNode, Role};
 use crate::draw::SizeHandle;
 use crate::event::{Callback, Event, Handler, Manager, Response, VoidMsg};
 use crate::geom::Size;
 use crate::layout::{self};
 use crate::macros::Widget;
-use crate::{CoreData, LayoutData, Widget, WidgetId};
+use crate::{AlignHints, CoreData, LayoutData, TextDirection, Widget, WidgetCore, WidgetId};
+
+/// A close handler: returns false to veto closure
+type OnClose<W> = &'static dyn Fn(&mut W, &mut Manager) -> bool;
 
 /// The main instantiation of the [`Window`] trait.
-#[widget]
-#[layout(single)]
 #[derive(Widget)]
+#[layout(single)]
 pub struct Window<W: Widget + 'static> {
     #[core]
     core: CoreData,
@@ -25,11 +28,13 @@ pub struct Window<W: Widget + 'static> {
     layout_data: <Self as LayoutData>::Data,
     enforce_min: bool,
     enforce_max: bool,
+    direction: TextDirection,
     title: String,
     #[widget]
     w: W,
     fns: Vec<(Callback, &'static dyn Fn(&mut W, &mut Manager))>,
     final_callback: Option<&'static dyn Fn(Box<dyn kas::Window>, &mut Manager)>,
+    on_close: Option<OnClose<W>>,
 }
 
 impl<W: Widget> Debug for Window<W> {
@@ -57,14 +62,22 @@ impl<W: Widget + Clone> Clone for Window<W> {
             layout_data: self.layout_data.clone(),
             enforce_min: self.enforce_min,
             enforce_max: self.enforce_max,
+            direction: self.direction,
             title: self.title.clone(),
             w: self.w.clone(),
             fns: self.fns.clone(),
             final_callback: self.final_callback.clone(),
+            on_close: self.on_close,
         }
     }
 }
 
+impl<W: Widget> Widget for Window<W> {
+    fn access_node(&self) -> AccessNode {
+        AccessNode::new(self.id(), Role::Window).with_name(&self.title)
+    }
+}
+
 impl<W: Widget> Window<W> {
     /// Create
     pub fn new<T: ToString>(title: T, w: W) -> Window<W> {
@@ -73,10 +86,12 @@ impl<W: Widget> Window<W> {
             layout_data: Default::default(),
             enforce_min: true,
             enforce_max: false,
+            direction: TextDirection::Ltr,
             title: title.to_string(),
             w,
             fns: Vec::new(),
             final_callback: None,
+            on_close: None,
         }
     }
 
@@ -88,6 +103,15 @@ impl<W: Widget> Window<W> {
         self.enforce_max = max;
     }
 
+    /// Set the layout direction
+    ///
+    /// This is passed to all widgets within the window (see
+    /// [`TextDirection`]). The default is left-to-right. Changes take effect
+    /// on the next resize.
+    pub fn set_text_direction(&mut self, direction: TextDirection) {
+        self.direction = direction;
+    }
+
     /// Add a closure to be called, with a reference to self, on the given
     /// condition. The closure must be passed by reference.
     pub fn add_callback(&mut self, condition: Callback, f: &'static dyn Fn(&mut W, &mut Manager)) {
@@ -104,6 +128,15 @@ impl<W: Widget> Window<W> {
     pub fn set_final_callback(&mut self, f: &'static dyn Fn(Box<dyn kas::Window>, &mut Manager)) {
         self.final_callback = Some(f);
     }
+
+    /// Set a closure to be called when closure of the window is requested
+    ///
+    /// If the closure returns `false`, the window is not closed (see
+    /// [`kas::Window::on_close`]). Only a single closure is allowed; if
+    /// another exists it is replaced.
+    pub fn set_on_close(&mut self, f: &'static dyn Fn(&mut W, &mut Manager) -> bool) {
+        self.on_close = Some(f);
+    }
 }
 
 impl<W: Widget + Handler<Msg = VoidMsg> + 'static> Handler for Window<W> {
@@ -125,7 +158,9 @@ impl<W: Widget + Handler<Msg = VoidMsg> + 'static> kas::Window for Window<W> {
         size_handle: &mut dyn SizeHandle,
         size: Size,
     ) -> (Option<Size>, Option<Size>) {
-        let (min, max) = layout::solve(self, size_handle, size);
+        let mut align = AlignHints::NONE;
+        align.direction = self.direction;
+        let (min, max) = layout::solve_with(self, size_handle, size, align);
         (
             if self.enforce_min { Some(min) } else { None },
             if self.enforce_max { Some(max) } else { None },
@@ -145,4 +180,8 @@ impl<W: Widget + Handler<Msg = VoidMsg> + 'static> kas::Window for Window<W> {
         let cb = &mut self.fns[index].1;
         cb(&mut self.w, mgr);
     }
+
+    fn on_close(&mut self, mgr: &mut Manager) -> bool {
+        self.on_close.map(|f| f(&mut self.w, mgr)).unwrap_or(true)
+    }
 }